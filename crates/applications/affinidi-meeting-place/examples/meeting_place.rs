@@ -1,5 +1,5 @@
 /*!
- * Demonstrates the four core Meeting Place flows: check, query, register,
+ * Demonstrates the core Meeting Place flows: propose, check, query, register,
  * deregister an offer phrase. Loads its identity from a TDKEnvironments file.
  */
 
@@ -7,6 +7,7 @@ use affinidi_meeting_place::{
     MeetingPlace,
     errors::{MeetingPlaceError, Result},
     offers::{ContactAttributeType, Offer, PlatformType, RegisterOffer},
+    phrases::{DEFAULT_MAX_ATTEMPTS, OfferPhraseGenerator, PhraseLanguage},
     vcard::Vcard,
 };
 use affinidi_tdk_common::{
@@ -43,6 +44,8 @@ struct Cli {
 
 #[derive(Debug, Subcommand)]
 enum Commands {
+    /// Propose a free, human-friendly offer phrase.
+    Propose(ProposeArgs),
     /// Check whether an offer phrase is in use.
     Check(OfferPhraseArgs),
 
@@ -56,6 +59,17 @@ enum Commands {
     Deregister(OfferPhraseArgs),
 }
 
+#[derive(Debug, Parser)]
+struct ProposeArgs {
+    /// Locale for the wordlist (e.g. `en`, `es-MX`, `fr`, `de`).
+    #[arg(long, default_value = "en")]
+    locale: String,
+
+    /// Minimum phrase entropy in bits.
+    #[arg(long, default_value_t = 32)]
+    entropy_bits: u32,
+}
+
 #[derive(Debug, Parser)]
 struct OfferPhraseArgs {
     /// Meeting Place offer phrase.
@@ -168,6 +182,25 @@ async fn main() -> Result<()> {
 
     let mp = MeetingPlace::new(&tdk, args.mp_did).await?;
     match args.command {
+        Commands::Propose(propose) => {
+            let language = PhraseLanguage::from_locale(&propose.locale).ok_or_else(|| {
+                MeetingPlaceError::Configuration(format!(
+                    "No offer-phrase wordlist for locale ({})",
+                    propose.locale
+                ))
+            })?;
+            let generator =
+                OfferPhraseGenerator::new(language).with_entropy_bits(propose.entropy_bits);
+            let phrase = mp
+                .propose_offer_phrase(
+                    &tdk,
+                    &profile,
+                    generator.random_candidates(),
+                    DEFAULT_MAX_ATTEMPTS,
+                )
+                .await?;
+            info!("Proposed offer phrase: {phrase}");
+        }
         Commands::Check(check_offer_phrase) => {
             let result = mp
                 .check_offer_phrase(&tdk, &profile, &check_offer_phrase.phrase)
@@ -7,9 +7,12 @@
 
 use affinidi_did_authentication::errors::DIDAuthError;
 use affinidi_did_resolver_cache_sdk::errors::DIDCacheError;
+use affinidi_error_codes::error_codes;
 use affinidi_tdk_common::errors::TDKError;
 use thiserror::Error;
 
+pub use affinidi_error_codes::{ErrorCode, ErrorCodes};
+
 /// Errors surfaced by [`crate::MeetingPlace`] and friends.
 #[non_exhaustive]
 #[derive(Error, Debug)]
@@ -40,11 +43,27 @@ pub enum MeetingPlaceError {
     #[error("Configuration error: {0}")]
     Configuration(String),
 
+    /// No usable offer phrase could be proposed (every candidate was taken, or
+    /// the profanity filter rejected them all).
+    #[error("Offer phrase error: {0}")]
+    OfferPhrase(String),
+
     /// Catch-all for callers that don't fit the other variants.
     #[error("{0}")]
     Other(String),
 }
 
+error_codes!(MeetingPlaceError, "MEETINGPLACE", {
+    1 => Authentication(..): "The Meeting Place service denied authentication.",
+    2 => API(..): "The Meeting Place service returned an error.",
+    3 => TDK(..): "The TDK reported an error.",
+    4 => Serialization(..): "A request or response could not be serialised.",
+    5 => DIDError(..): "A DID could not be resolved.",
+    6 => Configuration(..): "The configuration is invalid.",
+    7 => OfferPhrase(..): "No usable offer phrase could be proposed.",
+    8 => Other(..): "The Meeting Place client hit an unexpected error.",
+});
+
 pub type Result<T> = std::result::Result<T, MeetingPlaceError>;
 
 impl From<TDKError> for MeetingPlaceError {
@@ -11,7 +11,7 @@
 
 use affinidi_did_authentication::AuthorizationTokens;
 use affinidi_did_common::{Document, service::Endpoint};
-use affinidi_tdk_common::{TDKSharedState, profiles::TDKProfile};
+use affinidi_tdk_common::{TDKSharedState, capabilities::Capability, profiles::TDKProfile};
 use errors::{MeetingPlaceError, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -20,6 +20,7 @@ use tracing::debug;
 
 pub mod errors;
 pub mod offers;
+pub mod phrases;
 pub mod vcard;
 
 /// Affinidi Meeting Place client.
@@ -72,7 +73,7 @@ impl MeetingPlace {
         profile: &TDKProfile,
         phrase: &str,
     ) -> Result<bool> {
-        let tokens = tdk.authenticate_profile(profile, &self.mp_did).await?;
+        let tokens = self.authenticate(tdk, profile).await?;
 
         let response = http_post::<_, CheckOfferPhraseResponse>(
             tdk.client(),
@@ -84,6 +85,24 @@ impl MeetingPlace {
 
         Ok(response.is_in_use)
     }
+
+    /// Authenticate `profile` against the Meeting Place service. The service
+    /// registers a DID on its first authentication, so success also records
+    /// [`Capability::MeetingPlace`] for the profile.
+    pub(crate) async fn authenticate(
+        &self,
+        tdk: &TDKSharedState,
+        profile: &TDKProfile,
+    ) -> Result<AuthorizationTokens> {
+        let tokens = tdk.authenticate_profile(profile, &self.mp_did).await?;
+        tdk.capabilities().record(
+            &profile.did,
+            Capability::MeetingPlace {
+                service: self.mp_did.clone(),
+            },
+        );
+        Ok(tokens)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -148,31 +167,13 @@ where
 /// Find HTTP(S) and WebSocket service endpoints on a DID document's
 /// `service` entry.
 ///
-/// Both forms of `serviceEndpoint` are supported: a single
-/// [`Endpoint::Url`], or a [`Endpoint::Map`] that holds either an `{uri}`
-/// object or an array of them. Non-string `uri` values are skipped.
+/// Any `serviceEndpoint` shape is read (see [`Endpoint::endpoint_uris`]):
+/// a URL, an `{uri}` object, an array of either, or a nested DIDComm
+/// profile. Values that aren't URIs are skipped.
 pub(crate) fn find_mediator_service_endpoints(doc: &Document) -> Vec<String> {
-    let Some(service) = doc.find_service("service") else {
-        return Vec::new();
-    };
-    match &service.service_endpoint {
-        Endpoint::Url(url) => vec![url.to_string()],
-        Endpoint::Map(map) => {
-            if let Some(array) = map.as_array() {
-                array.iter().filter_map(extract_uri).collect()
-            } else {
-                extract_uri(map).into_iter().collect()
-            }
-        }
-        // `Endpoint` is `#[non_exhaustive]`; unknown shapes yield no URIs.
-        _ => Vec::new(),
-    }
-}
-
-/// Pull the `uri` field out of a service-endpoint map, returning `None` if
-/// it is absent or not a JSON string.
-fn extract_uri(value: &serde_json::Value) -> Option<String> {
-    value.get("uri")?.as_str().map(str::to_owned)
+    doc.find_service("service")
+        .map(|service| service.service_endpoint.get_uris())
+        .unwrap_or_default()
 }
 
 /// Find the [serviceEndpoint](https://www.w3.org/TR/did-1.0/#services) with
@@ -192,22 +193,49 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    fn mediator_doc(service_endpoint: serde_json::Value) -> Document {
+        serde_json::from_value(json!({
+            "id": "did:web:mediator.example.com",
+            "service": [{
+                "id": "did:web:mediator.example.com#service",
+                "type": "DIDCommMessaging",
+                "serviceEndpoint": service_endpoint
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn mediator_endpoints_from_map() {
+        let doc = mediator_doc(json!({ "uri": "https://example.com" }));
+        assert_eq!(
+            find_mediator_service_endpoints(&doc),
+            vec!["https://example.com"]
+        );
+    }
+
     #[test]
-    fn extract_uri_handles_string() {
-        let v = json!({ "uri": "https://example.com" });
-        assert_eq!(extract_uri(&v), Some("https://example.com".to_string()));
+    fn mediator_endpoints_from_array_of_profiles() {
+        let doc = mediator_doc(json!([
+            { "uri": "https://example.com", "accept": ["didcomm/v2"] },
+            { "uri": "wss://example.com/ws", "accept": ["didcomm/v2"] }
+        ]));
+        assert_eq!(
+            find_mediator_service_endpoints(&doc),
+            vec!["https://example.com", "wss://example.com/ws"]
+        );
     }
 
     #[test]
-    fn extract_uri_returns_none_for_missing() {
-        let v = json!({ "accept": ["didcomm/v2"] });
-        assert_eq!(extract_uri(&v), None);
+    fn mediator_endpoints_skip_missing_uri() {
+        let doc = mediator_doc(json!({ "accept": ["didcomm/v2"] }));
+        assert!(find_mediator_service_endpoints(&doc).is_empty());
     }
 
     #[test]
-    fn extract_uri_returns_none_for_non_string() {
-        let v = json!({ "uri": 42 });
-        assert_eq!(extract_uri(&v), None);
+    fn mediator_endpoints_skip_non_string_uri() {
+        let doc = mediator_doc(json!({ "uri": 42 }));
+        assert!(find_mediator_service_endpoints(&doc).is_empty());
     }
 
     #[test]
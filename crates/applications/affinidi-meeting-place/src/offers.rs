@@ -370,7 +370,7 @@ impl Offer {
             )
         })?;
 
-        let tokens = tdk.authenticate_profile(profile, &mp.mp_did).await?;
+        let tokens = mp.authenticate(tdk, profile).await?;
 
         let response = http_post::<_, RegisterOfferResponse>(
             tdk.client(),
@@ -417,7 +417,7 @@ impl Offer {
         profile: &TDKProfile,
         offer_phrase: &str,
     ) -> Result<Offer> {
-        let tokens = tdk.authenticate_profile(profile, &mp.mp_did).await?;
+        let tokens = mp.authenticate(tdk, profile).await?;
 
         let response = http_post::<_, QueryOfferResponse>(
             tdk.client(),
@@ -462,7 +462,7 @@ impl Offer {
             )
         })?;
 
-        let tokens = tdk.authenticate_profile(profile, &mp.mp_did).await?;
+        let tokens = mp.authenticate(tdk, profile).await?;
 
         http_post::<_, DeregisterOfferResponse>(
             tdk.client(),
@@ -0,0 +1,522 @@
+/*!
+ * Offer-phrase generation.
+ *
+ * Offers are found by their phrase, so it must be unique on the Meeting Place
+ * service and easy to say and type. [`OfferPhraseGenerator`] draws phrases
+ * from a wordlist in the user's language with a configurable amount of
+ * entropy, skips any that a [`ProfanityFilter`] rejects, and can be driven
+ * from a seed so the same seed always proposes the same phrases.
+ * [`MeetingPlace::propose_offer_phrase`] then pre-checks candidates against
+ * the service and returns the first one that is free.
+ *
+ * ```
+ * use affinidi_meeting_place::phrases::{OfferPhraseGenerator, PhraseLanguage};
+ *
+ * let generator = OfferPhraseGenerator::new(PhraseLanguage::from_locale("fr-CA").unwrap());
+ * let first: Vec<String> = generator.candidates(b"device seed").take(3).collect();
+ * let again: Vec<String> = generator.candidates(b"device seed").take(3).collect();
+ * assert_eq!(first, again);
+ * ```
+ */
+
+use crate::{
+    MeetingPlace,
+    errors::{MeetingPlaceError, Result},
+};
+use affinidi_tdk_common::{TDKSharedState, profiles::TDKProfile};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+mod wordlists;
+
+/// Default minimum entropy of a generated phrase, in bits.
+pub const DEFAULT_ENTROPY_BITS: u32 = 32;
+
+/// Default number of candidates [`MeetingPlace::propose_offer_phrase`] checks
+/// before giving up.
+pub const DEFAULT_MAX_ATTEMPTS: usize = 5;
+
+/// Candidates [`OfferPhraseGenerator::generate`] draws before deciding the
+/// profanity filter rejects everything.
+const MAX_GENERATE_ATTEMPTS: u32 = 1_000;
+
+/// Domain separator for seed expansion, so phrase seeds can't be confused with
+/// any other use of the same bytes.
+const SEED_DOMAIN: &[u8] = b"affinidi-meeting-place/offer-phrase/v1";
+
+/// Languages with a built-in wordlist.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PhraseLanguage {
+    #[default]
+    English,
+    Spanish,
+    French,
+    German,
+}
+
+impl PhraseLanguage {
+    /// Pick the language for a BCP 47 locale tag (`"de"`, `"es-MX"`,
+    /// `"fr_CA"`). Returns `None` when there is no wordlist for it.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let language = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(PhraseLanguage::English),
+            "es" => Some(PhraseLanguage::Spanish),
+            "fr" => Some(PhraseLanguage::French),
+            "de" => Some(PhraseLanguage::German),
+            _ => None,
+        }
+    }
+
+    fn words(self) -> &'static [&'static str] {
+        match self {
+            PhraseLanguage::English => &wordlists::ENGLISH,
+            PhraseLanguage::Spanish => &wordlists::SPANISH,
+            PhraseLanguage::French => &wordlists::FRENCH,
+            PhraseLanguage::German => &wordlists::GERMAN,
+        }
+    }
+}
+
+/// The words a phrase is drawn from.
+#[derive(Clone, Debug)]
+pub struct Wordlist {
+    words: Vec<String>,
+}
+
+impl Wordlist {
+    /// The built-in list for `language`.
+    pub fn builtin(language: PhraseLanguage) -> Self {
+        Wordlist {
+            words: language.words().iter().map(|w| w.to_string()).collect(),
+        }
+    }
+
+    /// A custom list. Words are lowercased; the list must hold at least 16
+    /// unique, non-empty words without whitespace.
+    pub fn custom<I, S>(words: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut list: Vec<String> = Vec::new();
+        for word in words {
+            let word = word.as_ref().trim().to_lowercase();
+            if word.is_empty() || word.contains(char::is_whitespace) {
+                return Err(MeetingPlaceError::Configuration(format!(
+                    "wordlist entry ({word:?}) must be a single non-empty word"
+                )));
+            }
+            if list.contains(&word) {
+                return Err(MeetingPlaceError::Configuration(format!(
+                    "wordlist contains \"{word}\" more than once"
+                )));
+            }
+            list.push(word);
+        }
+        if list.len() < 16 {
+            return Err(MeetingPlaceError::Configuration(format!(
+                "wordlist needs at least 16 words, got {}",
+                list.len()
+            )));
+        }
+        Ok(Wordlist { words: list })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Entropy contributed by each word, in bits.
+    pub fn bits_per_word(&self) -> f64 {
+        (self.words.len() as f64).log2()
+    }
+}
+
+/// Rejects phrases containing offensive terms.
+///
+/// A term matches when it is one of the phrase's words, or when it spans the
+/// join between two adjacent words (two harmless words can run together into
+/// something that isn't). Terms inside a single word are not matched — the
+/// built-in wordlists are curated, and substring matching would reject
+/// innocent words (`grape`, `class`).
+#[derive(Clone, Debug)]
+pub struct ProfanityFilter {
+    terms: Vec<String>,
+}
+
+impl Default for ProfanityFilter {
+    /// The built-in blocklist, covering every built-in language.
+    fn default() -> Self {
+        ProfanityFilter {
+            terms: BLOCKED_TERMS.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+}
+
+impl ProfanityFilter {
+    /// A filter that accepts every phrase.
+    pub fn none() -> Self {
+        ProfanityFilter { terms: Vec::new() }
+    }
+
+    /// Add terms (matched case-insensitively).
+    pub fn with_terms<I, S>(mut self, terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.terms.extend(
+            terms
+                .into_iter()
+                .map(|t| t.as_ref().trim().to_lowercase())
+                .filter(|t| !t.is_empty()),
+        );
+        self
+    }
+
+    /// True when no blocked term appears in `words`.
+    pub fn is_clean<S: AsRef<str>>(&self, words: &[S]) -> bool {
+        let words: Vec<String> = words.iter().map(|w| w.as_ref().to_lowercase()).collect();
+        let joined = words.concat();
+        // Byte offsets in `joined` where one word ends and the next begins.
+        let boundaries: Vec<usize> = words
+            .iter()
+            .scan(0, |end, w| {
+                *end += w.len();
+                Some(*end)
+            })
+            .collect();
+
+        !self.terms.iter().any(|term| {
+            words.iter().any(|w| w == term)
+                || joined.match_indices(term.as_str()).any(|(start, _)| {
+                    let end = start + term.len();
+                    boundaries.iter().any(|&b| start < b && b < end)
+                })
+        })
+    }
+}
+
+/// Proposes offer phrases.
+#[derive(Clone, Debug)]
+pub struct OfferPhraseGenerator {
+    wordlist: Wordlist,
+    entropy_bits: u32,
+    separator: String,
+    filter: ProfanityFilter,
+}
+
+impl Default for OfferPhraseGenerator {
+    fn default() -> Self {
+        OfferPhraseGenerator::new(PhraseLanguage::default())
+    }
+}
+
+impl OfferPhraseGenerator {
+    /// Generator for `language`'s built-in wordlist, with
+    /// [`DEFAULT_ENTROPY_BITS`], space-separated words and the default
+    /// [`ProfanityFilter`].
+    pub fn new(language: PhraseLanguage) -> Self {
+        OfferPhraseGenerator {
+            wordlist: Wordlist::builtin(language),
+            entropy_bits: DEFAULT_ENTROPY_BITS,
+            separator: " ".into(),
+            filter: ProfanityFilter::default(),
+        }
+    }
+
+    /// Draw words from `wordlist` instead.
+    pub fn with_wordlist(mut self, wordlist: Wordlist) -> Self {
+        self.wordlist = wordlist;
+        self
+    }
+
+    /// Use enough words for at least `bits` of entropy (minimum one word).
+    pub fn with_entropy_bits(mut self, bits: u32) -> Self {
+        self.entropy_bits = bits;
+        self
+    }
+
+    /// Join words with `separator`.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    pub fn with_profanity_filter(mut self, filter: ProfanityFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Number of words in each phrase.
+    pub fn word_count(&self) -> usize {
+        ((f64::from(self.entropy_bits) / self.wordlist.bits_per_word()).ceil() as usize).max(1)
+    }
+
+    /// Entropy of each phrase in bits (before profanity filtering, which
+    /// removes a negligible share).
+    pub fn entropy(&self) -> f64 {
+        self.word_count() as f64 * self.wordlist.bits_per_word()
+    }
+
+    /// Deterministic, endless sequence of filtered phrases for `seed`.
+    ///
+    /// The same seed, wordlist, entropy and filter always yield the same
+    /// sequence. The seed must be secret and high-entropy if the phrase
+    /// should be unguessable.
+    pub fn candidates<'a>(&'a self, seed: &'a [u8]) -> impl Iterator<Item = String> + 'a {
+        (0..=u32::MAX).filter_map(move |attempt| self.phrase_for(seed, attempt))
+    }
+
+    /// Sequence of filtered phrases from a fresh random seed.
+    pub fn random_candidates(&self) -> impl Iterator<Item = String> + '_ {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        (0..=u32::MAX).filter_map(move |attempt| self.phrase_for(&seed, attempt))
+    }
+
+    /// A random phrase.
+    ///
+    /// Fails with [`MeetingPlaceError::OfferPhrase`] if the profanity filter
+    /// rejects every one of the first candidates, which only happens when it
+    /// blocks most of the wordlist.
+    pub fn generate(&self) -> Result<String> {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        (0..MAX_GENERATE_ATTEMPTS)
+            .find_map(|attempt| self.phrase_for(&seed, attempt))
+            .ok_or_else(|| {
+                MeetingPlaceError::OfferPhrase(format!(
+                    "profanity filter rejected {MAX_GENERATE_ATTEMPTS} candidates in a row"
+                ))
+            })
+    }
+
+    /// Phrase number `attempt` for `seed`, or `None` if the filter rejects it.
+    fn phrase_for(&self, seed: &[u8], attempt: u32) -> Option<String> {
+        let mut stream = SeedStream::new(seed, attempt);
+        let words: Vec<&str> = (0..self.word_count())
+            .map(|_| self.wordlist.words[stream.index(self.wordlist.len())].as_str())
+            .collect();
+        self.filter
+            .is_clean(&words)
+            .then(|| words.join(&self.separator))
+    }
+}
+
+/// SHA-256 in counter mode over (domain, seed, attempt), read as `u32`s.
+struct SeedStream {
+    key: [u8; 32],
+    block: [u8; 32],
+    counter: u32,
+    position: usize,
+}
+
+impl SeedStream {
+    fn new(seed: &[u8], attempt: u32) -> Self {
+        let key: [u8; 32] = Sha256::new()
+            .chain_update(SEED_DOMAIN)
+            .chain_update((seed.len() as u64).to_be_bytes())
+            .chain_update(seed)
+            .chain_update(attempt.to_be_bytes())
+            .finalize()
+            .into();
+        SeedStream {
+            key,
+            block: [0; 32],
+            counter: 0,
+            position: 32,
+        }
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        if self.position == self.block.len() {
+            self.block = Sha256::new()
+                .chain_update(self.key)
+                .chain_update(self.counter.to_be_bytes())
+                .finalize()
+                .into();
+            self.counter += 1;
+            self.position = 0;
+        }
+        let bytes = &self.block[self.position..self.position + 4];
+        self.position += 4;
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    /// Uniform index in `0..n` (rejection sampling, so no modulo bias).
+    fn index(&mut self, n: usize) -> usize {
+        let n = n as u64;
+        let zone = (u64::from(u32::MAX) + 1) / n * n;
+        loop {
+            let value = u64::from(self.next_u32());
+            if value < zone {
+                return (value % n) as usize;
+            }
+        }
+    }
+}
+
+impl MeetingPlace {
+    /// Return the first of `candidates` that is not in use on the service,
+    /// checking at most `max_attempts` of them with
+    /// [`check_offer_phrase`](MeetingPlace::check_offer_phrase).
+    ///
+    /// Pass [`OfferPhraseGenerator::random_candidates`] for a fresh phrase or
+    /// [`OfferPhraseGenerator::candidates`] for a reproducible one. Fails with
+    /// [`MeetingPlaceError::OfferPhrase`] when every checked phrase is taken.
+    pub async fn propose_offer_phrase(
+        &self,
+        tdk: &TDKSharedState,
+        profile: &TDKProfile,
+        candidates: impl IntoIterator<Item = String>,
+        max_attempts: usize,
+    ) -> Result<String> {
+        for phrase in candidates.into_iter().take(max_attempts) {
+            if !self.check_offer_phrase(tdk, profile, &phrase).await? {
+                return Ok(phrase);
+            }
+            debug!("offer phrase candidate already in use; trying the next");
+        }
+        Err(MeetingPlaceError::OfferPhrase(format!(
+            "no free offer phrase after {max_attempts} attempts"
+        )))
+    }
+}
+
+/// Built-in blocklist. Short terms are only ever matched as whole words or
+/// across word joins (see [`ProfanityFilter`]).
+#[rustfmt::skip]
+const BLOCKED_TERMS: &[&str] = &[
+    // English
+    "anal", "ass", "bitch", "cock", "cum", "cunt", "dick", "fag", "fuck", "nazi", "nigg", "piss",
+    "porn", "rape", "retard", "sex", "shit", "slut", "tit", "twat", "wank", "whore",
+    // Spanish
+    "cabron", "culo", "joder", "mierda", "pene", "polla", "puta",
+    // French
+    "bite", "con", "cul", "merde", "pute", "salope",
+    // German
+    "arsch", "fick", "fotze", "hure", "nutte", "scheisse",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_wordlists_are_sorted_unique_powers_of_two() {
+        for language in [
+            PhraseLanguage::English,
+            PhraseLanguage::Spanish,
+            PhraseLanguage::French,
+            PhraseLanguage::German,
+        ] {
+            let words = language.words();
+            assert!(words.len().is_power_of_two(), "{language:?}");
+            assert!(words.windows(2).all(|w| w[0] < w[1]), "{language:?}");
+            assert!(
+                words
+                    .iter()
+                    .all(|w| w.chars().all(|c| c.is_ascii_lowercase())),
+                "{language:?}"
+            );
+            let filter = ProfanityFilter::default();
+            assert!(words.iter().all(|w| filter.is_clean(&[w])), "{language:?}");
+        }
+    }
+
+    #[test]
+    fn locale_selects_language() {
+        assert_eq!(
+            PhraseLanguage::from_locale("es-MX"),
+            Some(PhraseLanguage::Spanish)
+        );
+        assert_eq!(
+            PhraseLanguage::from_locale("DE_at"),
+            Some(PhraseLanguage::German)
+        );
+        assert_eq!(PhraseLanguage::from_locale("ja"), None);
+    }
+
+    #[test]
+    fn entropy_sets_word_count() {
+        let english = OfferPhraseGenerator::new(PhraseLanguage::English);
+        assert_eq!(english.word_count(), 4);
+        assert_eq!(english.entropy(), 32.0);
+        let french = OfferPhraseGenerator::new(PhraseLanguage::French).with_entropy_bits(40);
+        assert_eq!(french.word_count(), 6);
+    }
+
+    #[test]
+    fn seeded_candidates_are_deterministic() {
+        let generator = OfferPhraseGenerator::default().with_separator("-");
+        let a: Vec<String> = generator.candidates(b"seed").take(5).collect();
+        let b: Vec<String> = generator.candidates(b"seed").take(5).collect();
+        let c: Vec<String> = generator.candidates(b"other seed").take(5).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.iter().all(|p| p.split('-').count() == 4));
+        assert_ne!(a[0], a[1]);
+    }
+
+    #[test]
+    fn filter_matches_words_and_joins_only() {
+        let filter = ProfanityFilter::default();
+        assert!(filter.is_clean(&["grape", "class"]));
+        assert!(!filter.is_clean(&["sun", "sex"]));
+        assert!(!filter.is_clean(&["pi", "ss"]));
+        let custom = ProfanityFilter::none().with_terms(["Melon"]);
+        assert!(!custom.is_clean(&["melon", "pear"]));
+        assert!(ProfanityFilter::none().is_clean(&["sex"]));
+    }
+
+    #[test]
+    fn filtered_phrases_are_skipped() {
+        let words: Vec<String> = (0..16).map(|i| format!("w{i}")).collect();
+        let generator = OfferPhraseGenerator::default()
+            .with_wordlist(Wordlist::custom(&words).unwrap())
+            .with_entropy_bits(8)
+            .with_profanity_filter(ProfanityFilter::none().with_terms(["w0"]));
+        assert!(
+            generator
+                .candidates(b"seed")
+                .take(200)
+                .all(|p| !p.split(' ').any(|w| w == "w0"))
+        );
+    }
+
+    #[test]
+    fn generate_gives_up_when_everything_is_filtered() {
+        let words: Vec<String> = (0..16).map(|i| format!("w{i}")).collect();
+        let generator = OfferPhraseGenerator::default()
+            .with_wordlist(Wordlist::custom(&words).unwrap())
+            .with_profanity_filter(ProfanityFilter::none().with_terms(&words));
+        assert!(matches!(
+            generator.generate(),
+            Err(MeetingPlaceError::OfferPhrase(_))
+        ));
+
+        let phrase = OfferPhraseGenerator::default().generate().unwrap();
+        assert_eq!(phrase.split(' ').count(), 4);
+    }
+
+    #[test]
+    fn custom_wordlists_are_validated() {
+        assert!(Wordlist::custom(["one", "two"]).is_err());
+        let mut words: Vec<String> = (0..16).map(|i| format!("w{i}")).collect();
+        words.push("W1".into());
+        assert!(Wordlist::custom(&words).is_err());
+        assert!(Wordlist::custom(["two words"; 16]).is_err());
+    }
+}
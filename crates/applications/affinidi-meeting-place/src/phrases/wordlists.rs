@@ -0,0 +1,198 @@
+//! Built-in offer-phrase wordlists.
+//!
+//! Concrete, easily spelled nouns — no diacritics, so phrases can be typed on
+//! any keyboard. Each list is sorted, unique and a power of two long, so every
+//! word carries a whole number of bits.
+
+pub(crate) const ENGLISH: [&str; 256] = [
+    "acacia", "acorn", "almond", "amber", "anchor", "antler", "apple", "apricot", "arrow", "aspen",
+    "atlas", "autumn", "badge", "badger", "bamboo", "banjo", "barley", "basil", "beach", "beacon",
+    "bean", "beetle", "berry", "birch", "biscuit", "bison", "blossom", "bluff", "bonsai", "border",
+    "bramble", "breeze", "brick", "bridge", "brook", "bubble", "bucket", "buffalo", "button",
+    "cabin", "cactus", "camel", "candle", "canoe", "canyon", "carrot", "cashew", "castle",
+    "cavern", "cedar", "cello", "chalk", "cherry", "chess", "cider", "cinder", "circle", "citrus",
+    "clover", "cobalt", "cobble", "cocoa", "comet", "compass", "condor", "copper", "coral",
+    "cosmos", "cotton", "cradle", "crane", "crater", "cricket", "crystal", "cypress", "dahlia",
+    "daisy", "dawn", "delta", "denim", "desert", "dolphin", "dome", "dragon", "drum", "dune",
+    "eagle", "echo", "ember", "emerald", "falcon", "feather", "fern", "ferry", "fiddle", "fig",
+    "finch", "fjord", "flint", "flute", "forest", "fossil", "fountain", "fox", "galaxy", "garden",
+    "garnet", "gazelle", "gecko", "ginger", "glacier", "globe", "goose", "granite", "grape",
+    "gravel", "guitar", "hamlet", "harbor", "harp", "hazel", "heron", "hickory", "honey",
+    "horizon", "iris", "island", "ivory", "jade", "jaguar", "jasmine", "jelly", "jungle",
+    "juniper", "kayak", "kettle", "kiwi", "koala", "ladder", "lagoon", "lantern", "lemon", "lilac",
+    "lily", "lime", "linen", "lotus", "lunar", "magnet", "mango", "maple", "marble", "meadow",
+    "melon", "meteor", "mint", "mirror", "mitten", "moss", "mountain", "nectar", "needle", "nest",
+    "noodle", "nutmeg", "oak", "oasis", "ocean", "olive", "onyx", "opal", "orange", "orbit",
+    "orchid", "otter", "owl", "paddle", "panda", "paper", "parrot", "peach", "pearl", "pebble",
+    "pecan", "pepper", "piano", "pigeon", "pine", "planet", "plum", "pocket", "pollen", "pond",
+    "poppy", "prairie", "prism", "pumpkin", "puzzle", "quartz", "quill", "rabbit", "radish",
+    "rain", "raven", "reef", "ribbon", "ridge", "river", "robin", "rocket", "rose", "ruby",
+    "saddle", "saffron", "sage", "salmon", "sapphire", "satin", "sequoia", "shell", "silver",
+    "sky", "sled", "snow", "sparrow", "spruce", "squash", "star", "stone", "summit", "sun", "swan",
+    "tango", "teapot", "thistle", "thunder", "tiger", "timber", "topaz", "tulip", "tundra",
+    "turtle", "valley", "velvet", "violet", "walnut", "walrus", "willow", "window", "winter",
+    "wren", "yacht", "yarn", "zebra", "zephyr", "zinnia",
+];
+
+pub(crate) const SPANISH: [&str; 128] = [
+    "abeja", "abrigo", "aceite", "aguja", "alba", "almendra", "amarillo", "ancla", "anillo",
+    "arena", "arroz", "azul", "ballena", "barco", "barro", "bosque", "bota", "brisa", "caballo",
+    "cacao", "calabaza", "camino", "campana", "canela", "carta", "cascada", "castillo", "cebolla",
+    "cebra", "cereza", "cielo", "ciruela", "cisne", "cometa", "conejo", "copa", "coral", "cuerda",
+    "cuervo", "desierto", "duna", "espiga", "estrella", "faro", "flor", "fresa", "fuego", "gato",
+    "gaviota", "girasol", "globo", "granja", "grano", "guitarra", "hielo", "hierba", "higo",
+    "hoja", "hormiga", "isla", "jirafa", "lago", "lana", "libro", "lince", "lluvia", "lobo",
+    "loro", "luna", "madera", "manta", "manzana", "mapa", "mar", "mariposa", "miel", "molino",
+    "mora", "naranja", "nido", "nieve", "nogal", "nube", "nuez", "nutria", "ola", "oliva", "oro",
+    "oso", "palmera", "paloma", "pan", "panda", "papel", "pepino", "pera", "perla", "pez", "piano",
+    "pino", "planeta", "playa", "pluma", "puente", "queso", "rana", "rayo", "roble", "roca",
+    "rosa", "sal", "salvia", "selva", "sol", "tambor", "taza", "tierra", "tigre", "tomate",
+    "torre", "trigo", "trueno", "uva", "vela", "verde", "viento", "violeta", "zorro",
+];
+
+pub(crate) const FRENCH: [&str; 128] = [
+    "abeille",
+    "abricot",
+    "aigle",
+    "amande",
+    "ancre",
+    "arbre",
+    "argent",
+    "avion",
+    "baleine",
+    "bambou",
+    "bateau",
+    "biche",
+    "bijou",
+    "bougie",
+    "bouleau",
+    "branche",
+    "brise",
+    "cabane",
+    "cactus",
+    "caillou",
+    "canard",
+    "cannelle",
+    "carotte",
+    "castor",
+    "cerf",
+    "cerise",
+    "chameau",
+    "chardon",
+    "chat",
+    "cheval",
+    "citron",
+    "citrouille",
+    "cloche",
+    "colline",
+    "colombe",
+    "coquille",
+    "corail",
+    "coton",
+    "crabe",
+    "cuivre",
+    "cygne",
+    "dauphin",
+    "dragon",
+    "dune",
+    "falaise",
+    "faucon",
+    "feuille",
+    "figue",
+    "flamme",
+    "fleur",
+    "fourmi",
+    "fraise",
+    "framboise",
+    "fromage",
+    "galet",
+    "glace",
+    "gland",
+    "grenade",
+    "grenouille",
+    "guitare",
+    "hibou",
+    "hiver",
+    "horloge",
+    "jardin",
+    "jasmin",
+    "jonquille",
+    "kiwi",
+    "lac",
+    "laine",
+    "lampe",
+    "lapin",
+    "lavande",
+    "licorne",
+    "lierre",
+    "lion",
+    "loup",
+    "loutre",
+    "lune",
+    "mangue",
+    "marbre",
+    "melon",
+    "mer",
+    "miel",
+    "montagne",
+    "mouton",
+    "muguet",
+    "neige",
+    "nuage",
+    "oasis",
+    "olive",
+    "orage",
+    "orange",
+    "ours",
+    "paille",
+    "panda",
+    "papillon",
+    "perle",
+    "phare",
+    "piano",
+    "pigeon",
+    "pin",
+    "plage",
+    "plume",
+    "poire",
+    "pomme",
+    "pont",
+    "prairie",
+    "prune",
+    "radis",
+    "raisin",
+    "renard",
+    "roche",
+    "rose",
+    "ruisseau",
+    "sable",
+    "sapin",
+    "saumon",
+    "soleil",
+    "source",
+    "tambour",
+    "tigre",
+    "tomate",
+    "tortue",
+    "tulipe",
+    "vague",
+    "velours",
+    "vent",
+    "violon",
+];
+
+pub(crate) const GERMAN: [&str; 128] = [
+    "adler", "ahorn", "anker", "apfel", "bambus", "banane", "berg", "biene", "birke", "birne",
+    "blatt", "blitz", "blume", "boot", "burg", "dachs", "dattel", "delfin", "donner", "drache",
+    "eiche", "erdbeere", "eule", "falke", "farn", "feder", "feige", "fels", "fisch", "flamme",
+    "flocke", "fluss", "fuchs", "garten", "gecko", "geige", "glocke", "gold", "granit", "gras",
+    "hafen", "hagel", "harfe", "hase", "hirsch", "honig", "hummel", "igel", "insel", "jade",
+    "kaktus", "kamel", "kastanie", "kerze", "kiefer", "kiesel", "kirsche", "klee", "koala",
+    "komet", "koralle", "krabbe", "kranich", "kristall", "lachs", "lampe", "laterne", "lavendel",
+    "linde", "lotus", "luchs", "mais", "mango", "marmor", "meer", "melone", "minze", "mond",
+    "moos", "nebel", "nest", "nuss", "oase", "olive", "orange", "otter", "palme", "panda",
+    "papier", "perle", "pfeffer", "pflaume", "pinguin", "planet", "quelle", "rabe", "regen", "reh",
+    "rose", "rubin", "sand", "schnee", "schwan", "seide", "sonne", "spatz", "stern", "storch",
+    "sturm", "tanne", "tiger", "tomate", "trommel", "tulpe", "ulme", "vogel", "wal", "wald",
+    "wasser", "weide", "welle", "wiese", "wind", "wolf", "wolke", "zebra", "zimt", "zitrone",
+];
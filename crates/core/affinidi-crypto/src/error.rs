@@ -37,6 +37,10 @@ pub enum CryptoError {
 
     #[error("Verification error: {0}")]
     Verification(String),
+
+    // ─── Threshold signing (`frost` feature) ────────────────────────────────
+    #[error("Threshold signing error: {0}")]
+    Threshold(String),
 }
 
 pub type Result<T> = std::result::Result<T, CryptoError>;
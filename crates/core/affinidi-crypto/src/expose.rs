@@ -0,0 +1,93 @@
+//! Opt-in serialization of private key material.
+//!
+//! Key types serialize their public members only: a [`JWK`](crate::JWK)
+//! leaves out `d`, so logging or persisting one by accident can't leak the
+//! private key. Code that means to write private material (a secrets file, a
+//! keyring entry) asks for it explicitly, with `expose_secret()` or
+//! [`serialize_with_private`]:
+//!
+//! ```
+//! # use affinidi_crypto::ed25519;
+//! let keypair = ed25519::generate(None);
+//!
+//! let public = serde_json::to_value(&keypair.jwk).unwrap();
+//! assert!(public.get("d").is_none());
+//!
+//! let private = serde_json::to_value(keypair.jwk.expose_secret()).unwrap();
+//! assert!(private.get("d").is_some());
+//! ```
+//!
+//! Deserialization is unaffected: private members are always read.
+
+use std::collections::HashMap;
+
+use serde::{Serialize, Serializer};
+
+/// Types whose private members are only serialized on request.
+pub trait SerializePrivate {
+    /// Serializes `self` including its private members.
+    fn serialize_private<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+/// Serializes the wrapped value including its private members. Created by
+/// the `expose_secret()` methods of the key types.
+pub struct SerializeWithPrivate<'a, T: ?Sized>(&'a T);
+
+impl<'a, T: SerializePrivate + ?Sized> SerializeWithPrivate<'a, T> {
+    pub fn new(value: &'a T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: SerializePrivate + ?Sized> Serialize for SerializeWithPrivate<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_private(serializer)
+    }
+}
+
+/// For `#[serde(serialize_with = "affinidi_crypto::serialize_with_private")]`
+/// on fields that must be written with their private members, such as the
+/// secrets of a persisted profile.
+pub fn serialize_with_private<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: SerializePrivate + ?Sized,
+    S: Serializer,
+{
+    value.serialize_private(serializer)
+}
+
+impl<T: SerializePrivate + ?Sized> SerializePrivate for &T {
+    fn serialize_private<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize_private(serializer)
+    }
+}
+
+impl<T: SerializePrivate> SerializePrivate for [T] {
+    fn serialize_private<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(SerializeWithPrivate::new))
+    }
+}
+
+impl<T: SerializePrivate> SerializePrivate for Vec<T> {
+    fn serialize_private<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize_private(serializer)
+    }
+}
+
+impl<T: SerializePrivate> SerializePrivate for Option<T> {
+    fn serialize_private<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Some(value) => serializer.serialize_some(&SerializeWithPrivate::new(value)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<K: Serialize, T: SerializePrivate, H> SerializePrivate for HashMap<K, T, H> {
+    fn serialize_private<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.iter()
+                .map(|(key, value)| (key, SerializeWithPrivate::new(value))),
+        )
+    }
+}
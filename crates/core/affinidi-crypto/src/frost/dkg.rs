@@ -0,0 +1,190 @@
+//! Distributed key generation for FROST.
+//!
+//! Each of the `max_signers` participants runs three parts:
+//!
+//! 1. [`part1`] — pick a random degree-`min_signers - 1` polynomial and
+//!    broadcast a [`Round1Package`]: Feldman commitments to its coefficients
+//!    plus a Schnorr proof of knowledge of the constant term (which stops a
+//!    participant from choosing its contribution after seeing everyone else's).
+//! 2. [`part2`] — check every peer's proof, then send each peer `j` its share
+//!    `f(j)` as a [`Round2Package`]. These are secret: send each one
+//!    encrypted to its recipient only.
+//! 3. [`part3`] — check every received share against its sender's
+//!    commitments and derive the [`KeyPackage`] and the group's
+//!    [`PublicKeyPackage`].
+//!
+//! Every participant ends up with the same `PublicKeyPackage`; comparing it
+//! across participants after part 3 confirms that everyone saw the same
+//! broadcast in part 1.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use frost_ed25519::keys::dkg::{round1, round2};
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::{Identifier, KeyPackage, PublicKeyPackage, participant, threshold_error};
+use crate::error::Result;
+
+/// Part 1 broadcast: commitments to the sender's polynomial and a proof of
+/// knowledge of its constant term.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Round1Package(round1::Package);
+
+/// Part 2 message from one participant to one peer: that peer's share of the
+/// sender's polynomial. Secret — send encrypted to the recipient only.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Round2Package(round2::Package);
+
+impl fmt::Debug for Round2Package {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Round2Package")
+            .field("signing_share", &"<redacted>")
+            .finish()
+    }
+}
+
+/// State kept by a participant between [`part1`] and [`part2`].
+pub struct Round1SecretPackage(round1::SecretPackage);
+
+impl Drop for Round1SecretPackage {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// State kept by a participant between [`part2`] and [`part3`].
+pub struct Round2SecretPackage(round2::SecretPackage);
+
+impl Drop for Round2SecretPackage {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Start key generation for participant `identifier` in a
+/// `min_signers`-of-`max_signers` group.
+pub fn part1(
+    identifier: Identifier,
+    max_signers: u16,
+    min_signers: u16,
+    rng: &mut impl CryptoRngCore,
+) -> Result<(Round1SecretPackage, Round1Package)> {
+    let (secret, package) =
+        frost_ed25519::keys::dkg::part1(identifier.to_frost(), max_signers, min_signers, rng)
+            .map_err(threshold_error)?;
+    Ok((Round1SecretPackage(secret), Round1Package(package)))
+}
+
+/// Check every peer's part 1 package (all of them except our own) and produce
+/// the share to send each peer.
+pub fn part2(
+    secret: Round1SecretPackage,
+    round1_packages: &BTreeMap<Identifier, Round1Package>,
+) -> Result<(Round2SecretPackage, BTreeMap<Identifier, Round2Package>)> {
+    let (secret, outgoing) =
+        frost_ed25519::keys::dkg::part2(secret.0.clone(), &to_frost(round1_packages, |p| &p.0))
+            .map_err(threshold_error)?;
+    let outgoing = outgoing
+        .into_iter()
+        .map(|(peer, package)| Ok((participant(&peer)?, Round2Package(package))))
+        .collect::<Result<_>>()?;
+    Ok((Round2SecretPackage(secret), outgoing))
+}
+
+/// Check every share received in part 2 against its sender's part 1
+/// commitments and derive this participant's key share and the group key.
+///
+/// `round1_packages` are the same peer packages given to [`part2`];
+/// `round2_packages` are the shares the peers sent us, keyed by sender.
+pub fn part3(
+    secret: &Round2SecretPackage,
+    round1_packages: &BTreeMap<Identifier, Round1Package>,
+    round2_packages: &BTreeMap<Identifier, Round2Package>,
+) -> Result<(KeyPackage, PublicKeyPackage)> {
+    let (key_package, public_key_package) = frost_ed25519::keys::dkg::part3(
+        &secret.0,
+        &to_frost(round1_packages, |p| &p.0),
+        &to_frost(round2_packages, |p| &p.0),
+    )
+    .map_err(threshold_error)?;
+    let min_signers = *key_package.min_signers();
+    Ok((
+        KeyPackage::try_from(key_package)?,
+        PublicKeyPackage::new(public_key_package, min_signers)?,
+    ))
+}
+
+fn to_frost<P, T: Clone>(
+    packages: &BTreeMap<Identifier, P>,
+    inner: impl Fn(&P) -> &T,
+) -> BTreeMap<frost_ed25519::Identifier, T> {
+    packages
+        .iter()
+        .map(|(id, package)| (id.to_frost(), inner(package).clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    #[test]
+    fn rejects_invalid_threshold() {
+        let id = Identifier::new(1).unwrap();
+        assert!(part1(id, 3, 1, &mut OsRng).is_err());
+        assert!(part1(id, 2, 3, &mut OsRng).is_err());
+    }
+
+    #[test]
+    fn tampered_share_is_attributed_to_its_sender() {
+        let ids: Vec<_> = (1..=3).map(|i| Identifier::new(i).unwrap()).collect();
+        let mut secrets1 = BTreeMap::new();
+        let mut round1 = BTreeMap::new();
+        for id in &ids {
+            let (s, p) = part1(*id, 3, 2, &mut OsRng).unwrap();
+            secrets1.insert(*id, s);
+            round1.insert(*id, p);
+        }
+        let others = |me: &Identifier| -> BTreeMap<_, _> {
+            round1
+                .iter()
+                .filter(|(id, _)| *id != me)
+                .map(|(id, p)| (*id, p.clone()))
+                .collect()
+        };
+
+        let mut secrets2 = BTreeMap::new();
+        let mut inbox: BTreeMap<Identifier, BTreeMap<Identifier, Round2Package>> = BTreeMap::new();
+        for id in &ids {
+            let (s, out) = part2(secrets1.remove(id).unwrap(), &others(id)).unwrap();
+            secrets2.insert(*id, s);
+            for (to, p) in out {
+                inbox.entry(to).or_default().insert(*id, p);
+            }
+        }
+
+        // Participant 2 sends participant 1 the share it meant for participant 3.
+        let misdirected = inbox[&ids[2]][&ids[1]].clone();
+        inbox.get_mut(&ids[0]).unwrap().insert(ids[1], misdirected);
+        let err = part3(&secrets2[&ids[0]], &others(&ids[0]), &inbox[&ids[0]]).unwrap_err();
+        assert!(err.to_string().contains("participant 2"), "{err}");
+    }
+
+    #[test]
+    fn forged_proof_of_knowledge_is_rejected() {
+        let ids: Vec<_> = (1..=2).map(|i| Identifier::new(i).unwrap()).collect();
+        let (s1, _) = part1(ids[0], 2, 2, &mut OsRng).unwrap();
+        // A package proven for participant 1, replayed as participant 2's.
+        let (_, replayed) = part1(ids[0], 2, 2, &mut OsRng).unwrap();
+        let err = part2(s1, &BTreeMap::from([(ids[1], replayed)]))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("proof of knowledge"), "{err}");
+    }
+}
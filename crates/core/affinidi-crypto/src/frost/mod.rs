@@ -0,0 +1,795 @@
+//! FROST threshold Ed25519 signatures (RFC 9591, `FROST(Ed25519, SHA-512)`).
+//!
+//! A `t`-of-`n` group shares one Ed25519 key: no participant ever holds the
+//! whole private key, any `t` of them can produce a signature together, and
+//! the result is an ordinary 64-byte Ed25519 signature that verifies under
+//! the group's public key with any Ed25519 verifier. That makes the group key
+//! a drop-in `did:webvh` update key or Data Integrity verification method.
+//!
+//! The protocol is the Zcash Foundation's audited [`frost-ed25519`] crate;
+//! this module adapts it to the TDK: participants are numbered with a plain
+//! [`Identifier`], failures are [`CryptoError::Threshold`] naming the
+//! participant at fault, and the group key comes out as a Multikey.
+//!
+//! - **Key generation** ([`dkg`]) — a three-part distributed key generation
+//!   (Pedersen DKG with Feldman commitments and a proof of knowledge of each
+//!   participant's secret). Every share received is checked against its
+//!   sender's public commitment, so a participant handing out a bad share is
+//!   named rather than silently corrupting the key.
+//! - **Signing** — two rounds: each signer publishes [`SigningCommitments`]
+//!   from [`commit`]; the coordinator collects them with the message into a
+//!   [`SigningPackage`]; each signer returns a [`SignatureShare`] from
+//!   [`sign`]; the coordinator combines them with [`aggregate`], which checks
+//!   every share and names any signer whose share is invalid.
+//!
+//! The protocol is transport-agnostic. The packages serialize with serde in
+//! `frost-ed25519`'s encoding; [`dkg::Round2Package`] and the [`KeyPackage`]
+//! carry secret shares and must only travel encrypted to their recipient
+//! (e.g. DIDComm authcrypt). [`SigningNonces`] are single-use and
+//! deliberately neither `Clone` nor serializable.
+//!
+//! Signing is pinned to the RFC 9591 Appendix E.1 test vector for
+//! `FROST(Ed25519, SHA-512)` by a known-answer test in this module.
+//!
+//! [`frost-ed25519`]: https://docs.rs/frost-ed25519
+//!
+//! ```
+//! use affinidi_crypto::frost::{self, Identifier, dkg};
+//! use std::collections::BTreeMap;
+//!
+//! let mut rng = rand_core::OsRng;
+//! let ids: Vec<Identifier> = (1..=3).map(|i| Identifier::new(i).unwrap()).collect();
+//!
+//! // Part 1: everyone broadcasts a commitment.
+//! let mut round1 = BTreeMap::new();
+//! let mut secrets1 = BTreeMap::new();
+//! for id in &ids {
+//!     let (secret, package) = dkg::part1(*id, 3, 2, &mut rng).unwrap();
+//!     secrets1.insert(*id, secret);
+//!     round1.insert(*id, package);
+//! }
+//! let others = |me: &Identifier| -> BTreeMap<_, _> {
+//!     round1.iter().filter(|(id, _)| *id != me).map(|(id, p)| (*id, p.clone())).collect()
+//! };
+//!
+//! // Part 2: everyone sends each peer its share.
+//! let mut secrets2 = BTreeMap::new();
+//! let mut inbox: BTreeMap<Identifier, BTreeMap<Identifier, dkg::Round2Package>> = BTreeMap::new();
+//! for id in &ids {
+//!     let (secret, outgoing) = dkg::part2(secrets1.remove(id).unwrap(), &others(id)).unwrap();
+//!     secrets2.insert(*id, secret);
+//!     for (to, package) in outgoing {
+//!         inbox.entry(to).or_default().insert(*id, package);
+//!     }
+//! }
+//!
+//! // Part 3: everyone checks what they received and derives their key share.
+//! let mut keys = BTreeMap::new();
+//! let mut public = None;
+//! for id in &ids {
+//!     let (key, pubkeys) = dkg::part3(&secrets2[id], &others(id), &inbox[id]).unwrap();
+//!     keys.insert(*id, key);
+//!     public = Some(pubkeys);
+//! }
+//! let public = public.unwrap();
+//!
+//! // Any two participants sign.
+//! let message = b"log entry";
+//! let (n1, c1) = frost::commit(&keys[&ids[0]], &mut rng);
+//! let (n3, c3) = frost::commit(&keys[&ids[2]], &mut rng);
+//! let package = frost::SigningPackage::new(
+//!     BTreeMap::from([(ids[0], c1), (ids[2], c3)]),
+//!     message.to_vec(),
+//! );
+//! let shares = BTreeMap::from([
+//!     (ids[0], frost::sign(&package, n1, &keys[&ids[0]]).unwrap()),
+//!     (ids[2], frost::sign(&package, n3, &keys[&ids[2]]).unwrap()),
+//! ]);
+//! let signature = frost::aggregate(&package, &shares, &public).unwrap();
+//! assert!(frost::verify(&public.verifying_key_bytes(), message, &signature).is_ok());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use affinidi_encoding::{ED25519_PUB, MultiEncodedBuf};
+use base58::ToBase58;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use frost_ed25519 as frost;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize, Serializer};
+use zeroize::Zeroize;
+
+use crate::{CryptoError, error::Result};
+
+pub mod dkg;
+
+/// A participant's non-zero identifier. Participants are usually numbered
+/// `1..=n` in an order every party agrees on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct Identifier(u16);
+
+impl Identifier {
+    /// Identifier `value`; zero is rejected (it is the secret's evaluation point).
+    pub fn new(value: u16) -> Result<Self> {
+        if value == 0 {
+            Err(CryptoError::Threshold("identifier must be non-zero".into()))
+        } else {
+            Ok(Identifier(value))
+        }
+    }
+
+    /// The numeric value.
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+
+    fn to_frost(self) -> frost::Identifier {
+        frost::Identifier::try_from(self.0).expect("identifiers are non-zero")
+    }
+
+    /// The identifier `frost-ed25519` derived from a `u16`, if it is one.
+    fn from_frost(id: &frost::Identifier) -> Option<Self> {
+        let bytes = id.serialize();
+        if bytes.len() != 32 || bytes[2..].iter().any(|b| *b != 0) {
+            return None;
+        }
+        Identifier::new(u16::from_le_bytes([bytes[0], bytes[1]])).ok()
+    }
+}
+
+impl TryFrom<u16> for Identifier {
+    type Error = CryptoError;
+
+    fn try_from(value: u16) -> Result<Self> {
+        Identifier::new(value)
+    }
+}
+
+impl From<Identifier> for u16 {
+    fn from(id: Identifier) -> u16 {
+        id.0
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A participant's long-lived share of the group key, produced by
+/// [`dkg::part3`]. Secret: persist it encrypted and never send it anywhere.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "frost::keys::KeyPackage")]
+pub struct KeyPackage {
+    identifier: Identifier,
+    verifying_key: [u8; 32],
+    package: frost::keys::KeyPackage,
+}
+
+impl KeyPackage {
+    /// This participant's identifier.
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+
+    /// Number of signers needed to produce a signature.
+    pub fn min_signers(&self) -> u16 {
+        *self.package.min_signers()
+    }
+
+    /// The group's Ed25519 public key.
+    pub fn verifying_key_bytes(&self) -> [u8; 32] {
+        self.verifying_key
+    }
+}
+
+impl TryFrom<frost::keys::KeyPackage> for KeyPackage {
+    type Error = CryptoError;
+
+    fn try_from(package: frost::keys::KeyPackage) -> Result<Self> {
+        Ok(KeyPackage {
+            identifier: participant(package.identifier())?,
+            verifying_key: group_key(package.verifying_key())?,
+            package,
+        })
+    }
+}
+
+impl Serialize for KeyPackage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        Serialize::serialize(&self.package, serializer)
+    }
+}
+
+impl fmt::Debug for KeyPackage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPackage")
+            .field("identifier", &self.identifier)
+            .field("signing_share", &"<redacted>")
+            .field("min_signers", &self.min_signers())
+            .finish()
+    }
+}
+
+impl Drop for KeyPackage {
+    fn drop(&mut self) {
+        self.package.zeroize();
+    }
+}
+
+/// The group's public key material: the group verifying key plus every
+/// participant's verifying share (used to check signature shares).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", try_from = "PublicKeyPackageWire")]
+pub struct PublicKeyPackage {
+    package: frost::keys::PublicKeyPackage,
+    min_signers: u16,
+    #[serde(skip)]
+    identifiers: Vec<Identifier>,
+    #[serde(skip)]
+    verifying_key: [u8; 32],
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeyPackageWire {
+    package: frost::keys::PublicKeyPackage,
+    min_signers: u16,
+}
+
+impl TryFrom<PublicKeyPackageWire> for PublicKeyPackage {
+    type Error = CryptoError;
+
+    fn try_from(wire: PublicKeyPackageWire) -> Result<Self> {
+        PublicKeyPackage::new(wire.package, wire.min_signers)
+    }
+}
+
+impl PublicKeyPackage {
+    fn new(package: frost::keys::PublicKeyPackage, min_signers: u16) -> Result<Self> {
+        Ok(PublicKeyPackage {
+            identifiers: package
+                .verifying_shares()
+                .keys()
+                .map(participant)
+                .collect::<Result<_>>()?,
+            verifying_key: group_key(package.verifying_key())?,
+            package,
+            min_signers,
+        })
+    }
+
+    /// The group's Ed25519 public key.
+    pub fn verifying_key_bytes(&self) -> [u8; 32] {
+        self.verifying_key
+    }
+
+    /// The group public key as an Ed25519 Multikey (`z6Mk…`), the form
+    /// `did:webvh` expects in `updateKeys`.
+    pub fn verifying_key_multibase(&self) -> String {
+        let multikey = MultiEncodedBuf::encode_bytes(ED25519_PUB, &self.verifying_key)
+            .into_bytes()
+            .to_base58();
+        format!("z{multikey}")
+    }
+
+    /// Participants in the group.
+    pub fn identifiers(&self) -> impl Iterator<Item = Identifier> + '_ {
+        self.identifiers.iter().copied()
+    }
+
+    /// Number of signers needed to produce a signature.
+    pub fn min_signers(&self) -> u16 {
+        self.min_signers
+    }
+}
+
+/// A signer's secret nonces for one signing session. Single use: [`sign`]
+/// consumes them.
+pub struct SigningNonces {
+    nonces: frost::round1::SigningNonces,
+    commitments: SigningCommitments,
+}
+
+impl SigningNonces {
+    /// The public commitments to these nonces.
+    pub fn commitments(&self) -> &SigningCommitments {
+        &self.commitments
+    }
+}
+
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.nonces.zeroize();
+    }
+}
+
+/// A signer's public commitments to its [`SigningNonces`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SigningCommitments(frost::round1::SigningCommitments);
+
+/// Everything a signer needs to produce its share: the participating signers'
+/// commitments and the message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPackage {
+    commitments: BTreeMap<Identifier, SigningCommitments>,
+    #[serde(with = "b64")]
+    message: Vec<u8>,
+}
+
+impl SigningPackage {
+    /// Bundle the commitments collected from the signers with the message.
+    pub fn new(commitments: BTreeMap<Identifier, SigningCommitments>, message: Vec<u8>) -> Self {
+        SigningPackage {
+            commitments,
+            message,
+        }
+    }
+
+    /// The message being signed.
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// The signers taking part.
+    pub fn signers(&self) -> impl Iterator<Item = Identifier> + '_ {
+        self.commitments.keys().copied()
+    }
+
+    fn to_frost(&self) -> frost::SigningPackage {
+        let commitments = self
+            .commitments
+            .iter()
+            .map(|(id, c)| (id.to_frost(), c.0))
+            .collect();
+        frost::SigningPackage::new(commitments, &self.message)
+    }
+}
+
+/// One signer's contribution to the group signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SignatureShare(frost::round2::SignatureShare);
+
+/// Round one of signing: draw fresh nonces and the commitments to publish.
+pub fn commit(
+    key_package: &KeyPackage,
+    rng: &mut impl CryptoRngCore,
+) -> (SigningNonces, SigningCommitments) {
+    let (nonces, commitments) = frost::round1::commit(key_package.package.signing_share(), rng);
+    let commitments = SigningCommitments(commitments);
+    (
+        SigningNonces {
+            nonces,
+            commitments,
+        },
+        commitments,
+    )
+}
+
+/// Round two of signing: this signer's share over `signing_package`.
+///
+/// Fails if the package does not carry this signer's own commitments (so the
+/// nonces cannot be misapplied) or names fewer than `min_signers` signers.
+pub fn sign(
+    signing_package: &SigningPackage,
+    nonces: SigningNonces,
+    key_package: &KeyPackage,
+) -> Result<SignatureShare> {
+    frost::round2::sign(
+        &signing_package.to_frost(),
+        &nonces.nonces,
+        &key_package.package,
+    )
+    .map(SignatureShare)
+    .map_err(threshold_error)
+}
+
+/// Combine signature shares into an Ed25519 signature over the package's
+/// message, verifying it under the group key.
+///
+/// If the combined signature does not verify, every share is checked against
+/// its signer's verifying share and the first invalid one is reported by
+/// identifier.
+pub fn aggregate(
+    signing_package: &SigningPackage,
+    shares: &BTreeMap<Identifier, SignatureShare>,
+    public_key_package: &PublicKeyPackage,
+) -> Result<[u8; 64]> {
+    if signing_package.commitments.len() < usize::from(public_key_package.min_signers) {
+        return Err(CryptoError::Threshold(format!(
+            "signing package has {} signers, {} required",
+            signing_package.commitments.len(),
+            public_key_package.min_signers
+        )));
+    }
+    if shares.keys().ne(signing_package.commitments.keys()) {
+        return Err(CryptoError::Threshold(
+            "signature shares do not match the signing package's signers".into(),
+        ));
+    }
+
+    let shares = shares
+        .iter()
+        .map(|(id, share)| (id.to_frost(), share.0))
+        .collect();
+    frost::aggregate(
+        &signing_package.to_frost(),
+        &shares,
+        &public_key_package.package,
+    )
+    .map_err(threshold_error)?
+    .serialize()
+    .map_err(threshold_error)?
+    .try_into()
+    .map_err(|_| CryptoError::Threshold("aggregated signature is not 64 bytes".into()))
+}
+
+/// Verify an Ed25519 `signature` over `message` under `verifying_key` — a
+/// group signature from [`aggregate`] verifies exactly like a single-key one.
+pub fn verify(verifying_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> Result<()> {
+    VerifyingKey::from_bytes(verifying_key)
+        .map_err(|e| CryptoError::KeyError(format!("Invalid Ed25519 public key: {e}")))?
+        .verify(message, &Signature::from_bytes(signature))
+        .map_err(|e| CryptoError::Verification(e.to_string()))
+}
+
+/// Map a `frost-ed25519` error, naming the participant it blames.
+fn threshold_error(error: frost::Error) -> CryptoError {
+    match error.culprit().as_ref().and_then(Identifier::from_frost) {
+        Some(id) => CryptoError::Threshold(format!("participant {id}: {error}")),
+        None => CryptoError::Threshold(error.to_string()),
+    }
+}
+
+fn participant(id: &frost::Identifier) -> Result<Identifier> {
+    Identifier::from_frost(id)
+        .ok_or_else(|| CryptoError::Threshold("participant identifier is not a u16".into()))
+}
+
+fn group_key(key: &frost::VerifyingKey) -> Result<[u8; 32]> {
+    key.serialize()
+        .map_err(threshold_error)?
+        .try_into()
+        .map_err(|_| CryptoError::Threshold("group key is not 32 bytes".into()))
+}
+
+/// Serde adapter: a byte string as base64url (no padding).
+mod b64 {
+    use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&BASE64_URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(d)?;
+        BASE64_URL_SAFE_NO_PAD.decode(s).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::OsRng;
+
+    /// Run a full DKG for `max` participants with threshold `min`.
+    pub(super) fn keygen(
+        max: u16,
+        min: u16,
+    ) -> (BTreeMap<Identifier, KeyPackage>, PublicKeyPackage) {
+        let mut rng = OsRng;
+        let ids: Vec<_> = (1..=max).map(|i| Identifier::new(i).unwrap()).collect();
+
+        let mut secrets1 = BTreeMap::new();
+        let mut round1 = BTreeMap::new();
+        for id in &ids {
+            let (secret, package) = dkg::part1(*id, max, min, &mut rng).unwrap();
+            secrets1.insert(*id, secret);
+            round1.insert(*id, package);
+        }
+        let others = |me: &Identifier| -> BTreeMap<_, _> {
+            round1
+                .iter()
+                .filter(|(id, _)| *id != me)
+                .map(|(id, p)| (*id, p.clone()))
+                .collect()
+        };
+
+        let mut secrets2 = BTreeMap::new();
+        let mut inbox: BTreeMap<Identifier, BTreeMap<Identifier, dkg::Round2Package>> =
+            BTreeMap::new();
+        for id in &ids {
+            let (secret, outgoing) = dkg::part2(secrets1.remove(id).unwrap(), &others(id)).unwrap();
+            secrets2.insert(*id, secret);
+            for (to, package) in outgoing {
+                inbox.entry(to).or_default().insert(*id, package);
+            }
+        }
+
+        let mut keys = BTreeMap::new();
+        let mut public = None;
+        for id in &ids {
+            let (key, pubkeys) = dkg::part3(&secrets2[id], &others(id), &inbox[id]).unwrap();
+            if let Some(previous) = &public {
+                assert_eq!(previous, &pubkeys, "participants disagree on the group key");
+            }
+            keys.insert(*id, key);
+            public = Some(pubkeys);
+        }
+        (keys, public.unwrap())
+    }
+
+    fn sign_with(
+        keys: &BTreeMap<Identifier, KeyPackage>,
+        signers: &[u16],
+        message: &[u8],
+    ) -> (SigningPackage, BTreeMap<Identifier, SignatureShare>) {
+        let mut rng = OsRng;
+        let mut nonces = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        for s in signers {
+            let id = Identifier::new(*s).unwrap();
+            let (n, c) = commit(&keys[&id], &mut rng);
+            nonces.insert(id, n);
+            commitments.insert(id, c);
+        }
+        let package = SigningPackage::new(commitments, message.to_vec());
+        let shares = nonces
+            .into_iter()
+            .map(|(id, n)| (id, sign(&package, n, &keys[&id]).unwrap()))
+            .collect();
+        (package, shares)
+    }
+
+    #[test]
+    fn any_threshold_subset_produces_a_valid_ed25519_signature() {
+        let (keys, public) = keygen(3, 2);
+        for signers in [[1, 2], [1, 3], [2, 3]] {
+            let (package, shares) = sign_with(&keys, &signers, b"hello");
+            let signature = aggregate(&package, &shares, &public).unwrap();
+            verify(&public.verifying_key_bytes(), b"hello", &signature).unwrap();
+        }
+    }
+
+    #[test]
+    fn all_participants_can_sign() {
+        let (keys, public) = keygen(5, 3);
+        let (package, shares) = sign_with(&keys, &[1, 2, 3, 4, 5], b"entry");
+        let signature = aggregate(&package, &shares, &public).unwrap();
+        verify(&public.verifying_key_bytes(), b"entry", &signature).unwrap();
+    }
+
+    #[test]
+    fn below_threshold_is_rejected() {
+        let (keys, _) = keygen(3, 2);
+        let id = Identifier::new(1).unwrap();
+        let (nonces, c) = commit(&keys[&id], &mut OsRng);
+        let package = SigningPackage::new(BTreeMap::from([(id, c)]), b"m".to_vec());
+        assert!(sign(&package, nonces, &keys[&id]).is_err());
+    }
+
+    #[test]
+    fn bad_signature_share_names_the_signer() {
+        let (keys, public) = keygen(3, 2);
+        let (package, mut shares) = sign_with(&keys, &[1, 3], b"m");
+        // Participant 3 hands in participant 1's share as its own.
+        let cheater = Identifier::new(3).unwrap();
+        let copied = shares[&Identifier::new(1).unwrap()];
+        shares.insert(cheater, copied);
+        let err = aggregate(&package, &shares, &public).unwrap_err();
+        assert!(err.to_string().contains("participant 3"), "{err}");
+    }
+
+    #[test]
+    fn public_key_package_round_trips_through_serde() {
+        let (keys, public) = keygen(3, 2);
+        let json = serde_json::to_string(&public).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PublicKeyPackage>(&json).unwrap(),
+            public
+        );
+
+        let key = &keys[&Identifier::new(2).unwrap()];
+        let restored: KeyPackage =
+            serde_json::from_str(&serde_json::to_string(key).unwrap()).unwrap();
+        assert_eq!(restored.identifier(), key.identifier());
+        assert_eq!(restored.verifying_key_bytes(), public.verifying_key_bytes());
+        assert!(public.verifying_key_multibase().starts_with("z6Mk"));
+        assert_eq!(
+            public
+                .identifiers()
+                .map(|id| id.value())
+                .collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn zero_identifier_is_rejected() {
+        assert!(Identifier::new(0).is_err());
+        assert!(serde_json::from_str::<Identifier>("0").is_err());
+    }
+
+    /// Known-answer test against RFC 9591, Appendix E.1 — `FROST(Ed25519,
+    /// SHA-512)`, the only ciphersuite this module exposes. The vector uses a
+    /// trusted-dealer split (RFC 9591, Appendix C) of a fixed group key, so it
+    /// pins nonce derivation, commitments, signature shares and aggregation
+    /// through this adapter; the DKG itself has no published vectors and is
+    /// covered by the round-trip tests above.
+    mod rfc9591 {
+        use super::*;
+
+        const GROUP_SECRET_KEY: &str =
+            "7b1c33d3f5291d85de664833beb1ad469f7fb6025a0ec78b3a790c6e13a98304";
+        const GROUP_PUBLIC_KEY: &str =
+            "15d21ccd7ee42959562fc8aa63224c8851fb3ec85a3faf66040d380fb9738673";
+        const MESSAGE: &[u8] = b"test";
+        const SHARES: [(u16, &str); 3] = [
+            (
+                1,
+                "929dcc590407aae7d388761cddb0c0db6f5627aea8e217f4a033f2ec83d93509",
+            ),
+            (
+                2,
+                "a91e66e012e4364ac9aaa405fcafd370402d9859f7b6685c07eed76bf409e80d",
+            ),
+            (
+                3,
+                "d3cb090a075eb154e82fdb4b3cb507f110040905468bb9c46da8bdea643a9a02",
+            ),
+        ];
+
+        /// Round one inputs and outputs for one of the signers (1 and 3).
+        struct Signer {
+            id: u16,
+            hiding_randomness: &'static str,
+            binding_randomness: &'static str,
+            hiding_nonce: &'static str,
+            binding_nonce: &'static str,
+            hiding_commitment: &'static str,
+            binding_commitment: &'static str,
+            sig_share: &'static str,
+        }
+
+        const SIGNERS: [Signer; 2] = [
+            Signer {
+                id: 1,
+                hiding_randomness: "0fd2e39e111cdc266f6c0f4d0fd45c947761f1f5d3cb583dfcb9bbaf8d4c9fec",
+                binding_randomness: "69cd85f631d5f7f2721ed5e40519b1366f340a87c2f6856363dbdcda348a7501",
+                hiding_nonce: "812d6104142944d5a55924de6d49940956206909f2acaeedecda2b726e630407",
+                binding_nonce: "b1110165fc2334149750b28dd813a39244f315cff14d4e89e6142f262ed83301",
+                hiding_commitment: "b5aa8ab305882a6fc69cbee9327e5a45e54c08af61ae77cb8207be3d2ce13de3",
+                binding_commitment: "67e98ab55aa310c3120418e5050c9cf76cf387cb20ac9e4b6fdb6f82a469f932",
+                sig_share: "001719ab5a53ee1a12095cd088fd149702c0720ce5fd2f29dbecf24b7281b603",
+            },
+            Signer {
+                id: 3,
+                hiding_randomness: "86d64a260059e495d0fb4fcc17ea3da7452391baa494d4b00321098ed2a0062f",
+                binding_randomness: "13e6b25afb2eba51716a9a7d44130c0dbae0004a9ef8d7b5550c8a0e07c61775",
+                hiding_nonce: "c256de65476204095ebdc01bd11dc10e57b36bc96284595b8215222374f99c0e",
+                binding_nonce: "243d71944d929063bc51205714ae3c2218bd3451d0214dfb5aeec2a90c35180d",
+                hiding_commitment: "cfbdb165bd8aad6eb79deb8d287bcc0ab6658ae57fdcc98ed12c0669e90aec91",
+                binding_commitment: "7487bc41a6e712eea2f2af24681b58b1cf1da278ea11fe4e8b78398965f13552",
+                sig_share: "bd86125de990acc5e1f13781d8e32c03a9bbd4c53539bbc106058bfd14326007",
+            },
+        ];
+
+        const SIGNATURE: &str = "36282629c383bb820a88b71cae937d41f2f2adfcc3d02e55507e2fb9e2dd3cbebd9d2b0844e49ae0f3fa935161e1419aab7b47d21a37ebeae1f17d4987b3160b";
+
+        fn unhex(s: &str) -> Vec<u8> {
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect()
+        }
+
+        fn hex(bytes: &[u8]) -> String {
+            bytes.iter().map(|b| format!("{b:02x}")).collect()
+        }
+
+        /// Replays the vector's randomness in the order [`commit`] draws it:
+        /// hiding first, then binding.
+        struct Replay(Vec<u8>);
+
+        impl rand_core::RngCore for Replay {
+            fn next_u32(&mut self) -> u32 {
+                unreachable!("commit only draws nonce randomness")
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                unreachable!("commit only draws nonce randomness")
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                let n = dest.len();
+                dest.copy_from_slice(&self.0[..n]);
+                self.0.drain(..n);
+            }
+
+            fn try_fill_bytes(
+                &mut self,
+                dest: &mut [u8],
+            ) -> std::result::Result<(), rand_core::Error> {
+                self.fill_bytes(dest);
+                Ok(())
+            }
+        }
+
+        impl rand_core::CryptoRng for Replay {}
+
+        #[test]
+        fn ed25519_sha512_vector() {
+            let secret = frost::SigningKey::deserialize(&unhex(GROUP_SECRET_KEY)).unwrap();
+            let verifying_key = frost::VerifyingKey::from(&secret);
+            assert_eq!(hex(&verifying_key.serialize().unwrap()), GROUP_PUBLIC_KEY);
+
+            let mut key_packages = BTreeMap::new();
+            let mut verifying_shares = BTreeMap::new();
+            for (id, share) in SHARES {
+                let identifier = Identifier::new(id).unwrap();
+                let signing_share = frost::keys::SigningShare::deserialize(&unhex(share)).unwrap();
+                let verifying_share = frost::keys::VerifyingShare::from(signing_share);
+                verifying_shares.insert(identifier.to_frost(), verifying_share);
+                let package = frost::keys::KeyPackage::new(
+                    identifier.to_frost(),
+                    signing_share,
+                    verifying_share,
+                    verifying_key,
+                    2,
+                );
+                key_packages.insert(identifier, KeyPackage::try_from(package).unwrap());
+            }
+            let public = PublicKeyPackage::new(
+                frost::keys::PublicKeyPackage::new(verifying_shares, verifying_key),
+                2,
+            )
+            .unwrap();
+
+            let mut nonces = BTreeMap::new();
+            let mut commitments = BTreeMap::new();
+            for signer in &SIGNERS {
+                let id = Identifier::new(signer.id).unwrap();
+                let mut rng = Replay(
+                    [
+                        unhex(signer.hiding_randomness),
+                        unhex(signer.binding_randomness),
+                    ]
+                    .concat(),
+                );
+                let (n, c) = commit(&key_packages[&id], &mut rng);
+                assert_eq!(hex(&n.nonces.hiding().serialize()), signer.hiding_nonce);
+                assert_eq!(hex(&n.nonces.binding().serialize()), signer.binding_nonce);
+                assert_eq!(
+                    hex(&c.0.hiding().serialize().unwrap()),
+                    signer.hiding_commitment
+                );
+                assert_eq!(
+                    hex(&c.0.binding().serialize().unwrap()),
+                    signer.binding_commitment
+                );
+                nonces.insert(id, n);
+                commitments.insert(id, c);
+            }
+
+            let package = SigningPackage::new(commitments, MESSAGE.to_vec());
+            let mut shares = BTreeMap::new();
+            for signer in &SIGNERS {
+                let id = Identifier::new(signer.id).unwrap();
+                let share =
+                    sign(&package, nonces.remove(&id).unwrap(), &key_packages[&id]).unwrap();
+                assert_eq!(hex(&share.0.serialize()), signer.sig_share);
+                shares.insert(id, share);
+            }
+
+            let signature = aggregate(&package, &shares, &public).unwrap();
+            assert_eq!(hex(&signature), SIGNATURE);
+            verify(&public.verifying_key_bytes(), MESSAGE, &signature).unwrap();
+        }
+    }
+}
@@ -10,7 +10,7 @@
 //! change. The `Curve` enum doubles as the typed JOSE `crv` wire boundary.
 
 use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
-use rand_core::OsRng;
+use rand_core::{CryptoRng, OsRng, RngCore};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use zeroize::{Zeroize, ZeroizeOnDrop};
@@ -322,14 +322,19 @@ impl PrivateKeyAgreement {
 
     /// Generate a new random private key on the given curve.
     pub fn generate(curve: Curve) -> Self {
+        Self::generate_with_rng(curve, &mut OsRng)
+    }
+
+    /// Generate a private key on the given curve from `rng`.
+    pub fn generate_with_rng<R: RngCore + CryptoRng>(curve: Curve, rng: &mut R) -> Self {
         match curve {
             Curve::X25519 => {
-                PrivateKeyAgreement::X25519(x25519_dalek::StaticSecret::random_from_rng(OsRng))
+                PrivateKeyAgreement::X25519(x25519_dalek::StaticSecret::random_from_rng(rng))
             }
-            Curve::P256 => PrivateKeyAgreement::P256(p256::SecretKey::random(&mut OsRng)),
-            Curve::K256 => PrivateKeyAgreement::K256(k256::SecretKey::random(&mut OsRng)),
-            Curve::P384 => PrivateKeyAgreement::P384(p384::SecretKey::random(&mut OsRng)),
-            Curve::P521 => PrivateKeyAgreement::P521(p521::SecretKey::random(&mut OsRng)),
+            Curve::P256 => PrivateKeyAgreement::P256(p256::SecretKey::random(rng)),
+            Curve::K256 => PrivateKeyAgreement::K256(k256::SecretKey::random(rng)),
+            Curve::P384 => PrivateKeyAgreement::P384(p384::SecretKey::random(rng)),
+            Curve::P521 => PrivateKeyAgreement::P521(p521::SecretKey::random(rng)),
         }
     }
 
@@ -403,7 +408,12 @@ pub struct EphemeralKeyPair {
 impl EphemeralKeyPair {
     /// Generate a new ephemeral key pair on the given curve.
     pub fn generate(curve: Curve) -> Self {
-        let private = PrivateKeyAgreement::generate(curve);
+        Self::generate_with_rng(curve, &mut OsRng)
+    }
+
+    /// Generate an ephemeral key pair on the given curve from `rng`.
+    pub fn generate_with_rng<R: RngCore + CryptoRng>(curve: Curve, rng: &mut R) -> Self {
+        let private = PrivateKeyAgreement::generate_with_rng(curve, rng);
         let public = private.public_key();
         Self { private, public }
     }
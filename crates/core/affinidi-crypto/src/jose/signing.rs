@@ -4,11 +4,15 @@
 //!
 //! Ported verbatim from `affinidi-messaging-didcomm` for the #327
 //! centralization; byte-level output is locked by [`super::kat`] (both the
-//! Ed25519 and the deterministic-ECDSA P-256 golden vectors).
+//! Ed25519 and the deterministic-ECDSA P-256 golden vectors). Verification
+//! delegates to [`crate::signature`].
 
-use ed25519_dalek::{Signer, Verifier};
+use ed25519_dalek::Signer;
 
-use crate::error::CryptoError;
+use crate::{
+    error::CryptoError,
+    signature::{self, SignatureAlgorithm},
+};
 
 /// Sign data with an Ed25519 private key (32-byte seed → 64-byte sig).
 pub fn sign(data: &[u8], private_key: &[u8; 32]) -> Result<[u8; 64], CryptoError> {
@@ -17,14 +21,9 @@ pub fn sign(data: &[u8], private_key: &[u8; 32]) -> Result<[u8; 64], CryptoError
     Ok(signature.to_bytes())
 }
 
-/// Verify an Ed25519 signature.
+/// Verify an Ed25519 signature. Strict: see [`crate::signature`].
 pub fn verify(data: &[u8], signature: &[u8; 64], public_key: &[u8; 32]) -> Result<(), CryptoError> {
-    let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(public_key)
-        .map_err(|e| CryptoError::Verification(format!("invalid public key: {e}")))?;
-    let sig = ed25519_dalek::Signature::from_bytes(signature);
-    verifying_key
-        .verify(data, &sig)
-        .map_err(|e| CryptoError::Verification(format!("signature verification failed: {e}")))
+    signature::verify_public_key(SignatureAlgorithm::EdDSA, public_key, data, signature)
 }
 
 /// Derive the Ed25519 public key from a private key.
@@ -39,15 +38,7 @@ pub fn verify_p256(
     signature: &[u8; 64],
     public_key_sec1: &[u8],
 ) -> Result<(), CryptoError> {
-    use p256::ecdsa::signature::Verifier as _;
-
-    let verifying_key = p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key_sec1)
-        .map_err(|e| CryptoError::Verification(format!("invalid P-256 public key: {e}")))?;
-    let sig = p256::ecdsa::Signature::from_slice(signature)
-        .map_err(|e| CryptoError::Verification(format!("invalid P-256 signature: {e}")))?;
-    verifying_key
-        .verify(data, &sig)
-        .map_err(|e| CryptoError::Verification(format!("signature verification failed: {e}")))
+    signature::verify_public_key(SignatureAlgorithm::ES256, public_key_sec1, data, signature)
 }
 
 /// Verify an ECDSA secp256k1 signature (JWS `alg: ES256K`).
@@ -56,13 +47,5 @@ pub fn verify_secp256k1(
     signature: &[u8; 64],
     public_key_sec1: &[u8],
 ) -> Result<(), CryptoError> {
-    use k256::ecdsa::signature::Verifier as _;
-
-    let verifying_key = k256::ecdsa::VerifyingKey::from_sec1_bytes(public_key_sec1)
-        .map_err(|e| CryptoError::Verification(format!("invalid secp256k1 public key: {e}")))?;
-    let sig = k256::ecdsa::Signature::from_slice(signature)
-        .map_err(|e| CryptoError::Verification(format!("invalid secp256k1 signature: {e}")))?;
-    verifying_key
-        .verify(data, &sig)
-        .map_err(|e| CryptoError::Verification(format!("signature verification failed: {e}")))
+    signature::verify_public_key(SignatureAlgorithm::ES256K, public_key_sec1, data, signature)
 }
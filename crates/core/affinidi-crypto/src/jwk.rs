@@ -3,16 +3,22 @@
 use affinidi_encoding::{
     ED25519_PUB, MultiEncoded, P256_PUB, P384_PUB, P521_PUB, SECP256K1_PUB, X25519_PUB,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use crate::{CryptoError, KeyType};
+use crate::{
+    CryptoError, KeyType,
+    expose::{SerializePrivate, SerializeWithPrivate},
+};
 
 /// RFC 7517 JWK Struct
 ///
 /// `#[non_exhaustive]`: construct via [`JWK::new`] (or deserialization) rather
 /// than a struct literal, so new fields are not a breaking change. Fields stay
 /// public for reads.
+///
+/// Serializes without the private `d`; use [`JWK::expose_secret`] to include
+/// it.
 #[derive(Debug, Serialize, Deserialize, Clone, Zeroize, ZeroizeOnDrop)]
 #[non_exhaustive]
 pub struct JWK {
@@ -29,6 +35,12 @@ impl JWK {
         Self { key_id, params }
     }
 
+    /// Serializes this JWK including the private `d`, for the places that
+    /// store private keys on purpose.
+    pub fn expose_secret(&self) -> SerializeWithPrivate<'_, Self> {
+        SerializeWithPrivate::new(self)
+    }
+
     /// Returns the KeyType for a JWK
     pub fn key_type(&self) -> KeyType {
         match &self.params {
@@ -88,8 +100,8 @@ pub enum Params {
 /// Elliptic Curve parameters (P-256, P-384, secp256k1)
 ///
 /// `#[non_exhaustive]`: construct via [`ECParams::new`] rather than a struct
-/// literal. Fields stay public for reads.
-#[derive(Serialize, Deserialize, Clone, Zeroize, PartialEq, ZeroizeOnDrop)]
+/// literal. Fields stay public for reads. Serializes without `d`.
+#[derive(Deserialize, Clone, Zeroize, PartialEq, ZeroizeOnDrop)]
 #[non_exhaustive]
 pub struct ECParams {
     #[serde(rename = "crv")]
@@ -121,8 +133,8 @@ impl std::fmt::Debug for ECParams {
 /// Octet Key Pair parameters (Ed25519, X25519)
 ///
 /// `#[non_exhaustive]`: construct via [`OctectParams::new`] rather than a struct
-/// literal. Fields stay public for reads.
-#[derive(Serialize, Deserialize, Clone, Zeroize, PartialEq, ZeroizeOnDrop)]
+/// literal. Fields stay public for reads. Serializes without `d`.
+#[derive(Deserialize, Clone, Zeroize, PartialEq, ZeroizeOnDrop)]
 #[non_exhaustive]
 pub struct OctectParams {
     #[serde(rename = "crv")]
@@ -139,6 +151,77 @@ impl OctectParams {
     }
 }
 
+impl Serialize for ECParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ECParams", 3)?;
+        state.serialize_field("crv", &self.curve)?;
+        state.serialize_field("x", &self.x)?;
+        state.serialize_field("y", &self.y)?;
+        state.end()
+    }
+}
+
+impl Serialize for OctectParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("OctectParams", 2)?;
+        state.serialize_field("crv", &self.curve)?;
+        state.serialize_field("x", &self.x)?;
+        state.end()
+    }
+}
+
+/// The wire form of a [`JWK`] including `d`.
+#[derive(Serialize)]
+struct PrivateJwk<'a> {
+    #[serde(rename = "kid", skip_serializing_if = "Option::is_none")]
+    key_id: Option<&'a str>,
+    #[serde(flatten)]
+    params: PrivateParams<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kty")]
+enum PrivateParams<'a> {
+    #[serde(rename = "EC")]
+    Ec {
+        crv: &'a str,
+        x: &'a str,
+        y: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        d: Option<&'a str>,
+    },
+    #[serde(rename = "OKP")]
+    Okp {
+        crv: &'a str,
+        x: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        d: Option<&'a str>,
+    },
+}
+
+impl SerializePrivate for JWK {
+    fn serialize_private<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let params = match &self.params {
+            Params::EC(params) => PrivateParams::Ec {
+                crv: &params.curve,
+                x: &params.x,
+                y: &params.y,
+                d: params.d.as_deref(),
+            },
+            Params::OKP(params) => PrivateParams::Okp {
+                crv: &params.curve,
+                x: &params.x,
+                d: params.d.as_deref(),
+            },
+        };
+        PrivateJwk {
+            key_id: self.key_id.as_deref(),
+            params,
+        }
+        .serialize(serializer)
+    }
+}
+
 impl std::fmt::Debug for OctectParams {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("OctectParams")
@@ -219,6 +302,32 @@ mod tests {
         assert!(dbg.contains("<redacted>"));
     }
 
+    #[test]
+    fn serialize_omits_d_unless_exposed() {
+        let raw = r#"{
+            "kid": "did:example:alice#key-1",
+            "crv": "P-256",
+            "d": "kQrTUKhBU-6bHbCdiY0dIfg3knd5U2-1FlLGGHSbF6U",
+            "kty": "EC",
+            "x": "sl56LMzaiR5efwwWU1jzC_dfbxQ8gzyLj_N1q2cJmkE",
+            "y": "UnAimUtlHMPj_T_wIDVPoJAolKHy8DoXXTb8wch4hgU"
+        }"#;
+        let jwk: JWK = serde_json::from_str(raw).unwrap();
+
+        let public = serde_json::to_value(&jwk).unwrap();
+        assert!(public.get("d").is_none());
+        assert_eq!(public["kty"], "EC");
+        assert_eq!(public["kid"], "did:example:alice#key-1");
+
+        let private = serde_json::to_value(jwk.expose_secret()).unwrap();
+        assert_eq!(
+            private,
+            serde_json::from_str::<serde_json::Value>(raw).unwrap()
+        );
+        let back: JWK = serde_json::from_value(private).unwrap();
+        assert_eq!(back.params, jwk.params);
+    }
+
     #[test]
     fn from_multikey_secp256k1() {
         assert!(JWK::from_multikey("zQ3shT2ynSjzY5XoTxhWHvYVZ6GiLWhBVincVekcEpZDRCBHV").is_ok());
@@ -1,7 +1,8 @@
 //! Cryptographic primitives and JWK types for Affinidi TDK
 //!
 //! This crate provides:
-//! - JWK (JSON Web Key) types per RFC 7517
+//! - JWK (JSON Web Key) types per RFC 7517, which serialize private members
+//!   only on request (`expose_secret()`)
 //! - Key generation for various curves (Ed25519, X25519, P-256, P-384, secp256k1)
 //! - Key conversion utilities (e.g., Ed25519 → X25519)
 //! - `did:key` encode/decode helpers for raw-bytes APIs (HPKE, ECDH) —
@@ -9,8 +10,13 @@
 //! - Post-quantum signatures (FIPS 204 ML-DSA, FIPS 205 SLH-DSA) behind
 //!   the `post-quantum` feature (off by default; also available
 //!   individually as `ml-dsa` / `slh-dsa`)
+//! - Signature verification for Ed25519, P-256, secp256k1 and P-384 with one
+//!   set of strictness checks — see [`signature`]
+//! - FROST threshold Ed25519 (RFC 9591) key generation and signing behind
+//!   the `frost` feature — see [`frost`]
 
 mod error;
+mod expose;
 mod jwk;
 mod key_type;
 
@@ -18,6 +24,10 @@ mod key_type;
 /// no feature gate.
 pub mod bls12381;
 
+/// Signature verification for the classical signing curves. Each algorithm
+/// is gated on its curve's feature.
+pub mod signature;
+
 #[cfg(feature = "ed25519")]
 pub mod did_key;
 
@@ -39,6 +49,9 @@ pub mod p521;
 #[cfg(feature = "jose")]
 pub mod jose;
 
+#[cfg(feature = "frost")]
+pub mod frost;
+
 #[cfg(feature = "ml-dsa")]
 pub mod ml_dsa;
 
@@ -46,6 +59,7 @@ pub mod ml_dsa;
 pub mod slh_dsa;
 
 pub use error::CryptoError;
+pub use expose::{SerializePrivate, SerializeWithPrivate, serialize_with_private};
 pub use jwk::{ECParams, JWK, OctectParams, Params};
 pub use key_type::KeyType;
 
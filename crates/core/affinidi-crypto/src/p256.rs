@@ -107,19 +107,19 @@ pub fn sign(private_key_bytes: &[u8], data: &[u8]) -> Result<Vec<u8>> {
 /// Verify an ES256 signature with a P-256 public key.
 ///
 /// `public_key_bytes` should be the uncompressed or compressed SEC1 encoding.
-/// `signature_bytes` should be 64 bytes (r || s).
+/// `signature_bytes` should be 64 bytes (r || s). A signature that doesn't
+/// verify is `Ok(false)`; see [`crate::signature::verify_public_key`] for a
+/// variant that errors instead.
 pub fn verify(public_key_bytes: &[u8], data: &[u8], signature_bytes: &[u8]) -> Result<bool> {
-    use p256::ecdsa::{Signature, signature::Verifier};
-
-    let verifying_key = VerifyingKey::from_sec1_bytes(public_key_bytes)
-        .map_err(|e| CryptoError::KeyError(format!("invalid P-256 public key: {e}")))?;
-
-    let signature = Signature::from_slice(signature_bytes)
-        .map_err(|e| CryptoError::KeyError(format!("invalid signature: {e}")))?;
-
-    match verifying_key.verify(data, &signature) {
+    match crate::signature::verify_public_key(
+        crate::signature::SignatureAlgorithm::ES256,
+        public_key_bytes,
+        data,
+        signature_bytes,
+    ) {
         Ok(()) => Ok(true),
-        Err(_) => Ok(false),
+        Err(CryptoError::Verification(_)) => Ok(false),
+        Err(e) => Err(e),
     }
 }
 
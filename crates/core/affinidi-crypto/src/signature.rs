@@ -0,0 +1,415 @@
+//! Signature verification for the classical signing curves, symmetrical to
+//! the per-curve `sign` functions.
+//!
+//! [`verify`] (keyed by a JWK) and [`verify_public_key`] (keyed by raw public
+//! key bytes) are the one place the crate decides what a valid signature is,
+//! so every caller gets the same checks:
+//!
+//! - **EdDSA** is verified with `verify_strict`, which rejects non-canonical
+//!   `S` values and small-order public keys (both of which the lenient RFC
+//!   8032 check accepts).
+//! - **ECDSA** signatures must be the fixed-length `r || s` encoding JWS uses
+//!   (RFC 7518 §3.4); DER is rejected, as are `r` or `s` of zero or out of
+//!   range. `ES256K` additionally requires low-`S` (the Bitcoin/secp256k1
+//!   convention that removes the `(r, n - s)` malleability). `ES256` and
+//!   `ES384` accept either `S`: JOSE doesn't require normalisation, and
+//!   WebCrypto and HSM signers don't normalise.
+//!
+//! Errors say which input was at fault: [`CryptoError::KeyError`] for the
+//! public key (or a key that doesn't match the algorithm),
+//! [`CryptoError::Decoding`] for a signature of the wrong shape, and
+//! [`CryptoError::Verification`] for a well-formed signature that doesn't
+//! verify.
+
+use std::fmt;
+
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+
+use crate::{CryptoError, JWK, KeyType, Params, error::Result};
+
+/// A JWS signature algorithm [`verify`] supports.
+///
+/// This enum is `#[non_exhaustive]`: algorithms will be added as curves gain
+/// signing support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SignatureAlgorithm {
+    /// Ed25519 (RFC 8037)
+    EdDSA,
+    /// ECDSA over P-256 with SHA-256
+    ES256,
+    /// ECDSA over secp256k1 with SHA-256 (RFC 8812)
+    ES256K,
+    /// ECDSA over P-384 with SHA-384
+    ES384,
+}
+
+impl SignatureAlgorithm {
+    /// The JWS `alg` name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::EdDSA => "EdDSA",
+            Self::ES256 => "ES256",
+            Self::ES256K => "ES256K",
+            Self::ES384 => "ES384",
+        }
+    }
+
+    /// The type of key that verifies signatures of this algorithm.
+    pub fn key_type(&self) -> KeyType {
+        match self {
+            Self::EdDSA => KeyType::Ed25519,
+            Self::ES256 => KeyType::P256,
+            Self::ES256K => KeyType::Secp256k1,
+            Self::ES384 => KeyType::P384,
+        }
+    }
+
+    /// Length in bytes of a signature of this algorithm.
+    pub fn signature_len(&self) -> usize {
+        match self {
+            Self::EdDSA | Self::ES256 | Self::ES256K => 64,
+            Self::ES384 => 96,
+        }
+    }
+}
+
+impl fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for SignatureAlgorithm {
+    type Error = CryptoError;
+
+    /// Parses a JWS `alg`. The fully-specified `Ed25519`
+    /// (draft-ietf-jose-fully-specified-algorithms) is accepted as `EdDSA`.
+    fn try_from(alg: &str) -> Result<Self> {
+        match alg {
+            "EdDSA" | "Ed25519" => Ok(Self::EdDSA),
+            "ES256" => Ok(Self::ES256),
+            "ES256K" => Ok(Self::ES256K),
+            "ES384" => Ok(Self::ES384),
+            other => Err(CryptoError::UnsupportedKeyType(format!(
+                "Unsupported signature algorithm ({other})"
+            ))),
+        }
+    }
+}
+
+/// Verifies `signature` over `data` with the public key in `jwk`.
+///
+/// The JWK's curve must match `alg`: a P-256 key can't verify an `ES384`
+/// signature, even though both are ECDSA.
+pub fn verify(jwk: &JWK, alg: SignatureAlgorithm, data: &[u8], signature: &[u8]) -> Result<()> {
+    if jwk.key_type() != alg.key_type() {
+        return Err(CryptoError::KeyError(format!(
+            "{alg} needs a {:?} key (JWK is {:?})",
+            alg.key_type(),
+            jwk.key_type()
+        )));
+    }
+
+    let public_key = match &jwk.params {
+        Params::OKP(params) => decode_coordinate("x", &params.x)?,
+        Params::EC(params) => {
+            // Uncompressed SEC1 point: 0x04 || x || y
+            let x = decode_coordinate("x", &params.x)?;
+            let y = decode_coordinate("y", &params.y)?;
+            let mut point = Vec::with_capacity(1 + x.len() + y.len());
+            point.push(0x04);
+            point.extend_from_slice(&x);
+            point.extend_from_slice(&y);
+            point
+        }
+    };
+
+    verify_public_key(alg, &public_key, data, signature)
+}
+
+/// Verifies `signature` over `data` with a raw public key: the 32-byte key
+/// for `EdDSA`, a compressed or uncompressed SEC1 point for the ECDSA
+/// algorithms.
+#[cfg_attr(
+    not(any(
+        feature = "ed25519",
+        feature = "p256",
+        feature = "k256",
+        feature = "p384"
+    )),
+    allow(unused_variables)
+)]
+pub fn verify_public_key(
+    alg: SignatureAlgorithm,
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    if signature.len() != alg.signature_len() {
+        return Err(CryptoError::Decoding(format!(
+            "{alg} signature must be {} bytes (got {})",
+            alg.signature_len(),
+            signature.len()
+        )));
+    }
+
+    match alg {
+        #[cfg(feature = "ed25519")]
+        SignatureAlgorithm::EdDSA => verify_ed25519(public_key, data, signature),
+        #[cfg(feature = "p256")]
+        SignatureAlgorithm::ES256 => verify_p256(public_key, data, signature),
+        #[cfg(feature = "k256")]
+        SignatureAlgorithm::ES256K => verify_secp256k1(public_key, data, signature),
+        #[cfg(feature = "p384")]
+        SignatureAlgorithm::ES384 => verify_p384(public_key, data, signature),
+        #[allow(unreachable_patterns)]
+        other => Err(CryptoError::UnsupportedKeyType(format!(
+            "{other} verification isn't enabled in this build"
+        ))),
+    }
+}
+
+fn decode_coordinate(name: &str, value: &str) -> Result<Vec<u8>> {
+    BASE64_URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|e| CryptoError::KeyError(format!("JWK {name} isn't valid base64url: {e}")))
+}
+
+#[cfg(feature = "ed25519")]
+fn verify_ed25519(public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<()> {
+    use ed25519_dalek::{Signature, VerifyingKey};
+
+    let public_key: &[u8; 32] = public_key.try_into().map_err(|_| {
+        CryptoError::KeyError(format!(
+            "Ed25519 public key must be 32 bytes (got {})",
+            public_key.len()
+        ))
+    })?;
+    let verifying_key = VerifyingKey::from_bytes(public_key)
+        .map_err(|e| CryptoError::KeyError(format!("invalid Ed25519 public key: {e}")))?;
+    let signature = Signature::from_slice(signature)
+        .map_err(|e| CryptoError::Decoding(format!("invalid Ed25519 signature: {e}")))?;
+
+    verifying_key
+        .verify_strict(data, &signature)
+        .map_err(|e| CryptoError::Verification(format!("signature verification failed: {e}")))
+}
+
+#[cfg(feature = "p256")]
+fn verify_p256(public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<()> {
+    use p256::ecdsa::{Signature, VerifyingKey, signature::Verifier};
+
+    let verifying_key = VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|e| CryptoError::KeyError(format!("invalid P-256 public key: {e}")))?;
+    let signature = Signature::from_slice(signature)
+        .map_err(|e| CryptoError::Decoding(format!("invalid P-256 signature: {e}")))?;
+
+    verifying_key
+        .verify(data, &signature)
+        .map_err(|e| CryptoError::Verification(format!("signature verification failed: {e}")))
+}
+
+#[cfg(feature = "k256")]
+fn verify_secp256k1(public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<()> {
+    use k256::ecdsa::{Signature, VerifyingKey, signature::Verifier};
+
+    let verifying_key = VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|e| CryptoError::KeyError(format!("invalid secp256k1 public key: {e}")))?;
+    let signature = Signature::from_slice(signature)
+        .map_err(|e| CryptoError::Decoding(format!("invalid secp256k1 signature: {e}")))?;
+    // k256 checks this too; checking here keeps the policy in this module.
+    if signature.normalize_s().is_some() {
+        return Err(CryptoError::Verification(
+            "secp256k1 signature has a high S value".to_string(),
+        ));
+    }
+
+    verifying_key
+        .verify(data, &signature)
+        .map_err(|e| CryptoError::Verification(format!("signature verification failed: {e}")))
+}
+
+#[cfg(feature = "p384")]
+fn verify_p384(public_key: &[u8], data: &[u8], signature: &[u8]) -> Result<()> {
+    use p384::ecdsa::{Signature, VerifyingKey, signature::Verifier};
+
+    let verifying_key = VerifyingKey::from_sec1_bytes(public_key)
+        .map_err(|e| CryptoError::KeyError(format!("invalid P-384 public key: {e}")))?;
+    let signature = Signature::from_slice(signature)
+        .map_err(|e| CryptoError::Decoding(format!("invalid P-384 signature: {e}")))?;
+
+    verifying_key
+        .verify(data, &signature)
+        .map_err(|e| CryptoError::Verification(format!("signature verification failed: {e}")))
+}
+
+// The tests sign with every curve, so they need the default features.
+#[cfg(all(
+    test,
+    feature = "ed25519",
+    feature = "p256",
+    feature = "k256",
+    feature = "p384"
+))]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = b"symmetrical to sign";
+
+    #[test]
+    fn alg_names_round_trip() {
+        for alg in [
+            SignatureAlgorithm::EdDSA,
+            SignatureAlgorithm::ES256,
+            SignatureAlgorithm::ES256K,
+            SignatureAlgorithm::ES384,
+        ] {
+            assert_eq!(SignatureAlgorithm::try_from(alg.as_str()).unwrap(), alg);
+        }
+        assert_eq!(
+            SignatureAlgorithm::try_from("Ed25519").unwrap(),
+            SignatureAlgorithm::EdDSA
+        );
+        assert!(SignatureAlgorithm::try_from("RS256").is_err());
+    }
+
+    #[test]
+    fn ed25519_jwk() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let keypair = crate::ed25519::generate(None);
+        let signing_key =
+            SigningKey::from_bytes(keypair.private_bytes.as_slice().try_into().unwrap());
+        let signature = signing_key.sign(DATA).to_bytes();
+
+        verify(&keypair.jwk, SignatureAlgorithm::EdDSA, DATA, &signature).unwrap();
+        assert!(matches!(
+            verify(
+                &keypair.jwk,
+                SignatureAlgorithm::EdDSA,
+                b"tampered",
+                &signature
+            ),
+            Err(CryptoError::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn ed25519_rejects_non_canonical_s() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let public_key = signing_key.verifying_key().to_bytes();
+        let mut signature = signing_key.sign(DATA).to_bytes();
+
+        // S + L, where L is the order of the Ed25519 base point: the same
+        // signature to a lenient verifier, but not a canonical encoding.
+        const L: [u8; 32] = [
+            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9,
+            0xde, 0x14, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+        ];
+        let mut carry = 0u16;
+        for (s, l) in signature[32..].iter_mut().zip(L) {
+            let sum = *s as u16 + l as u16 + carry;
+            *s = sum as u8;
+            carry = sum >> 8;
+        }
+
+        assert!(
+            verify_public_key(SignatureAlgorithm::EdDSA, &public_key, DATA, &signature).is_err()
+        );
+    }
+
+    #[test]
+    fn p256_jwk() {
+        let keypair = crate::p256::generate(None).unwrap();
+        let signature = crate::p256::sign(&keypair.private_bytes, DATA).unwrap();
+
+        verify(&keypair.jwk, SignatureAlgorithm::ES256, DATA, &signature).unwrap();
+        assert!(matches!(
+            verify(
+                &keypair.jwk,
+                SignatureAlgorithm::ES256,
+                b"tampered",
+                &signature
+            ),
+            Err(CryptoError::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn p384_jwk() {
+        use p384::ecdsa::{Signature, SigningKey, signature::Signer};
+
+        let keypair = crate::p384::generate(None).unwrap();
+        let signing_key = SigningKey::from_slice(&keypair.private_bytes).unwrap();
+        let signature: Signature = signing_key.sign(DATA);
+
+        verify(
+            &keypair.jwk,
+            SignatureAlgorithm::ES384,
+            DATA,
+            &signature.to_bytes(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn secp256k1_rejects_high_s() {
+        use k256::ecdsa::{Signature, SigningKey, signature::Signer};
+
+        let keypair = crate::secp256k1::generate(None).unwrap();
+        let signing_key = SigningKey::from_slice(&keypair.private_bytes).unwrap();
+        let signature: Signature = signing_key.sign(DATA);
+        verify(
+            &keypair.jwk,
+            SignatureAlgorithm::ES256K,
+            DATA,
+            &signature.to_bytes(),
+        )
+        .unwrap();
+
+        // (r, n - s) verifies under plain ECDSA
+        let high_s = Signature::from_scalars(signature.r(), -*signature.s()).unwrap();
+        assert!(matches!(
+            verify(
+                &keypair.jwk,
+                SignatureAlgorithm::ES256K,
+                DATA,
+                &high_s.to_bytes()
+            ),
+            Err(CryptoError::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_key_and_alg() {
+        let keypair = crate::p256::generate(None).unwrap();
+        let signature = crate::p256::sign(&keypair.private_bytes, DATA).unwrap();
+
+        assert!(matches!(
+            verify(&keypair.jwk, SignatureAlgorithm::ES256K, DATA, &signature),
+            Err(CryptoError::KeyError(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_der_signatures() {
+        use p256::ecdsa::{Signature, SigningKey, signature::Signer};
+
+        let keypair = crate::p256::generate(None).unwrap();
+        let signing_key = SigningKey::from_slice(&keypair.private_bytes).unwrap();
+        let signature: Signature = signing_key.sign(DATA);
+
+        assert!(matches!(
+            verify(
+                &keypair.jwk,
+                SignatureAlgorithm::ES256,
+                DATA,
+                signature.to_der().as_bytes()
+            ),
+            Err(CryptoError::Decoding(_))
+        ));
+    }
+}
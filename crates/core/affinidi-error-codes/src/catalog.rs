@@ -0,0 +1,340 @@
+/*!
+ * Localisable message templates keyed by error code.
+ *
+ * A [`MessageCatalog`] holds, per locale, a template for each
+ * [`ErrorCode`]. Templates name parameters in braces — `{did}` — which are
+ * filled from the error's [`ErrorCodes::error_params`]; `{code}` is always
+ * available, and `{{` / `}}` write literal braces.
+ *
+ * [`MessageCatalog::from_registry`] ships the English templates, taken from
+ * the descriptions in the error code tables. Embedders add their own locales
+ * in code or from JSON objects mapping codes to templates:
+ *
+ * ```
+ * use affinidi_error_codes::{ErrorCodes, MessageCatalog, Registry, error_codes};
+ *
+ * #[derive(Debug)]
+ * pub enum StoreError {
+ *     NotFound(String),
+ * }
+ *
+ * error_codes!(StoreError, "STORE", {
+ *     1 => NotFound(key) [key]: "Nothing is stored under {key}.",
+ * });
+ *
+ * let catalog = MessageCatalog::from_registry(&Registry::new().with::<StoreError>())
+ *     .with_json("de", r#"{ "STORE-0001": "Unter {key} ist nichts gespeichert." }"#)
+ *     .unwrap();
+ *
+ * let error = StoreError::NotFound("avatar".into());
+ * assert_eq!(catalog.message("de-AT", &error), "Unter avatar ist nichts gespeichert.");
+ * assert_eq!(catalog.message("ja", &error), "Nothing is stored under avatar.");
+ * ```
+ *
+ * Lookup tries the locale as given (`pt-BR`), then with its subtags dropped
+ * one by one (`pt`), then the fallback locale (`en` unless changed). When no
+ * template is found at all, [`MessageCatalog::message`] returns the code
+ * itself.
+ */
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{ErrorCode, ErrorCodeEntry, ErrorCodes, Registry};
+
+/// Locale of the templates [`MessageCatalog::from_registry`] ships.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Named values for the placeholders of a message template.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageParams {
+    params: Vec<(String, String)>,
+}
+
+impl MessageParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `name` to `value`, replacing any earlier value.
+    pub fn with(mut self, name: impl Into<String>, value: impl fmt::Display) -> Self {
+        let name = name.into();
+        let value = value.to_string();
+        match self.params.iter_mut().find(|(n, _)| *n == name) {
+            Some(param) => param.1 = value,
+            None => self.params.push((name, value)),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// Every parameter, in the order they were set.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+}
+
+/// Message templates per locale, keyed by `PREFIX-NNNN` code.
+#[derive(Clone, Debug)]
+pub struct MessageCatalog {
+    fallback: String,
+    templates: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for MessageCatalog {
+    /// An empty catalog falling back to [`DEFAULT_LOCALE`].
+    fn default() -> Self {
+        Self {
+            fallback: DEFAULT_LOCALE.into(),
+            templates: HashMap::new(),
+        }
+    }
+}
+
+impl MessageCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A catalog with the English template of every code in `registry`.
+    pub fn from_registry(registry: &Registry) -> Self {
+        registry
+            .entries()
+            .iter()
+            .fold(Self::new(), |catalog, entry| {
+                catalog.with_template(DEFAULT_LOCALE, entry.code, entry.description)
+            })
+    }
+
+    /// Locale to use when neither the requested locale nor its language has
+    /// a template.
+    pub fn with_fallback(mut self, locale: &str) -> Self {
+        self.fallback = normalise(locale);
+        self
+    }
+
+    /// Add or replace the template for `code` in `locale`.
+    pub fn with_template(
+        mut self,
+        locale: &str,
+        code: impl fmt::Display,
+        template: impl Into<String>,
+    ) -> Self {
+        self.templates
+            .entry(normalise(locale))
+            .or_default()
+            .insert(code.to_string(), template.into());
+        self
+    }
+
+    /// Add or replace templates for `locale` from a JSON object mapping
+    /// codes to templates, such as a translation file.
+    pub fn with_json(self, locale: &str, json: &str) -> Result<Self, serde_json::Error> {
+        let templates: HashMap<String, String> = serde_json::from_str(json)?;
+        Ok(templates
+            .into_iter()
+            .fold(self, |catalog, (code, template)| {
+                catalog.with_template(locale, code, template)
+            }))
+    }
+
+    /// Locales with at least one template.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.templates.keys().map(String::as_str)
+    }
+
+    /// The template for `code`, looked up from `locale` down to the
+    /// fallback locale.
+    pub fn template(&self, locale: &str, code: impl fmt::Display) -> Option<&str> {
+        let code = code.to_string();
+        candidates(&normalise(locale))
+            .chain(std::iter::once(self.fallback.clone()))
+            .find_map(|locale| self.templates.get(&locale)?.get(&code))
+            .map(String::as_str)
+    }
+
+    /// The template for `code` with its placeholders filled from `params`.
+    pub fn render(&self, locale: &str, code: ErrorCode, params: &MessageParams) -> Option<String> {
+        self.template(locale, code)
+            .map(|template| fill(template, code, params))
+    }
+
+    /// The message for `error` in `locale`, or its code when no locale has a
+    /// template for it.
+    pub fn message<E: ErrorCodes>(&self, locale: &str, error: &E) -> String {
+        let code = error.error_code();
+        self.render(locale, code, &error.error_params())
+            .unwrap_or_else(|| code.to_string())
+    }
+
+    /// Entries of `registry` with no template in exactly `locale`, so
+    /// translations can be checked for completeness.
+    pub fn missing<'r>(&self, locale: &str, registry: &'r Registry) -> Vec<&'r ErrorCodeEntry> {
+        let templates = self.templates.get(&normalise(locale));
+        registry
+            .entries()
+            .iter()
+            .filter(|entry| {
+                templates.is_none_or(|templates| !templates.contains_key(&entry.code.to_string()))
+            })
+            .collect()
+    }
+}
+
+/// Locale tags compare case-insensitively, with `-` or `_` between subtags.
+fn normalise(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+/// `zh-hant-tw`, `zh-hant`, `zh`.
+fn candidates(locale: &str) -> impl Iterator<Item = String> + '_ {
+    std::iter::successors(Some(locale), |tag| {
+        tag.rsplit_once('-').map(|(head, _)| head)
+    })
+    .filter(|tag| !tag.is_empty())
+    .map(str::to_string)
+}
+
+/// Replace `{name}` with its parameter, leaving unknown names in place.
+fn fill(template: &str, code: ErrorCode, params: &MessageParams) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let placeholder = tail
+            .strip_prefix('{')
+            .and_then(|t| t.find('}').map(|end| &t[..end]));
+        match placeholder {
+            Some(name) => {
+                match (name, params.get(name)) {
+                    (_, Some(value)) => out.push_str(value),
+                    ("code", None) => out.push_str(&code.to_string()),
+                    _ => out.push_str(&tail[..name.len() + 2]),
+                }
+                rest = &tail[name.len() + 2..];
+            }
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_codes;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum Offer {
+        Taken(String),
+        Expired,
+    }
+
+    error_codes!(Offer, "OFFER", {
+        1 => Taken(phrase) [phrase]: "The phrase \"{phrase}\" is already in use.",
+        2 => Expired: "The offer has expired.",
+    });
+
+    fn catalog() -> MessageCatalog {
+        MessageCatalog::from_registry(&Registry::new().with::<Offer>())
+    }
+
+    #[test]
+    fn english_comes_from_the_registry() {
+        let catalog = catalog();
+        assert_eq!(
+            catalog.message("en", &Offer::Taken("blue fox".into())),
+            "The phrase \"blue fox\" is already in use."
+        );
+        assert_eq!(
+            catalog.template("en", ErrorCode::new("OFFER", 2)),
+            Some("The offer has expired.")
+        );
+    }
+
+    #[test]
+    fn locales_fall_back_by_subtag_then_default() {
+        let catalog = catalog()
+            .with_template("es", "OFFER-0002", "La oferta ha caducado.")
+            .with_template("es_MX", "OFFER-0002", "La oferta ya venció.");
+
+        assert_eq!(
+            catalog.message("es-MX", &Offer::Expired),
+            "La oferta ya venció."
+        );
+        assert_eq!(
+            catalog.message("ES-ar", &Offer::Expired),
+            "La oferta ha caducado."
+        );
+        assert_eq!(
+            catalog.message("fr", &Offer::Expired),
+            "The offer has expired."
+        );
+        assert_eq!(
+            MessageCatalog::new().message("fr", &Offer::Expired),
+            "OFFER-0002"
+        );
+
+        let catalog = catalog.with_fallback("es");
+        assert_eq!(
+            catalog.message("fr", &Offer::Expired),
+            "La oferta ha caducado."
+        );
+    }
+
+    #[test]
+    fn json_adds_a_locale_and_missing_lists_the_gaps() {
+        let registry = Registry::new().with::<Offer>();
+        let catalog = catalog()
+            .with_json(
+                "fr",
+                r#"{"OFFER-0001": "La phrase « {phrase} » est déjà prise."}"#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            catalog.message("fr-CA", &Offer::Taken("renard bleu".into())),
+            "La phrase « renard bleu » est déjà prise."
+        );
+        let missing: Vec<String> = catalog
+            .missing("fr", &registry)
+            .iter()
+            .map(|e| e.code.to_string())
+            .collect();
+        assert_eq!(missing, ["OFFER-0002"]);
+        assert!(catalog.missing("en", &registry).is_empty());
+        assert!(catalog.with_json("de", "[]").is_err());
+    }
+
+    #[test]
+    fn placeholders() {
+        let code = ErrorCode::new("OFFER", 1);
+        let params = MessageParams::new().with("phrase", "x").with("phrase", "y");
+        assert_eq!(
+            fill("{code}: {phrase} {{literal}} {unknown} {", code, &params),
+            "OFFER-0001: y {literal} {unknown} {"
+        );
+        assert_eq!(fill("}{phrase}{}", code, &params), "}y{}");
+    }
+}
@@ -0,0 +1,328 @@
+/*!
+ * Stable, machine-readable codes for the errors of the Affinidi TDK.
+ *
+ * Error messages are for people and change between releases. Products that
+ * embed the TDK need something steadier to branch on and to key translated
+ * messages by, so every TDK error enum maps each variant to an [`ErrorCode`]:
+ * a prefix naming the error type and a number within it, displayed as
+ * `PREFIX-NNNN` (`DIDCACHE-0005`, `ATM-0012`, …).
+ *
+ * ```
+ * use affinidi_error_codes::{ErrorCodes, Registry, error_codes};
+ *
+ * #[derive(Debug)]
+ * pub enum StoreError {
+ *     NotFound(String),
+ *     Timeout,
+ * }
+ *
+ * error_codes!(StoreError, "STORE", {
+ *     1 => NotFound(..): "The requested item does not exist.",
+ *     2 => Timeout: "The store did not answer in time.",
+ * });
+ *
+ * assert_eq!(StoreError::Timeout.error_code().to_string(), "STORE-0002");
+ *
+ * let json = Registry::new().with::<StoreError>().to_json();
+ * assert!(json.contains("\"STORE-0001\""));
+ * ```
+ *
+ * # Stability
+ *
+ * A code, once released, always means the same failure. When a variant is
+ * removed its number is retired, never reused; a new variant takes the next
+ * unused number. The `match` that [`error_codes!`] generates is exhaustive,
+ * so a variant added without a code doesn't compile.
+ *
+ * # Registry
+ *
+ * [`Registry`] gathers the tables of several error types and serialises
+ * them as JSON, one entry per code:
+ *
+ * ```json
+ * [{ "code": "STORE-0002", "error": "StoreError", "variant": "Timeout",
+ *    "description": "The store did not answer in time." }]
+ * ```
+ *
+ * `affinidi-tdk` builds the registry of every TDK error type; run its
+ * `error_registry` example to write it out.
+ *
+ * # Localised messages
+ *
+ * A row may name fields of its variant as parameters, and use them in its
+ * description: `8 => NotPinned(did) [did]: "{did} is not pinned."`. The
+ * descriptions form the built-in English templates of a [`MessageCatalog`],
+ * which embedders extend with templates for their own locales; see
+ * [`catalog`].
+ */
+
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+pub mod catalog;
+
+pub use catalog::{MessageCatalog, MessageParams};
+
+/// A stable error code: the prefix of an error type and a number within it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ErrorCode {
+    pub prefix: &'static str,
+    pub number: u16,
+}
+
+impl ErrorCode {
+    pub const fn new(prefix: &'static str, number: u16) -> Self {
+        Self { prefix, number }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:04}", self.prefix, self.number)
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// One code of an error type, as listed in the registry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ErrorCodeEntry {
+    pub code: ErrorCode,
+    /// Name of the error type, e.g. `DIDCacheError`.
+    pub error: &'static str,
+    /// Name of the variant, e.g. `NetworkTimeout`.
+    pub variant: &'static str,
+    /// What the failure means, in a sentence. Also the English message
+    /// template, so it may hold `{param}` placeholders.
+    pub description: &'static str,
+    /// Names of the parameters [`ErrorCodes::error_params`] fills in.
+    pub params: &'static [&'static str],
+}
+
+/// An error type whose variants carry [`ErrorCode`]s.
+///
+/// Implement it with [`error_codes!`] rather than by hand.
+pub trait ErrorCodes {
+    /// Prefix shared by every code of this type.
+    const PREFIX: &'static str;
+
+    /// The code of this error.
+    fn error_code(&self) -> ErrorCode;
+
+    /// Values for the message template parameters of this error.
+    fn error_params(&self) -> MessageParams {
+        MessageParams::new()
+    }
+
+    /// Every code this type can produce with the enabled features.
+    fn error_codes() -> &'static [ErrorCodeEntry];
+}
+
+/// The codes of several error types.
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    entries: Vec<ErrorCodeEntry>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add every code of `E`.
+    ///
+    /// # Panics
+    /// If a code is already registered, by `E` or by another type. Codes are
+    /// fixed tables in the source, so that is a bug in the table.
+    pub fn with<E: ErrorCodes>(mut self) -> Self {
+        for entry in E::error_codes() {
+            if let Some(existing) = self.lookup_code(entry.code) {
+                panic!(
+                    "error code {} is used by both {}::{} and {}::{}",
+                    entry.code, existing.error, existing.variant, entry.error, entry.variant
+                );
+            }
+            self.entries.push(*entry);
+        }
+        self
+    }
+
+    /// Every registered code, in registration order.
+    pub fn entries(&self) -> &[ErrorCodeEntry] {
+        &self.entries
+    }
+
+    /// Look up a code by its `PREFIX-NNNN` form.
+    pub fn lookup(&self, code: &str) -> Option<&ErrorCodeEntry> {
+        self.entries.iter().find(|e| e.code.to_string() == code)
+    }
+
+    /// The registry as a pretty-printed JSON array, sorted by code.
+    pub fn to_json(&self) -> String {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|e| e.code);
+        serde_json::to_string_pretty(&entries).expect("registry entries always serialise")
+    }
+
+    fn lookup_code(&self, code: ErrorCode) -> Option<&ErrorCodeEntry> {
+        self.entries.iter().find(|e| e.code == code)
+    }
+}
+
+/// Implement [`ErrorCodes`] for an error enum from a table of its variants.
+///
+/// Each row is `number => Variant: "description"`, with `(..)` or `{ .. }`
+/// after the variant name for tuple and struct variants. Attributes on a row,
+/// such as `#[cfg(feature = "…")]`, are applied to both the match arm and the
+/// registry entry, to follow variants that only exist with some features.
+///
+/// To pass fields to message templates, bind them in the pattern and list
+/// them in brackets after it; each must implement `Display`.
+///
+/// ```
+/// # use affinidi_error_codes::error_codes;
+/// # pub enum ResolveError { Http { status: u16 }, Timeout, #[cfg(any())] Cheqd(String) }
+/// error_codes!(ResolveError, "RESOLVE", {
+///     1 => Http { status } [status]: "The DID host answered with HTTP {status}.",
+///     2 => Timeout: "The DID host did not answer in time.",
+///     #[cfg(any())]
+///     3 => Cheqd(..): "The cheqd network rejected the request.",
+/// });
+/// ```
+#[macro_export]
+macro_rules! error_codes {
+    ($error:ty, $prefix:literal, {
+        $(
+            $(#[$attr:meta])*
+            $number:literal => $variant:ident
+                $( ( $($tuple:tt)* ) )? $( { $($fields:tt)* } )?
+                $( [ $($param:ident),* $(,)? ] )? : $description:literal
+        ),* $(,)?
+    }) => {
+        impl $crate::ErrorCodes for $error {
+            const PREFIX: &'static str = $prefix;
+
+            fn error_code(&self) -> $crate::ErrorCode {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        #[allow(unused_variables)]
+                        Self::$variant $( ( $($tuple)* ) )? $( { $($fields)* } )?
+                            => $crate::ErrorCode::new($prefix, $number),
+                    )*
+                }
+            }
+
+            fn error_params(&self) -> $crate::MessageParams {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        #[allow(unused_variables)]
+                        Self::$variant $( ( $($tuple)* ) )? $( { $($fields)* } )?
+                            => $crate::MessageParams::new()
+                                $( $( .with(stringify!($param), $param) )* )?,
+                    )*
+                }
+            }
+
+            fn error_codes() -> &'static [$crate::ErrorCodeEntry] {
+                const ENTRIES: &[$crate::ErrorCodeEntry] = &[
+                    $(
+                        $(#[$attr])*
+                        $crate::ErrorCodeEntry {
+                            code: $crate::ErrorCode::new($prefix, $number),
+                            error: stringify!($error),
+                            variant: stringify!($variant),
+                            description: $description,
+                            params: &[$( $( stringify!($param) ),* )?],
+                        },
+                    )*
+                ];
+                ENTRIES
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum First {
+        Tuple(String),
+        Struct {
+            reason: String,
+        },
+        Unit,
+        #[cfg(any())]
+        Disabled,
+    }
+
+    error_codes!(First, "FIRST", {
+        1 => Tuple(..): "A tuple variant.",
+        2 => Struct { reason } [reason]: "A struct variant: {reason}.",
+        4 => Unit: "A unit variant.",
+        #[cfg(any())]
+        3 => Disabled: "Only with a feature that is never enabled.",
+    });
+
+    #[allow(dead_code)]
+    enum Clash {
+        Again,
+    }
+
+    error_codes!(Clash, "FIRST", {
+        4 => Again: "Reuses FIRST-0004.",
+    });
+
+    #[test]
+    fn codes_follow_the_table() {
+        assert_eq!(
+            First::Tuple("x".into()).error_code().to_string(),
+            "FIRST-0001"
+        );
+        assert_eq!(
+            First::Struct { reason: "x".into() }.error_code(),
+            ErrorCode::new("FIRST", 2)
+        );
+        assert_eq!(First::Unit.error_code().to_string(), "FIRST-0004");
+        assert_eq!(First::error_codes().len(), 3, "disabled rows are left out");
+    }
+
+    #[test]
+    fn params_follow_the_table() {
+        let error = First::Struct {
+            reason: "full".into(),
+        };
+        assert_eq!(error.error_params().get("reason"), Some("full"));
+        assert!(First::Unit.error_params().is_empty());
+        assert_eq!(First::error_codes()[1].params, ["reason"]);
+        assert!(First::error_codes()[0].params.is_empty());
+    }
+
+    #[test]
+    fn registry_serialises_sorted_entries() {
+        let registry = Registry::new().with::<First>();
+        assert_eq!(registry.lookup("FIRST-0002").unwrap().variant, "Struct");
+        assert!(registry.lookup("FIRST-0003").is_none());
+
+        let json: serde_json::Value = serde_json::from_str(&registry.to_json()).unwrap();
+        assert_eq!(json[0]["code"], "FIRST-0001");
+        assert_eq!(json[0]["error"], "First");
+        assert_eq!(json[2]["description"], "A unit variant.");
+        assert_eq!(json[1]["params"], serde_json::json!(["reason"]));
+    }
+
+    #[test]
+    #[should_panic(expected = "FIRST-0004 is used by both First::Unit and Clash::Again")]
+    fn duplicate_codes_are_rejected() {
+        let _ = Registry::new().with::<First>().with::<Clash>();
+    }
+}
@@ -16,7 +16,8 @@
  * ```
  *
  * Setting `per_second` to `0` disables limiting entirely, and the layer becomes
- * a pass-through.
+ * a pass-through. [`RateLimiterState::set_limits`] changes the limits of a
+ * running service, for every clone of the state.
  *
  * # Two things that are easy to get wrong
  *
@@ -35,7 +36,13 @@
  * `into_make_service_with_connect_info::<SocketAddr>()`.
  */
 
-use std::{net::IpAddr, net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
+use std::{
+    net::IpAddr,
+    net::SocketAddr,
+    num::NonZeroU32,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use axum::{
     body::Body,
@@ -72,17 +79,18 @@ pub enum Refusal {
     NoClientIp,
 }
 
-/// Shared limiter state, cheap to clone.
+/// Shared limiter state, cheap to clone. Clones share one limiter, so
+/// [`set_limits`](RateLimiterState::set_limits) applies to all of them.
 #[derive(Clone)]
 pub struct RateLimiterState {
-    limiter: Option<Arc<KeyedLimiter>>,
+    limiter: Arc<RwLock<Option<Arc<KeyedLimiter>>>>,
     on_refused: Option<RefusalCallback>,
 }
 
 impl std::fmt::Debug for RateLimiterState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RateLimiterState")
-            .field("enabled", &self.limiter.is_some())
+            .field("enabled", &self.is_enabled())
             .field("has_callback", &self.on_refused.is_some())
             .finish()
     }
@@ -94,24 +102,35 @@ impl RateLimiterState {
     ///
     /// `per_second == 0` disables limiting; `burst == 0` is treated as 1.
     pub fn new(per_second: u32, burst: u32) -> Self {
-        let Some(per_second) = NonZeroU32::new(per_second) else {
-            return Self::disabled();
-        };
-        let burst = NonZeroU32::new(burst).unwrap_or(NonZeroU32::MIN);
         Self {
-            limiter: Some(Arc::new(RateLimiter::keyed(
-                Quota::per_second(per_second).allow_burst(burst),
-            ))),
+            limiter: Arc::new(RwLock::new(keyed_limiter(per_second, burst))),
             on_refused: None,
         }
     }
 
     /// A pass-through limiter that refuses nothing.
     pub fn disabled() -> Self {
-        Self {
-            limiter: None,
-            on_refused: None,
-        }
+        Self::new(0, 0)
+    }
+
+    /// Replace the limits, as [`new`](Self::new) takes them, for this state
+    /// and every clone of it — the running layer included.
+    ///
+    /// Buckets are not carried over: every client starts with a full bucket
+    /// under the new quota.
+    pub fn set_limits(&self, per_second: u32, burst: u32) {
+        *self
+            .limiter
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = keyed_limiter(per_second, burst);
+    }
+
+    /// The limiter in force, or `None` when limiting is disabled.
+    fn current(&self) -> Option<Arc<KeyedLimiter>> {
+        self.limiter
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
     }
 
     /// Observe refusals — for metrics, say.
@@ -125,12 +144,12 @@ impl RateLimiterState {
 
     /// Is limiting active?
     pub fn is_enabled(&self) -> bool {
-        self.limiter.is_some()
+        self.current().is_some()
     }
 
     /// Live bucket count, or 0 when disabled. Mainly useful in tests.
     pub fn tracked_keys(&self) -> usize {
-        self.limiter.as_ref().map_or(0, |l| l.len())
+        self.current().map_or(0, |l| l.len())
     }
 
     /// Charge one request against `ip`'s bucket.
@@ -138,7 +157,7 @@ impl RateLimiterState {
     /// This is the whole decision, separated from the middleware so it can be
     /// exercised directly.
     pub fn check(&self, ip: IpAddr) -> Result<(), Refusal> {
-        let Some(limiter) = &self.limiter else {
+        let Some(limiter) = self.current() else {
             return Ok(());
         };
         match limiter.check_key(&ip) {
@@ -163,11 +182,11 @@ impl RateLimiterState {
     /// a key is by definition indistinguishable from one that was never
     /// present, so sweeping cannot let a client exceed its quota.
     ///
-    /// No-op when limiting is disabled — there is no map to sweep.
+    /// Each sweep covers the limiter in force at the time, so one task serves
+    /// across [`set_limits`](Self::set_limits); while limiting is disabled
+    /// there is no map to sweep.
     pub fn spawn_gc(&self, shutdown: CancellationToken) {
-        let Some(limiter) = self.limiter.clone() else {
-            return;
-        };
+        let state = self.limiter.clone();
         tokio::spawn(async move {
             let mut ticker = tokio::time::interval(GC_INTERVAL);
             ticker.tick().await; // the first tick fires immediately
@@ -175,6 +194,13 @@ impl RateLimiterState {
                 tokio::select! {
                     _ = shutdown.cancelled() => break,
                     _ = ticker.tick() => {
+                        let Some(limiter) = state
+                            .read()
+                            .unwrap_or_else(|poisoned| poisoned.into_inner())
+                            .clone()
+                        else {
+                            continue;
+                        };
                         let before = limiter.len();
                         limiter.retain_recent();
                         limiter.shrink_to_fit();
@@ -217,6 +243,16 @@ impl RateLimiterState {
     }
 }
 
+/// A keyed limiter for `per_second` sustained requests per IP with bursts up
+/// to `burst`; `None` (disabled) when `per_second` is 0.
+fn keyed_limiter(per_second: u32, burst: u32) -> Option<Arc<KeyedLimiter>> {
+    let per_second = NonZeroU32::new(per_second)?;
+    let burst = NonZeroU32::new(burst).unwrap_or(NonZeroU32::MIN);
+    Some(Arc::new(RateLimiter::keyed(
+        Quota::per_second(per_second).allow_burst(burst),
+    )))
+}
+
 /// `tower` layer applying [`RateLimiterState`].
 #[derive(Clone, Debug)]
 pub struct RateLimitLayer {
@@ -387,6 +423,26 @@ mod tests {
         assert_eq!(hits.load(Ordering::SeqCst), 1);
     }
 
+    /// A clone — the layer's copy — sees new limits, starting from full buckets.
+    #[test]
+    fn set_limits_applies_to_clones() {
+        let limiter = RateLimiterState::new(1, 1);
+        let layer_copy = limiter.clone();
+        let client = ip("1.2.3.4");
+        assert!(layer_copy.check(client).is_ok());
+        assert!(layer_copy.check(client).is_err());
+
+        limiter.set_limits(1, 3);
+        for i in 0..3 {
+            assert!(layer_copy.check(client).is_ok(), "request {i} should pass");
+        }
+        assert!(layer_copy.check(client).is_err());
+
+        limiter.set_limits(0, 0);
+        assert!(!layer_copy.is_enabled());
+        assert!(layer_copy.check(client).is_ok());
+    }
+
     #[test]
     fn tracks_a_bucket_per_seen_ip() {
         let limiter = RateLimiterState::new(10, 10);
@@ -49,7 +49,10 @@ fn main() {
 
     println!("JWK: Private");
     println!("============");
-    println!("{}", serde_json::to_string_pretty(&secret).unwrap());
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&secret.expose_secret()).unwrap()
+    );
 
     println!();
     println!(
@@ -6,6 +6,7 @@ use x25519_dalek::{PublicKey, StaticSecret};
 use crate::{
     errors::SecretsResolverError,
     secrets::{Secret, SecretMaterial, SecretType},
+    usage::KeyUsagePolicy,
 };
 
 impl Secret {
@@ -25,6 +26,8 @@ impl Secret {
             private_bytes: keypair.private_bytes,
             public_bytes: keypair.public_bytes,
             key_type: KeyType::Ed25519,
+            purposes: Vec::new(),
+            key_usage_policy: KeyUsagePolicy::default(),
         }
     }
 
@@ -63,6 +66,8 @@ impl Secret {
             private_bytes: x25519.to_bytes().to_vec(),
             public_bytes: x25519_public.to_bytes().to_vec(),
             key_type: KeyType::X25519,
+            purposes: Vec::new(),
+            key_usage_policy: KeyUsagePolicy::default(),
         })
     }
 }
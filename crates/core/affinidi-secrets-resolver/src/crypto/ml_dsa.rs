@@ -9,7 +9,10 @@ use affinidi_crypto::KeyType;
 use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use rand::{TryRng, rngs::SysRng};
 
-use crate::secrets::{Secret, SecretMaterial, SecretType};
+use crate::{
+    secrets::{Secret, SecretMaterial, SecretType},
+    usage::KeyUsagePolicy,
+};
 
 fn random_kid() -> String {
     BASE64_URL_SAFE_NO_PAD.encode(SysRng.try_next_u64().unwrap().to_ne_bytes())
@@ -28,6 +31,8 @@ impl Secret {
             private_bytes: kp.private_bytes,
             public_bytes: kp.public_bytes,
             key_type: KeyType::MlDsa44,
+            purposes: Vec::new(),
+            key_usage_policy: KeyUsagePolicy::default(),
         }
     }
 
@@ -41,6 +46,8 @@ impl Secret {
             private_bytes: kp.private_bytes,
             public_bytes: kp.public_bytes,
             key_type: KeyType::MlDsa65,
+            purposes: Vec::new(),
+            key_usage_policy: KeyUsagePolicy::default(),
         }
     }
 
@@ -54,6 +61,8 @@ impl Secret {
             private_bytes: kp.private_bytes,
             public_bytes: kp.public_bytes,
             key_type: KeyType::MlDsa87,
+            purposes: Vec::new(),
+            key_usage_policy: KeyUsagePolicy::default(),
         }
     }
 }
@@ -5,6 +5,7 @@ use rand::{TryRng, rngs::SysRng};
 use crate::{
     errors::SecretsResolverError,
     secrets::{Secret, SecretMaterial, SecretType},
+    usage::KeyUsagePolicy,
 };
 
 impl Secret {
@@ -28,6 +29,8 @@ impl Secret {
             private_bytes: keypair.private_bytes,
             public_bytes: keypair.public_bytes,
             key_type: KeyType::Secp256k1,
+            purposes: Vec::new(),
+            key_usage_policy: KeyUsagePolicy::default(),
         })
     }
 }
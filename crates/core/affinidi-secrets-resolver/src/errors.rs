@@ -35,6 +35,12 @@ pub enum SecretsResolverError {
 
     #[error("Unsupported Key Type: {0}")]
     UnsupportedKeyType(String),
+
+    #[error("Key Usage Error: {0}")]
+    KeyUsage(String),
+
+    #[error("Secrets Task Error: {0}")]
+    SecretsTask(String),
 }
 
 pub type Result<T> = std::result::Result<T, SecretsResolverError>;
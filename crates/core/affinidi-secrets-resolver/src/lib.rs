@@ -9,20 +9,25 @@
  *   - SimpleSecretsResolver
  * 2. A task-based cache of Secrets used in a multi-threaded environment
  *   - ThreadedSecretsResolver
+ *
+ * Both enforce key usage separation (see [`usage`]) for secrets fetched with
+ * [`SecretsResolver::get_secret_for`], under the resolver's
+ * [`KeyUsagePolicy`].
  */
 
 use ahash::AHashMap;
+use dashmap::DashMap;
+use errors::{Result, SecretsResolverError};
 use secrets::Secret;
-use std::{cell::RefCell, time::Duration};
-use task::{SecretTaskCommand, SecretsTask};
+use std::{cell::RefCell, sync::Arc};
+use task::{SecretTaskCommand, SecretsTask, SharedSecrets};
 use tokio::{
-    sync::{
-        mpsc::{self, error::TrySendError},
-        oneshot,
-    },
+    sync::{mpsc, oneshot},
     task::JoinHandle,
 };
 use tracing::{debug, warn};
+use usage::{KeyPurpose, KeyUsagePolicy};
+use zeroize::Zeroize;
 
 // Private module - contains impl Secret blocks for generate_* methods
 mod crypto;
@@ -30,6 +35,7 @@ mod crypto;
 pub mod errors;
 pub mod secrets;
 pub mod task;
+pub mod usage;
 
 // Re-export multicodec from affinidi-encoding
 pub use affinidi_encoding::multicodec;
@@ -46,6 +52,28 @@ pub trait SecretsResolver {
     /// Get a Secret by its ID
     async fn get_secret(&self, secret_id: &str) -> Option<Secret>;
 
+    /// How a key used for the wrong purpose is handled by
+    /// [`get_secret_for`](Self::get_secret_for)
+    fn key_usage_policy(&self) -> KeyUsagePolicy {
+        KeyUsagePolicy::default()
+    }
+
+    /// Get a Secret by its ID to use for `purpose`
+    ///
+    /// Fails with [`KeyUsage`](errors::SecretsResolverError::KeyUsage) if the
+    /// secret isn't meant for `purpose` and the resolver's policy is
+    /// [`KeyUsagePolicy::Strict`]; logs a warning under
+    /// [`KeyUsagePolicy::Permissive`]. The returned secret carries the
+    /// resolver's policy, so code that checks its usage again (e.g. a Data
+    /// Integrity signer) applies the same one.
+    async fn get_secret_for(&self, secret_id: &str, purpose: KeyPurpose) -> Result<Option<Secret>> {
+        let Some(secret) = self.get_secret(secret_id).await else {
+            return Ok(None);
+        };
+        secret.check_usage(purpose, self.key_usage_policy())?;
+        Ok(Some(secret.with_key_usage_policy(self.key_usage_policy())))
+    }
+
     /// Find secrets by their key IDs
     /// # Arguments
     /// * `secret_ids` - A list of secret IDs to find
@@ -57,6 +85,21 @@ pub trait SecretsResolver {
     /// Removes the secret with the given ID
     async fn remove_secret(&self, secret_id: &str) -> Option<Secret>;
 
+    /// Removes the secret with the given ID and zeroizes its key material
+    ///
+    /// Returns true if the secret was known. Copies handed out earlier by
+    /// [`get_secret`](Self::get_secret) are zeroized when their holders drop
+    /// them; persisted copies (keyrings, files) must be shredded separately.
+    async fn shred(&self, secret_id: &str) -> bool {
+        match self.remove_secret(secret_id).await {
+            Some(mut secret) => {
+                secret.zeroize();
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Returns the number of known secrets
     async fn len(&self) -> usize;
 
@@ -69,6 +112,7 @@ pub trait SecretsResolver {
 /// Helps with loading and working with DID Secrets
 pub struct SimpleSecretsResolver {
     known_secrets: RefCell<AHashMap<String, Secret>>,
+    key_usage_policy: KeyUsagePolicy,
 }
 
 impl SimpleSecretsResolver {
@@ -88,12 +132,20 @@ impl SimpleSecretsResolver {
     pub async fn new(known_secrets: &[Secret]) -> Self {
         let secrets = SimpleSecretsResolver {
             known_secrets: RefCell::new(AHashMap::new()),
+            key_usage_policy: KeyUsagePolicy::default(),
         };
 
         secrets.insert_vec(known_secrets).await;
 
         secrets
     }
+
+    /// Set how keys used for the wrong purpose are handled
+    /// ([`KeyUsagePolicy::Permissive`] by default)
+    pub fn with_key_usage_policy(mut self, policy: KeyUsagePolicy) -> Self {
+        self.key_usage_policy = policy;
+        self
+    }
 }
 
 impl SecretsResolver for SimpleSecretsResolver {
@@ -114,6 +166,10 @@ impl SecretsResolver for SimpleSecretsResolver {
         self.known_secrets.borrow().get(secret_id).cloned()
     }
 
+    fn key_usage_policy(&self) -> KeyUsagePolicy {
+        self.key_usage_policy
+    }
+
     async fn find_secrets(&self, secret_ids: &[String]) -> Vec<String> {
         secret_ids
             .iter()
@@ -145,28 +201,128 @@ impl SecretsResolver for SimpleSecretsResolver {
 // *****************************************************************************************************
 
 /// Multithreaded Affinidi Secrets Resolver
-/// Operates as a common task, using channels to communicate without locks
+///
+/// Secrets live in a sharded concurrent map shared with the Secrets Task. Reads are served
+/// straight from the map without a channel round-trip, so `get_secret` never times out under
+/// load. Writes are sent to the Secrets Task, which applies them in order; they return once
+/// the task has applied them, so a resolver always reads its own writes.
 #[derive(Clone)]
 pub struct ThreadedSecretsResolver {
     tx: mpsc::Sender<SecretTaskCommand>,
+    secrets: SharedSecrets,
+    key_usage_policy: KeyUsagePolicy,
 }
 
 impl ThreadedSecretsResolver {
+    /// Build a resolver over a [`SecretsTask`] the caller runs itself (e.g. under a
+    /// supervisor via [`SecretsTask::serve`])
+    ///
+    /// # Arguments
+    /// * `task` - The task whose shared secrets map this resolver reads
+    /// * `tx` - The command channel returned alongside `task` by [`SecretsTask::new`]
+    pub fn from_task(task: &SecretsTask, tx: mpsc::Sender<SecretTaskCommand>) -> Self {
+        ThreadedSecretsResolver {
+            tx,
+            secrets: task.secrets(),
+            key_usage_policy: KeyUsagePolicy::default(),
+        }
+    }
+
+    /// Instantiate a new ThreadedSecretsResolver
+    ///
+    /// # Arguments
+    /// * `secrets_task_tx` - Channel to an existing Secrets Task. If `None`, a new task is started
+    ///
+    /// # Returns
+    /// The resolver, and the JoinHandle of the task if one was started
+    ///
+    /// If the existing Secrets Task has stopped, a warning is logged and the resolver holds no
+    /// secrets. Use [`attach`](Self::attach) to get an error instead.
     pub async fn new(
         secrets_task_tx: Option<mpsc::Sender<SecretTaskCommand>>,
     ) -> (Self, Option<JoinHandle<()>>) {
         if let Some(tx) = secrets_task_tx {
-            (ThreadedSecretsResolver { tx }, None)
+            match Self::attach(tx.clone()).await {
+                Ok(resolver) => (resolver, None),
+                Err(e) => {
+                    warn!("{e}");
+                    (
+                        ThreadedSecretsResolver {
+                            tx,
+                            secrets: Arc::new(DashMap::with_hasher(ahash::RandomState::new())),
+                            key_usage_policy: KeyUsagePolicy::default(),
+                        },
+                        None,
+                    )
+                }
+            }
         } else {
             let (task, tx) = SecretsTask::new();
-            (ThreadedSecretsResolver { tx }, Some(task.start().await))
+            let resolver = Self::from_task(&task, tx);
+            (resolver, Some(task.start().await))
         }
     }
 
+    /// Attach to an existing Secrets Task, sharing its secrets map
+    ///
+    /// Waits for the task to hand over its map, so the task must be running (or started
+    /// later) for this to return.
+    ///
+    /// # Errors
+    /// [`SecretsResolverError::SecretsTask`] if the Secrets Task has stopped
+    pub async fn attach(secrets_task_tx: mpsc::Sender<SecretTaskCommand>) -> Result<Self> {
+        let (tx, rx) = oneshot::channel();
+        secrets_task_tx
+            .send(SecretTaskCommand::GetSharedSecrets { tx })
+            .await
+            .map_err(|_| {
+                SecretsResolverError::SecretsTask("Secrets Task has been closed".into())
+            })?;
+        let secrets = rx.await.map_err(|_| {
+            SecretsResolverError::SecretsTask(
+                "Secrets Task closed before returning its secrets map".into(),
+            )
+        })?;
+
+        Ok(ThreadedSecretsResolver {
+            tx: secrets_task_tx,
+            secrets,
+            key_usage_policy: KeyUsagePolicy::default(),
+        })
+    }
+
+    /// Set how keys used for the wrong purpose are handled
+    /// ([`KeyUsagePolicy::Permissive`] by default). Clones made afterwards
+    /// share the policy; the Secrets Task does not.
+    pub fn with_key_usage_policy(mut self, policy: KeyUsagePolicy) -> Self {
+        self.key_usage_policy = policy;
+        self
+    }
+
     /// Stops the Secrets Task
     pub async fn stop(&self) {
         let _ = self.tx.send(SecretTaskCommand::Terminate).await;
     }
+
+    /// Sends a write to the Secrets Task and waits until it has been applied
+    async fn _write(&self, command: SecretTaskCommand) {
+        if self.tx.send(command).await.is_err() {
+            warn!("Secrets Task has been closed");
+            return;
+        }
+
+        // The task handles commands in order: once it answers, the write is in the map
+        let (tx, rx) = oneshot::channel();
+        if self
+            .tx
+            .send(SecretTaskCommand::SecretsStored { tx })
+            .await
+            .is_err()
+            || rx.await.is_err()
+        {
+            warn!("Secrets Task closed before applying a write");
+        }
+    }
 }
 
 impl SecretsResolver for ThreadedSecretsResolver {
@@ -177,139 +333,207 @@ impl SecretsResolver for ThreadedSecretsResolver {
     async fn insert_vec(&self, secrets: &[Secret]) {
         for secret in secrets {
             debug!("Adding secret ({})", secret.id);
-            match self.tx.try_send(SecretTaskCommand::AddSecret {
-                secret: secret.to_owned(),
-            }) {
-                Ok(_) => (),
-                Err(TrySendError::Closed(_)) => {
-                    warn!("Secrets Task has been closed");
-                }
-                Err(TrySendError::Full(_)) => {
-                    warn!("Secrets Task channel is full");
-                }
-            }
         }
+        self._write(SecretTaskCommand::AddSecrets {
+            secrets: secrets.to_vec(),
+        })
+        .await;
     }
 
     async fn get_secret(&self, secret_id: &str) -> Option<Secret> {
-        let (tx, rx) = oneshot::channel();
-        match self.tx.try_send(SecretTaskCommand::GetSecret {
-            key_id: secret_id.to_string(),
-            tx,
-        }) {
-            Ok(_) => (),
-            Err(TrySendError::Closed(_)) => {
-                warn!("Secrets Task has been closed");
-                return None;
-            }
-            Err(TrySendError::Full(_)) => {
-                warn!("Secrets Task channel is full");
-                return None;
-            }
-        }
-
-        let timeout = tokio::time::sleep(Duration::from_secs(1));
-        tokio::pin!(timeout);
+        self.secrets.get(secret_id).map(|s| s.value().clone())
+    }
 
-        tokio::select! {
-            _ = &mut timeout => None,
-            rx = rx => rx.unwrap_or(None)
-        }
+    fn key_usage_policy(&self) -> KeyUsagePolicy {
+        self.key_usage_policy
     }
 
     async fn find_secrets(&self, secret_ids: &[String]) -> Vec<String> {
-        let (tx, rx) = oneshot::channel();
-        match self.tx.try_send(SecretTaskCommand::FindSecrets {
-            keys: secret_ids.to_vec(),
-            tx,
-        }) {
-            Ok(_) => (),
-            Err(TrySendError::Closed(_)) => {
-                warn!("Secrets Task has been closed");
-                return vec![];
-            }
-            Err(TrySendError::Full(_)) => {
-                warn!("Secrets Task channel is full");
-                return vec![];
-            }
-        }
-
-        let timeout = tokio::time::sleep(Duration::from_secs(1));
-        tokio::pin!(timeout);
-
-        tokio::select! {
-            _ = &mut timeout => vec![],
-            rx = rx => rx.unwrap_or(vec![])
-        }
+        secret_ids
+            .iter()
+            .filter(|sid| self.secrets.contains_key(sid.as_str()))
+            .cloned()
+            .collect()
     }
 
-    /// This implementation will always return None!
     async fn remove_secret(&self, secret_id: &str) -> Option<Secret> {
-        match self.tx.try_send(SecretTaskCommand::RemoveSecret {
+        let secret = self.get_secret(secret_id).await;
+        self._write(SecretTaskCommand::RemoveSecret {
             key_id: secret_id.to_string(),
-        }) {
-            Ok(_) => (),
-            Err(TrySendError::Closed(_)) => {
-                warn!("Secrets Task has been closed");
-            }
-            Err(TrySendError::Full(_)) => {
-                warn!("Secrets Task channel is full");
-            }
-        }
-
-        None
+        })
+        .await;
+        secret
     }
 
     async fn len(&self) -> usize {
-        let (tx, rx) = oneshot::channel();
-        match self.tx.try_send(SecretTaskCommand::SecretsStored { tx }) {
-            Ok(_) => (),
-            Err(TrySendError::Closed(_)) => {
-                warn!("Secrets Task has been closed");
-                return 0;
-            }
-            Err(TrySendError::Full(_)) => {
-                warn!("Secrets Task channel is full");
-                return 0;
-            }
-        }
+        self.secrets.len()
+    }
 
-        let timeout = tokio::time::sleep(Duration::from_secs(1));
-        tokio::pin!(timeout);
+    async fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+}
 
-        tokio::select! {
-            _ = &mut timeout => 0,
-            rx = rx => {
-                rx.unwrap_or(0)
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(id: &str) -> Secret {
+        Secret::generate_ed25519(Some(id), None)
     }
 
-    async fn is_empty(&self) -> bool {
-        let (tx, rx) = oneshot::channel();
-        match self.tx.try_send(SecretTaskCommand::SecretsStored { tx }) {
-            Ok(_) => (),
-            Err(TrySendError::Closed(_)) => {
-                warn!("Secrets Task has been closed");
-                return true;
-            }
-            Err(TrySendError::Full(_)) => {
-                warn!("Secrets Task channel is full");
-                return true;
-            }
-        }
+    #[tokio::test]
+    async fn threaded_resolver_reads_own_writes() {
+        let (resolver, _task) = ThreadedSecretsResolver::new(None).await;
+        assert!(resolver.is_empty().await);
+
+        resolver
+            .insert_vec(&[secret("did:example:a#1"), secret("did:example:a#2")])
+            .await;
+        assert_eq!(resolver.len().await, 2);
+        assert!(resolver.get_secret("did:example:a#1").await.is_some());
+        assert_eq!(
+            resolver
+                .find_secrets(&["did:example:a#2".to_string(), "did:example:b#1".to_string()])
+                .await,
+            vec!["did:example:a#2".to_string()]
+        );
+
+        assert!(resolver.remove_secret("did:example:a#1").await.is_some());
+        assert!(resolver.get_secret("did:example:a#1").await.is_none());
+    }
 
-        let timeout = tokio::time::sleep(Duration::from_secs(1));
-        tokio::pin!(timeout);
+    #[tokio::test]
+    async fn shred_removes_from_every_resolver() {
+        let simple = SimpleSecretsResolver::new(&[secret("did:example:a#1")]).await;
+        assert!(simple.shred("did:example:a#1").await);
+        assert!(!simple.shred("did:example:a#1").await);
+        assert!(simple.is_empty().await);
+
+        let (threaded, _task) = ThreadedSecretsResolver::new(None).await;
+        threaded.insert(secret("did:example:a#1")).await;
+        assert!(threaded.shred("did:example:a#1").await);
+        assert!(threaded.get_secret("did:example:a#1").await.is_none());
+    }
 
-        tokio::select! {
-            _ = &mut timeout => true,
-            rx = rx => {
-                match rx {
-                    Ok(length) => length == 0,
-                    Err(_) => true,
-                }
-            }
-        }
+    #[tokio::test]
+    async fn get_secret_for_enforces_key_usage() {
+        let signing = secret("did:example:a#1");
+        let converted = signing.to_x25519().unwrap();
+        let p256 = Secret::generate_p256(Some("did:example:a#3"), None)
+            .unwrap()
+            .with_purposes(&[KeyPurpose::KeyAgreement]);
+        let mut agreement = converted.clone();
+        agreement.id = "did:example:a#2".into();
+
+        let (strict, _task) = ThreadedSecretsResolver::new(None).await;
+        let strict = strict.with_key_usage_policy(KeyUsagePolicy::Strict);
+        strict.insert_vec(&[signing, agreement, p256]).await;
+
+        let fetched = strict
+            .get_secret_for("did:example:a#1", KeyPurpose::Signing)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.key_usage_policy(), KeyUsagePolicy::Strict);
+        assert!(matches!(
+            strict
+                .get_secret_for("did:example:a#2", KeyPurpose::Signing)
+                .await,
+            Err(errors::SecretsResolverError::KeyUsage(_))
+        ));
+        assert!(
+            strict
+                .get_secret_for("did:example:a#3", KeyPurpose::Signing)
+                .await
+                .is_err()
+        );
+        assert!(
+            strict
+                .get_secret_for("did:example:a#3", KeyPurpose::KeyAgreement)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            strict
+                .get_secret_for("did:example:b#1", KeyPurpose::Signing)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Permissive only warns
+        let permissive = SimpleSecretsResolver::new(&[converted]).await;
+        assert!(
+            permissive
+                .get_secret_for("did:example:a#1", KeyPurpose::Signing)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn threaded_resolver_shares_existing_task() {
+        let (task, tx) = SecretsTask::new();
+        let _handle = task.start().await;
+
+        let (first, _) = ThreadedSecretsResolver::new(Some(tx.clone())).await;
+        let second = ThreadedSecretsResolver::attach(tx.clone()).await.unwrap();
+        first.insert(secret("did:example:a#1")).await;
+        assert!(second.get_secret("did:example:a#1").await.is_some());
+
+        // Writes submitted over the channel land in the same map
+        tx.send(SecretTaskCommand::AddSecret {
+            secret: secret("did:example:b#1"),
+        })
+        .await
+        .unwrap();
+        let (ack_tx, ack_rx) = oneshot::channel();
+        tx.send(SecretTaskCommand::SecretsStored { tx: ack_tx })
+            .await
+            .unwrap();
+        assert_eq!(ack_rx.await.unwrap(), 2);
+        assert!(first.get_secret("did:example:b#1").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn attaching_to_a_stopped_task_is_an_error() {
+        let (task, tx) = SecretsTask::new();
+        drop(task);
+
+        assert!(matches!(
+            ThreadedSecretsResolver::attach(tx.clone()).await,
+            Err(errors::SecretsResolverError::SecretsTask(_))
+        ));
+
+        // new() still hands back a resolver, which holds nothing
+        let (resolver, handle) = ThreadedSecretsResolver::new(Some(tx)).await;
+        assert!(handle.is_none());
+        resolver.insert(secret("did:example:a#1")).await;
+        assert!(resolver.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn serve_can_be_restarted_without_losing_secrets() {
+        let (task, tx) = SecretsTask::new();
+        let resolver = ThreadedSecretsResolver::from_task(&task, tx.clone());
+
+        // First run ends on Terminate; a second run picks up the same channel
+        let first = tokio::spawn({
+            let task = task.clone();
+            async move { task.serve().await }
+        });
+        resolver.insert(secret("did:example:a#1")).await;
+        tx.send(SecretTaskCommand::Terminate).await.unwrap();
+        first.await.unwrap();
+
+        tokio::spawn(async move { task.serve().await });
+        let (ack_tx, ack_rx) = oneshot::channel();
+        tx.send(SecretTaskCommand::SecretsStored { tx: ack_tx })
+            .await
+            .unwrap();
+        assert_eq!(ack_rx.await.unwrap(), 1);
     }
 }
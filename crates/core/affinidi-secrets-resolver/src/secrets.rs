@@ -1,6 +1,11 @@
 /*!
 Handles Secrets - mainly used for internal representation and for saving to files (should always be encrypted)
 
+A [`Secret`] serializes without its private key material, so one that ends up
+in a log line or a debug dump by accident doesn't leak the key. Code that
+stores secrets on purpose serializes [`Secret::expose_secret`], or marks the
+field with `#[serde(serialize_with = "serialize_with_private")]`.
+
 */
 
 #[cfg(feature = "slh-dsa")]
@@ -16,12 +21,14 @@ use crate::{
         ED25519_PRIV, ED25519_PUB, MultiEncoded, MultiEncodedBuf, P256_PRIV, P256_PUB, P384_PRIV,
         P384_PUB, P521_PRIV, P521_PUB, SECP256K1_PRIV, SECP256K1_PUB, X25519_PRIV, X25519_PUB,
     },
+    usage::{KeyPurpose, KeyUsagePolicy},
 };
 pub use affinidi_crypto::KeyType;
 use affinidi_crypto::{JWK, Params};
+pub use affinidi_crypto::{SerializePrivate, SerializeWithPrivate, serialize_with_private};
 use base58::ToBase58;
 use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer, ser::Error as _};
 use serde_json::{Value, json};
 use sha2::{Digest, Sha256};
 use tracing::warn;
@@ -38,10 +45,16 @@ struct SecretShadow {
     type_: SecretType,
     #[serde(flatten)]
     secret_material: SecretMaterial,
+    #[serde(default)]
+    purposes: Vec<KeyPurpose>,
 }
 
 /// Public Structure that manages everything to do with Keys and Secrets
-#[derive(Clone, Deserialize, Serialize, Zeroize, ZeroizeOnDrop)]
+///
+/// Serializes without the private key: a JWK secret is written as its
+/// `publicKeyJwk`, and other material is left out. Use
+/// [`Secret::expose_secret`] to write the private key.
+#[derive(Clone, Deserialize, Zeroize, ZeroizeOnDrop)]
 #[serde(try_from = "SecretShadow")]
 pub struct Secret {
     /// A key ID identifying a secret (private key).
@@ -66,6 +79,56 @@ pub struct Secret {
     /// What crypto type is this secret
     #[serde(skip)]
     pub(crate) key_type: KeyType,
+
+    /// Declared purposes; derived from the type and curve when empty
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub(crate) purposes: Vec<KeyPurpose>,
+
+    /// How using this secret for a purpose it isn't meant for is treated
+    #[serde(skip)]
+    #[zeroize(skip)]
+    pub(crate) key_usage_policy: KeyUsagePolicy,
+}
+
+/// The wire form of a [`Secret`]. `secret_material` is a [`PublicMaterial`]
+/// or a [`PrivateMaterial`].
+#[derive(Serialize)]
+struct SecretView<'a, M> {
+    id: &'a str,
+    #[serde(rename = "type")]
+    type_: &'a SecretType,
+    #[serde(flatten)]
+    secret_material: Option<M>,
+    #[serde(skip_serializing_if = "<[KeyPurpose]>::is_empty")]
+    purposes: &'a [KeyPurpose],
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        SecretView {
+            id: &self.id,
+            type_: &self.type_,
+            secret_material: self.secret_material.public(),
+            purposes: &self.purposes,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl SerializePrivate for Secret {
+    fn serialize_private<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        SecretView {
+            id: &self.id,
+            type_: &self.type_,
+            secret_material: Some(self.secret_material.private()),
+            purposes: &self.purposes,
+        }
+        .serialize(serializer)
+    }
 }
 
 impl std::fmt::Debug for Secret {
@@ -74,6 +137,8 @@ impl std::fmt::Debug for Secret {
             .field("id", &self.id)
             .field("type_", &self.type_)
             .field("key_type", &self.key_type)
+            .field("purposes", &self.purposes)
+            .field("key_usage_policy", &self.key_usage_policy)
             .field("secret_material", &"[REDACTED]")
             .field("private_bytes", &"[REDACTED]")
             .field("public_bytes", &"[REDACTED]")
@@ -87,20 +152,23 @@ impl TryFrom<SecretShadow> for Secret {
     type Error = SecretsResolverError;
 
     fn try_from(shadow: SecretShadow) -> Result<Self> {
-        match shadow.secret_material {
+        let secret = match shadow.secret_material {
             SecretMaterial::JWK(jwk) => {
                 let mut secret = Secret::from_jwk(&jwk)?;
                 secret.id = shadow.id;
                 secret.type_ = shadow.type_;
-                Ok(secret)
+                secret
             }
             SecretMaterial::PrivateKeyMultibase(private) => {
-                Secret::from_multibase(&private, Some(&shadow.id))
+                Secret::from_multibase(&private, Some(&shadow.id))?
             }
-            _ => Err(SecretsResolverError::KeyError(
-                "Unsupported secret material type".into(),
-            )),
-        }
+            _ => {
+                return Err(SecretsResolverError::KeyError(
+                    "Unsupported secret material type".into(),
+                ));
+            }
+        };
+        Ok(secret.with_purposes(&shadow.purposes))
     }
 }
 
@@ -134,6 +202,12 @@ fn compress_ec_point(
 }
 
 impl Secret {
+    /// Serializes this secret including its private key, for the places
+    /// that store secrets on purpose (an encrypted file, the OS keyring).
+    pub fn expose_secret(&self) -> SerializeWithPrivate<'_, Self> {
+        SerializeWithPrivate::new(self)
+    }
+
     /// Helper function to get raw bytes
     fn convert_to_raw(input: &str) -> Result<Vec<u8>> {
         BASE64_URL_SAFE_NO_PAD
@@ -160,6 +234,8 @@ impl Secret {
                     )?)?,
                     public_bytes: x,
                     key_type: KeyType::try_from(params.curve.as_str())?,
+                    purposes: Vec::new(),
+                    key_usage_policy: KeyUsagePolicy::default(),
                 })
             }
             Params::OKP(params) => Ok(Secret {
@@ -171,6 +247,8 @@ impl Secret {
                 )?)?,
                 public_bytes: Secret::convert_to_raw(&params.x)?,
                 key_type: KeyType::try_from(params.curve.as_str())?,
+                purposes: Vec::new(),
+                key_usage_policy: KeyUsagePolicy::default(),
             }),
             // `Params` is `#[non_exhaustive]`; reject unknown future kinds.
             _ => Err(SecretsResolverError::KeyError(
@@ -426,6 +504,92 @@ impl Secret {
         self.key_type
     }
 
+    /// Declare what this secret may be used for, typically from the
+    /// verification relationships its key appears under in the DID document.
+    /// An empty slice clears the declaration.
+    pub fn with_purposes(mut self, purposes: &[KeyPurpose]) -> Self {
+        self.set_purposes(purposes);
+        self
+    }
+
+    /// In-place form of [`with_purposes`](Self::with_purposes).
+    pub fn set_purposes(&mut self, purposes: &[KeyPurpose]) {
+        self.purposes = purposes.to_vec();
+    }
+
+    /// Set how using this secret for a purpose it isn't meant for is treated,
+    /// e.g. by signers that check [`KeyPurpose::Signing`] before signing.
+    /// [`SecretsResolver::get_secret_for`](crate::SecretsResolver::get_secret_for)
+    /// sets it to the resolver's policy.
+    pub fn with_key_usage_policy(mut self, policy: KeyUsagePolicy) -> Self {
+        self.set_key_usage_policy(policy);
+        self
+    }
+
+    /// In-place form of [`with_key_usage_policy`](Self::with_key_usage_policy).
+    pub fn set_key_usage_policy(&mut self, policy: KeyUsagePolicy) {
+        self.key_usage_policy = policy;
+    }
+
+    /// How using this secret for a purpose it isn't meant for is treated.
+    /// Default: [`KeyUsagePolicy::Permissive`]
+    pub fn key_usage_policy(&self) -> KeyUsagePolicy {
+        self.key_usage_policy
+    }
+
+    /// What this secret may be used for: the declared purposes, or when none
+    /// were declared, those its type and curve allow.
+    pub fn purposes(&self) -> Vec<KeyPurpose> {
+        use KeyPurpose::{KeyAgreement, Signing};
+
+        if !self.purposes.is_empty() {
+            return self.purposes.clone();
+        }
+        match self.type_ {
+            SecretType::X25519KeyAgreementKey2019 | SecretType::X25519KeyAgreementKey2020 => {
+                return vec![KeyAgreement];
+            }
+            SecretType::Ed25519VerificationKey2018
+            | SecretType::Ed25519VerificationKey2020
+            | SecretType::EcdsaSecp256k1VerificationKey2019 => return vec![Signing],
+            _ => {}
+        }
+        match self.key_type {
+            KeyType::X25519 => vec![KeyAgreement],
+            KeyType::P256 | KeyType::P384 | KeyType::P521 | KeyType::Secp256k1 => {
+                vec![Signing, KeyAgreement]
+            }
+            _ => vec![Signing],
+        }
+    }
+
+    /// Check this secret may be used for `purpose`. A mismatch is an error
+    /// under [`KeyUsagePolicy::Strict`] and a warning otherwise.
+    pub fn check_usage(&self, purpose: KeyPurpose, policy: KeyUsagePolicy) -> Result<()> {
+        let purposes = self.purposes();
+        if purposes.contains(&purpose) {
+            return Ok(());
+        }
+        let reason = format!(
+            "key ({}) is for {} and must not be used for {purpose}",
+            self.id,
+            purposes
+                .iter()
+                .map(KeyPurpose::to_string)
+                .collect::<Vec<_>>()
+                .join(" and "),
+        );
+        match policy {
+            KeyUsagePolicy::Strict => Err(SecretsResolverError::KeyUsage(reason)),
+            KeyUsagePolicy::Permissive => {
+                warn!("{reason}");
+                Ok(())
+            }
+        }
+    }
+
+    /// Convert an Ed25519 secret to the X25519 secret for the same key. The
+    /// result may only be used for key agreement.
     pub fn to_x25519(&self) -> Result<Secret> {
         if self.key_type != KeyType::Ed25519 {
             warn!(
@@ -455,7 +619,7 @@ impl Secret {
                 "x": public
             });
 
-            Secret::from_str(&self.id, &jwk)
+            Ok(Secret::from_str(&self.id, &jwk)?.with_purposes(&[KeyPurpose::KeyAgreement]))
         }
     }
 }
@@ -476,7 +640,11 @@ pub enum SecretType {
 // KeyType is re-exported from affinidi_crypto
 
 /// Represents secret crypto material.
-#[derive(Clone, Deserialize, Serialize, Zeroize)]
+///
+/// Only JWK material serializes by default, as its public `publicKeyJwk`; the
+/// other forms are nothing but a private key and refuse to serialize. Use
+/// [`SecretMaterial::expose_secret`] to write the private key.
+#[derive(Clone, Deserialize, Zeroize)]
 #[serde(rename_all = "camelCase")]
 pub enum SecretMaterial {
     #[serde(rename = "privateKeyJwk")]
@@ -495,6 +663,78 @@ pub enum SecretMaterial {
     },
 }
 
+/// The public part of a [`SecretMaterial`].
+#[derive(Serialize)]
+enum PublicMaterial<'a> {
+    #[serde(rename = "publicKeyJwk")]
+    Jwk(&'a JWK),
+}
+
+/// A [`SecretMaterial`] with its private key, in the shape it deserializes
+/// from.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum PrivateMaterial<'a> {
+    #[serde(rename = "privateKeyJwk")]
+    Jwk(SerializeWithPrivate<'a, JWK>),
+    PrivateKeyMultibase(&'a str),
+    Base58 {
+        private_key_base58: &'a str,
+    },
+    Multibase {
+        private_key_multibase: &'a str,
+    },
+}
+
+impl SecretMaterial {
+    /// Serializes this material including the private key.
+    pub fn expose_secret(&self) -> SerializeWithPrivate<'_, Self> {
+        SerializeWithPrivate::new(self)
+    }
+
+    fn public(&self) -> Option<PublicMaterial<'_>> {
+        match self {
+            SecretMaterial::JWK(jwk) => Some(PublicMaterial::Jwk(jwk)),
+            _ => None,
+        }
+    }
+
+    fn private(&self) -> PrivateMaterial<'_> {
+        match self {
+            SecretMaterial::JWK(jwk) => PrivateMaterial::Jwk(jwk.expose_secret()),
+            SecretMaterial::PrivateKeyMultibase(key) => PrivateMaterial::PrivateKeyMultibase(key),
+            SecretMaterial::Base58 { private_key_base58 } => {
+                PrivateMaterial::Base58 { private_key_base58 }
+            }
+            SecretMaterial::Multibase {
+                private_key_multibase,
+            } => PrivateMaterial::Multibase {
+                private_key_multibase,
+            },
+        }
+    }
+}
+
+impl Serialize for SecretMaterial {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.public() {
+            Some(public) => public.serialize(serializer),
+            None => Err(S::Error::custom(
+                "secret material without a public part only serializes through expose_secret()",
+            )),
+        }
+    }
+}
+
+impl SerializePrivate for SecretMaterial {
+    fn serialize_private<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.private().serialize(serializer)
+    }
+}
+
 impl std::fmt::Debug for SecretMaterial {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Every variant carries private key bytes; never print them.
@@ -578,6 +818,42 @@ mod tests {
         assert!(secret.is_ok());
     }
 
+    #[test]
+    fn serialize_omits_private_key_unless_exposed() {
+        let secret = Secret::generate_ed25519(Some("did:example:alice#key-1"), None);
+        let d = BASE64_URL_SAFE_NO_PAD.encode(&secret.private_bytes);
+
+        let public = serde_json::to_string(&secret).unwrap();
+        assert!(!public.contains(&d));
+        assert!(public.contains("publicKeyJwk"));
+        // A redacted secret can't be mistaken for a usable one
+        assert!(serde_json::from_str::<Secret>(&public).is_err());
+
+        let private = serde_json::to_string(&secret.expose_secret()).unwrap();
+        assert!(private.contains(&d));
+        let restored: Secret = serde_json::from_str(&private).unwrap();
+        assert_eq!(restored.private_bytes, secret.private_bytes);
+    }
+
+    #[test]
+    fn multibase_material_serializes_only_when_exposed() {
+        let mut secret = Secret::generate_ed25519(Some("did:example:alice#key-1"), None);
+        let private_key = secret.get_private_keymultibase().unwrap();
+        secret.secret_material = super::SecretMaterial::PrivateKeyMultibase(private_key.clone());
+
+        let public = serde_json::to_value(&secret).unwrap();
+        assert_eq!(
+            public,
+            json!({"id": "did:example:alice#key-1", "type": "JsonWebKey2020"})
+        );
+        assert!(serde_json::to_value(&secret.secret_material).is_err());
+
+        let private = serde_json::to_value(secret.expose_secret()).unwrap();
+        assert_eq!(private["privateKeyMultibase"], private_key);
+        let restored: Secret = serde_json::from_value(private).unwrap();
+        assert_eq!(restored.private_bytes, secret.private_bytes);
+    }
+
     #[test]
     fn from_multiencode_ed25519() {
         let seed = BASE64_URL_SAFE_NO_PAD
@@ -942,4 +1218,43 @@ mod tests {
             secret.get_private_keymultibase().unwrap()
         );
     }
+
+    #[test]
+    fn purposes_are_declared_or_derived() {
+        use crate::usage::{KeyPurpose, KeyUsagePolicy};
+
+        let ed25519 = Secret::generate_ed25519(Some("did:example:a#1"), None);
+        assert_eq!(ed25519.purposes(), [KeyPurpose::Signing]);
+        assert_eq!(
+            ed25519.to_x25519().unwrap().purposes(),
+            [KeyPurpose::KeyAgreement]
+        );
+
+        let p256 = Secret::generate_p256(Some("did:example:a#2"), None).unwrap();
+        assert_eq!(
+            p256.purposes(),
+            [KeyPurpose::Signing, KeyPurpose::KeyAgreement]
+        );
+        let p256 = p256.with_purposes(&[KeyPurpose::Signing]);
+        assert!(
+            p256.check_usage(KeyPurpose::KeyAgreement, KeyUsagePolicy::Strict)
+                .is_err()
+        );
+        assert!(
+            p256.check_usage(KeyPurpose::KeyAgreement, KeyUsagePolicy::Permissive)
+                .is_ok()
+        );
+
+        // Declared purposes survive serialisation; undeclared ones aren't written
+        let json = serde_json::to_value(p256.expose_secret()).unwrap();
+        assert_eq!(json["purposes"], json!(["signing"]));
+        let restored: Secret = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.purposes(), [KeyPurpose::Signing]);
+        assert!(
+            serde_json::to_value(&ed25519)
+                .unwrap()
+                .get("purposes")
+                .is_none()
+        );
+    }
 }
@@ -1,19 +1,32 @@
 /*!
  * In multi-threaded applications, it is suggested to use a separate task to handle secrets
  *
- * This removes the need for locks and copying secrets around
+ * Secrets are held in a sharded concurrent map ([`SharedSecrets`]) that readers access
+ * directly, so lookups never wait on a channel round-trip. Every write, including those
+ * made through [`ThreadedSecretsResolver`](crate::ThreadedSecretsResolver), goes through
+ * the task's command channel and is applied in order.
+ *
+ * [`SecretsTask::serve`] borrows the task instead of consuming it, so a supervisor
+ * can restart the loop after a panic without losing the command channel or the
+ * secrets already stored.
  */
 
 use crate::secrets::Secret;
-use ahash::AHashMap;
+use dashmap::DashMap;
+use std::sync::Arc;
 use tokio::{
-    sync::{mpsc, oneshot},
+    sync::{Mutex, mpsc, oneshot},
     task::JoinHandle,
 };
 use tracing::{debug, warn};
 
+/// Sharded concurrent map of secrets, keyed by secret ID
+pub type SharedSecrets = Arc<DashMap<String, Secret, ahash::RandomState>>;
+
+#[derive(Clone)]
 pub struct SecretsTask {
-    channel_rx: mpsc::Receiver<SecretTaskCommand>,
+    channel_rx: Arc<Mutex<mpsc::Receiver<SecretTaskCommand>>>,
+    secrets: SharedSecrets,
 }
 
 /// Secrets Task Commands
@@ -42,6 +55,9 @@ pub enum SecretTaskCommand {
     /// Number of secrets stored
     SecretsStored { tx: oneshot::Sender<usize> },
 
+    /// Get a handle to the shared secrets map so reads can bypass the task
+    GetSharedSecrets { tx: oneshot::Sender<SharedSecrets> },
+
     /// Terminate the Secrets Task
     Terminate,
 }
@@ -51,7 +67,18 @@ impl SecretsTask {
     pub fn new() -> (Self, mpsc::Sender<SecretTaskCommand>) {
         let (tx, rx) = mpsc::channel(32);
 
-        (SecretsTask { channel_rx: rx }, tx)
+        (
+            SecretsTask {
+                channel_rx: Arc::new(Mutex::new(rx)),
+                secrets: Arc::new(DashMap::with_hasher(ahash::RandomState::new())),
+            },
+            tx,
+        )
+    }
+
+    /// Handle to the shared secrets map managed by this task
+    pub fn secrets(&self) -> SharedSecrets {
+        self.secrets.clone()
     }
 
     /// Start the Secrets Task
@@ -62,27 +89,28 @@ impl SecretsTask {
     }
 
     /// Main loop of the Secrets Task
-    async fn run(mut self) {
-        let mut secrets_cache: AHashMap<String, Secret> = AHashMap::new();
+    async fn run(self) {
+        self.serve().await;
+
+        debug!("Exiting Secrets Task");
+    }
 
+    /// Process commands until [`SecretTaskCommand::Terminate`] is received or the
+    /// channel closes. Use this instead of [`start`](Self::start) when spawning
+    /// the task under a supervisor: it can be called again after a panic, and
+    /// picks up the same channel and secrets.
+    pub async fn serve(&self) {
+        let mut channel_rx = self.channel_rx.lock().await;
         loop {
-            tokio::select! {
-                    msg = self.channel_rx.recv() => {
-                        if _handle_msg(&mut secrets_cache, msg) {
-                            break;
-                        }
-                }
-            } // End of loop
+            let msg = channel_rx.recv().await;
+            if _handle_msg(&self.secrets, msg) {
+                break;
+            }
         }
-
-        debug!("Exiting Secrets Task");
     }
 }
 
-fn _handle_msg(
-    secrets_cache: &mut AHashMap<String, Secret>,
-    msg: Option<SecretTaskCommand>,
-) -> bool {
+fn _handle_msg(secrets_cache: &SharedSecrets, msg: Option<SecretTaskCommand>) -> bool {
     let mut exit_flag = false;
     match msg {
         Some(SecretTaskCommand::AddSecret { secret }) => {
@@ -97,7 +125,7 @@ fn _handle_msg(
             secrets_cache.remove(&key_id);
         }
         Some(SecretTaskCommand::GetSecret { key_id, tx }) => {
-            let _ = tx.send(secrets_cache.get(&key_id).cloned());
+            let _ = tx.send(secrets_cache.get(&key_id).map(|s| s.value().clone()));
         }
         Some(SecretTaskCommand::FindSecrets { keys, tx }) => {
             let _ = tx.send(
@@ -110,6 +138,9 @@ fn _handle_msg(
         Some(SecretTaskCommand::SecretsStored { tx }) => {
             let _ = tx.send(secrets_cache.len());
         }
+        Some(SecretTaskCommand::GetSharedSecrets { tx }) => {
+            let _ = tx.send(secrets_cache.clone());
+        }
         Some(SecretTaskCommand::Terminate) => {
             debug!("Terminating Secrets Task");
             exit_flag = true;
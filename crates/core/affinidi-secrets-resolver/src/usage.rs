@@ -0,0 +1,75 @@
+/*!
+ * Key usage separation
+ *
+ * A key should do one job: sign, or agree keys. Using one key for both —
+ * signing with an X25519 key converted from Ed25519, or running ECDH with a
+ * P-256 key published under `authentication` — weakens the guarantees of
+ * each and is rarely intended.
+ *
+ * Every [`Secret`](crate::secrets::Secret) has purposes. They are declared
+ * with [`Secret::with_purposes`](crate::secrets::Secret::with_purposes) (from
+ * the DID document's verification relationships, see
+ * [`KeyPurpose::from_relationship`]) or, when not declared, derived from the
+ * secret's type and curve. Code about to sign or agree keys calls
+ * [`Secret::check_usage`](crate::secrets::Secret::check_usage), or fetches the
+ * secret with
+ * [`SecretsResolver::get_secret_for`](crate::SecretsResolver::get_secret_for),
+ * and the [`KeyUsagePolicy`] decides whether a mismatch is an error or a
+ * warning.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An operation a key may be used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyPurpose {
+    /// Producing signatures (JWS, Data Integrity proofs, JWTs)
+    Signing,
+    /// Diffie-Hellman key agreement (DIDComm and TSP encryption)
+    KeyAgreement,
+}
+
+impl KeyPurpose {
+    /// The purpose implied by a DID document verification relationship, or
+    /// `None` for names that aren't verification relationships.
+    ///
+    /// ```
+    /// use affinidi_secrets_resolver::usage::KeyPurpose;
+    ///
+    /// assert_eq!(KeyPurpose::from_relationship("assertionMethod"), Some(KeyPurpose::Signing));
+    /// assert_eq!(KeyPurpose::from_relationship("keyAgreement"), Some(KeyPurpose::KeyAgreement));
+    /// assert_eq!(KeyPurpose::from_relationship("service"), None);
+    /// ```
+    pub fn from_relationship(relationship: &str) -> Option<Self> {
+        match relationship {
+            "authentication"
+            | "assertionMethod"
+            | "capabilityInvocation"
+            | "capabilityDelegation" => Some(KeyPurpose::Signing),
+            "keyAgreement" => Some(KeyPurpose::KeyAgreement),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KeyPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyPurpose::Signing => write!(f, "signing"),
+            KeyPurpose::KeyAgreement => write!(f, "key agreement"),
+        }
+    }
+}
+
+/// What to do when a key is used for a purpose it wasn't meant for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyUsagePolicy {
+    /// Refuse the operation with
+    /// [`SecretsResolverError::KeyUsage`](crate::errors::SecretsResolverError::KeyUsage)
+    Strict,
+    /// Log a warning and carry on
+    #[default]
+    Permissive,
+}
@@ -18,8 +18,18 @@
 //!   recorded in a [`HealthRegistry`] that a readiness handler can read to
 //!   decide readiness (a down *load-bearing* component → fail readiness; a
 //!   down non-load-bearing component → report `degraded` but stay ready).
+//! - **Status events.** Every state transition is also published as a
+//!   [`ComponentHealth`] snapshot on a broadcast channel
+//!   ([`TaskSupervisor::subscribe`]), so an application can react to a task
+//!   flapping without polling the registry.
 //! - **Clean shutdown.** When the shared [`CancellationToken`] fires, the
 //!   running task is aborted and marked `Stopped` with no restart.
+//!   [`TaskSupervisor::stopped`] waits until every task has actually exited.
+//! - **Critical start.** [`TaskSupervisor::spawn_critical`] waits until the
+//!   task's first attempt is up and returns a [`TaskStartError`] if it fails
+//!   before it gets there, so a constructor can refuse to hand out a client
+//!   whose core task never started. Once started, a critical task is
+//!   supervised exactly like any other.
 //!
 //! The supervisor owns cancellation, so supervised task bodies don't need
 //! their own shutdown `select!` — they can be a plain `loop { … }` and the
@@ -52,22 +62,33 @@
 //! ```
 
 use std::future::Future;
-use std::sync::Arc;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use serde::Serialize;
+use tokio::sync::{broadcast, oneshot};
+use tokio::task::JoinHandle;
+use tokio_util::task::TaskTracker;
 use tracing::{error, info, warn};
 
 pub use tokio_util::sync::CancellationToken;
 
+pub mod limits;
+pub use limits::{CallLimits, Interrupted};
+
 /// Backoff applied after the first failure; doubles each subsequent
 /// consecutive failure up to [`MAX_BACKOFF`].
 const BASE_BACKOFF: Duration = Duration::from_secs(1);
 /// Ceiling for the restart backoff, so a permanently-broken component
 /// retries at a steady, low rate rather than hot-looping.
 const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Capacity of the status-event channel. A subscriber that falls further
+/// behind than this sees `RecvError::Lagged` and can re-read the registry.
+const EVENT_CHANNEL_CAPACITY: usize = 64;
 
 /// Lifecycle state of a supervised component.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
@@ -105,21 +126,52 @@ pub struct ComponentHealth {
 /// locking out the supervisor's writes.
 pub type HealthRegistry = Arc<DashMap<String, ComponentHealth>>;
 
+/// A critical task failed on its first attempt (see
+/// [`TaskSupervisor::spawn_critical`]).
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("background task '{name}' failed to start: {reason}")]
+pub struct TaskStartError {
+    /// Name the task was spawned under.
+    pub name: String,
+    /// Display of the failure (error or panic) from the first attempt.
+    pub reason: String,
+}
+
 /// Spawns and supervises background tasks against a shared shutdown token,
 /// publishing their health into a [`HealthRegistry`].
 #[derive(Clone)]
 pub struct TaskSupervisor {
     registry: HealthRegistry,
+    events: broadcast::Sender<ComponentHealth>,
     shutdown: CancellationToken,
+    tasks: TaskTracker,
 }
 
 impl TaskSupervisor {
     /// Create a supervisor bound to `shutdown` (the same token used for
     /// graceful shutdown elsewhere in the process).
     pub fn new(shutdown: CancellationToken) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
         Self {
             registry: Arc::new(DashMap::new()),
+            events,
+            shutdown,
+            tasks: TaskTracker::new(),
+        }
+    }
+
+    /// A supervisor that shares this one's registry and event channel but is
+    /// bound to a different `shutdown` token — for a subsystem that must be
+    /// stopped independently yet report into the same health view. Its tasks
+    /// also count towards [`stopped`](Self::stopped), so pass a
+    /// [child](CancellationToken::child_token) of this supervisor's token
+    /// for them to stop with it.
+    pub fn with_shutdown(&self, shutdown: CancellationToken) -> Self {
+        Self {
+            registry: self.registry.clone(),
+            events: self.events.clone(),
             shutdown,
+            tasks: self.tasks.clone(),
         }
     }
 
@@ -129,6 +181,28 @@ impl TaskSupervisor {
         self.registry.clone()
     }
 
+    /// Subscribe to status events: a [`ComponentHealth`] snapshot is sent on
+    /// every state transition of every task spawned through this supervisor
+    /// (or one derived from it with [`with_shutdown`](Self::with_shutdown)).
+    /// Only transitions after the call are received; read the
+    /// [`registry`](Self::registry) for the current state.
+    pub fn subscribe(&self) -> broadcast::Receiver<ComponentHealth> {
+        self.events.subscribe()
+    }
+
+    /// The shutdown token this supervisor is bound to.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Wait until every task spawned through this supervisor, or one derived
+    /// from it with [`with_shutdown`](Self::with_shutdown), has exited. This
+    /// doesn't stop anything itself: cancel the shutdown tokens first.
+    pub async fn stopped(&self) {
+        self.tasks.close();
+        self.tasks.wait().await;
+    }
+
     /// Spawn a supervised task.
     ///
     /// `factory` is invoked once per (re)start to produce the task future;
@@ -138,18 +212,76 @@ impl TaskSupervisor {
     ///
     /// `load_bearing` controls how a readiness handler should treat a
     /// non-`Running` state — see [`ComponentHealth::load_bearing`].
-    pub fn spawn<F, Fut, E>(&self, name: impl Into<String>, load_bearing: bool, factory: F)
+    ///
+    /// The returned handle completes once the task has stopped for good.
+    pub fn spawn<F, Fut, E>(
+        &self,
+        name: impl Into<String>,
+        load_bearing: bool,
+        factory: F,
+    ) -> JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display + Send + 'static,
+    {
+        self.spawn_inner(name.into(), load_bearing, factory, None)
+    }
+
+    /// Spawn a load-bearing supervised task and wait for its first attempt to
+    /// start.
+    ///
+    /// The task counts as started once its future has been polled and is
+    /// waiting on something (for an actor loop: its command channel), or has
+    /// completed `Ok(())`. If the first attempt instead returns `Err(_)` or
+    /// panics, a [`TaskStartError`] is returned. The supervisor keeps
+    /// restarting the task either way; a caller that gives up on a failed start
+    /// should cancel the shutdown token.
+    pub async fn spawn_critical<F, Fut, E>(
+        &self,
+        name: impl Into<String>,
+        factory: F,
+    ) -> Result<(), TaskStartError>
     where
         F: Fn() -> Fut + Send + 'static,
         Fut: Future<Output = Result<(), E>> + Send + 'static,
         E: std::fmt::Display + Send + 'static,
     {
         let name = name.into();
+        let (started_tx, started_rx) = oneshot::channel();
+        self.spawn_inner(name.clone(), true, factory, Some(started_tx));
+
+        match started_rx.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(reason)) => Err(TaskStartError { name, reason }),
+            // The supervisor stopped before the first attempt reported back:
+            // shutdown raced the start.
+            Err(_) => Err(TaskStartError {
+                name,
+                reason: "stopped before it started".to_string(),
+            }),
+        }
+    }
+
+    fn spawn_inner<F, Fut, E>(
+        &self,
+        name: String,
+        load_bearing: bool,
+        factory: F,
+        started: Option<oneshot::Sender<Result<(), String>>>,
+    ) -> JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: std::fmt::Display + Send + 'static,
+    {
         let registry = self.registry.clone();
+        let events = self.events.clone();
         let shutdown = self.shutdown.clone();
 
         set_state(
             &registry,
+            &events,
             &name,
             load_bearing,
             ComponentState::Running,
@@ -157,7 +289,9 @@ impl TaskSupervisor {
             None,
         );
 
-        tokio::spawn(async move {
+        self.tasks.spawn(async move {
+            let start_signal: Option<StartSignal> =
+                started.map(|tx| Arc::new(Mutex::new(Some(tx))));
             let mut restarts: u64 = 0;
             loop {
                 // Mark Running at the top of every iteration so a task that
@@ -166,6 +300,7 @@ impl TaskSupervisor {
                 // untouched — so operators can still see it flapped).
                 set_state(
                     &registry,
+                    &events,
                     &name,
                     load_bearing,
                     ComponentState::Running,
@@ -174,17 +309,25 @@ impl TaskSupervisor {
                 );
 
                 // Spawn the task on its own handle so a panic surfaces as a
-                // `JoinError` rather than tearing down the supervisor.
-                let mut handle = tokio::spawn(factory());
+                // `JoinError` rather than tearing down the supervisor. A
+                // critical start watches its first attempt's first poll.
+                let mut handle = match &start_signal {
+                    Some(signal) if restarts == 0 => tokio::spawn(FirstPoll {
+                        inner: Box::pin(factory()),
+                        signal: Some(signal.clone()),
+                    }),
+                    _ => tokio::spawn(factory()),
+                };
 
                 let failure: String = tokio::select! {
                     joined = &mut handle => match joined {
                         Ok(Ok(())) => {
+                            signal_started(&start_signal, Ok(()));
                             // Completed without error. Under shutdown that's
                             // expected; otherwise a long-lived task ending is
                             // itself a fault, so restart it.
                             if shutdown.is_cancelled() {
-                                stop(&registry, &name, restarts);
+                                stop(&registry, &events, &name, restarts);
                                 return;
                             }
                             warn!(task = %name, "Supervised task completed unexpectedly; restarting");
@@ -196,18 +339,22 @@ impl TaskSupervisor {
                             // Cancelled/aborted without us asking — treat as a
                             // stop rather than spin.
                             warn!(task = %name, error = %join_err, "Supervised task aborted");
-                            stop(&registry, &name, restarts);
+                            stop(&registry, &events, &name, restarts);
                             return;
                         }
                     },
                     _ = shutdown.cancelled() => {
                         handle.abort();
-                        stop(&registry, &name, restarts);
+                        // Let the abort land, so the task has exited by the
+                        // time this one has.
+                        let _ = handle.await;
+                        stop(&registry, &events, &name, restarts);
                         info!(task = %name, "Supervised task stopped (shutdown)");
                         return;
                     }
                 };
 
+                signal_started(&start_signal, Err(failure.clone()));
                 restarts += 1;
                 let backoff = backoff_for(restarts);
                 error!(
@@ -222,6 +369,7 @@ impl TaskSupervisor {
                 );
                 set_state(
                     &registry,
+                    &events,
                     &name,
                     load_bearing,
                     ComponentState::Restarting,
@@ -232,13 +380,47 @@ impl TaskSupervisor {
                 tokio::select! {
                     _ = tokio::time::sleep(backoff) => {}
                     _ = shutdown.cancelled() => {
-                        stop(&registry, &name, restarts);
+                        stop(&registry, &events, &name, restarts);
                         info!(task = %name, "Supervised task stopped during backoff (shutdown)");
                         return;
                     }
                 }
             }
-        });
+        })
+    }
+}
+
+/// One-shot "first attempt is up / failed" report for a critical start, shared
+/// between the supervisor loop and the [`FirstPoll`] wrapper — whichever
+/// learns the outcome first sends it.
+type StartSignal = Arc<Mutex<Option<oneshot::Sender<Result<(), String>>>>>;
+
+fn signal_started(signal: &Option<StartSignal>, outcome: Result<(), String>) {
+    let tx = signal
+        .as_ref()
+        .and_then(|signal| signal.lock().ok().and_then(|mut slot| slot.take()));
+    if let Some(tx) = tx {
+        let _ = tx.send(outcome);
+    }
+}
+
+/// Reports a critical task as started the first time its future returns
+/// `Pending` — i.e. it got past setup and is now waiting for work.
+struct FirstPoll<Fut> {
+    inner: Pin<Box<Fut>>,
+    signal: Option<StartSignal>,
+}
+
+impl<Fut: Future> Future for FirstPoll<Fut> {
+    type Output = Fut::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let poll = self.inner.as_mut().poll(cx);
+        if poll.is_pending() {
+            let signal = self.signal.take();
+            signal_started(&signal, Ok(()));
+        }
+        poll
     }
 }
 
@@ -252,6 +434,7 @@ fn backoff_for(restarts: u64) -> Duration {
 
 fn set_state(
     registry: &HealthRegistry,
+    events: &broadcast::Sender<ComponentHealth>,
     name: &str,
     load_bearing: bool,
     state: ComponentState,
@@ -276,14 +459,29 @@ fn set_state(
             last_error,
             since: Utc::now(),
         });
+    publish(registry, events, name);
 }
 
-fn stop(registry: &HealthRegistry, name: &str, restarts: u64) {
+fn stop(
+    registry: &HealthRegistry,
+    events: &broadcast::Sender<ComponentHealth>,
+    name: &str,
+    restarts: u64,
+) {
     if let Some(mut h) = registry.get_mut(name) {
         h.state = ComponentState::Stopped;
         h.restarts = restarts;
         h.since = Utc::now();
     }
+    publish(registry, events, name);
+}
+
+/// Broadcast the component's current snapshot. No subscribers is not an error.
+fn publish(registry: &HealthRegistry, events: &broadcast::Sender<ComponentHealth>, name: &str) {
+    let snapshot = registry.get(name).map(|h| h.value().clone());
+    if let Some(snapshot) = snapshot {
+        let _ = events.send(snapshot);
+    }
 }
 
 #[cfg(test)]
@@ -407,4 +605,111 @@ mod tests {
             "task restarted after shutdown"
         );
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn stopped_waits_for_every_task_to_exit() {
+        /// Counts a task out when its future is dropped.
+        struct Exit(Arc<AtomicU32>);
+        impl Drop for Exit {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let token = CancellationToken::new();
+        let sup = TaskSupervisor::new(token.clone());
+        let scoped = sup.with_shutdown(token.child_token());
+        let exited = Arc::new(AtomicU32::new(0));
+
+        for (supervisor, name) in [(&sup, "outer"), (&scoped, "scoped")] {
+            let exited = exited.clone();
+            supervisor.spawn(name, false, move || {
+                let exit = Exit(exited.clone());
+                async move {
+                    let _exit = exit;
+                    std::future::pending::<()>().await;
+                    Ok::<(), String>(())
+                }
+            });
+        }
+
+        token.cancel();
+        timeout(Duration::from_secs(5), sup.stopped())
+            .await
+            .expect("supervised tasks exit on shutdown");
+        assert_eq!(exited.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn transitions_are_published_as_events() {
+        let token = CancellationToken::new();
+        let sup = TaskSupervisor::new(token.clone());
+        let mut events = sup.subscribe();
+
+        sup.spawn("observed", false, || async {
+            std::future::pending::<()>().await;
+            Ok::<(), String>(())
+        });
+
+        let first = timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.name, "observed");
+        assert_eq!(first.state, ComponentState::Running);
+
+        token.cancel();
+        loop {
+            let event = timeout(Duration::from_secs(5), events.recv())
+                .await
+                .unwrap()
+                .unwrap();
+            if event.state == ComponentState::Stopped {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn critical_start_succeeds_once_the_task_is_waiting() {
+        let token = CancellationToken::new();
+        let sup = TaskSupervisor::new(token.clone());
+
+        sup.spawn_critical("actor", || async {
+            std::future::pending::<()>().await;
+            Ok::<(), String>(())
+        })
+        .await
+        .expect("a task that reaches its wait point has started");
+
+        let reg = sup.registry();
+        assert!(reg.get("actor").unwrap().load_bearing);
+        token.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn critical_start_reports_a_failed_first_attempt() {
+        let token = CancellationToken::new();
+        let sup = TaskSupervisor::new(token.clone());
+
+        let err = sup
+            .spawn_critical("broken", || async {
+                Err::<(), String>("no backend".to_string())
+            })
+            .await
+            .expect_err("first attempt failed");
+        assert_eq!(err.name, "broken");
+        assert_eq!(err.reason, "no backend");
+
+        let err = sup
+            .spawn_critical("panics", || async {
+                panic!("setup exploded");
+                #[allow(unreachable_code)]
+                Ok::<(), String>(())
+            })
+            .await
+            .expect_err("first attempt panicked");
+        assert!(err.reason.starts_with("panicked"));
+        token.cancel();
+    }
 }
@@ -0,0 +1,179 @@
+//! Caller-controlled deadlines and cancellation for a single async call.
+//!
+//! [`CallLimits`] bundles an optional timeout and an optional
+//! [`CancellationToken`]. APIs that accept one race their work against both
+//! and, when either fires first, *drop* the in-flight future rather than
+//! detaching it — so any background state it registered is released through
+//! the future's own drop guards instead of lingering until a server replies.
+//!
+//! ```
+//! use affinidi_task_utils::{CallLimits, CancellationToken, Interrupted};
+//! use std::time::Duration;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let cancel = CancellationToken::new();
+//! let limits = CallLimits::new()
+//!     .with_timeout(Duration::from_secs(5))
+//!     .with_cancel(cancel.clone());
+//!
+//! cancel.cancel();
+//! let result = limits.run(std::future::pending::<()>()).await;
+//! assert!(matches!(result, Err(Interrupted::Cancelled)));
+//! # }
+//! ```
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+/// Why a call run under [`CallLimits`] did not complete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum Interrupted {
+    /// The caller's [`CancellationToken`] fired.
+    #[error("call cancelled by caller")]
+    Cancelled,
+    /// The call ran past its timeout.
+    #[error("call timed out after {0:?}")]
+    TimedOut(Duration),
+}
+
+/// An optional timeout and cancellation token for one call.
+///
+/// The default places no limits: [`run`](Self::run) then simply awaits the
+/// future.
+#[derive(Clone, Debug, Default)]
+pub struct CallLimits {
+    timeout: Option<Duration>,
+    cancel: Option<CancellationToken>,
+}
+
+impl CallLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give up once `timeout` has elapsed.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Give up as soon as `cancel` fires.
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// The timeout, if one is set.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// The cancellation token, if one is set.
+    pub fn cancel(&self) -> Option<&CancellationToken> {
+        self.cancel.as_ref()
+    }
+
+    /// True once the cancellation token has fired.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+    }
+
+    /// Run `fut` to completion unless the timeout elapses or the token fires
+    /// first, in which case `fut` is dropped and the reason returned.
+    ///
+    /// An already-cancelled token wins without polling `fut` at all.
+    pub async fn run<F: Future>(&self, fut: F) -> Result<F::Output, Interrupted> {
+        if self.is_cancelled() {
+            return Err(Interrupted::Cancelled);
+        }
+
+        let cancelled = async {
+            match &self.cancel {
+                Some(cancel) => cancel.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        let deadline = async {
+            match self.timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            biased;
+            _ = cancelled => Err(Interrupted::Cancelled),
+            _ = deadline => Err(Interrupted::TimedOut(self.timeout.unwrap_or_default())),
+            output = fut => Ok(output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn unlimited_runs_to_completion() {
+        assert_eq!(CallLimits::new().run(async { 7 }).await, Ok(7));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout_drops_the_future() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let guard = SetOnDrop(dropped.clone());
+        let limits = CallLimits::new().with_timeout(Duration::from_secs(2));
+
+        let result = limits
+            .run(async move {
+                let _guard = guard;
+                std::future::pending::<()>().await
+            })
+            .await;
+
+        assert_eq!(result, Err(Interrupted::TimedOut(Duration::from_secs(2))));
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn cancel_interrupts_a_running_call() {
+        let cancel = CancellationToken::new();
+        let limits = CallLimits::new().with_cancel(cancel.clone());
+
+        tokio::spawn(async move { cancel.cancel() });
+        let result = limits.run(std::future::pending::<()>()).await;
+        assert_eq!(result, Err(Interrupted::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn cancelled_token_skips_the_call() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let polled = Arc::new(AtomicBool::new(false));
+
+        let result = CallLimits::new()
+            .with_cancel(cancel)
+            .run({
+                let polled = polled.clone();
+                async move { polled.store(true, Ordering::SeqCst) }
+            })
+            .await;
+
+        assert_eq!(result, Err(Interrupted::Cancelled));
+        assert!(!polled.load(Ordering::SeqCst));
+    }
+}
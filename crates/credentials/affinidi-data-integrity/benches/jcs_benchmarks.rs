@@ -0,0 +1,80 @@
+//! JCS canonicalize-and-hash: the default canonicalizer against the
+//! streaming one, over documents from a single credential up to a webvh
+//! log entry carrying a large DID document.
+//!
+//! Run with: `cargo bench -p affinidi-data-integrity --bench jcs_benchmarks`
+
+use affinidi_data_integrity::jcs::{JsonCanonicalizer, SerdeJcs, StreamingJcs};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use serde_json::{Value, json};
+
+/// A webvh-style log entry whose DID document has `methods` verification
+/// methods and services.
+fn webvh_entry(methods: usize) -> Value {
+    let verification_method: Vec<Value> = (0..methods)
+        .map(|i| {
+            json!({
+                "id": format!("did:webvh:QmScid:example.com#key-{i}"),
+                "type": "Multikey",
+                "controller": "did:webvh:QmScid:example.com",
+                "publicKeyMultibase": format!("z6Mk{:0>44}", i),
+            })
+        })
+        .collect();
+    let service: Vec<Value> = (0..methods)
+        .map(|i| {
+            json!({
+                "id": format!("did:webvh:QmScid:example.com#service-{i}"),
+                "type": "DIDCommMessaging",
+                "serviceEndpoint": {
+                    "uri": format!("https://example.com/didcomm/{i}"),
+                    "accept": ["didcomm/v2"],
+                    "routingKeys": [],
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "versionId": "1-QmHash",
+        "versionTime": "2026-01-01T00:00:00Z",
+        "parameters": {
+            "method": "did:webvh:1.0",
+            "scid": "QmScid",
+            "updateKeys": ["z6MkUpdateKey"],
+            "portable": false,
+            "witness": {"threshold": 2, "witnesses": [{"id": "did:key:z6MkW1"}, {"id": "did:key:z6MkW2"}]},
+        },
+        "state": {
+            "@context": ["https://www.w3.org/ns/did/v1", "https://w3id.org/security/multikey/v1"],
+            "id": "did:webvh:QmScid:example.com",
+            "verificationMethod": verification_method,
+            "authentication": ["did:webvh:QmScid:example.com#key-0"],
+            "assertionMethod": ["did:webvh:QmScid:example.com#key-0"],
+            "service": service,
+        },
+    })
+}
+
+fn bench_hash(c: &mut Criterion) {
+    let canonicalizers: [&dyn JsonCanonicalizer; 2] = [&SerdeJcs, &StreamingJcs];
+    let mut group = c.benchmark_group("jcs_hash");
+
+    for methods in [1, 100, 5_000] {
+        let doc = webvh_entry(methods);
+        let bytes = serde_json::to_vec(&doc).unwrap().len();
+        group.throughput(Throughput::Bytes(bytes as u64));
+
+        for canonicalizer in canonicalizers {
+            group.bench_with_input(
+                BenchmarkId::new(canonicalizer.name(), format!("{methods}-methods")),
+                &doc,
+                |b, doc| b.iter(|| canonicalizer.hash(doc).unwrap()),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_hash);
+criterion_main!(benches);
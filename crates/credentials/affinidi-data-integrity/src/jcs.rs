@@ -0,0 +1,299 @@
+//! Pluggable RFC 8785 JSON Canonicalization Scheme (JCS) implementations.
+//!
+//! JCS suites hash the canonical form of the document and the proof
+//! config. By default the library canonicalizes with
+//! `serde_json_canonicalizer`, which builds the whole canonical string in
+//! memory. For large documents (e.g. webvh log entries carrying big DID
+//! documents) [`StreamingJcs`] writes the canonical form straight into
+//! the SHA-256 hasher instead, so the canonical string is never held in
+//! memory.
+//!
+//! Canonicalizers work on a [`serde_json::Value`]: members have to be
+//! sorted before they are written, so the document is converted to a
+//! `Value` first. Signing or verifying a `Value` still copies it once; the
+//! saving is the canonical string, not the tree.
+//!
+//! The two agree on every document except ones holding integers beyond
+//! 2^53: [`StreamingJcs`] rounds those to the nearest double as RFC 8785
+//! requires, while `serde_json_canonicalizer` writes them exactly.
+//!
+//! Both implement [`JsonCanonicalizer`]. Select one per call with
+//! [`SignOptions::with_canonicalizer`] / [`VerifyOptions::with_canonicalizer`];
+//! implement the trait yourself to test another canonicalizer against
+//! these for byte-for-byte compatibility.
+//!
+//! ```
+//! use affinidi_data_integrity::jcs::{JsonCanonicalizer, SerdeJcs, StreamingJcs};
+//! use serde_json::json;
+//!
+//! let doc = json!({"b": [1.0, 1e21, "\u{20ac}"], "a": null});
+//! let canonical = StreamingJcs.canonicalize(&doc).unwrap();
+//! assert_eq!(canonical, r#"{"a":null,"b":[1,1e+21,"€"]}"#);
+//! assert_eq!(canonical, SerdeJcs.canonicalize(&doc).unwrap());
+//! ```
+//!
+//! [`SignOptions::with_canonicalizer`]: crate::SignOptions::with_canonicalizer
+//! [`VerifyOptions::with_canonicalizer`]: crate::VerifyOptions::with_canonicalizer
+
+use std::io::{self, Write};
+
+use serde_json::{Map, Number, Value};
+use sha2::{Digest, Sha256};
+
+use crate::DataIntegrityError;
+
+/// A JSON Canonicalization Scheme (RFC 8785) implementation.
+pub trait JsonCanonicalizer: Send + Sync + std::fmt::Debug {
+    /// Short name used in logs and benchmark reports.
+    fn name(&self) -> &'static str;
+
+    /// Writes the canonical form of `value` to `out`.
+    fn canonicalize_to(&self, value: &Value, out: &mut dyn Write)
+    -> Result<(), DataIntegrityError>;
+
+    /// Returns the canonical form of `value` as a string.
+    fn canonicalize(&self, value: &Value) -> Result<String, DataIntegrityError> {
+        let mut out = Vec::new();
+        self.canonicalize_to(value, &mut out)?;
+        String::from_utf8(out).map_err(|e| DataIntegrityError::Canonicalization(e.to_string()))
+    }
+
+    /// Returns the SHA-256 digest of the canonical form of `value`.
+    fn hash(&self, value: &Value) -> Result<[u8; 32], DataIntegrityError> {
+        let mut hasher = HashWriter(Sha256::new());
+        self.canonicalize_to(value, &mut hasher)?;
+        Ok(hasher.0.finalize().into())
+    }
+}
+
+/// The default canonicalizer: `serde_json_canonicalizer`, buffered.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SerdeJcs;
+
+impl JsonCanonicalizer for SerdeJcs {
+    fn name(&self) -> &'static str {
+        "serde-json-canonicalizer"
+    }
+
+    fn canonicalize_to(
+        &self,
+        value: &Value,
+        out: &mut dyn Write,
+    ) -> Result<(), DataIntegrityError> {
+        let canonical = serde_json_canonicalizer::to_string(value)
+            .map_err(|e| DataIntegrityError::Canonicalization(e.to_string()))?;
+        out.write_all(canonical.as_bytes())
+            .map_err(|e| DataIntegrityError::Canonicalization(e.to_string()))
+    }
+}
+
+/// Writes the canonical form as it walks the value, without building the
+/// canonical string. Object members are emitted in UTF-16 code unit order
+/// by sorting references to the keys, so the only allocation per object
+/// is that key list.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StreamingJcs;
+
+impl JsonCanonicalizer for StreamingJcs {
+    fn name(&self) -> &'static str {
+        "streaming"
+    }
+
+    fn canonicalize_to(
+        &self,
+        value: &Value,
+        out: &mut dyn Write,
+    ) -> Result<(), DataIntegrityError> {
+        write_value(value, out).map_err(|e| DataIntegrityError::Canonicalization(e.to_string()))
+    }
+}
+
+fn write_value(value: &Value, out: &mut dyn Write) -> io::Result<()> {
+    match value {
+        Value::Null => out.write_all(b"null"),
+        Value::Bool(true) => out.write_all(b"true"),
+        Value::Bool(false) => out.write_all(b"false"),
+        Value::Number(n) => write_number(n, out),
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.write_all(b"[")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b",")?;
+                }
+                write_value(item, out)?;
+            }
+            out.write_all(b"]")
+        }
+        Value::Object(map) => write_object(map, out),
+    }
+}
+
+fn write_object(map: &Map<String, Value>, out: &mut dyn Write) -> io::Result<()> {
+    // RFC 8785 §3.2.3: sort by UTF-16 code units, not by UTF-8 bytes
+    let mut members: Vec<(&String, &Value)> = map.iter().collect();
+    members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+    out.write_all(b"{")?;
+    for (i, (key, value)) in members.into_iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        write_string(key, out)?;
+        out.write_all(b":")?;
+        write_value(value, out)?;
+    }
+    out.write_all(b"}")
+}
+
+/// Every number is an IEEE 754 double written in ECMAScript
+/// `Number.prototype.toString` form (RFC 8785 §3.2.2.3), so integers
+/// beyond 2^53 are rounded to the nearest double just as a JavaScript
+/// implementation would.
+fn write_number(n: &Number, out: &mut dyn Write) -> io::Result<()> {
+    match n.as_f64() {
+        Some(0.0) => out.write_all(b"0"),
+        Some(f) if f.is_finite() => {
+            out.write_all(ryu_js::Buffer::new().format_finite(f).as_bytes())
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("number ({n}) has no JCS form"),
+        )),
+    }
+}
+
+/// RFC 8785 §3.2.2.2: only `"`, `\` and control characters are escaped.
+fn write_string(s: &str, out: &mut dyn Write) -> io::Result<()> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+
+    out.write_all(b"\"")?;
+    let bytes = s.as_bytes();
+    let mut start = 0;
+    for (i, &b) in bytes.iter().enumerate() {
+        let escape: &[u8] = match b {
+            b'"' => b"\\\"",
+            b'\\' => b"\\\\",
+            b'\x08' => b"\\b",
+            b'\t' => b"\\t",
+            b'\n' => b"\\n",
+            b'\x0c' => b"\\f",
+            b'\r' => b"\\r",
+            0x00..=0x1f => &[
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[(b >> 4) as usize],
+                HEX[(b & 0xf) as usize],
+            ],
+            _ => continue,
+        };
+        out.write_all(&bytes[start..i])?;
+        out.write_all(escape)?;
+        start = i + 1;
+    }
+    out.write_all(&bytes[start..])?;
+    out.write_all(b"\"")
+}
+
+/// Feeds written bytes into a SHA-256 hasher.
+struct HashWriter(Sha256);
+
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn samples() -> Vec<Value> {
+        vec![
+            json!(null),
+            json!({}),
+            json!([]),
+            json!({
+                "numbers": [333_333_333.333_333_3, 1E30, 4.50, 2e-3, 0.000000000000000000000000001, -0.0, 1e21, 1e20, -7, 9007199254740991_i64],
+                "string": "\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/",
+                "literals": [null, true, false]
+            }),
+            json!({"nested": {"z": [{"b": 1, "a": 2}], "a": {"y": "\t", "x": "\u{7f}"}}}),
+        ]
+    }
+
+    #[test]
+    fn streaming_matches_the_default() {
+        for value in samples() {
+            assert_eq!(
+                StreamingJcs.canonicalize(&value).unwrap(),
+                SerdeJcs.canonicalize(&value).unwrap(),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn rfc8785_sorts_by_utf16_code_units() {
+        // RFC 8785 §3.2.3: UTF-16 order puts U+1F600 before U+FB33
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{0080}": "Control",
+            "\u{00f6}": "Latin Small Letter O With Diaeresis"
+        });
+        assert_eq!(
+            StreamingJcs.canonicalize(&value).unwrap(),
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{0080}\":\"Control\",\
+             \"\u{00f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\
+             \"\u{1f600}\":\"Emoji: Grinning Face\",\
+             \"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+    }
+
+    #[test]
+    fn rfc8785_number_and_string_forms() {
+        let value = json!({
+            "numbers": [333_333_333.333_333_3, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/",
+        });
+        assert_eq!(
+            StreamingJcs.canonicalize(&value).unwrap(),
+            r#"{"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn integers_beyond_2_pow_53_round_like_doubles() {
+        let value = json!([
+            9007199254740993_u64,
+            -9007199254740993_i64,
+            u64::MAX,
+            123_456_789_012_345_680_000_u128 as f64
+        ]);
+        assert_eq!(
+            StreamingJcs.canonicalize(&value).unwrap(),
+            "[9007199254740992,-9007199254740992,18446744073709552000,123456789012345680000]"
+        );
+    }
+
+    #[test]
+    fn hash_is_sha256_of_the_canonical_form() {
+        for value in samples() {
+            let expected: [u8; 32] = Sha256::digest(SerdeJcs.canonicalize(&value).unwrap()).into();
+            assert_eq!(StreamingJcs.hash(&value).unwrap(), expected);
+            assert_eq!(SerdeJcs.hash(&value).unwrap(), expected);
+        }
+    }
+}
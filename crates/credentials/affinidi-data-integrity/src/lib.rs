@@ -65,6 +65,7 @@ will live in sibling crates (`affinidi-data-integrity-jose`,
 
 use chrono::{DateTime, Utc};
 use crypto_suites::CryptoSuite;
+use jcs::JsonCanonicalizer;
 use multibase::Base;
 use serde::{Deserialize, Serialize};
 use serde_json_canonicalizer::to_string;
@@ -77,6 +78,7 @@ pub mod conformance;
 pub mod crypto_suites;
 pub mod did_vm;
 pub mod error;
+pub mod jcs;
 pub mod multi;
 pub mod options;
 pub mod signer;
@@ -214,6 +216,7 @@ impl DataIntegrityProof {
                 signer,
                 created_str,
                 proof_purpose,
+                options.canonicalizer.as_deref(),
             )
             .await
         }
@@ -296,14 +299,11 @@ async fn sign_jcs<S>(
     signer: &dyn Signer,
     created: String,
     proof_purpose: String,
+    canonicalizer: Option<&dyn JsonCanonicalizer>,
 ) -> Result<DataIntegrityProof, DataIntegrityError>
 where
     S: Serialize,
 {
-    let jcs = to_string(data_doc)
-        .map_err(|e| DataIntegrityError::Canonicalization(format!("document: {e}")))?;
-    debug!("Document (JCS): {}", jcs);
-
     let mut proof_options = DataIntegrityProof {
         type_: "DataIntegrityProof".to_string(),
         cryptosuite: crypto_suite,
@@ -314,11 +314,20 @@ where
         context,
     };
 
-    let proof_jcs = to_string(&proof_options)
-        .map_err(|e| DataIntegrityError::Canonicalization(format!("proof config: {e}")))?;
-    debug!("Proof options (JCS): {}", proof_jcs);
+    let hash_data = match canonicalizer {
+        Some(canonicalizer) => hashing_jcs_with(canonicalizer, data_doc, &proof_options)?,
+        None => {
+            let jcs = to_string(data_doc)
+                .map_err(|e| DataIntegrityError::Canonicalization(format!("document: {e}")))?;
+            debug!("Document (JCS): {}", jcs);
+
+            let proof_jcs = to_string(&proof_options)
+                .map_err(|e| DataIntegrityError::Canonicalization(format!("proof config: {e}")))?;
+            debug!("Proof options (JCS): {}", proof_jcs);
 
-    let hash_data = hashing_jcs(&jcs, &proof_jcs);
+            hashing_jcs(&jcs, &proof_jcs)
+        }
+    };
     let signed = signer.sign(&hash_data).await?;
     proof_options.proof_value = Some(multibase::encode(Base::Base58Btc, &signed));
 
@@ -463,11 +472,17 @@ where
                     .to_string(),
             });
         }
-        let jcs_doc = to_string(&signed_doc)
-            .map_err(|e| DataIntegrityError::Canonicalization(format!("document: {e}")))?;
-        let jcs_proof_config = to_string(&proof_config)
-            .map_err(|e| DataIntegrityError::Canonicalization(format!("proof config: {e}")))?;
-        hashing_jcs(&jcs_doc, &jcs_proof_config)
+        match options.canonicalizer.as_deref() {
+            Some(canonicalizer) => hashing_jcs_with(canonicalizer, signed_doc, &proof_config)?,
+            None => {
+                let jcs_doc = to_string(&signed_doc)
+                    .map_err(|e| DataIntegrityError::Canonicalization(format!("document: {e}")))?;
+                let jcs_proof_config = to_string(&proof_config).map_err(|e| {
+                    DataIntegrityError::Canonicalization(format!("proof config: {e}"))
+                })?;
+                hashing_jcs(&jcs_doc, &jcs_proof_config)
+            }
+        }
     };
 
     proof_config
@@ -488,6 +503,32 @@ fn hashing_jcs(transformed_document: &str, canonical_proof_config: &str) -> Vec<
     .concat()
 }
 
+/// [`hashing_jcs`] through a pluggable [`JsonCanonicalizer`], which hashes
+/// as it canonicalizes rather than materialising the canonical strings.
+/// The document is converted to a `Value` first, as canonicalizers need
+/// its members to sort them.
+fn hashing_jcs_with<S>(
+    canonicalizer: &dyn JsonCanonicalizer,
+    document: &S,
+    proof_config: &DataIntegrityProof,
+) -> Result<Vec<u8>, DataIntegrityError>
+where
+    S: Serialize + ?Sized,
+{
+    let document = serde_json::to_value(document)
+        .map_err(|e| DataIntegrityError::Canonicalization(format!("document serialize: {e}")))?;
+    let proof_config = serde_json::to_value(proof_config).map_err(|e| {
+        DataIntegrityError::Canonicalization(format!("proof config serialize: {e}"))
+    })?;
+    debug!("Canonicalizing with ({})", canonicalizer.name());
+
+    Ok([
+        canonicalizer.hash(&proof_config)?,
+        canonicalizer.hash(&document)?,
+    ]
+    .concat())
+}
+
 /// Hashing Algorithm for EDDSA RDFC.
 /// Runs both document and proof config through the RDFC pipeline
 /// (JSON-LD expansion → RDF Dataset → RDFC-1.0 canonicalization → SHA-256)
@@ -632,6 +673,39 @@ mod tests {
             .expect("verify");
     }
 
+    #[tokio::test]
+    async fn canonicalizers_are_interchangeable() {
+        use crate::jcs::StreamingJcs;
+        use std::sync::Arc;
+
+        let secret = Secret::generate_ed25519(Some("did:key:k#k"), Some(&[4u8; 32]));
+        let doc = json!({"hello": "world", "nested": {"b": [1, 2.5], "a": "\u{20ac}"}});
+        let created = chrono::Utc::now() - chrono::Duration::seconds(1);
+
+        let default_proof =
+            DataIntegrityProof::sign(&doc, &secret, SignOptions::new().with_created(created))
+                .await
+                .expect("sign");
+        let streamed_proof = DataIntegrityProof::sign(
+            &doc,
+            &secret,
+            SignOptions::new()
+                .with_created(created)
+                .with_canonicalizer(Arc::new(StreamingJcs)),
+        )
+        .await
+        .expect("sign");
+        assert_eq!(default_proof.proof_value, streamed_proof.proof_value);
+
+        default_proof
+            .verify_with_public_key(
+                &doc,
+                secret.get_public_bytes(),
+                VerifyOptions::new().with_canonicalizer(Arc::new(StreamingJcs)),
+            )
+            .expect("verify");
+    }
+
     #[cfg(feature = "ml-dsa")]
     #[tokio::test]
     async fn unified_sign_verify_ml_dsa_44_jcs() {
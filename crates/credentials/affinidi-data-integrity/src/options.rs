@@ -17,9 +17,12 @@
 //!     .with_proof_purpose("authentication");
 //! ```
 
+use std::sync::Arc;
+
 use chrono::{DateTime, Utc};
 
 use crate::crypto_suites::CryptoSuite;
+use crate::jcs::JsonCanonicalizer;
 
 /// Options for signing a Data Integrity proof.
 ///
@@ -48,6 +51,10 @@ pub struct SignOptions {
 
     /// Value of `proofPurpose`. Defaults to `"assertionMethod"`.
     pub proof_purpose: Option<String>,
+
+    /// JCS implementation for JCS suites. If `None`, the library's default
+    /// (`serde_json_canonicalizer`) is used. Ignored by RDFC suites.
+    pub canonicalizer: Option<Arc<dyn JsonCanonicalizer>>,
 }
 
 impl SignOptions {
@@ -88,6 +95,14 @@ impl SignOptions {
         self.proof_purpose = Some(purpose.into());
         self
     }
+
+    /// Canonicalizes JCS suites with `canonicalizer`, e.g.
+    /// [`crate::jcs::StreamingJcs`] for large documents.
+    #[must_use = "chained builder call returns self; assign or chain further"]
+    pub fn with_canonicalizer(mut self, canonicalizer: Arc<dyn JsonCanonicalizer>) -> Self {
+        self.canonicalizer = Some(canonicalizer);
+        self
+    }
 }
 
 /// Options for verifying a Data Integrity proof.
@@ -109,6 +124,10 @@ pub struct VerifyOptions {
     /// accept (e.g. refuse `bbs-2023` in a context that requires full
     /// disclosure).
     pub allowed_suites: Vec<CryptoSuite>,
+
+    /// JCS implementation for JCS suites. If `None`, the library's default
+    /// (`serde_json_canonicalizer`) is used. Ignored by RDFC suites.
+    pub canonicalizer: Option<Arc<dyn JsonCanonicalizer>>,
 }
 
 impl VerifyOptions {
@@ -132,6 +151,14 @@ impl VerifyOptions {
         self.allowed_suites = suites;
         self
     }
+
+    /// Canonicalizes JCS suites with `canonicalizer`, e.g.
+    /// [`crate::jcs::StreamingJcs`] for large documents.
+    #[must_use = "chained builder call returns self; assign or chain further"]
+    pub fn with_canonicalizer(mut self, canonicalizer: Arc<dyn JsonCanonicalizer>) -> Self {
+        self.canonicalizer = Some(canonicalizer);
+        self
+    }
 }
 
 #[cfg(test)]
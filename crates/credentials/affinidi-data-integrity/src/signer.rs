@@ -20,7 +20,10 @@
 //! [`crate::prepare_sign_input`] helper returns that byte slice ahead of
 //! time for remote-signing protocols that need it.
 
-use affinidi_secrets_resolver::secrets::{KeyType, Secret};
+use affinidi_secrets_resolver::{
+    secrets::{KeyType, Secret},
+    usage::KeyPurpose,
+};
 use async_trait::async_trait;
 use ed25519_dalek::{SigningKey, ed25519::signature::SignerMut};
 use zeroize::Zeroizing;
@@ -82,6 +85,11 @@ pub trait Signer: Send + Sync {
 /// Blanket implementation for `Secret`, providing local signing via
 /// `ed25519-dalek` / `ml-dsa` / `slh-dsa` depending on the key type.
 /// Existing callers can continue passing `&secret` directly.
+///
+/// Signing with a secret meant for key agreement is handled by the secret's
+/// own `KeyUsagePolicy`: a warning by default, an error once set to
+/// `Strict`. `SecretsResolver::get_secret_for` hands out secrets carrying the
+/// resolver's policy; set one directly with `Secret::with_key_usage_policy`.
 #[async_trait]
 impl Signer for Secret {
     fn key_type(&self) -> KeyType {
@@ -93,6 +101,8 @@ impl Signer for Secret {
     }
 
     async fn sign(&self, data: &[u8]) -> Result<Vec<u8>, DataIntegrityError> {
+        self.check_usage(KeyPurpose::Signing, self.key_usage_policy())
+            .map_err(DataIntegrityError::signing)?;
         match self.get_key_type() {
             KeyType::Ed25519 => {
                 // Wrap the stack copy in Zeroizing so it clears on
@@ -135,3 +145,19 @@ impl Signer for Secret {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use affinidi_secrets_resolver::usage::KeyUsagePolicy;
+
+    #[tokio::test]
+    async fn signing_honours_the_secrets_key_usage_policy() {
+        let agreement = Secret::generate_ed25519(Some("did:example:a#1"), None)
+            .with_purposes(&[KeyPurpose::KeyAgreement]);
+
+        assert!(agreement.sign(b"data").await.is_ok());
+        let strict = agreement.with_key_usage_policy(KeyUsagePolicy::Strict);
+        assert!(strict.sign(b"data").await.is_err());
+    }
+}
@@ -73,26 +73,29 @@ pub struct EddsaJcs2022;
 /// `eddsa-rdfc-2022` — EdDSA signatures over RDFC-canonicalized documents.
 pub struct EddsaRdfc2022;
 
+/// Strict Ed25519 verification via [`affinidi_crypto::signature`], which
+/// rejects non-canonical signatures and small-order keys.
 fn eddsa_verify(key: &[u8], data: &[u8], sig: &[u8]) -> Result<(), DataIntegrityError> {
     use crate::SignatureFailure;
-    use ed25519_dalek::{Signature, VerifyingKey};
+    use affinidi_crypto::{
+        CryptoError,
+        signature::{SignatureAlgorithm, verify_public_key},
+    };
 
-    let verifying_key =
-        VerifyingKey::try_from(key).map_err(|_| DataIntegrityError::InvalidPublicKey {
+    verify_public_key(SignatureAlgorithm::EdDSA, key, data, sig).map_err(|e| match e {
+        CryptoError::KeyError(reason) => DataIntegrityError::InvalidPublicKey {
             codec: None,
             len: key.len(),
-            reason: "invalid Ed25519 public key bytes".to_string(),
-        })?;
-    let signature =
-        Signature::from_slice(sig).map_err(|_| DataIntegrityError::InvalidSignature {
+            reason,
+        },
+        CryptoError::Decoding(_) => DataIntegrityError::InvalidSignature {
             suite: CryptoSuite::EddsaJcs2022,
             reason: SignatureFailure::Malformed,
-        })?;
-    verifying_key.verify_strict(data, &signature).map_err(|_| {
-        DataIntegrityError::InvalidSignature {
+        },
+        _ => DataIntegrityError::InvalidSignature {
             suite: CryptoSuite::EddsaJcs2022,
             reason: SignatureFailure::Invalid,
-        }
+        },
     })
 }
 
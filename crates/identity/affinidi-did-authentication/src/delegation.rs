@@ -0,0 +1,842 @@
+/*!
+ * Time-bound delegated authorization between DIDs.
+ *
+ * A delegation token lets one DID (the issuer) grant another DID (the
+ * audience) a set of capabilities — "send messages on my behalf", "read my
+ * messages" — until an expiry time, optionally narrowed by caveats. Tokens
+ * are UCAN-style compact JWS: the claims are signed with a key from the
+ * issuer's DID document (`capabilityDelegation`, falling back to
+ * `authentication`), so a service can verify them with nothing but a DID
+ * resolver.
+ *
+ * Tokens can be re-delegated. The audience of a token issues a new token
+ * that embeds the original in `prf`; the new token may only narrow what it
+ * received: same or fewer capabilities, the same caveats plus any new ones,
+ * and an expiry no later than its proof's.
+ *
+ * ```ignore
+ * // Profile A lets device B send on its behalf for a day.
+ * let token = DelegationBuilder::new(&profile_a, &device_b)
+ *     .capability(&profile_a, Ability::SendOnBehalf)
+ *     .expires_in(Duration::from_secs(86_400))
+ *     .caveat("recipients", json!(["did:example:bob"]))
+ *     .sign(&did_resolver, &secrets_resolver)
+ *     .await?;
+ *
+ * // The mediator checks it when B sends a message as A.
+ * let verified = verify_delegation(
+ *     &token,
+ *     &did_resolver,
+ *     &DelegationCheck::new(&device_b).require(&profile_a, Ability::SendOnBehalf),
+ * )
+ * .await?;
+ * ```
+ */
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    time::{Duration, SystemTime},
+};
+
+use affinidi_crypto::{KeyType, jose::signing};
+use affinidi_did_common::{
+    Document,
+    document::DocumentExt,
+    verification_method::{VerificationMethod, VerificationRelationship},
+};
+use affinidi_did_resolver_cache_sdk::DIDCacheClient;
+use affinidi_encoding::{ED25519_PUB, P256_PUB, SECP256K1_PUB};
+use affinidi_secrets_resolver::{SecretsResolver, usage::KeyPurpose};
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::errors::{DIDAuthError, Result};
+
+/// Longest chain of re-delegations accepted by [`verify_delegation`].
+pub const MAX_PROOF_DEPTH: usize = 8;
+
+/// Authentication response body field carrying the delegation tokens the
+/// authenticating DID presents to the service
+pub const DELEGATIONS_FIELD: &str = "delegations";
+
+/// JWT `typ` of a delegation token.
+const TOKEN_TYPE: &str = "JWT";
+
+/// UCAN spec version recorded in the token header.
+const UCAN_VERSION: &str = "0.10.0";
+
+/// What a capability allows the audience to do.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Ability {
+    /// Send messages as the resource owner (`messaging/send`).
+    SendOnBehalf,
+    /// Fetch and read the resource owner's messages (`messaging/read`).
+    ReadMessages,
+    /// Any other application-defined ability.
+    Other(String),
+}
+
+impl Ability {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Ability::SendOnBehalf => "messaging/send",
+            Ability::ReadMessages => "messaging/read",
+            Ability::Other(other) => other,
+        }
+    }
+}
+
+impl From<String> for Ability {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "messaging/send" => Ability::SendOnBehalf,
+            "messaging/read" => Ability::ReadMessages,
+            _ => Ability::Other(value),
+        }
+    }
+}
+
+impl From<Ability> for String {
+    fn from(value: Ability) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An ability over a resource. `with` identifies the resource, normally the
+/// DID whose messages or identity the capability covers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Capability {
+    pub with: String,
+    pub can: Ability,
+}
+
+impl Capability {
+    pub fn new(with: impl Into<String>, can: Ability) -> Self {
+        Capability {
+            with: with.into(),
+            can,
+        }
+    }
+}
+
+/// Claims carried by a delegation token.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DelegationClaims {
+    /// DID granting the capabilities
+    pub iss: String,
+    /// DID receiving the capabilities
+    pub aud: String,
+    /// Capabilities granted
+    pub att: Vec<Capability>,
+    /// Not valid before (seconds since UNIX epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    /// Expiry (seconds since UNIX epoch)
+    pub exp: u64,
+    /// Nonce, makes every token unique
+    pub nnc: String,
+    /// Caveats: restrictions the service enforces on every use
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fct: BTreeMap<String, Value>,
+    /// Parent tokens this delegation is derived from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prf: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    alg: String,
+    typ: String,
+    kid: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ucv: Option<String>,
+}
+
+/// Builds and signs a delegation token.
+#[derive(Clone, Debug)]
+pub struct DelegationBuilder {
+    claims: DelegationClaims,
+    lifetime: Duration,
+}
+
+impl DelegationBuilder {
+    /// Delegation from `issuer` to `audience`, valid for one hour unless
+    /// changed with [`Self::expires_in`].
+    pub fn new(issuer: impl Into<String>, audience: impl Into<String>) -> Self {
+        DelegationBuilder {
+            claims: DelegationClaims {
+                iss: issuer.into(),
+                aud: audience.into(),
+                att: Vec::new(),
+                nbf: None,
+                exp: 0,
+                nnc: Uuid::new_v4().to_string(),
+                fct: BTreeMap::new(),
+                prf: Vec::new(),
+            },
+            lifetime: Duration::from_secs(3600),
+        }
+    }
+
+    /// Grant `can` over the resource `with`.
+    pub fn capability(mut self, with: impl Into<String>, can: Ability) -> Self {
+        self.claims.att.push(Capability::new(with, can));
+        self
+    }
+
+    /// Token lifetime, counted from the moment it is signed. Capped at the
+    /// expiry of any [`Self::proof`].
+    pub fn expires_in(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Token is not valid before `not_before` (seconds since UNIX epoch).
+    pub fn not_before(mut self, not_before: u64) -> Self {
+        self.claims.nbf = Some(not_before);
+        self
+    }
+
+    /// Add a caveat. Services must enforce caveats they understand and
+    /// should refuse tokens carrying caveats they do not.
+    pub fn caveat(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.claims.fct.insert(name.into(), value);
+        self
+    }
+
+    /// Derive this delegation from `token`, which must have been issued to
+    /// this builder's issuer.
+    pub fn proof(mut self, token: impl Into<String>) -> Self {
+        self.claims.prf.push(token.into());
+        self
+    }
+
+    /// Sign with the issuer's first `capabilityDelegation` (or, failing
+    /// that, `authentication`) key that `secrets_resolver` holds and allows
+    /// signing with.
+    ///
+    /// Ed25519 and P-256 keys are supported.
+    pub async fn sign<S>(
+        mut self,
+        did_resolver: &DIDCacheClient,
+        secrets_resolver: &S,
+    ) -> Result<String>
+    where
+        S: SecretsResolver,
+    {
+        if self.claims.att.is_empty() {
+            return Err(DIDAuthError::Delegation(
+                "a delegation must grant at least one capability".to_string(),
+            ));
+        }
+        self.claims.exp = now() + self.lifetime.as_secs();
+        // A delegation cannot outlive its proofs; clamp rather than issue a
+        // token that will never verify.
+        for proof in &self.claims.prf {
+            let (_, parent, _, _) = decode(proof)?;
+            self.claims.exp = self.claims.exp.min(parent.exp);
+        }
+
+        let doc = did_resolver.resolve(&self.claims.iss).await?.doc;
+        let mut secret = None;
+        // Keys the resolver doesn't hold, or won't sign with, are skipped
+        for kid in delegation_key_ids(&doc) {
+            if let Ok(Some(found)) = secrets_resolver
+                .get_secret_for(&kid, KeyPurpose::Signing)
+                .await
+            {
+                secret = Some((kid, found));
+                break;
+            }
+        }
+        let Some((kid, secret)) = secret else {
+            return Err(DIDAuthError::Secrets(format!(
+                "no delegation or authentication key for {} in the secrets resolver",
+                self.claims.iss
+            )));
+        };
+
+        let alg = match secret.get_key_type() {
+            KeyType::Ed25519 => "EdDSA",
+            KeyType::P256 => "ES256",
+            other => {
+                return Err(DIDAuthError::Delegation(format!(
+                    "unsupported signing key type ({other:?}) for {kid}"
+                )));
+            }
+        };
+        let header = Header {
+            alg: alg.to_string(),
+            typ: TOKEN_TYPE.to_string(),
+            kid: kid.clone(),
+            ucv: Some(UCAN_VERSION.to_string()),
+        };
+        let signing_input = format!("{}.{}", encode_json(&header)?, encode_json(&self.claims)?);
+
+        let signature = match secret.get_key_type() {
+            KeyType::Ed25519 => {
+                let private: &[u8; 32] = secret.get_private_bytes().try_into().map_err(|_| {
+                    DIDAuthError::Secrets(format!("{kid}: Ed25519 private key must be 32 bytes"))
+                })?;
+                signing::sign(signing_input.as_bytes(), private)
+                    .map_err(|e| DIDAuthError::Delegation(format!("signing failed: {e}")))?
+                    .to_vec()
+            }
+            _ => affinidi_crypto::p256::sign(secret.get_private_bytes(), signing_input.as_bytes())
+                .map_err(|e| DIDAuthError::Delegation(format!("signing failed: {e}")))?,
+        };
+
+        Ok(format!(
+            "{signing_input}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+}
+
+/// What the verifying service requires of a token.
+#[derive(Clone, Debug)]
+pub struct DelegationCheck {
+    audience: String,
+    required: Vec<Capability>,
+    now: Option<u64>,
+}
+
+impl DelegationCheck {
+    /// The token must have been issued to `audience` — normally the DID that
+    /// authenticated the request presenting it.
+    pub fn new(audience: impl Into<String>) -> Self {
+        DelegationCheck {
+            audience: audience.into(),
+            required: Vec::new(),
+            now: None,
+        }
+    }
+
+    /// The token must grant `can` over `with`.
+    pub fn require(mut self, with: impl Into<String>, can: Ability) -> Self {
+        self.required.push(Capability::new(with, can));
+        self
+    }
+
+    /// Evaluate time bounds at `now` (seconds since UNIX epoch) instead of
+    /// the current time.
+    pub fn at(mut self, now: u64) -> Self {
+        self.now = Some(now);
+        self
+    }
+}
+
+/// A delegation token that passed [`verify_delegation`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifiedDelegation {
+    pub issuer: String,
+    pub audience: String,
+    pub capabilities: Vec<Capability>,
+    /// Caveats of the token and every proof in its chain. The caller is
+    /// responsible for enforcing them.
+    pub caveats: BTreeMap<String, Value>,
+    pub expires_at: u64,
+}
+
+impl VerifiedDelegation {
+    pub fn allows(&self, with: &str, can: &Ability) -> bool {
+        self.capabilities
+            .iter()
+            .any(|capability| capability.with == with && &capability.can == can)
+    }
+}
+
+/// Verify `token` and its proof chain against the DID documents of every
+/// issuer in it, then apply `check`.
+pub async fn verify_delegation(
+    token: &str,
+    did_resolver: &DIDCacheClient,
+    check: &DelegationCheck,
+) -> Result<VerifiedDelegation> {
+    let now = check.now.unwrap_or_else(now);
+    let claims = verify_chain(token, did_resolver, now, 0).await?;
+
+    if claims.aud != check.audience {
+        return Err(DIDAuthError::Delegation(format!(
+            "token was issued to {}, not {}",
+            claims.aud, check.audience
+        )));
+    }
+    let verified = VerifiedDelegation {
+        issuer: claims.iss,
+        audience: claims.aud,
+        capabilities: claims.att,
+        caveats: claims.fct,
+        expires_at: claims.exp,
+    };
+    if let Some(missing) = check
+        .required
+        .iter()
+        .find(|required| !verified.allows(&required.with, &required.can))
+    {
+        return Err(DIDAuthError::ACLDenied(format!(
+            "delegation does not grant {} over {}",
+            missing.can, missing.with
+        )));
+    }
+    Ok(verified)
+}
+
+/// Verify the signature and time bounds of `token`, then recursively its
+/// proofs, returning the claims once the chain checks out.
+fn verify_chain<'a>(
+    token: &'a str,
+    did_resolver: &'a DIDCacheClient,
+    now: u64,
+    depth: usize,
+) -> Pin<Box<dyn Future<Output = Result<DelegationClaims>> + Send + 'a>> {
+    Box::pin(async move {
+        if depth > MAX_PROOF_DEPTH {
+            return Err(DIDAuthError::Delegation(format!(
+                "proof chain is longer than {MAX_PROOF_DEPTH}"
+            )));
+        }
+        let (header, claims, signing_input, signature) = decode(token)?;
+        let doc = did_resolver.resolve(&claims.iss).await?.doc;
+        verify_signature(&doc, &claims.iss, &header, &signing_input, &signature)?;
+        check_time(&claims, now)?;
+
+        let mut parents = Vec::with_capacity(claims.prf.len());
+        for proof in &claims.prf {
+            parents.push(verify_chain(proof, did_resolver, now, depth + 1).await?);
+        }
+        check_attenuation(&claims, &parents)?;
+        Ok(claims)
+    })
+}
+
+/// Split a compact JWS into its decoded parts.
+fn decode(token: &str) -> Result<(Header, DelegationClaims, String, Vec<u8>)> {
+    let mut parts = token.split('.');
+    let (Some(header), Some(payload), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(DIDAuthError::Delegation(
+            "token is not a compact JWS".to_string(),
+        ));
+    };
+    let decode_part = |part: &str, name: &str| {
+        BASE64_URL_SAFE_NO_PAD
+            .decode(part)
+            .map_err(|e| DIDAuthError::Delegation(format!("invalid {name} encoding: {e}")))
+    };
+    let parsed_header: Header = serde_json::from_slice(&decode_part(header, "header")?)
+        .map_err(|e| DIDAuthError::Delegation(format!("invalid header: {e}")))?;
+    let claims: DelegationClaims = serde_json::from_slice(&decode_part(payload, "payload")?)
+        .map_err(|e| DIDAuthError::Delegation(format!("invalid claims: {e}")))?;
+    Ok((
+        parsed_header,
+        claims,
+        format!("{header}.{payload}"),
+        decode_part(signature, "signature")?,
+    ))
+}
+
+fn verify_signature(
+    doc: &Document,
+    issuer: &str,
+    header: &Header,
+    signing_input: &str,
+    signature: &[u8],
+) -> Result<()> {
+    if header.kid.split('#').next() != Some(issuer) {
+        return Err(DIDAuthError::Delegation(format!(
+            "signing key {} does not belong to issuer {issuer}",
+            header.kid
+        )));
+    }
+    if !delegation_key_ids(doc).contains(&header.kid) {
+        return Err(DIDAuthError::Delegation(format!(
+            "{} is not a delegation or authentication key of {issuer}",
+            header.kid
+        )));
+    }
+    let (codec, public_key) = find_method(doc, &header.kid)
+        .ok_or_else(|| {
+            DIDAuthError::Delegation(format!("verification method {} not found", header.kid))
+        })?
+        .decode_public_key()
+        .map_err(|e| DIDAuthError::Delegation(format!("{}: {e}", header.kid)))?;
+
+    let signature: &[u8; 64] = signature
+        .try_into()
+        .map_err(|_| DIDAuthError::Delegation("signature must be 64 bytes".to_string()))?;
+    let data = signing_input.as_bytes();
+    let result = match (codec, header.alg.as_str()) {
+        (ED25519_PUB, "EdDSA") => {
+            let public_key: &[u8; 32] = public_key.as_slice().try_into().map_err(|_| {
+                DIDAuthError::Delegation("Ed25519 public key must be 32 bytes".to_string())
+            })?;
+            signing::verify(data, signature, public_key)
+        }
+        (P256_PUB, "ES256") => signing::verify_p256(data, signature, &public_key),
+        (SECP256K1_PUB, "ES256K") => signing::verify_secp256k1(data, signature, &public_key),
+        (_, alg) => {
+            return Err(DIDAuthError::Delegation(format!(
+                "algorithm {alg} does not match key {}",
+                header.kid
+            )));
+        }
+    };
+    result.map_err(|e| DIDAuthError::Delegation(format!("invalid signature: {e}")))
+}
+
+fn check_time(claims: &DelegationClaims, now: u64) -> Result<()> {
+    if claims.exp <= now {
+        return Err(DIDAuthError::Delegation(format!(
+            "delegation from {} expired at {}",
+            claims.iss, claims.exp
+        )));
+    }
+    if let Some(nbf) = claims.nbf
+        && nbf > now
+    {
+        return Err(DIDAuthError::Delegation(format!(
+            "delegation from {} is not valid before {nbf}",
+            claims.iss
+        )));
+    }
+    Ok(())
+}
+
+/// A token may only narrow what its proofs grant. Without proofs the issuer
+/// can only delegate capabilities over itself.
+fn check_attenuation(claims: &DelegationClaims, parents: &[DelegationClaims]) -> Result<()> {
+    if claims.att.is_empty() {
+        return Err(DIDAuthError::Delegation(
+            "delegation grants no capabilities".to_string(),
+        ));
+    }
+    for parent in parents {
+        if parent.aud != claims.iss {
+            return Err(DIDAuthError::Delegation(format!(
+                "proof was issued to {}, not to {}",
+                parent.aud, claims.iss
+            )));
+        }
+        if claims.exp > parent.exp {
+            return Err(DIDAuthError::Delegation(
+                "delegation outlives its proof".to_string(),
+            ));
+        }
+        if let Some((name, _)) = parent
+            .fct
+            .iter()
+            .find(|(name, value)| claims.fct.get(*name) != Some(value))
+        {
+            return Err(DIDAuthError::Delegation(format!(
+                "delegation drops or changes caveat {name} of its proof"
+            )));
+        }
+    }
+    for capability in &claims.att {
+        let granted = capability.with == claims.iss
+            || parents.iter().any(|parent| parent.att.contains(capability));
+        if !granted {
+            return Err(DIDAuthError::Delegation(format!(
+                "{} cannot delegate {} over {}",
+                claims.iss, capability.can, capability.with
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Absolute ids of the keys allowed to sign delegations, in order of
+/// preference.
+fn delegation_key_ids(doc: &Document) -> Vec<String> {
+    doc.capability_delegation
+        .iter()
+        .chain(doc.authentication.iter())
+        .map(|relationship| absolute_id(doc, relationship.get_id()))
+        .collect()
+}
+
+fn absolute_id(doc: &Document, id: &str) -> String {
+    if id.starts_with('#') {
+        format!("{}{id}", doc.id)
+    } else {
+        id.to_string()
+    }
+}
+
+/// Verification method `kid`, whether it is embedded in a relationship or
+/// listed in `verificationMethod`.
+fn find_method<'a>(doc: &'a Document, kid: &str) -> Option<&'a VerificationMethod> {
+    let embedded = doc
+        .capability_delegation
+        .iter()
+        .chain(doc.authentication.iter())
+        .find_map(|relationship| match relationship {
+            VerificationRelationship::VerificationMethod(vm)
+                if absolute_id(doc, vm.id.as_str()) == kid =>
+            {
+                Some(vm.as_ref())
+            }
+            _ => None,
+        });
+    embedded
+        .or_else(|| doc.get_verification_method(kid))
+        .or_else(|| {
+            doc.verification_method
+                .iter()
+                .find(|vm| absolute_id(doc, vm.id.as_str()) == kid)
+        })
+}
+
+fn encode_json<T: Serialize>(value: &T) -> Result<String> {
+    let json = serde_json::to_vec(value)
+        .map_err(|e| DIDAuthError::Delegation(format!("could not serialize token: {e}")))?;
+    Ok(BASE64_URL_SAFE_NO_PAD.encode(json))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use affinidi_did_resolver_cache_sdk::config::DIDCacheConfigBuilder;
+    use affinidi_secrets_resolver::{
+        SimpleSecretsResolver, secrets::Secret, usage::KeyUsagePolicy,
+    };
+    use serde_json::json;
+
+    /// A did:key profile and a resolver holding its secret.
+    async fn profile(seed: u8) -> (String, SimpleSecretsResolver) {
+        let mut secret = Secret::generate_ed25519(None, Some(&[seed; 32]));
+        let multibase = secret.get_public_keymultibase().unwrap();
+        let did = format!("did:key:{multibase}");
+        secret.id = format!("{did}#{multibase}");
+        (did, SimpleSecretsResolver::new(&[secret]).await)
+    }
+
+    async fn resolver() -> DIDCacheClient {
+        DIDCacheClient::new(DIDCacheConfigBuilder::default().build())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn delegation_round_trip() {
+        let resolver = resolver().await;
+        let (alice, alice_secrets) = profile(1).await;
+        let (device, _) = profile(2).await;
+
+        let token = DelegationBuilder::new(&alice, &device)
+            .capability(&alice, Ability::SendOnBehalf)
+            .caveat("recipients", json!(["did:example:bob"]))
+            .sign(&resolver, &alice_secrets)
+            .await
+            .unwrap();
+
+        let verified = verify_delegation(
+            &token,
+            &resolver,
+            &DelegationCheck::new(&device).require(&alice, Ability::SendOnBehalf),
+        )
+        .await
+        .unwrap();
+        assert_eq!(verified.issuer, alice);
+        assert_eq!(verified.caveats["recipients"], json!(["did:example:bob"]));
+
+        let err = verify_delegation(
+            &token,
+            &resolver,
+            &DelegationCheck::new(&device).require(&alice, Ability::ReadMessages),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, DIDAuthError::ACLDenied(_)), "{err}");
+
+        assert!(
+            verify_delegation(&token, &resolver, &DelegationCheck::new(&alice))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_expired_and_tampered_tokens() {
+        let resolver = resolver().await;
+        let (alice, alice_secrets) = profile(1).await;
+        let (device, _) = profile(2).await;
+
+        let token = DelegationBuilder::new(&alice, &device)
+            .capability(&alice, Ability::ReadMessages)
+            .expires_in(Duration::from_secs(60))
+            .sign(&resolver, &alice_secrets)
+            .await
+            .unwrap();
+        let later = DelegationCheck::new(&device).at(now() + 120);
+        assert!(verify_delegation(&token, &resolver, &later).await.is_err());
+
+        let (header, claims, _, _) = decode(&token).unwrap();
+        let mut widened = claims.clone();
+        widened
+            .att
+            .push(Capability::new(&alice, Ability::SendOnBehalf));
+        let signature = token.rsplit('.').next().unwrap();
+        let forged = format!(
+            "{}.{}.{signature}",
+            encode_json(&header).unwrap(),
+            encode_json(&widened).unwrap()
+        );
+        assert!(
+            verify_delegation(&forged, &resolver, &DelegationCheck::new(&device))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn redelegation_can_only_narrow() {
+        let resolver = resolver().await;
+        let (alice, alice_secrets) = profile(1).await;
+        let (service, service_secrets) = profile(2).await;
+        let (worker, _) = profile(3).await;
+
+        let root = DelegationBuilder::new(&alice, &service)
+            .capability(&alice, Ability::SendOnBehalf)
+            .capability(&alice, Ability::ReadMessages)
+            .caveat("max_messages", json!(100))
+            .sign(&resolver, &alice_secrets)
+            .await
+            .unwrap();
+
+        let narrowed = DelegationBuilder::new(&service, &worker)
+            .capability(&alice, Ability::ReadMessages)
+            .caveat("max_messages", json!(100))
+            .expires_in(Duration::from_secs(600))
+            .proof(&root)
+            .sign(&resolver, &service_secrets)
+            .await
+            .unwrap();
+        let verified = verify_delegation(
+            &narrowed,
+            &resolver,
+            &DelegationCheck::new(&worker).require(&alice, Ability::ReadMessages),
+        )
+        .await
+        .unwrap();
+        assert!(!verified.allows(&alice, &Ability::SendOnBehalf));
+
+        let dropped_caveat = DelegationBuilder::new(&service, &worker)
+            .capability(&alice, Ability::ReadMessages)
+            .expires_in(Duration::from_secs(600))
+            .proof(&root)
+            .sign(&resolver, &service_secrets)
+            .await
+            .unwrap();
+        assert!(
+            verify_delegation(&dropped_caveat, &resolver, &DelegationCheck::new(&worker))
+                .await
+                .is_err()
+        );
+
+        let escalated = DelegationBuilder::new(&service, &worker)
+            .capability(&alice, Ability::Other("profile/admin".into()))
+            .caveat("max_messages", json!(100))
+            .expires_in(Duration::from_secs(600))
+            .proof(&root)
+            .sign(&resolver, &service_secrets)
+            .await
+            .unwrap();
+        assert!(
+            verify_delegation(&escalated, &resolver, &DelegationCheck::new(&worker))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn sign_skips_keys_refused_for_signing() {
+        let mut resolver = resolver().await;
+        let (alice, _) = profile(1).await;
+        let mut signing = Secret::generate_ed25519(None, Some(&[1; 32]));
+        let multibase = signing.get_public_keymultibase().unwrap();
+        signing.id = format!("{alice}#{multibase}");
+
+        // List an agreement-only key ahead of the signing key
+        let mut agreement = Secret::generate_ed25519(None, Some(&[9; 32]))
+            .to_x25519()
+            .unwrap();
+        agreement.id = format!("{alice}#agreement");
+        let mut doc = serde_json::to_value(&*resolver.resolve(&alice).await.unwrap().doc).unwrap();
+        doc["verificationMethod"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({
+                "id": agreement.id,
+                "type": "Multikey",
+                "controller": alice,
+                "publicKeyMultibase": agreement.get_public_keymultibase().unwrap(),
+            }));
+        doc["capabilityDelegation"]
+            .as_array_mut()
+            .unwrap()
+            .insert(0, json!(agreement.id));
+        resolver
+            .add_did_document(&alice, serde_json::from_value(doc).unwrap())
+            .await;
+
+        let secrets = SimpleSecretsResolver::new(&[agreement, signing.clone()])
+            .await
+            .with_key_usage_policy(KeyUsagePolicy::Strict);
+        let (device, _) = profile(2).await;
+        let token = DelegationBuilder::new(&alice, &device)
+            .capability(&alice, Ability::SendOnBehalf)
+            .sign(&resolver, &secrets)
+            .await
+            .unwrap();
+        let (header, _, _, _) = decode(&token).unwrap();
+        assert_eq!(header.kid, signing.id);
+        assert!(
+            verify_delegation(&token, &resolver, &DelegationCheck::new(&device))
+                .await
+                .is_ok()
+        );
+
+        // Nothing left once the signing key is gone
+        secrets.remove_secret(&signing.id).await;
+        assert!(matches!(
+            DelegationBuilder::new(&alice, &device)
+                .capability(&alice, Ability::SendOnBehalf)
+                .sign(&resolver, &secrets)
+                .await,
+            Err(DIDAuthError::Secrets(_))
+        ));
+    }
+
+    #[test]
+    fn abilities_serialize_as_strings() {
+        let capability = Capability::new("did:example:alice", Ability::SendOnBehalf);
+        assert_eq!(
+            serde_json::to_value(&capability).unwrap(),
+            json!({ "with": "did:example:alice", "can": "messaging/send" })
+        );
+        let custom: Ability = serde_json::from_value(json!("calendar/write")).unwrap();
+        assert_eq!(custom, Ability::Other("calendar/write".into()));
+    }
+}
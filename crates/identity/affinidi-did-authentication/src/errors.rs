@@ -35,6 +35,14 @@ pub enum DIDAuthError {
 
     #[error("Secrets Error: {0}")]
     Secrets(String),
+
+    /// Delegation token is malformed, badly signed, expired or over-reaching
+    #[error("Delegation error: {0}")]
+    Delegation(String),
+
+    /// The service's token response couldn't be tied to its DID, cannot be retried
+    #[error("Service verification failed: {0}")]
+    ServiceVerification(String),
 }
 
 pub type Result<T> = std::result::Result<T, DIDAuthError>;
@@ -7,6 +7,9 @@
  * Step 3. Sign and Encrypt the DIDComm message, send to the authentication service
  * Step 4. Receive the tokens from the authentication service
  *
+ * With mutual authentication (see [`mutual`]) the service also proves itself: it
+ * returns the tokens sealed in a DIDComm message from its DID to the profile.
+ *
  * NOTE: This library currently supports two different implementations of DID Auth
  * 1. Affinidi Messaging
  * 2. MeetingPlace
@@ -22,21 +25,25 @@ use affinidi_did_common::{
 };
 use affinidi_did_resolver_cache_sdk::DIDCacheClient;
 use affinidi_messaging_didcomm::message::{Message, pack};
-use affinidi_secrets_resolver::SecretsResolver;
+use affinidi_secrets_resolver::{SecretsResolver, usage::KeyPurpose};
 use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 use chrono::DateTime;
 use errors::{DIDAuthError, Result};
+use mutual::{MUTUAL_AUTH_HINT, Sealable};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::time::SystemTime;
-use tracing::{Instrument, Level, debug, error, info, span, trace};
+use tracing::{Instrument, Level, debug, error, info, span, trace, warn};
 use uuid::Uuid;
 
 pub mod custom_auth;
+pub mod delegation;
 pub mod errors;
+pub mod mutual;
 
 pub use custom_auth::{CustomAuthHandler, CustomAuthHandlers, CustomRefreshHandler};
+pub use mutual::ServiceVerification;
 
 /// The authorization tokens received in the fourth step of the DID authentication process
 #[derive(Serialize, Deserialize, Default, Clone)]
@@ -200,6 +207,12 @@ pub struct DIDAuthentication {
 
     /// Custom authentication handlers
     pub custom_handlers: Option<CustomAuthHandlers>,
+
+    /// How strictly to verify that tokens came from the service DID
+    pub service_verification: ServiceVerification,
+
+    /// true once the service has proven the tokens came from its DID
+    pub service_verified: bool,
 }
 
 impl std::fmt::Debug for DIDAuthentication {
@@ -209,6 +222,8 @@ impl std::fmt::Debug for DIDAuthentication {
             .field("tokens", &self.tokens)
             .field("authenticated", &self.authenticated)
             .field("custom_handlers", &self.custom_handlers.is_some())
+            .field("service_verification", &self.service_verification)
+            .field("service_verified", &self.service_verified)
             .finish()
     }
 }
@@ -220,6 +235,8 @@ impl Default for DIDAuthentication {
             tokens: None,
             authenticated: false,
             custom_handlers: None,
+            service_verification: ServiceVerification::default(),
+            service_verified: false,
         }
     }
 }
@@ -235,6 +252,12 @@ impl DIDAuthentication {
         self
     }
 
+    /// Set how strictly the service's token responses are verified
+    pub fn with_service_verification(mut self, verification: ServiceVerification) -> Self {
+        self.service_verification = verification;
+        self
+    }
+
     /// Find the [serviceEndpoint](https://www.w3.org/TR/did-1.0/#services) with type `Authentication` from a DID Document
     /// # Arguments
     /// * `doc` - The DID Document to search
@@ -318,6 +341,9 @@ impl DIDAuthentication {
                 Err(DIDAuthError::ACLDenied(err)) => {
                     return Err(DIDAuthError::ACLDenied(err));
                 }
+                Err(DIDAuthError::ServiceVerification(err)) => {
+                    return Err(DIDAuthError::ServiceVerification(err));
+                }
                 Err(err) => {
                     retry_count += 1;
                     if retry_limit != -1 && retry_count >= retry_limit {
@@ -430,15 +456,34 @@ impl DIDAuthentication {
             };
 
             let step2_response =
-                _http_post::<TokensType>(client, &[&endpoint, ""].concat(), &step2_body).await?;
+                _http_post::<Sealable<TokensType>>(client, &[&endpoint, ""].concat(), &step2_body)
+                    .await?;
 
             debug!("Tokens received");
             trace_sensitive("Tokens received", &format!("{step2_response:#?}"));
 
+            let tokens = match step2_response {
+                Sealable::Sealed(sealed) => {
+                    self._open_sealed(
+                        &sealed.data,
+                        &auth_response.id,
+                        profile_did,
+                        endpoint_did,
+                        did_resolver,
+                        secrets_resolver,
+                    )
+                    .await?
+                }
+                Sealable::Plain(plain) => {
+                    self._accept_unsealed(endpoint_did)?;
+                    plain.tokens()?
+                }
+            };
+
             debug!("Successfully authenticated");
 
             self.authenticated = true;
-            self.tokens = Some(step2_response.tokens()?);
+            self.tokens = Some(tokens);
             Ok(())
         }
         .instrument(_span)
@@ -468,18 +513,69 @@ impl DIDAuthentication {
         }
     }
 
+    /// Open tokens sealed by the service and record that it proved itself
+    async fn _open_sealed<T, S>(
+        &mut self,
+        sealed: &str,
+        request_id: &str,
+        profile_did: &str,
+        endpoint_did: &str,
+        did_resolver: &DIDCacheClient,
+        secrets_resolver: &S,
+    ) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        S: SecretsResolver,
+    {
+        let service = did_resolver.resolve(endpoint_did).await?;
+        let tokens = mutual::open_sealed(
+            sealed,
+            profile_did,
+            endpoint_did,
+            request_id,
+            &service.doc,
+            secrets_resolver,
+        )
+        .await?;
+
+        debug!("Service ({endpoint_did}) verified as the token issuer");
+        self.service_verified = true;
+        Ok(tokens)
+    }
+
+    /// Apply the [`ServiceVerification`] policy to tokens the service didn't seal
+    fn _accept_unsealed(&mut self, endpoint_did: &str) -> Result<()> {
+        if self.service_verified {
+            // A service that sealed before doesn't stop; something in between did
+            return Err(DIDAuthError::ServiceVerification(format!(
+                "({endpoint_did}) previously sealed its tokens but this response is unsealed"
+            )));
+        }
+        match self.service_verification {
+            ServiceVerification::Required => Err(DIDAuthError::ServiceVerification(format!(
+                "({endpoint_did}) returned unsealed tokens; mutual authentication is required"
+            ))),
+            ServiceVerification::Preferred => {
+                warn!(
+                    "Service ({endpoint_did}) doesn't support mutual authentication; tokens are trusted on TLS alone"
+                );
+                Ok(())
+            }
+        }
+    }
+
     /// Refresh the JWT access token
     /// # Arguments
     ///   * `refresh_token` - The refresh token to be used
     /// # Returns
-    /// A packed DIDComm message to be sent
+    /// The refresh message ID and the packed DIDComm message to be sent
     async fn _create_refresh_request<S>(
         &self,
         profile_did: &str,
         endpoint_did: &str,
         did_resolver: &DIDCacheClient,
         secrets_resolver: &S,
-    ) -> Result<String>
+    ) -> Result<(String, String)>
     where
         S: SecretsResolver,
     {
@@ -499,7 +595,7 @@ impl DIDAuthentication {
         let refresh_message = Message::build(
             Uuid::new_v4().to_string(),
             "https://affinidi.com/atm/1.0/authenticate/refresh".to_string(),
-            json!({"refresh_token": refresh_token}),
+            json!({"refresh_token": refresh_token, MUTUAL_AUTH_HINT: true}),
         )
         .to(endpoint_did.to_string())
         .from(profile_did.to_owned())
@@ -507,7 +603,7 @@ impl DIDAuthentication {
         .expires_time(now + 60)
         .finalize();
 
-        _pack_encrypted_for_did(
+        let packed = _pack_encrypted_for_did(
             &refresh_message,
             profile_did,
             endpoint_did,
@@ -519,7 +615,9 @@ impl DIDAuthentication {
             DIDAuthError::Authentication(format!(
                 "Couldn't pack authentication refresh message: {err:?}"
             ))
-        })
+        })?;
+
+        Ok((refresh_message.id, packed))
     }
 
     /// Refresh the access tokens as required
@@ -564,7 +662,7 @@ impl DIDAuthentication {
 
                 // Refresh using default logic
                 debug!("Refreshing tokens");
-                let refresh_msg = self
+                let (refresh_id, refresh_msg) = self
                     ._create_refresh_request(
                         profile_did,
                         endpoint_did,
@@ -577,12 +675,29 @@ impl DIDAuthentication {
                     ._get_endpoint_address(endpoint_did, did_resolver)
                     .await?;
 
-                let new_tokens = _http_post::<HTTPResponse<AuthRefreshResponse>>(
+                let new_tokens = match _http_post::<Sealable<HTTPResponse<AuthRefreshResponse>>>(
                     client,
                     &[&endpoint, "/refresh"].concat(),
                     &refresh_msg,
                 )
-                .await?;
+                .await?
+                {
+                    Sealable::Sealed(sealed) => {
+                        self._open_sealed::<AuthRefreshResponse, _>(
+                            &sealed.data,
+                            &refresh_id,
+                            profile_did,
+                            endpoint_did,
+                            did_resolver,
+                            secrets_resolver,
+                        )
+                        .await?
+                    }
+                    Sealable::Plain(plain) => {
+                        self._accept_unsealed(endpoint_did)?;
+                        plain.data
+                    }
+                };
 
                 let Some(tokens) = &mut self.tokens else {
                     return Err(DIDAuthError::Authentication(
@@ -590,12 +705,12 @@ impl DIDAuthentication {
                     ));
                 };
 
-                tokens.access_token = new_tokens.data.access_token;
-                tokens.access_expires_at = new_tokens.data.access_expires_at;
+                tokens.access_token = new_tokens.access_token;
+                tokens.access_expires_at = new_tokens.access_expires_at;
                 // Update rotated refresh token if provided
-                if !new_tokens.data.refresh_token.is_empty() {
-                    tokens.refresh_token = new_tokens.data.refresh_token;
-                    tokens.refresh_expires_at = new_tokens.data.refresh_expires_at;
+                if !new_tokens.refresh_token.is_empty() {
+                    tokens.refresh_token = new_tokens.refresh_token;
+                    tokens.refresh_expires_at = new_tokens.refresh_expires_at;
                 }
 
                 debug!("JWT successfully refreshed");
@@ -630,9 +745,9 @@ impl DIDAuthentication {
             .as_secs();
 
         let body = if let DidChallenges::Complex(c) = body {
-            json!({"challenge": c.data.challenge, "session_id": c.session_id})
+            json!({"challenge": c.data.challenge, "session_id": c.session_id, MUTUAL_AUTH_HINT: true})
         } else {
-            json!({"challenge": body.challenge()})
+            json!({"challenge": body.challenge(), MUTUAL_AUTH_HINT: true})
         };
 
         Ok(Message::build(
@@ -725,7 +840,10 @@ where
 
     let mut sender_keys: Vec<(&str, PrivateKeyAgreement, Curve)> = Vec::new();
     for &kid in &sender_ka_kids {
-        let Some(secret) = secrets_resolver.get_secret(kid).await else {
+        let Some(secret) = secrets_resolver
+            .get_secret_for(kid, KeyPurpose::KeyAgreement)
+            .await?
+        else {
             continue;
         };
         let Some(curve) = secret.get_key_type().key_agreement_curve() else {
@@ -0,0 +1,380 @@
+/*!
+ * Mutual authentication: proving the tokens came from the service DID
+ *
+ * A plain DID authentication proves the profile to the service, but the
+ * client only has TLS to tell it who answered. When TLS is misconfigured or
+ * intercepted, whoever terminates it can hand back tokens of its choosing.
+ *
+ * To close that gap the client marks its authentication and refresh messages
+ * with `"mutual_auth": true`. A service that supports it returns the tokens
+ * *sealed*: the response `data` is an authcrypted DIDComm message of type
+ * [`SEALED_TOKENS_TYPE`] from the service DID to the profile DID, threaded to
+ * the request message. Opening it proves the service DID's key agreement key
+ * produced it for this profile and this request.
+ *
+ * Services that predate this return the tokens unsealed. Whether that is
+ * accepted is set by [`ServiceVerification`].
+ */
+
+use crate::{HTTPResponse, errors::DIDAuthError, errors::Result};
+use affinidi_crypto::jose::key_agreement::PrivateKeyAgreement;
+use affinidi_did_common::{Document, document::DocumentExt, key_negotiation};
+use affinidi_messaging_didcomm::{
+    jwe::envelope::{Jwe, ProtectedHeader},
+    message::unpack::{UnpackResult, unpack},
+};
+use affinidi_secrets_resolver::{SecretsResolver, usage::KeyPurpose};
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, de::DeserializeOwned};
+use std::time::SystemTime;
+
+/// DIDComm message type of the sealed tokens a service returns
+pub const SEALED_TOKENS_TYPE: &str = "https://affinidi.com/atm/1.0/authenticate/tokens";
+
+/// Message body field asking the service to seal its tokens
+pub const MUTUAL_AUTH_HINT: &str = "mutual_auth";
+
+/// How strictly to verify that tokens came from the service DID
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServiceVerification {
+    /// Verify sealed tokens; accept unsealed tokens from services that don't
+    /// support mutual authentication, with a warning. Once a service has
+    /// sealed its tokens, an unsealed response from it is rejected.
+    #[default]
+    Preferred,
+
+    /// Only accept sealed tokens that verify
+    Required,
+}
+
+/// A token response as returned by either kind of service
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub(crate) enum Sealable<T> {
+    /// `data` is a packed DIDComm message carrying the tokens
+    Sealed(HTTPResponse<String>),
+    /// Tokens as returned by a service without mutual authentication
+    Plain(T),
+}
+
+/// Open tokens sealed by `service_did` for `profile_did` in answer to the
+/// message `request_id`, and check every binding before trusting them.
+///
+/// # Arguments
+/// * `sealed` - The packed DIDComm message from the response `data`
+/// * `service_doc` - The resolved DID Document of `service_did`
+/// * `secrets_resolver` - Holds the profile's key agreement secrets
+pub(crate) async fn open_sealed<T, S>(
+    sealed: &str,
+    profile_did: &str,
+    service_did: &str,
+    request_id: &str,
+    service_doc: &Document,
+    secrets_resolver: &S,
+) -> Result<T>
+where
+    T: DeserializeOwned,
+    S: SecretsResolver,
+{
+    let jwe: Jwe = serde_json::from_str(sealed)
+        .map_err(|e| rejected(format!("tokens are not a DIDComm encrypted message: {e}")))?;
+    let header: ProtectedHeader = BASE64_URL_SAFE_NO_PAD
+        .decode(&jwe.protected)
+        .ok()
+        .and_then(|header| serde_json::from_slice(&header).ok())
+        .ok_or_else(|| rejected("tokens have an unreadable protected header".into()))?;
+
+    // The sender must be a key agreement key of the service DID itself
+    let Some(sender_kid) = header.skid else {
+        return Err(rejected(
+            "tokens were anoncrypted, the sender is unknown".into(),
+        ));
+    };
+    if did_of(&sender_kid) != service_did {
+        return Err(rejected(format!(
+            "tokens were sealed by ({sender_kid}), not by ({service_did})"
+        )));
+    }
+    if !service_doc
+        .find_key_agreement(None)
+        .contains(&sender_kid.as_str())
+    {
+        return Err(rejected(format!(
+            "({sender_kid}) is not a key agreement key of ({service_did})"
+        )));
+    }
+    let sender_public = key_negotiation::resolve_public_key_agreement(service_doc, &sender_kid)
+        .map_err(|e| rejected(format!("unusable service key ({sender_kid}): {e}")))?;
+
+    // Open with the first of the profile's keys the message is addressed to
+    let mut opened = None;
+    for recipient in &jwe.recipients {
+        let kid = recipient.header.kid.as_str();
+        if did_of(kid) != profile_did {
+            continue;
+        }
+        let Some(secret) = secrets_resolver
+            .get_secret_for(kid, KeyPurpose::KeyAgreement)
+            .await?
+        else {
+            continue;
+        };
+        let Some(curve) = secret.get_key_type().key_agreement_curve() else {
+            continue;
+        };
+        let Ok(private) = PrivateKeyAgreement::from_raw_bytes(curve, secret.get_private_bytes())
+        else {
+            continue;
+        };
+        opened = Some(
+            unpack(
+                sealed,
+                Some(kid),
+                Some(&private),
+                Some(&sender_public),
+                None,
+            )
+            .map_err(|e| rejected(format!("couldn't open sealed tokens: {e}")))?,
+        );
+        break;
+    }
+
+    let message = match opened {
+        Some(UnpackResult::Encrypted {
+            message,
+            authenticated: true,
+            ..
+        }) => message,
+        Some(_) => return Err(rejected("tokens were not authcrypted".into())),
+        None => {
+            return Err(rejected(format!(
+                "tokens were not sealed to a key of ({profile_did})"
+            )));
+        }
+    };
+
+    // The plaintext must agree with the envelope and answer this request
+    if message.typ != SEALED_TOKENS_TYPE {
+        return Err(rejected(format!(
+            "unexpected message type ({})",
+            message.typ
+        )));
+    }
+    if message.from.as_deref() != Some(service_did) {
+        return Err(rejected(format!(
+            "message is from ({:?}), not ({service_did})",
+            message.from
+        )));
+    }
+    if !message
+        .to
+        .as_ref()
+        .is_some_and(|to| to.iter().any(|did| did == profile_did))
+    {
+        return Err(rejected(format!(
+            "message is not addressed to ({profile_did})"
+        )));
+    }
+    if message.thid.as_deref() != Some(request_id) {
+        return Err(rejected("tokens answer a different request".into()));
+    }
+    if let Some(expires) = message.expires_time {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if expires <= now {
+            return Err(rejected("sealed tokens have expired".into()));
+        }
+    }
+
+    serde_json::from_value(message.body)
+        .map_err(|e| rejected(format!("sealed tokens couldn't be parsed: {e}")))
+}
+
+/// The DID part of a DID URL
+fn did_of(kid: &str) -> &str {
+    kid.split('#').next().unwrap_or(kid)
+}
+
+fn rejected(reason: String) -> DIDAuthError {
+    DIDAuthError::ServiceVerification(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuthorizationTokens;
+    use affinidi_crypto::jose::key_agreement::Curve;
+    use affinidi_messaging_didcomm::message::{Message, pack};
+    use affinidi_secrets_resolver::{SimpleSecretsResolver, secrets::Secret};
+    use serde_json::json;
+
+    const SERVICE: &str = "did:web:service.example";
+    const PROFILE: &str = "did:web:profile.example";
+    const SERVICE_KID: &str = "did:web:service.example#key-agreement";
+    const PROFILE_KID: &str = "did:web:profile.example#key-agreement";
+
+    struct Fixture {
+        service_doc: Document,
+        service_private: PrivateKeyAgreement,
+        profile_public: affinidi_crypto::jose::key_agreement::PublicKeyAgreement,
+        secrets: SimpleSecretsResolver,
+    }
+
+    async fn fixture() -> Fixture {
+        let service_private = PrivateKeyAgreement::generate(Curve::X25519);
+        let service_doc: Document = serde_json::from_value(json!({
+            "id": SERVICE,
+            "verificationMethod": [{
+                "id": SERVICE_KID,
+                "type": "JsonWebKey2020",
+                "controller": SERVICE,
+                "publicKeyJwk": service_private.public_key().to_jwk(),
+            }],
+            "keyAgreement": [SERVICE_KID],
+        }))
+        .unwrap();
+
+        let profile_secret = Secret::generate_x25519(Some(PROFILE_KID), None).unwrap();
+        let profile_public =
+            PrivateKeyAgreement::from_raw_bytes(Curve::X25519, profile_secret.get_private_bytes())
+                .unwrap()
+                .public_key();
+
+        Fixture {
+            service_doc,
+            service_private,
+            profile_public,
+            secrets: SimpleSecretsResolver::new(&[profile_secret]).await,
+        }
+    }
+
+    fn tokens() -> AuthorizationTokens {
+        AuthorizationTokens {
+            access_token: "access".into(),
+            access_expires_at: 1,
+            refresh_token: "refresh".into(),
+            refresh_expires_at: 2,
+        }
+    }
+
+    fn seal(f: &Fixture, from: &str, thid: &str) -> String {
+        let msg = Message::build(
+            "tokens-1".to_string(),
+            SEALED_TOKENS_TYPE.to_string(),
+            serde_json::to_value(tokens()).unwrap(),
+        )
+        .from(from.to_string())
+        .to(PROFILE.to_string())
+        .thid(thid.to_string())
+        .finalize();
+        pack::pack_encrypted_authcrypt(
+            &msg,
+            SERVICE_KID,
+            &f.service_private,
+            &[(PROFILE_KID, &f.profile_public)],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sealed_tokens_open_for_the_request() {
+        let f = fixture().await;
+        let sealed = seal(&f, SERVICE, "request-1");
+
+        let opened: AuthorizationTokens = open_sealed(
+            &sealed,
+            PROFILE,
+            SERVICE,
+            "request-1",
+            &f.service_doc,
+            &f.secrets,
+        )
+        .await
+        .unwrap();
+        assert_eq!(opened.access_token, "access");
+        assert_eq!(opened.refresh_expires_at, 2);
+    }
+
+    #[tokio::test]
+    async fn sealed_tokens_are_bound_to_service_and_request() {
+        let f = fixture().await;
+        let sealed = seal(&f, SERVICE, "request-1");
+
+        // Replayed against another request
+        let err = open_sealed::<AuthorizationTokens, _>(
+            &sealed,
+            PROFILE,
+            SERVICE,
+            "request-2",
+            &f.service_doc,
+            &f.secrets,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, DIDAuthError::ServiceVerification(_)), "{err}");
+
+        // Expected from a different service
+        let err = open_sealed::<AuthorizationTokens, _>(
+            &sealed,
+            PROFILE,
+            "did:web:other.example",
+            "request-1",
+            &f.service_doc,
+            &f.secrets,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not by"), "{err}");
+
+        // Plaintext claims another sender than the envelope
+        let sealed = seal(&f, "did:web:other.example", "request-1");
+        let err = open_sealed::<AuthorizationTokens, _>(
+            &sealed,
+            PROFILE,
+            SERVICE,
+            "request-1",
+            &f.service_doc,
+            &f.secrets,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("message is from"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn sealed_tokens_need_the_profile_secret() {
+        let f = fixture().await;
+        let sealed = seal(&f, SERVICE, "request-1");
+        let no_secrets = SimpleSecretsResolver::new(&[]).await;
+
+        let err = open_sealed::<AuthorizationTokens, _>(
+            &sealed,
+            PROFILE,
+            SERVICE,
+            "request-1",
+            &f.service_doc,
+            &no_secrets,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("not sealed to a key"), "{err}");
+    }
+
+    #[test]
+    fn plain_and_sealed_responses_are_told_apart() {
+        let sealed: Sealable<HTTPResponse<AuthorizationTokens>> =
+            serde_json::from_value(json!({"sessionId": "s", "data": "{\"protected\":\"\"}"}))
+                .unwrap();
+        assert!(matches!(sealed, Sealable::Sealed(_)));
+
+        let plain: Sealable<HTTPResponse<AuthorizationTokens>> = serde_json::from_value(json!({
+            "sessionId": "s",
+            "data": serde_json::to_value(tokens()).unwrap(),
+        }))
+        .unwrap();
+        assert!(matches!(plain, Sealable::Plain(_)));
+    }
+}
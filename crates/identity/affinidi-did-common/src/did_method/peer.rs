@@ -259,15 +259,24 @@ pub struct PeerServiceEndpointShort {
 }
 
 /// Long format service endpoint map (standard DID Document format)
+///
+/// [`PeerServiceEndpoint`] tries this form first, so it also reads the short
+/// `a`/`r` keys and the `routingKeys` spelling; otherwise a short-form service
+/// decodes with its accept list and routing keys dropped.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PeerServiceEndpointLong {
     /// Service URI
     pub uri: String,
     /// Accepted message types
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, alias = "a", skip_serializing_if = "Vec::is_empty")]
     pub accept: Vec<String>,
     /// Routing keys
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        alias = "r",
+        alias = "routingKeys",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     pub routing_keys: Vec<String>,
 }
 
@@ -387,6 +396,58 @@ impl PeerService {
     }
 }
 
+// ============================================================================
+// Canonical Form
+// ============================================================================
+
+/// The canonical spelling of a `did:peer:2` DID, for use as a cache or map
+/// key.
+///
+/// A did:peer:2 document numbers its keys (`#key-1`, `#key-2`, …) in the
+/// order the key elements appear, and its services (`#service`,
+/// `#service-1`, …) in the order the service elements appear. The order of
+/// keys relative to services carries no meaning, so DIDs that interleave them
+/// differently resolve to the same document apart from its `id`. The
+/// canonical form lists every key element, then every service element, each
+/// group in its original order, and drops empty elements.
+///
+/// Keys are never reordered among themselves: swapping a `V` and an `E`
+/// element renumbers the keys, so the two DIDs are genuinely different.
+///
+/// Anything that isn't a bare `did:peer:2` DID (other numalgos, other
+/// methods, DID URLs) is returned unchanged.
+pub fn canonical_peer_did(did: &str) -> std::borrow::Cow<'_, str> {
+    use std::borrow::Cow;
+
+    let Some(content) = did.strip_prefix("did:peer:2") else {
+        return Cow::Borrowed(did);
+    };
+    if content.contains(['#', '?', '/']) {
+        return Cow::Borrowed(did);
+    }
+
+    let elements = content.split('.').filter(|e| !e.is_empty());
+    let (keys, services): (Vec<&str>, Vec<&str>) = elements.partition(|e| !e.starts_with('S'));
+    let mut canonical = String::with_capacity(did.len());
+    canonical.push_str("did:peer:2");
+    for element in keys.into_iter().chain(services) {
+        canonical.push('.');
+        canonical.push_str(element);
+    }
+
+    if canonical == did {
+        Cow::Borrowed(did)
+    } else {
+        Cow::Owned(canonical)
+    }
+}
+
+/// Whether two DIDs name the same document, comparing `did:peer:2` DIDs by
+/// their [canonical form](canonical_peer_did) and anything else exactly.
+pub fn peer_dids_equivalent(a: &str, b: &str) -> bool {
+    a == b || canonical_peer_did(a) == canonical_peer_did(b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -532,6 +593,19 @@ mod tests {
         assert_eq!(long.routing_keys, vec!["did:example:123#key-1"]);
     }
 
+    #[test]
+    fn short_form_service_keeps_accept_and_routing_keys() {
+        let service: PeerService = serde_json::from_str(
+            r#"{"t":"dm","s":{"uri":"https://example.com/didcomm","a":["didcomm/v2"],"r":["did:example:123#key-1"]}}"#,
+        )
+        .unwrap();
+        let PeerServiceEndpoint::Long(OneOrMany::One(long)) = service.endpoint else {
+            panic!("expected a single endpoint map");
+        };
+        assert_eq!(long.accept, vec!["didcomm/v2"]);
+        assert_eq!(long.routing_keys, vec!["did:example:123#key-1"]);
+    }
+
     #[test]
     fn long_to_short_conversion() {
         let long = PeerServiceEndpointLong {
@@ -685,4 +759,46 @@ mod tests {
             crate::service::Endpoint::Map(_)
         ));
     }
+
+    // --- Canonical form ---
+
+    const V_KEY: &str = "Vz6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+    const E_KEY: &str = "Ez6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc";
+    const SERVICE: &str = "SeyJ0IjoiZG0iLCJzIjoiaHR0cHM6Ly9leGFtcGxlLmNvbS9kaWRjb21tIn0";
+
+    #[test]
+    fn canonical_form_moves_services_after_keys() {
+        let canonical = format!("did:peer:2.{V_KEY}.{E_KEY}.{SERVICE}");
+        for variant in [
+            format!("did:peer:2.{SERVICE}.{V_KEY}.{E_KEY}"),
+            format!("did:peer:2.{V_KEY}.{SERVICE}.{E_KEY}"),
+            format!("did:peer:2.{V_KEY}..{E_KEY}.{SERVICE}"),
+        ] {
+            assert_eq!(canonical_peer_did(&variant), canonical);
+            assert!(peer_dids_equivalent(&variant, &canonical));
+        }
+        assert!(matches!(
+            canonical_peer_did(&canonical),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn canonical_form_keeps_key_order() {
+        let ve = format!("did:peer:2.{V_KEY}.{E_KEY}");
+        let ev = format!("did:peer:2.{E_KEY}.{V_KEY}");
+        assert_eq!(canonical_peer_did(&ev), ev);
+        assert!(!peer_dids_equivalent(&ve, &ev));
+    }
+
+    #[test]
+    fn canonical_form_ignores_other_dids() {
+        for did in [
+            "did:peer:0z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK",
+            "did:web:example.com",
+            "did:peer:2.SeyJ0IjoiZG0ifQ.Vz6Mk#key-1",
+        ] {
+            assert_eq!(canonical_peer_did(did), did);
+        }
+    }
 }
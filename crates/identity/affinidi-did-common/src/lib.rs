@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use affinidi_encoding::EncodingError;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 use url::Url;
 
@@ -33,7 +34,7 @@ pub use did_method::key::{KeyError, KeyMaterial, KeyMaterialFormat, KeyMaterialT
 pub use did_method::peer::{
     PeerCreateKey, PeerCreatedKey, PeerError, PeerKeyPurpose, PeerKeyType, PeerNumAlgo,
     PeerPurpose, PeerService, PeerServiceEndpoint, PeerServiceEndpointLong,
-    PeerServiceEndpointShort,
+    PeerServiceEndpointShort, canonical_peer_did, peer_dids_equivalent,
 };
 pub use document::DocumentExt;
 
@@ -53,6 +54,9 @@ pub enum DocumentError {
 
     #[error("Key expansion error: {0}")]
     KeyExpansionError(String),
+
+    #[error("Serialization Error: {0}")]
+    Serialization(String),
 }
 
 /// A [DID Document]
@@ -152,6 +156,26 @@ impl Document {
             ..Default::default()
         })
     }
+
+    /// Serializes the Document using the JSON Canonicalization Scheme ([RFC 8785])
+    ///
+    /// Object keys are sorted and whitespace removed, so two semantically equal
+    /// Documents always produce the same bytes regardless of how the flattened
+    /// `parameters_set` map happens to be ordered. Use this (or
+    /// [`Document::sha256_hash`]) wherever a Document is hashed or compared as bytes.
+    ///
+    /// [RFC 8785]: https://www.rfc-editor.org/rfc/rfc8785
+    pub fn canonical_json(&self) -> Result<String, DocumentError> {
+        serde_json_canonicalizer::to_string(self)
+            .map_err(|e| DocumentError::Serialization(format!("JCS canonicalization failed: {e}")))
+    }
+
+    /// SHA-256 hash of the Document's canonical JSON ([`Document::canonical_json`])
+    ///
+    /// Suitable for state hashing, cache fingerprinting and change detection.
+    pub fn sha256_hash(&self) -> Result<[u8; 32], DocumentError> {
+        Ok(Sha256::digest(self.canonical_json()?.as_bytes()).into())
+    }
 }
 
 #[cfg(test)]
@@ -256,6 +280,37 @@ mod tests {
         assert!(!doc.parameters_set.contains_key("alsoKnownAs"));
     }
 
+    // --- canonical JSON / hashing ---
+
+    #[test]
+    fn canonical_json_sorts_keys_and_strips_whitespace() {
+        let doc: Document = serde_json::from_str(
+            r#"{ "id": "did:example:123", "zeta": 1, "alpha": { "b": true, "a": null } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            doc.canonical_json().unwrap(),
+            r#"{"alpha":{"a":null,"b":true},"id":"did:example:123","zeta":1}"#
+        );
+    }
+
+    #[test]
+    fn sha256_hash_ignores_parameter_order() {
+        let a: Document =
+            serde_json::from_str(r#"{"id":"did:example:123","one":1,"two":2,"three":3}"#).unwrap();
+        let b: Document =
+            serde_json::from_str(r#"{"three":3,"two":2,"id":"did:example:123","one":1}"#).unwrap();
+        assert_eq!(a.sha256_hash().unwrap(), b.sha256_hash().unwrap());
+    }
+
+    #[test]
+    fn sha256_hash_detects_changes() {
+        let a = Document::new("did:example:123").unwrap();
+        let mut b = a.clone();
+        b.also_known_as.push("example.com/@alice".to_string());
+        assert_ne!(a.sha256_hash().unwrap(), b.sha256_hash().unwrap());
+    }
+
     #[test]
     fn also_known_as_serde_roundtrip() {
         let json = r#"{"id":"did:example:123","alsoKnownAs":["example.com/@alice"]}"#;
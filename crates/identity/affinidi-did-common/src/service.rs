@@ -98,50 +98,130 @@ pub enum Endpoint {
     Map(Value),
 }
 
+/// One address a service can be reached at, as read by
+/// [`Endpoint::endpoint_uris`].
+///
+/// `accept` and `routing_keys` are the DIDComm v2 profile fields; both are
+/// empty when the endpoint doesn't give them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EndpointUri {
+    /// The URI as written in the document, trimmed of whitespace
+    pub uri: String,
+
+    /// Media types the endpoint accepts (`accept`, or `a` in did:peer)
+    pub accept: Vec<String>,
+
+    /// Keys to wrap messages for before delivery (`routingKeys`, or `r`; the
+    /// did:peer resolver writes `routing_keys`)
+    pub routing_keys: Vec<String>,
+}
+
+/// How deep [`Endpoint::endpoint_uris`] descends through nested arrays and
+/// maps. Documents in the wild nest two or three levels; anything deeper is
+/// ignored rather than walked.
+const MAX_ENDPOINT_DEPTH: usize = 8;
+
 impl Endpoint {
     /// Returns the first URI String for a service Endpoint, if available
     /// This may not be what you always want
     pub fn get_uri(&self) -> Option<String> {
-        match self {
-            Endpoint::Url(uri) => Some(uri.to_string()),
-            Endpoint::Map(map) => match map {
-                Value::Array(array) => {
-                    if let Some(first) = array.first() {
-                        first.get("uri").map(|u| u.to_string())
-                    } else {
-                        None
-                    }
-                }
-                Value::Object(obj) => obj.get("uri").map(|u| u.to_string()),
-                _ => None,
-            },
-        }
+        self.endpoint_uris().into_iter().next().map(|e| e.uri)
     }
 
     /// Returns all found URI's within a service Endpoint
     pub fn get_uris(&self) -> Vec<String> {
+        self.endpoint_uris().into_iter().map(|e| e.uri).collect()
+    }
+
+    /// Every URI in the endpoint, in document order and without duplicates,
+    /// whichever shape it is written in:
+    ///
+    /// - a string: `"https://example.com"`
+    /// - a map: `{"uri": "https://example.com", "accept": ["didcomm/v2"]}`
+    /// - an array mixing strings and maps
+    /// - a nested DIDComm profile, where `uri` (or `serviceEndpoint`) holds
+    ///   another string, map or array. Inner maps inherit `accept` and
+    ///   `routingKeys` from the outer one unless they set their own.
+    /// - LinkedDomains `{"origins": [...]}`
+    ///
+    /// Strings that don't parse as a URI (a DID counts as one) are skipped.
+    pub fn endpoint_uris(&self) -> Vec<EndpointUri> {
+        let mut found = Vec::new();
         match self {
-            Endpoint::Url(uri) => vec![uri.to_string()],
-            Endpoint::Map(map) => {
-                let mut uris = Vec::new();
-                match map {
-                    Value::Array(array) => {
-                        for sep in array {
-                            if let Some(uri) = sep.get("uri") {
-                                uris.push(uri.to_string());
-                            }
-                        }
-                    }
-                    Value::Object(obj) => {
-                        if let Some(uri) = obj.get("uri") {
-                            uris.push(uri.to_string());
-                        }
-                    }
-                    _ => {}
-                }
-                uris
+            Endpoint::Url(url) => push_endpoint_uri(&mut found, url.as_str(), &[], &[]),
+            Endpoint::Map(value) => collect_endpoint_uris(value, &[], &[], 0, &mut found),
+        }
+        found
+    }
+}
+
+fn collect_endpoint_uris(
+    value: &Value,
+    accept: &[String],
+    routing_keys: &[String],
+    depth: usize,
+    found: &mut Vec<EndpointUri>,
+) {
+    if depth > MAX_ENDPOINT_DEPTH {
+        return;
+    }
+    match value {
+        Value::String(uri) => push_endpoint_uri(found, uri, accept, routing_keys),
+        Value::Array(items) => {
+            for item in items {
+                collect_endpoint_uris(item, accept, routing_keys, depth + 1, found);
             }
         }
+        Value::Object(map) => {
+            let Some(inner) = ["uri", "serviceEndpoint", "origins"]
+                .iter()
+                .find_map(|key| map.get(*key))
+            else {
+                return;
+            };
+            let accept = string_list(map.get("accept").or_else(|| map.get("a")))
+                .unwrap_or_else(|| accept.to_vec());
+            let routing_keys = string_list(
+                ["routingKeys", "routing_keys", "r"]
+                    .iter()
+                    .find_map(|key| map.get(*key)),
+            )
+            .unwrap_or_else(|| routing_keys.to_vec());
+            collect_endpoint_uris(inner, &accept, &routing_keys, depth + 1, found);
+        }
+        _ => {}
+    }
+}
+
+fn push_endpoint_uri(
+    found: &mut Vec<EndpointUri>,
+    uri: &str,
+    accept: &[String],
+    routing_keys: &[String],
+) {
+    let uri = uri.trim();
+    if Url::parse(uri).is_err() || found.iter().any(|e| e.uri == uri) {
+        return;
+    }
+    found.push(EndpointUri {
+        uri: uri.to_string(),
+        accept: accept.to_vec(),
+        routing_keys: routing_keys.to_vec(),
+    });
+}
+
+/// A string or an array of strings; `None` when absent or another type.
+fn string_list(value: Option<&Value>) -> Option<Vec<String>> {
+    match value? {
+        Value::String(s) => Some(vec![s.clone()]),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_owned))
+                .collect(),
+        ),
+        _ => None,
     }
 }
 
@@ -177,7 +257,7 @@ mod tests {
     #[test]
     fn get_uri_from_map_object() {
         let ep = Endpoint::Map(json!({"uri": "https://example.com"}));
-        assert_eq!(ep.get_uri().unwrap(), "\"https://example.com\"");
+        assert_eq!(ep.get_uri().unwrap(), "https://example.com");
     }
 
     #[test]
@@ -186,7 +266,7 @@ mod tests {
             {"uri": "https://first.example.com"},
             {"uri": "https://second.example.com"}
         ]));
-        assert_eq!(ep.get_uri().unwrap(), "\"https://first.example.com\"");
+        assert_eq!(ep.get_uri().unwrap(), "https://first.example.com");
     }
 
     #[test]
@@ -218,7 +298,7 @@ mod tests {
     #[test]
     fn get_uris_from_map_object() {
         let ep = Endpoint::Map(json!({"uri": "https://example.com"}));
-        assert_eq!(ep.get_uris(), vec!["\"https://example.com\""]);
+        assert_eq!(ep.get_uris(), vec!["https://example.com"]);
     }
 
     #[test]
@@ -229,8 +309,8 @@ mod tests {
         ]));
         let uris = ep.get_uris();
         assert_eq!(uris.len(), 2);
-        assert!(uris[0].contains("first.example.com"));
-        assert!(uris[1].contains("second.example.com"));
+        assert_eq!(uris[0], "https://first.example.com");
+        assert_eq!(uris[1], "https://second.example.com");
     }
 
     #[test]
@@ -255,6 +335,374 @@ mod tests {
         assert!(ep.get_uris().is_empty());
     }
 
+    // --- Endpoint::endpoint_uris ---
+
+    fn uris(value: Value) -> Vec<String> {
+        Endpoint::Map(value).get_uris()
+    }
+
+    #[test]
+    fn endpoint_uris_from_string_in_map_variant() {
+        assert_eq!(
+            uris(json!(" https://example.com/didcomm ")),
+            vec!["https://example.com/didcomm"]
+        );
+    }
+
+    #[test]
+    fn endpoint_uris_from_mixed_array() {
+        let ep = json!([
+            "https://one.example.com",
+            {"uri": "wss://two.example.com/ws"},
+            ["did:web:three.example.com"]
+        ]);
+        assert_eq!(
+            uris(ep),
+            vec![
+                "https://one.example.com",
+                "wss://two.example.com/ws",
+                "did:web:three.example.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn endpoint_uris_skips_non_uris_and_duplicates() {
+        let ep = json!([
+            "not a uri",
+            {"uri": 42},
+            {"uri": "https://example.com"},
+            "https://example.com",
+            null
+        ]);
+        assert_eq!(uris(ep), vec!["https://example.com"]);
+    }
+
+    #[test]
+    fn endpoint_uris_reads_didcomm_profile() {
+        let ep = Endpoint::Map(json!({
+            "uri": "https://example.com",
+            "accept": ["didcomm/v2", "didcomm/aip2;env=rfc587"],
+            "routingKeys": ["did:example:mediator#key-1"]
+        }));
+        let found = ep.endpoint_uris();
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].accept,
+            vec!["didcomm/v2", "didcomm/aip2;env=rfc587"]
+        );
+        assert_eq!(found[0].routing_keys, vec!["did:example:mediator#key-1"]);
+    }
+
+    #[test]
+    fn endpoint_uris_reads_abbreviated_profile() {
+        let ep = Endpoint::Map(json!({"uri": "https://example.com", "a": "didcomm/v2", "r": []}));
+        let found = ep.endpoint_uris();
+        assert_eq!(found[0].accept, vec!["didcomm/v2"]);
+        assert!(found[0].routing_keys.is_empty());
+    }
+
+    #[test]
+    fn endpoint_uris_nested_profile_inherits_outer_fields() {
+        let ep = Endpoint::Map(json!({
+            "uri": [
+                {"uri": "https://inner.example.com"},
+                {"uri": "wss://inner.example.com/ws", "accept": ["didcomm/v2"]}
+            ],
+            "accept": ["didcomm/aip2;env=rfc19"],
+            "routingKeys": ["did:example:mediator#key-1"]
+        }));
+        let found = ep.endpoint_uris();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].accept, vec!["didcomm/aip2;env=rfc19"]);
+        assert_eq!(found[1].accept, vec!["didcomm/v2"]);
+        assert_eq!(found[1].routing_keys, vec!["did:example:mediator#key-1"]);
+    }
+
+    #[test]
+    fn endpoint_uris_nested_service_endpoint_and_origins() {
+        assert_eq!(
+            uris(json!({"serviceEndpoint": {"uri": "https://example.com"}})),
+            vec!["https://example.com"]
+        );
+        assert_eq!(
+            uris(json!({"origins": ["https://a.example.com", "https://b.example.com"]})),
+            vec!["https://a.example.com", "https://b.example.com"]
+        );
+    }
+
+    #[test]
+    fn endpoint_uris_stops_at_depth_bound() {
+        let mut ep = json!("https://example.com");
+        for _ in 0..=MAX_ENDPOINT_DEPTH {
+            ep = json!([ep]);
+        }
+        assert!(uris(ep.clone()).is_empty());
+        assert_eq!(uris(ep[0].clone()), vec!["https://example.com"]);
+    }
+
+    // --- Endpoint::endpoint_uris over published documents ---
+
+    /// A did:web mediator document (the shape Affinidi's hosted mediators
+    /// publish): an array of DIDComm profiles plus a plain string endpoint.
+    const DID_WEB_DOC: &str = r#"{
+        "id": "did:web:apse1.mediator.affinidi.io:.well-known",
+        "service": [
+            {
+                "id": "did:web:apse1.mediator.affinidi.io:.well-known#service",
+                "type": "DIDCommMessaging",
+                "serviceEndpoint": [
+                    {
+                        "accept": ["didcomm/v2"],
+                        "routingKeys": [],
+                        "uri": "https://apse1.mediator.affinidi.io"
+                    },
+                    {
+                        "accept": ["didcomm/v2"],
+                        "routingKeys": [],
+                        "uri": "wss://apse1.mediator.affinidi.io/ws"
+                    }
+                ]
+            },
+            {
+                "id": "did:web:apse1.mediator.affinidi.io:.well-known#auth",
+                "type": "Authentication",
+                "serviceEndpoint": "https://apse1.mediator.affinidi.io/authenticate"
+            }
+        ]
+    }"#;
+
+    /// A synthetic did:cheqd document in the shape the cheqd resolver
+    /// returns: the ledger stores every `serviceEndpoint` as an array of
+    /// strings. The DIDComm service and its `agent.example.com` host are made
+    /// up for the test.
+    const DID_CHEQD_DOC: &str = r#"{
+        "@context": ["https://www.w3.org/ns/did/v1"],
+        "id": "did:cheqd:testnet:55dbc8bf-fba3-4117-855c-1e0dc1d3bb47",
+        "service": [
+            {
+                "id": "did:cheqd:testnet:55dbc8bf-fba3-4117-855c-1e0dc1d3bb47#website",
+                "type": "LinkedDomains",
+                "serviceEndpoint": ["https://www.cheqd.io"]
+            },
+            {
+                "id": "did:cheqd:testnet:55dbc8bf-fba3-4117-855c-1e0dc1d3bb47#didcomm",
+                "type": "DIDCommMessaging",
+                "serviceEndpoint": ["https://agent.example.com/didcomm", "wss://agent.example.com/ws"]
+            }
+        ]
+    }"#;
+
+    /// did:peer:2 DIDs whose services are a single profile with routing keys,
+    /// an array of profiles, and a mediator DID.
+    const DID_PEER: &[&str] = &[
+        "did:peer:2.Vz6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK.SeyJ0IjoiZG0iLCJzIjp7InVyaSI6Imh0dHBzOi8vZXhhbXBsZS5jb20vZGlkY29tbSIsImEiOlsiZGlkY29tbS92MiJdLCJyIjpbImRpZDpleGFtcGxlOjEyMzQ1Njc4OWFiY2RlZmdoaSNrZXktMSJdfX0",
+        "did:peer:2.Vz6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK.SeyJ0IjoiZG0iLCJzIjpbeyJ1cmkiOiJodHRwczovL21lZGlhdG9yLmV4YW1wbGUuY29tIiwiYSI6WyJkaWRjb21tL3YyIl19LHsidXJpIjoid3NzOi8vbWVkaWF0b3IuZXhhbXBsZS5jb20vd3MiLCJhIjpbImRpZGNvbW0vdjIiXX1dfQ",
+        "did:peer:2.Vz6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK.SeyJ0IjoiZG0iLCJzIjp7InVyaSI6ImRpZDp3ZWI6bWVkaWF0b3IuZXhhbXBsZS5jb20ifX0",
+    ];
+
+    fn service_uris(doc: &Document, fragment: &str) -> Vec<String> {
+        doc.find_service(fragment)
+            .map(|s| s.service_endpoint.get_uris())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn endpoint_uris_did_web_document() {
+        let doc: Document = serde_json::from_str(DID_WEB_DOC).unwrap();
+        assert_eq!(
+            service_uris(&doc, "service"),
+            vec![
+                "https://apse1.mediator.affinidi.io",
+                "wss://apse1.mediator.affinidi.io/ws"
+            ]
+        );
+        assert_eq!(
+            service_uris(&doc, "auth"),
+            vec!["https://apse1.mediator.affinidi.io/authenticate"]
+        );
+    }
+
+    #[test]
+    fn endpoint_uris_did_cheqd_document() {
+        let doc: Document = serde_json::from_str(DID_CHEQD_DOC).unwrap();
+        assert_eq!(service_uris(&doc, "website"), vec!["https://www.cheqd.io"]);
+        assert_eq!(
+            service_uris(&doc, "didcomm"),
+            vec![
+                "https://agent.example.com/didcomm",
+                "wss://agent.example.com/ws"
+            ]
+        );
+    }
+
+    #[test]
+    fn endpoint_uris_did_peer_documents() {
+        let resolve = |did: &str| did.parse::<crate::DID>().unwrap().resolve().unwrap();
+
+        let found = resolve(DID_PEER[0]).service[0]
+            .service_endpoint
+            .endpoint_uris();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].uri, "https://example.com/didcomm");
+        assert_eq!(found[0].accept, vec!["didcomm/v2"]);
+        assert_eq!(
+            found[0].routing_keys,
+            vec!["did:example:123456789abcdefghi#key-1"]
+        );
+
+        assert_eq!(
+            service_uris(&resolve(DID_PEER[1]), "service"),
+            vec![
+                "https://mediator.example.com",
+                "wss://mediator.example.com/ws"
+            ]
+        );
+        assert_eq!(
+            service_uris(&resolve(DID_PEER[2]), "service"),
+            vec!["did:web:mediator.example.com"]
+        );
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Every `serviceEndpoint` in the documents above.
+        fn published_endpoints() -> Vec<Endpoint> {
+            let mut docs: Vec<Document> = [DID_WEB_DOC, DID_CHEQD_DOC]
+                .iter()
+                .map(|doc| serde_json::from_str(doc).unwrap())
+                .collect();
+            docs.extend(
+                DID_PEER
+                    .iter()
+                    .map(|did| did.parse::<crate::DID>().unwrap().resolve().unwrap()),
+            );
+            docs.into_iter()
+                .flat_map(|doc| doc.service)
+                .map(|s| s.service_endpoint)
+                .collect()
+        }
+
+        fn arb_uri() -> impl Strategy<Value = String> {
+            prop_oneof![
+                "(https|wss)://[a-z]{1,12}\\.example\\.com(/[a-z0-9]{1,8}){0,2}",
+                "did:(web|peer):[a-z0-9]{1,16}",
+            ]
+        }
+
+        /// One way of writing `uri` as an endpoint.
+        fn arb_shape(uri: String) -> impl Strategy<Value = Value> {
+            prop_oneof![
+                Just(json!(uri)),
+                Just(json!({"uri": uri})),
+                Just(json!({"uri": uri, "accept": ["didcomm/v2"], "routingKeys": []})),
+                Just(json!({"uri": {"uri": uri}})),
+                Just(json!({"serviceEndpoint": uri})),
+                Just(json!([uri])),
+            ]
+        }
+
+        fn arb_json() -> impl Strategy<Value = Value> {
+            let leaf = prop_oneof![
+                Just(Value::Null),
+                any::<bool>().prop_map(Value::from),
+                any::<i64>().prop_map(Value::from),
+                ".*".prop_map(Value::from),
+                arb_uri().prop_map(Value::from),
+            ];
+            leaf.prop_recursive(6, 64, 8, |inner| {
+                prop_oneof![
+                    prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+                    prop::collection::vec(
+                        (
+                            prop_oneof![
+                                Just("uri".to_string()),
+                                Just("accept".to_string()),
+                                Just("routingKeys".to_string()),
+                                Just("serviceEndpoint".to_string()),
+                                "[a-z]{1,6}",
+                            ],
+                            inner
+                        ),
+                        0..6
+                    )
+                    .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+                ]
+            })
+        }
+
+        proptest! {
+            /// However each URI is written, the URIs come back in order.
+            #[test]
+            fn any_mix_of_shapes_yields_the_uris(
+                written in prop::collection::vec(arb_uri(), 1..6)
+                    .prop_flat_map(|uris| {
+                        let shapes: Vec<_> = uris.iter().cloned().map(arb_shape).collect();
+                        (Just(uris), shapes)
+                    })
+            ) {
+                let (uris, shapes) = written;
+                let mut expected = Vec::new();
+                for uri in uris {
+                    if !expected.contains(&uri) {
+                        expected.push(uri);
+                    }
+                }
+                prop_assert_eq!(Endpoint::Map(Value::Array(shapes)).get_uris(), expected);
+            }
+
+            /// Wrapping a published endpoint in an array or an outer profile
+            /// doesn't change the URIs read from it.
+            #[test]
+            fn wrapping_published_endpoints_keeps_their_uris(
+                index in any::<prop::sample::Index>(),
+                wrappers in prop::collection::vec(0..3u8, 0..4)
+            ) {
+                let endpoints = published_endpoints();
+                let endpoint = index.get(&endpoints);
+                let mut value = serde_json::to_value(endpoint).unwrap();
+                for wrapper in wrappers {
+                    value = match wrapper {
+                        0 => json!([value]),
+                        1 => json!({"uri": value}),
+                        _ => json!({"serviceEndpoint": value}),
+                    };
+                }
+                prop_assert_eq!(Endpoint::Map(value).get_uris(), endpoint.get_uris());
+            }
+
+            /// Arbitrary JSON never panics, and everything returned is a URI.
+            #[test]
+            fn arbitrary_json_yields_only_uris(value in arb_json()) {
+                let found = Endpoint::Map(value).endpoint_uris();
+                for (i, entry) in found.iter().enumerate() {
+                    prop_assert!(Url::parse(&entry.uri).is_ok());
+                    prop_assert!(found[..i].iter().all(|e| e.uri != entry.uri));
+                }
+            }
+
+            /// A service survives a serialization round trip with the same URIs.
+            #[test]
+            fn service_round_trip_keeps_uris(
+                uri in arb_uri().prop_flat_map(arb_shape)
+            ) {
+                let service: Service = serde_json::from_value(json!({
+                    "type": "DIDCommMessaging",
+                    "serviceEndpoint": uri
+                }))
+                .unwrap();
+                let back: Service =
+                    serde_json::from_str(&serde_json::to_string(&service).unwrap()).unwrap();
+                prop_assert_eq!(
+                    back.service_endpoint.endpoint_uris(),
+                    service.service_endpoint.endpoint_uris()
+                );
+            }
+        }
+    }
+
     // --- Document::find_service ---
 
     #[test]
@@ -27,7 +27,7 @@ use agent_names::{AgentName, AgentNameError, AgentNameResolver, verify_also_know
 use tokio::sync::watch;
 use tracing::{debug, warn};
 
-use crate::{DIDCacheClient, DidShortcut, ResolveResponse, errors::DIDCacheError};
+use crate::{DIDCacheClient, DidShortcut, InflightLeader, ResolveResponse, errors::DIDCacheError};
 
 /// How many claimed names to check before giving up establishing a shortcut.
 ///
@@ -131,6 +131,7 @@ impl DIDCacheClient {
             }
 
             let did_hash = Self::hash_did(&did);
+            let doc = std::sync::Arc::new(doc);
             // Populate the shared document cache so a later lookup by DID hits.
             self.cache.insert(did_hash, doc.clone()).await;
             self.agent_name_cache.insert(name_hash, did.clone()).await;
@@ -263,21 +264,12 @@ impl DIDCacheClient {
         loop {
             // Decide our role under the lock; no `.await` is held across it.
             enum Role {
-                Leader(watch::Sender<()>),
+                Leader(InflightLeader),
                 Follower(watch::Receiver<()>),
             }
-            let role = {
-                let mut map = self
-                    .agent_name_inflight
-                    .lock()
-                    .expect("agent name inflight mutex not poisoned");
-                if let Some(rx) = map.get(&name_hash) {
-                    Role::Follower(rx.clone())
-                } else {
-                    let (tx, rx) = watch::channel(());
-                    map.insert(name_hash, rx);
-                    Role::Leader(tx)
-                }
+            let role = match InflightLeader::claim(&self.agent_name_inflight, name_hash) {
+                Ok(leader) => Role::Leader(leader),
+                Err(rx) => Role::Follower(rx),
             };
 
             match role {
@@ -293,11 +285,11 @@ impl DIDCacheClient {
                     // become the leader ourselves.
                     continue;
                 }
-                Role::Leader(tx) => {
+                Role::Leader(leader) => {
                     // A prior leader may have populated the mapping between our
                     // cache miss and our acquiring leadership.
                     if let Some(did) = self.agent_name_cache.get(&name_hash).await {
-                        self.release_name_leadership(name_hash, tx);
+                        drop(leader);
                         return Ok(did);
                     }
 
@@ -306,22 +298,14 @@ impl DIDCacheClient {
                         self.agent_name_cache.insert(name_hash, did.clone()).await;
                     }
                     // Release leadership and wake followers regardless of
-                    // outcome — an early return here would hang every waiter.
-                    self.release_name_leadership(name_hash, tx);
+                    // outcome. The guard also does this if we are dropped.
+                    drop(leader);
                     return result;
                 }
             }
         }
     }
 
-    fn release_name_leadership(&self, name_hash: [u64; 2], tx: watch::Sender<()>) {
-        self.agent_name_inflight
-            .lock()
-            .expect("agent name inflight mutex not poisoned")
-            .remove(&name_hash);
-        drop(tx);
-    }
-
     /// Walk the registered backends until one resolves the name.
     async fn resolve_name_to_did(&self, name: &AgentName) -> Result<String, DIDCacheError> {
         for resolver in self.agent_name_resolvers.iter() {
@@ -20,11 +20,147 @@
 //!     .build();
 //! ```
 //!
+//! Example: Bounding cache memory on a constrained device:
+//! ```rust
+//! use affinidi_did_resolver_cache_sdk::config::DIDCacheConfigBuilder;
+//! let config = DIDCacheConfigBuilder::default()
+//!     .with_cache_max_bytes(512 * 1024)
+//!     .with_cache_tti(120)
+//!     .with_eviction_listener(|eviction| {
+//!         println!("evicted {} ({:?})", eviction.did, eviction.cause);
+//!     })
+//!     .build();
+//! ```
+//!
+//! Example: Reproducible verification against a pinned set of documents:
+//! ```rust,ignore
+//! use affinidi_did_resolver_cache_sdk::{config::DIDCacheConfigBuilder, pinned::PinnedDocuments};
+//! let config = DIDCacheConfigBuilder::default()
+//!     .with_pinned_documents(PinnedDocuments::from_file("fixtures/dids.json")?)
+//!     .build();
+//! ```
+//!
+
+use std::sync::Arc;
 
-#[cfg(feature = "network")]
+use crate::pinned::PinnedDocuments;
+#[cfg(feature = "did-cheqd")]
+use affinidi_task_utils::CallLimits;
+#[cfg(any(feature = "network", feature = "did-cheqd"))]
 use std::time::Duration;
 use wasm_bindgen::prelude::*;
 
+/// Why a document left the local cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CacheEvictionCause {
+    /// Its TTL or TTI ran out.
+    Expired,
+    /// It was removed with [`remove`](crate::DIDCacheClient::remove).
+    Explicit,
+    /// A newer document for the same DID replaced it.
+    Replaced,
+    /// The cache was over capacity (entries or bytes).
+    Size,
+}
+
+impl From<moka::notification::RemovalCause> for CacheEvictionCause {
+    fn from(cause: moka::notification::RemovalCause) -> Self {
+        use moka::notification::RemovalCause;
+        match cause {
+            RemovalCause::Expired => CacheEvictionCause::Expired,
+            RemovalCause::Explicit => CacheEvictionCause::Explicit,
+            RemovalCause::Replaced => CacheEvictionCause::Replaced,
+            RemovalCause::Size => CacheEvictionCause::Size,
+        }
+    }
+}
+
+/// A document leaving the local cache, as passed to the
+/// [eviction listener](DIDCacheConfigBuilder::with_eviction_listener).
+#[derive(Clone, Debug)]
+pub struct CacheEviction {
+    pub did: String,
+    pub cause: CacheEvictionCause,
+    /// Serialized size of the document, as counted against
+    /// [`with_cache_max_bytes`](DIDCacheConfigBuilder::with_cache_max_bytes).
+    pub size_bytes: u32,
+}
+
+#[derive(Clone)]
+pub(crate) struct EvictionListener(pub(crate) Arc<dyn Fn(CacheEviction) + Send + Sync>);
+
+impl std::fmt::Debug for EvictionListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EvictionListener")
+    }
+}
+
+/// Connection settings for the `did:cheqd` resolver.
+///
+/// One resolver instance is shared by every resolution, so its gRPC channels
+/// (one per network) are opened once and reused.
+///
+/// ```rust,ignore
+/// let cheqd = CheqdResolverConfig::default()
+///     .with_network("testnet", "https://grpc.cheqd.internal:443")
+///     .with_retries(3, Duration::from_millis(250))
+///     .with_timeout(Duration::from_secs(5));
+/// let config = DIDCacheConfigBuilder::default()
+///     .with_cheqd_config(cheqd)
+///     .build();
+/// ```
+#[cfg(feature = "did-cheqd")]
+#[derive(Clone, Debug)]
+pub struct CheqdResolverConfig {
+    pub(crate) networks: Vec<(String, String)>,
+    pub(crate) max_retries: u32,
+    pub(crate) retry_backoff: Duration,
+    pub(crate) attempt_limits: CallLimits,
+}
+
+#[cfg(feature = "did-cheqd")]
+impl Default for CheqdResolverConfig {
+    fn default() -> Self {
+        Self {
+            networks: Vec::new(),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(200),
+            attempt_limits: CallLimits::new().with_timeout(Duration::from_secs(10)),
+        }
+    }
+}
+
+#[cfg(feature = "did-cheqd")]
+impl CheqdResolverConfig {
+    /// gRPC endpoint for a network namespace (`mainnet`, `testnet`, or a
+    /// custom one). Networks not set here use the public cheqd endpoints;
+    /// setting a network replaces its public endpoint.
+    pub fn with_network(mut self, namespace: &str, grpc_url: &str) -> Self {
+        self.networks.retain(|(ns, _)| ns != namespace);
+        self.networks
+            .push((namespace.to_string(), grpc_url.to_string()));
+        self
+    }
+
+    /// Retry failed resolutions up to `max_retries` times, doubling
+    /// `backoff` after each attempt. A DID that does not exist is not
+    /// retried.
+    /// Default: 2 retries, 200ms initial backoff
+    pub fn with_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Time limit for a single resolution attempt.
+    /// Default: 10 seconds
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.attempt_limits = self.attempt_limits.with_timeout(timeout);
+        self
+    }
+}
+
 /// Configuration for the DID Cache client.
 ///
 /// Use the [DIDCacheConfigBuilder] to create a new configuration.
@@ -34,7 +170,12 @@ pub struct DIDCacheConfig {
     #[cfg(feature = "network")]
     pub(crate) service_address: Option<String>,
     pub(crate) cache_capacity: u32,
+    pub(crate) cache_max_bytes: Option<u64>,
     pub(crate) cache_ttl: u32,
+    pub(crate) cache_tti: Option<u32>,
+    pub(crate) eviction_listener: Option<EvictionListener>,
+    pub(crate) resource_max_bytes: usize,
+    pub(crate) resource_cache_max_bytes: u64,
     #[cfg(feature = "network")]
     pub(crate) network_timeout: Duration,
     #[cfg(feature = "network")]
@@ -49,6 +190,9 @@ pub struct DIDCacheConfig {
     pub(crate) agent_names_over_websocket: bool,
     #[cfg(feature = "agent-names")]
     pub(crate) resolve_shortcuts: bool,
+    #[cfg(feature = "did-cheqd")]
+    pub(crate) cheqd: CheqdResolverConfig,
+    pub(crate) pinned: Option<Arc<PinnedDocuments>>,
 }
 
 /// DID Cache Config Builder to construct options required for the client.
@@ -56,14 +200,21 @@ pub struct DIDCacheConfig {
 ///
 /// - service_address: REQUIRED: The address of the service to connect to.
 /// - cache_capacity: The maximum number of items to store in the local cache (default: 100).
+/// - cache_max_bytes: Bound the local cache by document size instead of count (default: unset).
 /// - cache_ttl: The time-to-live in seconds for each item in the local cache (default: 300 (5 Minutes)).
+/// - cache_tti: Evict items not read for this many seconds (default: unset).
 /// - network_timeout: The timeout for network requests in milliseconds (default: 5000 (5 seconds)).
 /// - network_cache_limit_count: The maximum number of items to store in the network cache (default: 100).
 pub struct DIDCacheConfigBuilder {
     #[cfg(feature = "network")]
     service_address: Option<String>,
     cache_capacity: u32,
+    cache_max_bytes: Option<u64>,
     cache_ttl: u32,
+    cache_tti: Option<u32>,
+    eviction_listener: Option<EvictionListener>,
+    resource_max_bytes: usize,
+    resource_cache_max_bytes: u64,
     #[cfg(feature = "network")]
     network_timeout: u32,
     #[cfg(feature = "network")]
@@ -78,6 +229,9 @@ pub struct DIDCacheConfigBuilder {
     agent_names_over_websocket: bool,
     #[cfg(feature = "agent-names")]
     resolve_shortcuts: bool,
+    #[cfg(feature = "did-cheqd")]
+    cheqd: CheqdResolverConfig,
+    pinned: Option<Arc<PinnedDocuments>>,
 }
 
 impl Default for DIDCacheConfigBuilder {
@@ -86,7 +240,12 @@ impl Default for DIDCacheConfigBuilder {
             #[cfg(feature = "network")]
             service_address: None,
             cache_capacity: 100,
+            cache_max_bytes: None,
             cache_ttl: 300,
+            cache_tti: None,
+            eviction_listener: None,
+            resource_max_bytes: 1024 * 1024,
+            resource_cache_max_bytes: 16 * 1024 * 1024,
             #[cfg(feature = "network")]
             network_timeout: 5000,
             #[cfg(feature = "network")]
@@ -101,6 +260,9 @@ impl Default for DIDCacheConfigBuilder {
             agent_names_over_websocket: false,
             #[cfg(feature = "agent-names")]
             resolve_shortcuts: false,
+            #[cfg(feature = "did-cheqd")]
+            cheqd: CheqdResolverConfig::default(),
+            pinned: None,
         }
     }
 }
@@ -121,6 +283,19 @@ impl DIDCacheConfigBuilder {
         self
     }
 
+    /// Bound the local cache by the total serialized size of the cached
+    /// documents rather than by their number. Replaces
+    /// [`with_cache_capacity`](Self::with_cache_capacity) when set.
+    ///
+    /// A single `did:webvh` document with many services can be tens of
+    /// kilobytes while a `did:key` document is a few hundred bytes, so a byte
+    /// budget is the better bound on memory-constrained devices.
+    /// Default: unset (bounded by entry count)
+    pub fn with_cache_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.cache_max_bytes = Some(max_bytes);
+        self
+    }
+
     /// Set the time-to-live in seconds for mutable DID methods (web, webvh, cheqd, scid)
     /// in the local cache. Immutable methods (key, peer, jwk, ethr, pkh) are cached
     /// indefinitely and only evicted by capacity pressure.
@@ -130,6 +305,52 @@ impl DIDCacheConfigBuilder {
         self
     }
 
+    /// Evict any document, mutable or not, that has not been read for
+    /// `cache_tti` seconds. Applies on top of
+    /// [`with_cache_ttl`](Self::with_cache_ttl); whichever runs out first wins.
+    /// Default: unset (immutable documents stay until capacity pressure)
+    pub fn with_cache_tti(mut self, cache_tti: u32) -> Self {
+        self.cache_tti = Some(cache_tti);
+        self
+    }
+
+    /// Call `listener` whenever a document leaves the local cache, for
+    /// whatever reason. Runs on the cache's maintenance path, so it should
+    /// be quick and must not panic (after a panic it is no longer called).
+    pub fn with_eviction_listener(
+        mut self,
+        listener: impl Fn(CacheEviction) + Send + Sync + 'static,
+    ) -> Self {
+        self.eviction_listener = Some(EvictionListener(Arc::new(listener)));
+        self
+    }
+
+    /// Largest DID-linked resource
+    /// [`resolve_resource`](crate::DIDCacheClient::resolve_resource) will
+    /// download, in bytes.
+    /// Default: 1 MiB
+    pub fn with_resource_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.resource_max_bytes = max_bytes;
+        self
+    }
+
+    /// Total size of the DID-linked resource cache, in bytes.
+    /// Default: 16 MiB
+    pub fn with_resource_cache_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.resource_cache_max_bytes = max_bytes;
+        self
+    }
+
+    /// Read-only mode: answer only from `pinned`, never from the network,
+    /// the cache server or a resolver. A DID outside the set fails with
+    /// [`NotPinned`](crate::errors::DIDCacheError::NotPinned), and
+    /// DID-linked resources are unavailable. See [`crate::pinned`].
+    /// Default: unset
+    pub fn with_pinned_documents(mut self, pinned: PinnedDocuments) -> Self {
+        self.pinned = Some(Arc::new(pinned));
+        self
+    }
+
     /// Set the timeout for network requests in milliseconds.
     /// Default: 5000 (5 seconds)
     #[cfg(feature = "network")]
@@ -218,13 +439,26 @@ impl DIDCacheConfigBuilder {
         self
     }
 
+    /// Endpoints, retries and timeout for `did:cheqd` resolution.
+    /// Default: public mainnet/testnet endpoints, 2 retries, 10s timeout
+    #[cfg(feature = "did-cheqd")]
+    pub fn with_cheqd_config(mut self, cheqd: CheqdResolverConfig) -> Self {
+        self.cheqd = cheqd;
+        self
+    }
+
     /// Build the [ClientConfig].
     pub fn build(self) -> DIDCacheConfig {
         DIDCacheConfig {
             #[cfg(feature = "network")]
             service_address: self.service_address,
             cache_capacity: self.cache_capacity,
+            cache_max_bytes: self.cache_max_bytes,
             cache_ttl: self.cache_ttl,
+            cache_tti: self.cache_tti,
+            eviction_listener: self.eviction_listener,
+            resource_max_bytes: self.resource_max_bytes,
+            resource_cache_max_bytes: self.resource_cache_max_bytes,
             #[cfg(feature = "network")]
             network_timeout: Duration::from_millis(self.network_timeout.into()),
             #[cfg(feature = "network")]
@@ -239,6 +473,9 @@ impl DIDCacheConfigBuilder {
             agent_names_over_websocket: self.agent_names_over_websocket,
             #[cfg(feature = "agent-names")]
             resolve_shortcuts: self.resolve_shortcuts,
+            #[cfg(feature = "did-cheqd")]
+            cheqd: self.cheqd,
+            pinned: self.pinned,
         }
     }
 }
@@ -254,6 +491,37 @@ mod tests {
         assert_eq!(config.cache_ttl, 300);
         assert_eq!(config.max_did_parts, 12);
         assert_eq!(config.max_did_size_in_bytes, 1_000);
+        assert_eq!(config.cache_max_bytes, None);
+        assert_eq!(config.cache_tti, None);
+        assert!(config.eviction_listener.is_none());
+    }
+
+    #[cfg(feature = "did-cheqd")]
+    #[test]
+    fn cheqd_network_overrides_replace_by_namespace() {
+        let cheqd = CheqdResolverConfig::default()
+            .with_network("testnet", "https://a.example:443")
+            .with_network("testnet", "https://b.example:443")
+            .with_network("devnet", "https://c.example:443");
+        assert_eq!(
+            cheqd.networks,
+            [
+                ("testnet".to_string(), "https://b.example:443".to_string()),
+                ("devnet".to_string(), "https://c.example:443".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn builder_sets_memory_bounds() {
+        let config = DIDCacheConfigBuilder::default()
+            .with_cache_max_bytes(64 * 1024)
+            .with_cache_tti(30)
+            .with_eviction_listener(|_| {})
+            .build();
+        assert_eq!(config.cache_max_bytes, Some(64 * 1024));
+        assert_eq!(config.cache_tti, Some(30));
+        assert!(config.eviction_listener.is_some());
     }
 
     #[test]
@@ -1,9 +1,12 @@
 //! Error types for the DID Cache Client SDK
 use std::string::FromUtf8Error;
 
+use affinidi_error_codes::error_codes;
 use thiserror::Error;
 use wasm_bindgen::JsValue;
 
+pub use affinidi_error_codes::{ErrorCode, ErrorCodes};
+
 /// DIDCacheError is the error type for the DID Cache Client SDK.
 ///
 /// This error type is used for all errors that can occur in the DID Cache Client SDK.
@@ -37,8 +40,52 @@ pub enum DIDCacheError {
     #[cfg(feature = "agent-names")]
     #[error("Agent name error: {0}")]
     AgentNameError(String),
+
+    /// The client is in read-only pinned mode and the DID isn't in its
+    /// [pinned set](crate::pinned::PinnedDocuments).
+    #[error("DID is not in the pinned document set: {0}")]
+    NotPinned(String),
+
+    /// A DID-linked resource could not be located, fetched, or was too large.
+    #[error("Resource error: {0}")]
+    ResourceError(String),
+
+    /// A `did:webvh` archive failed to export, or failed a hash or log check on
+    /// import.
+    #[cfg(feature = "did-webvh")]
+    #[error("WebVH archive error: {0}")]
+    ArchiveError(String),
+
+    /// A `did:webvh` couldn't be moved to a new location, or a log moved one
+    /// without keeping its SCID, while not portable, or without naming the
+    /// DID it moved from.
+    #[cfg(feature = "did-webvh")]
+    #[error("WebVH migration error: {0}")]
+    MigrationError(String),
+
+    /// The caller's [`CallLimits`](crate::CallLimits) cut the call short.
+    #[error("Resolution interrupted: {0}")]
+    Interrupted(#[from] affinidi_task_utils::Interrupted),
 }
 
+error_codes!(DIDCacheError, "DIDCACHE", {
+    1 => DIDError(..): "The DID could not be parsed or resolved.",
+    2 => UnsupportedMethod(method) [method]: "No resolver supports the DID method {method}.",
+    3 => TransportError(..): "The connection to the cache server failed.",
+    4 => ConfigError(..): "The client configuration is invalid.",
+    5 => NetworkTimeout: "The cache server did not answer in time.",
+    6 => ParsingError(..): "A document or response could not be parsed.",
+    #[cfg(feature = "agent-names")]
+    7 => AgentNameError(..): "An agent name failed to parse, resolve or verify.",
+    8 => NotPinned(did) [did]: "The client is in pinned mode and {did} is not pinned.",
+    9 => ResourceError(..): "A DID-linked resource could not be fetched.",
+    #[cfg(feature = "did-webvh")]
+    10 => ArchiveError(..): "A did:webvh archive failed to export or import.",
+    11 => Interrupted(..): "The caller cancelled the call or its deadline passed.",
+    #[cfg(feature = "did-webvh")]
+    12 => MigrationError(..): "A did:webvh failed to move, or its log records an invalid move.",
+});
+
 // Converts DIDCacheError to JsValue which is required for propagating errors to WASM
 impl From<DIDCacheError> for JsValue {
     fn from(err: DIDCacheError) -> JsValue {
@@ -98,6 +145,30 @@ mod tests {
         assert_eq!(err.to_string(), "Parsing error: bad json");
     }
 
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(
+            DIDCacheError::NetworkTimeout.error_code().to_string(),
+            "DIDCACHE-0005"
+        );
+        assert_eq!(
+            DIDCacheError::NotPinned("did:example:1".into()).error_code(),
+            ErrorCode::new("DIDCACHE", 8)
+        );
+        let _ = affinidi_error_codes::Registry::new().with::<DIDCacheError>();
+    }
+
+    #[test]
+    fn error_messages_fill_params() {
+        let catalog = affinidi_error_codes::MessageCatalog::from_registry(
+            &affinidi_error_codes::Registry::new().with::<DIDCacheError>(),
+        );
+        assert_eq!(
+            catalog.message("en-GB", &DIDCacheError::NotPinned("did:example:1".into())),
+            "The client is in pinned mode and did:example:1 is not pinned."
+        );
+    }
+
     #[test]
     fn from_utf8_error() {
         let bytes = vec![0xff, 0xfe];
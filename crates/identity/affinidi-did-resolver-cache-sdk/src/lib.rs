@@ -15,9 +15,9 @@ As this crate can be used either natively or in a WASM environment, the followin
 #[cfg(all(feature = "network", target_arch = "wasm32"))]
 compile_error!("The 'network' feature is not supported on wasm32 targets");
 
-use affinidi_did_common::{DID, Document};
+use affinidi_did_common::{DID, Document, canonical_peer_did, peer_dids_equivalent};
 #[cfg(feature = "network")]
-use affinidi_task_utils::{CancellationToken, HealthRegistry, TaskSupervisor};
+use affinidi_task_utils::{HealthRegistry, TaskSupervisor};
 use config::DIDCacheConfig;
 use errors::DIDCacheError;
 use highway::{HighwayHash, HighwayHasher};
@@ -28,14 +28,19 @@ use networking::{
     network::{NetworkTask, WSCommands},
 };
 
+pub use affinidi_task_utils::{CallLimits, CancellationToken, Interrupted};
 #[cfg(feature = "network")]
 pub use affinidi_task_utils::{ComponentHealth, ComponentState};
+use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
-use std::sync::{Arc, Mutex as StdMutex};
+use std::sync::{
+    Arc, Mutex as StdMutex,
+    atomic::{AtomicU32, Ordering},
+};
 use std::{fmt, time::Duration};
-use tokio::sync::watch;
 #[cfg(feature = "network")]
 use tokio::sync::{Mutex, mpsc};
+use tokio::sync::{broadcast, watch};
 use tracing::debug;
 #[cfg(feature = "network")]
 use tracing::warn;
@@ -48,13 +53,20 @@ pub mod config;
 pub mod errors;
 #[cfg(feature = "network")]
 pub mod networking;
+pub mod pinned;
 mod resolver;
+pub mod resources;
+#[cfg(feature = "did-webvh")]
+pub mod webvh_archive;
+#[cfg(feature = "did-webvh")]
+pub mod webvh_portability;
 
 // Re-export resolver traits and network resolver implementations
 pub use affinidi_did_resolver_traits::{
     AsyncResolver, MethodName, Resolution, Resolver, ResolverError,
 };
 pub use resolver::network_resolvers;
+pub use resources::DidResource;
 
 /// DID Methods supported by the DID Universal Resolver Cache
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -170,10 +182,13 @@ pub struct ResolveResponse {
     pub did: String,
     /// The DID method of [`Self::did`].
     pub method: DIDMethod,
-    /// HighwayHash128 of [`Self::did`] — the document cache key.
+    /// HighwayHash128 of [`Self::did`] — the document cache key. A
+    /// `did:peer:2` DID is hashed in its canonical form.
     pub did_hash: [u64; 2],
-    /// The resolved DID Document.
-    pub doc: Document,
+    /// The resolved DID Document, shared with the cache rather than copied
+    /// out of it. Read through it as a `&Document`; use
+    /// [`Self::into_document`] where an owned document is needed.
+    pub doc: Arc<Document>,
     /// Whether the document came from cache rather than a fresh resolution.
     pub cache_hit: bool,
     /// A **verified** human-facing shortcut for [`Self::did`], when one is
@@ -193,19 +208,25 @@ impl ResolveResponse {
         did: String,
         method: DIDMethod,
         did_hash: [u64; 2],
-        doc: Document,
+        doc: impl Into<Arc<Document>>,
         cache_hit: bool,
     ) -> Self {
         Self {
             did,
             method,
             did_hash,
-            doc,
+            doc: doc.into(),
             cache_hit,
             shortcut: None,
         }
     }
 
+    /// The resolved document as an owned [`Document`]. Copies it only if the
+    /// cache (or another response) still shares it.
+    pub fn into_document(self) -> Document {
+        Arc::unwrap_or_clone(self.doc)
+    }
+
     /// Attach a verified shortcut.
     ///
     /// Separate from [`Self::new`] so adding shortcut support did not break the
@@ -284,21 +305,25 @@ impl std::fmt::Display for DidShortcut {
     }
 }
 
-/// Per-entry expiry policy for the DID document cache.
+/// Per-entry expiry policy for the DID document and resource caches.
 ///
 /// - **Immutable methods** (key, peer, jwk, ethr, pkh): no expiry — entries
 ///   stay cached until evicted by capacity pressure.
 /// - **Mutable methods** (web, webvh, cheqd, scid, ebsi): expire after `mutable_ttl`
-///   so that updated documents are eventually re-fetched.
+///   seconds so that updated documents are eventually re-fetched. Shared with
+///   the client, which can change it at runtime
+///   (see [`DIDCacheClient::set_cache_ttl`]).
+/// - **DID-linked resources**: always expire after `mutable_ttl`, since a
+///   resource addressed by name or path can change.
 struct DIDExpiry {
-    mutable_ttl: Duration,
+    mutable_ttl: Arc<AtomicU32>,
 }
 
-impl Expiry<[u64; 2], Document> for DIDExpiry {
+impl Expiry<[u64; 2], Arc<Document>> for DIDExpiry {
     fn expire_after_create(
         &self,
         _key: &[u64; 2],
-        value: &Document,
+        value: &Arc<Document>,
         _created_at: std::time::Instant,
     ) -> Option<Duration> {
         let did_str = value.id.as_str();
@@ -311,13 +336,85 @@ impl Expiry<[u64; 2], Document> for DIDExpiry {
             .is_some_and(|m| m.is_mutable());
 
         if is_mutable {
-            Some(self.mutable_ttl)
+            Some(Duration::from_secs(
+                self.mutable_ttl.load(Ordering::Relaxed).into(),
+            ))
         } else {
             None // no expiry — evicted only by capacity
         }
     }
 }
 
+impl Expiry<[u64; 2], DidResource> for DIDExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &[u64; 2],
+        _value: &DidResource,
+        _created_at: std::time::Instant,
+    ) -> Option<Duration> {
+        Some(Duration::from_secs(
+            self.mutable_ttl.load(Ordering::Relaxed).into(),
+        ))
+    }
+}
+
+/// Weight of a document in a byte-bounded cache: its serialized JSON size.
+fn document_weight(doc: &Document) -> u32 {
+    serde_json::to_vec(doc)
+        .map(|json| u32::try_from(json.len()).unwrap_or(u32::MAX))
+        .unwrap_or(u32::MAX)
+}
+
+/// Single-flight map: hashed key -> receiver the followers wait on.
+type InflightMap = Arc<StdMutex<HashMap<[u64; 2], watch::Receiver<()>>>>;
+
+/// Leadership of one single-flight slot.
+///
+/// Dropping it removes the slot and wakes the followers — whether the leader
+/// finished, failed, or was itself dropped mid-resolution because its caller
+/// gave up. Followers of a cancelled leader find the cache empty and retry,
+/// so a new leader takes over instead of everyone waiting on one that is gone.
+pub(crate) struct InflightLeader {
+    inflight: InflightMap,
+    hash: [u64; 2],
+    _tx: watch::Sender<()>,
+}
+
+impl InflightLeader {
+    /// Claim the slot for `hash`, or return the receiver of the call already
+    /// holding it.
+    pub(crate) fn claim(
+        inflight: &InflightMap,
+        hash: [u64; 2],
+    ) -> Result<Self, watch::Receiver<()>> {
+        let mut map = inflight.lock().expect("inflight mutex not poisoned");
+        if let Some(rx) = map.get(&hash) {
+            return Err(rx.clone());
+        }
+        let (tx, rx) = watch::channel(());
+        map.insert(hash, rx);
+        Ok(InflightLeader {
+            inflight: inflight.clone(),
+            hash,
+            _tx: tx,
+        })
+    }
+}
+
+impl Drop for InflightLeader {
+    fn drop(&mut self) {
+        // Remove the slot before the sender drops, so a woken follower that
+        // loops straight back round can claim it.
+        self.inflight
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&self.hash);
+    }
+}
+
+/// Events buffered per [`DIDCacheClient::subscribe_changes`] receiver.
+pub const CHANGE_CHANNEL_CAPACITY: usize = 256;
+
 // ***************************************************************************
 
 /// [DIDCacheClient] is how you interact with the DID Universal Resolver Cache
@@ -328,7 +425,10 @@ impl Expiry<[u64; 2], Document> for DIDExpiry {
 #[wasm_bindgen(getter_with_clone)]
 pub struct DIDCacheClient {
     config: DIDCacheConfig,
-    cache: Cache<[u64; 2], Document>,
+    cache: Cache<[u64; 2], Arc<Document>>,
+    /// TTL in seconds of mutable DID methods' documents, shared with the
+    /// cache's expiry policy.
+    mutable_ttl: Arc<AtomicU32>,
     #[cfg(feature = "network")]
     network_task_tx: Option<mpsc::Sender<WSCommands>>,
     #[cfg(feature = "network")]
@@ -359,12 +459,22 @@ pub struct DIDCacheClient {
     /// them apart means a hash collision between the two can never make one
     /// wait on the other.
     #[cfg(feature = "agent-names")]
-    agent_name_inflight: Arc<StdMutex<HashMap<[u64; 2], watch::Receiver<()>>>>,
+    agent_name_inflight: InflightMap,
     /// Single-flight map: concurrent cache misses for the same DID hash share
     /// one underlying resolution. The leader holds the `watch::Sender`; the
     /// stored `Receiver` is cloned by followers, who wake when the leader drops
     /// it and then read the freshly-cached document.
-    inflight: Arc<StdMutex<HashMap<[u64; 2], watch::Receiver<()>>>>,
+    inflight: InflightMap,
+    /// DID-linked resources by hashed DID URL, bounded by total content size.
+    resource_cache: Cache<[u64; 2], DidResource>,
+    /// HTTP client for resource fetches.
+    resource_fetcher: affinidi_did_web::DIDWeb,
+    /// Ledger client for `did:cheqd` resources.
+    #[cfg(feature = "did-cheqd")]
+    cheqd_resources: Arc<resources::CheqdResources>,
+    /// DIDs whose cached document was dropped or replaced, for
+    /// [`subscribe_changes`](DIDCacheClient::subscribe_changes).
+    changes: broadcast::Sender<String>,
 }
 
 impl Clone for DIDCacheClient {
@@ -372,6 +482,7 @@ impl Clone for DIDCacheClient {
         Self {
             config: self.config.clone(),
             cache: self.cache.clone(),
+            mutable_ttl: self.mutable_ttl.clone(),
             #[cfg(feature = "network")]
             network_task_tx: self.network_task_tx.clone(),
             #[cfg(feature = "network")]
@@ -390,6 +501,11 @@ impl Clone for DIDCacheClient {
             #[cfg(feature = "agent-names")]
             agent_name_inflight: self.agent_name_inflight.clone(),
             inflight: self.inflight.clone(),
+            resource_cache: self.resource_cache.clone(),
+            resource_fetcher: self.resource_fetcher.clone(),
+            #[cfg(feature = "did-cheqd")]
+            cheqd_resources: self.cheqd_resources.clone(),
+            changes: self.changes.clone(),
         }
     }
 }
@@ -401,6 +517,42 @@ fn is_locally_resolvable(method: &DIDMethod) -> bool {
     matches!(method, DIDMethod::KEY | DIDMethod::PEER)
 }
 
+/// Present a did:peer document cached for an equivalent spelling of `did` as
+/// the document of `did` itself. Equivalent spellings number their keys and
+/// services alike (see [`canonical_peer_did`]), so only the DID in front of
+/// each id changes.
+fn rebind_peer_document(doc: Arc<Document>, did: &str) -> Arc<Document> {
+    let from = doc.id.as_str();
+    if from == did || !peer_dids_equivalent(from, did) {
+        return doc;
+    }
+
+    fn rebind(value: &mut serde_json::Value, from: &str, to: &str) {
+        match value {
+            serde_json::Value::String(s) => {
+                if let Some(rest) = s.strip_prefix(from)
+                    && (rest.is_empty() || rest.starts_with(['#', '?', '/']))
+                {
+                    *s = format!("{to}{rest}");
+                }
+            }
+            serde_json::Value::Array(items) => {
+                items.iter_mut().for_each(|item| rebind(item, from, to))
+            }
+            serde_json::Value::Object(map) => {
+                map.values_mut().for_each(|item| rebind(item, from, to))
+            }
+            _ => {}
+        }
+    }
+
+    let Ok(mut value) = serde_json::to_value(&*doc) else {
+        return doc;
+    };
+    rebind(&mut value, from, did);
+    serde_json::from_value(value).map(Arc::new).unwrap_or(doc)
+}
+
 impl DIDCacheClient {
     /// Get a mutable reference to the inner resolver map.
     ///
@@ -511,16 +663,82 @@ impl DIDCacheClient {
     pub async fn resolve(&self, did: &str) -> Result<ResolveResponse, DIDCacheError> {
         let response = self.resolve_document(did).await?;
 
+        // Shortcuts need the naming host, which pinned mode never contacts.
         #[cfg(feature = "agent-names")]
-        if self.config.resolve_shortcuts {
-            if let Some(shortcut) = self.derive_shortcut(&response.did, &response.doc).await {
-                return Ok(response.with_shortcut(shortcut));
-            }
+        if self.config.resolve_shortcuts
+            && self.config.pinned.is_none()
+            && let Some(shortcut) = self.derive_shortcut(&response.did, &response.doc).await
+        {
+            return Ok(response.with_shortcut(shortcut));
         }
 
         Ok(response)
     }
 
+    /// [`resolve`](Self::resolve) under caller-controlled [`CallLimits`].
+    ///
+    /// When the timeout elapses or the cancellation token fires first, the
+    /// resolution is dropped and [`DIDCacheError::Interrupted`] returned.
+    /// Dropping it aborts the work rather than detaching it: a pending network
+    /// request is withdrawn from the network task, and other callers waiting
+    /// on the same DID take over the resolution instead of waiting on this one.
+    ///
+    /// In network mode the limits apply on top of the configured network
+    /// timeout, which still bounds each request to the cache server.
+    pub async fn resolve_with_limits(
+        &self,
+        did: &str,
+        limits: &CallLimits,
+    ) -> Result<ResolveResponse, DIDCacheError> {
+        limits.run(self.resolve(did)).await?
+    }
+
+    /// Fetch a DID-linked resource — a credential schema, status list, … —
+    /// named by `did_url`. See [`resources`] for the supported methods.
+    ///
+    /// Resources are cached by DID URL for the configured TTL. Bodies larger
+    /// than [`with_resource_max_bytes`](config::DIDCacheConfigBuilder::with_resource_max_bytes)
+    /// are rejected.
+    pub async fn resolve_resource(&self, did_url: &str) -> Result<DidResource, DIDCacheError> {
+        if self.config.pinned.is_some() {
+            return Err(DIDCacheError::ResourceError(format!(
+                "{did_url}: DID-linked resources are unavailable in pinned mode"
+            )));
+        }
+
+        let hash = DIDCacheClient::hash_did(did_url);
+        if let Some(mut resource) = self.resource_cache.get(&hash).await {
+            resource.cache_hit = true;
+            return Ok(resource);
+        }
+
+        let (content, content_type) = match resources::resource_location(did_url)? {
+            resources::ResourceLocation::Url(url) => {
+                debug!("resolving DID-linked resource {did_url} from {url}");
+                self.resource_fetcher
+                    .fetch_resource(&url, self.config.resource_max_bytes)
+                    .await
+                    .map_err(|e| DIDCacheError::ResourceError(format!("{did_url}: {e}")))?
+            }
+            #[cfg(feature = "did-cheqd")]
+            resources::ResourceLocation::Cheqd => {
+                debug!("resolving DID-linked resource {did_url} from the cheqd ledger");
+                self.cheqd_resources
+                    .fetch(did_url, self.config.resource_max_bytes)
+                    .await?
+            }
+        };
+
+        let resource = DidResource {
+            did_url: did_url.to_string(),
+            content_type,
+            content: content.into(),
+            cache_hit: false,
+        };
+        self.resource_cache.insert(hash, resource.clone()).await;
+        Ok(resource)
+    }
+
     /// Resolve a DID to its document, without attempting any shortcut lookup.
     ///
     /// The shortcut derivation in [`Self::resolve`] is layered on top of this
@@ -569,35 +787,64 @@ impl DIDCacheClient {
 
         let hash = DIDCacheClient::hash_did(did);
 
+        if let Some(pinned) = &self.config.pinned {
+            return match pinned.get(did) {
+                Some(doc) => Ok(ResolveResponse {
+                    did: did.to_string(),
+                    method,
+                    did_hash: hash,
+                    doc: rebind_peer_document(doc.clone(), did),
+                    cache_hit: true,
+                    shortcut: None,
+                }),
+                None => Err(DIDCacheError::NotPinned(did.to_string())),
+            };
+        }
+
         #[cfg(feature = "did_example")]
         // Short-circuit for example DIDs
         if matches!(method, DIDMethod::EXAMPLE)
-            && let Some(doc) = self.did_example_cache.get(did)
+            && let Some(doc) = self.did_example_cache.get_shared(did)
         {
             return Ok(ResolveResponse {
                 did: did.to_string(),
                 method,
                 did_hash: hash,
-                doc: doc.clone(),
+                doc,
                 cache_hit: true,
                 shortcut: None,
             });
         }
 
-        // Check if the DID is in the cache
+        // Check if the DID is in the cache. Equivalent did:peer spellings hash
+        // alike, so the entry may hold the document of another spelling.
         if let Some(doc) = self.cache.get(&hash).await {
             debug!("DID cache hit: {}", did);
             Ok(ResolveResponse {
                 did: did.to_string(),
                 method,
                 did_hash: hash,
-                doc,
+                doc: rebind_peer_document(doc, did),
                 cache_hit: true,
                 shortcut: None,
             })
         } else {
             debug!("DID cache miss: {}", did);
-            self.resolve_uncached(did, &parsed_did, &method, hash).await
+            // Resolve the canonical spelling, so whichever spelling is seen
+            // first, the cache holds the same document.
+            let canonical = canonical_peer_did(did);
+            let parsed_canonical = match &canonical {
+                Cow::Borrowed(_) => parsed_did,
+                Cow::Owned(canonical) => canonical
+                    .parse()
+                    .map_err(|e| DIDCacheError::DIDError(format!("Failed to parse DID: {e}")))?,
+            };
+            let mut response = self
+                .resolve_uncached(&canonical, &parsed_canonical, &method, hash)
+                .await?;
+            response.did = did.to_string();
+            response.doc = rebind_peer_document(response.doc, did);
+            Ok(response)
         }
     }
 
@@ -615,18 +862,12 @@ impl DIDCacheClient {
         loop {
             // Decide our role under the lock. No `.await` is held across it.
             enum Role {
-                Leader(watch::Sender<()>),
+                Leader(InflightLeader),
                 Follower(watch::Receiver<()>),
             }
-            let role = {
-                let mut map = self.inflight.lock().expect("inflight mutex not poisoned");
-                if let Some(rx) = map.get(&hash) {
-                    Role::Follower(rx.clone())
-                } else {
-                    let (tx, rx) = watch::channel(());
-                    map.insert(hash, rx);
-                    Role::Leader(tx)
-                }
+            let role = match InflightLeader::claim(&self.inflight, hash) {
+                Ok(leader) => Role::Leader(leader),
+                Err(rx) => Role::Follower(rx),
             };
 
             match role {
@@ -648,15 +889,11 @@ impl DIDCacheClient {
                     // try to become the leader ourselves.
                     continue;
                 }
-                Role::Leader(tx) => {
+                Role::Leader(leader) => {
                     // A prior leader may have populated the cache between our
                     // miss check and acquiring leadership.
                     if let Some(doc) = self.cache.get(&hash).await {
-                        self.inflight
-                            .lock()
-                            .expect("inflight mutex not poisoned")
-                            .remove(&hash);
-                        drop(tx);
+                        drop(leader);
                         return Ok(ResolveResponse {
                             did: did.to_string(),
                             method: method.clone(),
@@ -667,17 +904,16 @@ impl DIDCacheClient {
                         });
                     }
 
-                    let result = self.resolve_once(did, parsed_did, method, hash).await;
+                    let result = self
+                        .resolve_once(did, parsed_did, method, hash)
+                        .await
+                        .map(Arc::new);
                     if let Ok(ref doc) = result {
                         debug!("DID cached: {}", did);
                         self.cache.insert(hash, doc.clone()).await;
                     }
                     // Release leadership and wake followers regardless of outcome.
-                    self.inflight
-                        .lock()
-                        .expect("inflight mutex not poisoned")
-                        .remove(&hash);
-                    drop(tx);
+                    drop(leader);
 
                     return result.map(|doc| ResolveResponse {
                         did: did.to_string(),
@@ -733,10 +969,29 @@ impl DIDCacheClient {
     /// If you want to interact directly with the DID Document cache
     /// This will return a clone of the cache (the clone is cheap, and the cache is shared)
     /// For example, accessing cache statistics or manually inserting a DID Document
-    pub fn get_cache(&self) -> Cache<[u64; 2], Document> {
+    ///
+    /// Documents are cached as `Arc<Document>`, so resolving hands out a
+    /// reference to the cached document rather than a copy of it.
+    pub fn get_cache(&self) -> Cache<[u64; 2], Arc<Document>> {
         self.cache.clone()
     }
 
+    /// The TTL in seconds of documents of mutable DID methods and of
+    /// DID-linked resources, initially
+    /// [`with_cache_ttl`](config::DIDCacheConfigBuilder::with_cache_ttl).
+    pub fn cache_ttl(&self) -> u32 {
+        self.mutable_ttl.load(Ordering::Relaxed)
+    }
+
+    /// Changes the TTL in seconds of documents of mutable DID methods and of
+    /// DID-linked resources, for every clone of this client.
+    ///
+    /// Applies to entries cached from now on; entries already cached keep
+    /// the TTL they were cached with.
+    pub fn set_cache_ttl(&self, cache_ttl: u32) {
+        self.mutable_ttl.store(cache_ttl, Ordering::Relaxed);
+    }
+
     /// Stops the network task if it is running and removes any resources.
     ///
     /// Cancels the supervisor's shutdown token, which aborts the network task
@@ -764,20 +1019,40 @@ impl DIDCacheClient {
     /// Removes the specified DID from the cache
     /// Returns the removed DID Document if it was in the cache, or None if it was not
     pub async fn remove(&self, did: &str) -> Option<Document> {
-        self.cache.remove(&DIDCacheClient::hash_did(did)).await
+        self.cache
+            .remove(&DIDCacheClient::hash_did(did))
+            .await
+            .map(Arc::unwrap_or_clone)
+    }
+
+    /// Subscribe to DIDs whose cached document was removed, replaced or
+    /// expired.
+    ///
+    /// Anything derived from a document (negotiated keys, service endpoints)
+    /// should be re-derived after its DID appears here: the next resolve may
+    /// return a different document. A receiver that falls more than
+    /// [`CHANGE_CHANNEL_CAPACITY`] events behind gets
+    /// [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged)
+    /// and should treat every DID as changed.
+    pub fn subscribe_changes(&self) -> broadcast::Receiver<String> {
+        self.changes.subscribe()
     }
 
     /// Add a DID Document to the cache manually
     pub async fn add_did_document(&mut self, did: &str, doc: Document) {
         let hash = DIDCacheClient::hash_did(did);
         debug!("DID manually cached: {}", did);
-        self.cache.insert(hash, doc).await;
+        self.cache.insert(hash, Arc::new(doc)).await;
     }
 
     /// Convenience function to hash a DID
+    ///
+    /// `did:peer:2` DIDs are hashed in their
+    /// [canonical form](affinidi_did_common::canonical_peer_did), so
+    /// equivalent spellings share a cache entry.
     pub fn hash_did(did: &str) -> [u64; 2] {
         // Use a consistent Seed so it always hashes to the same value
-        HighwayHasher::default().hash128(did.as_bytes())
+        HighwayHasher::default().hash128(canonical_peer_did(did).as_bytes())
     }
 }
 
@@ -795,12 +1070,37 @@ impl DIDCacheClient {
         // Create the cache with per-entry expiry:
         // - Immutable DID methods (key, peer, jwk, ethr, pkh) → no TTL (evicted only by capacity)
         // - Mutable DID methods (web, webvh, cheqd, scid, ebsi) → expire after cache_ttl seconds
-        let cache = Cache::builder()
-            .max_capacity(config.cache_capacity.into())
-            .expire_after(DIDExpiry {
-                mutable_ttl: Duration::from_secs(config.cache_ttl.into()),
-            })
-            .build();
+        //
+        // With `cache_max_bytes` the capacity is a byte budget, each entry
+        // weighing its serialized size; otherwise every entry weighs 1.
+        let mutable_ttl = Arc::new(AtomicU32::new(config.cache_ttl));
+        let mut builder = Cache::builder().expire_after(DIDExpiry {
+            mutable_ttl: mutable_ttl.clone(),
+        });
+        builder = match config.cache_max_bytes {
+            Some(max_bytes) => builder
+                .max_capacity(max_bytes)
+                .weigher(|_, doc: &Arc<Document>| document_weight(doc)),
+            None => builder.max_capacity(config.cache_capacity.into()),
+        };
+        if let Some(tti) = config.cache_tti {
+            builder = builder.time_to_idle(Duration::from_secs(tti.into()));
+        }
+        let (changes, _) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        let listener = config.eviction_listener.clone();
+        let change_tx = changes.clone();
+        builder = builder.eviction_listener(move |_, doc: Arc<Document>, cause| {
+            // No subscribers is not an error
+            let _ = change_tx.send(doc.id.to_string());
+            if let Some(listener) = &listener {
+                (listener.0)(config::CacheEviction {
+                    did: doc.id.to_string(),
+                    cause: cause.into(),
+                    size_bytes: document_weight(&doc),
+                })
+            }
+        });
+        let cache = builder.build();
 
         // Register built-in resolvers
         let mut resolvers: HashMap<MethodName, VecDeque<Box<dyn AsyncResolver>>> = HashMap::new();
@@ -841,7 +1141,9 @@ impl DIDCacheClient {
         resolvers
             .entry(MethodName::Cheqd)
             .or_default()
-            .push_back(Box::new(network_resolvers::CheqdResolver));
+            .push_back(Box::new(network_resolvers::CheqdResolver::new(
+                &config.cheqd,
+            )));
         #[cfg(feature = "did-scid")]
         resolvers
             .entry(MethodName::Scid)
@@ -869,10 +1171,26 @@ impl DIDCacheClient {
                 Box::new(::agent_names::HttpRedirectResolver::new()) as Box<_>
             ]);
 
+        // DID-linked resources: a byte-bounded cache on the mutable TTL, since
+        // a resource addressed by name or path can change.
+        let resource_cache = Cache::builder()
+            .max_capacity(config.resource_cache_max_bytes)
+            .weigher(|_, resource: &DidResource| {
+                u32::try_from(resource.content.len()).unwrap_or(u32::MAX)
+            })
+            .expire_after(DIDExpiry {
+                mutable_ttl: mutable_ttl.clone(),
+            })
+            .build();
+        let resource_fetcher = affinidi_did_web::DIDWeb::new();
+        #[cfg(feature = "did-cheqd")]
+        let cheqd_resources = Arc::new(resources::CheqdResources::new(&config.cheqd));
+
         #[cfg(feature = "network")]
         let mut client = Self {
             config,
             cache,
+            mutable_ttl,
             network_task_tx: None,
             network_task_rx: None,
             network_shutdown: None,
@@ -887,11 +1205,17 @@ impl DIDCacheClient {
             #[cfg(feature = "agent-names")]
             agent_name_inflight: Arc::new(StdMutex::new(HashMap::new())),
             inflight: Arc::new(StdMutex::new(HashMap::new())),
+            resource_cache: resource_cache.clone(),
+            resource_fetcher: resource_fetcher.clone(),
+            #[cfg(feature = "did-cheqd")]
+            cheqd_resources: cheqd_resources.clone(),
+            changes,
         };
         #[cfg(not(feature = "network"))]
         let client = Self {
             config,
             cache,
+            mutable_ttl,
             #[cfg(feature = "did_example")]
             did_example_cache: did_example::DiDExampleCache::new(),
             resolvers,
@@ -902,11 +1226,17 @@ impl DIDCacheClient {
             #[cfg(feature = "agent-names")]
             agent_name_inflight: Arc::new(StdMutex::new(HashMap::new())),
             inflight: Arc::new(StdMutex::new(HashMap::new())),
+            resource_cache,
+            resource_fetcher,
+            #[cfg(feature = "did-cheqd")]
+            cheqd_resources,
+            changes,
         };
 
         #[cfg(feature = "network")]
         {
-            if client.config.service_address.is_some() {
+            // Pinned mode never resolves remotely, so needs no network task.
+            if client.config.service_address.is_some() && client.config.pinned.is_none() {
                 // Running in network mode
 
                 // Channel to communicate from SDK to network task
@@ -1103,7 +1433,20 @@ mod tests {
         // Resolve a DID which automatically adds it to the cache
         let response = client.resolve(DID_KEY).await.unwrap();
         let removed_doc = client.remove(DID_KEY).await;
-        assert_eq!(removed_doc, Some(response.doc));
+        assert_eq!(removed_doc, Some(response.into_document()));
+    }
+
+    #[tokio::test]
+    async fn removal_is_published_to_change_subscribers() {
+        let client = basic_local_client().await;
+        let mut changes = client.subscribe_changes();
+
+        client.resolve(DID_KEY).await.unwrap();
+        assert!(changes.try_recv().is_err());
+
+        client.remove(DID_KEY).await;
+        client.get_cache().run_pending_tasks().await;
+        assert_eq!(changes.try_recv().unwrap(), DID_KEY);
     }
 
     #[tokio::test]
@@ -1137,7 +1480,9 @@ mod tests {
         client.remove(DID_KEY).await;
 
         // Manually add it back
-        client.add_did_document(DID_KEY, response.doc.clone()).await;
+        client
+            .add_did_document(DID_KEY, (*response.doc).clone())
+            .await;
 
         // Should be a cache hit now
         let cached = client.resolve(DID_KEY).await.unwrap();
@@ -1155,6 +1500,47 @@ mod tests {
         assert!(cache.get(&hash).await.is_some());
     }
 
+    #[tokio::test]
+    async fn cache_hits_share_the_cached_document() {
+        let client = basic_local_client().await;
+        let first = client.resolve(DID_KEY).await.unwrap();
+        let second = client.resolve(DID_KEY).await.unwrap();
+        assert!(second.cache_hit);
+        assert!(Arc::ptr_eq(&first.doc, &second.doc));
+
+        let owned: Document = second.into_document();
+        assert_eq!(owned, *first.doc);
+    }
+
+    #[tokio::test]
+    async fn equivalent_peer_dids_share_a_cache_entry() {
+        const KEYS: &str = "Vz6MkiToqovww7vYtxm1xNM15u9JzqzUFZ1k7s7MazYJUyAxv.EzQ3shQLqRUza6AMJFbPuMdvFRFWm1wKviQRnQSC1fScovJN4s";
+        const SERVICE: &str = "SeyJ0IjoiZG0iLCJzIjoiaHR0cHM6Ly9leGFtcGxlLmNvbS9kaWRjb21tIn0";
+        let canonical = format!("did:peer:2.{KEYS}.{SERVICE}");
+        let reordered = format!("did:peer:2.{SERVICE}.{KEYS}");
+        assert_eq!(
+            DIDCacheClient::hash_did(&canonical),
+            DIDCacheClient::hash_did(&reordered)
+        );
+
+        let client = basic_local_client().await;
+        let first = client.resolve(&reordered).await.unwrap();
+        assert!(!first.cache_hit);
+        assert_eq!(first.doc.id.as_str(), reordered);
+
+        let second = client.resolve(&canonical).await.unwrap();
+        assert!(second.cache_hit);
+        assert_eq!(second.did, canonical);
+        assert_eq!(second.doc.id.as_str(), canonical);
+        assert_eq!(
+            second.doc.verification_method[0].id.as_str(),
+            format!("{canonical}#key-1")
+        );
+        let cache = client.get_cache();
+        cache.run_pending_tasks().await;
+        assert_eq!(cache.entry_count(), 1);
+    }
+
     #[tokio::test]
     async fn clone_shares_cache() {
         let client = basic_local_client().await;
@@ -1337,6 +1723,169 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn set_cache_ttl_applies_to_new_entries() {
+        let client = DIDCacheClient::new(config::DIDCacheConfigBuilder::default().build())
+            .await
+            .unwrap();
+        let expiry = DIDExpiry {
+            mutable_ttl: client.mutable_ttl.clone(),
+        };
+        let doc = Arc::new(Document::new("did:web:example.com").unwrap());
+        let ttl = |expiry: &DIDExpiry| {
+            expiry.expire_after_create(&[0, 0], &doc, std::time::Instant::now())
+        };
+
+        assert_eq!(ttl(&expiry), Some(Duration::from_secs(300)));
+
+        // Set through a clone: all clones share the one cache.
+        client.clone().set_cache_ttl(30);
+        assert_eq!(client.cache_ttl(), 30);
+        assert_eq!(ttl(&expiry), Some(Duration::from_secs(30)));
+    }
+
+    #[tokio::test]
+    async fn set_cache_ttl_applies_to_resources() {
+        let client = DIDCacheClient::new(config::DIDCacheConfigBuilder::default().build())
+            .await
+            .unwrap();
+        let expiry = DIDExpiry {
+            mutable_ttl: client.mutable_ttl.clone(),
+        };
+        let resource = DidResource {
+            did_url: "did:web:example.com/schemas/a.json".to_string(),
+            content_type: None,
+            content: Arc::from(&b"{}"[..]),
+            cache_hit: false,
+        };
+        let ttl = |expiry: &DIDExpiry| {
+            expiry.expire_after_create(&[0, 0], &resource, std::time::Instant::now())
+        };
+
+        assert_eq!(ttl(&expiry), Some(Duration::from_secs(300)));
+
+        client.set_cache_ttl(30);
+        assert_eq!(ttl(&expiry), Some(Duration::from_secs(30)));
+
+        // The resource cache itself follows the new TTL.
+        let hash = [1, 2];
+        client.resource_cache.insert(hash, resource.clone()).await;
+        client.set_cache_ttl(0);
+        client.resource_cache.insert([3, 4], resource.clone()).await;
+        client.resource_cache.run_pending_tasks().await;
+        assert!(client.resource_cache.get(&hash).await.is_some());
+        assert!(client.resource_cache.get(&[3, 4]).await.is_none());
+    }
+
+    // -----------------------------------------------------------------------
+    // Memory bounds and eviction listener
+    // -----------------------------------------------------------------------
+
+    const OTHER_DID_KEY: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+
+    fn recording_listener() -> (
+        Arc<StdMutex<Vec<config::CacheEviction>>>,
+        impl Fn(config::CacheEviction) + Send + Sync + 'static,
+    ) {
+        let events = Arc::new(StdMutex::new(Vec::new()));
+        let recorded = events.clone();
+        (events, move |eviction| {
+            recorded.lock().unwrap().push(eviction)
+        })
+    }
+
+    #[tokio::test]
+    async fn byte_budget_bounds_cache_and_reports_evictions() {
+        let probe = DIDCacheClient::new(config::DIDCacheConfigBuilder::default().build())
+            .await
+            .unwrap();
+        let weight = u64::from(document_weight(&probe.resolve(DID_KEY).await.unwrap().doc));
+
+        let (events, listener) = recording_listener();
+        let config = config::DIDCacheConfigBuilder::default()
+            .with_cache_max_bytes(weight + weight / 2)
+            .with_eviction_listener(listener)
+            .build();
+        let client = DIDCacheClient::new(config).await.unwrap();
+        client.resolve(DID_KEY).await.unwrap();
+        client.resolve(OTHER_DID_KEY).await.unwrap();
+        client.cache.run_pending_tasks().await;
+
+        assert!(client.cache.weighted_size() <= weight + weight / 2);
+        let events = events.lock().unwrap();
+        assert!(
+            events
+                .iter()
+                .any(|e| e.cause == config::CacheEvictionCause::Size
+                    && u64::from(e.size_bytes) == weight),
+            "expected a size eviction, got {events:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn tti_evicts_idle_immutable_documents() {
+        let (events, listener) = recording_listener();
+        let config = config::DIDCacheConfigBuilder::default()
+            .with_cache_tti(1)
+            .with_eviction_listener(listener)
+            .build();
+        let client = DIDCacheClient::new(config).await.unwrap();
+        client.resolve(DID_KEY).await.unwrap();
+
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        client.cache.run_pending_tasks().await;
+
+        assert!(!client.resolve(DID_KEY).await.unwrap().cache_hit);
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1, "{events:?}");
+        assert_eq!(events[0].did, DID_KEY);
+        assert_eq!(events[0].cause, config::CacheEvictionCause::Expired);
+    }
+
+    #[tokio::test]
+    async fn pinned_mode_answers_only_from_the_pinned_set() {
+        let pinned =
+            pinned::PinnedDocuments::from_json(r#"[{ "id": "did:web:issuer.example" }]"#).unwrap();
+        let config = config::DIDCacheConfigBuilder::default()
+            .with_pinned_documents(pinned)
+            .build();
+        let client = DIDCacheClient::new(config).await.unwrap();
+
+        let response = client.resolve("did:web:issuer.example").await.unwrap();
+        assert_eq!(response.doc.id.as_str(), "did:web:issuer.example");
+
+        // Even a DID the client could compute locally is refused.
+        assert!(matches!(
+            client.resolve(DID_KEY).await,
+            Err(DIDCacheError::NotPinned(_))
+        ));
+        assert!(
+            client
+                .resolve_resource("did:web:issuer.example/schema.json")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn pinned_mode_accepts_equivalent_peer_spellings() {
+        const KEYS: &str = "Vz6MkiToqovww7vYtxm1xNM15u9JzqzUFZ1k7s7MazYJUyAxv.EzQ3shQLqRUza6AMJFbPuMdvFRFWm1wKviQRnQSC1fScovJN4s";
+        const SERVICE: &str = "SeyJ0IjoiZG0iLCJzIjoiaHR0cHM6Ly9leGFtcGxlLmNvbS9kaWRjb21tIn0";
+        let canonical = format!("did:peer:2.{KEYS}.{SERVICE}");
+        let reordered = format!("did:peer:2.{SERVICE}.{KEYS}");
+
+        let pinned =
+            pinned::PinnedDocuments::new().with_document(Document::new(&canonical).unwrap());
+        let config = config::DIDCacheConfigBuilder::default()
+            .with_pinned_documents(pinned)
+            .build();
+        let client = DIDCacheClient::new(config).await.unwrap();
+
+        let response = client.resolve(&reordered).await.unwrap();
+        assert_eq!(response.did, reordered);
+        assert_eq!(response.doc.id.as_str(), reordered);
+    }
+
     // -----------------------------------------------------------------------
     // W3 resilience: single-flight dedup + degraded-mode local fallback
     // -----------------------------------------------------------------------
@@ -1429,6 +1978,55 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn cancelled_leader_hands_over_to_a_follower() {
+        let did = "did:web:example.com";
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut client = basic_local_client().await;
+        client.set_resolver(
+            MethodName::Web,
+            Box::new(CountingResolver {
+                calls: calls.clone(),
+                delay: Duration::from_millis(200),
+                doc: Document::new(did).unwrap(),
+            }),
+        );
+
+        let cancel = CancellationToken::new();
+        let leader = tokio::spawn({
+            let client = client.clone();
+            let limits = CallLimits::new().with_cancel(cancel.clone());
+            async move { client.resolve_with_limits(did, &limits).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let follower = tokio::spawn({
+            let client = client.clone();
+            async move { client.resolve(did).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancel.cancel();
+
+        assert!(matches!(
+            leader.await.unwrap(),
+            Err(DIDCacheError::Interrupted(Interrupted::Cancelled))
+        ));
+        let response = follower.await.unwrap().expect("follower takes over");
+        assert!(!response.cache_hit);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(client.inflight.lock().unwrap().is_empty());
+
+        let timed_out = client
+            .resolve_with_limits(
+                "did:web:other.example.com",
+                &CallLimits::new().with_timeout(Duration::from_millis(10)),
+            )
+            .await;
+        assert!(matches!(
+            timed_out,
+            Err(DIDCacheError::Interrupted(Interrupted::TimedOut(_)))
+        ));
+    }
+
     #[cfg(feature = "network")]
     #[tokio::test]
     async fn network_mode_degrades_then_falls_back_to_local() {
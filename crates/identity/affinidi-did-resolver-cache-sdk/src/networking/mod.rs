@@ -8,7 +8,10 @@ use affinidi_did_common::Document;
 use network::WSCommands;
 use rand::{RngExt, distr::Alphanumeric};
 use serde::{Deserialize, Serialize};
-use tokio::{select, sync::oneshot};
+use tokio::{
+    select,
+    sync::{mpsc, oneshot},
+};
 use tracing::{Instrument, Level, debug, span, warn};
 
 use crate::{DIDCacheClient, errors::DIDCacheError};
@@ -19,6 +22,47 @@ pub mod network;
 pub(crate) mod utils;
 
 mod request_queue;
+/// A request registered with the network task and not yet answered.
+///
+/// Dropping it unsettled — the resolving future was dropped because its caller
+/// cancelled or hit its own deadline — withdraws the request, so the network
+/// task frees its slot in the in-flight request cache rather than holding it
+/// until the server replies to nobody.
+struct PendingRequest {
+    network_task_tx: mpsc::Sender<WSCommands>,
+    unique_id: String,
+    hash: [u64; 2],
+    settled: bool,
+}
+
+impl PendingRequest {
+    fn new(network_task_tx: &mpsc::Sender<WSCommands>, unique_id: &str, hash: [u64; 2]) -> Self {
+        PendingRequest {
+            network_task_tx: network_task_tx.clone(),
+            unique_id: unique_id.to_string(),
+            hash,
+            settled: false,
+        }
+    }
+
+    /// The request was answered, or its withdrawal is handled by the caller.
+    fn settle(mut self) {
+        self.settled = true;
+    }
+}
+
+impl Drop for PendingRequest {
+    fn drop(&mut self) {
+        if !self.settled {
+            debug!("withdrawing abandoned network request ({})", self.unique_id);
+            let _ = self.network_task_tx.try_send(WSCommands::TimeOut(
+                std::mem::take(&mut self.unique_id),
+                self.hash,
+            ));
+        }
+    }
+}
+
 /// A resolution request sent over the WebSocket connection.
 ///
 /// `#[non_exhaustive]`: build via [`WSRequest::new`]. Fields stay public for
@@ -229,6 +273,7 @@ impl DIDCacheClient {
                         "Couldn't send request to network_task. Reason: {e}",
                     ))
                 })?;
+            let pending = PendingRequest::new(&network_task_tx, &unique_id, name_hash);
 
             let sleep = tokio::time::sleep(self.config.network_timeout);
             tokio::pin!(sleep);
@@ -236,6 +281,7 @@ impl DIDCacheClient {
             select! {
                 _ = &mut sleep => {
                     warn!("Timeout resolving agent name ({canonical_name})");
+                    pending.settle();
                     network_task_tx
                         .send(WSCommands::TimeOut(unique_id, name_hash))
                         .await
@@ -247,6 +293,7 @@ impl DIDCacheClient {
                     Err(DIDCacheError::NetworkTimeout)
                 }
                 value = rx => {
+                    pending.settle();
                     match value {
                         Ok(WSCommands::ResponseReceived(response)) => {
                             let response = *response;
@@ -320,6 +367,9 @@ impl DIDCacheClient {
                         "Couldn't send request to network_task. Reason: {e}",
                     ))
                 })?;
+            // Withdraws the request from the network task if we are dropped
+            // before an answer arrives (the caller cancelled or timed out)
+            let pending = PendingRequest::new(&network_task_tx, &unique_id, did_hash);
 
             // 2. Wait for the response from the network task
 
@@ -330,12 +380,14 @@ impl DIDCacheClient {
                 select! {
                     _ = &mut sleep => {
                         warn!("Timeout reached, no message received did_hash ({:#?})", did_hash);
+                        pending.settle();
                         network_task_tx.send(WSCommands::TimeOut(unique_id, did_hash)).await.map_err(|err| {
                             DIDCacheError::TransportError(format!("Could not send timeout message to ws_handler: {err:?}"))
                         })?;
                          Err(DIDCacheError::NetworkTimeout)
                     }
                     value = rx => {
+                        pending.settle();
                         match value {
                             Ok(WSCommands::ResponseReceived(response)) => {
                                 debug!("Received response from network task ({:#?})", did_hash);
@@ -421,6 +473,19 @@ impl DIDCacheClient {
                     )));
                 }
 
+                // A log for another DID is only acceptable as the redirect
+                // chain of a DID that has since moved.
+                if verified_doc.id.as_str() != did {
+                    let chain = crate::webvh_portability::verify_migration_chain(did, log_data)
+                        .map_err(|e| {
+                            DIDCacheError::DIDError(format!(
+                                "WebVH log for DID {did} is for {}: {e}",
+                                verified_doc.id
+                            ))
+                        })?;
+                    debug!("did:webvh {} has moved to {}", did, chain.current());
+                }
+
                 debug!("WebVH log verification passed for DID: {}", did);
                 return Ok(doc);
             } else {
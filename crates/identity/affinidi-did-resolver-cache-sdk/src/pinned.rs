@@ -0,0 +1,241 @@
+//! Pinned document sets for read-only resolution.
+//!
+//! An audit that re-verifies a credential months later, or a CI run of a
+//! downstream service, needs every DID to resolve to the same document every
+//! time. Give the client a [`PinnedDocuments`] set with
+//! [`with_pinned_documents`](crate::config::DIDCacheConfigBuilder::with_pinned_documents)
+//! and it answers only from that set: it never touches the network, the cache
+//! server or a resolver, and a DID outside the set fails with
+//! [`DIDCacheError::NotPinned`].
+//!
+//! A set can be loaded from:
+//!
+//! - a JSON array of DID documents,
+//! - a JSON object mapping each DID to its document,
+//! - a single DID document, or
+//! - JSON Lines, one document per line,
+//!
+//! and, with the `did-webvh` feature, from a verified
+//! [`WebVHArchive`](crate::webvh_archive::WebVHArchive) import.
+//!
+//! ```rust,ignore
+//! use affinidi_did_resolver_cache_sdk::{
+//!     DIDCacheClient, config::DIDCacheConfigBuilder, pinned::PinnedDocuments,
+//! };
+//!
+//! let pinned = PinnedDocuments::from_file("fixtures/dids.json")?;
+//! let client = DIDCacheClient::new(
+//!     DIDCacheConfigBuilder::default()
+//!         .with_pinned_documents(pinned)
+//!         .build(),
+//! )
+//! .await?;
+//! ```
+
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use affinidi_did_common::{Document, canonical_peer_did, peer_dids_equivalent};
+use serde_json::Value;
+
+use crate::errors::DIDCacheError;
+
+/// A fixed set of DID documents, keyed by DID. Equivalent `did:peer:2`
+/// spellings share one entry (see [`canonical_peer_did`]).
+#[derive(Clone, Debug, Default)]
+pub struct PinnedDocuments {
+    documents: HashMap<String, Arc<Document>>,
+}
+
+impl PinnedDocuments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pin `doc` under its own `id`.
+    pub fn with_document(mut self, doc: Document) -> Self {
+        self.insert(doc);
+        self
+    }
+
+    /// Pin `doc` under its own `id`, replacing any document already pinned
+    /// for that DID.
+    pub fn insert(&mut self, doc: Document) -> Option<Arc<Document>> {
+        self.documents
+            .insert(pin_key(doc.id.as_str()), Arc::new(doc))
+    }
+
+    /// Pin the current document of a verified `did:webvh` log.
+    #[cfg(feature = "did-webvh")]
+    pub fn insert_webvh(
+        &mut self,
+        log: &crate::webvh_archive::VerifiedWebVHLog,
+    ) -> Option<Arc<Document>> {
+        self.documents
+            .insert(pin_key(log.did()), Arc::new(log.document().clone()))
+    }
+
+    /// Parse a JSON array of documents, a JSON object mapping DIDs to
+    /// documents, a single document, or JSON Lines.
+    ///
+    /// Rejects a mapping whose key differs from the document's `id`, and a DID
+    /// that appears twice, rather than guess which document was meant.
+    pub fn from_json(input: &str) -> Result<Self, DIDCacheError> {
+        let mut pinned = PinnedDocuments::new();
+        let documents = match serde_json::from_str::<Value>(input) {
+            Ok(Value::Array(documents)) => documents,
+            Ok(Value::Object(map)) if !map.contains_key("id") => {
+                for (did, doc) in map {
+                    let doc: Document = serde_json::from_value(doc)?;
+                    if !peer_dids_equivalent(doc.id.as_str(), &did) {
+                        return Err(DIDCacheError::ConfigError(format!(
+                            "pinned document for {did} has id {}",
+                            doc.id
+                        )));
+                    }
+                    pinned.insert_unique(doc)?;
+                }
+                return Ok(pinned);
+            }
+            Ok(document @ Value::Object(_)) => vec![document],
+            // JSON Lines
+            _ => input
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<_, _>>()?,
+        };
+        for doc in documents {
+            pinned.insert_unique(serde_json::from_value(doc)?)?;
+        }
+        Ok(pinned)
+    }
+
+    /// Read a set from a file. See [`Self::from_json`] for the formats.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, DIDCacheError> {
+        let path = path.as_ref();
+        let input = std::fs::read_to_string(path).map_err(|e| {
+            DIDCacheError::ConfigError(format!(
+                "couldn't read pinned documents from {}: {e}",
+                path.display()
+            ))
+        })?;
+        Self::from_json(&input)
+    }
+
+    /// Serialize as a JSON object mapping each DID to its document, sorted by
+    /// DID so the output is stable.
+    pub fn to_json(&self) -> Result<String, DIDCacheError> {
+        let sorted: std::collections::BTreeMap<_, &Document> = self
+            .documents
+            .values()
+            .map(|doc| (doc.id.as_str(), doc.as_ref()))
+            .collect();
+        Ok(serde_json::to_string_pretty(&sorted)?)
+    }
+
+    /// The document pinned for `did` or an equivalent `did:peer:2` spelling
+    /// of it. Its `id` is the spelling that was pinned.
+    pub fn get(&self, did: &str) -> Option<&Arc<Document>> {
+        self.documents.get(canonical_peer_did(did).as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    fn insert_unique(&mut self, doc: Document) -> Result<(), DIDCacheError> {
+        let did = doc.id.to_string();
+        if self
+            .documents
+            .insert(pin_key(&did), Arc::new(doc))
+            .is_some()
+        {
+            return Err(DIDCacheError::ConfigError(format!(
+                "{did} is pinned more than once"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The key a DID is pinned under.
+fn pin_key(did: &str) -> String {
+    canonical_peer_did(did).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc(did: &str) -> Value {
+        json!({ "id": did })
+    }
+
+    #[test]
+    fn loads_arrays_maps_and_json_lines() {
+        let array = json!([doc("did:web:a.example"), doc("did:web:b.example")]).to_string();
+        assert_eq!(PinnedDocuments::from_json(&array).unwrap().len(), 2);
+
+        let map = json!({ "did:web:a.example": doc("did:web:a.example") }).to_string();
+        let pinned = PinnedDocuments::from_json(&map).unwrap();
+        assert!(pinned.get("did:web:a.example").is_some());
+
+        let lines = format!(
+            "{}\n\n{}\n",
+            doc("did:web:a.example"),
+            doc("did:web:b.example")
+        );
+        assert_eq!(PinnedDocuments::from_json(&lines).unwrap().len(), 2);
+
+        let single = doc("did:web:a.example").to_string();
+        assert_eq!(PinnedDocuments::from_json(&single).unwrap().len(), 1);
+
+        let pretty = serde_json::to_string_pretty(&doc("did:web:a.example")).unwrap();
+        assert!(pretty.lines().count() > 1);
+        let pinned = PinnedDocuments::from_json(&pretty).unwrap();
+        assert!(pinned.get("did:web:a.example").is_some());
+    }
+
+    #[test]
+    fn equivalent_peer_spellings_share_an_entry() {
+        const KEYS: &str = "Vz6MkiToqovww7vYtxm1xNM15u9JzqzUFZ1k7s7MazYJUyAxv.EzQ3shQLqRUza6AMJFbPuMdvFRFWm1wKviQRnQSC1fScovJN4s";
+        const SERVICE: &str = "SeyJ0IjoiZG0iLCJzIjoiaHR0cHM6Ly9leGFtcGxlLmNvbS9kaWRjb21tIn0";
+        let canonical = format!("did:peer:2.{KEYS}.{SERVICE}");
+        let reordered = format!("did:peer:2.{SERVICE}.{KEYS}");
+
+        let pinned = PinnedDocuments::from_json(&json!([doc(&reordered)]).to_string()).unwrap();
+        assert_eq!(pinned.get(&canonical).unwrap().id.as_str(), reordered);
+        assert!(pinned.get(&reordered).is_some());
+
+        let map = json!({ canonical.clone(): doc(&reordered) }).to_string();
+        assert!(PinnedDocuments::from_json(&map).is_ok());
+
+        let both = json!([doc(&canonical), doc(&reordered)]).to_string();
+        assert!(PinnedDocuments::from_json(&both).is_err());
+    }
+
+    #[test]
+    fn rejects_ambiguous_sets() {
+        let mismatched = json!({ "did:web:a.example": doc("did:web:b.example") }).to_string();
+        assert!(PinnedDocuments::from_json(&mismatched).is_err());
+
+        let duplicated = json!([doc("did:web:a.example"), doc("did:web:a.example")]).to_string();
+        assert!(PinnedDocuments::from_json(&duplicated).is_err());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let pinned = PinnedDocuments::from_json(
+            &json!([doc("did:web:b.example"), doc("did:web:a.example")]).to_string(),
+        )
+        .unwrap();
+        let again = PinnedDocuments::from_json(&pinned.to_json().unwrap()).unwrap();
+        assert_eq!(again.len(), 2);
+        assert_eq!(pinned.to_json().unwrap(), again.to_json().unwrap());
+    }
+}
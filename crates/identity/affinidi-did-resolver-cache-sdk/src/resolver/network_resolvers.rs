@@ -10,6 +10,8 @@ use std::pin::Pin;
 use affinidi_did_common::{DID, DIDMethod, Document};
 use affinidi_did_resolver_traits::{AsyncResolver, Resolution, ResolverError};
 use tracing::error;
+#[cfg(feature = "did-cheqd")]
+use tracing::warn;
 
 // ---------------------------------------------------------------------------
 // Helpers
@@ -254,11 +256,30 @@ impl AsyncResolver for WebvhResolver {
                             "Resolved webvh DID but couldn't convert to DID Document: {e}"
                         ))
                     });
-                    match doc_value {
+                    let doc: Result<Document, _> = match doc_value {
                         Ok(value) => serde_json::from_value(value).map_err(|e| {
                             ResolverError::InvalidDocument(format!("Invalid document: {e}"))
                         }),
                         Err(e) => Err(e),
+                    };
+                    // A log for another DID is only acceptable as the
+                    // redirect chain of a DID that has since moved.
+                    match doc {
+                        Ok(doc) if doc.id.as_str() != did_str => {
+                            crate::webvh_portability::did_log(&method)
+                                .and_then(|did_log| {
+                                    crate::webvh_portability::verify_migration_chain(
+                                        &did_str, &did_log,
+                                    )
+                                })
+                                .map(|_| doc)
+                                .map_err(|e| {
+                                    ResolverError::InvalidDocument(format!(
+                                        "did:webvh log for {did_str} is for another DID: {e}"
+                                    ))
+                                })
+                        }
+                        doc => doc,
                     }
                 }
                 Err(e) => {
@@ -275,8 +296,109 @@ impl AsyncResolver for WebvhResolver {
 // ---------------------------------------------------------------------------
 
 /// Resolver for `did:cheqd` — Cheqd network DID method.
+///
+/// Holds a single `did-resolver-cheqd` instance, which keeps one gRPC channel
+/// per network open across resolutions. Failed attempts are retried with
+/// exponential backoff, except when the DID does not exist.
+#[cfg(feature = "did-cheqd")]
+pub struct CheqdResolver {
+    inner: std::sync::Arc<did_resolver_cheqd::DIDCheqd>,
+    config: crate::config::CheqdResolverConfig,
+}
+
+#[cfg(feature = "did-cheqd")]
+impl CheqdResolver {
+    pub fn new(config: &crate::config::CheqdResolverConfig) -> Self {
+        use did_resolver_cheqd::resolution::resolver::DidCheqdResolverConfiguration;
+
+        Self {
+            inner: std::sync::Arc::new(did_resolver_cheqd::DIDCheqd::new(Some(
+                DidCheqdResolverConfiguration {
+                    networks: cheqd_networks(config),
+                },
+            ))),
+            config: config.clone(),
+        }
+    }
+
+    async fn resolve_with_retries(
+        &self,
+        did: &ssi_dids_core::DID,
+    ) -> Result<Document, ResolverError> {
+        use ssi_dids_core::DIDResolver;
+
+        let res = with_retries(&self.config, did.as_str(), || self.inner.resolve(did)).await?;
+        document_from_ssi_output(res.document.into_document())
+    }
+}
+
+/// The public mainnet and testnet endpoints, with those set in `config`
+/// replacing or adding to them.
+#[cfg(feature = "did-cheqd")]
+pub(crate) fn cheqd_networks(
+    config: &crate::config::CheqdResolverConfig,
+) -> Vec<did_resolver_cheqd::resolution::resolver::NetworkConfiguration> {
+    use did_resolver_cheqd::resolution::resolver::NetworkConfiguration;
+
+    let mut networks = vec![
+        NetworkConfiguration::mainnet(),
+        NetworkConfiguration::testnet(),
+    ];
+    for (namespace, grpc_url) in &config.networks {
+        networks.retain(|n| &n.namespace != namespace);
+        networks.push(NetworkConfiguration {
+            grpc_url: grpc_url.clone(),
+            namespace: namespace.clone(),
+        });
+    }
+    networks
+}
+
+/// Run `attempt` under the config's per-attempt [`CallLimits`], retrying
+/// failures and timeouts with exponential backoff. A DID that does not exist
+/// fails straight away.
+///
+/// [`CallLimits`]: affinidi_task_utils::CallLimits
 #[cfg(feature = "did-cheqd")]
-pub struct CheqdResolver;
+async fn with_retries<T, F, Fut>(
+    config: &crate::config::CheqdResolverConfig,
+    did: &str,
+    mut attempt: F,
+) -> Result<T, ResolverError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ssi_dids_core::resolution::Error>>,
+{
+    use ssi_dids_core::resolution::Error as SsiError;
+
+    let mut backoff = config.retry_backoff;
+    let mut retries = 0;
+    loop {
+        let error = match config.attempt_limits.run(attempt()).await {
+            Ok(Ok(output)) => return Ok(output),
+            Ok(Err(SsiError::NotFound)) => {
+                return Err(ResolverError::ResolutionFailed(format!("{did} not found")));
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(interrupted) => interrupted.to_string(),
+        };
+        if retries >= config.max_retries {
+            error!("did:cheqd resolution error: {error}");
+            return Err(ResolverError::ResolutionFailed(error));
+        }
+        retries += 1;
+        warn!("did:cheqd resolution attempt {retries} failed ({error}), retrying in {backoff:?}");
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
+#[cfg(feature = "did-cheqd")]
+impl Default for CheqdResolver {
+    fn default() -> Self {
+        Self::new(&crate::config::CheqdResolverConfig::default())
+    }
+}
 
 #[cfg(feature = "did-cheqd")]
 impl AsyncResolver for CheqdResolver {
@@ -294,7 +416,6 @@ impl AsyncResolver for CheqdResolver {
             }
 
             let did_str = did.to_string();
-            use ssi_dids_core::DIDResolver;
             let ssi_did = match ssi_dids_core::DID::new(&did_str) {
                 Ok(d) => d,
                 Err(e) => {
@@ -304,18 +425,7 @@ impl AsyncResolver for CheqdResolver {
                 }
             };
 
-            Some(
-                match did_resolver_cheqd::DIDCheqd::default()
-                    .resolve(ssi_did)
-                    .await
-                {
-                    Ok(res) => document_from_ssi_output(res.document.into_document()),
-                    Err(e) => {
-                        error!("did:cheqd resolution error: {e:?}");
-                        Err(ResolverError::ResolutionFailed(e.to_string()))
-                    }
-                },
-            )
+            Some(self.resolve_with_retries(ssi_did).await)
         })
     }
 }
@@ -390,3 +500,90 @@ impl AsyncResolver for EbsiResolver {
         })
     }
 }
+
+#[cfg(all(test, feature = "did-cheqd"))]
+mod tests {
+    use super::*;
+    use crate::config::CheqdResolverConfig;
+    use ssi_dids_core::resolution::Error as SsiError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::{Duration, Instant};
+
+    const DID: &str = "did:cheqd:testnet:zF7rhDBfUt9d1gJPjx7s1J";
+
+    #[tokio::test]
+    async fn failures_are_retried_with_backoff() {
+        let config = CheqdResolverConfig::default().with_retries(2, Duration::from_millis(20));
+        let attempts = AtomicU32::new(0);
+
+        let started = Instant::now();
+        let result: Result<(), _> = with_retries(&config, DID, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err(SsiError::Internal("unavailable".into())) }
+        })
+        .await;
+
+        assert!(
+            matches!(result, Err(ResolverError::ResolutionFailed(e)) if e.contains("unavailable"))
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        // 20ms, then 40ms
+        assert!(started.elapsed() >= Duration::from_millis(60));
+    }
+
+    #[tokio::test]
+    async fn a_later_attempt_can_succeed() {
+        let config = CheqdResolverConfig::default().with_retries(2, Duration::from_millis(1));
+        let attempts = AtomicU32::new(0);
+
+        let result = with_retries(&config, DID, || {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    Err(SsiError::Internal("unavailable".into()))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn not_found_is_not_retried() {
+        let config = CheqdResolverConfig::default().with_retries(5, Duration::from_secs(1));
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = with_retries(&config, DID, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            async { Err(SsiError::NotFound) }
+        })
+        .await;
+
+        assert!(
+            matches!(result, Err(ResolverError::ResolutionFailed(e)) if e.ends_with("not found"))
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn slow_attempts_time_out() {
+        let config = CheqdResolverConfig::default()
+            .with_retries(1, Duration::from_millis(1))
+            .with_timeout(Duration::from_millis(10));
+        let attempts = AtomicU32::new(0);
+
+        let result: Result<(), _> = with_retries(&config, DID, || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            std::future::pending()
+        })
+        .await;
+
+        assert!(
+            matches!(result, Err(ResolverError::ResolutionFailed(e)) if e.contains("timed out"))
+        );
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,398 @@
+//! DID-Linked Resources.
+//!
+//! Credential schemas, status lists and similar artefacts can be published
+//! next to a DID and addressed by a DID URL. [`DIDCacheClient::resolve_resource`]
+//! fetches them uniformly, whatever the method:
+//!
+//! - `did:cheqd` — on-ledger resources
+//!   (`did:cheqd:mainnet:<id>/resources/<resource-id>`, or a
+//!   `?resourceName=…&resourceType=…` query), read from the ledger over gRPC
+//!   through the networks configured for `did:cheqd` resolution. The content
+//!   must match the SHA-256 checksum the ledger records for it. Requires the
+//!   `did-cheqd` feature.
+//! - `did:webvh` and `did:web` — DID URL path resolution: the path is served
+//!   relative to where the DID lives, so
+//!   `did:webvh:{scid}:example.com/schemas/a.json` is
+//!   `https://example.com/schemas/a.json`.
+//!
+//! Resources are cached by DID URL in a separate, byte-bounded cache and
+//! expire after the client's TTL for mutable methods, including changes made
+//! with [`DIDCacheClient::set_cache_ttl`].
+//!
+//! [`DIDCacheClient::resolve_resource`]: crate::DIDCacheClient::resolve_resource
+//! [`DIDCacheClient::set_cache_ttl`]: crate::DIDCacheClient::set_cache_ttl
+
+use std::sync::Arc;
+
+use affinidi_did_common::{DID, DIDMethod};
+
+use crate::errors::DIDCacheError;
+
+/// A resource fetched through a DID URL.
+#[derive(Clone, Debug)]
+pub struct DidResource {
+    /// The DID URL that was resolved.
+    pub did_url: String,
+    /// `Content-Type` reported by the host, if any.
+    pub content_type: Option<String>,
+    /// Resource bytes.
+    pub content: Arc<[u8]>,
+    /// Whether the resource came from the local cache.
+    pub cache_hit: bool,
+}
+
+impl DidResource {
+    /// Parse the content as JSON.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, DIDCacheError> {
+        Ok(serde_json::from_slice(&self.content)?)
+    }
+}
+
+/// Where the resource named by a DID URL is read from.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ResourceLocation {
+    /// An HTTPS URL (`did:web`, `did:webvh`).
+    Url(String),
+    /// The cheqd ledger.
+    #[cfg(feature = "did-cheqd")]
+    Cheqd,
+}
+
+/// Where the resource named by `did_url` is read from.
+pub(crate) fn resource_location(did_url: &str) -> Result<ResourceLocation, DIDCacheError> {
+    let did: DID = did_url
+        .parse()
+        .map_err(|e| DIDCacheError::ResourceError(format!("invalid DID URL {did_url}: {e}")))?;
+    if did.fragment().is_some() {
+        return Err(DIDCacheError::ResourceError(format!(
+            "{did_url}: a fragment identifies part of a resource, not a resource"
+        )));
+    }
+
+    match did.method() {
+        DIDMethod::Cheqd { .. } => {
+            if did.path().is_none() && did.query().is_none() {
+                return Err(DIDCacheError::ResourceError(format!(
+                    "{did_url} names a DID, not a resource"
+                )));
+            }
+            #[cfg(feature = "did-cheqd")]
+            return Ok(ResourceLocation::Cheqd);
+            #[cfg(not(feature = "did-cheqd"))]
+            Err(DIDCacheError::UnsupportedMethod(
+                "did:cheqd resources need the `did-cheqd` feature".into(),
+            ))
+        }
+        DIDMethod::Web {
+            domain,
+            path_segments,
+            ..
+        }
+        | DIDMethod::Webvh {
+            domain,
+            path_segments,
+            ..
+        } => {
+            let path = did.path().unwrap_or_default();
+            let path = path.trim_start_matches('/');
+            if path.is_empty() {
+                return Err(DIDCacheError::ResourceError(format!(
+                    "{did_url} has no resource path"
+                )));
+            }
+            affinidi_did_web::build_resource_url(
+                &domain,
+                &path_segments,
+                path,
+                did.query().as_deref(),
+            )
+            .map(ResourceLocation::Url)
+            .map_err(|e| DIDCacheError::ResourceError(e.to_string()))
+        }
+        other => Err(DIDCacheError::UnsupportedMethod(format!(
+            "DID-linked resources are not supported for did:{other}"
+        ))),
+    }
+}
+
+#[cfg(feature = "did-cheqd")]
+pub(crate) use cheqd::CheqdResources;
+
+#[cfg(feature = "did-cheqd")]
+mod cheqd {
+    use std::collections::HashMap;
+
+    use affinidi_task_utils::CallLimits;
+    use chrono::{DateTime, Utc};
+    use did_resolver_cheqd::{
+        proto::cheqd::resource::v2::{
+            Metadata, QueryCollectionResourcesRequest, QueryResourceRequest,
+            query_client::QueryClient,
+        },
+        resolution::{parser::DidCheqdParser, resolver::NetworkConfiguration},
+    };
+    use sha2::{Digest, Sha256};
+    use tokio::sync::Mutex;
+    use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+
+    use crate::{config::CheqdResolverConfig, errors::DIDCacheError};
+
+    /// Reads `did:cheqd` resources from the ledger, keeping one gRPC channel
+    /// per network.
+    pub(crate) struct CheqdResources {
+        networks: Vec<NetworkConfiguration>,
+        limits: CallLimits,
+        clients: Mutex<HashMap<String, QueryClient<Channel>>>,
+    }
+
+    impl CheqdResources {
+        pub(crate) fn new(config: &CheqdResolverConfig) -> Self {
+            Self {
+                networks: crate::resolver::network_resolvers::cheqd_networks(config),
+                limits: config.attempt_limits.clone(),
+                clients: Mutex::default(),
+            }
+        }
+
+        /// The content and media type of the resource named by `did_url`,
+        /// once its checksum matches the one on the ledger.
+        pub(crate) async fn fetch(
+            &self,
+            did_url: &str,
+            max_bytes: usize,
+        ) -> Result<(Vec<u8>, Option<String>), DIDCacheError> {
+            self.limits
+                .run(self.fetch_inner(did_url, max_bytes))
+                .await?
+        }
+
+        async fn fetch_inner(
+            &self,
+            did_url: &str,
+            max_bytes: usize,
+        ) -> Result<(Vec<u8>, Option<String>), DIDCacheError> {
+            let err = |e: String| DIDCacheError::ResourceError(format!("{did_url}: {e}"));
+
+            let parsed = DidCheqdParser::parse(did_url).map_err(|e| err(e.to_string()))?;
+            let query = parsed
+                .query
+                .ok_or_else(|| err("names a DID, not a resource".into()))?;
+            let mut client = self.client(&parsed.namespace).await.map_err(err)?;
+
+            let id = match query.get("resourceId") {
+                Some(id) => id.clone(),
+                None => {
+                    let (Some(name), Some(resource_type)) =
+                        (query.get("resourceName"), query.get("resourceType"))
+                    else {
+                        return Err(err(
+                            "needs a resource ID, or both resourceName and resourceType".into(),
+                        ));
+                    };
+                    let at = match query.get("resourceVersionTime") {
+                        Some(time) => DateTime::parse_from_rfc3339(time)
+                            .map_err(|e| err(format!("invalid resourceVersionTime: {e}")))?
+                            .to_utc(),
+                        None => Utc::now(),
+                    };
+                    let resources = client
+                        .collection_resources(QueryCollectionResourcesRequest {
+                            collection_id: parsed.id.clone(),
+                            pagination: None,
+                        })
+                        .await
+                        .map_err(|e| err(e.message().to_string()))?
+                        .into_inner()
+                        .resources;
+                    version_at(&resources, name, resource_type, at)
+                        .ok_or_else(|| err("no such resource".into()))?
+                        .id
+                        .clone()
+                }
+            };
+
+            let resource = client
+                .resource(QueryResourceRequest {
+                    collection_id: parsed.id,
+                    id,
+                })
+                .await
+                .map_err(|e| err(e.message().to_string()))?
+                .into_inner()
+                .resource
+                .ok_or_else(|| err("the ledger returned no resource".into()))?;
+            let metadata = resource
+                .metadata
+                .ok_or_else(|| err("the ledger returned no resource metadata".into()))?;
+            let data = resource.resource.map(|r| r.data).unwrap_or_default();
+
+            if data.len() > max_bytes {
+                return Err(err(format!(
+                    "resource is {} bytes, over the {max_bytes} byte limit",
+                    data.len()
+                )));
+            }
+            verify_checksum(&data, &metadata.checksum).map_err(err)?;
+
+            let media_type = Some(metadata.media_type).filter(|m| !m.trim().is_empty());
+            Ok((data, media_type))
+        }
+
+        /// The resource query client for `namespace`, connecting on first use.
+        async fn client(&self, namespace: &str) -> Result<QueryClient<Channel>, String> {
+            let mut clients = self.clients.lock().await;
+            if let Some(client) = clients.get(namespace) {
+                return Ok(client.clone());
+            }
+
+            let network = self
+                .networks
+                .iter()
+                .find(|n| n.namespace == namespace)
+                .ok_or_else(|| format!("no gRPC endpoint for cheqd network {namespace}"))?;
+            let channel = Endpoint::new(network.grpc_url.clone())
+                .and_then(|endpoint| {
+                    endpoint.tls_config(ClientTlsConfig::new().with_webpki_roots())
+                })
+                .map_err(|e| format!("invalid gRPC endpoint ({}): {e}", network.grpc_url))?
+                .connect()
+                .await
+                .map_err(|e| format!("couldn't connect to {}: {e}", network.grpc_url))?;
+
+            let client = QueryClient::new(channel);
+            clients.insert(namespace.to_string(), client.clone());
+            Ok(client)
+        }
+    }
+
+    /// The latest version of the resource named `name` of `resource_type`
+    /// created no later than `at`.
+    pub(super) fn version_at<'a>(
+        resources: &'a [Metadata],
+        name: &str,
+        resource_type: &str,
+        at: DateTime<Utc>,
+    ) -> Option<&'a Metadata> {
+        let at = (at.timestamp(), at.timestamp_subsec_nanos() as i32);
+        resources
+            .iter()
+            .filter(|r| r.name == name && r.resource_type == resource_type)
+            .filter_map(|r| {
+                let created = r.created.as_ref()?;
+                Some(((created.seconds, created.nanos), r))
+            })
+            .filter(|(created, _)| *created <= at)
+            .max_by_key(|(created, _)| *created)
+            .map(|(_, r)| r)
+    }
+
+    /// Check `data` against the hex SHA-256 `checksum` the ledger records.
+    pub(super) fn verify_checksum(data: &[u8], checksum: &str) -> Result<(), String> {
+        let actual: String = Sha256::digest(data)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        if checksum.is_empty() {
+            return Err("the ledger records no checksum".into());
+        }
+        if !actual.eq_ignore_ascii_case(checksum) {
+            return Err(format!(
+                "content doesn't match its checksum (expected {checksum}, got {actual})"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHEQD_RESOURCE: &str = "did:cheqd:testnet:cad53e1d-71e0-48d2-9352-39cc3d0fac99/\
+                                  resources/0f964a80-5d18-4867-83e3-b47f5a756f02";
+
+    #[cfg(feature = "did-cheqd")]
+    #[test]
+    fn cheqd_resources_come_from_the_ledger() {
+        assert_eq!(
+            resource_location(CHEQD_RESOURCE).unwrap(),
+            ResourceLocation::Cheqd
+        );
+        assert!(
+            resource_location("did:cheqd:testnet:cad53e1d-71e0-48d2-9352-39cc3d0fac99").is_err()
+        );
+    }
+
+    #[cfg(not(feature = "did-cheqd"))]
+    #[test]
+    fn cheqd_resources_need_the_feature() {
+        let err = resource_location(CHEQD_RESOURCE).unwrap_err();
+        assert!(matches!(err, DIDCacheError::UnsupportedMethod(_)));
+    }
+
+    #[test]
+    fn web_paths_resolve_relative_to_the_did() {
+        let url = |did_url| match resource_location(did_url).unwrap() {
+            ResourceLocation::Url(url) => url,
+            #[cfg(feature = "did-cheqd")]
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(
+            url("did:webvh:QmSCID:example.com:dids:issuer/schemas/a.json"),
+            "https://example.com/dids/issuer/schemas/a.json"
+        );
+        assert_eq!(
+            url("did:web:example.com/status/1"),
+            "https://example.com/status/1"
+        );
+        assert!(resource_location("did:web:example.com").is_err());
+        assert!(resource_location("did:web:example.com/a.json#key").is_err());
+    }
+
+    #[test]
+    fn other_methods_are_unsupported() {
+        let err = resource_location("did:key:z6MkiToqovww7vYtxm1xNM15u9JzqzUFZ1k7s7MazYJUyAxv/a")
+            .unwrap_err();
+        assert!(matches!(err, DIDCacheError::UnsupportedMethod(_)));
+    }
+
+    #[cfg(feature = "did-cheqd")]
+    #[test]
+    fn cheqd_content_must_match_its_checksum() {
+        // SHA-256 of "abc"
+        let checksum = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert!(cheqd::verify_checksum(b"abc", checksum).is_ok());
+        assert!(cheqd::verify_checksum(b"abc", &checksum.to_uppercase()).is_ok());
+        assert!(cheqd::verify_checksum(b"abd", checksum).is_err());
+        assert!(cheqd::verify_checksum(b"abc", "").is_err());
+    }
+
+    #[cfg(feature = "did-cheqd")]
+    #[test]
+    fn cheqd_names_resolve_to_the_version_current_at_the_time() {
+        use chrono::{TimeZone, Utc};
+        use did_resolver_cheqd::proto::cheqd::resource::v2::Metadata;
+
+        let version = |id: &str, resource_type: &str, seconds: i64| Metadata {
+            id: id.into(),
+            name: "Schema".into(),
+            resource_type: resource_type.into(),
+            created: Some(prost_types::Timestamp { seconds, nanos: 0 }),
+            ..Default::default()
+        };
+        let resources = [
+            version("v1", "JsonSchema", 100),
+            version("v2", "JsonSchema", 200),
+            version("other", "StatusList", 150),
+        ];
+        let at = |seconds| Utc.timestamp_opt(seconds, 0).unwrap();
+
+        let id = |seconds| {
+            cheqd::version_at(&resources, "Schema", "JsonSchema", at(seconds)).map(|r| &r.id)
+        };
+        assert_eq!(id(150).unwrap(), "v1");
+        assert_eq!(id(200).unwrap(), "v2");
+        assert_eq!(id(300).unwrap(), "v2");
+        assert!(id(50).is_none());
+    }
+}
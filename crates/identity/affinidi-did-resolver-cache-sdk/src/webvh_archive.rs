@@ -0,0 +1,626 @@
+/*!
+Content-addressed export and import of `did:webvh` logs.
+
+A watcher or mirror needs more than the resolved DID document: it needs the raw
+log (`did.jsonl`) and the witness proofs (`did-witness.json`) so that whoever
+fetches from the mirror can replay and verify the history themselves. This
+module packages both into a [`WebVHArchive`]:
+
+* the log is split into JSONL **chunks** of at most `entries_per_chunk` entries;
+  the witness proofs are a chunk of their own;
+* every chunk is addressed by the SHA-256 of its bytes;
+* an [`ArchiveManifest`] lists the chunks in order, and is itself addressed by
+  the SHA-256 of its canonical JSON — the **manifest hash**. Publishing that one hash is enough to
+  pin the whole archive.
+
+[`WebVHArchive::blobs`] yields `(hash, bytes)` pairs for any key/value or
+IPFS-style store, and [`WebVHArchive::from_blobs`] reassembles an archive from
+one. Nothing fetched from a store is trusted until [`WebVHArchive::import`] has
+checked every hash *and* replayed the log (with its witness proofs) through
+`didwebvh-rs`, exactly as a resolver would. Only the [`VerifiedWebVHLog`] it
+returns should be served.
+
+```ignore
+use affinidi_did_resolver_cache_sdk::webvh_archive::WebVHArchive;
+
+let archive = WebVHArchive::export(did, &did_log, did_witness.as_deref(), 100)?;
+for (hash, bytes) in archive.blobs()? {
+    store.put(&hash, bytes);
+}
+publish(archive.manifest_hash()?);
+
+// On the mirror
+let archive = WebVHArchive::from_blobs(&manifest_hash, |hash| store.get(hash))?;
+let verified = archive.import(&manifest_hash).await?;
+serve("did.jsonl", verified.did_log());
+```
+*/
+
+use crate::errors::DIDCacheError;
+use affinidi_did_common::Document;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Archive format version written to [`ArchiveManifest::version`].
+pub const WEBVH_ARCHIVE_VERSION: u32 = 1;
+
+/// Default number of log entries per chunk.
+pub const DEFAULT_ENTRIES_PER_CHUNK: usize = 100;
+
+/// What a chunk holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChunkKind {
+    /// Consecutive `did.jsonl` log entries, one JSON object per line.
+    Log,
+    /// The `did-witness.json` witness proofs file.
+    WitnessProofs,
+}
+
+/// A manifest entry pointing at one chunk.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkRef {
+    /// `sha256:<hex>` of the chunk bytes.
+    pub hash: String,
+    pub kind: ChunkKind,
+    /// Size of the chunk in bytes.
+    pub size: usize,
+    /// `versionId` of the first log entry in the chunk (log chunks only).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub first_version_id: Option<String>,
+    /// `versionId` of the last log entry in the chunk (log chunks only).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub last_version_id: Option<String>,
+}
+
+/// Ordered index of an archive's chunks.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    /// Archive format version, see [`WEBVH_ARCHIVE_VERSION`].
+    pub version: u32,
+    /// The `did:webvh` DID the log belongs to.
+    pub did: String,
+    /// `versionId` of the newest log entry.
+    pub latest_version_id: String,
+    /// Chunks in log order; the witness proofs chunk, if any, comes last.
+    pub chunks: Vec<ChunkRef>,
+}
+
+impl ArchiveManifest {
+    /// The bytes the manifest hash is computed over: the manifest's canonical
+    /// JSON ([RFC 8785]), so any implementation rebuilding it gets the same hash.
+    ///
+    /// [RFC 8785]: https://www.rfc-editor.org/rfc/rfc8785
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DIDCacheError> {
+        serde_json_canonicalizer::to_vec(self)
+            .map_err(|e| DIDCacheError::ArchiveError(format!("couldn't serialize manifest: {e}")))
+    }
+
+    /// `sha256:<hex>` over [`to_bytes`](Self::to_bytes).
+    pub fn hash(&self) -> Result<String, DIDCacheError> {
+        Ok(content_hash(&self.to_bytes()?))
+    }
+}
+
+/// A `did:webvh` log plus witness proofs, split into content-addressed chunks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebVHArchive {
+    manifest: ArchiveManifest,
+    chunks: HashMap<String, Vec<u8>>,
+}
+
+/// A log that passed [`WebVHArchive::import`]: every hash matched and the log
+/// verified under `didwebvh-rs`. Safe to serve.
+#[derive(Clone, Debug)]
+pub struct VerifiedWebVHLog {
+    did: String,
+    version_id: String,
+    did_log: String,
+    did_witness_log: Option<String>,
+    document: Document,
+}
+
+impl VerifiedWebVHLog {
+    /// The DID this log belongs to.
+    pub fn did(&self) -> &str {
+        &self.did
+    }
+
+    /// `versionId` of the newest log entry.
+    pub fn version_id(&self) -> &str {
+        &self.version_id
+    }
+
+    /// The reassembled `did.jsonl` contents.
+    pub fn did_log(&self) -> &str {
+        &self.did_log
+    }
+
+    /// The `did-witness.json` contents, if the archive carried witness proofs.
+    pub fn did_witness_log(&self) -> Option<&str> {
+        self.did_witness_log.as_deref()
+    }
+
+    /// The DID document the verified log resolves to.
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// `sha256:<hex>` of the document's canonical JSON
+    /// ([`Document::sha256_hash`]). Changes only when the resolved state does,
+    /// so a mirror can compare it across imports.
+    pub fn document_hash(&self) -> Result<String, DIDCacheError> {
+        let digest = self.document.sha256_hash().map_err(|e| {
+            DIDCacheError::ArchiveError(format!("couldn't hash the verified document: {e}"))
+        })?;
+        Ok(prefixed_hex(&digest))
+    }
+}
+
+impl WebVHArchive {
+    /// Package a `did:webvh` log (`did.jsonl` contents) and optional witness
+    /// proofs (`did-witness.json` contents) into an archive.
+    ///
+    /// Blank lines in the log are dropped; every other line must be a JSON
+    /// object carrying a `versionId`. The log is not cryptographically verified
+    /// here — that is [`import`](Self::import)'s job on the receiving side.
+    pub fn export(
+        did: &str,
+        did_log: &str,
+        did_witness_log: Option<&str>,
+        entries_per_chunk: usize,
+    ) -> Result<Self, DIDCacheError> {
+        if !did.starts_with("did:webvh:") {
+            return Err(DIDCacheError::ArchiveError(format!(
+                "{did} is not a did:webvh DID"
+            )));
+        }
+        if entries_per_chunk == 0 {
+            return Err(DIDCacheError::ArchiveError(
+                "entries_per_chunk must be at least 1".into(),
+            ));
+        }
+
+        let entries = did_log
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| Ok((line, version_id(line)?)))
+            .collect::<Result<Vec<_>, DIDCacheError>>()?;
+        let Some((_, latest_version_id)) = entries.last() else {
+            return Err(DIDCacheError::ArchiveError("DID log is empty".into()));
+        };
+        let latest_version_id = latest_version_id.clone();
+
+        let mut manifest = ArchiveManifest {
+            version: WEBVH_ARCHIVE_VERSION,
+            did: did.to_string(),
+            latest_version_id,
+            chunks: Vec::new(),
+        };
+        let mut chunks = HashMap::new();
+
+        for group in entries.chunks(entries_per_chunk) {
+            let mut bytes = Vec::new();
+            for (line, _) in group {
+                bytes.extend_from_slice(line.as_bytes());
+                bytes.push(b'\n');
+            }
+            let hash = content_hash(&bytes);
+            manifest.chunks.push(ChunkRef {
+                hash: hash.clone(),
+                kind: ChunkKind::Log,
+                size: bytes.len(),
+                first_version_id: group.first().map(|(_, v)| v.clone()),
+                last_version_id: group.last().map(|(_, v)| v.clone()),
+            });
+            chunks.insert(hash, bytes);
+        }
+
+        if let Some(witness) = did_witness_log {
+            let bytes = witness.as_bytes().to_vec();
+            let hash = content_hash(&bytes);
+            manifest.chunks.push(ChunkRef {
+                hash: hash.clone(),
+                kind: ChunkKind::WitnessProofs,
+                size: bytes.len(),
+                first_version_id: None,
+                last_version_id: None,
+            });
+            chunks.insert(hash, bytes);
+        }
+
+        Ok(WebVHArchive { manifest, chunks })
+    }
+
+    /// Reassemble an archive from a content-addressed store.
+    ///
+    /// `fetch` is called with the manifest hash and then with every chunk hash
+    /// the manifest lists. Each blob's hash is checked as it arrives, so a store
+    /// serving the wrong bytes is caught here; the log itself is only verified
+    /// by [`import`](Self::import).
+    pub fn from_blobs<F>(manifest_hash: &str, mut fetch: F) -> Result<Self, DIDCacheError>
+    where
+        F: FnMut(&str) -> Option<Vec<u8>>,
+    {
+        let mut fetch_checked = |hash: &str| -> Result<Vec<u8>, DIDCacheError> {
+            let bytes = fetch(hash).ok_or_else(|| {
+                DIDCacheError::ArchiveError(format!("blob {hash} not found in store"))
+            })?;
+            check_hash(hash, &bytes)?;
+            Ok(bytes)
+        };
+
+        let manifest: ArchiveManifest = serde_json::from_slice(&fetch_checked(manifest_hash)?)
+            .map_err(|e| DIDCacheError::ArchiveError(format!("invalid manifest: {e}")))?;
+
+        let mut chunks = HashMap::with_capacity(manifest.chunks.len());
+        for chunk in &manifest.chunks {
+            let bytes = fetch_checked(&chunk.hash)?;
+            chunks.insert(chunk.hash.clone(), bytes);
+        }
+
+        Ok(WebVHArchive { manifest, chunks })
+    }
+
+    /// The archive's manifest.
+    pub fn manifest(&self) -> &ArchiveManifest {
+        &self.manifest
+    }
+
+    /// The manifest hash that pins this archive.
+    pub fn manifest_hash(&self) -> Result<String, DIDCacheError> {
+        self.manifest.hash()
+    }
+
+    /// Bytes of the chunk addressed by `hash`, if the archive holds it.
+    pub fn chunk(&self, hash: &str) -> Option<&[u8]> {
+        self.chunks.get(hash).map(Vec::as_slice)
+    }
+
+    /// Every blob to write to a content-addressed store, keyed by its hash:
+    /// the manifest first, then the chunks in manifest order.
+    ///
+    /// Fails if a chunk the manifest lists isn't in the archive: publishing
+    /// that manifest would pin an archive nobody can restore.
+    pub fn blobs(&self) -> Result<Vec<(String, Vec<u8>)>, DIDCacheError> {
+        let manifest = self.manifest.to_bytes()?;
+        let mut blobs = Vec::with_capacity(self.manifest.chunks.len() + 1);
+        blobs.push((content_hash(&manifest), manifest));
+        for (index, chunk) in self.manifest.chunks.iter().enumerate() {
+            let bytes = self.chunks.get(&chunk.hash).ok_or_else(|| {
+                DIDCacheError::ArchiveError(format!("chunk {index} ({}) is missing", chunk.hash))
+            })?;
+            blobs.push((chunk.hash.clone(), bytes.clone()));
+        }
+        Ok(blobs)
+    }
+
+    /// Validate the archive end-to-end and return the log ready to serve.
+    ///
+    /// Checks, in order: the manifest hashes to `expected_manifest_hash`; the
+    /// format version is supported; every chunk is present, has the listed size
+    /// and hash, and its log entries carry the `versionId`s the manifest claims;
+    /// finally the reassembled log and witness proofs are replayed through
+    /// `didwebvh-rs`, which checks the SCID, the hash chain, every entry's proof
+    /// and the witness threshold.
+    pub async fn import(
+        &self,
+        expected_manifest_hash: &str,
+    ) -> Result<VerifiedWebVHLog, DIDCacheError> {
+        use didwebvh_rs::log_entry::LogEntryMethods;
+
+        let manifest_hash = self.manifest.hash()?;
+        if manifest_hash != expected_manifest_hash {
+            return Err(DIDCacheError::ArchiveError(format!(
+                "manifest hash mismatch: expected {expected_manifest_hash}, got {manifest_hash}"
+            )));
+        }
+        if self.manifest.version != WEBVH_ARCHIVE_VERSION {
+            return Err(DIDCacheError::ArchiveError(format!(
+                "unsupported archive version {}",
+                self.manifest.version
+            )));
+        }
+
+        let did = &self.manifest.did;
+        let (did_log, did_witness_log) = self.reassemble()?;
+
+        let mut state = didwebvh_rs::DIDWebVHState::default();
+        let (log_entry, _) = state
+            .resolve_log(did, &did_log, did_witness_log.as_deref())
+            .await
+            .map_err(|e| {
+                DIDCacheError::ArchiveError(format!("WebVH log verification failed for {did}: {e}"))
+            })?;
+        let document: Document = log_entry
+            .get_did_document()
+            .map_err(|e| {
+                DIDCacheError::ArchiveError(format!(
+                    "couldn't extract document from verified WebVH log: {e}"
+                ))
+            })
+            .and_then(|value| {
+                serde_json::from_value(value).map_err(|e| {
+                    DIDCacheError::ArchiveError(format!("invalid verified WebVH document: {e}"))
+                })
+            })?;
+
+        Ok(VerifiedWebVHLog {
+            did: did.clone(),
+            version_id: self.manifest.latest_version_id.clone(),
+            did_log,
+            did_witness_log,
+            document,
+        })
+    }
+
+    /// Check every chunk against the manifest and stitch the log and witness
+    /// proofs back together.
+    fn reassemble(&self) -> Result<(String, Option<String>), DIDCacheError> {
+        let mut did_log = String::new();
+        let mut did_witness_log = None;
+        let mut last_version_id = None;
+
+        for (index, chunk) in self.manifest.chunks.iter().enumerate() {
+            let bytes = self.chunks.get(&chunk.hash).ok_or_else(|| {
+                DIDCacheError::ArchiveError(format!("chunk {index} ({}) is missing", chunk.hash))
+            })?;
+            if bytes.len() != chunk.size {
+                return Err(DIDCacheError::ArchiveError(format!(
+                    "chunk {index} is {} bytes, manifest says {}",
+                    bytes.len(),
+                    chunk.size
+                )));
+            }
+            check_hash(&chunk.hash, bytes)?;
+            let text = std::str::from_utf8(bytes).map_err(|e| {
+                DIDCacheError::ArchiveError(format!("chunk {index} is not UTF-8: {e}"))
+            })?;
+
+            match chunk.kind {
+                ChunkKind::Log => {
+                    if did_witness_log.is_some() {
+                        return Err(DIDCacheError::ArchiveError(
+                            "log chunk follows the witness proofs chunk".into(),
+                        ));
+                    }
+                    let version_ids = text
+                        .lines()
+                        .map(version_id)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    if version_ids.first() != chunk.first_version_id.as_ref()
+                        || version_ids.last() != chunk.last_version_id.as_ref()
+                    {
+                        return Err(DIDCacheError::ArchiveError(format!(
+                            "chunk {index} versionIds don't match the manifest"
+                        )));
+                    }
+                    last_version_id = version_ids.last().cloned();
+                    did_log.push_str(text);
+                }
+                ChunkKind::WitnessProofs => {
+                    if did_witness_log.is_some() {
+                        return Err(DIDCacheError::ArchiveError(
+                            "archive has more than one witness proofs chunk".into(),
+                        ));
+                    }
+                    did_witness_log = Some(text.to_string());
+                }
+            }
+        }
+
+        if last_version_id.as_ref() != Some(&self.manifest.latest_version_id) {
+            return Err(DIDCacheError::ArchiveError(format!(
+                "log ends at {}, manifest says {}",
+                last_version_id.as_deref().unwrap_or("<no entries>"),
+                self.manifest.latest_version_id
+            )));
+        }
+
+        Ok((did_log, did_witness_log))
+    }
+}
+
+/// `sha256:<hex>` of `bytes`.
+fn content_hash(bytes: &[u8]) -> String {
+    prefixed_hex(&Sha256::digest(bytes))
+}
+
+/// `sha256:<hex>` of an already computed digest.
+fn prefixed_hex(digest: &[u8]) -> String {
+    let mut out = String::with_capacity(7 + digest.len() * 2);
+    out.push_str("sha256:");
+    for byte in digest {
+        let _ = write!(out, "{byte:02x}");
+    }
+    out
+}
+
+fn check_hash(expected: &str, bytes: &[u8]) -> Result<(), DIDCacheError> {
+    let actual = content_hash(bytes);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(DIDCacheError::ArchiveError(format!(
+            "content hash mismatch: expected {expected}, got {actual}"
+        )))
+    }
+}
+
+/// The `versionId` of a single JSONL log entry.
+fn version_id(line: &str) -> Result<String, DIDCacheError> {
+    let entry: serde_json::Value = serde_json::from_str(line)
+        .map_err(|e| DIDCacheError::ArchiveError(format!("invalid log entry: {e}")))?;
+    entry
+        .get("versionId")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| DIDCacheError::ArchiveError("log entry has no versionId".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DID: &str = "did:webvh:QmScid:example.com";
+
+    fn log(entries: usize) -> String {
+        (1..=entries)
+            .map(|n| format!("{{\"versionId\":\"{n}-Qm{n}\",\"state\":{{}}}}\n"))
+            .collect()
+    }
+
+    #[test]
+    fn export_chunks_log_and_witness_proofs() {
+        let archive = WebVHArchive::export(DID, &log(5), Some("[]"), 2).unwrap();
+        let manifest = archive.manifest();
+
+        assert_eq!(manifest.latest_version_id, "5-Qm5");
+        let kinds: Vec<_> = manifest.chunks.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                ChunkKind::Log,
+                ChunkKind::Log,
+                ChunkKind::Log,
+                ChunkKind::WitnessProofs
+            ]
+        );
+        assert_eq!(
+            manifest.chunks[1].first_version_id.as_deref(),
+            Some("3-Qm3")
+        );
+        assert_eq!(manifest.chunks[1].last_version_id.as_deref(), Some("4-Qm4"));
+
+        let (did_log, witness) = archive.reassemble().unwrap();
+        assert_eq!(did_log, log(5));
+        assert_eq!(witness.as_deref(), Some("[]"));
+    }
+
+    #[test]
+    fn export_rejects_bad_input() {
+        assert!(WebVHArchive::export("did:web:example.com", &log(1), None, 1).is_err());
+        assert!(WebVHArchive::export(DID, "\n\n", None, 1).is_err());
+        assert!(WebVHArchive::export(DID, "{\"state\":{}}", None, 1).is_err());
+        assert!(WebVHArchive::export(DID, &log(1), None, 0).is_err());
+    }
+
+    #[test]
+    fn blobs_round_trip_through_a_store() {
+        let archive = WebVHArchive::export(DID, &log(3), Some("[]"), 1).unwrap();
+        let manifest_hash = archive.manifest_hash().unwrap();
+        let store: HashMap<String, Vec<u8>> = archive.blobs().unwrap().into_iter().collect();
+
+        let restored =
+            WebVHArchive::from_blobs(&manifest_hash, |hash| store.get(hash).cloned()).unwrap();
+        assert_eq!(restored, archive);
+    }
+
+    #[test]
+    fn blobs_rejects_missing_chunk() {
+        let mut archive = WebVHArchive::export(DID, &log(3), None, 1).unwrap();
+        let missing = archive.manifest().chunks[1].hash.clone();
+        archive.chunks.remove(&missing);
+
+        let err = archive.blobs().unwrap_err();
+        assert!(err.to_string().contains(&missing), "{err}");
+    }
+
+    #[test]
+    fn from_blobs_rejects_substituted_chunk() {
+        let archive = WebVHArchive::export(DID, &log(3), None, 1).unwrap();
+        let manifest_hash = archive.manifest_hash().unwrap();
+        let mut store: HashMap<String, Vec<u8>> = archive.blobs().unwrap().into_iter().collect();
+        let victim = archive.manifest().chunks[1].hash.clone();
+        store.insert(victim, b"{\"versionId\":\"2-evil\"}\n".to_vec());
+
+        let err =
+            WebVHArchive::from_blobs(&manifest_hash, |hash| store.get(hash).cloned()).unwrap_err();
+        assert!(err.to_string().contains("content hash mismatch"));
+    }
+
+    #[tokio::test]
+    async fn import_verifies_a_created_log() {
+        use affinidi_secrets_resolver::secrets::Secret;
+        use didwebvh_rs::{
+            create::{CreateDIDConfig, create_did},
+            parameters::Parameters,
+        };
+        use serde_json::json;
+        use std::sync::Arc;
+
+        let mut update_key = Secret::generate_ed25519(None, None);
+        let public_key = update_key.get_public_keymultibase().unwrap();
+        update_key.id = format!("did:key:{public_key}#{public_key}");
+        let config = CreateDIDConfig::builder()
+            .address("https://example.com")
+            .authorization_key(update_key)
+            .did_document(json!({
+                "@context": ["https://www.w3.org/ns/did/v1"],
+                "id": "{DID}",
+            }))
+            .parameters(Parameters {
+                update_keys: Some(Arc::new(vec![public_key.into()])),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let created = create_did(config).await.unwrap();
+        let did_log = format!("{}\n", serde_json::to_string(created.log_entry()).unwrap());
+
+        let archive = WebVHArchive::export(created.did(), &did_log, None, 1).unwrap();
+        let manifest_hash = archive.manifest_hash().unwrap();
+        let store: HashMap<String, Vec<u8>> = archive.blobs().unwrap().into_iter().collect();
+        let verified = WebVHArchive::from_blobs(&manifest_hash, |hash| store.get(hash).cloned())
+            .unwrap()
+            .import(&manifest_hash)
+            .await
+            .unwrap();
+
+        assert_eq!(verified.did(), created.did());
+        assert_eq!(verified.version_id(), archive.manifest().latest_version_id);
+        assert_eq!(verified.did_log(), did_log);
+        assert_eq!(verified.document().id.as_str(), created.did());
+        assert_eq!(
+            verified.document_hash().unwrap(),
+            prefixed_hex(&verified.document().sha256_hash().unwrap())
+        );
+    }
+
+    #[test]
+    fn manifest_bytes_are_canonical_json() {
+        let archive = WebVHArchive::export(DID, &log(1), None, 1).unwrap();
+        let bytes = archive.manifest().to_bytes().unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert!(bytes.starts_with(b"{\"chunks\":[{"));
+        assert_eq!(bytes, serde_json_canonicalizer::to_vec(&value).unwrap());
+    }
+
+    #[tokio::test]
+    async fn import_rejects_wrong_manifest_hash() {
+        let archive = WebVHArchive::export(DID, &log(2), None, 1).unwrap();
+        let err = archive.import("sha256:00").await.unwrap_err();
+        assert!(err.to_string().contains("manifest hash mismatch"));
+    }
+
+    #[test]
+    fn reassemble_rejects_reordered_manifest() {
+        let mut archive = WebVHArchive::export(DID, &log(3), None, 1).unwrap();
+        archive.manifest.chunks.swap(0, 2);
+        assert!(archive.reassemble().is_err());
+    }
+
+    #[test]
+    fn reassemble_rejects_dropped_tail() {
+        let mut archive = WebVHArchive::export(DID, &log(3), None, 1).unwrap();
+        archive.manifest.chunks.pop();
+        let err = archive.reassemble().unwrap_err();
+        assert!(err.to_string().contains("log ends at 2-Qm2"));
+    }
+}
@@ -0,0 +1,632 @@
+/*!
+Moving a portable `did:webvh` to a new domain.
+
+A `did:webvh` DID is `did:webvh:<scid>:<location>`. The SCID is bound to the
+log; the location is only where the log is hosted. A DID created with the
+`portable` parameter set can change its location: the controller appends a log
+entry whose document carries the new DID, and lists the old DID in
+`alsoKnownAs`. Everything else about the DID — its SCID, its history, its keys —
+carries over.
+
+The controller side is [`WebVHMigration::migrate_domain`], on the
+`didwebvh-rs` [`DIDWebVHState`] holding the DID's log:
+
+```ignore
+use affinidi_did_resolver_cache_sdk::webvh_portability::WebVHMigration;
+
+let moved = state.migrate_domain("https://new.example.com", &update_key).await?;
+// Publish at the new location, then point the old one at it.
+host(&moved.log_url, moved.did_log);
+redirect(&moved.previous_log_url, &moved.log_url); // HTTP 301
+```
+
+The old location keeps answering with a permanent redirect to the new
+`did.jsonl`, so those still holding the old DID reach the moved log. The log
+read there starts under the old DID and ends under the new one: the redirect
+chain. [`verify_migration_chain`] is the resolver side. It accepts a log for a
+DID that has since moved only when every move in it kept the SCID, happened
+while the DID was portable (which only the first entry can make it), and named
+the DID it moved from in `alsoKnownAs`. Both the local `did:webvh` resolver and
+the cache server client run it on every `did:webvh` log whose document is for
+a different DID than the one asked for.
+
+If the DID uses witnesses, the new entry needs their proofs before it is
+published, as any other entry does.
+*/
+
+use crate::errors::DIDCacheError;
+use affinidi_secrets_resolver::secrets::Secret;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use didwebvh_rs::{DIDWebVHState, parameters::Parameters, url::WebVHURL};
+use serde_json::Value;
+
+/// What a [`WebVHMigration::migrate_domain`] produces for hosting the moved
+/// DID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationArtifacts {
+    /// The DID at its new location.
+    pub did: String,
+    /// The DID it moved from, now in the document's `alsoKnownAs`.
+    pub previous_did: String,
+    /// The whole log (`did.jsonl` contents), ending with the move.
+    pub did_log: String,
+    /// Where to publish [`did_log`](Self::did_log).
+    pub log_url: String,
+    /// The old log location, to redirect (HTTP 301) to
+    /// [`log_url`](Self::log_url).
+    pub previous_log_url: String,
+}
+
+/// Moving a `did:webvh` to a new location.
+#[allow(async_fn_in_trait)]
+pub trait WebVHMigration {
+    /// Move the DID to be hosted under `new_url` (e.g.
+    /// `https://new.example.com` or `https://example.org/dids/alice`),
+    /// appending the log entry that changes the DID, signed with
+    /// `signing_key` (one of the DID's current update keys).
+    ///
+    /// Every occurrence of the old DID in the document (its `id`, controller,
+    /// verification method ids, ...) becomes the new DID, and the old DID is
+    /// added to `alsoKnownAs`. Fails if the DID isn't portable or `new_url`
+    /// is where it already lives.
+    async fn migrate_domain(
+        &mut self,
+        new_url: &str,
+        signing_key: &Secret,
+    ) -> Result<MigrationArtifacts, DIDCacheError>;
+}
+
+impl WebVHMigration for DIDWebVHState {
+    async fn migrate_domain(
+        &mut self,
+        new_url: &str,
+        signing_key: &Secret,
+    ) -> Result<MigrationArtifacts, DIDCacheError> {
+        let entries = log_entries(self)?;
+        let Some(latest) = self.log_entries().last() else {
+            return Err(DIDCacheError::MigrationError(
+                "the state holds no log; load or create the DID first".into(),
+            ));
+        };
+        if !portable(&entries) {
+            return Err(DIDCacheError::MigrationError(
+                "the DID was not created portable, so it can't move".into(),
+            ));
+        }
+
+        let mut document = latest.get_did_document().map_err(|e| {
+            DIDCacheError::MigrationError(format!("couldn't read the current document: {e}"))
+        })?;
+        let version_time = next_version_time(&entries, Utc::now().fixed_offset())?;
+        let previous_did = document_id(&document)?.to_string();
+        let did = did_for_url(scid(&previous_did)?, new_url)?;
+        if did == previous_did {
+            return Err(DIDCacheError::MigrationError(format!(
+                "{previous_did} is already hosted at {new_url}"
+            )));
+        }
+
+        rebind(&mut document, &previous_did, &did);
+        let mut also_known_as: Vec<Value> = document
+            .get("alsoKnownAs")
+            .and_then(Value::as_array)
+            .map(|aka| {
+                aka.iter()
+                    .filter(|name| name.as_str() != Some(&did))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !also_known_as.iter().any(|name| name == &previous_did) {
+            also_known_as.push(previous_did.clone().into());
+        }
+        document["alsoKnownAs"] = also_known_as.into();
+
+        // No parameter changes: portability and keys carry over.
+        self.create_log_entry(
+            Some(version_time),
+            &document,
+            &Parameters::default(),
+            signing_key,
+        )
+        .await
+        .map_err(|e| {
+            DIDCacheError::MigrationError(format!("couldn't create the move entry: {e}"))
+        })?;
+
+        let did_log = did_log(self)?;
+        verify_migration_chain(&previous_did, &did_log)?;
+
+        Ok(MigrationArtifacts {
+            log_url: log_url(&did)?,
+            previous_log_url: log_url(&previous_did)?,
+            did,
+            previous_did,
+            did_log,
+        })
+    }
+}
+
+/// The DIDs a `did:webvh` log has been hosted as, oldest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationChain {
+    dids: Vec<String>,
+}
+
+impl MigrationChain {
+    /// Every DID the log was hosted as, oldest first.
+    pub fn dids(&self) -> &[String] {
+        &self.dids
+    }
+
+    /// The DID the log is hosted as now.
+    pub fn current(&self) -> &str {
+        self.dids.last().map(String::as_str).unwrap_or_default()
+    }
+
+    /// Whether the DID ever moved.
+    pub fn moved(&self) -> bool {
+        self.dids.len() > 1
+    }
+}
+
+/// Check the moves recorded in `did_log` (`did.jsonl` contents) and that
+/// `requested_did` is one of the DIDs it was hosted as.
+///
+/// Each move must keep the SCID, happen while the DID is portable, and list
+/// the DID it moved from in the new document's `alsoKnownAs`. Only the first
+/// entry may make the DID portable; later entries may only turn it off. This
+/// checks the chain of DIDs only: the entries' hashes and proofs are checked
+/// by replaying the log through `didwebvh-rs`.
+pub fn verify_migration_chain(
+    requested_did: &str,
+    did_log: &str,
+) -> Result<MigrationChain, DIDCacheError> {
+    let mut dids: Vec<String> = Vec::new();
+    let mut portable = false;
+
+    for (index, line) in did_log
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+    {
+        let entry: Value = serde_json::from_str(line).map_err(|e| {
+            DIDCacheError::MigrationError(format!("log entry {index} is invalid: {e}"))
+        })?;
+        let state = entry.get("state").ok_or_else(|| {
+            DIDCacheError::MigrationError(format!("log entry {index} has no state"))
+        })?;
+        let did = document_id(state)?;
+
+        if let Some(previous) = dids.last()
+            && previous != did
+        {
+            if scid(previous)? != scid(did)? {
+                return Err(DIDCacheError::MigrationError(format!(
+                    "log entry {index} changes the SCID ({previous} to {did})"
+                )));
+            }
+            if !portable {
+                return Err(DIDCacheError::MigrationError(format!(
+                    "log entry {index} moves {previous} to {did}, but the DID is not portable"
+                )));
+            }
+            if !also_known_as(state).any(|name| name == previous) {
+                return Err(DIDCacheError::MigrationError(format!(
+                    "log entry {index} moves {previous} to {did} without listing it in alsoKnownAs"
+                )));
+            }
+        }
+        if dids.last().map(String::as_str) != Some(did) {
+            dids.push(did.to_string());
+        }
+
+        // Applies from the next entry on.
+        if let Some(value) = entry
+            .pointer("/parameters/portable")
+            .and_then(Value::as_bool)
+        {
+            if value && !portable && index > 0 {
+                return Err(DIDCacheError::MigrationError(format!(
+                    "log entry {index} makes {did} portable, but only the first entry may"
+                )));
+            }
+            portable = value;
+        }
+    }
+
+    if dids.is_empty() {
+        return Err(DIDCacheError::MigrationError("DID log is empty".into()));
+    }
+    if !dids.iter().any(|did| did == requested_did) {
+        return Err(DIDCacheError::MigrationError(format!(
+            "the log is not for {requested_did} (hosted as {})",
+            dids.join(", ")
+        )));
+    }
+    Ok(MigrationChain { dids })
+}
+
+/// The state's log entries as JSON, oldest first.
+fn log_entries(state: &DIDWebVHState) -> Result<Vec<Value>, DIDCacheError> {
+    state
+        .log_entries()
+        .iter()
+        .map(|entry| serde_json::to_value(&entry.log_entry))
+        .collect::<Result<_, _>>()
+        .map_err(|e| DIDCacheError::MigrationError(format!("couldn't serialize the log: {e}")))
+}
+
+/// The state's log as `did.jsonl` contents: one entry per line.
+pub(crate) fn did_log(state: &DIDWebVHState) -> Result<String, DIDCacheError> {
+    let mut did_log = String::new();
+    for entry in log_entries(state)? {
+        did_log.push_str(&entry.to_string());
+        did_log.push('\n');
+    }
+    Ok(did_log)
+}
+
+/// The `versionTime` for an entry appended to `entries` at `now`: `now`, or a
+/// second after the latest entry if that isn't later. `versionTime` has
+/// second precision and must increase, so a move in the same second as the
+/// previous entry would otherwise produce an invalid log. Resolvers reject a
+/// `versionTime` in the future, so such a move only replays once that second
+/// has passed.
+fn next_version_time(
+    entries: &[Value],
+    now: DateTime<FixedOffset>,
+) -> Result<DateTime<FixedOffset>, DIDCacheError> {
+    let Some(previous) = entries
+        .last()
+        .and_then(|entry| entry.get("versionTime"))
+        .and_then(Value::as_str)
+    else {
+        return Ok(now);
+    };
+    let previous = DateTime::parse_from_rfc3339(previous).map_err(|e| {
+        DIDCacheError::MigrationError(format!("invalid versionTime ({previous}): {e}"))
+    })?;
+    Ok(now.max(previous + Duration::seconds(1)))
+}
+
+/// Whether the latest of `entries` may move the DID.
+fn portable(entries: &[Value]) -> bool {
+    entries
+        .iter()
+        .rev()
+        .find_map(|entry| entry.pointer("/parameters/portable")?.as_bool())
+        .unwrap_or(false)
+}
+
+fn document_id(document: &Value) -> Result<&str, DIDCacheError> {
+    document
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| DIDCacheError::MigrationError("DID document has no id".into()))
+}
+
+fn also_known_as(document: &Value) -> impl Iterator<Item = &str> {
+    match document.get("alsoKnownAs") {
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        Some(Value::String(name)) => vec![name.as_str()],
+        _ => Vec::new(),
+    }
+    .into_iter()
+}
+
+/// The SCID of a `did:webvh` DID.
+fn scid(did: &str) -> Result<&str, DIDCacheError> {
+    did.strip_prefix("did:webvh:")
+        .and_then(|rest| rest.split_once(':'))
+        .map(|(scid, _)| scid)
+        .filter(|scid| !scid.is_empty())
+        .ok_or_else(|| DIDCacheError::MigrationError(format!("{did} is not a did:webvh DID")))
+}
+
+/// The `did:webvh` DID with `scid` whose log is hosted under `url`: the host
+/// (with any port as `%3A<port>`) and the path segments, `:`-separated. A
+/// trailing `did.jsonl` or a bare `/.well-known` is dropped, as both are
+/// implied.
+fn did_for_url(scid: &str, url: &str) -> Result<String, DIDCacheError> {
+    let with_scheme = if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("https://{url}")
+    };
+    let parsed = url::Url::parse(&with_scheme)
+        .map_err(|e| DIDCacheError::MigrationError(format!("invalid URL ({url}): {e}")))?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| DIDCacheError::MigrationError(format!("URL has no host ({url})")))?;
+
+    let mut did = format!("did:webvh:{scid}:{host}");
+    if let Some(port) = parsed.port() {
+        did.push_str(&format!("%3A{port}"));
+    }
+    let mut segments: Vec<&str> = parsed
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    if segments.last() == Some(&"did.jsonl") {
+        segments.pop();
+    }
+    if segments == [".well-known"] {
+        segments.clear();
+    }
+    for segment in segments {
+        did.push(':');
+        did.push_str(segment);
+    }
+    Ok(did)
+}
+
+fn log_url(did: &str) -> Result<String, DIDCacheError> {
+    WebVHURL::parse_did_url(did)
+        .and_then(|url| url.get_http_url(Some("did.jsonl")))
+        .map(|url| url.to_string())
+        .map_err(|e| DIDCacheError::MigrationError(format!("no log URL for {did}: {e}")))
+}
+
+/// Replace `from` with `to` in every string that is `from` or a DID URL
+/// under it.
+fn rebind(value: &mut Value, from: &str, to: &str) {
+    match value {
+        Value::String(s) => {
+            if let Some(rest) = s.strip_prefix(from)
+                && (rest.is_empty() || rest.starts_with(['#', '?', '/']))
+            {
+                *s = format!("{to}{rest}");
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| rebind(item, from, to)),
+        Value::Object(map) => map.values_mut().for_each(|item| rebind(item, from, to)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use didwebvh_rs::log_entry::LogEntryMethods;
+    use serde_json::json;
+
+    const OLD: &str = "did:webvh:QmScid:old.example.com";
+    const NEW: &str = "did:webvh:QmScid:new.example.com";
+
+    fn entry(n: u32, portable: Option<bool>, state: Value) -> String {
+        let mut parameters = json!({});
+        if let Some(portable) = portable {
+            parameters["portable"] = portable.into();
+        }
+        format!(
+            "{}\n",
+            json!({ "versionId": format!("{n}-Qm{n}"), "parameters": parameters, "state": state })
+        )
+    }
+
+    fn moved_log(portable: bool, also_known_as: &[&str]) -> String {
+        entry(1, Some(portable), json!({ "id": OLD }))
+            + &entry(2, None, json!({ "id": OLD }))
+            + &entry(3, None, json!({ "id": NEW, "alsoKnownAs": also_known_as }))
+    }
+
+    #[test]
+    fn accepts_a_documented_move_from_either_did() {
+        let log = moved_log(true, &[OLD]);
+
+        let chain = verify_migration_chain(OLD, &log).unwrap();
+        assert_eq!(chain.dids(), [OLD, NEW]);
+        assert_eq!(chain.current(), NEW);
+        assert!(chain.moved());
+        assert_eq!(verify_migration_chain(NEW, &log).unwrap(), chain);
+    }
+
+    #[test]
+    fn rejects_moves_of_a_non_portable_did() {
+        let err = verify_migration_chain(OLD, &moved_log(false, &[OLD])).unwrap_err();
+        assert!(err.to_string().contains("not portable"), "got {err}");
+    }
+
+    #[test]
+    fn rejects_portability_set_after_creation() {
+        let log = entry(1, Some(false), json!({ "id": OLD }))
+            + &entry(2, Some(true), json!({ "id": OLD }))
+            + &entry(3, None, json!({ "id": NEW, "alsoKnownAs": [OLD] }));
+        let err = verify_migration_chain(OLD, &log).unwrap_err();
+        assert!(
+            err.to_string().contains("only the first entry"),
+            "got {err}"
+        );
+
+        // Restating it, or turning it off, is fine
+        let log = entry(1, Some(true), json!({ "id": OLD }))
+            + &entry(2, Some(true), json!({ "id": OLD }))
+            + &entry(3, Some(false), json!({ "id": OLD }));
+        assert!(!verify_migration_chain(OLD, &log).unwrap().moved());
+    }
+
+    #[test]
+    fn rejects_moves_without_also_known_as() {
+        let err = verify_migration_chain(OLD, &moved_log(true, &[])).unwrap_err();
+        assert!(err.to_string().contains("alsoKnownAs"), "got {err}");
+    }
+
+    #[test]
+    fn rejects_scid_changes_and_unrelated_dids() {
+        let log = entry(1, Some(true), json!({ "id": OLD }))
+            + &entry(
+                2,
+                None,
+                json!({ "id": "did:webvh:QmOther:new.example.com", "alsoKnownAs": [OLD] }),
+            );
+        let err = verify_migration_chain(OLD, &log).unwrap_err();
+        assert!(err.to_string().contains("SCID"), "got {err}");
+
+        let err = verify_migration_chain(
+            "did:webvh:QmScid:elsewhere.example",
+            &moved_log(true, &[OLD]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not for"), "got {err}");
+    }
+
+    /// A portable DID at `old.example.com`, created with `didwebvh-rs` a
+    /// minute ago, and its update key. Creating it in the past keeps a move
+    /// stamped now replayable: a resolver rejects a `versionTime` in the
+    /// future, which the move's would be if it had to follow an entry made in
+    /// the same second.
+    async fn portable_did() -> (DIDWebVHState, Secret) {
+        use didwebvh_rs::create::{CreateDIDConfig, create_did};
+        use std::sync::Arc;
+
+        let mut update_key = Secret::generate_ed25519(None, None);
+        let public_key = update_key.get_public_keymultibase().unwrap();
+        update_key.id = format!("did:key:{public_key}#{public_key}");
+
+        let config = CreateDIDConfig::builder()
+            .address("https://old.example.com")
+            .version_time((Utc::now() - Duration::minutes(1)).fixed_offset())
+            .authorization_key(update_key.clone())
+            .did_document(json!({
+                "@context": ["https://www.w3.org/ns/did/v1"],
+                "id": "{DID}",
+            }))
+            .parameters(Parameters {
+                update_keys: Some(Arc::new(vec![public_key.into()])),
+                portable: Some(true),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        let created = create_did(config).await.unwrap();
+        let did_log = format!("{}\n", serde_json::to_string(created.log_entry()).unwrap());
+
+        let mut state = DIDWebVHState::default();
+        state
+            .resolve_log(created.did(), &did_log, None)
+            .await
+            .unwrap();
+        (state, update_key)
+    }
+
+    #[tokio::test]
+    async fn migrated_log_replays_for_the_old_and_new_did() {
+        let (mut state, update_key) = portable_did().await;
+        let moved = state
+            .migrate_domain("https://new.example.com/dids/alice", &update_key)
+            .await
+            .unwrap();
+
+        assert!(moved.previous_did.ends_with(":old.example.com"));
+        assert!(moved.did.ends_with(":new.example.com:dids:alice"));
+        assert_eq!(
+            scid(&moved.did).unwrap(),
+            scid(&moved.previous_did).unwrap()
+        );
+        assert_eq!(
+            moved.log_url,
+            "https://new.example.com/dids/alice/did.jsonl"
+        );
+        assert_eq!(
+            moved.previous_log_url,
+            "https://old.example.com/.well-known/did.jsonl"
+        );
+
+        // The move is stamped after the entry it follows
+        let times: Vec<_> = state
+            .log_entries()
+            .iter()
+            .map(|entry| entry.log_entry.get_version_time())
+            .collect();
+        assert!(times[1] > times[0], "{times:?}");
+
+        // Both DIDs reach the same verified log, which ends under the new one
+        for did in [&moved.previous_did, &moved.did] {
+            let mut replayed = DIDWebVHState::default();
+            let (latest, _) = replayed
+                .resolve_log(did, &moved.did_log, None)
+                .await
+                .unwrap();
+            let document = latest.get_did_document().unwrap();
+            assert_eq!(document["id"], moved.did.as_str(), "{did}");
+            assert_eq!(document["alsoKnownAs"], json!([moved.previous_did]));
+
+            let chain = verify_migration_chain(did, &moved.did_log).unwrap();
+            assert_eq!(
+                chain.dids(),
+                [moved.previous_did.clone(), moved.did.clone()]
+            );
+        }
+
+        // Moving again to where it now lives is refused
+        let err = state
+            .migrate_domain("https://new.example.com/dids/alice", &update_key)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already hosted"), "got {err}");
+    }
+
+    #[test]
+    fn version_time_follows_the_previous_entry() {
+        let at = |time: &str| DateTime::parse_from_rfc3339(time).unwrap();
+        let entries = [json!({ "versionTime": "2026-01-01T00:00:00Z" })];
+
+        // Same second, or a clock behind the log: a second after the last entry
+        for now in ["2026-01-01T00:00:00Z", "2025-12-31T23:59:00Z"] {
+            assert_eq!(
+                next_version_time(&entries, at(now)).unwrap(),
+                at("2026-01-01T00:00:01Z"),
+                "{now}"
+            );
+        }
+        // Otherwise now
+        assert_eq!(
+            next_version_time(&entries, at("2026-01-01T00:05:00Z")).unwrap(),
+            at("2026-01-01T00:05:00Z")
+        );
+        assert_eq!(
+            next_version_time(&[], at("2026-01-01T00:05:00Z")).unwrap(),
+            at("2026-01-01T00:05:00Z")
+        );
+    }
+
+    #[test]
+    fn derives_the_did_for_a_hosting_url() {
+        for (url, did) in [
+            (
+                "https://new.example.com",
+                "did:webvh:QmScid:new.example.com",
+            ),
+            (
+                "new.example.com/.well-known/did.jsonl",
+                "did:webvh:QmScid:new.example.com",
+            ),
+            (
+                "https://new.example.com:8443/dids/alice/",
+                "did:webvh:QmScid:new.example.com%3A8443:dids:alice",
+            ),
+        ] {
+            assert_eq!(did_for_url("QmScid", url).unwrap(), did, "{url}");
+        }
+    }
+
+    #[test]
+    fn rebinds_did_urls_only() {
+        let mut document = json!({
+            "id": OLD,
+            "verificationMethod": [{ "id": format!("{OLD}#key-0"), "controller": OLD }],
+            "service": [{ "serviceEndpoint": format!("{OLD}x") }],
+        });
+        rebind(&mut document, OLD, NEW);
+
+        assert_eq!(document["id"], NEW);
+        assert_eq!(
+            document["verificationMethod"][0]["id"],
+            format!("{NEW}#key-0")
+        );
+        assert_eq!(document["verificationMethod"][0]["controller"], NEW);
+        assert_eq!(document["service"][0]["serviceEndpoint"], format!("{OLD}x"));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::errors::CacheError;
 use regex::{Captures, Regex};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
 use std::{
     env, fmt,
     fs::File,
@@ -44,6 +44,12 @@ struct ConfigRaw {
     /// `default_agent_name_concurrency`.
     #[serde(default = "default_agent_name_concurrency")]
     pub agent_name_concurrency: String,
+    /// Debug dashboard on /did/v1/debug. Defaults to **off**: it shows the
+    /// DIDs resolved recently. See handlers/dashboard.rs.
+    #[serde(default = "default_enable_debug_dashboard")]
+    pub enable_debug_dashboard: String,
+    #[serde(default = "default_debug_history_size")]
+    pub debug_history_size: String,
     #[serde(default = "default_rate_limit_per_ip")]
     pub rate_limit_per_ip: String,
     #[serde(default = "default_rate_limit_burst")]
@@ -53,6 +59,14 @@ struct ConfigRaw {
     pub resolve_timeout: String,
     #[serde(default = "default_max_did_size")]
     pub max_did_size: String,
+    /// Seconds between checks of the config file for changes; `0` (the
+    /// default) reloads on SIGHUP only. See reload.rs.
+    #[serde(default = "default_config_reload_interval")]
+    pub config_reload_interval: String,
+    /// Admin API on /did/v1/admin. Defaults to **off**: it describes how the
+    /// server is configured. See handlers/admin.rs.
+    #[serde(default = "default_enable_admin_api")]
+    pub enable_admin_api: String,
     pub cache: CacheConfig,
 }
 
@@ -68,6 +82,17 @@ fn default_max_did_size() -> String {
     "1024".into()
 }
 
+/// The config file is only re-read on SIGHUP unless polling is switched on.
+fn default_config_reload_interval() -> String {
+    "0".into()
+}
+
+/// The admin API defaults to **off**: it describes the server's configuration,
+/// which a public deployment shouldn't disclose.
+fn default_enable_admin_api() -> String {
+    "false".into()
+}
+
 /// Agent name lookup defaults to **off**. Enabling it lets callers make this
 /// server issue HTTP requests to hosts of their choosing; see
 /// `handlers/agent_names.rs` for the SSRF considerations.
@@ -94,6 +119,17 @@ fn default_agent_name_concurrency() -> String {
     "16".into()
 }
 
+/// The debug dashboard defaults to **off**: it lists the DIDs the server
+/// resolved recently, which a public deployment shouldn't disclose.
+fn default_enable_debug_dashboard() -> String {
+    "false".into()
+}
+
+/// Resolutions the debug dashboard keeps in its history.
+fn default_debug_history_size() -> String {
+    "200".into()
+}
+
 /// Sustained requests per second per client IP; `0` disables limiting.
 ///
 /// Matches the mediator's default. Generous for a resolver cache, whose whole
@@ -112,6 +148,7 @@ fn default_rate_limit_burst() -> String {
     "50".into()
 }
 
+#[derive(Clone)]
 pub struct Config {
     pub log_level: LevelFilter,
     pub listen_address: String,
@@ -119,6 +156,9 @@ pub struct Config {
     pub enable_websocket_endpoint: bool,
     pub enable_agent_names: bool,
     pub agent_name_concurrency: usize,
+    pub enable_debug_dashboard: bool,
+    /// Number of recent resolutions the debug dashboard keeps
+    pub debug_history_size: usize,
     pub rate_limit_per_ip: u32,
     pub rate_limit_burst: u32,
     pub statistics_interval: Duration,
@@ -128,6 +168,10 @@ pub struct Config {
     /// Maximum accepted DID length in bytes; longer DIDs are rejected before
     /// resolution so a crafted request can't drive unbounded work.
     pub max_did_size: usize,
+    /// How often the config file is checked for changes, or `None` to reload
+    /// on SIGHUP only.
+    pub config_reload_interval: Option<Duration>,
+    pub enable_admin_api: bool,
     pub cache_capacity_count: u32,
     pub cache_expire: u32,
 }
@@ -140,6 +184,8 @@ impl fmt::Debug for Config {
             .field("enable_http_endpoint", &self.enable_http_endpoint)
             .field("enable_agent_names", &self.enable_agent_names)
             .field("agent_name_concurrency", &self.agent_name_concurrency)
+            .field("enable_debug_dashboard", &self.enable_debug_dashboard)
+            .field("debug_history_size", &self.debug_history_size)
             .field("rate_limit_per_ip", &self.rate_limit_per_ip)
             .field("rate_limit_burst", &self.rate_limit_burst)
             .field("enable_websocket_endpoint", &self.enable_websocket_endpoint)
@@ -152,12 +198,50 @@ impl fmt::Debug for Config {
                 &format!("{} seconds", self.resolve_timeout.as_secs()),
             )
             .field("max_did_size", &format!("{} bytes", self.max_did_size))
+            .field(
+                "config_reload_interval",
+                &self
+                    .config_reload_interval
+                    .map(|interval| format!("{} seconds", interval.as_secs())),
+            )
+            .field("enable_admin_api", &self.enable_admin_api)
             .field("cache_capacity_count", &self.cache_capacity_count)
             .field("cache_expire", &format!("{} seconds", self.cache_expire))
             .finish()
     }
 }
 
+/// The configuration in force, as the admin API reports it: durations in
+/// seconds, like the config file. Holds no secrets.
+impl Serialize for Config {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut config = serializer.serialize_struct("Config", 17)?;
+        config.serialize_field("log_level", &self.log_level.to_string().to_lowercase())?;
+        config.serialize_field("listen_address", &self.listen_address)?;
+        config.serialize_field("enable_http_endpoint", &self.enable_http_endpoint)?;
+        config.serialize_field("enable_websocket_endpoint", &self.enable_websocket_endpoint)?;
+        config.serialize_field("enable_agent_names", &self.enable_agent_names)?;
+        config.serialize_field("agent_name_concurrency", &self.agent_name_concurrency)?;
+        config.serialize_field("enable_debug_dashboard", &self.enable_debug_dashboard)?;
+        config.serialize_field("debug_history_size", &self.debug_history_size)?;
+        config.serialize_field("rate_limit_per_ip", &self.rate_limit_per_ip)?;
+        config.serialize_field("rate_limit_burst", &self.rate_limit_burst)?;
+        config.serialize_field("statistics_interval", &self.statistics_interval.as_secs())?;
+        config.serialize_field("resolve_timeout", &self.resolve_timeout.as_secs())?;
+        config.serialize_field("max_did_size", &self.max_did_size)?;
+        config.serialize_field(
+            "config_reload_interval",
+            &self
+                .config_reload_interval
+                .map_or(0, |interval| interval.as_secs()),
+        )?;
+        config.serialize_field("enable_admin_api", &self.enable_admin_api)?;
+        config.serialize_field("cache_capacity_count", &self.cache_capacity_count)?;
+        config.serialize_field("cache_expire", &self.cache_expire)?;
+        config.end()
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
@@ -167,11 +251,15 @@ impl Default for Config {
             enable_websocket_endpoint: true,
             enable_agent_names: false,
             agent_name_concurrency: 16,
+            enable_debug_dashboard: false,
+            debug_history_size: 200,
             rate_limit_per_ip: 100,
             rate_limit_burst: 50,
             statistics_interval: Duration::from_secs(60),
             resolve_timeout: Duration::from_secs(30),
             max_did_size: 1024,
+            config_reload_interval: None,
+            enable_admin_api: false,
             cache_capacity_count: CacheConfig::default()
                 .capacity_count
                 .parse()
@@ -208,6 +296,14 @@ impl TryFrom<ConfigRaw> for Config {
                 .ok()
                 .filter(|n: &usize| *n > 0)
                 .unwrap_or(16),
+            // Like agent names, an unreadable value must not turn this on.
+            enable_debug_dashboard: raw.enable_debug_dashboard.parse().unwrap_or(false),
+            debug_history_size: raw
+                .debug_history_size
+                .parse()
+                .ok()
+                .filter(|n: &usize| *n > 0)
+                .unwrap_or(200),
             // An unparseable value falls back to the default rather than to 0:
             // 0 means "disabled", and a typo must not silently remove limiting.
             // An explicit "0" still disables, which is the documented way to.
@@ -216,6 +312,14 @@ impl TryFrom<ConfigRaw> for Config {
             statistics_interval: Duration::from_secs(raw.statistics_interval.parse().unwrap_or(60)),
             resolve_timeout: Duration::from_secs(raw.resolve_timeout.parse().unwrap_or(30)),
             max_did_size: raw.max_did_size.parse().unwrap_or(1024),
+            config_reload_interval: raw
+                .config_reload_interval
+                .parse()
+                .ok()
+                .filter(|secs: &u64| *secs > 0)
+                .map(Duration::from_secs),
+            // Like the debug dashboard, an unreadable value must not turn this on.
+            enable_admin_api: raw.enable_admin_api.parse().unwrap_or(false),
             cache_capacity_count: raw.cache.capacity_count.parse().unwrap_or(1000),
             cache_expire: raw.cache.expire.parse().unwrap_or(300),
         })
@@ -304,6 +408,12 @@ fn expand_env_vars(raw_config: &Vec<String>) -> Vec<String> {
 /// Default config path used when no `-c` flag is supplied.
 pub const DEFAULT_CONFIG_PATH: &str = "conf/cache-conf.toml";
 
+/// Read and parse the configuration file, without touching logging. This is
+/// how a running server re-reads it; see [`crate::reload`].
+pub fn load(config_path: &str) -> Result<Config, CacheError> {
+    Config::try_from(read_config_file(config_path)?)
+}
+
 pub fn init(
     config_path: &str,
     reload_handle: Option<Handle<LevelFilter, Registry>>,
@@ -0,0 +1,22 @@
+//! Admin API: `GET /did/v1/admin/config`.
+//!
+//! Registered only when `enable_admin_api` is set. Reports the configuration
+//! in force and how it got there: when it was last (re)loaded, why the last
+//! reload was rejected if it was, and which changed settings are waiting for a
+//! restart. See `reload.rs`.
+//!
+//! The configuration holds no secrets, but describes the deployment, so the
+//! admin API is meant for operators and should not be reachable from the
+//! public internet.
+use crate::{SharedData, reload::RELOADABLE};
+use axum::{Json, extract::State};
+use serde_json::{Value, json};
+
+/// `GET /did/v1/admin/config`
+pub async fn config_handler(State(state): State<SharedData>) -> Json<Value> {
+    Json(json!({
+        "config": state.config.current(),
+        "reloadable": RELOADABLE,
+        "reload": state.config.status(),
+    }))
+}
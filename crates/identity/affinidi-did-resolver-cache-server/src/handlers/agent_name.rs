@@ -56,14 +56,15 @@ pub async fn resolve_name_handler(
     State(state): State<SharedData>,
     Path(name): Path<String>,
 ) -> (StatusCode, Json<Value>) {
-    if name.len() > state.max_did_size {
+    let config = state.config.current();
+    if name.len() > config.max_did_size {
         state.stats.lock().await.increment_agent_name_error();
         return (
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "error": format!(
                     "Agent name exceeds maximum length of {} bytes",
-                    state.max_did_size
+                    config.max_did_size
                 )
             })),
         );
@@ -104,7 +105,7 @@ pub async fn resolve_name_handler(
         );
     };
 
-    let outcome = tokio::time::timeout(state.resolve_timeout, resolver.resolve(&parsed)).await;
+    let outcome = tokio::time::timeout(config.resolve_timeout, resolver.resolve(&parsed)).await;
 
     match outcome {
         Ok(Some(Ok(did))) => {
@@ -138,7 +139,7 @@ pub async fn resolve_name_handler(
                 Json(json!({
                     "error": format!(
                         "Timed out after {} seconds resolving agent name",
-                        state.resolve_timeout.as_secs()
+                        config.resolve_timeout.as_secs()
                     )
                 })),
             )
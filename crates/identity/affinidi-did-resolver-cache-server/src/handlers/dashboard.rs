@@ -0,0 +1,145 @@
+//! Debug dashboard: `GET /did/v1/debug` and the JSON endpoints under it.
+//!
+//! Registered only when `enable_debug_dashboard` is set. The HTML page polls
+//! the JSON endpoints:
+//!
+//! - `/debug/resolutions?limit=N` — recent resolutions, most recent first
+//! - `/debug/cache` — cache entry count and entries per DID method
+//! - `/debug/latency` — latency percentiles of successful resolutions per
+//!   DID method
+//! - `/debug/resolve/{did}` — resolve a DID now and report how it went
+//!
+//! The page lists DIDs other clients resolved, so the dashboard is meant for
+//! operators and should not be reachable from the public internet.
+use crate::{
+    SharedData,
+    handlers::{did_within_size_limit, resolve_recorded},
+    resolutions::{ResolutionSource, did_method},
+};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::Html,
+};
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::{collections::BTreeMap, time::Instant};
+
+const DASHBOARD_PAGE: &str = include_str!("dashboard.html");
+
+/// Resolutions returned when the request doesn't set `limit`
+const DEFAULT_RESOLUTIONS_LIMIT: usize = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct ResolutionsQuery {
+    limit: Option<usize>,
+}
+
+fn disabled() -> (StatusCode, Json<Value>) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(json!({ "error": "The debug dashboard is disabled" })),
+    )
+}
+
+/// `GET /did/v1/debug`
+pub async fn page_handler() -> Html<&'static str> {
+    Html(DASHBOARD_PAGE)
+}
+
+/// `GET /did/v1/debug/resolutions`
+pub async fn resolutions_handler(
+    State(state): State<SharedData>,
+    Query(query): Query<ResolutionsQuery>,
+) -> (StatusCode, Json<Value>) {
+    let Some(resolutions) = &state.resolutions else {
+        return disabled();
+    };
+
+    let limit = query.limit.unwrap_or(DEFAULT_RESOLUTIONS_LIMIT);
+    (
+        StatusCode::OK,
+        Json(json!({ "resolutions": resolutions.recent(limit).await })),
+    )
+}
+
+/// `GET /did/v1/debug/cache`
+pub async fn cache_handler(State(state): State<SharedData>) -> (StatusCode, Json<Value>) {
+    let cache = state.resolver.get_cache();
+    cache.run_pending_tasks().await;
+
+    let mut by_method: BTreeMap<String, u64> = BTreeMap::new();
+    for (_, doc) in cache.iter() {
+        *by_method
+            .entry(did_method(doc.id.as_str()).to_string())
+            .or_default() += 1;
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "entries": cache.entry_count(),
+            "by_method": by_method,
+        })),
+    )
+}
+
+/// `GET /did/v1/debug/latency`
+pub async fn latency_handler(State(state): State<SharedData>) -> (StatusCode, Json<Value>) {
+    let Some(resolutions) = &state.resolutions else {
+        return disabled();
+    };
+
+    (
+        StatusCode::OK,
+        Json(json!({ "methods": resolutions.latency_by_method().await })),
+    )
+}
+
+/// `GET /did/v1/debug/resolve/{did}`
+///
+/// Resolves through the same path as client requests, so the result shows up
+/// in the history and latency figures, and reports whether it was a cache hit
+/// and how long it took.
+pub async fn resolve_handler(
+    State(state): State<SharedData>,
+    Path(did): Path<String>,
+) -> (StatusCode, Json<Value>) {
+    let max_did_size = state.config.current().max_did_size;
+    if !did_within_size_limit(&did, max_did_size) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": format!(
+                    "DID exceeds maximum length of {max_did_size} bytes"
+                )
+            })),
+        );
+    }
+
+    let started = Instant::now();
+    let result = resolve_recorded(&state, ResolutionSource::Dashboard, &did).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    match result {
+        Ok(response) => (
+            StatusCode::OK,
+            Json(json!({
+                "did": response.did,
+                "method": response.method.to_string(),
+                "cache_hit": response.cache_hit,
+                "latency_ms": latency_ms,
+                "document": response.doc,
+            })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "did": did,
+                "latency_ms": latency_ms,
+                "error": e.to_string(),
+            })),
+        ),
+    }
+}
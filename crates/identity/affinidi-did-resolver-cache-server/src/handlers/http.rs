@@ -1,6 +1,7 @@
 use crate::{
     SharedData,
-    handlers::{did_within_size_limit, fetch_webvh_log, resolve_with_timeout},
+    handlers::{did_within_size_limit, fetch_webvh_log, resolve_recorded},
+    resolutions::ResolutionSource,
 };
 use affinidi_did_resolver_cache_sdk::DIDMethod;
 use axum::{
@@ -15,20 +16,20 @@ pub async fn resolver_handler(
     State(state): State<SharedData>,
     Path(did): Path<String>,
 ) -> (StatusCode, Json<Value>) {
-    if !did_within_size_limit(&did, state.max_did_size) {
+    let max_did_size = state.config.current().max_did_size;
+    if !did_within_size_limit(&did, max_did_size) {
         state.stats.lock().await.increment_resolver_error();
         return (
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "error": format!(
-                    "DID exceeds maximum length of {} bytes",
-                    state.max_did_size
+                    "DID exceeds maximum length of {max_did_size} bytes"
                 )
             })),
         );
     }
 
-    match resolve_with_timeout(&state.resolver, state.resolve_timeout, &did).await {
+    match resolve_recorded(&state, ResolutionSource::Http, &did).await {
         Ok(doc) => {
             let mut stats = state.stats.lock().await;
             stats.increment_resolver_success();
@@ -40,7 +41,7 @@ pub async fn resolver_handler(
 
             // For WebVH DIDs, include the raw log so clients can verify
             let (did_log, did_witness_log) = if doc.method == DIDMethod::WEBVH {
-                fetch_webvh_log(&state.webvh_client, &did).await
+                fetch_webvh_log(&state.webvh_logs, &did).await
             } else {
                 (None, None)
             };
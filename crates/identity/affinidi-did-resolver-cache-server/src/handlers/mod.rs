@@ -1,17 +1,22 @@
-use crate::{SharedData, config::Config};
+use crate::{
+    SharedData,
+    config::Config,
+    resolutions::{ResolutionRecord, ResolutionSource},
+    webvh_logs::{MAX_WEBVH_LOG_BYTES, WebVHLogs, read_text_limited},
+};
 use affinidi_did_resolver_cache_sdk::{DIDCacheClient, ResolveResponse, errors::DIDCacheError};
 use axum::{Json, Router, extract::State, response::IntoResponse, routing::get};
 use std::future::Future;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
+pub(crate) mod admin;
 pub(crate) mod agent_name;
+pub(crate) mod dashboard;
 pub(crate) mod http;
 #[cfg(feature = "network")]
 pub(crate) mod websocket;
 
-const MAX_WEBVH_LOG_BYTES: usize = 1024 * 1024;
-
 /// Outcome of a timeout-bounded upstream resolution.
 #[derive(Debug)]
 pub(crate) enum ResolveError {
@@ -53,34 +58,42 @@ pub(crate) async fn resolve_with_timeout(
     apply_timeout(timeout, resolver.resolve(did)).await
 }
 
+/// Resolve `did` as [`resolve_with_timeout`] does, recording the outcome for
+/// the debug dashboard when it is enabled.
+pub(crate) async fn resolve_recorded(
+    state: &SharedData,
+    source: ResolutionSource,
+    did: &str,
+) -> Result<ResolveResponse, ResolveError> {
+    let started = Instant::now();
+    let resolve_timeout = state.config.current().resolve_timeout;
+    let result = resolve_with_timeout(&state.resolver, resolve_timeout, did).await;
+
+    if let Some(resolutions) = &state.resolutions {
+        let (cache_hit, error) = match &result {
+            Ok(response) => (response.cache_hit, None),
+            Err(e) => (false, Some(e.to_string())),
+        };
+        resolutions
+            .record(ResolutionRecord::new(
+                did,
+                source,
+                started.elapsed(),
+                cache_hit,
+                error,
+            ))
+            .await;
+    }
+
+    result
+}
+
 /// Whether `did` is within the configured byte-length limit. Oversized DIDs are
 /// rejected before resolution so a crafted request can't drive unbounded work.
 pub(crate) fn did_within_size_limit(did: &str, max: usize) -> bool {
     did.len() <= max
 }
 
-/// Read a response body as UTF-8 text, refusing anything larger than `limit` bytes.
-async fn read_text_limited(mut resp: reqwest::Response, limit: usize) -> Option<String> {
-    let mut buf = Vec::new();
-    loop {
-        match resp.chunk().await {
-            Ok(Some(chunk)) => {
-                if buf.len() + chunk.len() > limit {
-                    warn!("WebVH log body exceeded {limit} byte cap; dropping");
-                    return None;
-                }
-                buf.extend_from_slice(&chunk);
-            }
-            Ok(None) => break,
-            Err(e) => {
-                warn!("Failed to read WebVH log response body: {e}");
-                return None;
-            }
-        }
-    }
-    String::from_utf8(buf).ok()
-}
-
 /// For did:webvh DIDs, fetch the raw DID log + witness file from the source
 /// HTTP endpoint so clients can independently verify the cryptographic chain.
 ///
@@ -88,7 +101,7 @@ async fn read_text_limited(mut resp: reqwest::Response, limit: usize) -> Option<
 /// refuses redirects and caps the response body to avoid being used as an SSRF
 /// pivot / reflection oracle or memory-exhaustion vector.
 pub(crate) async fn fetch_webvh_log(
-    client: &reqwest::Client,
+    logs: &WebVHLogs,
     did: &str,
 ) -> (Option<String>, Option<String>) {
     let parsed_url = match didwebvh_rs::url::WebVHURL::parse_did_url(did) {
@@ -107,26 +120,14 @@ pub(crate) async fn fetch_webvh_log(
         }
     };
 
-    let did_log = match client.get(log_url).send().await {
-        Ok(resp) if resp.status().is_success() => {
-            read_text_limited(resp, MAX_WEBVH_LOG_BYTES).await
-        }
-        Ok(resp) => {
-            warn!("WebVH log fetch returned HTTP {}: {}", resp.status(), did);
-            None
-        }
-        Err(e) => {
-            warn!("Failed to fetch WebVH log for {}: {e}", did);
-            None
-        }
-    };
+    let did_log = logs.fetch(log_url.as_str()).await;
 
     let did_witness_log = if did_log.is_some() {
         let witness_url = match parsed_url.get_http_url(Some("did-witness.json")) {
             Ok(url) => url,
             Err(_) => return (did_log, None),
         };
-        match client.get(witness_url).send().await {
+        match logs.client().get(witness_url).send().await {
             Ok(resp) if resp.status().is_success() => {
                 read_text_limited(resp, MAX_WEBVH_LOG_BYTES).await
             }
@@ -169,6 +170,23 @@ pub fn application_routes(shared_data: &SharedData, config: &Config) -> Router {
         );
     }
 
+    if config.enable_debug_dashboard {
+        // The dashboard shows every DID resolved recently, so it is off by
+        // default and should not be exposed beyond operators.
+        warn!("Enabling debug dashboard on /did/v1/debug (exposes recently resolved DIDs)");
+        app = app
+            .route("/debug", get(dashboard::page_handler))
+            .route("/debug/resolutions", get(dashboard::resolutions_handler))
+            .route("/debug/cache", get(dashboard::cache_handler))
+            .route("/debug/latency", get(dashboard::latency_handler))
+            .route("/debug/resolve/{did}", get(dashboard::resolve_handler));
+    }
+
+    if config.enable_admin_api {
+        warn!("Enabling admin API on /did/v1/admin (exposes the server configuration)");
+        app = app.route("/admin/config", get(admin::config_handler));
+    }
+
     Router::new()
         .nest("/did/v1", app)
         .with_state(shared_data.to_owned())
@@ -15,20 +15,26 @@ use tracing::{Instrument, debug, info, span, warn};
 
 use crate::{
     SharedData,
-    handlers::{did_within_size_limit, fetch_webvh_log, resolve_with_timeout},
+    handlers::{did_within_size_limit, fetch_webvh_log, resolve_recorded},
+    resolutions::ResolutionSource,
+    webvh_logs::WebVHLogs,
 };
 
 /// Build a WSResponse, fetching the raw DID log for WebVH DIDs.
-async fn build_response(client: &reqwest::Client, response: ResolveResponse) -> WSResponseType {
+async fn build_response(logs: &WebVHLogs, response: ResolveResponse) -> WSResponseType {
     let (did_log, did_witness_log) = if response.method == DIDMethod::WEBVH {
-        fetch_webvh_log(client, &response.did).await
+        fetch_webvh_log(logs, &response.did).await
     } else {
         (None, None)
     };
 
     WSResponseType::Response(Box::new(
-        WSResponse::new(response.did.clone(), response.did_hash, response.doc)
-            .with_logs(did_log, did_witness_log),
+        WSResponse::new(
+            response.did.clone(),
+            response.did_hash,
+            response.into_document(),
+        )
+        .with_logs(did_log, did_witness_log),
     ))
 }
 
@@ -89,13 +95,14 @@ async fn resolve_agent_name_and_respond(
 
     let fail = |error: String| WSResponseType::Error(WSResponseError::new(&name, name_hash, error));
 
-    if name.len() > state.max_did_size {
+    let config = state.config.current();
+    if name.len() > config.max_did_size {
         state.stats().await.increment_agent_name_error();
         return send_response(
             socket,
             &fail(format!(
                 "Agent name exceeds maximum length of {} bytes",
-                state.max_did_size
+                config.max_did_size
             )),
         )
         .await;
@@ -129,7 +136,7 @@ async fn resolve_agent_name_and_respond(
         .await;
     };
 
-    let did = match tokio::time::timeout(state.resolve_timeout, resolver.resolve(&parsed)).await {
+    let did = match tokio::time::timeout(config.resolve_timeout, resolver.resolve(&parsed)).await {
         Ok(Some(Ok(did))) => did,
         Ok(Some(Err(e))) => {
             state.stats().await.increment_agent_name_error();
@@ -151,7 +158,7 @@ async fn resolve_agent_name_and_respond(
     };
 
     // Now resolve that DID exactly as a normal request would.
-    match resolve_with_timeout(&state.resolver, state.resolve_timeout, &did).await {
+    match resolve_recorded(state, ResolutionSource::AgentName, &did).await {
         Ok(response) => {
             {
                 let mut stats = state.stats().await;
@@ -163,12 +170,12 @@ async fn resolve_agent_name_and_respond(
                 stats.increment_did_method_success(response.method.clone());
             }
             let (did_log, did_witness_log) = if response.method == DIDMethod::WEBVH {
-                fetch_webvh_log(&state.webvh_client, &response.did).await
+                fetch_webvh_log(&state.webvh_logs, &response.did).await
             } else {
                 (None, None)
             };
             let message = WSResponseType::Response(Box::new(
-                WSResponse::new(response.did.clone(), name_hash, response.doc)
+                WSResponse::new(response.did.clone(), name_hash, response.into_document())
                     .with_logs(did_log, did_witness_log)
                     .with_agent_name(Some(parsed.as_str().to_string())),
             ));
@@ -182,19 +189,20 @@ async fn resolve_agent_name_and_respond(
 }
 
 async fn resolve_and_respond(socket: &mut WebSocket, state: &SharedData, did: String) -> bool {
-    if !did_within_size_limit(&did, state.max_did_size) {
+    let max_did_size = state.config.current().max_did_size;
+    if !did_within_size_limit(&did, max_did_size) {
         let hash = DIDCacheClient::hash_did(&did);
         warn!("ws: rejecting oversized DID ({} bytes)", did.len());
         state.stats().await.increment_resolver_error();
         let message = WSResponseType::Error(WSResponseError::new(
             did,
             hash,
-            format!("DID exceeds maximum length of {} bytes", state.max_did_size),
+            format!("DID exceeds maximum length of {max_did_size} bytes"),
         ));
         return send_response(socket, &message).await;
     }
 
-    match resolve_with_timeout(&state.resolver, state.resolve_timeout, &did).await {
+    match resolve_recorded(state, ResolutionSource::WebSocket, &did).await {
         Ok(response) => {
             {
                 let mut stats = state.stats().await;
@@ -208,7 +216,7 @@ async fn resolve_and_respond(socket: &mut WebSocket, state: &SharedData, did: St
                 "resolved DID: ({}) cache_hit?({})",
                 response.did, response.cache_hit
             );
-            let message = build_response(&state.webvh_client, response).await;
+            let message = build_response(&state.webvh_logs, response).await;
             send_response(socket, &message).await
         }
         Err(e) => {
@@ -239,8 +247,9 @@ pub async fn websocket_handler(
 
     // Bound incoming frames so a crafted client can't buffer huge messages
     // before we even parse them. A DID request is tiny; size it to the DID
-    // limit plus envelope overhead.
-    let max_message_size = state.max_did_size.saturating_add(1024);
+    // limit plus envelope overhead. Fixed for the life of the connection: a
+    // reload that changes the limit applies to connections opened after it.
+    let max_message_size = state.config.current().max_did_size.saturating_add(1024);
     let ws = ws
         .max_message_size(max_message_size)
         .max_frame_size(max_message_size);
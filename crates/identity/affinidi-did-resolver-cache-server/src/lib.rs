@@ -4,33 +4,38 @@ use axum::{
     http::request::Parts,
 };
 use chrono::{DateTime, Utc};
+use reload::LiveConfig;
+use resolutions::ResolutionLog;
 use session::SessionError;
 use statistics::Statistics;
-use std::{fmt::Debug, sync::Arc, time::Duration};
+use std::{fmt::Debug, sync::Arc};
 use tokio::sync::{Mutex, MutexGuard, Semaphore};
+use webvh_logs::WebVHLogs;
 
 pub(crate) mod common;
 pub mod config;
 pub mod errors;
 pub mod handlers;
+pub mod reload;
+pub mod resolutions;
 pub mod server;
 pub mod session;
 pub mod statistics;
+pub mod webvh_logs;
 
 #[derive(Clone)]
 pub struct SharedData {
     pub service_start_timestamp: DateTime<Utc>,
     pub stats: Arc<Mutex<Statistics>>,
     pub resolver: DIDCacheClient,
-    /// Upper bound on a single upstream resolution before the request path
-    /// returns an error rather than blocking the connection.
-    pub resolve_timeout: Duration,
-    /// Maximum accepted DID length in bytes; longer DIDs are rejected before
-    /// resolution.
-    pub max_did_size: usize,
-    /// Shared HTTP client for did:webvh log fetches, built once at startup so
-    /// connections are pooled instead of a fresh client per request.
-    pub webvh_client: reqwest::Client,
+    /// The configuration in force, replaced on reload. Request paths read
+    /// `resolve_timeout` and `max_did_size` from it.
+    pub config: LiveConfig,
+    /// did:webvh log fetches. Holds one HTTP client, built once at startup so
+    /// connections are pooled instead of a fresh client per request, and the
+    /// checkpoints that let repeat fetches of a log resume where the last one
+    /// ended.
+    pub webvh_logs: WebVHLogs,
     /// Present only when `enable_agent_names` is set. `None` means the feature
     /// is off and the route is not registered.
     pub agent_name_resolver: Option<Arc<agent_names::HttpRedirectResolver>>,
@@ -41,6 +46,10 @@ pub struct SharedData {
     /// something per-IP rate limiting cannot do, because the cap has to hold
     /// however many source addresses the load arrives from.
     pub agent_name_permits: Arc<Semaphore>,
+    /// Present only when `enable_debug_dashboard` is set: the recent
+    /// resolutions and per-method latencies the dashboard shows. `None` means
+    /// nothing is recorded and the dashboard routes are not registered.
+    pub resolutions: Option<Arc<ResolutionLog>>,
 }
 
 impl<S> FromRequestParts<S> for SharedData
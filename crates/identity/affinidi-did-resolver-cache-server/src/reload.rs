@@ -0,0 +1,485 @@
+//! Reloading the configuration of a running server.
+//!
+//! The config file is re-read on SIGHUP and, when `config_reload_interval` is
+//! set, whenever its modification time changes. A file that fails to read or
+//! parse is rejected as a whole and the running configuration stays in force.
+//!
+//! Settings read per request or applied to a live component take effect
+//! immediately, without dropping open connections: WebSocket sessions hold the
+//! same [`LiveConfig`] and see the new values from their next request. The
+//! rest (listen address, which endpoints exist, capacities sized at startup)
+//! need a restart; a reload that changes them logs a warning and reports them
+//! as pending, and the server keeps running with the values it started with.
+//!
+//! The configuration is swapped as a whole, so a request sees either the old
+//! or the new settings, never a mix.
+
+use crate::{config, config::Config, errors::CacheError};
+use affinidi_did_resolver_cache_sdk::DIDCacheClient;
+use affinidi_rate_limit::RateLimiterState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Level, debug, info, span, warn};
+use tracing_subscriber::{Registry, filter::LevelFilter, reload::Handle};
+
+/// Settings a reload applies to the running server.
+pub const RELOADABLE: [&str; 6] = [
+    "log_level",
+    "rate_limit_per_ip",
+    "rate_limit_burst",
+    "resolve_timeout",
+    "max_did_size",
+    "cache_expire",
+];
+
+/// The configuration in force, shared by every clone.
+#[derive(Clone)]
+pub struct LiveConfig {
+    state: Arc<RwLock<LiveState>>,
+}
+
+struct LiveState {
+    config: Arc<Config>,
+    status: ReloadStatus,
+}
+
+/// How the configuration in force came to be, as the admin API reports it.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReloadStatus {
+    /// Successful loads, counting the one at startup
+    pub generation: u64,
+    pub loaded_at: DateTime<Utc>,
+    /// Why the last reload was rejected, if it was
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Settings changed in the file that only take effect on restart
+    pub pending_restart: Vec<&'static str>,
+}
+
+impl LiveConfig {
+    pub fn new(config: Config) -> Self {
+        LiveConfig {
+            state: Arc::new(RwLock::new(LiveState {
+                config: Arc::new(config),
+                status: ReloadStatus {
+                    generation: 1,
+                    loaded_at: Utc::now(),
+                    last_error: None,
+                    last_error_at: None,
+                    pending_restart: Vec::new(),
+                },
+            })),
+        }
+    }
+
+    /// The configuration in force. Hold on to it for the length of a request
+    /// so the request sees one consistent configuration.
+    pub fn current(&self) -> Arc<Config> {
+        self.state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .config
+            .clone()
+    }
+
+    pub fn status(&self) -> ReloadStatus {
+        self.state
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .status
+            .clone()
+    }
+}
+
+/// Re-reads the config file and applies it to the running server.
+#[derive(Clone)]
+pub struct ConfigReloader {
+    path: PathBuf,
+    live: LiveConfig,
+    resolver: DIDCacheClient,
+    rate_limiter: RateLimiterState,
+    log_level: Option<Handle<LevelFilter, Registry>>,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        path: impl Into<PathBuf>,
+        live: LiveConfig,
+        resolver: DIDCacheClient,
+        rate_limiter: RateLimiterState,
+    ) -> Self {
+        ConfigReloader {
+            path: path.into(),
+            live,
+            resolver,
+            rate_limiter,
+            log_level: None,
+        }
+    }
+
+    /// Apply `log_level` through this handle on reload.
+    pub fn with_log_level_handle(mut self, handle: Handle<LevelFilter, Registry>) -> Self {
+        self.log_level = Some(handle);
+        self
+    }
+
+    /// Re-read the config file and apply it. Returns the settings that
+    /// changed.
+    ///
+    /// On error nothing is applied: the running configuration stays in force
+    /// and the error is recorded for the admin API.
+    pub fn reload(&self) -> Result<Vec<&'static str>, CacheError> {
+        let loaded = config::load(&self.path.to_string_lossy()).inspect_err(|err| {
+            warn!("Configuration reload rejected, keeping the running configuration: {err}");
+            let mut state = self
+                .live
+                .state
+                .write()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            state.status.last_error = Some(err.to_string());
+            state.status.last_error_at = Some(Utc::now());
+        })?;
+
+        // Held across applying, so concurrent reloads (a SIGHUP during a file
+        // change) apply one after the other.
+        let mut state = self
+            .live
+            .state
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let running = state.config.clone();
+        let changed = reloadable_changes(&running, &loaded);
+        let pending_restart = restart_only_changes(&running, &loaded);
+
+        if running.log_level != loaded.log_level
+            && let Some(handle) = &self.log_level
+        {
+            handle
+                .modify(|filter| *filter = loaded.log_level)
+                .map_err(|e| CacheError::InternalError("NA".into(), e.to_string()))?;
+        }
+        // Only when changed: new limits start every client on a full bucket.
+        if running.rate_limit_per_ip != loaded.rate_limit_per_ip
+            || running.rate_limit_burst != loaded.rate_limit_burst
+        {
+            self.rate_limiter
+                .set_limits(loaded.rate_limit_per_ip, loaded.rate_limit_burst);
+        }
+        if running.cache_expire != loaded.cache_expire {
+            self.resolver.set_cache_ttl(loaded.cache_expire);
+        }
+
+        state.config = Arc::new(Config {
+            log_level: loaded.log_level,
+            rate_limit_per_ip: loaded.rate_limit_per_ip,
+            rate_limit_burst: loaded.rate_limit_burst,
+            resolve_timeout: loaded.resolve_timeout,
+            max_did_size: loaded.max_did_size,
+            cache_expire: loaded.cache_expire,
+            ..(*running).clone()
+        });
+        state.status.generation += 1;
+        state.status.loaded_at = Utc::now();
+        state.status.last_error = None;
+        state.status.last_error_at = None;
+        state.status.pending_restart = pending_restart;
+
+        if changed.is_empty() {
+            info!("Configuration reloaded; no changes");
+        } else {
+            info!("Configuration reloaded; applied ({})", changed.join(", "));
+        }
+        if !state.status.pending_restart.is_empty() {
+            warn!(
+                "Configuration changes need a restart to take effect ({})",
+                state.status.pending_restart.join(", ")
+            );
+        }
+        Ok(changed)
+    }
+
+    /// Reload on SIGHUP, and when the config file changes if `poll_interval`
+    /// is set, until `shutdown` is cancelled.
+    pub async fn run(
+        &self,
+        poll_interval: Option<Duration>,
+        shutdown: CancellationToken,
+    ) -> Result<(), CacheError> {
+        let _span = span!(Level::INFO, "config_reload");
+
+        async move {
+            let mut hangup = Hangup::new()?;
+            let mut poll = poll_interval.map(tokio::time::interval);
+            let mut modified = self.modified();
+
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => {
+                        debug!("Config reload task shutting down");
+                        return Ok(());
+                    }
+                    _ = hangup.recv() => info!("SIGHUP received; reloading configuration"),
+                    _ = tick(&mut poll) => {
+                        if self.modified() == modified {
+                            continue;
+                        }
+                        info!("Config file ({}) changed; reloading", self.path.display());
+                    }
+                }
+                modified = self.modified();
+                // A rejected reload is logged and recorded; keep watching.
+                let _ = self.reload();
+            }
+        }
+        .instrument(_span)
+        .await
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+/// Ticks `poll`, or never when polling is off.
+async fn tick(poll: &mut Option<tokio::time::Interval>) {
+    match poll {
+        Some(poll) => {
+            poll.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// SIGHUP, on platforms that have it. Registering replaces the default action
+/// of terminating the process.
+struct Hangup {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+}
+
+impl Hangup {
+    fn new() -> Result<Self, CacheError> {
+        Ok(Hangup {
+            #[cfg(unix)]
+            signal: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                .map_err(|e| {
+                    CacheError::InternalError(
+                        "NA".into(),
+                        format!("Couldn't listen for SIGHUP: {e}"),
+                    )
+                })?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if self.signal.recv().await.is_some() {
+            return;
+        }
+        std::future::pending().await
+    }
+}
+
+/// The reloadable settings that differ between `running` and `loaded`.
+fn reloadable_changes(running: &Config, loaded: &Config) -> Vec<&'static str> {
+    [
+        running.log_level != loaded.log_level,
+        running.rate_limit_per_ip != loaded.rate_limit_per_ip,
+        running.rate_limit_burst != loaded.rate_limit_burst,
+        running.resolve_timeout != loaded.resolve_timeout,
+        running.max_did_size != loaded.max_did_size,
+        running.cache_expire != loaded.cache_expire,
+    ]
+    .into_iter()
+    .zip(RELOADABLE)
+    .filter_map(|(changed, name)| changed.then_some(name))
+    .collect()
+}
+
+/// The settings that differ between `running` and `loaded` but only take
+/// effect on restart.
+fn restart_only_changes(running: &Config, loaded: &Config) -> Vec<&'static str> {
+    [
+        (
+            running.listen_address != loaded.listen_address,
+            "listen_address",
+        ),
+        (
+            running.enable_http_endpoint != loaded.enable_http_endpoint,
+            "enable_http_endpoint",
+        ),
+        (
+            running.enable_websocket_endpoint != loaded.enable_websocket_endpoint,
+            "enable_websocket_endpoint",
+        ),
+        (
+            running.enable_agent_names != loaded.enable_agent_names,
+            "enable_agent_names",
+        ),
+        (
+            running.agent_name_concurrency != loaded.agent_name_concurrency,
+            "agent_name_concurrency",
+        ),
+        (
+            running.enable_debug_dashboard != loaded.enable_debug_dashboard,
+            "enable_debug_dashboard",
+        ),
+        (
+            running.debug_history_size != loaded.debug_history_size,
+            "debug_history_size",
+        ),
+        (
+            running.statistics_interval != loaded.statistics_interval,
+            "statistics_interval",
+        ),
+        (
+            running.config_reload_interval != loaded.config_reload_interval,
+            "config_reload_interval",
+        ),
+        (
+            running.enable_admin_api != loaded.enable_admin_api,
+            "enable_admin_api",
+        ),
+        (
+            running.cache_capacity_count != loaded.cache_capacity_count,
+            "cache_capacity_count",
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(changed, name)| changed.then_some(name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use affinidi_did_resolver_cache_sdk::config::DIDCacheConfigBuilder;
+
+    const CONFIG: &str = r#"
+log_level = "info"
+listen_address = "127.0.0.1:8080"
+enable_http_endpoint = "true"
+enable_websocket_endpoint = "true"
+statistics_interval = "60"
+resolve_timeout = "30"
+rate_limit_per_ip = "100"
+rate_limit_burst = "50"
+
+[cache]
+capacity_count = "1000"
+expire = "300"
+"#;
+
+    /// A config file in a fresh temporary directory.
+    struct ConfigFile(PathBuf);
+
+    impl ConfigFile {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir()
+                .join(format!("cache-server-reload-{name}-{}", std::process::id()));
+            std::fs::create_dir_all(&dir).unwrap();
+            let file = ConfigFile(dir.join("cache-conf.toml"));
+            file.write(CONFIG);
+            file
+        }
+
+        fn write(&self, contents: &str) {
+            std::fs::write(&self.0, contents).unwrap();
+        }
+    }
+
+    impl Drop for ConfigFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(self.0.parent().unwrap());
+        }
+    }
+
+    async fn reloader(file: &ConfigFile) -> (ConfigReloader, RateLimiterState) {
+        let config = config::load(&file.0.to_string_lossy()).unwrap();
+        let resolver = DIDCacheClient::new(
+            DIDCacheConfigBuilder::default()
+                .with_cache_ttl(config.cache_expire)
+                .build(),
+        )
+        .await
+        .unwrap();
+        let rate_limiter = RateLimiterState::new(config.rate_limit_per_ip, config.rate_limit_burst);
+        (
+            ConfigReloader::new(
+                &file.0,
+                LiveConfig::new(config),
+                resolver,
+                rate_limiter.clone(),
+            ),
+            rate_limiter,
+        )
+    }
+
+    #[tokio::test]
+    async fn reload_applies_reloadable_settings() {
+        let file = ConfigFile::new("apply");
+        let (reloader, rate_limiter) = reloader(&file).await;
+
+        file.write(
+            &CONFIG
+                .replace(r#"resolve_timeout = "30""#, r#"resolve_timeout = "5""#)
+                .replace(r#"rate_limit_per_ip = "100""#, r#"rate_limit_per_ip = "0""#)
+                .replace(r#"expire = "300""#, r#"expire = "60""#),
+        );
+        let changed = reloader.reload().unwrap();
+
+        assert_eq!(
+            changed,
+            ["rate_limit_per_ip", "resolve_timeout", "cache_expire"]
+        );
+        let config = reloader.live.current();
+        assert_eq!(config.resolve_timeout, Duration::from_secs(5));
+        assert_eq!(config.cache_expire, 60);
+        assert_eq!(reloader.resolver.cache_ttl(), 60);
+        assert!(!rate_limiter.is_enabled());
+
+        let status = reloader.live.status();
+        assert_eq!(status.generation, 2);
+        assert!(status.pending_restart.is_empty());
+    }
+
+    #[tokio::test]
+    async fn restart_only_settings_are_reported_not_applied() {
+        let file = ConfigFile::new("restart");
+        let (reloader, _) = reloader(&file).await;
+
+        file.write(&CONFIG.replace("127.0.0.1:8080", "127.0.0.1:9090"));
+        assert!(reloader.reload().unwrap().is_empty());
+
+        assert_eq!(reloader.live.current().listen_address, "127.0.0.1:8080");
+        assert_eq!(reloader.live.status().pending_restart, ["listen_address"]);
+    }
+
+    #[tokio::test]
+    async fn invalid_file_keeps_running_config() {
+        let file = ConfigFile::new("invalid");
+        let (reloader, _) = reloader(&file).await;
+        let before = reloader.live.current();
+
+        file.write(&CONFIG.replace(r#"resolve_timeout = "30""#, "resolve_timeout = "));
+        assert!(reloader.reload().is_err());
+
+        assert!(Arc::ptr_eq(&before, &reloader.live.current()));
+        let status = reloader.live.status();
+        assert_eq!(status.generation, 1);
+        assert!(status.last_error.is_some());
+
+        // A good file clears the error.
+        file.write(CONFIG);
+        reloader.reload().unwrap();
+        assert!(reloader.live.status().last_error.is_none());
+    }
+}
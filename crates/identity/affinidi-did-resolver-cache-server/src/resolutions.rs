@@ -0,0 +1,231 @@
+//! Resolution history for the debug dashboard.
+//! Keeps the most recent resolutions and a window of latency samples per DID
+//! method, so the dashboard can show what the server resolved lately and how
+//! long each method takes. Only populated when `enable_debug_dashboard` is set.
+use ahash::AHashMap as HashMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+use tokio::sync::Mutex;
+
+/// Latency samples kept per DID method for the percentile summary
+const LATENCY_SAMPLES_PER_METHOD: usize = 1000;
+
+/// Where a resolution request came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolutionSource {
+    Http,
+    WebSocket,
+    AgentName,
+    Dashboard,
+}
+
+/// One resolution, as shown on the dashboard
+#[derive(Clone, Debug, Serialize)]
+pub struct ResolutionRecord {
+    pub timestamp: DateTime<Utc>,
+    pub did: String,
+    /// DID method taken from the DID itself, so failed resolutions have one too
+    pub method: String,
+    pub source: ResolutionSource,
+    pub cache_hit: bool,
+    pub latency_ms: f64,
+    /// Set when the resolution failed or timed out
+    pub error: Option<String>,
+}
+
+impl ResolutionRecord {
+    pub fn new(
+        did: &str,
+        source: ResolutionSource,
+        latency: Duration,
+        cache_hit: bool,
+        error: Option<String>,
+    ) -> Self {
+        ResolutionRecord {
+            timestamp: Utc::now(),
+            did: did.to_string(),
+            method: did_method(did).to_string(),
+            source,
+            cache_hit,
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            error,
+        }
+    }
+}
+
+/// Latency percentiles (milliseconds) for one DID method
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencySummary {
+    /// Nearest-rank percentiles over `samples`
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return LatencySummary::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let rank = |p: f64| {
+            let index = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+            sorted[index.clamp(1, sorted.len()) - 1]
+        };
+
+        LatencySummary {
+            samples: sorted.len(),
+            p50: rank(50.0),
+            p90: rank(90.0),
+            p99: rank(99.0),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+#[derive(Default)]
+struct ResolutionLogInner {
+    recent: VecDeque<ResolutionRecord>,
+    latencies: HashMap<String, VecDeque<f64>>,
+}
+
+/// Bounded history of resolutions
+pub struct ResolutionLog {
+    capacity: usize,
+    inner: Mutex<ResolutionLogInner>,
+}
+
+impl ResolutionLog {
+    /// Keeps the `capacity` most recent resolutions
+    pub fn new(capacity: usize) -> Self {
+        ResolutionLog {
+            capacity: capacity.max(1),
+            inner: Mutex::new(ResolutionLogInner::default()),
+        }
+    }
+
+    pub async fn record(&self, record: ResolutionRecord) {
+        let mut inner = self.inner.lock().await;
+
+        // Failed resolutions are left out of the latency figures: a timeout
+        // would otherwise dominate the tail of an otherwise healthy method.
+        if record.error.is_none() {
+            let samples = inner.latencies.entry(record.method.clone()).or_default();
+            if samples.len() == LATENCY_SAMPLES_PER_METHOD {
+                samples.pop_front();
+            }
+            samples.push_back(record.latency_ms);
+        }
+
+        if inner.recent.len() == self.capacity {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(record);
+    }
+
+    /// Up to `limit` resolutions, most recent first
+    pub async fn recent(&self, limit: usize) -> Vec<ResolutionRecord> {
+        self.inner
+            .lock()
+            .await
+            .recent
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Latency percentiles of successful resolutions, per DID method
+    pub async fn latency_by_method(&self) -> BTreeMap<String, LatencySummary> {
+        self.inner
+            .lock()
+            .await
+            .latencies
+            .iter()
+            .map(|(method, samples)| {
+                let samples: Vec<f64> = samples.iter().copied().collect();
+                (method.clone(), LatencySummary::from_samples(&samples))
+            })
+            .collect()
+    }
+}
+
+/// The method segment of a DID (`web` for `did:web:example.com`), or
+/// `unknown` if `did` isn't a DID
+pub(crate) fn did_method(did: &str) -> &str {
+    match did.split(':').collect::<Vec<&str>>().as_slice() {
+        ["did", method, _, ..] if !method.is_empty() => method,
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(did: &str, latency_ms: u64, error: Option<&str>) -> ResolutionRecord {
+        ResolutionRecord::new(
+            did,
+            ResolutionSource::Http,
+            Duration::from_millis(latency_ms),
+            false,
+            error.map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn method_is_taken_from_the_did() {
+        assert_eq!(did_method("did:web:example.com"), "web");
+        assert_eq!(did_method("did:peer:2.Ez6LS"), "peer");
+        assert_eq!(did_method("not-a-did"), "unknown");
+        assert_eq!(did_method("did::x"), "unknown");
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+        let summary = LatencySummary::from_samples(&samples);
+        assert_eq!(summary.samples, 100);
+        assert_eq!(summary.p50, 50.0);
+        assert_eq!(summary.p90, 90.0);
+        assert_eq!(summary.p99, 99.0);
+        assert_eq!(summary.max, 100.0);
+
+        assert_eq!(LatencySummary::from_samples(&[]), LatencySummary::default());
+    }
+
+    #[tokio::test]
+    async fn history_is_bounded_and_newest_first() {
+        let log = ResolutionLog::new(2);
+        log.record(record("did:key:z1", 1, None)).await;
+        log.record(record("did:key:z2", 1, None)).await;
+        log.record(record("did:key:z3", 1, None)).await;
+
+        let recent = log.recent(10).await;
+        let dids: Vec<&str> = recent.iter().map(|r| r.did.as_str()).collect();
+        assert_eq!(dids, ["did:key:z3", "did:key:z2"]);
+        assert_eq!(log.recent(1).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_are_kept_out_of_latency() {
+        let log = ResolutionLog::new(10);
+        log.record(record("did:web:a.example", 10, None)).await;
+        log.record(record("did:web:b.example", 30_000, Some("timed out")))
+            .await;
+
+        let latency = log.latency_by_method().await;
+        assert_eq!(latency["web"].samples, 1);
+        assert_eq!(latency["web"].max, 10.0);
+        assert_eq!(log.recent(10).await.len(), 2);
+    }
+}
@@ -2,10 +2,13 @@ use crate::{
     SharedData,
     config::init,
     handlers::{application_routes, health_checker_handler},
+    reload::{ConfigReloader, LiveConfig},
+    resolutions::ResolutionLog,
     statistics::{Statistics, statistics},
+    webvh_logs::{WebVHLogResolver, WebVHLogs},
 };
 use affinidi_did_resolver_cache_sdk::{
-    DIDCacheClient, config::DIDCacheConfigBuilder, errors::DIDCacheError,
+    DIDCacheClient, MethodName, config::DIDCacheConfigBuilder, errors::DIDCacheError,
 };
 use affinidi_rate_limit::{RateLimitLayer, RateLimiterState};
 use affinidi_task_utils::TaskSupervisor;
@@ -80,7 +83,7 @@ pub async fn start_with_config(config_path: &str) -> Result<(), DIDCacheError> {
 
     event!(Level::INFO, "[Loading Affinidi DID Cache configuration]");
 
-    let config = init(config_path, Some(reload_handle))
+    let config = init(config_path, Some(reload_handle.clone()))
         .map_err(|e| DIDCacheError::ConfigError(format!("Couldn't initialize DID Cache: {e}")))?;
 
     // Use the affinidi-did-resolver-cache-sdk in local mode
@@ -89,7 +92,7 @@ pub async fn start_with_config(config_path: &str) -> Result<(), DIDCacheError> {
         .with_cache_ttl(config.cache_expire)
         .build();
 
-    let resolver = DIDCacheClient::new(cache_config).await?;
+    let mut resolver = DIDCacheClient::new(cache_config).await?;
 
     // One shared HTTP client for did:webvh log fetches, built once so
     // connections are pooled (was previously rebuilt per request). Refuses
@@ -101,6 +104,14 @@ pub async fn start_with_config(config_path: &str) -> Result<(), DIDCacheError> {
         .map_err(|e| {
             DIDCacheError::ConfigError(format!("Failed to build WebVH HTTP client: {e}"))
         })?;
+    let webvh_logs = WebVHLogs::new(webvh_client);
+
+    // Resolve did:webvh from resumed logs, so re-resolving a long-lived DID
+    // downloads only its new entries. The SDK resolver stays behind it.
+    resolver.prepend_resolver(
+        MethodName::Webvh,
+        Box::new(WebVHLogResolver::new(webvh_logs.clone())),
+    )?;
 
     // Agent name resolution is off unless explicitly enabled: it makes this
     // server issue HTTP requests to caller-supplied hosts. The resolver keeps
@@ -117,11 +128,13 @@ pub async fn start_with_config(config_path: &str) -> Result<(), DIDCacheError> {
         service_start_timestamp: chrono::Utc::now(),
         stats: Arc::new(Mutex::new(Statistics::default())),
         resolver,
-        resolve_timeout: config.resolve_timeout,
-        max_did_size: config.max_did_size,
-        webvh_client,
+        config: LiveConfig::new(config.clone()),
+        webvh_logs,
         agent_name_resolver,
         agent_name_permits: Arc::new(Semaphore::new(config.agent_name_concurrency)),
+        resolutions: config
+            .enable_debug_dashboard
+            .then(|| Arc::new(ResolutionLog::new(config.debug_history_size))),
     };
 
     // Supervise the statistics task through the shared TaskSupervisor: a
@@ -165,6 +178,27 @@ pub async fn start_with_config(config_path: &str) -> Result<(), DIDCacheError> {
         );
     }
 
+    // Reload the configuration on SIGHUP, and on file change when
+    // `config_reload_interval` is set. Reloads apply in place, so open
+    // connections carry on under the new settings. Non-load-bearing like the
+    // statistics task: if it fails the server keeps its current configuration.
+    {
+        let reloader = ConfigReloader::new(
+            config_path,
+            shared_state.config.clone(),
+            shared_state.resolver.clone(),
+            rate_limiter.clone(),
+        )
+        .with_log_level_handle(reload_handle);
+        let poll_interval = config.config_reload_interval;
+        let reload_shutdown = shutdown.clone();
+        TaskSupervisor::new(shutdown.clone()).spawn("config_reload", false, move || {
+            let reloader = reloader.clone();
+            let reload_shutdown = reload_shutdown.clone();
+            async move { reloader.run(poll_interval, reload_shutdown).await }
+        });
+    }
+
     let app: Router = application_routes(&shared_state, &config);
 
     // Add middleware to all routes
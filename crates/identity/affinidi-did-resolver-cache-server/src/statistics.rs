@@ -149,7 +149,7 @@ impl Statistics {
 pub async fn statistics(
     interval: Duration,
     stats: &Arc<Mutex<Statistics>>,
-    cache: Cache<[u64; 2], Document>,
+    cache: Cache<[u64; 2], Arc<Document>>,
     shutdown: CancellationToken,
 ) -> Result<(), CacheError> {
     let _span = span!(Level::INFO, "statistics");
@@ -191,7 +191,7 @@ mod tests {
     #[tokio::test]
     async fn statistics_exits_promptly_on_cancel() {
         let stats = Arc::new(Mutex::new(Statistics::default()));
-        let cache: Cache<[u64; 2], Document> = Cache::new(10);
+        let cache: Cache<[u64; 2], Arc<Document>> = Cache::new(10);
         let token = CancellationToken::new();
         // Pre-cancel: with a 1h interval the loop can only leave via the
         // cancellation branch, so the call must return promptly (and Ok).
@@ -221,7 +221,7 @@ mod tests {
         let attempts = Arc::new(AtomicU32::new(0));
 
         let stats = Arc::new(Mutex::new(Statistics::default()));
-        let cache: Cache<[u64; 2], Document> = Cache::new(10);
+        let cache: Cache<[u64; 2], Arc<Document>> = Cache::new(10);
         {
             let attempts = attempts.clone();
             let stats = stats.clone();
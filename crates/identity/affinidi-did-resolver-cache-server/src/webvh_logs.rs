@@ -0,0 +1,581 @@
+//! Resumable fetches of `did:webvh` logs (`did.jsonl`).
+//!
+//! A `did:webvh` log only grows: every update appends an entry. Fetching the
+//! whole file each time a long-lived DID is resolved costs bandwidth in
+//! proportion to its history rather than to what changed. [`WebVHLogs`] keeps
+//! the last log fetched from each URL together with a checkpoint: the byte
+//! offset of its last entry and that entry's `versionId`, which embeds the
+//! entry hash. The next fetch asks only for the bytes from that offset on,
+//! with an HTTP range request:
+//!
+//! - `206 Partial Content` starting with the checkpointed entry unchanged: the
+//!   new entries are appended to the kept log
+//! - `206` starting with anything else, or `416 Range Not Satisfiable` (the
+//!   file shrank): the log was rewritten, and is fetched in full
+//! - `200 OK`: the host ignores `Range`, and the body is the full log
+//!
+//! A log is checkpointed only when every entry carries a `versionId` whose
+//! version number follows on from the previous entry's, so a truncated or
+//! malformed body is never resumed from. Clients still verify the log
+//! themselves; these checks only guard the resume.
+//!
+//! Both the server's own did:webvh resolution and the log attached to
+//! resolution responses go through here: [`WebVHLogResolver`] sits in front of
+//! the `DIDCacheClient`'s did:webvh resolver and verifies the resumed log
+//! offline.
+
+use affinidi_did_common::{DID, DIDMethod, Document};
+use affinidi_did_resolver_cache_sdk::{AsyncResolver, Resolution};
+use didwebvh_rs::{DIDWebVHState, log_entry::LogEntryMethods};
+use http::{HeaderMap, StatusCode, header};
+use moka::future::Cache;
+use std::{future::Future, pin::Pin, sync::Arc};
+use tracing::{debug, warn};
+
+use crate::handlers::fetch_webvh_log;
+
+/// Largest `did.jsonl` or `did-witness.json` accepted from a host.
+pub const MAX_WEBVH_LOG_BYTES: usize = 1024 * 1024;
+
+/// Default ceiling on the log text kept for resuming, across all logs.
+pub const DEFAULT_WEBVH_LOG_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A fetched log and where to resume it from.
+struct Checkpoint {
+    log: String,
+    /// Byte offset of the last entry in `log`
+    offset: usize,
+    /// `versionId` of the last entry
+    version_id: String,
+}
+
+/// Outcome of a range request against a checkpoint.
+enum Ranged {
+    /// The request settled the fetch, successfully or not.
+    Done(Option<String>),
+    /// The log has to be fetched in full.
+    Refetch,
+}
+
+/// Resolves `did:webvh` DIDs from logs fetched by [`WebVHLogs`], so resolving
+/// a DID again downloads only the entries added since.
+///
+/// The log is verified with `didwebvh-rs` exactly as a downloaded one would
+/// be. The resolver declines, leaving the DID to the next resolver in the
+/// chain, when the log can't be fetched or doesn't verify, and when it
+/// belongs to another DID: only the SDK resolver follows a moved DID's
+/// redirect chain.
+pub struct WebVHLogResolver {
+    logs: WebVHLogs,
+}
+
+impl WebVHLogResolver {
+    pub fn new(logs: WebVHLogs) -> Self {
+        Self { logs }
+    }
+}
+
+impl AsyncResolver for WebVHLogResolver {
+    fn name(&self) -> &str {
+        "WebVHLogResolver"
+    }
+
+    fn resolve<'a>(
+        &'a self,
+        did: &'a DID,
+    ) -> Pin<Box<dyn Future<Output = Resolution> + Send + 'a>> {
+        Box::pin(async move {
+            if !matches!(did.method(), DIDMethod::Webvh { .. }) {
+                return None;
+            }
+            let did = did.to_string();
+            let (Some(log), witness) = fetch_webvh_log(&self.logs, &did).await else {
+                return None;
+            };
+
+            let mut state = DIDWebVHState::default();
+            let document = match state.resolve_log(&did, &log, witness.as_deref()).await {
+                Ok((entry, _)) => entry
+                    .get_did_document()
+                    .ok()
+                    .and_then(|value| serde_json::from_value::<Document>(value).ok()),
+                Err(e) => {
+                    debug!("WebVH log for {did} didn't verify; deferring: {e}");
+                    None
+                }
+            }?;
+            (document.id.as_str() == did).then_some(Ok(document))
+        })
+    }
+}
+
+/// Fetches `did:webvh` logs, resuming from the last fetch of each log where
+/// the host supports range requests.
+///
+/// Cloning is cheap; clones share the same checkpoints.
+#[derive(Clone)]
+pub struct WebVHLogs {
+    client: reqwest::Client,
+    checkpoints: Cache<String, Arc<Checkpoint>>,
+}
+
+impl WebVHLogs {
+    /// Fetch logs with `client`, keeping up to
+    /// [`DEFAULT_WEBVH_LOG_CACHE_BYTES`] of log text to resume from.
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            checkpoints: checkpoint_cache(DEFAULT_WEBVH_LOG_CACHE_BYTES),
+        }
+    }
+
+    /// Keep up to `bytes` of log text to resume from. `0` turns resuming off.
+    pub fn with_cache_bytes(mut self, bytes: u64) -> Self {
+        self.checkpoints = checkpoint_cache(bytes);
+        self
+    }
+
+    /// The HTTP client logs are fetched with.
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Fetch the log at `url`, or `None` if the host doesn't serve one within
+    /// [`MAX_WEBVH_LOG_BYTES`].
+    pub async fn fetch(&self, url: &str) -> Option<String> {
+        if let Some(checkpoint) = self.checkpoints.get(url).await {
+            match self.fetch_range(url, &checkpoint).await {
+                Ranged::Done(log) => return log,
+                Ranged::Refetch => {}
+            }
+        }
+
+        let log = match self.client.get(url).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                read_text_limited(resp, MAX_WEBVH_LOG_BYTES).await
+            }
+            Ok(resp) => {
+                warn!("WebVH log fetch returned HTTP {}: {url}", resp.status());
+                None
+            }
+            Err(e) => {
+                warn!("Failed to fetch WebVH log ({url}): {e}");
+                None
+            }
+        };
+        match log {
+            Some(log) => Some(self.keep_full(url, log).await),
+            None => {
+                self.checkpoints.invalidate(url).await;
+                None
+            }
+        }
+    }
+
+    /// Ask for the bytes of `url` from the checkpointed entry on.
+    async fn fetch_range(&self, url: &str, checkpoint: &Checkpoint) -> Ranged {
+        let resp = match self
+            .client
+            .get(url)
+            .header(header::RANGE, format!("bytes={}-", checkpoint.offset))
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(e) => {
+                warn!("Failed to fetch WebVH log ({url}): {e}");
+                return Ranged::Done(None);
+            }
+        };
+
+        match resp.status() {
+            StatusCode::PARTIAL_CONTENT => {
+                if !range_starts_at(resp.headers(), checkpoint.offset) {
+                    debug!("WebVH log ({url}) returned an unexpected range; fetching it in full");
+                    return Ranged::Refetch;
+                }
+                let limit = MAX_WEBVH_LOG_BYTES.saturating_sub(checkpoint.offset);
+                let Some(body) = read_text_limited(resp, limit).await else {
+                    self.checkpoints.invalidate(url).await;
+                    return Ranged::Done(None);
+                };
+                match resume(checkpoint, &body) {
+                    Some(next) => {
+                        let log = next.log.clone();
+                        self.checkpoints
+                            .insert(url.to_string(), Arc::new(next))
+                            .await;
+                        Ranged::Done(Some(log))
+                    }
+                    None => {
+                        debug!("WebVH log ({url}) was rewritten; fetching it in full");
+                        Ranged::Refetch
+                    }
+                }
+            }
+            StatusCode::OK => {
+                let log = match read_text_limited(resp, MAX_WEBVH_LOG_BYTES).await {
+                    Some(log) => Some(self.keep_full(url, log).await),
+                    None => {
+                        self.checkpoints.invalidate(url).await;
+                        None
+                    }
+                };
+                Ranged::Done(log)
+            }
+            _ => Ranged::Refetch,
+        }
+    }
+
+    /// Checkpoint a log fetched in full, if it is well formed, and return it.
+    async fn keep_full(&self, url: &str, log: String) -> String {
+        match last_entry(&log, 0, 0) {
+            Some((offset, version_id)) => {
+                let checkpoint = Checkpoint {
+                    log: log.clone(),
+                    offset,
+                    version_id,
+                };
+                self.checkpoints
+                    .insert(url.to_string(), Arc::new(checkpoint))
+                    .await;
+            }
+            None => self.checkpoints.invalidate(url).await,
+        }
+        log
+    }
+}
+
+fn checkpoint_cache(bytes: u64) -> Cache<String, Arc<Checkpoint>> {
+    Cache::builder()
+        .weigher(|url: &String, checkpoint: &Arc<Checkpoint>| {
+            u32::try_from(url.len() + checkpoint.log.len()).unwrap_or(u32::MAX)
+        })
+        .max_capacity(bytes)
+        .build()
+}
+
+/// Read a response body as UTF-8 text, refusing anything larger than `limit` bytes.
+pub(crate) async fn read_text_limited(mut resp: reqwest::Response, limit: usize) -> Option<String> {
+    let mut buf = Vec::new();
+    loop {
+        match resp.chunk().await {
+            Ok(Some(chunk)) => {
+                if buf.len() + chunk.len() > limit {
+                    warn!("WebVH log body exceeded {limit} byte cap; dropping");
+                    return None;
+                }
+                buf.extend_from_slice(&chunk);
+            }
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to read WebVH log response body: {e}");
+                return None;
+            }
+        }
+    }
+    String::from_utf8(buf).ok()
+}
+
+/// Whether a `206` response's `Content-Range` starts at byte `offset`.
+fn range_starts_at(headers: &HeaderMap, offset: usize) -> bool {
+    headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("bytes "))
+        .and_then(|range| range.split_once('-'))
+        .and_then(|(start, _)| start.parse::<usize>().ok())
+        == Some(offset)
+}
+
+/// Extend `checkpoint` with `body`, the log from the checkpointed entry on.
+/// `None` if `body` doesn't start with that entry, or the entries after it
+/// don't follow on from it.
+fn resume(checkpoint: &Checkpoint, body: &str) -> Option<Checkpoint> {
+    let (first, rest) = body.split_once('\n').unwrap_or((body, ""));
+    if first.trim_end() != checkpoint.log[checkpoint.offset..].trim_end() {
+        return None;
+    }
+
+    let log = format!("{}{body}", &checkpoint.log[..checkpoint.offset]);
+    let (offset, version_id) = if rest.trim().is_empty() {
+        (checkpoint.offset, checkpoint.version_id.clone())
+    } else {
+        last_entry(
+            rest,
+            checkpoint.offset + first.len() + 1,
+            version_number(&checkpoint.version_id)?,
+        )?
+    };
+    Some(Checkpoint {
+        log,
+        offset,
+        version_id,
+    })
+}
+
+/// Byte offset (counted from `start`) and `versionId` of the last entry in
+/// `text`, provided every entry's version number follows on from the one
+/// before, starting after `previous`.
+fn last_entry(text: &str, start: usize, mut previous: u64) -> Option<(usize, String)> {
+    let mut last = None;
+    let mut offset = start;
+    for line in text.split_inclusive('\n') {
+        let entry = line.trim_end();
+        if !entry.is_empty() {
+            let version_id = serde_json::from_str::<serde_json::Value>(entry)
+                .ok()?
+                .get("versionId")?
+                .as_str()?
+                .to_string();
+            let number = version_number(&version_id)?;
+            if number != previous + 1 {
+                return None;
+            }
+            previous = number;
+            last = Some((offset, version_id));
+        }
+        offset += line.len();
+    }
+    last
+}
+
+/// The version number of a `versionId` (`<number>-<entry hash>`).
+fn version_number(version_id: &str) -> Option<u64> {
+    version_id.split_once('-')?.0.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Router,
+        extract::State,
+        response::{IntoResponse, Response},
+        routing::get,
+    };
+    use std::sync::Mutex;
+
+    fn entry(number: u64) -> String {
+        format!("{{\"versionId\":\"{number}-Qm{number}\",\"state\":{{}}}}\n")
+    }
+
+    fn log(entries: u64) -> String {
+        (1..=entries).map(entry).collect()
+    }
+
+    fn checkpoint(log: &str) -> Checkpoint {
+        let (offset, version_id) = last_entry(log, 0, 0).unwrap();
+        Checkpoint {
+            log: log.to_string(),
+            offset,
+            version_id,
+        }
+    }
+
+    #[test]
+    fn checkpoints_the_last_entry() {
+        let checkpoint = checkpoint(&log(3));
+        assert_eq!(checkpoint.version_id, "3-Qm3");
+        assert_eq!(&checkpoint.log[checkpoint.offset..], entry(3));
+    }
+
+    #[test]
+    fn malformed_logs_are_not_checkpointed() {
+        assert!(last_entry("", 0, 0).is_none());
+        assert!(last_entry("not json\n", 0, 0).is_none());
+        // Doesn't start at version 1
+        assert!(last_entry(&entry(2), 0, 0).is_none());
+        // Skips a version
+        assert!(last_entry(&format!("{}{}", entry(1), entry(3)), 0, 0).is_none());
+    }
+
+    #[test]
+    fn resume_appends_new_entries() {
+        let checkpoint = checkpoint(&log(2));
+        let body = format!("{}{}{}", entry(2), entry(3), entry(4));
+
+        let next = resume(&checkpoint, &body).unwrap();
+        assert_eq!(next.log, log(4));
+        assert_eq!(next.version_id, "4-Qm4");
+        assert_eq!(&next.log[next.offset..], entry(4));
+
+        // Nothing new
+        let same = resume(&checkpoint, &entry(2)).unwrap();
+        assert_eq!(same.log, log(2));
+        assert_eq!(same.offset, checkpoint.offset);
+    }
+
+    #[test]
+    fn resume_detects_a_rewritten_log() {
+        let checkpoint = checkpoint(&log(2));
+        let rewritten = "{\"versionId\":\"2-QmOther\",\"state\":{}}\n";
+        assert!(resume(&checkpoint, rewritten).is_none());
+        // New entries that don't follow on from the checkpoint
+        assert!(resume(&checkpoint, &format!("{}{}", entry(2), entry(4))).is_none());
+    }
+
+    #[derive(Clone, Default)]
+    struct Host {
+        log: Arc<Mutex<String>>,
+        /// Bytes of log served, across all requests
+        served: Arc<Mutex<usize>>,
+    }
+
+    /// Serves `did.jsonl`, honouring `Range: bytes=<start>-`.
+    async fn serve_log(State(host): State<Host>, headers: HeaderMap) -> Response {
+        let log = host.log.lock().unwrap().clone();
+        let start = headers
+            .get(header::RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("bytes="))
+            .and_then(|value| value.strip_suffix('-'))
+            .and_then(|value| value.parse::<usize>().ok());
+
+        match start {
+            Some(start) if start >= log.len() => (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", log.len()))],
+            )
+                .into_response(),
+            Some(start) => {
+                *host.served.lock().unwrap() += log.len() - start;
+                (
+                    StatusCode::PARTIAL_CONTENT,
+                    [(
+                        header::CONTENT_RANGE,
+                        format!("bytes {start}-{}/{}", log.len() - 1, log.len()),
+                    )],
+                    log[start..].to_string(),
+                )
+                    .into_response()
+            }
+            None => {
+                *host.served.lock().unwrap() += log.len();
+                log.into_response()
+            }
+        }
+    }
+
+    async fn start_host(host: Host) -> String {
+        let app = Router::new()
+            .route("/did.jsonl", get(serve_log))
+            .route("/.well-known/did.jsonl", get(serve_log))
+            .with_state(host);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}/did.jsonl")
+    }
+
+    #[tokio::test]
+    async fn refetches_only_new_entries() {
+        let host = Host::default();
+        *host.log.lock().unwrap() = log(50);
+        let url = start_host(host.clone()).await;
+        let logs = WebVHLogs::new(reqwest::Client::new());
+
+        assert_eq!(logs.fetch(&url).await.unwrap(), log(50));
+        assert_eq!(*host.served.lock().unwrap(), log(50).len());
+
+        // One entry appended: only the last two entries come over the wire.
+        *host.log.lock().unwrap() = log(51);
+        *host.served.lock().unwrap() = 0;
+        assert_eq!(logs.fetch(&url).await.unwrap(), log(51));
+        assert_eq!(
+            *host.served.lock().unwrap(),
+            entry(50).len() + entry(51).len()
+        );
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_a_full_fetch_when_rewritten() {
+        let host = Host::default();
+        *host.log.lock().unwrap() = log(5);
+        let url = start_host(host.clone()).await;
+        let logs = WebVHLogs::new(reqwest::Client::new());
+        logs.fetch(&url).await.unwrap();
+
+        // Same length, different last entry
+        let rewritten = format!("{}{{\"versionId\":\"5-QmXX\",\"state\":{{}}}}\n", log(4));
+        *host.log.lock().unwrap() = rewritten.clone();
+        assert_eq!(logs.fetch(&url).await.unwrap(), rewritten);
+
+        // Shorter than the checkpoint: the range is unsatisfiable
+        *host.log.lock().unwrap() = log(1);
+        assert_eq!(logs.fetch(&url).await.unwrap(), log(1));
+    }
+
+    /// A `did:webvh` DID hosted at `localhost:port` and its log of `entries`
+    /// entries.
+    async fn webvh_log(port: u16, entries: u32) -> (String, String) {
+        use affinidi_secrets_resolver::secrets::Secret;
+        use didwebvh_rs::{
+            create::{CreateDIDConfig, create_did},
+            parameters::Parameters,
+        };
+
+        let mut update_key = Secret::generate_ed25519(None, Some(&[7; 32]));
+        let update_public = update_key.get_public_keymultibase().unwrap();
+        update_key.id = format!("did:key:{update_public}#{update_public}");
+        let parameters = Parameters {
+            update_keys: Some(Arc::new(vec![update_public.into()])),
+            ..Default::default()
+        };
+        let start = chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap();
+
+        let config = CreateDIDConfig::builder()
+            .address(format!("http://localhost:{port}/"))
+            .authorization_key(update_key.clone())
+            .did_document(serde_json::json!({
+                "@context": ["https://www.w3.org/ns/did/v1"],
+                "id": "{DID}",
+            }))
+            .parameters(parameters.clone())
+            .version_time(start)
+            .build()
+            .unwrap();
+        let created = create_did(config).await.unwrap();
+        let did = created.did().to_string();
+
+        let mut state = DIDWebVHState::default();
+        let genesis = serde_json::to_string(created.log_entry()).unwrap();
+        state.resolve_log(&did, &genesis, None).await.unwrap();
+        for minute in 1..entries {
+            let document = state.log_entries().last().unwrap().get_state().clone();
+            let version_time = start + chrono::Duration::minutes(minute.into());
+            state
+                .create_log_entry(Some(version_time), &document, &parameters, &update_key)
+                .await
+                .unwrap();
+        }
+
+        let log = state
+            .log_entries()
+            .iter()
+            .map(|entry| serde_json::to_string(&entry.log_entry).unwrap() + "\n")
+            .collect();
+        (did, log)
+    }
+
+    #[tokio::test]
+    async fn resolution_refetches_only_new_entries() {
+        let host = Host::default();
+        let url = start_host(host.clone()).await;
+        let port = reqwest::Url::parse(&url).unwrap().port().unwrap();
+        let (did, log) = webvh_log(port, 20).await;
+        *host.log.lock().unwrap() = log.clone();
+
+        let resolver = WebVHLogResolver::new(WebVHLogs::new(reqwest::Client::new()));
+        let parsed: DID = did.parse().unwrap();
+        let document = resolver.resolve(&parsed).await.unwrap().unwrap();
+        assert_eq!(document.id.as_str(), did);
+        assert_eq!(*host.served.lock().unwrap(), log.len());
+
+        // Resolving again fetches the last entry only.
+        *host.served.lock().unwrap() = 0;
+        resolver.resolve(&parsed).await.unwrap().unwrap();
+        let last = log.lines().last().unwrap().len() + 1;
+        assert_eq!(*host.served.lock().unwrap(), last);
+    }
+}
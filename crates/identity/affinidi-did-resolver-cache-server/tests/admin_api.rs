@@ -0,0 +1,84 @@
+//! `GET /did/v1/admin/config` — the admin API.
+//!
+//! Exercises the router directly rather than binding a port.
+
+use affinidi_did_resolver_cache_sdk::{DIDCacheClient, config::DIDCacheConfigBuilder};
+use affinidi_did_resolver_cache_server::{
+    SharedData, config::Config, handlers::application_routes, reload::LiveConfig,
+    statistics::Statistics, webvh_logs::WebVHLogs,
+};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{Mutex, Semaphore};
+use tower::ServiceExt;
+
+/// A router with the admin API either on or off.
+async fn app(enable_admin_api: bool) -> axum::Router {
+    let resolver = DIDCacheClient::new(DIDCacheConfigBuilder::default().build())
+        .await
+        .unwrap();
+
+    let config = Config {
+        enable_admin_api,
+        resolve_timeout: Duration::from_secs(5),
+        ..Default::default()
+    };
+
+    let state = SharedData {
+        service_start_timestamp: chrono::Utc::now(),
+        stats: Arc::new(Mutex::new(Statistics::default())),
+        resolver,
+        config: LiveConfig::new(config.clone()),
+        webvh_logs: WebVHLogs::new(reqwest::Client::new()),
+        agent_name_resolver: None,
+        agent_name_permits: Arc::new(Semaphore::new(16)),
+        resolutions: None,
+    };
+
+    application_routes(&state, &config)
+}
+
+async fn get(app: &axum::Router, uri: &str) -> (StatusCode, String) {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// The admin API describes the deployment, so it must not exist unless
+/// explicitly switched on.
+#[tokio::test]
+async fn route_is_absent_when_disabled() {
+    let (status, _) = get(&app(false).await, "/did/v1/admin/config").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn reports_effective_config_and_reload_status() {
+    let (status, body) = get(&app(true).await, "/did/v1/admin/config").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let body: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(body["config"]["resolve_timeout"], 5);
+    assert_eq!(body["config"]["log_level"], "info");
+    assert_eq!(body["config"]["config_reload_interval"], 0);
+    assert!(
+        body["reloadable"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("rate_limit_per_ip"))
+    );
+    assert_eq!(body["reload"]["generation"], 1);
+    assert_eq!(body["reload"]["last_error"], Value::Null);
+    assert_eq!(body["reload"]["pending_restart"], Value::Array(vec![]));
+}
@@ -5,7 +5,8 @@
 
 use affinidi_did_resolver_cache_sdk::{DIDCacheClient, config::DIDCacheConfigBuilder};
 use affinidi_did_resolver_cache_server::{
-    SharedData, config::Config, handlers::application_routes, statistics::Statistics,
+    SharedData, config::Config, handlers::application_routes, reload::LiveConfig,
+    statistics::Statistics, webvh_logs::WebVHLogs,
 };
 use axum::{
     body::Body,
@@ -36,15 +37,18 @@ async fn app_with_permits(enable_agent_names: bool, permits: usize) -> axum::Rou
         service_start_timestamp: chrono::Utc::now(),
         stats: Arc::new(Mutex::new(Statistics::default())),
         resolver,
-        resolve_timeout: Duration::from_secs(5),
-        max_did_size: 1024,
-        webvh_client: reqwest::Client::new(),
+        config: LiveConfig::new(Config {
+            resolve_timeout: Duration::from_secs(5),
+            ..Default::default()
+        }),
+        webvh_logs: WebVHLogs::new(reqwest::Client::new()),
         agent_name_resolver: if enable_agent_names {
             Some(Arc::new(agent_names::HttpRedirectResolver::new()))
         } else {
             None
         },
         agent_name_permits: Arc::new(Semaphore::new(permits)),
+        resolutions: None,
     };
 
     application_routes(&state, &config)
@@ -150,11 +154,14 @@ async fn sheds_lookups_once_the_fetch_ceiling_is_reached() {
         service_start_timestamp: chrono::Utc::now(),
         stats: Arc::new(Mutex::new(Statistics::default())),
         resolver,
-        resolve_timeout: Duration::from_secs(5),
-        max_did_size: 1024,
-        webvh_client: reqwest::Client::new(),
+        config: LiveConfig::new(Config {
+            resolve_timeout: Duration::from_secs(5),
+            ..Default::default()
+        }),
+        webvh_logs: WebVHLogs::new(reqwest::Client::new()),
         agent_name_resolver: Some(Arc::new(agent_names::HttpRedirectResolver::new())),
         agent_name_permits: permits.clone(),
+        resolutions: None,
     };
     let config = Config {
         enable_agent_names: true,
@@ -0,0 +1,138 @@
+//! `GET /did/v1/debug` — the debug dashboard and its JSON endpoints.
+//!
+//! Exercises the router directly rather than binding a port. did:key resolves
+//! locally, so no network access is needed.
+
+use affinidi_did_resolver_cache_sdk::{DIDCacheClient, config::DIDCacheConfigBuilder};
+use affinidi_did_resolver_cache_server::{
+    SharedData, config::Config, handlers::application_routes, reload::LiveConfig,
+    resolutions::ResolutionLog, statistics::Statistics, webvh_logs::WebVHLogs,
+};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use serde_json::Value;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{Mutex, Semaphore};
+use tower::ServiceExt;
+
+const DID_KEY: &str = "did:key:z6MkiToqovww7vYtxm1xNM15u9JzqzUFZ1k7s7MazYJUyAxv";
+
+/// A router with the debug dashboard either on or off.
+async fn app(enable_debug_dashboard: bool) -> axum::Router {
+    let resolver = DIDCacheClient::new(DIDCacheConfigBuilder::default().build())
+        .await
+        .unwrap();
+
+    let config = Config {
+        enable_debug_dashboard,
+        ..Default::default()
+    };
+
+    let state = SharedData {
+        service_start_timestamp: chrono::Utc::now(),
+        stats: Arc::new(Mutex::new(Statistics::default())),
+        resolver,
+        config: LiveConfig::new(Config {
+            resolve_timeout: Duration::from_secs(5),
+            ..Default::default()
+        }),
+        webvh_logs: WebVHLogs::new(reqwest::Client::new()),
+        agent_name_resolver: None,
+        agent_name_permits: Arc::new(Semaphore::new(16)),
+        resolutions: enable_debug_dashboard.then(|| Arc::new(ResolutionLog::new(10))),
+    };
+
+    application_routes(&state, &config)
+}
+
+async fn get(app: &axum::Router, uri: &str) -> (StatusCode, String) {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8_lossy(&bytes).to_string())
+}
+
+async fn get_json(app: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let (status, body) = get(app, uri).await;
+    (status, serde_json::from_str(&body).unwrap())
+}
+
+/// The dashboard lists the DIDs other clients resolved, so it must not exist
+/// unless explicitly switched on.
+#[tokio::test]
+async fn routes_are_absent_when_disabled() {
+    let app = app(false).await;
+    for uri in [
+        "/did/v1/debug",
+        "/did/v1/debug/resolutions",
+        "/did/v1/debug/cache",
+        "/did/v1/debug/latency",
+    ] {
+        let (status, _) = get(&app, uri).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn page_is_served_when_enabled() {
+    let (status, body) = get(&app(true).await, "/did/v1/debug").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("Debug Dashboard"));
+}
+
+#[tokio::test]
+async fn test_resolution_is_recorded() {
+    let app = app(true).await;
+
+    let (status, body) = get_json(&app, &format!("/did/v1/debug/resolve/{DID_KEY}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["did"], DID_KEY);
+    assert_eq!(body["cache_hit"], false);
+    assert_eq!(body["document"]["id"], DID_KEY);
+
+    // Resolved again, the document comes from the cache.
+    let (_, body) = get_json(&app, &format!("/did/v1/debug/resolve/{DID_KEY}")).await;
+    assert_eq!(body["cache_hit"], true);
+
+    let (status, body) = get_json(&app, "/did/v1/debug/resolutions").await;
+    assert_eq!(status, StatusCode::OK);
+    let resolutions = body["resolutions"].as_array().unwrap();
+    assert_eq!(resolutions.len(), 2);
+    assert_eq!(resolutions[0]["source"], "dashboard");
+    assert_eq!(resolutions[0]["method"], "key");
+    assert_eq!(resolutions[0]["cache_hit"], true);
+
+    let (_, body) = get_json(&app, "/did/v1/debug/latency").await;
+    assert_eq!(body["methods"]["key"]["samples"], 2);
+
+    let (_, body) = get_json(&app, "/did/v1/debug/cache").await;
+    assert_eq!(body["entries"], 1);
+    assert_eq!(body["by_method"]["key"], 1);
+}
+
+#[tokio::test]
+async fn failed_resolution_is_recorded_with_its_error() {
+    let app = app(true).await;
+
+    let (status, body) = get_json(&app, "/did/v1/debug/resolve/did:unknown:abc").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body["error"].is_string());
+
+    let (_, body) = get_json(&app, "/did/v1/debug/resolutions?limit=1").await;
+    let resolutions = body["resolutions"].as_array().unwrap();
+    assert_eq!(resolutions.len(), 1);
+    assert_eq!(resolutions[0]["method"], "unknown");
+    assert!(resolutions[0]["error"].is_string());
+
+    // Failures stay out of the latency figures.
+    let (_, body) = get_json(&app, "/did/v1/debug/latency").await;
+    assert!(body["methods"].as_object().unwrap().is_empty());
+}
@@ -10,7 +10,7 @@ use affinidi_did_common::{
 use affinidi_did_resolver_cache_sdk::{DIDCacheClient, config::DIDCacheConfigBuilder};
 use affinidi_did_resolver_cache_server::server::start_with_config;
 use affinidi_secrets_resolver::secrets::Secret;
-use std::{net::TcpListener as StdTcpListener, path::PathBuf};
+use std::{net::TcpListener as StdTcpListener, path::PathBuf, sync::Arc};
 use tokio::time::{Duration, sleep};
 
 const DID_ETHR: &str = "did:ethr:0x1:0xb9c5714089478a327f09197987f16f9e5d936e8a";
@@ -34,10 +34,10 @@ async fn test_cache_server() {
     // Resolve DIDs and add to cache
     let client = DIDCacheClient::new(config).await.unwrap();
     let dids: Vec<&str> = vec![&did_peer, DID_ETHR, DID_KEY, DID_PKH];
-    let mut did_docs_vec: Vec<Document> = vec![];
+    let mut did_docs_vec: Vec<Arc<Document>> = vec![];
     for did in dids.clone() {
         let res = client.resolve(did).await.unwrap();
-        let doc = res.doc;
+        let doc = res.doc.clone();
         did_docs_vec.push(doc);
         assert!(!res.cache_hit)
     }
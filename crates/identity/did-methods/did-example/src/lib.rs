@@ -8,6 +8,7 @@
 
 use affinidi_did_common::Document;
 use ahash::AHashMap as HashMap;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,7 +20,7 @@ pub enum DidExampleError {
 
 #[derive(Clone, Default)]
 pub struct DiDExampleCache {
-    cache: HashMap<String, Document>,
+    cache: HashMap<String, Arc<Document>>,
 }
 
 impl DiDExampleCache {
@@ -42,11 +43,17 @@ impl DiDExampleCache {
     /// `document`: A string representation of a DID document
     pub fn insert_from_string(&mut self, document: &str) -> Result<(), DidExampleError> {
         let (id, doc) = DiDExampleCache::from_string(document.to_string())?;
-        self.cache.insert(id, doc);
+        self.cache.insert(id, Arc::new(doc));
         Ok(())
     }
 
     pub fn get(&self, id: &str) -> Option<&Document> {
-        self.cache.get(id)
+        self.cache.get(id).map(Arc::as_ref)
+    }
+
+    /// Like [`Self::get`], but hands out a shared reference to the cached
+    /// document instead of a borrow.
+    pub fn get_shared(&self, id: &str) -> Option<Arc<Document>> {
+        self.cache.get(id).cloned()
     }
 }
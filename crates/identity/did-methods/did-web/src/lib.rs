@@ -47,13 +47,16 @@
  * ```
  */
 
-use std::time::Duration;
+use std::{borrow::Cow, time::Duration};
 
 use affinidi_did_common::{DID, DIDMethod, Document};
 use percent_encoding::percent_decode_str;
 use thiserror::Error;
 use tracing::debug;
 
+#[cfg(feature = "publish")]
+pub mod publish;
+
 /// did:web resolver errors.
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -78,6 +81,19 @@ pub enum DidWebError {
     /// The response body was not a valid DID Document.
     #[error("did:web response was not a valid DID Document: {0}")]
     InvalidDocument(String),
+
+    /// Uploading or verifying a published DID Document failed.
+    #[error("did:web publication failed: {0}")]
+    Publish(String),
+
+    /// A fetched resource was larger than the caller's limit.
+    #[error("resource at {url} exceeds {limit} bytes")]
+    ResourceTooLarge {
+        /// URL we requested.
+        url: String,
+        /// Limit in bytes.
+        limit: usize,
+    },
 }
 
 /// Default request timeout. Aligns with the historic spruceid `did-web` default.
@@ -90,7 +106,7 @@ pub const DEFAULT_ACCEPT: &str = "application/did+ld+json, application/json";
 /// did:web resolver wrapping a reusable [`reqwest::Client`].
 #[derive(Debug, Clone)]
 pub struct DIDWeb {
-    client: reqwest::Client,
+    pub(crate) client: reqwest::Client,
 }
 
 impl DIDWeb {
@@ -168,6 +184,64 @@ impl DIDWeb {
     }
 }
 
+impl DIDWeb {
+    /// Fetch a DID-linked resource from `url`, reading at most `max_bytes`
+    /// of body. Returns the body and its `Content-Type`, if any.
+    ///
+    /// Uses the same client (and so the same no-redirect policy) as
+    /// [`Self::resolve`].
+    pub async fn fetch_resource(
+        &self,
+        url: &str,
+        max_bytes: usize,
+    ) -> Result<(Vec<u8>, Option<String>), DidWebError> {
+        let too_large = || DidWebError::ResourceTooLarge {
+            url: url.to_string(),
+            limit: max_bytes,
+        };
+
+        let mut response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| DidWebError::Http(format!("GET {url}: {e}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(DidWebError::ResolutionFailed {
+                status: status.as_u16(),
+                url: url.to_string(),
+            });
+        }
+        if response
+            .content_length()
+            .is_some_and(|length| length > max_bytes as u64)
+        {
+            return Err(too_large());
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        // Content-Length can be absent or wrong, so count as we read.
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| DidWebError::Http(format!("reading body from {url}: {e}")))?
+        {
+            if body.len() + chunk.len() > max_bytes {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok((body, content_type))
+    }
+}
+
 impl Default for DIDWeb {
     fn default() -> Self {
         Self::new()
@@ -187,6 +261,52 @@ pub async fn resolve(did: &str) -> Result<Document, DidWebError> {
 /// Pure function; exposed for callers that want to compute the URL without
 /// performing the HTTP request.
 pub fn build_url(domain: &str, path_segments: &[String]) -> Result<String, DidWebError> {
+    let (host, directory) = location(domain, path_segments)?;
+    Ok(format!("https://{host}/{directory}/did.json"))
+}
+
+/// Build the HTTPS URL that a DID URL path refers to, for `did:web` and
+/// `did:webvh` (whose DID URL path resolution maps
+/// `did:webvh:{scid}:example.com:dids/schemas/a.json` to
+/// `https://example.com/dids/schemas/a.json`).
+///
+/// `resource_path` is the DID URL path without its leading `/`; `query`, if
+/// any, is appended unchanged. Each path segment, once percent-decoded, must
+/// be a single path component: not empty, `.` or `..`, and without `/` or
+/// `\`.
+pub fn build_resource_url(
+    domain: &str,
+    path_segments: &[String],
+    resource_path: &str,
+    query: Option<&str>,
+) -> Result<String, DidWebError> {
+    for segment in resource_path.split('/') {
+        path_component(segment).map_err(|_| {
+            DidWebError::InvalidDid(format!(
+                "resource path {resource_path:?} is not a valid relative path"
+            ))
+        })?;
+    }
+    let (host, directory) = location(domain, path_segments)?;
+    let mut url = if path_segments.is_empty() {
+        format!("https://{host}/{resource_path}")
+    } else {
+        format!("https://{host}/{directory}/{resource_path}")
+    };
+    if let Some(query) = query {
+        url.push('?');
+        url.push_str(query);
+    }
+    Ok(url)
+}
+
+/// Decoded host and the directory (relative to the site root) that hold a
+/// `did:web` document: `.well-known` when there are no path segments,
+/// otherwise the segments joined with `/`.
+pub(crate) fn location(
+    domain: &str,
+    path_segments: &[String],
+) -> Result<(String, String), DidWebError> {
     let decoded_domain = percent_decode_str(domain)
         .decode_utf8()
         .map_err(|e| DidWebError::InvalidDid(format!("domain is not valid UTF-8: {e}")))?;
@@ -195,33 +315,31 @@ pub fn build_url(domain: &str, path_segments: &[String]) -> Result<String, DidWe
         return Err(DidWebError::InvalidDid("domain is empty".into()));
     }
 
-    let mut url = format!("https://{decoded_domain}");
     if path_segments.is_empty() {
-        url.push_str("/.well-known/did.json");
-    } else {
-        for segment in path_segments {
-            let decoded_segment = percent_decode_str(segment).decode_utf8().map_err(|e| {
-                DidWebError::InvalidDid(format!("path segment {segment:?} is not valid UTF-8: {e}"))
-            })?;
-            // A decoded segment must stay a single path component. `%2E%2E`
-            // (`..`), `%2F` (`/`), `%5C` (`\`), etc. would otherwise let a
-            // crafted DID escape the expected `/{segments}/did.json` shape.
-            if decoded_segment.is_empty()
-                || decoded_segment == "."
-                || decoded_segment == ".."
-                || decoded_segment.contains(['/', '\\'])
-            {
-                return Err(DidWebError::InvalidDid(format!(
-                    "path segment {segment:?} is not a valid single path component"
-                )));
-            }
-            url.push('/');
-            url.push_str(&decoded_segment);
-        }
-        url.push_str("/did.json");
+        return Ok((decoded_domain.into_owned(), ".well-known".to_string()));
     }
 
-    Ok(url)
+    let directory = path_segments
+        .iter()
+        .map(|segment| path_component(segment))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((decoded_domain.into_owned(), directory.join("/")))
+}
+
+/// Percent-decode `segment` and check that it is still a single path
+/// component. `%2E%2E` (`..`), `%2F` (`/`), `%5C` (`\`), etc. would otherwise
+/// let a crafted DID or DID URL escape the directory it should resolve in.
+fn path_component(segment: &str) -> Result<Cow<'_, str>, DidWebError> {
+    let decoded = percent_decode_str(segment).decode_utf8().map_err(|e| {
+        DidWebError::InvalidDid(format!("path segment {segment:?} is not valid UTF-8: {e}"))
+    })?;
+    if decoded.is_empty() || decoded == "." || decoded == ".." || decoded.contains(['/', '\\']) {
+        return Err(DidWebError::InvalidDid(format!(
+            "path segment {segment:?} is not a valid single path component"
+        )));
+    }
+    Ok(decoded)
 }
 
 fn other_method_name(method: &DIDMethod) -> String {
@@ -240,6 +358,73 @@ mod tests {
         assert_eq!(url, "https://example.com/.well-known/did.json");
     }
 
+    #[test]
+    fn resource_url_maps_did_url_path() {
+        assert_eq!(
+            build_resource_url("example.com", &[], "schemas/a.json", None).unwrap(),
+            "https://example.com/schemas/a.json"
+        );
+        assert_eq!(
+            build_resource_url(
+                "example.com%3A8443",
+                &["dids".into(), "issuer".into()],
+                "status/1",
+                Some("v=2")
+            )
+            .unwrap(),
+            "https://example.com:8443/dids/issuer/status/1?v=2"
+        );
+        assert!(build_resource_url("example.com", &[], "../secret", None).is_err());
+        assert!(build_resource_url("example.com", &[], "", None).is_err());
+    }
+
+    #[test]
+    fn resource_url_rejects_encoded_traversal() {
+        for resource_path in [
+            "%2E%2E/secret",
+            "%2e%2e/secret",
+            "schemas/%2E",
+            "schemas%2F..%2Fsecret",
+            "schemas\\..\\secret",
+            "schemas/a%5C..%5Csecret",
+        ] {
+            let err = build_resource_url("example.com", &["dids".into()], resource_path, None)
+                .unwrap_err();
+            assert!(
+                matches!(err, DidWebError::InvalidDid(_)),
+                "resource path {resource_path:?} should be rejected, got {err:?}"
+            );
+        }
+        assert_eq!(
+            build_resource_url("example.com", &[], "schemas/a%20b.json", None).unwrap(),
+            "https://example.com/schemas/a%20b.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_resource_enforces_size_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/schemas/a.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_raw(r#"{"type":"object"}"#, "application/schema+json"),
+            )
+            .mount(&server)
+            .await;
+        let url = format!("{}/schemas/a.json", server.uri());
+
+        let (body, content_type) = DIDWeb::new().fetch_resource(&url, 1024).await.unwrap();
+        assert_eq!(body, br#"{"type":"object"}"#);
+        assert_eq!(content_type.as_deref(), Some("application/schema+json"));
+
+        let err = DIDWeb::new().fetch_resource(&url, 8).await.unwrap_err();
+        assert!(matches!(
+            err,
+            DidWebError::ResourceTooLarge { limit: 8, .. }
+        ));
+    }
+
     #[test]
     fn url_for_path_segments() {
         let url = build_url("example.com", &["user".to_string(), "alice".to_string()]).unwrap();
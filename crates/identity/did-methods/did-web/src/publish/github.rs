@@ -0,0 +1,271 @@
+//! GitHub Pages publisher, committing through the REST contents API.
+
+use std::{future::Future, pin::Pin};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::{RequestBuilder, StatusCode};
+use serde_json::{Value, json};
+
+use super::{PublishFile, Publisher};
+use crate::DidWebError;
+
+/// Default API root.
+pub const GITHUB_API: &str = "https://api.github.com";
+
+/// Commits each file to the branch GitHub Pages serves from.
+///
+/// Pages runs Jekyll by default, which drops directories starting with `.`
+/// — including `.well-known/`. When a file lands in such a directory the
+/// publisher first makes sure `.nojekyll` exists at the site root.
+///
+/// GitHub Pages sets `Content-Type` from the file extension and its own
+/// `Cache-Control`, so [`PublishFile::content_type`] and
+/// [`PublishFile::cache_control`] are not used here.
+#[derive(Clone)]
+pub struct GitHubPagesPublisher {
+    owner: String,
+    repo: String,
+    token: String,
+    branch: String,
+    path_prefix: String,
+    api_base: String,
+    client: reqwest::Client,
+}
+
+impl std::fmt::Debug for GitHubPagesPublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GitHubPagesPublisher")
+            .field("owner", &self.owner)
+            .field("repo", &self.repo)
+            .field("token", &"[REDACTED]")
+            .field("branch", &self.branch)
+            .field("path_prefix", &self.path_prefix)
+            .field("api_base", &self.api_base)
+            .finish()
+    }
+}
+
+impl GitHubPagesPublisher {
+    /// Publish to `owner/repo` on the `gh-pages` branch. `token` needs
+    /// `contents: write` on the repository.
+    pub fn new(
+        owner: impl Into<String>,
+        repo: impl Into<String>,
+        token: impl Into<String>,
+    ) -> Self {
+        GitHubPagesPublisher {
+            owner: owner.into(),
+            repo: repo.into(),
+            token: token.into(),
+            branch: "gh-pages".to_string(),
+            path_prefix: String::new(),
+            api_base: GITHUB_API.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Branch Pages is built from.
+    pub fn with_branch(mut self, branch: impl Into<String>) -> Self {
+        self.branch = branch.into();
+        self
+    }
+
+    /// Directory in the repository that Pages serves (e.g. `docs`).
+    pub fn with_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        let prefix = prefix.trim_matches('/');
+        self.path_prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{prefix}/")
+        };
+        self
+    }
+
+    /// API root, for GitHub Enterprise Server (`https://{host}/api/v3`).
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn request(&self, method: reqwest::Method, repo_path: &str, query: &str) -> RequestBuilder {
+        let url = format!(
+            "{}/repos/{}/{}/contents/{repo_path}{query}",
+            self.api_base, self.owner, self.repo
+        );
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .header(
+                reqwest::header::USER_AGENT,
+                concat!("affinidi-did-web/", env!("CARGO_PKG_VERSION")),
+            )
+    }
+
+    /// Blob SHA of the file currently on the branch, if any.
+    async fn current_sha(&self, repo_path: &str) -> Result<Option<String>, DidWebError> {
+        let response = self
+            .request(
+                reqwest::Method::GET,
+                repo_path,
+                &format!(
+                    "?ref={}",
+                    utf8_percent_encode(&self.branch, NON_ALPHANUMERIC)
+                ),
+            )
+            .send()
+            .await
+            .map_err(|e| DidWebError::Publish(format!("GitHub GET {repo_path}: {e}")))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = check(response, "GET", repo_path).await?;
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| DidWebError::Publish(format!("GitHub GET {repo_path}: {e}")))?;
+        Ok(body["sha"].as_str().map(str::to_string))
+    }
+
+    async fn commit(&self, repo_path: &str, body: &[u8]) -> Result<(), DidWebError> {
+        let mut request = json!({
+            "message": format!("Publish {repo_path}"),
+            "content": STANDARD.encode(body),
+            "branch": self.branch,
+        });
+        if let Some(sha) = self.current_sha(repo_path).await? {
+            request["sha"] = Value::String(sha);
+        }
+        let response = self
+            .request(reqwest::Method::PUT, repo_path, "")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| DidWebError::Publish(format!("GitHub PUT {repo_path}: {e}")))?;
+        check(response, "PUT", repo_path).await?;
+        Ok(())
+    }
+
+    async fn put_file(&self, file: &PublishFile) -> Result<(), DidWebError> {
+        if file.path.split('/').any(|segment| segment.starts_with('.')) {
+            let nojekyll = format!("{}.nojekyll", self.path_prefix);
+            if self.current_sha(&nojekyll).await?.is_none() {
+                self.commit(&nojekyll, &[]).await?;
+            }
+        }
+        self.commit(&format!("{}{}", self.path_prefix, file.path), &file.body)
+            .await
+    }
+}
+
+async fn check(
+    response: reqwest::Response,
+    method: &str,
+    repo_path: &str,
+) -> Result<reqwest::Response, DidWebError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(DidWebError::Publish(format!(
+        "GitHub {method} {repo_path}: HTTP {status}: {body}"
+    )))
+}
+
+impl Publisher for GitHubPagesPublisher {
+    fn put<'a>(
+        &'a self,
+        file: &'a PublishFile,
+    ) -> Pin<Box<dyn Future<Output = Result<(), DidWebError>> + Send + 'a>> {
+        Box::pin(self.put_file(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn file(path: &str) -> PublishFile {
+        PublishFile {
+            path: path.into(),
+            body: b"{}".to_vec(),
+            content_type: "application/did+ld+json".into(),
+            cache_control: "public, max-age=300".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn updates_existing_file_and_adds_nojekyll() {
+        let server = MockServer::start().await;
+        let contents = "/repos/acme/site/contents";
+        Mock::given(method("GET"))
+            .and(path(format!("{contents}/docs/.nojekyll")))
+            .and(query_param("ref", "main"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(format!("{contents}/docs/.nojekyll")))
+            .and(body_partial_json(
+                json!({ "content": "", "branch": "main" }),
+            ))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{contents}/docs/.well-known/did.json")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "sha": "abc123" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path(format!("{contents}/docs/.well-known/did.json")))
+            .and(header("authorization", "Bearer ghp_test"))
+            .and(body_partial_json(
+                json!({ "content": "e30=", "sha": "abc123" }),
+            ))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        GitHubPagesPublisher::new("acme", "site", "ghp_test")
+            .with_branch("main")
+            .with_path_prefix("/docs/")
+            .with_api_base(server.uri())
+            .put(&file(".well-known/did.json"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn surfaces_api_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("Resource not accessible"))
+            .mount(&server)
+            .await;
+
+        let err = GitHubPagesPublisher::new("acme", "site", "ghp_test")
+            .with_api_base(server.uri())
+            .put(&file("alice/did.json"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Resource not accessible"), "{err}");
+    }
+}
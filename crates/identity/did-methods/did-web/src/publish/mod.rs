@@ -0,0 +1,372 @@
+/*!
+ * Publishing DID documents to web hosting (`publish` feature).
+ *
+ * Hosting a `did:web` (or `did:webvh`) DID means putting its files at the
+ * path the DID maps to. A [`PublicationBundle`] works out that path from the
+ * DID and holds the files — `did.json`, plus `did.jsonl` and
+ * `did-witness.json` for `did:webvh` — with the right `Content-Type` and
+ * `Cache-Control`. A [`Publisher`] uploads them:
+ *
+ * - [`S3Publisher`] — `PUT` to an S3 (or S3-compatible) bucket, SigV4-signed;
+ * - [`GitHubPagesPublisher`] — commits through the GitHub contents API,
+ *   adding `.nojekyll` so Pages serves `.well-known/`;
+ * - [`WebDavPublisher`] — WebDAV `PUT`, creating missing collections.
+ *
+ * [`verify_publication`] then fetches the files back from the public URL
+ * (resolving `did:web` documents through [`DIDWeb`]) until they match, so a
+ * deploy is only reported done once the DID actually resolves.
+ *
+ * ```no_run
+ * # async fn run(doc: affinidi_did_common::Document) -> Result<(), affinidi_did_web::DidWebError> {
+ * use affinidi_did_web::{DIDWeb, publish::*};
+ *
+ * let bundle = PublicationBundle::new("did:web:example.com:alice")?.with_document(&doc)?;
+ * let s3 = S3Publisher::new(S3Config::new("my-bucket", "eu-west-1", "AKIA…", "secret"));
+ * publish(&s3, &bundle).await?;
+ * verify_publication(&DIDWeb::new(), &bundle, &VerifyOptions::default()).await?;
+ * # Ok(()) }
+ * ```
+ */
+
+use std::{future::Future, pin::Pin, time::Duration};
+
+use affinidi_did_common::{DID, DIDMethod, Document};
+use tracing::debug;
+
+use crate::{DIDWeb, DidWebError, location};
+
+mod github;
+mod s3;
+mod webdav;
+
+pub use github::GitHubPagesPublisher;
+pub use s3::{S3Config, S3Publisher};
+pub use webdav::{WebDavAuth, WebDavPublisher};
+
+/// `Content-Type` for `did.json`.
+pub const DID_JSON_CONTENT_TYPE: &str = "application/did+ld+json";
+
+/// `Content-Type` for the `did:webvh` log (`did.jsonl`).
+pub const DID_JSONL_CONTENT_TYPE: &str = "application/jsonl";
+
+/// `Content-Type` for `did-witness.json`.
+pub const WITNESS_CONTENT_TYPE: &str = "application/json";
+
+/// Default `Cache-Control`: short enough that a key rotation propagates within
+/// minutes, long enough to spare the origin.
+pub const DEFAULT_CACHE_CONTROL: &str = "public, max-age=300";
+
+/// One file to upload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublishFile {
+    /// Path relative to the site root, without a leading `/`
+    /// (e.g. `.well-known/did.json`).
+    pub path: String,
+    pub body: Vec<u8>,
+    pub content_type: String,
+    pub cache_control: String,
+}
+
+/// The files that host one DID, and where they go.
+#[derive(Clone, Debug)]
+pub struct PublicationBundle {
+    did: String,
+    host: String,
+    directory: String,
+    cache_control: String,
+    document: Option<Document>,
+    files: Vec<PublishFile>,
+}
+
+impl PublicationBundle {
+    /// Start a bundle for a `did:web` or `did:webvh` DID.
+    pub fn new(did: &str) -> Result<Self, DidWebError> {
+        let parsed: DID = did
+            .parse()
+            .map_err(|e| DidWebError::InvalidDid(format!("{e}")))?;
+        let (domain, path_segments) = match parsed.method() {
+            DIDMethod::Web {
+                domain,
+                path_segments,
+                ..
+            }
+            | DIDMethod::Webvh {
+                domain,
+                path_segments,
+                ..
+            } => (domain, path_segments),
+            other => {
+                return Err(DidWebError::InvalidDid(format!(
+                    "expected did:web or did:webvh, got did:{other}"
+                )));
+            }
+        };
+        let (host, directory) = location(&domain, &path_segments)?;
+
+        Ok(PublicationBundle {
+            did: did.to_string(),
+            host,
+            directory,
+            cache_control: DEFAULT_CACHE_CONTROL.to_string(),
+            document: None,
+            files: Vec::new(),
+        })
+    }
+
+    /// Use `cache_control` for files added after this call.
+    pub fn with_cache_control(mut self, cache_control: impl Into<String>) -> Self {
+        self.cache_control = cache_control.into();
+        self
+    }
+
+    /// Add `did.json`. The document's `id` must be this bundle's DID.
+    pub fn with_document(mut self, document: &Document) -> Result<Self, DidWebError> {
+        if document.id.as_str() != self.did {
+            return Err(DidWebError::Publish(format!(
+                "document id ({}) does not match {}",
+                document.id, self.did
+            )));
+        }
+        let body = serde_json::to_vec_pretty(document)
+            .map_err(|e| DidWebError::InvalidDocument(e.to_string()))?;
+        self.document = Some(document.clone());
+        Ok(self.with_file("did.json", body, DID_JSON_CONTENT_TYPE))
+    }
+
+    /// Add the `did:webvh` log (`did.jsonl`).
+    pub fn with_log(self, did_jsonl: impl Into<Vec<u8>>) -> Self {
+        self.with_file("did.jsonl", did_jsonl, DID_JSONL_CONTENT_TYPE)
+    }
+
+    /// Add the `did:webvh` witness proofs (`did-witness.json`).
+    pub fn with_witness_proofs(self, did_witness_json: impl Into<Vec<u8>>) -> Self {
+        self.with_file("did-witness.json", did_witness_json, WITNESS_CONTENT_TYPE)
+    }
+
+    /// Add any other file next to the DID's documents. Replaces an earlier
+    /// file of the same name.
+    pub fn with_file(mut self, name: &str, body: impl Into<Vec<u8>>, content_type: &str) -> Self {
+        let path = format!("{}/{name}", self.directory);
+        self.files.retain(|f| f.path != path);
+        self.files.push(PublishFile {
+            path,
+            body: body.into(),
+            content_type: content_type.to_string(),
+            cache_control: self.cache_control.clone(),
+        });
+        self
+    }
+
+    pub fn did(&self) -> &str {
+        &self.did
+    }
+
+    /// Host (with port, if any) that must serve the files.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Directory the files go in, relative to the site root
+    /// (`.well-known` or the DID's path segments).
+    pub fn directory(&self) -> &str {
+        &self.directory
+    }
+
+    pub fn files(&self) -> &[PublishFile] {
+        &self.files
+    }
+
+    /// Public HTTPS URL of a file in this bundle.
+    pub fn public_url(&self, file: &PublishFile) -> String {
+        format!("https://{}/{}", self.host, file.path)
+    }
+}
+
+/// A hosting backend.
+///
+/// Uses a boxed future (rather than `async fn`) so publishers can be held as
+/// `Box<dyn Publisher>`.
+pub trait Publisher: Send + Sync {
+    /// Upload one file, creating or replacing it.
+    fn put<'a>(
+        &'a self,
+        file: &'a PublishFile,
+    ) -> Pin<Box<dyn Future<Output = Result<(), DidWebError>> + Send + 'a>>;
+}
+
+/// Upload every file in `bundle`, in order, stopping at the first failure.
+pub async fn publish<P: Publisher + ?Sized>(
+    publisher: &P,
+    bundle: &PublicationBundle,
+) -> Result<(), DidWebError> {
+    if bundle.files.is_empty() {
+        return Err(DidWebError::Publish(format!(
+            "nothing to publish for {}",
+            bundle.did
+        )));
+    }
+    for file in &bundle.files {
+        debug!(target: "affinidi_did_web", did = bundle.did, path = file.path, "publishing");
+        publisher.put(file).await?;
+    }
+    Ok(())
+}
+
+/// How long [`verify_publication`] waits for the files to appear.
+#[derive(Clone, Debug)]
+pub struct VerifyOptions {
+    /// Checks to make before giving up (at least one).
+    pub attempts: u32,
+    /// Wait between checks. CDNs and GitHub Pages builds can take minutes.
+    pub interval: Duration,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            attempts: 10,
+            interval: Duration::from_secs(15),
+        }
+    }
+}
+
+/// Check that every file in `bundle` is publicly served with the published
+/// content: `did.json` of a `did:web` is resolved through `resolver` and
+/// compared as a document; other files are fetched from their public URL and
+/// compared byte for byte.
+///
+/// Retries per `options`; returns [`DidWebError::Publish`] naming the first
+/// file still missing or stale.
+pub async fn verify_publication(
+    resolver: &DIDWeb,
+    bundle: &PublicationBundle,
+    options: &VerifyOptions,
+) -> Result<(), DidWebError> {
+    let attempts = options.attempts.max(1);
+    let mut last_problem = String::new();
+    for attempt in 1..=attempts {
+        match check_once(resolver, bundle).await {
+            Ok(()) => return Ok(()),
+            Err(problem) => {
+                debug!(target: "affinidi_did_web", did = bundle.did, attempt, %problem, "not yet visible");
+                last_problem = problem;
+            }
+        }
+        if attempt < attempts {
+            tokio::time::sleep(options.interval).await;
+        }
+    }
+    Err(DidWebError::Publish(format!(
+        "{} is not publicly resolvable after {attempts} attempts: {last_problem}",
+        bundle.did
+    )))
+}
+
+async fn check_once(resolver: &DIDWeb, bundle: &PublicationBundle) -> Result<(), String> {
+    for file in &bundle.files {
+        if file.path.ends_with("/did.json")
+            && bundle.did.starts_with("did:web:")
+            && let Some(expected) = &bundle.document
+        {
+            let resolved = resolver
+                .resolve(&bundle.did)
+                .await
+                .map_err(|e| e.to_string())?;
+            if !same_document(&resolved, expected) {
+                return Err(format!("{} serves a different document", bundle.did));
+            }
+            continue;
+        }
+
+        // A body longer than the published file can't match it, so stop
+        // reading there rather than buffering whatever the host sends.
+        let url = bundle.public_url(file);
+        let body = match resolver.fetch_resource(&url, file.body.len()).await {
+            Ok((body, _)) => body,
+            Err(DidWebError::ResourceTooLarge { .. }) => {
+                return Err(format!("{url} serves different content"));
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        if body != file.body {
+            return Err(format!("{url} serves different content"));
+        }
+    }
+    Ok(())
+}
+
+/// Compare documents by their canonical JSON hash, so formatting and key order
+/// don't matter.
+fn same_document(a: &Document, b: &Document) -> bool {
+    matches!((a.sha256_hash(), b.sha256_hash()), (Ok(a), Ok(b)) if a == b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(id: &str) -> Document {
+        serde_json::from_value(json!({
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": id,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn bundle_paths_follow_the_did() {
+        let bundle = PublicationBundle::new("did:web:example.com")
+            .unwrap()
+            .with_document(&document("did:web:example.com"))
+            .unwrap();
+        assert_eq!(bundle.files()[0].path, ".well-known/did.json");
+        assert_eq!(bundle.files()[0].content_type, DID_JSON_CONTENT_TYPE);
+        assert_eq!(
+            bundle.public_url(&bundle.files()[0]),
+            "https://example.com/.well-known/did.json"
+        );
+
+        let bundle = PublicationBundle::new("did:web:example.com%3A8443:users:alice")
+            .unwrap()
+            .with_cache_control("no-cache")
+            .with_log("{}\n");
+        assert_eq!(bundle.host(), "example.com:8443");
+        assert_eq!(bundle.files()[0].path, "users/alice/did.jsonl");
+        assert_eq!(bundle.files()[0].cache_control, "no-cache");
+    }
+
+    #[test]
+    fn bundle_supports_webvh() {
+        let bundle = PublicationBundle::new("did:webvh:QmSCID:example.com:alice")
+            .unwrap()
+            .with_log("{}\n")
+            .with_witness_proofs("[]");
+        let paths: Vec<_> = bundle.files().iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["alice/did.jsonl", "alice/did-witness.json"]);
+    }
+
+    #[test]
+    fn bundle_rejects_mismatched_document_and_other_methods() {
+        let bundle = PublicationBundle::new("did:web:example.com").unwrap();
+        assert!(matches!(
+            bundle.with_document(&document("did:web:other.com")),
+            Err(DidWebError::Publish(_))
+        ));
+        assert!(matches!(
+            PublicationBundle::new("did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK"),
+            Err(DidWebError::InvalidDid(_))
+        ));
+    }
+
+    #[test]
+    fn documents_compare_by_content() {
+        let a = document("did:web:example.com");
+        let b: Document = serde_json::from_str(
+            r#"{ "id": "did:web:example.com", "@context": ["https://www.w3.org/ns/did/v1"] }"#,
+        )
+        .unwrap();
+        assert!(same_document(&a, &b));
+        assert!(!same_document(&a, &document("did:web:example.org")));
+    }
+}
@@ -0,0 +1,189 @@
+//! WebDAV publisher.
+
+use std::{future::Future, pin::Pin};
+
+use reqwest::{Method, RequestBuilder, StatusCode};
+
+use super::{PublishFile, Publisher};
+use crate::DidWebError;
+
+/// Credentials for a WebDAV server.
+#[derive(Clone)]
+pub enum WebDavAuth {
+    Basic { username: String, password: String },
+    Bearer(String),
+}
+
+impl std::fmt::Debug for WebDavAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebDavAuth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"[REDACTED]")
+                .finish(),
+            WebDavAuth::Bearer(_) => f.debug_tuple("Bearer").field(&"[REDACTED]").finish(),
+        }
+    }
+}
+
+/// Uploads with WebDAV `PUT` under a base URL, creating missing collections
+/// with `MKCOL`.
+#[derive(Clone, Debug)]
+pub struct WebDavPublisher {
+    base_url: String,
+    auth: Option<WebDavAuth>,
+    client: reqwest::Client,
+}
+
+impl WebDavPublisher {
+    /// `base_url` is the collection that maps to the site root,
+    /// e.g. `https://dav.example.com/www`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        WebDavPublisher {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            auth: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn with_auth(mut self, auth: WebDavAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}/{path}", self.base_url));
+        match &self.auth {
+            Some(WebDavAuth::Basic { username, password }) => {
+                request.basic_auth(username, Some(password))
+            }
+            Some(WebDavAuth::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn upload(&self, file: &PublishFile) -> Result<StatusCode, DidWebError> {
+        let response = self
+            .request(Method::PUT, &file.path)
+            .header(reqwest::header::CONTENT_TYPE, &file.content_type)
+            .header(reqwest::header::CACHE_CONTROL, &file.cache_control)
+            .body(file.body.clone())
+            .send()
+            .await
+            .map_err(|e| DidWebError::Publish(format!("WebDAV PUT {}: {e}", file.path)))?;
+        Ok(response.status())
+    }
+
+    /// `MKCOL` every ancestor collection of `path`. Collections that already
+    /// exist answer 405, which is fine.
+    async fn create_collections(&self, path: &str) -> Result<(), DidWebError> {
+        let mut collection = String::new();
+        let Some((parents, _)) = path.rsplit_once('/') else {
+            return Ok(());
+        };
+        for segment in parents.split('/') {
+            collection.push_str(segment);
+            collection.push('/');
+            let status = self
+                .request(
+                    Method::from_bytes(b"MKCOL").expect("valid method"),
+                    &collection,
+                )
+                .send()
+                .await
+                .map_err(|e| DidWebError::Publish(format!("WebDAV MKCOL {collection}: {e}")))?
+                .status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(DidWebError::Publish(format!(
+                    "WebDAV MKCOL {collection}: HTTP {status}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn put_file(&self, file: &PublishFile) -> Result<(), DidWebError> {
+        let mut status = self.upload(file).await?;
+        // 409 Conflict is the RFC 4918 answer for a missing parent; some
+        // servers say 404.
+        if status == StatusCode::CONFLICT || status == StatusCode::NOT_FOUND {
+            self.create_collections(&file.path).await?;
+            status = self.upload(file).await?;
+        }
+        if !status.is_success() {
+            return Err(DidWebError::Publish(format!(
+                "WebDAV PUT {}: HTTP {status}",
+                file.path
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Publisher for WebDavPublisher {
+    fn put<'a>(
+        &'a self,
+        file: &'a PublishFile,
+    ) -> Pin<Box<dyn Future<Output = Result<(), DidWebError>> + Send + 'a>> {
+        Box::pin(self.put_file(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn creates_missing_collections_then_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path("/www/users/alice/did.json"))
+            .respond_with(ResponseTemplate::new(409))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("MKCOL"))
+            .and(path("/www/users/"))
+            .respond_with(ResponseTemplate::new(405))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("MKCOL"))
+            .and(path("/www/users/alice/"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("PUT"))
+            .and(path("/www/users/alice/did.json"))
+            .and(header("content-type", "application/did+ld+json"))
+            .and(header("cache-control", "no-cache"))
+            .and(header("authorization", "Bearer dav-token"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let file = PublishFile {
+            path: "users/alice/did.json".into(),
+            body: b"{}".to_vec(),
+            content_type: "application/did+ld+json".into(),
+            cache_control: "no-cache".into(),
+        };
+        WebDavPublisher::new(format!("{}/www/", server.uri()))
+            .with_auth(WebDavAuth::Bearer("dav-token".into()))
+            .put(&file)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,346 @@
+//! The backchannel: the harness's HTTP API, mapped onto DIDComm.
+//!
+//! The harness drives an agent with `POST /agent/command/{topic}/{operation}`
+//! (body `{"id": ..., "data": ...}`) and reads records back with
+//! `GET /agent/command/{topic}/{id}`. Topics and operations this agent
+//! doesn't support answer `501 Not Implemented`, which the harness reports as
+//! skipped rather than failed.
+//!
+//! Supported:
+//!
+//! | Topic | Operations |
+//! |-------|------------|
+//! | `status`, `version`, `did` | `GET` |
+//! | `out-of-band` | `send-invitation-message`, `receive-invitation` |
+//! | `did-exchange` | `send-request`, `send-response`, `GET` record |
+//! | `connection` | `accept-invitation`, `accept-request`, `send-ping`, `GET` record or list |
+//!
+//! `did-exchange` and `connection` are the DIDComm v1 connection protocols;
+//! on DIDComm v2 a connection completes when the invitee's trust ping on the
+//! invitation's thread gets its response (see [`crate::connections`]).
+
+use crate::{
+    connections::{Connection, ConnectionState, Connections},
+    error::BackchannelError,
+    protocols::{self, TRUST_PING_TYPE, TRUST_PONG_TYPE},
+    transport::Transport,
+};
+use affinidi_messaging_didcomm::Message;
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Body of a harness command.
+#[derive(Debug, Default, Deserialize)]
+pub struct CommandRequest {
+    /// The record the command applies to, usually a connection id
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub data: Value,
+}
+
+/// A TDK agent as the harness sees it. Cheap to clone; clones share state.
+#[derive(Clone)]
+pub struct Backchannel {
+    transport: Arc<dyn Transport>,
+    connections: Connections,
+}
+
+impl Backchannel {
+    pub fn new(transport: impl Transport) -> Self {
+        Backchannel {
+            transport: Arc::new(transport),
+            connections: Connections::default(),
+        }
+    }
+
+    pub fn connections(&self) -> &Connections {
+        &self.connections
+    }
+
+    /// The harness's HTTP API.
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route(
+                "/agent/command/{*path}",
+                get(query_handler).post(command_handler),
+            )
+            .route("/agent/response/{*path}", get(response_handler))
+            .with_state(self.clone())
+    }
+
+    /// Answers `GET /agent/command/{topic}[/{id}]`.
+    pub fn query(&self, topic: &str, id: Option<&str>) -> Result<Value, BackchannelError> {
+        match (topic, id) {
+            ("status", None) => Ok(json!({ "status": "active" })),
+            ("version", None) => Ok(json!(env!("CARGO_PKG_VERSION"))),
+            ("did", None) => Ok(json!({ "did": self.transport.did() })),
+            ("connection", None) => Ok(json!(self.connections.list())),
+            ("connection" | "did-exchange" | "out-of-band", Some(id)) => {
+                Ok(json!(self.connection(id)?))
+            }
+            _ => Err(not_implemented(topic, id.unwrap_or("GET"))),
+        }
+    }
+
+    /// Runs `POST /agent/command/{topic}/{operation}`.
+    pub async fn command(
+        &self,
+        topic: &str,
+        operation: &str,
+        request: CommandRequest,
+    ) -> Result<Value, BackchannelError> {
+        match (topic, operation) {
+            ("out-of-band", "send-invitation-message") => {
+                let invitation = protocols::invitation(
+                    self.transport.did(),
+                    request.data.get("goal_code").and_then(Value::as_str),
+                    request.data.get("goal").and_then(Value::as_str),
+                );
+                let connection = Connection {
+                    connection_id: uuid::Uuid::new_v4().to_string(),
+                    state: ConnectionState::InvitationSent,
+                    invitation_id: invitation.id.clone(),
+                    their_did: None,
+                    request_thid: None,
+                };
+                self.connections.insert(connection.clone());
+                info!(connection_id = %connection.connection_id, "Invitation created");
+
+                let mut response = json!(connection);
+                response["invitation"] = json!(invitation);
+                Ok(response)
+            }
+            ("out-of-band", "receive-invitation") => {
+                let invitation = protocols::parse_invitation(&request.data)?;
+                let connection = Connection {
+                    connection_id: uuid::Uuid::new_v4().to_string(),
+                    state: ConnectionState::InvitationReceived,
+                    invitation_id: invitation.id,
+                    their_did: invitation.from,
+                    request_thid: None,
+                };
+                self.connections.insert(connection.clone());
+                info!(connection_id = %connection.connection_id, "Invitation received");
+                Ok(json!(connection))
+            }
+            ("did-exchange", "send-request") | ("connection", "accept-invitation") => {
+                let connection = self.connection(&required_id(&request)?)?;
+                if connection.state != ConnectionState::InvitationReceived {
+                    return Err(BackchannelError::BadRequest(format!(
+                        "Connection ({}) is not waiting to use an invitation",
+                        connection.connection_id
+                    )));
+                }
+                let their_did = their_did(&connection)?;
+                let ping = protocols::ping(
+                    self.transport.did(),
+                    their_did,
+                    Some(&connection.invitation_id),
+                    None,
+                );
+                // Recorded before sending: the response can arrive before
+                // `send` returns.
+                let request_thid = ping.id.clone();
+                self.update(&connection.connection_id, |connection| {
+                    connection.state = ConnectionState::RequestSent;
+                    connection.request_thid = Some(request_thid);
+                })?;
+                if let Err(err) = self.transport.send(ping, their_did).await {
+                    self.update(&connection.connection_id, |connection| {
+                        connection.state = ConnectionState::InvitationReceived;
+                        connection.request_thid = None;
+                    })?;
+                    return Err(err);
+                }
+                Ok(json!(self.connection(&connection.connection_id)?))
+            }
+            // The inviter's side completes as soon as the request arrives;
+            // there is nothing left to send.
+            ("did-exchange", "send-response") | ("connection", "accept-request") => {
+                let connection = self.connection(&required_id(&request)?)?;
+                if connection.state != ConnectionState::Completed {
+                    return Err(BackchannelError::BadRequest(format!(
+                        "No request received on connection ({})",
+                        connection.connection_id
+                    )));
+                }
+                Ok(json!(connection))
+            }
+            ("connection", "send-ping") => {
+                let connection = self.connection(&required_id(&request)?)?;
+                let their_did = their_did(&connection)?;
+                let ping = protocols::ping(
+                    self.transport.did(),
+                    their_did,
+                    None,
+                    request.data.get("comment").and_then(Value::as_str),
+                );
+                self.transport.send(ping, their_did).await?;
+                Ok(json!({
+                    "connection_id": connection.connection_id,
+                    "state": connection.state,
+                }))
+            }
+            _ => Err(not_implemented(topic, operation)),
+        }
+    }
+
+    /// Handles a message received from `sender` (the authenticated sender
+    /// DID, if any). Returns the reply to send back, if there is one.
+    pub fn receive(&self, sender: Option<&str>, message: &Message) -> Option<Message> {
+        match message.typ.as_str() {
+            TRUST_PING_TYPE => {
+                let sender = sender?;
+                if let Some(pthid) = &message.pthid
+                    && let Some(connection) = self.connections.update_where(
+                        |c| c.state == ConnectionState::InvitationSent && c.invitation_id == *pthid,
+                        |c| {
+                            c.state = ConnectionState::Completed;
+                            c.their_did = Some(sender.to_string());
+                        },
+                    )
+                {
+                    info!(connection_id = %connection.connection_id, "Connection completed (inviter)");
+                }
+                protocols::response_requested(message).then(|| {
+                    protocols::pong(
+                        self.transport.did(),
+                        sender,
+                        message.thid.as_deref().unwrap_or(&message.id),
+                    )
+                })
+            }
+            TRUST_PONG_TYPE => {
+                if let Some(thid) = &message.thid
+                    && let Some(connection) = self.connections.update_where(
+                        |c| {
+                            c.state == ConnectionState::RequestSent
+                                && c.request_thid.as_ref() == Some(thid)
+                        },
+                        |c| c.state = ConnectionState::Completed,
+                    )
+                {
+                    info!(connection_id = %connection.connection_id, "Connection completed (invitee)");
+                }
+                None
+            }
+            other => {
+                debug!(
+                    "Ignoring message ({other}) from {}",
+                    sender.unwrap_or("<anon>")
+                );
+                None
+            }
+        }
+    }
+
+    fn connection(&self, connection_id: &str) -> Result<Connection, BackchannelError> {
+        self.connections
+            .get(connection_id)
+            .ok_or_else(|| BackchannelError::NotFound(format!("connection {connection_id}")))
+    }
+
+    fn update(
+        &self,
+        connection_id: &str,
+        update: impl FnOnce(&mut Connection),
+    ) -> Result<Connection, BackchannelError> {
+        self.connections
+            .update_where(|c| c.connection_id == connection_id, update)
+            .ok_or_else(|| BackchannelError::NotFound(format!("connection {connection_id}")))
+    }
+}
+
+fn not_implemented(topic: &str, operation: &str) -> BackchannelError {
+    BackchannelError::NotImplemented(format!("{topic}/{operation}"))
+}
+
+fn required_id(request: &CommandRequest) -> Result<String, BackchannelError> {
+    request
+        .id
+        .clone()
+        .ok_or_else(|| BackchannelError::BadRequest("Command needs an `id`".into()))
+}
+
+fn their_did(connection: &Connection) -> Result<&str, BackchannelError> {
+    connection.their_did.as_deref().ok_or_else(|| {
+        BackchannelError::BadRequest(format!(
+            "Connection ({}) has no DID for the other agent yet",
+            connection.connection_id
+        ))
+    })
+}
+
+/// The non-empty segments of a wildcard path, so a trailing slash doesn't
+/// matter.
+fn segments(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+/// `GET /agent/command/{topic}[/{id}]`
+async fn query_handler(
+    State(backchannel): State<Backchannel>,
+    Path(path): Path<String>,
+) -> Response {
+    let result = match segments(&path).as_slice() {
+        [topic] => backchannel.query(topic, None),
+        [topic, id] => backchannel.query(topic, Some(id)),
+        _ => Err(BackchannelError::NotFound(path.clone())),
+    };
+    match result {
+        // The harness reads `version` as plain text.
+        Ok(Value::String(text)) => text.into_response(),
+        Ok(value) => Json(value).into_response(),
+        Err(err) => err.into_response(),
+    }
+}
+
+/// `POST /agent/command/{topic}/{operation}`
+async fn command_handler(
+    State(backchannel): State<Backchannel>,
+    Path(path): Path<String>,
+    body: Bytes,
+) -> Response {
+    let [topic, operation] = segments(&path)[..] else {
+        return BackchannelError::NotFound(path.clone()).into_response();
+    };
+    // The harness sends some commands without a body.
+    let request = if body.is_empty() {
+        CommandRequest::default()
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                return BackchannelError::BadRequest(format!("Invalid command body: {e}"))
+                    .into_response();
+            }
+        }
+    };
+
+    match backchannel.command(topic, operation, request).await {
+        Ok(value) => Json(value).into_response(),
+        Err(err) => err.into_response(),
+    }
+}
+
+/// `GET /agent/response/{topic}/{id}`: the harness polls this for the last
+/// webhook event of a record. Only the credential and proof protocols use
+/// it, and neither is supported.
+async fn response_handler(Path(path): Path<String>) -> Response {
+    let topic = segments(&path)
+        .first()
+        .copied()
+        .unwrap_or_default()
+        .to_string();
+    not_implemented(&topic, "response").into_response()
+}
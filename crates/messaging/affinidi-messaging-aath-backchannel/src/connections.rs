@@ -0,0 +1,81 @@
+//! Connection records, as the harness sees them.
+//!
+//! DIDComm v2 has no connection protocol: an out-of-band invitation carries
+//! the inviter's DID, and the first message the invitee sends on it (a trust
+//! ping, with the invitation as its parent thread) tells the inviter the
+//! invitee's DID. The harness still tracks "connections" with the AATH
+//! state names, so this module keeps one record per invitation.
+
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Where a connection is, in the state names the harness expects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConnectionState {
+    /// We created the invitation and are waiting for the first message on it.
+    InvitationSent,
+    /// We accepted an invitation and haven't used it yet.
+    InvitationReceived,
+    /// We sent the first message on an invitation and are waiting for the
+    /// reply.
+    RequestSent,
+    /// Both sides know each other's DID.
+    Completed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Connection {
+    pub connection_id: String,
+    pub state: ConnectionState,
+    /// The `id` of the out-of-band invitation the connection started from
+    pub invitation_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub their_did: Option<String>,
+    /// Thread of the request we sent on the invitation, as the invitee
+    #[serde(skip)]
+    pub request_thid: Option<String>,
+}
+
+/// The agent's connections, shared by every clone.
+#[derive(Clone, Debug, Default)]
+pub struct Connections {
+    inner: Arc<Mutex<HashMap<String, Connection>>>,
+}
+
+impl Connections {
+    pub fn insert(&self, connection: Connection) {
+        self.lock()
+            .insert(connection.connection_id.clone(), connection);
+    }
+
+    pub fn get(&self, connection_id: &str) -> Option<Connection> {
+        self.lock().get(connection_id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<Connection> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// Updates the first connection matching `filter` and returns it as
+    /// updated.
+    pub fn update_where(
+        &self,
+        filter: impl Fn(&Connection) -> bool,
+        update: impl FnOnce(&mut Connection),
+    ) -> Option<Connection> {
+        let mut connections = self.lock();
+        let connection = connections.values_mut().find(|c| filter(c))?;
+        update(connection);
+        Some(connection.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Connection>> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,40 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum BackchannelError {
+    /// The harness asked for a topic or operation this agent doesn't support.
+    /// Reported as `501 Not Implemented`, which the harness treats as "skip".
+    #[error("Not implemented ({0})")]
+    NotImplemented(String),
+    #[error("Not found ({0})")]
+    NotFound(String),
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+    /// Packing or sending a DIDComm message failed.
+    #[error("Transport error: {0}")]
+    Transport(String),
+}
+
+impl BackchannelError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            BackchannelError::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+            BackchannelError::NotFound(_) => StatusCode::NOT_FOUND,
+            BackchannelError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            BackchannelError::Transport(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+impl IntoResponse for BackchannelError {
+    fn into_response(self) -> Response {
+        (self.status(), Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
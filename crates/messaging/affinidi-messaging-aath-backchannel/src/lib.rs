@@ -0,0 +1,22 @@
+//! A backchannel for the [Aries Agent Test Harness] (AATH), so the harness
+//! can drive a TDK agent through its DIDComm conformance tests.
+//!
+//! The harness talks to each agent under test through a small HTTP API, the
+//! backchannel. [`Backchannel`] implements that API on top of a
+//! [`Transport`]; [`service::start`] runs it as a TDK agent connected to a
+//! mediator, and the `aath-backchannel` binary serves it on the port the
+//! harness assigns. Topics the TDK doesn't implement answer `501`, which the
+//! harness reports as skipped. See the README for what is covered.
+//!
+//! [Aries Agent Test Harness]: https://github.com/openwallet-foundation/owl-agent-test-harness
+
+pub mod backchannel;
+pub mod connections;
+pub mod error;
+pub mod protocols;
+pub mod service;
+pub mod transport;
+
+pub use backchannel::{Backchannel, CommandRequest};
+pub use error::BackchannelError;
+pub use transport::Transport;
@@ -0,0 +1,64 @@
+use affinidi_messaging_aath_backchannel::service;
+use affinidi_tdk::dids::{DID, KeyType, PeerKeyRole};
+use affinidi_tdk_common::profiles::TDKProfile;
+use clap::Parser;
+use std::net::SocketAddr;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// Aries Agent Test Harness backchannel for a TDK agent.
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Port to serve the backchannel on (the harness passes `-p`)
+    #[arg(short, long, default_value_t = 9020)]
+    port: u16,
+
+    /// Address to bind
+    #[arg(long, default_value = "0.0.0.0")]
+    interface: String,
+
+    /// DID of the mediator the agent receives messages through
+    #[arg(long, env = "MEDIATOR_DID")]
+    mediator_did: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive("affinidi_messaging_aath_backchannel=info".parse()?),
+        )
+        .init();
+
+    let args = Args::parse();
+
+    // A fresh identity per run, as the harness expects of an agent, routed
+    // through the mediator.
+    let (did, secrets) = DID::generate_did_peer(
+        vec![
+            (PeerKeyRole::Verification, KeyType::Ed25519),
+            (PeerKeyRole::Encryption, KeyType::X25519),
+        ],
+        Some(args.mediator_did.clone()),
+    )?;
+    info!("Agent DID: {did}");
+
+    let shutdown = CancellationToken::new();
+    let profile = TDKProfile::new("aath", &did, Some(&args.mediator_did), secrets);
+    let backchannel = service::start(profile, shutdown.clone()).await?;
+
+    let address: SocketAddr = format!("{}:{}", args.interface, args.port).parse()?;
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    info!("Backchannel listening on {address}");
+
+    axum::serve(listener, backchannel.router())
+        .with_graceful_shutdown(async move {
+            tokio::signal::ctrl_c().await.ok();
+            info!("Shutting down...");
+            shutdown.cancel();
+        })
+        .await?;
+    Ok(())
+}
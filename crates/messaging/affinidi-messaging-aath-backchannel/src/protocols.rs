@@ -0,0 +1,129 @@
+//! The DIDComm messages the backchannel sends and understands.
+
+use crate::error::BackchannelError;
+use affinidi_messaging_didcomm::Message;
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde_json::{Value, json};
+use std::time::SystemTime;
+
+pub const OOB_INVITATION_TYPE: &str = "https://didcomm.org/out-of-band/2.0/invitation";
+pub const TRUST_PING_TYPE: &str = "https://didcomm.org/trust-ping/2.0/ping";
+pub const TRUST_PONG_TYPE: &str = "https://didcomm.org/trust-ping/2.0/ping-response";
+
+/// An out-of-band invitation to connect to `from`.
+pub fn invitation(from: &str, goal_code: Option<&str>, goal: Option<&str>) -> Message {
+    let mut body = json!({ "accept": ["didcomm/v2"] });
+    if let Some(goal_code) = goal_code {
+        body["goal_code"] = goal_code.into();
+    }
+    if let Some(goal) = goal {
+        body["goal"] = goal.into();
+    }
+    Message::new(OOB_INVITATION_TYPE, body)
+        .from(from)
+        .created_time(now())
+}
+
+/// A trust ping asking for a response. `pthid` ties it to an invitation.
+pub fn ping(from: &str, to: &str, pthid: Option<&str>, comment: Option<&str>) -> Message {
+    let mut body = json!({ "response_requested": true });
+    if let Some(comment) = comment {
+        body["comment"] = comment.into();
+    }
+    let mut message = Message::new(TRUST_PING_TYPE, body)
+        .from(from)
+        .to(vec![to.to_string()])
+        .created_time(now());
+    message.thid = Some(message.id.clone());
+    if let Some(pthid) = pthid {
+        message.pthid = Some(pthid.to_string());
+    }
+    message
+}
+
+/// The response to the trust ping `thid`.
+pub fn pong(from: &str, to: &str, thid: &str) -> Message {
+    Message::new(TRUST_PONG_TYPE, json!({}))
+        .from(from)
+        .to(vec![to.to_string()])
+        .thid(thid)
+        .created_time(now())
+}
+
+/// Whether a trust ping asks for a response. Defaults to yes, as in the
+/// protocol.
+pub fn response_requested(ping: &Message) -> bool {
+    ping.body
+        .get("response_requested")
+        .and_then(Value::as_bool)
+        .unwrap_or(true)
+}
+
+/// Reads an out-of-band invitation given either as the message itself or as
+/// an invitation URL (`...?_oob=<base64url message>`).
+pub fn parse_invitation(data: &Value) -> Result<Message, BackchannelError> {
+    let invitation = match data.get("invitation_url").and_then(Value::as_str) {
+        Some(invitation_url) => decode_invitation_url(invitation_url)?,
+        None => serde_json::from_value(data.get("invitation").unwrap_or(data).clone())
+            .map_err(|e| BackchannelError::BadRequest(format!("Invalid invitation: {e}")))?,
+    };
+
+    if invitation.typ != OOB_INVITATION_TYPE {
+        return Err(BackchannelError::BadRequest(format!(
+            "Not an out-of-band 2.0 invitation (type: {})",
+            invitation.typ
+        )));
+    }
+    if invitation.from.is_none() {
+        return Err(BackchannelError::BadRequest(
+            "Invitation has no `from` DID".into(),
+        ));
+    }
+    Ok(invitation)
+}
+
+fn decode_invitation_url(invitation_url: &str) -> Result<Message, BackchannelError> {
+    let url = url::Url::parse(invitation_url)
+        .map_err(|e| BackchannelError::BadRequest(format!("Invalid invitation URL: {e}")))?;
+    let oob = url
+        .query_pairs()
+        .find_map(|(name, value)| (name == "_oob").then_some(value))
+        .ok_or_else(|| {
+            BackchannelError::BadRequest("Invitation URL has no `_oob` parameter".into())
+        })?;
+    let json = BASE64_URL_SAFE_NO_PAD
+        .decode(oob.trim_end_matches('='))
+        .map_err(|e| BackchannelError::BadRequest(format!("Invalid `_oob` encoding: {e}")))?;
+    serde_json::from_slice(&json)
+        .map_err(|e| BackchannelError::BadRequest(format!("Invalid invitation: {e}")))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invitation_url_round_trips() {
+        let invitation = invitation("did:example:alice", Some("aries.vc"), None);
+        let encoded = BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&invitation).unwrap());
+        let data = json!({ "invitation_url": format!("https://example.com/?_oob={encoded}") });
+
+        assert_eq!(parse_invitation(&data).unwrap(), invitation);
+    }
+
+    #[test]
+    fn rejects_other_message_types() {
+        let ping = ping("did:example:alice", "did:example:bob", None, None);
+        assert!(matches!(
+            parse_invitation(&serde_json::to_value(ping).unwrap()),
+            Err(BackchannelError::BadRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,106 @@
+//! Running the backchannel as a TDK agent: a [`DIDCommService`] listener on
+//! the agent's mediator, sending and receiving for a [`Backchannel`].
+
+use crate::{backchannel::Backchannel, error::BackchannelError, transport::Transport};
+use affinidi_messaging_didcomm::Message;
+use affinidi_messaging_didcomm_service::{
+    DIDCommResponse, DIDCommService, DIDCommServiceConfig, DIDCommServiceError, Extension,
+    HandlerContext, ListenerConfig, MESSAGE_PICKUP_STATUS_TYPE, MessagePolicy, RequestLogging,
+    RestartPolicy, RetryConfig, Router, handler_fn, ignore_handler,
+};
+use affinidi_tdk_common::profiles::TDKProfile;
+use async_trait::async_trait;
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+use tokio_util::sync::CancellationToken;
+
+const LISTENER_ID: &str = "aath";
+
+/// How long [`start`] waits for the listener to connect to the mediator.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sends through the agent's [`DIDCommService`] listener.
+pub struct ServiceTransport {
+    did: String,
+    // The service's router holds the backchannel, so the service is started
+    // after the backchannel that sends through it.
+    service: Arc<OnceLock<DIDCommService>>,
+}
+
+#[async_trait]
+impl Transport for ServiceTransport {
+    fn did(&self) -> &str {
+        &self.did
+    }
+
+    async fn send(&self, message: Message, to: &str) -> Result<(), BackchannelError> {
+        let service = self
+            .service
+            .get()
+            .ok_or_else(|| BackchannelError::Transport("DIDComm service not started".into()))?;
+        service
+            .send_message(LISTENER_ID, message, to)
+            .await
+            .map_err(|e| BackchannelError::Transport(e.to_string()))
+    }
+}
+
+/// Connects `profile` to its mediator and returns the backchannel driving it.
+/// The listener runs until `shutdown` is cancelled.
+pub async fn start(
+    profile: TDKProfile,
+    shutdown: CancellationToken,
+) -> Result<Backchannel, DIDCommServiceError> {
+    let service_cell = Arc::new(OnceLock::new());
+    let backchannel = Backchannel::new(ServiceTransport {
+        did: profile.did.clone(),
+        service: service_cell.clone(),
+    });
+
+    let router = Router::new()
+        .extension(backchannel.clone())
+        .route(MESSAGE_PICKUP_STATUS_TYPE, handler_fn(ignore_handler))?
+        .fallback(handler_fn(inbound_handler))
+        .layer(
+            MessagePolicy::new()
+                .require_encrypted(true)
+                .require_authenticated(true)
+                .allow_anonymous_sender(false),
+        )
+        .layer(RequestLogging);
+
+    let config = DIDCommServiceConfig {
+        listeners: vec![ListenerConfig {
+            id: LISTENER_ID.into(),
+            profile,
+            restart_policy: RestartPolicy::Always {
+                backoff: RetryConfig::default(),
+            },
+            ..Default::default()
+        }],
+    };
+
+    let service = DIDCommService::start(config, router, shutdown).await?;
+    service.wait_connected(LISTENER_ID, CONNECT_TIMEOUT).await?;
+    let _ = service_cell.set(service);
+    Ok(backchannel)
+}
+
+/// Hands every inbound message to the backchannel and sends its reply back.
+async fn inbound_handler(
+    ctx: HandlerContext,
+    message: Message,
+    Extension(backchannel): Extension<Backchannel>,
+) -> Result<Option<DIDCommResponse>, DIDCommServiceError> {
+    Ok(backchannel
+        .receive(ctx.sender_did.as_deref(), &message)
+        .map(|reply| {
+            let response = DIDCommResponse::new(reply.typ, reply.body);
+            match reply.thid {
+                Some(thid) => response.thid(thid),
+                None => response,
+            }
+        }))
+}
@@ -0,0 +1,16 @@
+use crate::error::BackchannelError;
+use affinidi_messaging_didcomm::Message;
+use async_trait::async_trait;
+
+/// How the backchannel reaches other agents.
+///
+/// [`ServiceTransport`](crate::service::ServiceTransport) sends through the
+/// agent's mediator; tests use an in-process loopback.
+#[async_trait]
+pub trait Transport: Send + Sync + 'static {
+    /// The agent's own DID, used as `from` on every message it sends.
+    fn did(&self) -> &str;
+
+    /// Pack `message` for `to` and deliver it.
+    async fn send(&self, message: Message, to: &str) -> Result<(), BackchannelError>;
+}
@@ -0,0 +1,183 @@
+//! Drives two backchannels through the harness's HTTP API, with messages
+//! delivered in-process instead of through a mediator.
+
+use affinidi_messaging_aath_backchannel::{Backchannel, BackchannelError, Transport};
+use affinidi_messaging_didcomm::Message;
+use async_trait::async_trait;
+use axum::{
+    body::{Body, to_bytes},
+    http::{Request, StatusCode},
+};
+use serde_json::{Value, json};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tower::ServiceExt;
+
+type Agents = Arc<Mutex<HashMap<String, Backchannel>>>;
+
+/// Delivers straight to the recipient's backchannel, and its reply straight
+/// back.
+struct Loopback {
+    did: String,
+    agents: Agents,
+}
+
+#[async_trait]
+impl Transport for Loopback {
+    fn did(&self) -> &str {
+        &self.did
+    }
+
+    async fn send(&self, message: Message, to: &str) -> Result<(), BackchannelError> {
+        let agent = |did: &str| self.agents.lock().unwrap().get(did).cloned();
+        let recipient =
+            agent(to).ok_or_else(|| BackchannelError::Transport(format!("unknown DID {to}")))?;
+        if let Some(reply) = recipient.receive(Some(&self.did), &message)
+            && let Some(sender) = agent(&self.did)
+        {
+            sender.receive(Some(to), &reply);
+        }
+        Ok(())
+    }
+}
+
+fn agents(dids: [&str; 2]) -> [Backchannel; 2] {
+    let agents = Agents::default();
+    dids.map(|did| {
+        let backchannel = Backchannel::new(Loopback {
+            did: did.to_string(),
+            agents: agents.clone(),
+        });
+        agents
+            .lock()
+            .unwrap()
+            .insert(did.to_string(), backchannel.clone());
+        backchannel
+    })
+}
+
+async fn call(
+    agent: &Backchannel,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+
+    let response = agent.router().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&bytes)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()));
+    (status, body)
+}
+
+#[tokio::test]
+async fn out_of_band_connection_completes() {
+    let [alice, bob] = agents(["did:example:alice", "did:example:bob"]);
+
+    let (status, invited) = call(
+        &alice,
+        "POST",
+        "/agent/command/out-of-band/send-invitation-message",
+        Some(json!({ "data": {} })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(invited["state"], "invitation-sent");
+    let alice_connection = invited["connection_id"].as_str().unwrap().to_string();
+
+    let (status, received) = call(
+        &bob,
+        "POST",
+        "/agent/command/out-of-band/receive-invitation",
+        Some(json!({ "data": invited["invitation"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(received["state"], "invitation-received");
+    assert_eq!(received["their_did"], "did:example:alice");
+    let bob_connection = received["connection_id"].as_str().unwrap().to_string();
+
+    let (status, _) = call(
+        &bob,
+        "POST",
+        "/agent/command/did-exchange/send-request",
+        Some(json!({ "id": bob_connection })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, record) = call(
+        &alice,
+        "GET",
+        &format!("/agent/command/did-exchange/{alice_connection}"),
+        None,
+    )
+    .await;
+    assert_eq!(record["state"], "completed");
+    assert_eq!(record["their_did"], "did:example:bob");
+
+    let (_, record) = call(
+        &bob,
+        "GET",
+        &format!("/agent/command/did-exchange/{bob_connection}/"),
+        None,
+    )
+    .await;
+    assert_eq!(record["state"], "completed");
+
+    let (status, _) = call(
+        &alice,
+        "POST",
+        "/agent/command/connection/send-ping",
+        Some(json!({ "id": alice_connection, "data": { "comment": "hello" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn request_before_invitation_is_rejected() {
+    let [alice, _] = agents(["did:example:alice", "did:example:bob"]);
+    let (status, _) = call(
+        &alice,
+        "POST",
+        "/agent/command/did-exchange/send-request",
+        Some(json!({ "id": "no-such-connection" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn status_and_unsupported_topics() {
+    let [alice, _] = agents(["did:example:alice", "did:example:bob"]);
+
+    let (status, body) = call(&alice, "GET", "/agent/command/status/", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "active");
+
+    let (status, body) = call(&alice, "GET", "/agent/command/did", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["did"], "did:example:alice");
+
+    // Skipped by the harness, not failed.
+    for (method, uri) in [
+        ("POST", "/agent/command/proof-v2/send-request"),
+        ("POST", "/agent/command/mediation/send-request"),
+        ("GET", "/agent/response/issue-credential-v2/some-id"),
+    ] {
+        let (status, _) = call(&alice, method, uri, Some(json!({}))).await;
+        assert_eq!(status, StatusCode::NOT_IMPLEMENTED, "{method} {uri}");
+    }
+}
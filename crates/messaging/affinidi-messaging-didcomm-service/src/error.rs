@@ -18,6 +18,51 @@ pub enum PolicyViolation {
     MissingSenderDid,
 }
 
+/// One problem found while validating a message body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// JSON Pointer into the body (e.g. `/items/0/quantity`); empty when the
+    /// issue concerns the body as a whole.
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// An inbound message rejected by [`MessageValidation`](crate::MessageValidation).
+#[derive(Debug, Clone, Error)]
+#[error("message {message_id} ({message_type}) failed validation: {}", join_issues(.issues))]
+pub struct ValidationError {
+    pub message_type: String,
+    pub message_id: String,
+    pub issues: Vec<ValidationIssue>,
+}
+
+pub(crate) fn join_issues(issues: &[ValidationIssue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum TransportError {
@@ -116,9 +161,18 @@ pub enum DIDCommServiceError {
     #[error("Policy violation: {0}")]
     Policy(#[from] PolicyViolation),
 
+    #[error("Validation failed: {0}")]
+    Validation(#[from] ValidationError),
+
+    #[error("Invalid JSON Schema: {0}")]
+    InvalidSchema(String),
+
     #[error("Transport error: {0}")]
     Transport(#[from] TransportError),
 
+    #[error("Timer store error: {0}")]
+    TimerStore(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -105,7 +105,10 @@ impl TspHandler for IgnoreTspHandler {
 /// Return `Some(response)` to send a reply (e.g., a problem report) back to the
 /// sender. Return `None` to silently drop the error.
 ///
-/// The default implementation logs the error and returns a problem report.
+/// The default implementation logs the error and returns a problem report:
+/// [`codes::ERROR_INVALID_MESSAGE`](crate::problem_report::codes::ERROR_INVALID_MESSAGE)
+/// listing the issues for [`DIDCommServiceError::Validation`], otherwise an
+/// internal error.
 #[async_trait]
 pub trait ErrorHandler: Send + Sync + 'static {
     async fn on_error(
@@ -124,6 +127,13 @@ impl ErrorHandler for DefaultErrorHandler {
         ctx: &HandlerContext,
         error: &DIDCommServiceError,
     ) -> Option<DIDCommResponse> {
+        if let DIDCommServiceError::Validation(validation) = error {
+            // The peer sent a malformed message; tell it what was wrong.
+            return Some(DIDCommResponse::problem_report(
+                crate::problem_report::ProblemReport::from(validation),
+            ));
+        }
+
         tracing::warn!(
             profile = %ctx.profile.inner.alias,
             message_id = %ctx.message_id,
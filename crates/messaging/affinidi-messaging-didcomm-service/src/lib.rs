@@ -6,18 +6,28 @@ pub mod problem_report;
 pub mod response;
 pub mod router;
 pub mod service;
+pub mod timers;
 pub mod transport;
 pub mod utils;
+#[cfg(feature = "webhook")]
+pub mod webhook;
 
 pub use config::{DIDCommServiceConfig, ListenerConfig, Protocols, RestartPolicy, RetryConfig};
-pub use error::{ConfigError, DIDCommServiceError, PolicyViolation, StartupError, TransportError};
+pub use error::{
+    ConfigError, DIDCommServiceError, PolicyViolation, StartupError, TransportError,
+    ValidationError, ValidationIssue,
+};
 pub use handler::{
     DIDCommHandler, DefaultErrorHandler, ErrorHandler, Extension, Extensions, FromMessageParts,
     HandlerContext, IgnoreTspHandler, MESSAGE_PICKUP_STATUS_TYPE, TRUST_PING_TYPE, TRUST_PONG_TYPE,
     TspHandler, TspResponse, ignore_handler, trust_ping_handler,
 };
+#[cfg(feature = "json-schema")]
+pub use middleware::JsonSchema;
 pub use middleware::{
-    MessagePolicy, MiddlewareHandler, MiddlewareResult, Next, RequestLogging, middleware_fn,
+    AllowList, KnownSenders, MessagePolicy, MessageValidation, MessageValidator, MiddlewareHandler,
+    MiddlewareResult, Next, QuarantineQueue, QuarantinedMessage, RateLimit, RequestLogging,
+    SenderFilter, TypedBody, UnknownSenderPolicy, middleware_fn,
 };
 pub use problem_report::{ProblemReport, ServiceProblemReport};
 pub use response::DIDCommResponse;
@@ -25,5 +35,8 @@ pub use router::{MessageHandler, Router, handler_fn};
 pub use service::{
     AffinidiMessageService, DIDCommService, ListenerEvent, ListenerState, ListenerStatus,
 };
+pub use timers::{ProtocolTimer, ProtocolTimers, TimerEvent, TimerNotify};
 pub use transport::{build_problem_report, build_response, send_problem_report, send_response};
 pub use utils::{get_parent_thread_id, get_thread_id, new_message_id};
+#[cfg(feature = "webhook")]
+pub use webhook::{WebhookEndpoint, WebhookResponse};
@@ -13,9 +13,18 @@ use crate::router::MessageHandler;
 
 mod policy;
 mod request_logging;
+mod sender_filter;
+mod validation;
 
 pub use policy::MessagePolicy;
 pub use request_logging::RequestLogging;
+pub use sender_filter::{
+    AllowList, KnownSenders, QuarantineQueue, QuarantinedMessage, RateLimit, SenderFilter,
+    UnknownSenderPolicy,
+};
+#[cfg(feature = "json-schema")]
+pub use validation::JsonSchema;
+pub use validation::{MessageValidation, MessageValidator, TypedBody};
 
 type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
 /// The return type shared by middleware handlers and route handlers.
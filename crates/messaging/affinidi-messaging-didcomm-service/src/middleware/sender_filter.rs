@@ -0,0 +1,554 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Instant, SystemTime};
+
+use affinidi_messaging_didcomm::{Message, UnpackMetadata};
+use affinidi_tdk_common::audit::AuditEvent;
+use async_trait::async_trait;
+use moka::{policy::EvictionPolicy, sync::Cache};
+
+use super::{MiddlewareHandler, MiddlewareResult, Next};
+use crate::handler::HandlerContext;
+
+/// Bucket key for messages whose envelope proves no sender DID. Anonymous
+/// senders share one bucket: there is nothing to tell them apart by.
+const ANONYMOUS: &str = "";
+
+/// Decides which senders count as known, e.g. the user's contacts.
+pub trait KnownSenders: Send + Sync + 'static {
+    fn is_known(&self, did: &str) -> bool;
+}
+
+impl<F> KnownSenders for F
+where
+    F: Fn(&str) -> bool + Send + Sync + 'static,
+{
+    fn is_known(&self, did: &str) -> bool {
+        self(did)
+    }
+}
+
+/// A shared, mutable set of known sender DIDs. Clones share the set, so the
+/// application can approve a sender while the filter is running.
+#[derive(Clone, Default)]
+pub struct AllowList(Arc<RwLock<HashSet<String>>>);
+
+impl AllowList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, did: impl Into<String>) -> bool {
+        self.0
+            .write()
+            .expect("allow list lock not poisoned")
+            .insert(did.into())
+    }
+
+    pub fn remove(&self, did: &str) -> bool {
+        self.0
+            .write()
+            .expect("allow list lock not poisoned")
+            .remove(did)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for AllowList {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        Self(Arc::new(RwLock::new(
+            iter.into_iter().map(Into::into).collect(),
+        )))
+    }
+}
+
+impl KnownSenders for AllowList {
+    fn is_known(&self, did: &str) -> bool {
+        self.0
+            .read()
+            .expect("allow list lock not poisoned")
+            .contains(did)
+    }
+}
+
+/// What to do with a message from a sender that isn't known. Anonymous
+/// messages are always from an unknown sender.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownSenderPolicy {
+    /// Hand it to its handler like any other message.
+    #[default]
+    Accept,
+    /// Hold it in the [`QuarantineQueue`] for the application to review.
+    Quarantine,
+    /// Drop it.
+    Reject,
+}
+
+/// A token bucket per sender: up to `burst` messages at once, refilled at
+/// `per_second`.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    burst: u32,
+    per_second: f64,
+}
+
+impl RateLimit {
+    pub fn new(burst: u32, per_second: f64) -> Self {
+        Self { burst, per_second }
+    }
+
+    /// `count` messages per minute, with bursts of up to `count`.
+    pub fn per_minute(count: u32) -> Self {
+        Self::new(count, f64::from(count) / 60.0)
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// A message held back by [`SenderFilter`].
+#[derive(Clone, Debug)]
+pub struct QuarantinedMessage {
+    pub listener_id: String,
+    /// The sender the envelope proves; `None` for anonymous messages.
+    pub sender_did: Option<String>,
+    pub message: Message,
+    pub meta: UnpackMetadata,
+    pub received_at: SystemTime,
+}
+
+/// Messages from unknown senders, waiting for the application to release
+/// or discard them. Bounded: once full, the oldest message is dropped.
+/// Clones share the queue.
+#[derive(Clone)]
+pub struct QuarantineQueue {
+    messages: Arc<Mutex<VecDeque<QuarantinedMessage>>>,
+    capacity: usize,
+}
+
+impl QuarantineQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            messages: Arc::new(Mutex::new(VecDeque::new())),
+            capacity,
+        }
+    }
+
+    /// A snapshot of the queued messages, oldest first.
+    pub fn list(&self) -> Vec<QuarantinedMessage> {
+        self.lock().iter().cloned().collect()
+    }
+
+    /// Remove a message so the application can process it.
+    pub fn release(&self, message_id: &str) -> Option<QuarantinedMessage> {
+        let mut messages = self.lock();
+        let index = messages.iter().position(|q| q.message.id == message_id)?;
+        messages.remove(index)
+    }
+
+    /// Remove every message from `sender_did`, e.g. after approving the
+    /// sender.
+    pub fn release_sender(&self, sender_did: &str) -> Vec<QuarantinedMessage> {
+        let mut messages = self.lock();
+        let (released, kept) = messages
+            .drain(..)
+            .partition(|q| q.sender_did.as_deref() == Some(sender_did));
+        *messages = kept;
+        released.into()
+    }
+
+    /// Drop a message. Returns whether it was queued.
+    pub fn discard(&self, message_id: &str) -> bool {
+        self.release(message_id).is_some()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn push(&self, message: QuarantinedMessage) {
+        let mut messages = self.lock();
+        while messages.len() >= self.capacity.max(1) {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<QuarantinedMessage>> {
+        self.messages.lock().expect("quarantine lock not poisoned")
+    }
+}
+
+impl Default for QuarantineQueue {
+    fn default() -> Self {
+        Self::new(1_000)
+    }
+}
+
+/// Verdict for one inbound message.
+#[derive(Debug, PartialEq, Eq)]
+enum Verdict {
+    Pass,
+    RateLimited,
+    Quarantine,
+    Reject,
+}
+
+/// Middleware that protects a public address from spam floods.
+///
+/// Senders are identified by the DID their envelope proves: an authcrypted
+/// message whose encrypting key belongs to its `from`. Plaintext and anoncrypt
+/// messages only *claim* a sender, so they are treated as anonymous whatever
+/// their `from` says. Each sender DID gets its own token bucket (anonymous
+/// senders share one); a sender over its [`RateLimit`] has its messages
+/// dropped. Senders that
+/// [`KnownSenders`] doesn't recognise are then handled by the
+/// [`UnknownSenderPolicy`], which can hold their messages in a
+/// [`QuarantineQueue`] for review.
+///
+/// Dropped and quarantined messages are consumed without a reply: answering
+/// a flood with problem reports doubles the traffic and tells the sender the
+/// address is live.
+///
+/// ```ignore
+/// let contacts = AllowList::from_iter(["did:peer:2.alice"]);
+/// let quarantine = QuarantineQueue::default();
+/// let router = Router::new()
+///     .route(BASIC_MESSAGE_TYPE, handler_fn(on_chat))?
+///     .layer(
+///         SenderFilter::new()
+///             .rate_limit(RateLimit::per_minute(30))
+///             .known_senders(contacts.clone())
+///             .unknown_senders(UnknownSenderPolicy::Quarantine)
+///             .quarantine(quarantine.clone()),
+///     );
+///
+/// // Later, once the user accepts Bob:
+/// contacts.insert("did:peer:2.bob");
+/// for held in quarantine.release_sender("did:peer:2.bob") { /* … */ }
+/// ```
+#[derive(Clone)]
+pub struct SenderFilter {
+    rate_limit: Option<RateLimit>,
+    buckets: Cache<String, Arc<Mutex<Bucket>>>,
+    known: Option<Arc<dyn KnownSenders>>,
+    unknown_policy: UnknownSenderPolicy,
+    quarantine: QuarantineQueue,
+}
+
+impl SenderFilter {
+    /// A filter that lets everything through until configured.
+    pub fn new() -> Self {
+        Self {
+            rate_limit: None,
+            buckets: bucket_cache(10_000),
+            known: None,
+            unknown_policy: UnknownSenderPolicy::Accept,
+            quarantine: QuarantineQueue::default(),
+        }
+    }
+
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Most senders to keep a bucket for. Beyond this, the buckets of the
+    /// least recently seen senders are evicted.
+    /// Default: 10 000
+    pub fn max_tracked_senders(mut self, max: usize) -> Self {
+        self.buckets = bucket_cache(max);
+        self
+    }
+
+    /// Which senders are known. Without this every sender DID counts as
+    /// known, and only anonymous messages are subject to the
+    /// [`UnknownSenderPolicy`].
+    pub fn known_senders(mut self, known: impl KnownSenders) -> Self {
+        self.known = Some(Arc::new(known));
+        self
+    }
+
+    /// Default: [`UnknownSenderPolicy::Accept`]
+    pub fn unknown_senders(mut self, policy: UnknownSenderPolicy) -> Self {
+        self.unknown_policy = policy;
+        self
+    }
+
+    /// Where quarantined messages go. Keep a clone to review them.
+    pub fn quarantine(mut self, queue: QuarantineQueue) -> Self {
+        self.quarantine = queue;
+        self
+    }
+
+    fn check(&self, sender_did: Option<&str>, now: Instant) -> Verdict {
+        if let Some(limit) = self.rate_limit
+            && !self.take_token(sender_did.unwrap_or(ANONYMOUS), limit, now)
+        {
+            return Verdict::RateLimited;
+        }
+
+        let known = match (sender_did, &self.known) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(did), Some(known)) => known.is_known(did),
+        };
+        if known {
+            return Verdict::Pass;
+        }
+        match self.unknown_policy {
+            UnknownSenderPolicy::Accept => Verdict::Pass,
+            UnknownSenderPolicy::Quarantine => Verdict::Quarantine,
+            UnknownSenderPolicy::Reject => Verdict::Reject,
+        }
+    }
+
+    fn take_token(&self, key: &str, limit: RateLimit, now: Instant) -> bool {
+        let burst = f64::from(limit.burst);
+        let bucket = self.buckets.get_with_by_ref(key, || {
+            Arc::new(Mutex::new(Bucket {
+                tokens: burst,
+                updated: now,
+            }))
+        });
+        let mut bucket = bucket.lock().expect("bucket lock not poisoned");
+        let elapsed = now.saturating_duration_since(bucket.updated);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * limit.per_second).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// The sender the filter keys on. Not `ctx.sender_did`: that is the message's
+/// `from`, which plaintext and anoncrypt senders can set to anything.
+fn proven_sender(message: &Message, meta: &UnpackMetadata) -> Option<String> {
+    meta.authenticated_sender(message.from.as_deref())
+        .map(str::to_string)
+}
+
+/// Token buckets for at most `max` senders, evicting the least recently used.
+fn bucket_cache(max: usize) -> Cache<String, Arc<Mutex<Bucket>>> {
+    Cache::builder()
+        .max_capacity(max as u64)
+        .eviction_policy(EvictionPolicy::lru())
+        .build()
+}
+
+impl Default for SenderFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl MiddlewareHandler for SenderFilter {
+    async fn handle(
+        &self,
+        ctx: HandlerContext,
+        message: Message,
+        meta: UnpackMetadata,
+        next: Next,
+    ) -> MiddlewareResult {
+        let sender = proven_sender(&message, &meta);
+        match self.check(sender.as_deref(), Instant::now()) {
+            Verdict::Pass => return next.run(ctx, message, meta).await,
+            Verdict::Quarantine => {
+                tracing::debug!(
+                    message_id = %message.id,
+                    sender = sender.as_deref().unwrap_or("<anon>"),
+                    "Quarantined message from unknown sender"
+                );
+                self.quarantine.push(QuarantinedMessage {
+                    listener_id: ctx.listener_id.clone(),
+                    sender_did: sender,
+                    message,
+                    meta,
+                    received_at: SystemTime::now(),
+                });
+            }
+            verdict => {
+                tracing::info!(
+                    message_id = %message.id,
+                    sender = sender.as_deref().unwrap_or("<anon>"),
+                    verdict = ?verdict,
+                    "Sender filter dropped message"
+                );
+                ctx.atm
+                    .get_tdk()
+                    .record_audit_event(AuditEvent::PolicyDenied {
+                        subject: sender.unwrap_or_else(|| "<anon>".to_string()),
+                        action: format!("deliver message {}", message.id),
+                        reason: match verdict {
+                            Verdict::RateLimited => "sender is rate limited",
+                            _ => "sender is unknown",
+                        }
+                        .to_string(),
+                    })
+                    .await;
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    /// Time for `limit` to refill one token.
+    fn token_interval(limit: RateLimit) -> Duration {
+        Duration::from_secs_f64(1.0 / limit.per_second)
+    }
+
+    fn msg(id: &str) -> Message {
+        Message::build(id.to_string(), "test".to_string(), json!({})).finalize()
+    }
+
+    fn quarantined(id: &str, sender: &str) -> QuarantinedMessage {
+        QuarantinedMessage {
+            listener_id: "l".into(),
+            sender_did: Some(sender.into()),
+            message: msg(id),
+            meta: UnpackMetadata::default(),
+            received_at: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn buckets_are_per_sender_and_refill() {
+        let limit = RateLimit::new(2, 1.0);
+        let filter = SenderFilter::new().rate_limit(limit);
+        let t0 = Instant::now();
+
+        assert_eq!(filter.check(Some("did:a"), t0), Verdict::Pass);
+        assert_eq!(filter.check(Some("did:a"), t0), Verdict::Pass);
+        assert_eq!(filter.check(Some("did:a"), t0), Verdict::RateLimited);
+        assert_eq!(filter.check(Some("did:b"), t0), Verdict::Pass);
+
+        let later = t0 + token_interval(limit);
+        assert_eq!(filter.check(Some("did:a"), later), Verdict::Pass);
+        assert_eq!(filter.check(Some("did:a"), later), Verdict::RateLimited);
+    }
+
+    #[test]
+    fn least_recently_seen_senders_are_evicted_at_capacity() {
+        let filter = SenderFilter::new()
+            .rate_limit(RateLimit::new(1, 1.0))
+            .max_tracked_senders(2);
+        let t0 = Instant::now();
+        for sender in ["did:a", "did:b", "did:c", "did:d"] {
+            filter.check(Some(sender), t0);
+            filter.buckets.run_pending_tasks();
+        }
+        assert_eq!(filter.buckets.entry_count(), 2);
+        assert!(!filter.buckets.contains_key("did:a"));
+        assert!(filter.buckets.contains_key("did:d"));
+        assert_eq!(filter.check(Some("did:d"), t0), Verdict::RateLimited);
+    }
+
+    #[test]
+    fn unknown_sender_policies() {
+        let contacts = AllowList::from_iter(["did:friend"]);
+        let filter = SenderFilter::new()
+            .known_senders(contacts.clone())
+            .unknown_senders(UnknownSenderPolicy::Quarantine);
+        let now = Instant::now();
+
+        assert_eq!(filter.check(Some("did:friend"), now), Verdict::Pass);
+        assert_eq!(filter.check(Some("did:stranger"), now), Verdict::Quarantine);
+        assert_eq!(filter.check(None, now), Verdict::Quarantine);
+
+        contacts.insert("did:stranger");
+        assert_eq!(filter.check(Some("did:stranger"), now), Verdict::Pass);
+
+        let rejecting = filter.unknown_senders(UnknownSenderPolicy::Reject);
+        assert_eq!(rejecting.check(Some("did:other"), now), Verdict::Reject);
+    }
+
+    #[test]
+    fn only_authcrypt_proves_a_sender() {
+        let mut message = msg("1");
+        message.from = Some("did:friend".into());
+        let authcrypt = UnpackMetadata {
+            encrypted: true,
+            authenticated: true,
+            encrypted_from_kid: Some("did:friend#key-1".into()),
+            ..Default::default()
+        };
+        let anoncrypt = UnpackMetadata {
+            encrypted: true,
+            anonymous_sender: true,
+            ..Default::default()
+        };
+        let other_key = UnpackMetadata {
+            encrypted_from_kid: Some("did:mallory#key-1".into()),
+            ..authcrypt.clone()
+        };
+        assert_eq!(
+            proven_sender(&message, &authcrypt).as_deref(),
+            Some("did:friend")
+        );
+        for meta in [&UnpackMetadata::default(), &anoncrypt, &other_key] {
+            assert_eq!(proven_sender(&message, meta), None);
+        }
+
+        // Forging a known `from` lands in the shared anonymous bucket.
+        let filter = SenderFilter::new()
+            .rate_limit(RateLimit::new(1, 1.0))
+            .known_senders(AllowList::from_iter(["did:friend"]))
+            .unknown_senders(UnknownSenderPolicy::Quarantine);
+        let now = Instant::now();
+        let forged = proven_sender(&message, &anoncrypt);
+        assert_eq!(filter.check(forged.as_deref(), now), Verdict::Quarantine);
+        assert_eq!(filter.check(None, now), Verdict::RateLimited);
+        assert_eq!(filter.check(Some("did:friend"), now), Verdict::Pass);
+    }
+
+    #[test]
+    fn without_known_senders_only_anonymous_is_unknown() {
+        let filter = SenderFilter::new().unknown_senders(UnknownSenderPolicy::Reject);
+        let now = Instant::now();
+        assert_eq!(filter.check(Some("did:any"), now), Verdict::Pass);
+        assert_eq!(filter.check(None, now), Verdict::Reject);
+    }
+
+    #[test]
+    fn quarantine_queue_is_bounded_and_releasable() {
+        let queue = QuarantineQueue::new(3);
+        for (id, sender) in [
+            ("1", "did:a"),
+            ("2", "did:b"),
+            ("3", "did:a"),
+            ("4", "did:a"),
+        ] {
+            queue.push(quarantined(id, sender));
+        }
+        assert_eq!(queue.len(), 3, "oldest message dropped");
+
+        assert_eq!(queue.release("2").unwrap().message.id, "2");
+        let from_a: Vec<_> = queue
+            .release_sender("did:a")
+            .into_iter()
+            .map(|q| q.message.id)
+            .collect();
+        assert_eq!(from_a, ["3", "4"]);
+        assert!(queue.is_empty());
+        assert!(!queue.discard("1"));
+    }
+}
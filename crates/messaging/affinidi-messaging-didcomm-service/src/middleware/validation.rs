@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use affinidi_messaging_didcomm::{Message, UnpackMetadata};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+
+use super::{MiddlewareHandler, MiddlewareResult, Next};
+use crate::error::{ValidationError, ValidationIssue};
+use crate::handler::HandlerContext;
+
+/// Checks the content of an inbound message before it reaches its handler.
+///
+/// Return every problem found rather than stopping at the first, so the
+/// problem report sent back to the peer is as useful as possible.
+pub trait MessageValidator: Send + Sync + 'static {
+    fn validate(&self, message: &Message) -> Result<(), Vec<ValidationIssue>>;
+}
+
+impl<F> MessageValidator for F
+where
+    F: Fn(&Message) -> Result<(), Vec<ValidationIssue>> + Send + Sync + 'static,
+{
+    fn validate(&self, message: &Message) -> Result<(), Vec<ValidationIssue>> {
+        self(message)
+    }
+}
+
+/// Accepts a message when its body deserializes into `T`.
+///
+/// Use the same type the handler extracts, so a body that passes validation
+/// is guaranteed to extract.
+pub struct TypedBody<T>(PhantomData<fn() -> T>);
+
+impl<T: DeserializeOwned + 'static> TypedBody<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: DeserializeOwned + 'static> Default for TypedBody<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: DeserializeOwned + 'static> MessageValidator for TypedBody<T> {
+    fn validate(&self, message: &Message) -> Result<(), Vec<ValidationIssue>> {
+        T::deserialize(&message.body)
+            .map(|_| ())
+            .map_err(|e| vec![ValidationIssue::new("", e.to_string())])
+    }
+}
+
+/// Validates the message body against a JSON Schema.
+#[cfg(feature = "json-schema")]
+pub struct JsonSchema(jsonschema::Validator);
+
+#[cfg(feature = "json-schema")]
+impl JsonSchema {
+    /// Compile `schema`. Fails when the schema itself is invalid.
+    pub fn new(schema: &serde_json::Value) -> Result<Self, crate::error::DIDCommServiceError> {
+        jsonschema::validator_for(schema)
+            .map(Self)
+            .map_err(|e| crate::error::DIDCommServiceError::InvalidSchema(e.to_string()))
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl MessageValidator for JsonSchema {
+    fn validate(&self, message: &Message) -> Result<(), Vec<ValidationIssue>> {
+        let issues: Vec<_> = self
+            .0
+            .iter_errors(&message.body)
+            .map(|e| ValidationIssue::new(e.instance_path.to_string(), e.to_string()))
+            .collect();
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
+
+/// Middleware that validates message bodies per message type.
+///
+/// Messages whose type has no registered validator pass through untouched.
+/// A message that fails is not handed to its handler: the middleware returns
+/// [`DIDCommServiceError::Validation`](crate::DIDCommServiceError::Validation),
+/// which the router passes to its [`ErrorHandler`](crate::ErrorHandler). The
+/// default error handler answers it with a problem report listing the issues.
+///
+/// ```ignore
+/// let router = Router::new()
+///     .route(ORDER_TYPE, handler_fn(place_order))?
+///     .layer(MessageValidation::new().typed::<Order>(ORDER_TYPE));
+/// ```
+#[derive(Clone, Default)]
+pub struct MessageValidation {
+    validators: HashMap<String, Vec<Arc<dyn MessageValidator>>>,
+}
+
+impl MessageValidation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `validator` on messages of `message_type`. Several validators may
+    /// be registered for one type; all of them run.
+    pub fn validator(mut self, message_type: &str, validator: impl MessageValidator) -> Self {
+        self.validators
+            .entry(message_type.to_string())
+            .or_default()
+            .push(Arc::new(validator));
+        self
+    }
+
+    /// Require bodies of `message_type` to deserialize into `T`.
+    pub fn typed<T: DeserializeOwned + 'static>(self, message_type: &str) -> Self {
+        self.validator(message_type, TypedBody::<T>::new())
+    }
+
+    /// Require bodies of `message_type` to match the JSON Schema `schema`.
+    #[cfg(feature = "json-schema")]
+    pub fn json_schema(
+        self,
+        message_type: &str,
+        schema: &serde_json::Value,
+    ) -> Result<Self, crate::error::DIDCommServiceError> {
+        Ok(self.validator(message_type, JsonSchema::new(schema)?))
+    }
+
+    pub(crate) fn check(&self, message: &Message) -> Result<(), ValidationError> {
+        let Some(validators) = self.validators.get(&message.typ) else {
+            return Ok(());
+        };
+        let issues: Vec<_> = validators
+            .iter()
+            .filter_map(|v| v.validate(message).err())
+            .flatten()
+            .collect();
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError {
+                message_type: message.typ.clone(),
+                message_id: message.id.clone(),
+                issues,
+            })
+        }
+    }
+}
+
+#[async_trait]
+impl MiddlewareHandler for MessageValidation {
+    async fn handle(
+        &self,
+        ctx: HandlerContext,
+        message: Message,
+        meta: UnpackMetadata,
+        next: Next,
+    ) -> MiddlewareResult {
+        if let Err(error) = self.check(&message) {
+            tracing::info!(
+                message_id = %message.id,
+                message_type = %message.typ,
+                sender = ctx.sender_did.as_deref().unwrap_or("<anon>"),
+                issues = error.issues.len(),
+                "Validation rejected message"
+            );
+            return Err(error.into());
+        }
+        next.run(ctx, message, meta).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::{Value, json};
+
+    const ORDER: &str = "https://example.com/order/1.0/place";
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Order {
+        item: String,
+        quantity: u32,
+    }
+
+    fn msg(typ: &str, body: Value) -> Message {
+        Message::build("msg-1".to_string(), typ.to_string(), body).finalize()
+    }
+
+    #[test]
+    fn unregistered_types_pass() {
+        let validation = MessageValidation::new().typed::<Order>(ORDER);
+        assert!(
+            validation
+                .check(&msg("https://example.com/other", json!({})))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn typed_body_accepts_and_rejects() {
+        let validation = MessageValidation::new().typed::<Order>(ORDER);
+        assert!(
+            validation
+                .check(&msg(ORDER, json!({ "item": "tea", "quantity": 2 })))
+                .is_ok()
+        );
+
+        let err = validation
+            .check(&msg(ORDER, json!({ "item": "tea", "quantity": -1 })))
+            .unwrap_err();
+        assert_eq!(err.message_type, ORDER);
+        assert_eq!(err.message_id, "msg-1");
+        assert_eq!(err.issues.len(), 1);
+    }
+
+    #[test]
+    fn all_validators_for_a_type_report() {
+        let validation =
+            MessageValidation::new()
+                .typed::<Order>(ORDER)
+                .validator(ORDER, |m: &Message| {
+                    if m.body.get("note").is_some() {
+                        Ok(())
+                    } else {
+                        Err(vec![ValidationIssue::new("/note", "note is required")])
+                    }
+                });
+        let err = validation.check(&msg(ORDER, json!({}))).unwrap_err();
+        assert_eq!(err.issues.len(), 2);
+        assert_eq!(err.issues[1].path, "/note");
+    }
+
+    #[cfg(feature = "json-schema")]
+    #[test]
+    fn json_schema_reports_each_issue() {
+        let validation = MessageValidation::new()
+            .json_schema(
+                ORDER,
+                &json!({
+                    "type": "object",
+                    "required": ["item", "quantity"],
+                    "properties": {
+                        "item": { "type": "string" },
+                        "quantity": { "type": "integer", "minimum": 1 }
+                    }
+                }),
+            )
+            .unwrap();
+        assert!(
+            validation
+                .check(&msg(ORDER, json!({ "item": "tea", "quantity": 1 })))
+                .is_ok()
+        );
+
+        let err = validation
+            .check(&msg(ORDER, json!({ "quantity": 0 })))
+            .unwrap_err();
+        let mut paths: Vec<_> = err.issues.iter().map(|i| i.path.as_str()).collect();
+        paths.sort_unstable();
+        assert_eq!(paths, ["", "/quantity"]);
+    }
+
+    #[cfg(feature = "json-schema")]
+    #[test]
+    fn invalid_schema_is_rejected() {
+        assert!(JsonSchema::new(&json!({ "type": 12 })).is_err());
+    }
+}
@@ -2,6 +2,8 @@ pub use affinidi_messaging_sdk::messages::problem_report::{
     ProblemReport, ProblemReportScope, ProblemReportSorter,
 };
 
+use crate::error::{ValidationError, join_issues};
+
 /// DIDComm error codes for server-side problem reports.
 pub mod codes {
     pub const ERROR_UNAUTHORIZED: &str = "e.p.msg.unauthorized";
@@ -9,6 +11,11 @@ pub mod codes {
     pub const ERROR_NOT_FOUND: &str = "e.p.msg.not-found";
     pub const ERROR_CONFLICT: &str = "e.p.msg.conflict";
     pub const ERROR_INTERNAL: &str = "e.p.msg.internal-error";
+    pub const ERROR_INVALID_MESSAGE: &str = "e.p.msg.invalid-message";
+    /// A protocol thread passed its deadline ([`TimerEvent::Expired`](crate::TimerEvent::Expired)).
+    pub const ERROR_EXPIRED: &str = "e.p.req.time";
+    /// A protocol thread is nearing its deadline ([`TimerEvent::Reminder`](crate::TimerEvent::Reminder)).
+    pub const WARNING_TIMING: &str = "w.p.req.time";
 }
 
 /// Convenience constructors and builders for server-side DIDComm problem reports.
@@ -67,6 +74,17 @@ impl ServiceProblemReport for ProblemReport {
     }
 }
 
+/// Problem report for a message rejected by
+/// [`MessageValidation`](crate::MessageValidation): code
+/// [`codes::ERROR_INVALID_MESSAGE`], with the message type and the issues as
+/// interpolation args.
+impl From<&ValidationError> for ProblemReport {
+    fn from(error: &ValidationError) -> Self {
+        Self::from_code(codes::ERROR_INVALID_MESSAGE, "Invalid {1} message: {2}")
+            .with_args(vec![error.message_type.clone(), join_issues(&error.issues)])
+    }
+}
+
 trait FromCode {
     fn from_code(code: &str, comment: impl Into<String>) -> Self;
 }
@@ -85,6 +103,7 @@ impl FromCode for ProblemReport {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ValidationIssue;
 
     #[test]
     fn unauthorized_sets_correct_code() {
@@ -155,6 +174,24 @@ mod tests {
         assert_eq!(body["escalate_to"], "support@example.com");
     }
 
+    #[test]
+    fn validation_error_becomes_invalid_message_report() {
+        let error = ValidationError {
+            message_type: "https://example.com/order".into(),
+            message_id: "1".into(),
+            issues: vec![
+                ValidationIssue::new("/quantity", "must be positive"),
+                ValidationIssue::new("", "missing field `item`"),
+            ],
+        };
+        let r = ProblemReport::from(&error);
+        assert_eq!(r.code, codes::ERROR_INVALID_MESSAGE);
+        assert_eq!(
+            r.interpolation(),
+            "Invalid https://example.com/order message: /quantity: must be positive; missing field `item`"
+        );
+    }
+
     #[test]
     fn default_has_no_args_or_escalate() {
         let r = ProblemReport::internal_error("fail");
@@ -33,6 +33,9 @@ pub(crate) fn convert_meta(
         encrypted_from_kid: sdk_meta.encrypted_from_kid,
         encrypted_to_kids: sdk_meta.encrypted_to_kids,
         sign_from: sdk_meta.sign_from,
+        algorithms: sdk_meta.algorithms,
+        forward_hops: sdk_meta.forward_hops,
+        timing: sdk_meta.timing,
     }
 }
 
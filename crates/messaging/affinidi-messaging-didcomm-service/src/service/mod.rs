@@ -1,4 +1,4 @@
-mod listener;
+pub(crate) mod listener;
 mod mediator;
 mod restart;
 
@@ -0,0 +1,610 @@
+//! Deadlines for long-running protocol threads.
+//!
+//! A protocol such as present-proof can wait minutes or days for the other
+//! party. [`ProtocolTimers`] tracks one deadline per thread (`thid`), emits a
+//! [`TimerEvent`] when a reminder or the deadline is reached, and can tell the
+//! peer with a problem report. Timers opened with [`ProtocolTimers::open`] are
+//! written to a JSON file on every change, so a restart picks them up again;
+//! deadlines that passed while the process was down fire as soon as the
+//! scheduler starts. A fired timer is only removed (or its reminder cleared)
+//! once its event has been handed to the receiver, so an event the process
+//! crashed before delivering fires again on restart: events are delivered at
+//! least once.
+//!
+//! The scheduler doesn't know the protocol's states: the application moves
+//! the thread to its timed-out state when it receives
+//! [`TimerEvent::Expired`], and cancels the timer when the thread completes.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use affinidi_messaging_didcomm::Message;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Notify, mpsc};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::error::DIDCommServiceError;
+use crate::handler::HandlerContext;
+use crate::problem_report::{ProblemReport, ServiceProblemReport, codes};
+use crate::service::AffinidiMessageService;
+use crate::transport::PROBLEM_REPORT_TYPE;
+use crate::utils::new_message_id;
+
+const EVENT_CHANNEL_CAPACITY: usize = 64;
+
+/// A deadline on one protocol thread.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolTimer {
+    /// Thread the deadline applies to. One timer per thread: scheduling
+    /// another replaces it.
+    pub thid: String,
+    /// Application label for the protocol or step, e.g.
+    /// `present-proof/request`.
+    pub protocol: String,
+    /// When the thread times out.
+    pub expires_at: SystemTime,
+    /// When to remind, if at all. Cleared once the reminder has fired.
+    pub remind_at: Option<SystemTime>,
+    /// Peer to send problem reports to, and the listener to send them through.
+    pub notify: Option<TimerNotify>,
+}
+
+/// Where [`ProtocolTimers`] sends reminder and expiry problem reports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimerNotify {
+    pub listener_id: String,
+    pub peer_did: String,
+}
+
+impl ProtocolTimer {
+    /// A timer on `thid` expiring `timeout` from now.
+    pub fn new(thid: impl Into<String>, protocol: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            thid: thid.into(),
+            protocol: protocol.into(),
+            expires_at: SystemTime::now() + timeout,
+            remind_at: None,
+            notify: None,
+        }
+    }
+
+    /// A timer on the thread of the message being handled, notifying its
+    /// sender through the same listener when the sender is known.
+    pub fn for_thread(
+        ctx: &HandlerContext,
+        protocol: impl Into<String>,
+        timeout: Duration,
+    ) -> Self {
+        let mut timer = Self::new(ctx.thread_id.clone(), protocol, timeout);
+        timer.notify = ctx.sender_did.as_ref().map(|peer_did| TimerNotify {
+            listener_id: ctx.listener_id.clone(),
+            peer_did: peer_did.clone(),
+        });
+        timer
+    }
+
+    /// Remind `before` the deadline. Ignored if that is already past.
+    pub fn with_reminder(mut self, before: Duration) -> Self {
+        self.remind_at = self.expires_at.checked_sub(before);
+        self
+    }
+
+    /// Send problem reports for this timer to `peer_did` via `listener_id`.
+    pub fn with_notify(
+        mut self,
+        listener_id: impl Into<String>,
+        peer_did: impl Into<String>,
+    ) -> Self {
+        self.notify = Some(TimerNotify {
+            listener_id: listener_id.into(),
+            peer_did: peer_did.into(),
+        });
+        self
+    }
+
+    /// Don't send problem reports for this timer; only emit events.
+    pub fn without_notify(mut self) -> Self {
+        self.notify = None;
+        self
+    }
+
+    /// The next time this timer needs attention.
+    fn next_due(&self) -> SystemTime {
+        self.remind_at
+            .map_or(self.expires_at, |remind_at| remind_at.min(self.expires_at))
+    }
+}
+
+/// Emitted by [`ProtocolTimers::start`] as timers fire.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TimerEvent {
+    /// The reminder time was reached; the thread is still open.
+    Reminder(ProtocolTimer),
+    /// The deadline passed. The timer has been removed; the thread should move
+    /// to its timed-out state.
+    Expired(ProtocolTimer),
+}
+
+impl TimerEvent {
+    pub fn timer(&self) -> &ProtocolTimer {
+        match self {
+            Self::Reminder(timer) | Self::Expired(timer) => timer,
+        }
+    }
+
+    /// The problem report telling the peer about this event: warning
+    /// [`codes::WARNING_TIMING`] for a reminder, error
+    /// [`codes::ERROR_EXPIRED`] for an expiry.
+    pub fn problem_report(&self) -> ProblemReport {
+        match self {
+            Self::Reminder(timer) => ProblemReport {
+                code: codes::WARNING_TIMING.to_string(),
+                comment: "{1} expires in {2} seconds".to_string(),
+                args: vec![timer.protocol.clone(), seconds_until(timer.expires_at)],
+                escalate_to: None,
+            },
+            Self::Expired(timer) => ProblemReport {
+                code: codes::ERROR_EXPIRED.to_string(),
+                comment: "{1} expired without a response".to_string(),
+                args: vec![timer.protocol.clone()],
+                escalate_to: None,
+            },
+        }
+    }
+}
+
+fn seconds_until(time: SystemTime) -> String {
+    time.duration_since(SystemTime::now())
+        .unwrap_or_default()
+        .as_secs()
+        .to_string()
+}
+
+/// Per-thread protocol deadlines. Clones share the timers.
+#[derive(Clone, Default)]
+pub struct ProtocolTimers {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    timers: Mutex<HashMap<String, ProtocolTimer>>,
+    /// JSON file the timers are persisted to; `None` keeps them in memory
+    path: Option<PathBuf>,
+    /// Wakes the scheduler when a timer is added or removed
+    changed: Notify,
+    /// Serializes file writes so an older snapshot never overwrites a newer one
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+impl ProtocolTimers {
+    /// Timers kept in memory only: they are lost on restart.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Timers persisted to `path`, loading any saved by a previous run. The
+    /// file is created on the first change.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, DIDCommServiceError> {
+        let path = path.as_ref().to_path_buf();
+        let timers: Vec<ProtocolTimer> = match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                DIDCommServiceError::TimerStore(format!(
+                    "couldn't parse timers ({}): {e}",
+                    path.display()
+                ))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(DIDCommServiceError::TimerStore(format!(
+                    "couldn't read timers ({}): {e}",
+                    path.display()
+                )));
+            }
+        };
+        debug!(path = %path.display(), count = timers.len(), "Loaded protocol timers");
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                timers: Mutex::new(
+                    timers
+                        .into_iter()
+                        .map(|timer| (timer.thid.clone(), timer))
+                        .collect(),
+                ),
+                path: Some(path),
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Start (or replace) the timer on `timer.thid`.
+    pub async fn schedule(&self, timer: ProtocolTimer) -> Result<(), DIDCommServiceError> {
+        self.lock().insert(timer.thid.clone(), timer);
+        self.inner.changed.notify_one();
+        self.persist().await
+    }
+
+    /// Stop the timer on `thid`, e.g. because the thread completed. Returns
+    /// the timer if there was one.
+    pub async fn cancel(&self, thid: &str) -> Result<Option<ProtocolTimer>, DIDCommServiceError> {
+        let removed = self.lock().remove(thid);
+        if removed.is_some() {
+            self.inner.changed.notify_one();
+            self.persist().await?;
+        }
+        Ok(removed)
+    }
+
+    pub fn get(&self, thid: &str) -> Option<ProtocolTimer> {
+        self.lock().get(thid).cloned()
+    }
+
+    /// All pending timers, soonest first.
+    pub fn pending(&self) -> Vec<ProtocolTimer> {
+        let mut timers: Vec<ProtocolTimer> = self.lock().values().cloned().collect();
+        timers.sort_by_key(ProtocolTimer::next_due);
+        timers
+    }
+
+    /// Run the scheduler until `shutdown` is cancelled, returning the events
+    /// as timers fire. With a `service`, timers that have a
+    /// [`TimerNotify`] also send the event's problem report to the peer;
+    /// a failed send is logged and the event still emitted.
+    ///
+    /// Events are delivered on a bounded channel: the scheduler waits for the
+    /// receiver rather than drop a timeout. Dropping the receiver stops it;
+    /// timers whose events weren't delivered stay pending, and fire again
+    /// when the scheduler next starts.
+    pub fn start(
+        &self,
+        service: Option<AffinidiMessageService>,
+        shutdown: CancellationToken,
+    ) -> mpsc::Receiver<TimerEvent> {
+        let (tx, rx) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+        let timers = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let changed = timers.inner.changed.notified();
+                let now = SystemTime::now();
+                let events = timers.due(now);
+                if !events.is_empty() {
+                    for event in events {
+                        if let Some(service) = &service {
+                            notify(service, &event).await;
+                        }
+                        // Settled only once delivered: a crash before this
+                        // leaves the timer in the file, to fire on restart
+                        if tx.send(event.clone()).await.is_err() {
+                            return;
+                        }
+                        timers.settle(&event, now);
+                        if let Err(e) = timers.persist().await {
+                            warn!(error = %e, "Failed to persist protocol timers");
+                        }
+                    }
+                    continue;
+                }
+
+                let sleep = timers.next_due().map(|due| {
+                    due.duration_since(SystemTime::now())
+                        .unwrap_or(Duration::ZERO)
+                });
+                tokio::select! {
+                    _ = shutdown.cancelled() => return,
+                    _ = tx.closed() => return,
+                    _ = changed => {}
+                    _ = async {
+                        match sleep {
+                            Some(sleep) => tokio::time::sleep(sleep).await,
+                            None => std::future::pending().await,
+                        }
+                    } => {}
+                }
+            }
+        });
+
+        rx
+    }
+
+    /// The events due at `now`, leaving the timers as they are until
+    /// [`settle`](Self::settle)d. A timer whose deadline has also passed only
+    /// expires: there's nothing left to remind about.
+    fn due(&self, now: SystemTime) -> Vec<TimerEvent> {
+        let timers = self.lock();
+        let mut events: Vec<TimerEvent> = timers
+            .values()
+            .filter_map(|timer| {
+                if timer.expires_at <= now {
+                    Some(TimerEvent::Expired(timer.clone()))
+                } else if timer.remind_at.is_some_and(|remind_at| remind_at <= now) {
+                    Some(TimerEvent::Reminder(ProtocolTimer {
+                        remind_at: None,
+                        ..timer.clone()
+                    }))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        events.sort_by_key(|event| event.timer().next_due());
+        events
+    }
+
+    /// Record that `event`, taken by [`due`](Self::due) at `now`, was
+    /// delivered: remove the expired timer, or clear the fired reminder.
+    /// A timer rescheduled in the meantime is left alone.
+    fn settle(&self, event: &TimerEvent, now: SystemTime) {
+        let mut timers = self.lock();
+        let fired = event.timer();
+        let Some(timer) = timers.get_mut(&fired.thid) else {
+            return;
+        };
+        if timer.expires_at != fired.expires_at {
+            return;
+        }
+        match event {
+            TimerEvent::Expired(_) => {
+                timers.remove(&fired.thid);
+            }
+            TimerEvent::Reminder(_) => {
+                if timer.remind_at.is_some_and(|remind_at| remind_at <= now) {
+                    timer.remind_at = None;
+                }
+            }
+        }
+    }
+
+    fn next_due(&self) -> Option<SystemTime> {
+        self.lock().values().map(ProtocolTimer::next_due).min()
+    }
+
+    /// Write the timers to the file, via a temporary file so a crash mid-write
+    /// leaves the previous copy intact.
+    async fn persist(&self) -> Result<(), DIDCommServiceError> {
+        let Some(path) = &self.inner.path else {
+            return Ok(());
+        };
+
+        let _guard = self.inner.write_lock.lock().await;
+        let json = serde_json::to_vec_pretty(&self.pending()).map_err(|e| {
+            DIDCommServiceError::TimerStore(format!("couldn't serialize timers: {e}"))
+        })?;
+        let tmp = path.with_extension("tmp");
+        let write = async {
+            tokio::fs::write(&tmp, json).await?;
+            tokio::fs::rename(&tmp, path).await
+        };
+        write.await.map_err(|e| {
+            DIDCommServiceError::TimerStore(format!(
+                "couldn't write timers ({}): {e}",
+                path.display()
+            ))
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ProtocolTimer>> {
+        self.inner
+            .timers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+async fn notify(service: &AffinidiMessageService, event: &TimerEvent) {
+    let timer = event.timer();
+    let Some(notify) = &timer.notify else {
+        return;
+    };
+
+    let message = Message::build(
+        new_message_id(),
+        PROBLEM_REPORT_TYPE.to_string(),
+        event.problem_report().to_body(),
+    )
+    .to(notify.peer_did.clone())
+    .thid(timer.thid.clone())
+    .finalize();
+
+    if let Err(e) = service
+        .send_message(&notify.listener_id, message, &notify.peer_did)
+        .await
+    {
+        warn!(thid = %timer.thid, peer = %notify.peer_did, error = %e, "Failed to send timer problem report");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn timer(thid: &str, expires_in: Duration) -> ProtocolTimer {
+        ProtocolTimer::new(thid, "present-proof/request", expires_in)
+    }
+
+    /// What the scheduler does at `now`, with every event delivered.
+    fn fire(timers: &ProtocolTimers, now: SystemTime) -> Vec<TimerEvent> {
+        let events = timers.due(now);
+        for event in &events {
+            timers.settle(event, now);
+        }
+        events
+    }
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("protocol-timers-{}.json", new_message_id()))
+    }
+
+    #[tokio::test]
+    async fn reminder_fires_before_expiry() {
+        let timers = ProtocolTimers::new();
+        let t = timer("thread-1", 10 * MINUTE).with_reminder(2 * MINUTE);
+        timers.schedule(t.clone()).await.unwrap();
+
+        let now = SystemTime::now();
+        assert!(fire(&timers, now).is_empty());
+
+        let events = fire(&timers, now + 9 * MINUTE);
+        assert!(matches!(&events[..], [TimerEvent::Reminder(t)] if t.thid == "thread-1"));
+        assert_eq!(timers.get("thread-1").unwrap().remind_at, None);
+        assert!(fire(&timers, now + 9 * MINUTE).is_empty());
+
+        let events = fire(&timers, now + 11 * MINUTE);
+        assert!(matches!(&events[..], [TimerEvent::Expired(t)] if t.thid == "thread-1"));
+        assert!(timers.get("thread-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn overdue_timer_only_expires() {
+        let timers = ProtocolTimers::new();
+        timers
+            .schedule(timer("thread-1", MINUTE).with_reminder(MINUTE / 2))
+            .await
+            .unwrap();
+
+        let events = fire(&timers, SystemTime::now() + 2 * MINUTE);
+        assert!(matches!(&events[..], [TimerEvent::Expired(_)]));
+    }
+
+    #[tokio::test]
+    async fn cancel_and_replace() {
+        let timers = ProtocolTimers::new();
+        timers.schedule(timer("thread-1", MINUTE)).await.unwrap();
+        timers
+            .schedule(timer("thread-1", 10 * MINUTE))
+            .await
+            .unwrap();
+        timers
+            .schedule(timer("thread-2", 5 * MINUTE))
+            .await
+            .unwrap();
+
+        let pending = timers.pending();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].thid, "thread-2");
+
+        assert!(timers.cancel("thread-2").await.unwrap().is_some());
+        assert!(timers.cancel("thread-2").await.unwrap().is_none());
+        assert!(fire(&timers, SystemTime::now() + 2 * MINUTE).is_empty());
+    }
+
+    #[tokio::test]
+    async fn timers_survive_reopen() {
+        let path = temp_path();
+        let t = timer("thread-1", 10 * MINUTE)
+            .with_reminder(MINUTE)
+            .with_notify("listener-1", "did:example:alice");
+
+        let timers = ProtocolTimers::open(&path).await.unwrap();
+        timers.schedule(t.clone()).await.unwrap();
+        timers.schedule(timer("thread-2", MINUTE)).await.unwrap();
+        timers.cancel("thread-2").await.unwrap();
+        drop(timers);
+
+        let reopened = ProtocolTimers::open(&path).await.unwrap();
+        assert_eq!(reopened.pending(), vec![t]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn scheduler_emits_expiry() {
+        let timers = ProtocolTimers::new();
+        let shutdown = CancellationToken::new();
+        let mut events = timers.start(None, shutdown.clone());
+
+        // Scheduled after the scheduler is already waiting
+        timers
+            .schedule(timer("thread-1", Duration::from_millis(50)))
+            .await
+            .unwrap();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, TimerEvent::Expired(t) if t.thid == "thread-1"));
+        assert!(timers.pending().is_empty());
+        shutdown.cancel();
+    }
+
+    #[tokio::test]
+    async fn undelivered_expiry_fires_after_restart() {
+        let path = temp_path();
+        let timers = ProtocolTimers::open(&path).await.unwrap();
+        timers
+            .schedule(timer("thread-1", Duration::ZERO))
+            .await
+            .unwrap();
+
+        // The receiver is gone before the scheduler first runs, so the expiry
+        // is never delivered
+        let shutdown = CancellationToken::new();
+        drop(timers.start(None, shutdown.clone()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown.cancel();
+        drop(timers);
+
+        let reopened = ProtocolTimers::open(&path).await.unwrap();
+        assert_eq!(reopened.pending().len(), 1);
+
+        let shutdown = CancellationToken::new();
+        let mut events = reopened.start(None, shutdown.clone());
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, TimerEvent::Expired(t) if t.thid == "thread-1"));
+
+        // Removed from the file once delivered
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !ProtocolTimers::open(&path)
+                .await
+                .unwrap()
+                .pending()
+                .is_empty()
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        shutdown.cancel();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn settle_leaves_a_rescheduled_timer() {
+        let timers = ProtocolTimers::new();
+        timers.schedule(timer("thread-1", MINUTE)).await.unwrap();
+        let now = SystemTime::now() + 2 * MINUTE;
+        let events = timers.due(now);
+        assert!(matches!(&events[..], [TimerEvent::Expired(_)]));
+
+        // Rescheduled while the expiry was being delivered
+        timers
+            .schedule(timer("thread-1", 10 * MINUTE))
+            .await
+            .unwrap();
+        timers.settle(&events[0], now);
+        assert!(timers.get("thread-1").is_some());
+    }
+
+    #[test]
+    fn problem_report_codes() {
+        let t = timer("thread-1", MINUTE);
+        let reminder = TimerEvent::Reminder(t.clone()).problem_report();
+        assert_eq!(reminder.code, codes::WARNING_TIMING);
+        assert_eq!(reminder.args[0], "present-proof/request");
+
+        let expired = TimerEvent::Expired(t).problem_report();
+        assert_eq!(expired.code, codes::ERROR_EXPIRED);
+    }
+}
@@ -0,0 +1,453 @@
+//! Receiving DIDComm messages over plain HTTPS.
+//!
+//! Some peers deliver DIDComm by `POST`ing packed messages straight to a
+//! service endpoint rather than through a mediator. [`WebhookEndpoint`] turns
+//! a [`DIDCommHandler`] — usually a [`Router`](crate::Router) — into an axum
+//! route that accepts them:
+//!
+//! ```ignore
+//! let router = Router::new()
+//!     .route(ORDER_TYPE, handler_fn(place_order))?
+//!     .layer(MessagePolicy::new().require_encrypted(true));
+//!
+//! let app = axum::Router::new()
+//!     .nest("/didcomm", WebhookEndpoint::new(atm, profile, router).into_router());
+//! ```
+//!
+//! Each request body is unpacked with the ATM (and so the TDK shared state and
+//! secrets) of the endpoint's profile, then handed to the handler with the
+//! same [`HandlerContext`] a mediator listener builds. The HTTP response is:
+//!
+//! - `202 Accepted`, empty, once the handler has taken the message. A reply
+//!   from the handler is packed and sent to the sender through the profile's
+//!   mediator.
+//! - `200 OK` with the packed reply (`application/didcomm-encrypted+json`)
+//!   when the message asked for it in-band with a `return_route` header of
+//!   `all` or `thread`.
+//! - `4xx` / `5xx` with a plaintext problem report
+//!   (`application/didcomm-plain+json`) when the message can't be read, isn't
+//!   authenticated, or the handler fails. Unpack failures get a generic
+//!   `400` so callers learn nothing about why.
+//!
+//! The endpoint is reachable by anyone who can reach the URL, and a message's
+//! `from` is whatever the poster wrote. The ATM's secrets are shared by all of
+//! its profiles, so a message is only accepted when it was encrypted to (and,
+//! if it has a `to`, addressed to) the endpoint's own profile. By default only authcrypted messages
+//! whose encrypting key belongs to their `from` DID are accepted, and only
+//! that proven DID becomes [`HandlerContext::sender_did`]. Call
+//! [`with_require_authenticated(false)`](WebhookEndpoint::with_require_authenticated)
+//! to also accept plaintext and anoncrypt messages; they reach the handler
+//! with no `sender_did`. Layer [`MessagePolicy`](crate::MessagePolicy) and
+//! [`SenderFilter`](crate::SenderFilter) onto the router to narrow things
+//! further.
+
+use std::sync::Arc;
+
+use affinidi_messaging_didcomm::{Message, UnpackMetadata};
+use affinidi_messaging_sdk::{ATM, profiles::ATMProfile};
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, State, rejection::BytesRejection},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use tracing::{debug, warn};
+
+use crate::handler::{DIDCommHandler, HandlerContext};
+use crate::problem_report::{ProblemReport, ServiceProblemReport};
+use crate::response::DIDCommResponse;
+use crate::service::listener::convert_meta;
+use crate::transport::{self, PROBLEM_REPORT_TYPE};
+use crate::utils::{get_parent_thread_id, get_thread_id, new_message_id};
+
+/// Content type of a packed (encrypted) DIDComm message.
+pub const ENCRYPTED_CONTENT_TYPE: &str = "application/didcomm-encrypted+json";
+/// Content type of a plaintext DIDComm message.
+pub const PLAIN_CONTENT_TYPE: &str = "application/didcomm-plain+json";
+
+/// Largest request body accepted by default (1 MiB).
+pub const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// An HTTPS endpoint that receives DIDComm messages for one profile.
+///
+/// Cloning is cheap — the ATM, profile and handler are shared.
+#[derive(Clone)]
+pub struct WebhookEndpoint {
+    listener_id: String,
+    atm: ATM,
+    profile: Arc<ATMProfile>,
+    handler: Arc<dyn DIDCommHandler>,
+    max_body_bytes: usize,
+    require_authenticated: bool,
+}
+
+impl WebhookEndpoint {
+    /// Receive messages for `profile`, unpacked with `atm` and dispatched to
+    /// `handler`.
+    pub fn new(atm: ATM, profile: Arc<ATMProfile>, handler: impl DIDCommHandler) -> Self {
+        Self {
+            listener_id: "webhook".into(),
+            atm,
+            profile,
+            handler: Arc::new(handler),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            require_authenticated: true,
+        }
+    }
+
+    /// The `listener_id` handlers see in their [`HandlerContext`].
+    /// Default: `webhook`.
+    pub fn with_listener_id(mut self, listener_id: impl Into<String>) -> Self {
+        self.listener_id = listener_id.into();
+        self
+    }
+
+    /// Reject request bodies larger than `max_body_bytes` with
+    /// `413 Payload Too Large`. Default: [`DEFAULT_MAX_BODY_BYTES`].
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    /// Whether to reject messages that don't prove their sender with
+    /// authcrypt. When `false`, plaintext and anoncrypt messages are
+    /// dispatched with no [`HandlerContext::sender_did`]. Default: `true`.
+    pub fn with_require_authenticated(mut self, require_authenticated: bool) -> Self {
+        self.require_authenticated = require_authenticated;
+        self
+    }
+
+    /// An axum router that accepts `POST /`. Mount it with
+    /// [`nest`](axum::Router::nest) or [`merge`](axum::Router::merge).
+    pub fn into_router<S>(self) -> axum::Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let max_body_bytes = self.max_body_bytes;
+        axum::Router::new()
+            .route("/", post(receive_request))
+            .layer(DefaultBodyLimit::max(max_body_bytes))
+            .with_state(self)
+    }
+
+    /// Unpack and dispatch one request body.
+    ///
+    /// This is what the axum route runs; call it directly to serve the
+    /// endpoint from another HTTP stack.
+    pub async fn receive(&self, body: &[u8]) -> WebhookResponse {
+        if body.len() > self.max_body_bytes {
+            return self.problem(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                ProblemReport::bad_request("Message is larger than {1} bytes")
+                    .with_args(vec![self.max_body_bytes.to_string()]),
+                None,
+            );
+        }
+        let Ok(packed) = std::str::from_utf8(body) else {
+            return self.problem(
+                StatusCode::BAD_REQUEST,
+                ProblemReport::bad_request("Message is not valid UTF-8"),
+                None,
+            );
+        };
+
+        let (message, meta) = match self.atm.unpack(packed).await {
+            Ok(unpacked) => unpacked,
+            Err(e) => {
+                debug!(profile = %self.profile.inner.alias, error = %e, "Failed to unpack webhook message");
+                return self.problem(
+                    StatusCode::BAD_REQUEST,
+                    ProblemReport::bad_request("Message could not be unpacked"),
+                    None,
+                );
+            }
+        };
+
+        let meta = convert_meta(meta);
+        if !self.is_addressed_to_profile(&message, &meta) {
+            debug!(
+                profile = %self.profile.inner.alias,
+                message_id = %message.id,
+                "Rejected webhook message addressed to another DID"
+            );
+            return self.problem(
+                StatusCode::BAD_REQUEST,
+                ProblemReport::bad_request("Message is not addressed to this endpoint"),
+                None,
+            );
+        }
+
+        let sender_did = meta
+            .authenticated_sender(message.from.as_deref())
+            .map(str::to_string);
+        if sender_did.is_none() && self.require_authenticated {
+            debug!(
+                profile = %self.profile.inner.alias,
+                message_id = %message.id,
+                "Rejected webhook message that does not authenticate its sender"
+            );
+            return self.problem(
+                StatusCode::BAD_REQUEST,
+                ProblemReport::bad_request("Message must be authcrypted by its sender"),
+                None,
+            );
+        }
+
+        self.atm
+            .get_tdk()
+            .usage_tracker()
+            .record_message_received(&self.profile.inner.did, body.len());
+
+        let ctx = HandlerContext {
+            listener_id: self.listener_id.clone(),
+            atm: self.atm.clone(),
+            profile: self.profile.clone(),
+            sender_did,
+            message_id: message.id.clone(),
+            thread_id: get_thread_id(&message),
+            parent_thread_id: get_parent_thread_id(&message, false),
+        };
+        let return_route = wants_return_route(&message);
+
+        match self.handler.handle(ctx.clone(), message, meta).await {
+            Ok(None) => WebhookResponse::accepted(),
+            Ok(Some(response)) if return_route => self.reply_in_band(&ctx, response).await,
+            Ok(Some(response)) => {
+                // Answer the POST now; the reply travels through the mediator
+                tokio::spawn(async move {
+                    let result = match response.into_message(&ctx) {
+                        Ok(message) => transport::send_response(&ctx, message).await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = result {
+                        warn!(profile = %ctx.profile.inner.alias, error = %e, "Failed to send response");
+                    }
+                });
+                WebhookResponse::accepted()
+            }
+            Err(e) => {
+                warn!(
+                    profile = %ctx.profile.inner.alias,
+                    message_id = %ctx.message_id,
+                    error = %e,
+                    "Unhandled handler error"
+                );
+                self.problem(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ProblemReport::internal_error(e.to_string()),
+                    Some(ctx.thread_id),
+                )
+            }
+        }
+    }
+
+    /// Whether every key `message` was encrypted to, and every DID in its
+    /// `to`, belongs to this endpoint's profile.
+    fn is_addressed_to_profile(&self, message: &Message, meta: &UnpackMetadata) -> bool {
+        let did = self.profile.inner.did.as_str();
+        let is_profile = |id: &str| id.split('#').next() == Some(did);
+        meta.encrypted_to_kids.iter().all(|kid| is_profile(kid))
+            && message
+                .to
+                .as_ref()
+                .is_none_or(|to| to.iter().all(|to| is_profile(to)))
+    }
+
+    /// Pack `response` for the sender and return it as the HTTP body.
+    async fn reply_in_band(
+        &self,
+        ctx: &HandlerContext,
+        response: DIDCommResponse,
+    ) -> WebhookResponse {
+        let Some(sender) = ctx.sender_did.as_deref() else {
+            warn!(
+                profile = %ctx.profile.inner.alias,
+                "Anonymous sender asked for a return route, dropping the reply"
+            );
+            return WebhookResponse::accepted();
+        };
+
+        let packed = match response.into_message(ctx) {
+            Ok(message) => {
+                let did = &self.profile.inner.did;
+                self.atm
+                    .pack_encrypted(&message, sender, Some(did), Some(did))
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+
+        match packed {
+            Ok((packed, _)) => WebhookResponse {
+                status: StatusCode::OK,
+                content_type: Some(ENCRYPTED_CONTENT_TYPE),
+                body: packed,
+            },
+            Err(e) => {
+                warn!(profile = %ctx.profile.inner.alias, error = %e, "Failed to pack in-band reply");
+                self.problem(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    ProblemReport::internal_error("Reply could not be packed: {1}")
+                        .with_args(vec![e]),
+                    Some(ctx.thread_id.clone()),
+                )
+            }
+        }
+    }
+
+    fn problem(
+        &self,
+        status: StatusCode,
+        report: ProblemReport,
+        pthid: Option<String>,
+    ) -> WebhookResponse {
+        WebhookResponse::problem_report(status, &self.profile.inner.did, &report, pthid)
+    }
+}
+
+/// What a [`WebhookEndpoint`] answers to a request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebhookResponse {
+    pub status: StatusCode,
+    /// `None` for an empty body.
+    pub content_type: Option<&'static str>,
+    pub body: String,
+}
+
+impl WebhookResponse {
+    /// `202 Accepted` with no body.
+    pub fn accepted() -> Self {
+        Self {
+            status: StatusCode::ACCEPTED,
+            content_type: None,
+            body: String::new(),
+        }
+    }
+
+    /// `status` with a plaintext problem report from `from`, whose `pthid`
+    /// is the thread of the failed message when it is known.
+    pub fn problem_report(
+        status: StatusCode,
+        from: &str,
+        report: &ProblemReport,
+        pthid: Option<String>,
+    ) -> Self {
+        let mut builder = Message::build(
+            new_message_id(),
+            PROBLEM_REPORT_TYPE.to_string(),
+            report.to_body(),
+        )
+        .from(from.to_string());
+        if let Some(pthid) = pthid {
+            builder = builder.pthid(pthid);
+        }
+        let message = builder.finalize();
+
+        Self {
+            status,
+            content_type: Some(PLAIN_CONTENT_TYPE),
+            body: serde_json::to_string(&message).unwrap_or_else(|_| report.to_body().to_string()),
+        }
+    }
+}
+
+impl IntoResponse for WebhookResponse {
+    fn into_response(self) -> Response {
+        match self.content_type {
+            Some(content_type) => (
+                self.status,
+                [(header::CONTENT_TYPE, content_type)],
+                self.body,
+            )
+                .into_response(),
+            None => self.status.into_response(),
+        }
+    }
+}
+
+async fn receive_request(
+    State(endpoint): State<WebhookEndpoint>,
+    body: Result<Bytes, BytesRejection>,
+) -> WebhookResponse {
+    match body {
+        Ok(body) => endpoint.receive(&body).await,
+        Err(rejection) => endpoint.problem(
+            rejection.status(),
+            ProblemReport::bad_request(rejection.body_text()),
+            None,
+        ),
+    }
+}
+
+/// Whether `message` asks for its reply on the same connection.
+fn wants_return_route(message: &Message) -> bool {
+    message
+        .extra
+        .get("return_route")
+        .and_then(|value| value.as_str())
+        .is_some_and(|value| value == "all" || value == "thread")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(extra: Option<&str>) -> Message {
+        let mut builder = Message::build("id", "https://example.com/t", json!({}));
+        if let Some(value) = extra {
+            builder = builder.header("return_route".into(), json!(value));
+        }
+        builder.finalize()
+    }
+
+    #[test]
+    fn return_route_is_read_from_the_header() {
+        assert!(wants_return_route(&message(Some("all"))));
+        assert!(wants_return_route(&message(Some("thread"))));
+        assert!(!wants_return_route(&message(Some("none"))));
+        assert!(!wants_return_route(&message(None)));
+    }
+
+    #[test]
+    fn problem_reports_are_plaintext_didcomm() {
+        let response = WebhookResponse::problem_report(
+            StatusCode::BAD_REQUEST,
+            "did:example:service",
+            &ProblemReport::bad_request("Message could not be unpacked: {1}")
+                .with_args(vec!["bad".into()]),
+            Some("thread-1".into()),
+        );
+        assert_eq!(response.status, StatusCode::BAD_REQUEST);
+        assert_eq!(response.content_type, Some(PLAIN_CONTENT_TYPE));
+
+        let message: Message = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(message.typ, PROBLEM_REPORT_TYPE);
+        assert_eq!(message.from.as_deref(), Some("did:example:service"));
+        assert_eq!(message.pthid.as_deref(), Some("thread-1"));
+        assert_eq!(
+            message.body["code"],
+            crate::problem_report::codes::ERROR_BAD_REQUEST
+        );
+        assert_eq!(message.body["args"], json!(["bad"]));
+    }
+
+    #[test]
+    fn responses_carry_their_content_type() {
+        let response = WebhookResponse::problem_report(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "did:example:service",
+            &ProblemReport::internal_error("oops"),
+            None,
+        )
+        .into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PLAIN_CONTENT_TYPE);
+
+        let accepted = WebhookResponse::accepted().into_response();
+        assert_eq!(accepted.status(), StatusCode::ACCEPTED);
+        assert!(accepted.headers().get(header::CONTENT_TYPE).is_none());
+    }
+}
@@ -0,0 +1,150 @@
+//! `WebhookEndpoint::receive` must only trust a sender the envelope proves.
+//!
+//! Anyone who can reach a webhook URL can `POST` to it, and a message's `from`
+//! is just a header. A plaintext or anoncrypt message claiming a client's
+//! `from` must be turned away before any handler runs; an authcrypted message
+//! from that client must be dispatched with the client as `sender_did`. The
+//! ATM's secrets are shared across profiles, so a message for another profile
+//! unpacks fine and must still be turned away.
+
+#![cfg(feature = "webhook")]
+
+use std::sync::{Arc, Mutex};
+
+use affinidi_messaging_didcomm::{Message, message::pack::pack_plaintext};
+use affinidi_messaging_didcomm_service::{
+    DIDCommResponse, DIDCommServiceError, Extension, HandlerContext, Router, WebhookEndpoint,
+    handler_fn, ignore_handler,
+};
+use affinidi_messaging_test_mediator::{TestEnvironment, TestUser};
+use axum::http::StatusCode;
+use serde_json::json;
+
+const PING_TYPE: &str = "https://example.org/webhook-test/1.0/ping";
+
+/// Sender DIDs seen by the handler, one entry per dispatched message.
+type Seen = Arc<Mutex<Vec<Option<String>>>>;
+
+async fn record_sender(
+    ctx: HandlerContext,
+    _message: Message,
+    Extension(seen): Extension<Seen>,
+) -> Result<Option<DIDCommResponse>, DIDCommServiceError> {
+    seen.lock().unwrap().push(ctx.sender_did);
+    Ok(None)
+}
+
+fn endpoint(env: &TestEnvironment, service: &TestUser) -> (WebhookEndpoint, Seen) {
+    let seen = Seen::default();
+    let router = Router::new()
+        .extension(seen.clone())
+        .route(PING_TYPE, handler_fn(record_sender))
+        .expect("route ping")
+        .fallback(handler_fn(ignore_handler));
+    let endpoint = WebhookEndpoint::new(env.atm.clone(), service.profile.clone(), router);
+    (endpoint, seen)
+}
+
+fn ping(client: &TestUser, service: &TestUser) -> Message {
+    Message::new(PING_TYPE, json!({}))
+        .from(client.did.clone())
+        .to(vec![service.did.clone()])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn unauthenticated_messages_with_a_forged_from_are_rejected() {
+    let env = TestEnvironment::spawn().await.expect("spawn test mediator");
+    let service = env.add_user("service").await.expect("service identity");
+    let client = env.add_user("client").await.expect("client identity");
+    let (endpoint, seen) = endpoint(&env, &service);
+
+    let plaintext = pack_plaintext(&ping(&client, &service)).expect("pack plaintext");
+    let response = endpoint.receive(plaintext.as_bytes()).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    let (anoncrypt, _) = env
+        .atm
+        .pack_encrypted(&ping(&client, &service), &service.did, None, None)
+        .await
+        .expect("pack anoncrypt");
+    let response = endpoint.receive(anoncrypt.as_bytes()).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    assert!(seen.lock().unwrap().is_empty(), "no handler may run");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn authcrypt_messages_are_dispatched_with_their_sender() {
+    let env = TestEnvironment::spawn().await.expect("spawn test mediator");
+    let service = env.add_user("service").await.expect("service identity");
+    let client = env.add_user("client").await.expect("client identity");
+    let (endpoint, seen) = endpoint(&env, &service);
+
+    let (authcrypt, _) = env
+        .atm
+        .pack_encrypted(
+            &ping(&client, &service),
+            &service.did,
+            Some(&client.did),
+            None,
+        )
+        .await
+        .expect("pack authcrypt");
+    let response = endpoint.receive(authcrypt.as_bytes()).await;
+
+    assert_eq!(response.status, StatusCode::ACCEPTED);
+    assert_eq!(*seen.lock().unwrap(), vec![Some(client.did.clone())]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn opting_out_dispatches_anonymous_messages_without_a_sender() {
+    let env = TestEnvironment::spawn().await.expect("spawn test mediator");
+    let service = env.add_user("service").await.expect("service identity");
+    let client = env.add_user("client").await.expect("client identity");
+    let (endpoint, seen) = endpoint(&env, &service);
+    let endpoint = endpoint.with_require_authenticated(false);
+
+    let (anoncrypt, _) = env
+        .atm
+        .pack_encrypted(&ping(&client, &service), &service.did, None, None)
+        .await
+        .expect("pack anoncrypt");
+    let response = endpoint.receive(anoncrypt.as_bytes()).await;
+
+    assert_eq!(response.status, StatusCode::ACCEPTED);
+    assert_eq!(*seen.lock().unwrap(), vec![None]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn messages_for_another_profile_are_rejected() {
+    let env = TestEnvironment::spawn().await.expect("spawn test mediator");
+    let service = env.add_user("service").await.expect("service identity");
+    let other = env.add_user("other").await.expect("other identity");
+    let client = env.add_user("client").await.expect("client identity");
+    let (endpoint, seen) = endpoint(&env, &service);
+
+    // Encrypted to another profile of the same ATM
+    let (for_other, _) = env
+        .atm
+        .pack_encrypted(&ping(&client, &other), &other.did, Some(&client.did), None)
+        .await
+        .expect("pack authcrypt");
+    let response = endpoint.receive(for_other.as_bytes()).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    // Encrypted to the service but addressed to the other profile
+    let (misaddressed, _) = env
+        .atm
+        .pack_encrypted(
+            &ping(&client, &other),
+            &service.did,
+            Some(&client.did),
+            None,
+        )
+        .await
+        .expect("pack authcrypt");
+    let response = endpoint.receive(misaddressed.as_bytes()).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    assert!(seen.lock().unwrap().is_empty(), "no handler may run");
+}
@@ -1,6 +1,7 @@
 //! JWE encryption — build DIDComm encrypted messages.
 
 use base64ct::{Base64UrlUnpadded, Encoding};
+use rand_core::{CryptoRng, OsRng, RngCore};
 use sha2::{Digest, Sha256};
 
 use crate::error::DIDCommError;
@@ -15,6 +16,26 @@ pub fn authcrypt(
     sender_kid: &str,
     sender_private: &PrivateKeyAgreement,
     recipients: &[(&str, &PublicKeyAgreement)], // (kid, public_key)
+) -> Result<String, DIDCommError> {
+    authcrypt_with_rng(
+        plaintext,
+        sender_kid,
+        sender_private,
+        recipients,
+        &mut OsRng,
+    )
+}
+
+/// [`authcrypt`] drawing the ephemeral key, CEK and IV from `rng`.
+///
+/// A seeded `rng` is for reproducible test vectors only: reusing an ephemeral
+/// key or IV across messages breaks the encryption.
+pub fn authcrypt_with_rng<R: RngCore + CryptoRng>(
+    plaintext: &[u8],
+    sender_kid: &str,
+    sender_private: &PrivateKeyAgreement,
+    recipients: &[(&str, &PublicKeyAgreement)], // (kid, public_key)
+    rng: &mut R,
 ) -> Result<String, DIDCommError> {
     if recipients.is_empty() {
         return Err(DIDCommError::InvalidMessage("no recipients".into()));
@@ -41,15 +62,15 @@ pub fn authcrypt(
             )));
         }
     }
-    let ephemeral = EphemeralKeyPair::generate(curve);
+    let ephemeral = EphemeralKeyPair::generate_with_rng(curve, rng);
 
     // Compute APU and APV
     let apu_raw = sender_kid.as_bytes();
     let apv_raw = compute_apv(recipients.iter().map(|(kid, _)| *kid));
 
     // Generate CEK and IV, encrypt content
-    let cek = content_encryption::generate_cek();
-    let iv = content_encryption::generate_iv();
+    let cek = content_encryption::generate_cek_with_rng(rng);
+    let iv = content_encryption::generate_iv_with_rng(rng);
 
     // Build protected header (needed as AAD before encryption)
     let protected_header = ProtectedHeader {
@@ -106,6 +127,18 @@ pub fn authcrypt(
 pub fn anoncrypt(
     plaintext: &[u8],
     recipients: &[(&str, &PublicKeyAgreement)],
+) -> Result<String, DIDCommError> {
+    anoncrypt_with_rng(plaintext, recipients, &mut OsRng)
+}
+
+/// [`anoncrypt`] drawing the ephemeral key, CEK and IV from `rng`.
+///
+/// A seeded `rng` is for reproducible test vectors only: reusing an ephemeral
+/// key or IV across messages breaks the encryption.
+pub fn anoncrypt_with_rng<R: RngCore + CryptoRng>(
+    plaintext: &[u8],
+    recipients: &[(&str, &PublicKeyAgreement)],
+    rng: &mut R,
 ) -> Result<String, DIDCommError> {
     if recipients.is_empty() {
         return Err(DIDCommError::InvalidMessage("no recipients".into()));
@@ -127,12 +160,12 @@ pub fn anoncrypt(
             )));
         }
     }
-    let ephemeral = EphemeralKeyPair::generate(curve);
+    let ephemeral = EphemeralKeyPair::generate_with_rng(curve, rng);
 
     let apv_raw = compute_apv(recipients.iter().map(|(kid, _)| *kid));
 
-    let cek = content_encryption::generate_cek();
-    let iv = content_encryption::generate_iv();
+    let cek = content_encryption::generate_cek_with_rng(rng);
+    let iv = content_encryption::generate_iv_with_rng(rng);
 
     let protected_header = ProtectedHeader {
         typ: Some("application/didcomm-encrypted+json".into()),
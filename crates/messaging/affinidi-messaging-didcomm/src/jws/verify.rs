@@ -4,7 +4,7 @@ use base64ct::{Base64UrlUnpadded, Encoding};
 
 use crate::error::DIDCommError;
 use crate::jws::envelope::*;
-use affinidi_crypto::jose::signing;
+use affinidi_crypto::signature::{SignatureAlgorithm, verify_public_key};
 
 /// Result of verifying a JWS.
 pub struct VerifiedJws {
@@ -13,6 +13,8 @@ pub struct VerifiedJws {
     /// The signer KID, taken from the protected header if present,
     /// otherwise from the per-signature unprotected header (issue #323).
     pub signer_kid: Option<String>,
+    /// The signature algorithm (`alg`) that was verified.
+    pub alg: String,
 }
 
 /// Shared JWS verification skeleton (General JSON Serialization): parse the
@@ -76,6 +78,7 @@ fn verify_jws(
     Ok(VerifiedJws {
         payload,
         signer_kid,
+        alg: header.alg,
     })
 }
 
@@ -93,7 +96,10 @@ pub fn verify_ed25519(jws_str: &str, public_key: &[u8; 32]) -> Result<VerifiedJw
         jws_str,
         |alg| alg == "EdDSA" || alg == "Ed25519",
         "EdDSA or Ed25519",
-        |input, sig| signing::verify(input, sig, public_key).map_err(DIDCommError::from),
+        |input, sig| {
+            verify_public_key(SignatureAlgorithm::EdDSA, public_key, input, sig)
+                .map_err(DIDCommError::from)
+        },
     )
 }
 
@@ -107,7 +113,10 @@ pub fn verify_p256(jws_str: &str, public_key: &[u8]) -> Result<VerifiedJws, DIDC
         jws_str,
         |alg| alg == "ES256",
         "ES256",
-        |input, sig| signing::verify_p256(input, sig, public_key).map_err(DIDCommError::from),
+        |input, sig| {
+            verify_public_key(SignatureAlgorithm::ES256, public_key, input, sig)
+                .map_err(DIDCommError::from)
+        },
     )
 }
 
@@ -121,7 +130,10 @@ pub fn verify_secp256k1(jws_str: &str, public_key: &[u8]) -> Result<VerifiedJws,
         jws_str,
         |alg| alg == "ES256K",
         "ES256K",
-        |input, sig| signing::verify_secp256k1(input, sig, public_key).map_err(DIDCommError::from),
+        |input, sig| {
+            verify_public_key(SignatureAlgorithm::ES256K, public_key, input, sig)
+                .map_err(DIDCommError::from)
+        },
     )
 }
 
@@ -371,7 +383,8 @@ mod tests {
             Base64UrlUnpadded::encode_string(serde_json::to_string(&protected).unwrap().as_bytes());
         let payload_b64 = Base64UrlUnpadded::encode_string(payload);
         let signing_input = format!("{protected_b64}.{payload_b64}");
-        let sig = signing::sign(signing_input.as_bytes(), &sk.to_bytes()).unwrap();
+        let sig =
+            affinidi_crypto::jose::signing::sign(signing_input.as_bytes(), &sk.to_bytes()).unwrap();
 
         let jws = Jws {
             payload: payload_b64,
@@ -21,6 +21,7 @@ pub mod identity;
 pub mod jwe;
 pub mod jws;
 pub mod message;
+pub mod metadata;
 pub mod store;
 
 #[cfg(feature = "messaging-core")]
@@ -35,6 +36,7 @@ mod arbitrary_support;
 pub use crate::error::DIDCommError;
 pub use crate::message::unpack::UnpackResult;
 pub use crate::message::{Attachment, AttachmentData, Message, MessageBuilder};
+pub use crate::metadata::{AuthenticationLevel, ForwardHop, UnpackAlgorithms, UnpackTiming};
 
 use crate::identity::{PrivateIdentity, ResolvedIdentity};
 use crate::message::forward;
@@ -56,9 +58,34 @@ pub struct UnpackMetadata {
     pub encrypted_from_kid: Option<String>,
     pub encrypted_to_kids: Vec<String>,
     pub sign_from: Option<String>,
+    #[serde(default)]
+    pub algorithms: UnpackAlgorithms,
+    /// Forward envelopes unwrapped to reach the message, outermost first.
+    #[serde(default)]
+    pub forward_hops: Vec<ForwardHop>,
+    #[serde(default)]
+    pub timing: Option<UnpackTiming>,
 }
 
 impl UnpackMetadata {
+    /// How the message was protected, derived from the flags above.
+    pub fn authentication_level(&self) -> AuthenticationLevel {
+        AuthenticationLevel::from_flags(self.encrypted, self.authenticated, self.non_repudiation)
+    }
+
+    /// The sender DID the envelope proves, or `None` if it proves none.
+    ///
+    /// A message's `from` is just a header the sender wrote. It is only
+    /// trustworthy when the message was authcrypted and the DID of the key
+    /// that encrypted it (`encrypted_from_kid`) is that same `from`. Use this
+    /// instead of `message.from` whenever the sender decides what happens.
+    pub fn authenticated_sender<'a>(&self, from: Option<&'a str>) -> Option<&'a str> {
+        let from = from?;
+        let kid = self.encrypted_from_kid.as_deref()?;
+        let kid_did = kid.split_once('#').map_or(kid, |(did, _)| did);
+        (self.encrypted && self.authenticated && kid_did == from).then_some(from)
+    }
+
     /// Construct from an [`UnpackResult`] (convenience for migration).
     pub fn from_unpack_result(result: &UnpackResult) -> Self {
         match result {
@@ -66,13 +93,17 @@ impl UnpackMetadata {
                 authenticated,
                 sender_kid,
                 recipient_kid,
+                non_repudiation,
+                signer_kid,
                 ..
             } => Self {
                 encrypted: true,
                 authenticated: *authenticated,
+                non_repudiation: *non_repudiation,
                 anonymous_sender: !*authenticated,
                 encrypted_from_kid: sender_kid.clone(),
                 encrypted_to_kids: vec![recipient_kid.clone()],
+                sign_from: signer_kid.clone(),
                 ..Default::default()
             },
             UnpackResult::Signed { signer_kid, .. } => Self {
@@ -325,6 +356,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn metadata_reports_authentication_level() {
+        let mut alice_agent = DIDCommAgent::new();
+        let mut bob_agent = DIDCommAgent::new();
+        let alice = PrivateIdentity::generate("did:example:alice");
+        let bob = PrivateIdentity::generate("did:example:bob");
+        alice_agent.add_peer(bob.to_resolved());
+        bob_agent.add_peer(alice.to_resolved());
+        alice_agent.add_identity(alice);
+        bob_agent.add_identity(bob);
+
+        let msg = Message::new("https://example.org/test/1.0", serde_json::json!({}))
+            .from("did:example:alice")
+            .to(vec!["did:example:bob".into()]);
+
+        let authcrypted = alice_agent
+            .pack_authcrypt(&msg, "did:example:alice", "did:example:bob")
+            .unwrap();
+        let result = bob_agent
+            .unpack(&authcrypted, Some("did:example:alice"))
+            .unwrap();
+        assert_eq!(
+            UnpackMetadata::from_unpack_result(&result).authentication_level(),
+            AuthenticationLevel::Authcrypt
+        );
+
+        let anoncrypted = alice_agent.pack_anoncrypt(&msg, "did:example:bob").unwrap();
+        let result = bob_agent.unpack(&anoncrypted, None).unwrap();
+        assert_eq!(
+            UnpackMetadata::from_unpack_result(&result).authentication_level(),
+            AuthenticationLevel::Anoncrypt
+        );
+    }
+
+    #[test]
+    fn authenticated_sender_requires_authcrypt_from_the_same_did() {
+        let authcrypt = UnpackMetadata {
+            encrypted: true,
+            authenticated: true,
+            encrypted_from_kid: Some("did:example:alice#key-x25519-1".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            authcrypt.authenticated_sender(Some("did:example:alice")),
+            Some("did:example:alice")
+        );
+        assert_eq!(
+            authcrypt.authenticated_sender(Some("did:example:mallory")),
+            None
+        );
+        assert_eq!(authcrypt.authenticated_sender(None), None);
+
+        let anoncrypt = UnpackMetadata {
+            encrypted: true,
+            anonymous_sender: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            anoncrypt.authenticated_sender(Some("did:example:alice")),
+            None
+        );
+        assert_eq!(
+            UnpackMetadata::default().authenticated_sender(Some("did:example:alice")),
+            None
+        );
+    }
+
     #[test]
     fn agent_anoncrypt_roundtrip() {
         let mut alice_agent = DIDCommAgent::new();
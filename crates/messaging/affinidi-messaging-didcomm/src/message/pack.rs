@@ -5,6 +5,7 @@ use crate::jwe::{decrypt, encrypt};
 use crate::jws::sign;
 use crate::message::Message;
 use affinidi_crypto::jose::key_agreement::{PrivateKeyAgreement, PublicKeyAgreement};
+use rand_core::{CryptoRng, RngCore};
 
 /// Pack a message as encrypted (authcrypt — sender authenticated).
 ///
@@ -23,6 +24,20 @@ pub fn pack_encrypted_authcrypt(
     encrypt::authcrypt(&plaintext, sender_kid, sender_private, recipients)
 }
 
+/// [`pack_encrypted_authcrypt`] with the encryption randomness drawn from
+/// `rng`. Meant for reproducible test vectors; see
+/// [`encrypt::authcrypt_with_rng`].
+pub fn pack_encrypted_authcrypt_with_rng<R: RngCore + CryptoRng>(
+    msg: &Message,
+    sender_kid: &str,
+    sender_private: &PrivateKeyAgreement,
+    recipients: &[(&str, &PublicKeyAgreement)],
+    rng: &mut R,
+) -> Result<String, DIDCommError> {
+    let plaintext = msg.to_json()?;
+    encrypt::authcrypt_with_rng(&plaintext, sender_kid, sender_private, recipients, rng)
+}
+
 /// Pack a message as encrypted (anoncrypt — anonymous).
 ///
 /// # Arguments
@@ -36,6 +51,18 @@ pub fn pack_encrypted_anoncrypt(
     encrypt::anoncrypt(&plaintext, recipients)
 }
 
+/// [`pack_encrypted_anoncrypt`] with the encryption randomness drawn from
+/// `rng`. Meant for reproducible test vectors; see
+/// [`encrypt::anoncrypt_with_rng`].
+pub fn pack_encrypted_anoncrypt_with_rng<R: RngCore + CryptoRng>(
+    msg: &Message,
+    recipients: &[(&str, &PublicKeyAgreement)],
+    rng: &mut R,
+) -> Result<String, DIDCommError> {
+    let plaintext = msg.to_json()?;
+    encrypt::anoncrypt_with_rng(&plaintext, recipients, rng)
+}
+
 /// Pack a message as signed (JWS with EdDSA).
 ///
 /// # Arguments
@@ -0,0 +1,128 @@
+//! Structured details of how a message was unpacked, carried on
+//! [`UnpackMetadata`](crate::UnpackMetadata).
+//!
+//! Policy code ("only accept authcrypted messages from known DIDs") should
+//! match on [`AuthenticationLevel`] rather than combine the metadata's
+//! boolean flags by hand.
+
+use serde::{Deserialize, Serialize};
+
+/// How the outermost non-forward envelope of a message was protected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AuthenticationLevel {
+    /// No encryption and no signature.
+    #[default]
+    Plaintext,
+    /// Encrypted to the recipient; the sender is unknown.
+    Anoncrypt,
+    /// Signed but not encrypted.
+    Signed,
+    /// Signed, then anonymously encrypted.
+    AnoncryptSigned,
+    /// Encrypted with the sender's key agreement key (ECDH-1PU).
+    Authcrypt,
+    /// Signed, then authcrypted.
+    AuthcryptSigned,
+}
+
+impl AuthenticationLevel {
+    /// Derive the level from the flags on [`UnpackMetadata`](crate::UnpackMetadata).
+    pub fn from_flags(encrypted: bool, authenticated: bool, non_repudiation: bool) -> Self {
+        match (encrypted, authenticated, non_repudiation) {
+            (false, _, false) => Self::Plaintext,
+            (false, _, true) => Self::Signed,
+            (true, false, false) => Self::Anoncrypt,
+            (true, false, true) => Self::AnoncryptSigned,
+            (true, true, false) => Self::Authcrypt,
+            (true, true, true) => Self::AuthcryptSigned,
+        }
+    }
+
+    pub fn is_encrypted(self) -> bool {
+        matches!(
+            self,
+            Self::Anoncrypt | Self::AnoncryptSigned | Self::Authcrypt | Self::AuthcryptSigned
+        )
+    }
+
+    /// Whether the sender is bound cryptographically, by authcrypt or by a
+    /// signature.
+    pub fn is_sender_authenticated(self) -> bool {
+        !matches!(self, Self::Plaintext | Self::Anoncrypt)
+    }
+
+    /// Whether the sender signed the message, so a third party can verify
+    /// who sent it.
+    pub fn is_non_repudiable(self) -> bool {
+        matches!(
+            self,
+            Self::Signed | Self::AnoncryptSigned | Self::AuthcryptSigned
+        )
+    }
+}
+
+/// JOSE algorithms of the envelope the message was unpacked from.
+///
+/// A field is `None` when that layer was absent, or when the unpacker did not
+/// record it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnpackAlgorithms {
+    /// JWE key management algorithm, e.g. `ECDH-1PU+A256KW`.
+    pub key_wrap: Option<String>,
+    /// JWE content encryption algorithm, e.g. `A256CBC-HS512`.
+    pub content_encryption: Option<String>,
+    /// JWS signature algorithm, e.g. `EdDSA`.
+    pub signature: Option<String>,
+}
+
+/// A forward envelope unwrapped on the way to the message.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardHop {
+    /// Id of the forward message.
+    pub forward_id: String,
+    /// The `next` DID the forward asked to deliver to.
+    pub next: String,
+    /// Key that authcrypted the forward, if it was authcrypted.
+    pub from_kid: Option<String>,
+}
+
+/// When and how quickly a message was unpacked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnpackTiming {
+    /// When unpacking finished, in seconds since the Unix epoch.
+    pub unpacked_at: u64,
+    /// Time spent unpacking, including key resolution, in microseconds.
+    pub duration_micros: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_follow_the_flags() {
+        assert_eq!(
+            AuthenticationLevel::from_flags(false, false, false),
+            AuthenticationLevel::Plaintext
+        );
+        assert_eq!(
+            AuthenticationLevel::from_flags(true, false, false),
+            AuthenticationLevel::Anoncrypt
+        );
+        assert_eq!(
+            AuthenticationLevel::from_flags(true, true, true),
+            AuthenticationLevel::AuthcryptSigned
+        );
+
+        let signed = AuthenticationLevel::from_flags(false, false, true);
+        assert!(signed.is_sender_authenticated());
+        assert!(signed.is_non_repudiable());
+        assert!(!signed.is_encrypted());
+
+        let anon = AuthenticationLevel::Anoncrypt;
+        assert!(anon.is_encrypted());
+        assert!(!anon.is_sender_authenticated());
+    }
+}
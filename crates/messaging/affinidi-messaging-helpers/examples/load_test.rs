@@ -0,0 +1,115 @@
+//! Load test the mediator path
+//! Provisions ephemeral profiles on the environment's default mediator, sends
+//! messages between them at a fixed rate, and prints a JSON report of
+//! end-to-end latency, throughput and errors.
+
+use affinidi_messaging_helpers::load_test::{LoadTest, LoadTestConfig};
+use affinidi_messaging_sdk::{ATM, config::ATMConfig, errors::ATMError};
+use affinidi_tdk::common::{TDKSharedState, environments::TDKEnvironments};
+use clap::Parser;
+use std::{env, sync::Arc, time::Duration};
+use tracing_subscriber::filter;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Environment to use
+    #[arg(short, long)]
+    environment: Option<String>,
+
+    /// Path to the environments file (defaults to environments.json)
+    #[arg(short, long)]
+    path_environments: Option<String>,
+
+    /// Number of ephemeral profiles to provision
+    #[arg(long, default_value_t = 10)]
+    profiles: usize,
+
+    /// Messages per second, across all profiles
+    #[arg(short, long, default_value_t = 10.0)]
+    rate: f64,
+
+    /// Seconds to keep sending
+    #[arg(short, long, default_value_t = 30)]
+    duration: u64,
+
+    /// Message body padding in bytes
+    #[arg(short, long, default_value_t = 256)]
+    size: usize,
+
+    /// Most messages awaiting delivery at once
+    #[arg(long, default_value_t = 256)]
+    max_in_flight: usize,
+
+    /// Seconds to wait for each message to be delivered
+    #[arg(long, default_value_t = 10)]
+    receive_timeout: u64,
+
+    /// Keep the profiles' mediator accounts after the run
+    #[arg(long)]
+    keep_accounts: bool,
+
+    /// Write the JSON report to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), ATMError> {
+    let args: Args = Args::parse();
+
+    let environment_name = if let Some(environment_name) = &args.environment {
+        environment_name.to_string()
+    } else if let Ok(environment_name) = env::var("TDK_ENVIRONMENT") {
+        environment_name
+    } else {
+        "default".to_string()
+    };
+
+    let environment =
+        TDKEnvironments::fetch_from_file(args.path_environments.as_deref(), &environment_name)?;
+    eprintln!("Using Environment: {environment_name}");
+
+    let Some(mediator_did) = environment.default_mediator() else {
+        return Err(ATMError::ConfigError(format!(
+            "Environment ({environment_name}) has no default mediator"
+        )));
+    };
+
+    // construct a subscriber that prints formatted traces to stderr
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).expect("Logging failed, exiting...");
+
+    let tdk =
+        Arc::new(TDKSharedState::new(affinidi_tdk::common::config::TDKConfig::headless()?).await?);
+    let atm = ATM::new(
+        ATMConfig::builder()
+            .with_ssl_certificates(&mut environment.ssl_certificate_paths().to_vec())
+            .build()?,
+        tdk,
+    )
+    .await?;
+
+    let config = LoadTestConfig::new(mediator_did)
+        .with_profiles(args.profiles)
+        .with_rate(args.rate)?
+        .with_duration(Duration::from_secs(args.duration))
+        .with_message_size(args.size)
+        .with_max_in_flight(args.max_in_flight)
+        .with_receive_timeout(Duration::from_secs(args.receive_timeout))
+        .with_cleanup(!args.keep_accounts);
+
+    let report = LoadTest::new(atm.clone(), config).run().await?;
+    atm.graceful_shutdown().await;
+
+    match &args.output {
+        Some(path) => std::fs::write(path, report.to_json())
+            .map_err(|e| ATMError::ConfigError(format!("Couldn't write report ({path}): {e}")))?,
+        None => println!("{}", report.to_json()),
+    }
+
+    Ok(())
+}
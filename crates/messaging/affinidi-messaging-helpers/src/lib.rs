@@ -1 +1,2 @@
 pub mod common;
+pub mod load_test;
@@ -0,0 +1,513 @@
+//! Load testing the mediator path.
+//!
+//! [`LoadTest`] provisions a set of ephemeral `did:peer` profiles on a
+//! target mediator, then sends messages between them at a fixed rate for a
+//! fixed time. Each message is packed, forwarded through the mediator, and
+//! picked up by the recipient over its live stream; the time from packing
+//! to pickup is its end-to-end latency. The run ends with a [`LoadReport`]
+//! of latency percentiles, throughput and errors by stage, serialisable as
+//! JSON.
+//!
+//! The rate is open-loop: messages start on schedule whether or not earlier
+//! ones have been delivered, up to `max_in_flight`. Ticks that find every
+//! slot busy are counted as [`Failure::Saturated`] rather than delayed, so a
+//! mediator that can't keep up shows as errors instead of a quietly lower
+//! rate.
+//!
+//! ```ignore
+//! use affinidi_messaging_helpers::load_test::{LoadTest, LoadTestConfig};
+//!
+//! let config = LoadTestConfig::new(mediator_did)
+//!     .with_profiles(20)
+//!     .with_rate(50.0)?
+//!     .with_duration(Duration::from_secs(60));
+//! let report = LoadTest::new(atm, config).run().await?;
+//! println!("{}", report.to_json());
+//! ```
+
+use affinidi_messaging_didcomm::message::Message;
+use affinidi_messaging_sdk::{ATM, errors::ATMError, profiles::ATMProfile};
+use affinidi_tdk::common::secrets::SecretsResolver;
+use affinidi_tdk::dids::{DID, KeyType, PeerKeyRole};
+use serde::Serialize;
+use serde_json::json;
+use sha256::digest;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+use tracing::{debug, info, warn};
+use trust_tasks_rs::specs::messaging::account::get::v0_1::MediatorAclAccessListMode;
+use uuid::Uuid;
+
+/// DIDComm message type of load test messages
+pub const LOAD_TEST_MESSAGE_TYPE: &str = "https://affinidi.com/atm/1.0/load-test";
+
+/// Settings of a load test run. Construct with [`LoadTestConfig::new`].
+#[derive(Clone, Debug)]
+pub struct LoadTestConfig {
+    mediator_did: String,
+    profiles: usize,
+    rate: f64,
+    /// Time between message starts, `1 / rate`
+    period: Duration,
+    duration: Duration,
+    message_size: usize,
+    max_in_flight: usize,
+    receive_timeout: Duration,
+    cleanup: bool,
+}
+
+impl LoadTestConfig {
+    /// Defaults: 10 profiles, 10 messages per second for 30 seconds,
+    /// 256-byte bodies, 256 messages in flight, 10 second receive timeout,
+    /// accounts removed afterwards.
+    pub fn new(mediator_did: impl Into<String>) -> Self {
+        LoadTestConfig {
+            mediator_did: mediator_did.into(),
+            profiles: 10,
+            rate: 10.0,
+            period: Duration::from_millis(100),
+            duration: Duration::from_secs(30),
+            message_size: 256,
+            max_in_flight: 256,
+            receive_timeout: Duration::from_secs(10),
+            cleanup: true,
+        }
+    }
+
+    /// Number of ephemeral profiles to provision (at least 2). Messages go
+    /// round-robin from each profile to the next.
+    pub fn with_profiles(mut self, profiles: usize) -> Self {
+        self.profiles = profiles.max(2);
+        self
+    }
+
+    /// Messages started per second, across all profiles.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ATMError::ConfigError`] unless `rate` is a positive, finite
+    /// number of at most one message per nanosecond.
+    pub fn with_rate(mut self, rate: f64) -> Result<Self, ATMError> {
+        self.period = rate_period(rate)?;
+        self.rate = rate;
+        Ok(self)
+    }
+
+    /// How long to keep starting messages.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Size of each message body's padding, in bytes.
+    pub fn with_message_size(mut self, message_size: usize) -> Self {
+        self.message_size = message_size;
+        self
+    }
+
+    /// Most messages awaiting delivery at once.
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// How long a recipient waits for a message before it counts as
+    /// [`Failure::Timeout`].
+    pub fn with_receive_timeout(mut self, receive_timeout: Duration) -> Self {
+        self.receive_timeout = receive_timeout;
+        self
+    }
+
+    /// Whether to remove the profiles' mediator accounts after the run.
+    pub fn with_cleanup(mut self, cleanup: bool) -> Self {
+        self.cleanup = cleanup;
+        self
+    }
+}
+
+/// Stage at which a message failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Failure {
+    /// Not started: `max_in_flight` messages were already in flight
+    Saturated,
+    /// Packing the message failed
+    Pack,
+    /// The mediator didn't accept the forwarded message
+    Send,
+    /// Picking the message up failed
+    Receive,
+    /// The recipient didn't receive the message within the receive timeout
+    Timeout,
+}
+
+/// End-to-end latency distribution, in milliseconds.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencySummary {
+    /// Summarise samples in milliseconds; `None` when there are none.
+    pub fn from_samples(mut samples: Vec<f64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_by(f64::total_cmp);
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+        Some(LatencySummary {
+            min: samples[0],
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            max: samples[samples.len() - 1],
+        })
+    }
+}
+
+/// Outcome of a load test run.
+#[derive(Clone, Debug, Serialize)]
+pub struct LoadReport {
+    pub mediator_did: String,
+    pub profiles: usize,
+    /// Requested messages per second
+    pub target_rate: f64,
+    pub message_size: usize,
+    /// Wall-clock time from the first message to the last delivery, in seconds
+    pub elapsed_secs: f64,
+    /// Messages scheduled, including those never started
+    pub attempted: u64,
+    pub delivered: u64,
+    pub failed: u64,
+    /// `failed / attempted`
+    pub error_rate: f64,
+    /// Delivered messages per second
+    pub throughput: f64,
+    pub errors: BTreeMap<Failure, u64>,
+    /// `None` when nothing was delivered
+    pub latency_ms: Option<LatencySummary>,
+}
+
+impl LoadReport {
+    /// The report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("load reports always serialise")
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    latencies_ms: Vec<f64>,
+    errors: BTreeMap<Failure, u64>,
+}
+
+impl Tally {
+    fn record(&mut self, outcome: Result<Duration, Failure>) {
+        match outcome {
+            Ok(latency) => self.latencies_ms.push(latency.as_secs_f64() * 1000.0),
+            Err(failure) => *self.errors.entry(failure).or_default() += 1,
+        }
+    }
+}
+
+/// A load test against one mediator.
+pub struct LoadTest {
+    atm: ATM,
+    config: LoadTestConfig,
+}
+
+impl LoadTest {
+    pub fn new(atm: ATM, config: LoadTestConfig) -> Self {
+        LoadTest { atm, config }
+    }
+
+    /// Provision the profiles, drive the load, and clean up.
+    ///
+    /// Fails only if the profiles can't be provisioned; failures while
+    /// driving the load are counted in the report. Whatever was provisioned
+    /// is torn down either way.
+    pub async fn run(&self) -> Result<LoadReport, ATMError> {
+        let mut provisioned = Provisioned::default();
+        let report = match self.provision(&mut provisioned).await {
+            Ok(()) => Ok(self.drive(&provisioned.profiles).await),
+            Err(e) => Err(e),
+        };
+        self.teardown(provisioned).await;
+        report
+    }
+
+    /// Create the ephemeral profiles and open their live streams, recording
+    /// each profile and secret in `provisioned` as soon as it exists.
+    async fn provision(&self, provisioned: &mut Provisioned) -> Result<(), ATMError> {
+        let run_id = &Uuid::new_v4().to_string()[..8];
+        for i in 0..self.config.profiles {
+            let (did, secrets) = DID::generate_did_peer(
+                vec![
+                    (PeerKeyRole::Verification, KeyType::Ed25519),
+                    (PeerKeyRole::Encryption, KeyType::X25519),
+                ],
+                Some(self.config.mediator_did.clone()),
+            )
+            .map_err(|e| ATMError::DIDError(format!("Couldn't create load test DID: {e}")))?;
+            self.atm
+                .get_tdk()
+                .secrets_resolver()
+                .insert_vec(&secrets)
+                .await;
+            provisioned
+                .secret_ids
+                .extend(secrets.iter().map(|secret| secret.id.clone()));
+
+            let profile = ATMProfile::new(
+                &self.atm,
+                Some(format!("load-test-{run_id}-{i}")),
+                did,
+                Some(self.config.mediator_did.clone()),
+            )
+            .await?;
+            provisioned
+                .profiles
+                .push(self.atm.profile_add(&profile, true).await?);
+        }
+
+        // Each profile receives from the one before it
+        let profiles = &provisioned.profiles;
+        for (i, profile) in profiles.iter().enumerate() {
+            let sender = &profiles[(i + profiles.len() - 1) % profiles.len()];
+            let account = self.atm.trust_tasks().account_get(profile, None).await?;
+            if account.acl.access_list_mode == Some(MediatorAclAccessListMode::ExplicitAllow) {
+                self.atm
+                    .trust_tasks()
+                    .access_list_add(profile, None, vec![digest(&sender.inner.did)])
+                    .await?;
+            }
+        }
+        info!(
+            profiles = profiles.len(),
+            mediator = %self.config.mediator_did,
+            "load test profiles provisioned"
+        );
+        Ok(())
+    }
+
+    async fn drive(&self, profiles: &[Arc<ATMProfile>]) -> LoadReport {
+        let tally = Arc::new(Mutex::new(Tally::default()));
+        let slots = Arc::new(Semaphore::new(self.config.max_in_flight));
+        let padding: Arc<str> = "x".repeat(self.config.message_size).into();
+        let mut ticker = tokio::time::interval(self.config.period);
+        let mut in_flight = JoinSet::new();
+        let mut attempted: u64 = 0;
+
+        let start = Instant::now();
+        while start.elapsed() < self.config.duration {
+            ticker.tick().await;
+            attempted += 1;
+            let Ok(slot) = slots.clone().try_acquire_owned() else {
+                tally.lock().unwrap().record(Err(Failure::Saturated));
+                continue;
+            };
+
+            let index = attempted as usize % profiles.len();
+            let sender = profiles[index].clone();
+            let recipient = profiles[(index + 1) % profiles.len()].clone();
+            let atm = self.atm.clone();
+            let mediator_did = self.config.mediator_did.clone();
+            let padding = padding.clone();
+            let timeout = self.config.receive_timeout;
+            let tally = tally.clone();
+            in_flight.spawn(async move {
+                let outcome =
+                    send_one(&atm, &sender, &recipient, &mediator_did, &padding, timeout).await;
+                tally.lock().unwrap().record(outcome);
+                drop(slot);
+            });
+        }
+        while in_flight.join_next().await.is_some() {}
+        let elapsed = start.elapsed().as_secs_f64();
+
+        let tally = std::mem::take(&mut *tally.lock().unwrap());
+        let delivered = tally.latencies_ms.len() as u64;
+        let failed = tally.errors.values().sum();
+        LoadReport {
+            mediator_did: self.config.mediator_did.clone(),
+            profiles: profiles.len(),
+            target_rate: self.config.rate,
+            message_size: self.config.message_size,
+            elapsed_secs: elapsed,
+            attempted,
+            delivered,
+            failed,
+            error_rate: if attempted == 0 {
+                0.0
+            } else {
+                failed as f64 / attempted as f64
+            },
+            throughput: delivered as f64 / elapsed,
+            errors: tally.errors,
+            latency_ms: LatencySummary::from_samples(tally.latencies_ms),
+        }
+    }
+
+    /// Remove the profiles and their secrets, and their mediator accounts if
+    /// configured.
+    async fn teardown(&self, provisioned: Provisioned) {
+        for profile in &provisioned.profiles {
+            if self.config.cleanup
+                && let Err(e) = self.atm.trust_tasks().account_remove(profile, None).await
+            {
+                warn!(did = %profile.inner.did, "couldn't remove load test account: {e}");
+            }
+            let _ = self.atm.profile_remove(&profile.inner.alias).await;
+        }
+        for id in &provisioned.secret_ids {
+            self.atm.get_tdk().remove_secret(id).await;
+        }
+    }
+}
+
+/// What a run has provisioned so far, for [`LoadTest::teardown`].
+#[derive(Default)]
+struct Provisioned {
+    profiles: Vec<Arc<ATMProfile>>,
+    secret_ids: Vec<String>,
+}
+
+/// Time between message starts at `rate` messages per second.
+fn rate_period(rate: f64) -> Result<Duration, ATMError> {
+    if rate > 0.0
+        && let Ok(period) = Duration::try_from_secs_f64(1.0 / rate)
+        && !period.is_zero()
+    {
+        Ok(period)
+    } else {
+        Err(ATMError::ConfigError(format!(
+            "load test rate ({rate}) must be a positive, finite number of messages per second"
+        )))
+    }
+}
+
+/// Send one message and wait for the recipient to pick it up.
+async fn send_one(
+    atm: &ATM,
+    sender: &Arc<ATMProfile>,
+    recipient: &Arc<ATMProfile>,
+    mediator_did: &str,
+    padding: &str,
+    timeout: Duration,
+) -> Result<Duration, Failure> {
+    let start = Instant::now();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let msg = Message::build(
+        Uuid::new_v4().to_string(),
+        LOAD_TEST_MESSAGE_TYPE.to_string(),
+        json!({ "padding": padding }),
+    )
+    .from(sender.inner.did.clone())
+    .to(recipient.inner.did.clone())
+    .created_time(now)
+    .expires_time(now + timeout.as_secs() + 1)
+    .finalize();
+    let msg_id = msg.id.clone();
+
+    let (packed, _) = atm
+        .pack_encrypted(
+            &msg,
+            &recipient.inner.did,
+            Some(&sender.inner.did),
+            Some(&sender.inner.did),
+        )
+        .await
+        .map_err(|e| {
+            debug!("load test pack failed: {e}");
+            Failure::Pack
+        })?;
+
+    atm.forward_and_send_message(
+        sender,
+        false,
+        &packed,
+        None,
+        mediator_did,
+        &recipient.inner.did,
+        None,
+        None,
+        false,
+    )
+    .await
+    .map_err(|e| {
+        debug!("load test send failed: {e}");
+        Failure::Send
+    })?;
+
+    match atm
+        .message_pickup()
+        .live_stream_get(recipient, &msg_id, timeout, true)
+        .await
+    {
+        Ok(Some(_)) => Ok(start.elapsed()),
+        Ok(None) => Err(Failure::Timeout),
+        Err(e) => {
+            debug!("load test receive failed: {e}");
+            Err(Failure::Receive)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_sets_the_period() {
+        let config = LoadTestConfig::new("did:example:mediator")
+            .with_rate(50.0)
+            .unwrap();
+        assert_eq!(config.rate, 50.0);
+        assert_eq!(config.period, Duration::from_millis(20));
+    }
+
+    #[test]
+    fn unusable_rates_are_rejected() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY, f64::MIN_POSITIVE, 1e12] {
+            assert!(
+                matches!(
+                    LoadTestConfig::new("did:example:mediator").with_rate(rate),
+                    Err(ATMError::ConfigError(_))
+                ),
+                "rate {rate} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn latency_percentiles_use_the_nearest_rank() {
+        let summary = LatencySummary::from_samples((1..=100).rev().map(f64::from).collect())
+            .expect("samples");
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.p50, 50.0);
+        assert_eq!(summary.p99, 99.0);
+        assert_eq!(summary.max, 100.0);
+        assert_eq!(summary.mean, 50.5);
+        assert!(LatencySummary::from_samples(Vec::new()).is_none());
+    }
+}
@@ -8,7 +8,7 @@
  */
 
 use serde::{Deserialize, Serialize};
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 use thiserror::Error;
 
 /// Failures originating in the ACL parser / mutator code paths. The
@@ -59,6 +59,22 @@ impl Display for AccessListModeType {
     }
 }
 
+/// Parses the config spelling (`explicit_allow` / `explicit_deny`),
+/// case-insensitively, so it also accepts the [`Display`] form.
+impl FromStr for AccessListModeType {
+    type Err = ACLError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "explicit_allow" => Ok(AccessListModeType::ExplicitAllow),
+            "explicit_deny" => Ok(AccessListModeType::ExplicitDeny),
+            _ => Err(ACLError::Config(format!(
+                "Unknown access list mode ({s}). Expected explicit_allow or explicit_deny"
+            ))),
+        }
+    }
+}
+
 /// The ACL Set for a DID
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct MediatorACLSet {
@@ -793,6 +809,26 @@ impl MediatorACLSet {
 mod tests {
     use super::*;
 
+    #[test]
+    fn access_list_mode_from_str() {
+        assert_eq!(
+            "explicit_allow".parse::<AccessListModeType>().unwrap(),
+            AccessListModeType::ExplicitAllow
+        );
+        assert_eq!(
+            "Explicit_Deny".parse::<AccessListModeType>().unwrap(),
+            AccessListModeType::ExplicitDeny
+        );
+        assert_eq!(
+            AccessListModeType::ExplicitDeny
+                .to_string()
+                .parse::<AccessListModeType>()
+                .unwrap(),
+            AccessListModeType::ExplicitDeny
+        );
+        assert!("deny".parse::<AccessListModeType>().is_err());
+    }
+
     #[test]
     fn test_u64_conversions() {
         let mut acl = MediatorACLSet::default();
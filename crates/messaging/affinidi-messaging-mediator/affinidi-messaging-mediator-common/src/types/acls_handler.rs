@@ -43,6 +43,10 @@ pub enum MediatorACLRequest {
     },
     #[serde(rename = "access_list_clear")]
     AccessListClear { did_hash: String },
+    /// Mediator-wide ACL mode plus the caller's own ACLs. Any authenticated
+    /// account may ask; it only ever reports on the caller's own DID.
+    #[serde(rename = "acl_mode_get")]
+    GetACLMode {},
 }
 
 /// DIDComm message body for responding with a set of ACLs for a list of DID Hashes
@@ -53,6 +57,19 @@ pub struct MediatorACLGetResponse {
     pub mediator_acl_mode: AccessListModeType,
 }
 
+/// DIDComm message body for responding to an ACL mode request
+/// `mediator_acl_mode`: Mediator-wide mode. `ExplicitDeny` lets any DID connect
+///   unless blocked; `ExplicitAllow` only admits DIDs an admin has added
+/// `did_hash`: Hash of the caller's DID
+/// `acls`: The caller's own ACLs (blocked, send/receive, own access list mode, ...)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename = "acl_mode_get_response")]
+pub struct MediatorACLModeResponse {
+    pub mediator_acl_mode: AccessListModeType,
+    pub did_hash: String,
+    pub acls: MediatorACLSet,
+}
+
 /// DIDComm message body for responding with a set of ACLs for a list of DID Hashes
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename = "acl_set_response")]
@@ -2,10 +2,17 @@
 //! The client-side `Mediator` / `MediatorOps` handler types live in the
 //! SDK and use these vocabulary types directly.
 
-use super::accounts::AccountType;
+use super::accounts::{Account, AccountType};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// DIDComm message type of the announcements a mediator fans out to its
+/// accounts. Announcements are signed (not encrypted) by the mediator: the
+/// store only keeps DID hashes, so the mediator cannot encrypt to each
+/// account, and the content is meant for everyone anyway.
+pub const ANNOUNCEMENT_MESSAGE_TYPE: &str =
+    "https://affinidi.com/messaging/mediator/1.0/announcement";
+
 #[derive(Serialize, Deserialize)]
 pub enum MediatorAdminRequest {
     #[serde(rename = "admin_add")]
@@ -25,9 +32,98 @@ pub enum MediatorAdminRequest {
         cursor: u32,
         limit: u32,
     },
+    /// Send an announcement to every local account matching the filter.
+    /// The mediator fans it out in the background; the response is a
+    /// [`MediatorBroadcastAccepted`].
+    #[serde(rename = "broadcast")]
+    Broadcast(MediatorBroadcast),
     Configuration(Value),
 }
 
+/// What an announcement is about, so clients can decide how to surface it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementKind {
+    #[default]
+    Info,
+    /// Planned downtime; `starts_at` / `ends_at` give the window.
+    Maintenance,
+    /// A feature, protocol or endpoint is going away.
+    Deprecation,
+    /// A kind this client doesn't know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Body of an [`ANNOUNCEMENT_MESSAGE_TYPE`] message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediatorAnnouncement {
+    #[serde(default)]
+    pub kind: AnnouncementKind,
+    pub title: String,
+    pub body: String,
+    /// Unix timestamp (seconds) the announced event starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<u64>,
+    /// Unix timestamp (seconds) the announced event ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<u64>,
+}
+
+/// Which accounts receive a broadcast. An empty field doesn't filter, so the
+/// default filter matches every account.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BroadcastFilter {
+    /// Only accounts of these types.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub account_types: Vec<AccountType>,
+    /// Only these accounts (SHA256 hashed DIDs).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub did_hashes: Vec<String>,
+}
+
+impl BroadcastFilter {
+    pub fn is_empty(&self) -> bool {
+        self.account_types.is_empty() && self.did_hashes.is_empty()
+    }
+
+    pub fn matches(&self, account: &Account) -> bool {
+        (self.account_types.is_empty() || self.account_types.contains(&account._type))
+            && (self.did_hashes.is_empty() || self.did_hashes.contains(&account.did_hash))
+    }
+}
+
+/// Admin request to fan an announcement out to local accounts.
+/// - `announcement` - What to send
+/// - `filter` - Which accounts receive it (all when empty)
+/// - `expires_at` - Unix timestamp (seconds) after which undelivered copies
+///   are dropped. Capped by the mediator's message expiry.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MediatorBroadcast {
+    pub announcement: MediatorAnnouncement,
+    #[serde(default, skip_serializing_if = "BroadcastFilter::is_empty")]
+    pub filter: BroadcastFilter,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+/// Acknowledgement of a broadcast request. The mediator queues the copies
+/// after responding; the outcome is recorded in the audit log.
+/// - `broadcast_id` - Identifies this broadcast in the audit log
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediatorBroadcastAccepted {
+    pub broadcast_id: String,
+}
+
+/// Outcome of a broadcast, recorded in the audit log once the fan-out ends
+/// - `delivered` - Accounts the announcement was queued for
+/// - `failed` - Accounts it couldn't be queued for (e.g. full queues)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediatorBroadcastResult {
+    pub delivered: u32,
+    pub failed: u32,
+}
+
 /// A list of admins in the mediator
 /// - `accounts` - The list of admins (SHA256 Hashed DIDs)
 /// - `cursor` - The offset to use for the next request
@@ -65,4 +161,51 @@ mod tests {
             _ => panic!("expected AuditLogList variant"),
         }
     }
+
+    #[test]
+    fn broadcast_request_wire_format() {
+        let json = serde_json::json!({"broadcast": {
+            "announcement": {"kind": "maintenance", "title": "Upgrade", "body": "Down for 10m",
+                             "starts_at": 1_700_000_000},
+            "filter": {"account_types": ["Standard"]}
+        }});
+        let req: MediatorAdminRequest =
+            serde_json::from_value(json).expect("deserialize broadcast");
+        let MediatorAdminRequest::Broadcast(broadcast) = req else {
+            panic!("expected Broadcast variant");
+        };
+        assert_eq!(broadcast.announcement.kind, AnnouncementKind::Maintenance);
+        assert_eq!(broadcast.announcement.starts_at, Some(1_700_000_000));
+        assert_eq!(broadcast.filter.account_types, vec![AccountType::Standard]);
+        assert_eq!(broadcast.expires_at, None);
+    }
+
+    #[test]
+    fn unknown_announcement_kinds_still_parse() {
+        let announcement: MediatorAnnouncement = serde_json::from_value(
+            serde_json::json!({"kind": "incident", "title": "t", "body": "b"}),
+        )
+        .unwrap();
+        assert_eq!(announcement.kind, AnnouncementKind::Unknown);
+    }
+
+    #[test]
+    fn broadcast_filter_matches() {
+        let account = Account {
+            did_hash: "aa".into(),
+            _type: AccountType::Admin,
+            ..Default::default()
+        };
+        assert!(BroadcastFilter::default().matches(&account));
+        let by_type = BroadcastFilter {
+            account_types: vec![AccountType::Standard],
+            ..Default::default()
+        };
+        assert!(!by_type.matches(&account));
+        let by_hash = BroadcastFilter {
+            did_hashes: vec!["aa".into()],
+            ..Default::default()
+        };
+        assert!(by_hash.matches(&account));
+    }
 }
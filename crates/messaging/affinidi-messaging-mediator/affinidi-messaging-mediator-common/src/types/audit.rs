@@ -51,6 +51,9 @@ pub enum AuditAction {
     /// One or more DIDs had admin status stripped.
     #[serde(rename = "admin_strip")]
     AdminStrip,
+    /// An announcement was broadcast to local accounts.
+    #[serde(rename = "broadcast")]
+    Broadcast,
 }
 
 /// A single audit-log record: one privileged change, by one actor, at one time.
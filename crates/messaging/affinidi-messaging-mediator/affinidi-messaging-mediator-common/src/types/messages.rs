@@ -168,6 +168,65 @@ impl Default for FetchOptions {
     }
 }
 
+/// Query parameters of the mediator's `/search/{did_hash}/{folder}` endpoint.
+///
+/// Every filter is optional and they combine with AND. Times are milliseconds
+/// since the epoch, like [`MessageListElement::timestamp`], and both ends of
+/// the range are inclusive.
+///
+/// Stored messages are encrypted for their recipient, so a mediator can only
+/// filter on what it records when queuing them: the sender hash and the
+/// time. `thid` and `type` are part of the query so mediators that do index
+/// them can honour them; the others reject a query that sets them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MessageSearchQuery {
+    /// sha256 hash of the sender DID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_hash: Option<String>,
+    /// DIDComm thread id (`thid`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thid: Option<String>,
+    /// DIDComm message type URI.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub msg_type: Option<String>,
+    /// Only messages stored at or after this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+    /// Only messages stored at or before this time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<u64>,
+    /// Maximum number of results, capped by the mediator's list limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+impl MessageSearchQuery {
+    /// Whether `element` passes the sender and time filters.
+    ///
+    /// `thid` and `type` aren't recorded on a [`MessageListElement`] and are
+    /// not checked here.
+    pub fn matches(&self, element: &MessageListElement) -> bool {
+        self.from_hash
+            .as_ref()
+            .is_none_or(|hash| element.from_address.as_ref() == Some(hash))
+            && self.since.is_none_or(|since| element.timestamp >= since)
+            && self.until.is_none_or(|until| element.timestamp <= until)
+    }
+}
+
+/// The stream id right after `stream_id` (`<ms>-<seq>`), for paging through
+/// a folder with an inclusive range. `None` if `stream_id` isn't one or is
+/// the last possible id.
+pub fn stream_id_after(stream_id: &str) -> Option<String> {
+    let (ms, seq) = stream_id.split_once('-')?;
+    let (ms, seq): (u64, u64) = (ms.parse().ok()?, seq.parse().ok()?);
+    match seq.checked_add(1) {
+        Some(seq) => Some(format!("{ms}-{seq}")),
+        None => Some(format!("{}-0", ms.checked_add(1)?)),
+    }
+}
+
 #[cfg(test)]
 mod protocol_tests {
     use super::MessageProtocol;
@@ -193,3 +252,55 @@ mod protocol_tests {
         assert_eq!(MessageProtocol::detect(""), MessageProtocol::Other);
     }
 }
+
+#[cfg(test)]
+mod search_tests {
+    use super::{MessageListElement, MessageSearchQuery, stream_id_after};
+
+    fn element(from: &str, timestamp: u64) -> MessageListElement {
+        MessageListElement {
+            msg_id: "m".into(),
+            from_address: Some(from.into()),
+            timestamp,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn filters_combine() {
+        let query = MessageSearchQuery {
+            from_hash: Some("alice".into()),
+            since: Some(100),
+            until: Some(200),
+            ..Default::default()
+        };
+        assert!(query.matches(&element("alice", 100)));
+        assert!(query.matches(&element("alice", 200)));
+        assert!(!query.matches(&element("alice", 201)));
+        assert!(!query.matches(&element("bob", 150)));
+        assert!(MessageSearchQuery::default().matches(&element("bob", 0)));
+    }
+
+    #[test]
+    fn stream_ids_step_to_the_next() {
+        assert_eq!(stream_id_after("1700-4").as_deref(), Some("1700-5"));
+        assert_eq!(
+            stream_id_after(&format!("1700-{}", u64::MAX)).as_deref(),
+            Some("1701-0")
+        );
+        assert_eq!(stream_id_after(&format!("{0}-{0}", u64::MAX)), None);
+        assert_eq!(stream_id_after("+"), None);
+    }
+
+    #[test]
+    fn query_uses_wire_names() {
+        let query = MessageSearchQuery {
+            msg_type: Some("https://didcomm.org/trust-ping/2.0/ping".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            serde_json::json!({ "type": "https://didcomm.org/trust-ping/2.0/ping" })
+        );
+    }
+}
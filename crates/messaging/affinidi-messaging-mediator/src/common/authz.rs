@@ -9,11 +9,15 @@
 //! - [`authentication_check`] — the pre-auth "can this DID connect?" check
 //!   (resolves the ACL set from the session, the store, or the configured
 //!   default, then applies the blocked gate).
+//! - [`sends_on_behalf_of`] — may a session send as another DID, under a
+//!   delegation it presented when it authenticated?
 //!
 //! Access-list (sender↔recipient) checks and the remaining handler/routing
 //! ACL sites migrate here in the following tasks; the capability vocabulary
 //! below is intentionally complete ahead of every call site adopting it.
 
+use affinidi_did_authentication::delegation::{Ability, DelegationCheck, verify_delegation};
+use affinidi_did_resolver_cache_sdk::DIDCacheClient;
 use affinidi_messaging_mediator_common::errors::MediatorError;
 use affinidi_messaging_mediator_common::store::MediatorStore;
 use affinidi_messaging_sdk::protocols::mediator::acls::MediatorACLSet;
@@ -107,6 +111,32 @@ pub(crate) async fn check_access_list(
     }
 }
 
+/// Whether one of the delegations carried by `session` lets it send
+/// messages as `sender_did` at `now`.
+///
+/// Every token is re-verified against the delegators' current DID documents,
+/// so an expired delegation, or one whose signing key has since been rotated
+/// out, stops working mid-session. Delegations carrying caveats are not
+/// honoured: the mediator can't enforce them, so it fails closed.
+pub(crate) async fn sends_on_behalf_of(
+    did_resolver: &DIDCacheClient,
+    session: &Session,
+    sender_did: &str,
+    now: u64,
+) -> bool {
+    let check = DelegationCheck::new(&session.did)
+        .require(sender_did, Ability::SendOnBehalf)
+        .at(now);
+    for token in &session.delegations {
+        match verify_delegation(token, did_resolver, &check).await {
+            Ok(delegation) if delegation.caveats.is_empty() => return true,
+            Ok(_) => debug!(sender_did, "delegation has caveats, not honoured"),
+            Err(e) => debug!(sender_did, "delegation not usable: {e}"),
+        }
+    }
+    false
+}
+
 /// Pre-authentication check: is `did_hash` allowed to connect to the
 /// mediator, and is it already known?
 ///
@@ -434,4 +464,81 @@ mod tests {
             }
         }
     }
+
+    /// A did:key profile and a resolver holding its secret.
+    async fn profile(seed: u8) -> (String, affinidi_secrets_resolver::SimpleSecretsResolver) {
+        let mut secret =
+            affinidi_secrets_resolver::secrets::Secret::generate_ed25519(None, Some(&[seed; 32]));
+        let multibase = secret.get_public_keymultibase().unwrap();
+        let did = format!("did:key:{multibase}");
+        secret.id = format!("{did}#{multibase}");
+        (
+            did,
+            affinidi_secrets_resolver::SimpleSecretsResolver::new(&[secret]).await,
+        )
+    }
+
+    fn delegated_session(did: &str, delegations: Vec<String>) -> Session {
+        Session {
+            did: did.to_string(),
+            authenticated: true,
+            delegations,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn only_an_in_scope_delegation_lets_a_session_send_on_behalf() {
+        use affinidi_did_authentication::delegation::DelegationBuilder;
+        use affinidi_did_resolver_cache_sdk::config::DIDCacheConfigBuilder;
+        use std::time::Duration;
+
+        let resolver = DIDCacheClient::new(DIDCacheConfigBuilder::default().build())
+            .await
+            .unwrap();
+        let (alice, alice_secrets) = profile(1).await;
+        let (device, _) = profile(2).await;
+        let (carol, _) = profile(3).await;
+        let now = crate::common::time::unix_timestamp_secs();
+        let delegate = |ability: Ability| {
+            DelegationBuilder::new(&alice, &device)
+                .capability(&alice, ability)
+                .expires_in(Duration::from_secs(60))
+        };
+
+        let send = delegate(Ability::SendOnBehalf)
+            .sign(&resolver, &alice_secrets)
+            .await
+            .unwrap();
+        let session = delegated_session(&device, vec![send]);
+        assert!(sends_on_behalf_of(&resolver, &session, &alice, now).await);
+        // Out of scope: the delegation covers alice, not carol.
+        assert!(!sends_on_behalf_of(&resolver, &session, &carol, now).await);
+        // Expired.
+        assert!(!sends_on_behalf_of(&resolver, &session, &alice, now + 120).await);
+        // Presented by a session it wasn't issued to.
+        let stolen = delegated_session(&carol, session.delegations.clone());
+        assert!(!sends_on_behalf_of(&resolver, &stolen, &alice, now).await);
+
+        // Out of scope: reading alice's messages doesn't extend to sending.
+        let read = delegate(Ability::ReadMessages)
+            .sign(&resolver, &alice_secrets)
+            .await
+            .unwrap();
+        let session = delegated_session(&device, vec![read]);
+        assert!(!sends_on_behalf_of(&resolver, &session, &alice, now).await);
+
+        // Caveats the mediator can't enforce.
+        let restricted = delegate(Ability::SendOnBehalf)
+            .caveat("recipients", serde_json::json!([carol]))
+            .sign(&resolver, &alice_secrets)
+            .await
+            .unwrap();
+        let session = delegated_session(&device, vec![restricted]);
+        assert!(!sends_on_behalf_of(&resolver, &session, &alice, now).await);
+
+        assert!(
+            !sends_on_behalf_of(&resolver, &delegated_session(&device, vec![]), &alice, now).await
+        );
+    }
 }
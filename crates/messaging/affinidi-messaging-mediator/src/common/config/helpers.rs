@@ -15,7 +15,7 @@
 //! `parameter_store` module, shared with the `mediator-setup` wizard that
 //! publishes to it, so the string the wizard writes is the string read here.
 
-use affinidi_did_common::{Document, DocumentExt, service::Endpoint};
+use affinidi_did_common::{Document, DocumentExt};
 use affinidi_did_resolver_cache_sdk::DIDCacheClient;
 use affinidi_messaging_mediator_common::errors::MediatorError;
 #[cfg(feature = "aws")]
@@ -552,39 +552,14 @@ pub(crate) async fn load_forwarding_protection_blocks(
         forwarding_config.blocked_forwarding.insert(did.clone());
 
         for service in doc.doc.service.iter() {
-            match &service.service_endpoint {
-                Endpoint::Url(uri) => {
-                    forwarding_config.blocked_forwarding.insert(uri.to_string());
-                }
-                Endpoint::Map(map) => {
-                    if let Some(endpoints) = map.as_array() {
-                        for endpoint in endpoints {
-                            if let Some(uri) = endpoint.get("uri") {
-                                if let Some(uri) = uri.as_str() {
-                                    forwarding_config.blocked_forwarding.insert(uri.into());
-                                } else {
-                                    warn!("Couldn't parse URI as a string: {uri:#?}");
-                                }
-                            } else {
-                                warn!(
-                                    "Service endpoint map does not contain a URI. DID ({did}), Service ({service:#?}), Endpoint ({endpoint:#?})"
-                                );
-                            }
-                        }
-                    } else if let Some(uri) = map.get("uri") {
-                        if let Some(uri) = uri.as_str() {
-                            forwarding_config.blocked_forwarding.insert(uri.into());
-                        } else {
-                            warn!("Couldn't parse URI as a string: {uri:#?}");
-                        }
-                    } else {
-                        warn!(
-                            "Service endpoint map does not contain a URI. DID ({did}), Service ({service:#?}), Endpoint ({map:#?})"
-                        );
-                    }
-                }
-                // `Endpoint` is `#[non_exhaustive]`; ignore unknown shapes.
-                _ => {}
+            let endpoints = service.service_endpoint.endpoint_uris();
+            if endpoints.is_empty() {
+                warn!(
+                    "Service endpoint does not contain a URI. DID ({did}), Service ({service:#?})"
+                );
+            }
+            for endpoint in endpoints {
+                forwarding_config.blocked_forwarding.insert(endpoint.uri);
             }
         }
     }
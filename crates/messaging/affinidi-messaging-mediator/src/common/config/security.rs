@@ -470,11 +470,14 @@ impl SecurityConfigRawExt for SecurityConfigRaw {
         };
 
         let mut config = SecurityConfig {
-            mediator_acl_mode: match self.mediator_acl_mode.as_str() {
-                "explicit_allow" => AccessListModeType::ExplicitAllow,
-                "explicit_deny" => AccessListModeType::ExplicitDeny,
-                _ => AccessListModeType::ExplicitDeny,
-            },
+            mediator_acl_mode: self.mediator_acl_mode.parse().unwrap_or_else(|_| {
+                warn_default(
+                    "mediator_acl_mode",
+                    &self.mediator_acl_mode,
+                    "explicit_deny",
+                );
+                AccessListModeType::ExplicitDeny
+            }),
             local_direct_delivery_allowed: self
                 .local_direct_delivery_allowed
                 .parse()
@@ -199,6 +199,7 @@ pub(crate) async fn authenticate_token(
 
     // Update the expires at time
     saved_session.expires_at = token_data.claims.exp;
+    saved_session.delegations = token_data.claims.delegations;
 
     debug!(session_id, did_hash, "JWT auth accepted");
 
@@ -28,6 +28,9 @@ pub struct SessionClaims {
     pub session_id: String,
     /// Expiration time as a Unix timestamp.
     pub exp: u64,
+    /// Delegation tokens the DID presented when it authenticated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delegations: Vec<String>,
 }
 
 /// Lifecycle state of an authentication session.
@@ -94,6 +97,10 @@ pub struct Session {
     pub expires_at: u64,
     /// Hash of the most recently issued refresh token.
     pub refresh_token_hash: Option<String>,
+    /// Delegation tokens carried by the session JWT (not serialized; the
+    /// token, not the session record, is their source of truth).
+    #[serde(skip)]
+    pub delegations: Vec<String>,
 }
 
 // ─── Conversions to / from the trait-layer Session ──────────────────────────
@@ -135,6 +142,7 @@ impl From<affinidi_messaging_mediator_common::store::types::Session> for Session
             account_type: s.account_type,
             expires_at: s.expires_at,
             refresh_token_hash: s.refresh_token_hash,
+            delegations: Vec::new(),
         }
     }
 }
@@ -27,7 +27,7 @@ use affinidi_messaging_didcomm::{
     },
 };
 use affinidi_messaging_sdk::messages::compat::{PackEncryptedMetadata, UnpackMetadata};
-use affinidi_secrets_resolver::SecretsResolver;
+use affinidi_secrets_resolver::{SecretsResolver, secrets::KeyType, usage::KeyPurpose};
 use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
 
 /// Pre-parsed envelope metadata extracted without decryption.
@@ -225,9 +225,20 @@ impl MetaEnvelope {
         let mut recipient_private: Option<PrivateKeyAgreement> = None;
 
         for recipient in recipients {
-            if let Some(kid) = recipient["header"]["kid"].as_str()
-                && let Some(secret) = secrets_resolver.get_secret(kid).await
+            let Some(kid) = recipient["header"]["kid"].as_str() else {
+                continue;
+            };
+            let secret = match secrets_resolver
+                .get_secret_for(kid, KeyPurpose::KeyAgreement)
+                .await
             {
+                Ok(secret) => secret,
+                Err(e) => {
+                    tracing::warn!("Not decrypting JWE recipient {kid}: {e}");
+                    None
+                }
+            };
+            if let Some(secret) = secret {
                 let Some(curve) = secret.get_key_type().key_agreement_curve() else {
                     continue;
                 };
@@ -763,6 +774,51 @@ fn resolve_public_key(
     None
 }
 
+/// Sign a message as a general JSON JWS, using the signer's first Ed25519
+/// `authentication` key whose secret we hold.
+pub async fn pack_signed<S: SecretsResolver>(
+    message: &Message,
+    signer_did: &str,
+    did_resolver: &DIDCacheClient,
+    secrets_resolver: &S,
+) -> Result<String, String> {
+    let doc = did_resolver
+        .resolve(signer_did)
+        .await
+        .map_err(|e| format!("Failed to resolve signer DID: {e}"))?;
+    let mut refused = Vec::new();
+    for kid in doc.doc.find_authentication(None) {
+        let secret = match secrets_resolver
+            .get_secret_for(kid, KeyPurpose::Signing)
+            .await
+        {
+            Ok(secret) => secret,
+            Err(e) => {
+                refused.push(format!("{kid} ({e})"));
+                continue;
+            }
+        };
+        if let Some(secret) = secret
+            && secret.get_key_type() == KeyType::Ed25519
+            && let Ok(private) = <[u8; 32]>::try_from(secret.get_private_bytes())
+        {
+            return affinidi_messaging_didcomm::message::pack::pack_signed(message, kid, &private)
+                .map_err(|e| e.to_string());
+        }
+    }
+    if refused.is_empty() {
+        Err(format!(
+            "Signer ({signer_did}) has no Ed25519 authentication key with a held secret"
+        ))
+    } else {
+        Err(format!(
+            "Signer ({signer_did}) has no Ed25519 authentication key with a held secret; \
+             refused: [{}]",
+            refused.join("; ")
+        ))
+    }
+}
+
 /// Pack (encrypt) a message for a recipient.
 ///
 /// Key selection negotiates a **shared curve** between sender and recipient
@@ -805,9 +861,19 @@ pub async fn pack_encrypted<S: SecretsResolver>(
         // can show what the sender advertised vs. what was actually usable.
         let mut skipped: Vec<String> = Vec::new();
         for &kid in &sender_ka_kids {
-            let Some(secret) = secrets_resolver.get_secret(kid).await else {
-                skipped.push(format!("{kid} (no secret held)"));
-                continue;
+            let secret = match secrets_resolver
+                .get_secret_for(kid, KeyPurpose::KeyAgreement)
+                .await
+            {
+                Ok(Some(secret)) => secret,
+                Ok(None) => {
+                    skipped.push(format!("{kid} (no secret held)"));
+                    continue;
+                }
+                Err(e) => {
+                    skipped.push(format!("{kid} (refused: {e})"));
+                    continue;
+                }
             };
             let key_type = secret.get_key_type();
             let Some(curve) = key_type.key_agreement_curve() else {
@@ -54,6 +54,7 @@ pub async fn authentication_challenge(
         account_type: AccountType::Standard,
         expires_at: 0,
         refresh_token_hash: None,
+        delegations: Vec::new(),
     };
     let _span = span!(
         Level::DEBUG,
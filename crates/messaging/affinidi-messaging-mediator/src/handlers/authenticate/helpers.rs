@@ -1,10 +1,12 @@
+use super::{DELEGATIONS_FIELD, MUTUAL_AUTH_HINT, SEALED_TOKENS_TYPE, TokenResponse};
 use crate::SharedData;
 use crate::common::session::SessionClaims;
 // Production token creation now takes `now` from the injected clock; the only
 // remaining caller of this helper is the test module below.
 #[cfg(test)]
 use crate::common::time::unix_timestamp_secs;
-use crate::didcomm_compat::MetaEnvelope;
+use crate::didcomm_compat::{self, MetaEnvelope};
+use affinidi_did_authentication::delegation::{DelegationCheck, verify_delegation};
 use affinidi_messaging_didcomm::Message;
 use affinidi_messaging_mediator_common::errors::MediatorError;
 use affinidi_messaging_sdk::messages::compat::UnpackMetadata;
@@ -12,8 +14,10 @@ use affinidi_messaging_sdk::messages::problem_report::{ProblemReportScope, Probl
 use http::StatusCode;
 use jsonwebtoken::{EncodingKey, Header, encode};
 use rand::{RngExt, distr::Alphanumeric};
+use serde::Serialize;
 use sha256::digest;
 use tracing::debug;
+use uuid::Uuid;
 
 /// Unpack an authentication envelope and enforce the signed-AND-encrypted
 /// invariant in a single place, shared by the `authenticate` response and
@@ -91,6 +95,7 @@ pub fn create_random_string(length: usize) -> String {
 pub(super) fn _create_access_token(
     did: &str,
     session_id: &str,
+    delegations: &[String],
     expiry: u64,
     now: u64,
     encoding_key: &EncodingKey,
@@ -101,6 +106,7 @@ pub(super) fn _create_access_token(
         sub: did.to_owned(),
         session_id: session_id.to_owned(),
         exp: (now + expiry),
+        delegations: delegations.to_vec(),
     };
 
     let access_token = encode(
@@ -131,6 +137,7 @@ pub(super) fn _create_access_token(
 pub(super) fn _create_refresh_token(
     did: &str,
     session_id: &str,
+    delegations: &[String],
     expiry: u64,
     now: u64,
     encoding_key: &EncodingKey,
@@ -140,6 +147,7 @@ pub(super) fn _create_refresh_token(
         sub: did.to_owned(),
         session_id: session_id.to_owned(),
         exp: (now + expiry),
+        delegations: delegations.to_vec(),
     };
 
     let refresh_token = encode(
@@ -167,6 +175,113 @@ pub(super) fn _create_refresh_token(
     Ok((refresh_token, refresh_claims.exp, token_hash))
 }
 
+/// True when the client asked for its tokens sealed (mutual authentication)
+pub(super) fn wants_sealed_tokens(body: &serde_json::Value) -> bool {
+    body.get(MUTUAL_AUTH_HINT)
+        .and_then(|hint| hint.as_bool())
+        .unwrap_or(false)
+}
+
+/// Most delegation tokens a DID may present when it authenticates; they ride
+/// along in every session token.
+const MAX_DELEGATIONS: usize = 8;
+
+/// Delegation tokens presented in an authenticate message body, each verified
+/// as issued to `did`. Any invalid token fails the authentication rather than
+/// being dropped, so the client learns why it can't act for a delegator.
+pub(super) async fn presented_delegations(
+    state: &SharedData,
+    did: &str,
+    body: &serde_json::Value,
+    now: u64,
+) -> Result<Vec<String>, MediatorError> {
+    let invalid = |reason: String| {
+        MediatorError::problem_with_log(
+            96,
+            "",
+            None,
+            ProblemReportSorter::Error,
+            ProblemReportScope::Protocol,
+            "authentication.delegation.invalid",
+            "Delegation token is invalid: {1}",
+            vec![reason.clone()],
+            StatusCode::FORBIDDEN,
+            format!("Delegation token is invalid: {reason}"),
+        )
+    };
+
+    let Some(tokens) = body.get(DELEGATIONS_FIELD) else {
+        return Ok(Vec::new());
+    };
+    let tokens: Vec<String> =
+        serde_json::from_value(tokens.clone()).map_err(|e| invalid(e.to_string()))?;
+    if tokens.len() > MAX_DELEGATIONS {
+        return Err(invalid(format!(
+            "{} tokens presented, at most {MAX_DELEGATIONS} are accepted",
+            tokens.len()
+        )));
+    }
+
+    let check = DelegationCheck::new(did).at(now);
+    for token in &tokens {
+        verify_delegation(token, &state.did_resolver, &check)
+            .await
+            .map_err(|e| invalid(e.to_string()))?;
+    }
+    Ok(tokens)
+}
+
+/// Returns `tokens` as-is, or when `sealed` is set, packed in an authcrypted
+/// DIDComm message from the mediator DID to `did`, threaded to the client's
+/// request `thid`. The client opens it to prove the tokens came from the
+/// mediator and not from whoever terminated TLS.
+pub(super) async fn token_response<T: Serialize>(
+    state: &SharedData,
+    sealed: bool,
+    did: &str,
+    thid: &str,
+    session_id: &str,
+    now: u64,
+    tokens: T,
+) -> Result<TokenResponse<T>, MediatorError> {
+    if !sealed {
+        return Ok(TokenResponse::Plain(tokens));
+    }
+
+    let body = serde_json::to_value(&tokens).map_err(|err| {
+        MediatorError::MessagePackError(
+            47,
+            session_id.to_string(),
+            format!("Couldn't serialize tokens. Reason: {err}"),
+        )
+    })?;
+    let msg = Message::build(Uuid::new_v4().to_string(), SEALED_TOKENS_TYPE, body)
+        .from(state.config.mediator_did.clone())
+        .to(did.to_string())
+        .thid(thid.to_string())
+        .created_time(now)
+        .expires_time(now + 60)
+        .finalize();
+
+    let (packed, _) = didcomm_compat::pack_encrypted(
+        &msg,
+        did,
+        Some(&state.config.mediator_did),
+        &state.did_resolver,
+        &*state.config.security.mediator_secrets,
+    )
+    .await
+    .map_err(|err| {
+        MediatorError::MessagePackError(
+            47,
+            session_id.to_string(),
+            format!("Couldn't seal tokens. Reason: {err}"),
+        )
+    })?;
+
+    Ok(TokenResponse::Sealed(packed))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,6 +300,17 @@ mod tests {
         (encoding_key, decoding_key)
     }
 
+    #[test]
+    fn mutual_auth_hint_is_opt_in() {
+        assert!(wants_sealed_tokens(
+            &serde_json::json!({"challenge": "c", "mutual_auth": true})
+        ));
+        assert!(!wants_sealed_tokens(
+            &serde_json::json!({"challenge": "c", "mutual_auth": false})
+        ));
+        assert!(!wants_sealed_tokens(&serde_json::json!({"challenge": "c"})));
+    }
+
     #[test]
     fn create_random_string_has_expected_length() {
         let s = create_random_string(32);
@@ -211,6 +337,7 @@ mod tests {
         let (token, _exp) = _create_access_token(
             "did:example:123",
             "session-1",
+            &[],
             3600,
             unix_timestamp_secs(),
             &encoding_key,
@@ -238,6 +365,7 @@ mod tests {
         let (_token, exp) = _create_access_token(
             "did:example:456",
             "session-2",
+            &[],
             expiry_delta,
             now,
             &encoding_key,
@@ -264,6 +392,7 @@ mod tests {
         let (token, exp, hash) = _create_refresh_token(
             "did:example:789",
             "session-3",
+            &[],
             3600,
             unix_timestamp_secs(),
             &encoding_key,
@@ -282,6 +411,7 @@ mod tests {
         let (token, _exp, hash) = _create_refresh_token(
             "did:example:abc",
             "session-4",
+            &[],
             3600,
             unix_timestamp_secs(),
             &encoding_key,
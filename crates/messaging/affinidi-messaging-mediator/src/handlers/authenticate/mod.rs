@@ -6,6 +6,14 @@
 //! 5. Client uses the access token to access protected services
 //! 6. If the access token expires, the client uses the refresh token to get a new access token
 //!
+//! Clients that set `"mutual_auth": true` in their authenticate or refresh message body get
+//! their tokens back sealed: an authcrypted DIDComm message from the mediator DID to theirs,
+//! threaded to their request, so they can check the tokens came from the mediator.
+//!
+//! An authenticate message body may also carry `"delegations"`: delegation tokens issued to
+//! the authenticating DID. They are verified, then carried in the session tokens, and let the
+//! session send messages as a delegator that granted it `messaging/send`.
+//!
 //! NOTE: All errors handled in the handlers are returned as a Problem Report messages
 
 mod challenge;
@@ -20,6 +28,13 @@ pub use helpers::create_random_string;
 pub use refresh::*;
 pub use response::*;
 
+/// Shared with the client, so both sides agree on the hint and the sealed
+/// message type
+pub use affinidi_did_authentication::mutual::{MUTUAL_AUTH_HINT, SEALED_TOKENS_TYPE};
+
+/// Authenticate message body field carrying delegation tokens, shared with the
+/// client
+pub use affinidi_did_authentication::delegation::DELEGATIONS_FIELD;
 use affinidi_messaging_sdk::messages::GenericDataStruct;
 use serde::{Deserialize, Serialize};
 
@@ -30,6 +45,17 @@ pub struct AuthenticationChallenge {
 }
 impl GenericDataStruct for AuthenticationChallenge {}
 
+/// Tokens returned by the authenticate and refresh endpoints
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum TokenResponse<T> {
+    /// Packed DIDComm message of type [`SEALED_TOKENS_TYPE`] carrying the tokens
+    Sealed(String),
+    /// The tokens themselves, for clients that didn't ask for mutual authentication
+    Plain(T),
+}
+impl<T: GenericDataStruct> GenericDataStruct for TokenResponse<T> {}
+
 /// Refresh tokens response from the authentication service.
 /// Includes a rotated refresh token (one-time use).
 #[derive(Serialize, Deserialize, Default, Clone)]
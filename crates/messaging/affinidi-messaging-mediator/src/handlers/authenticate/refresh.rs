@@ -1,6 +1,8 @@
 use super::super::message_inbound::InboundMessage;
-use super::AuthRefreshResponse;
-use super::helpers::{_create_access_token, _create_refresh_token};
+use super::helpers::{
+    _create_access_token, _create_refresh_token, token_response, wants_sealed_tokens,
+};
+use super::{AuthRefreshResponse, TokenResponse};
 use crate::common::jwt_auth::{clock_aware_validation, jwt_exp_is_expired};
 use crate::didcomm_compat::MetaEnvelope;
 use crate::{
@@ -26,7 +28,13 @@ use tracing::{Instrument, Level, info, span};
 pub async fn authentication_refresh(
     State(state): State<SharedData>,
     Json(body): Json<InboundMessage>,
-) -> Result<(StatusCode, Json<SuccessResponse<AuthRefreshResponse>>), AppError> {
+) -> Result<
+    (
+        StatusCode,
+        Json<SuccessResponse<TokenResponse<AuthRefreshResponse>>>,
+    ),
+    AppError,
+> {
     let _span = span!(Level::DEBUG, "authentication_refresh",);
 
     async move {
@@ -321,10 +329,12 @@ pub async fn authentication_refresh(
             .into());
         }
 
-        // Generate a new access token
+        // Generate a new access token, carrying over the delegations presented
+        // at authentication
         let (access_token, access_expires_at) = _create_access_token(
             &session_check.did,
             &session_check.session_id,
+            &results.claims.delegations,
             state.config.security.jwt_access_expiry,
             now,
             &state.config.security.jwt_encoding_key,
@@ -336,11 +346,29 @@ pub async fn authentication_refresh(
         let (new_refresh_token, new_refresh_expires_at, new_refresh_hash) = _create_refresh_token(
             &session_check.did,
             &session_check.session_id,
+            &results.claims.delegations,
             refresh_expiry,
             now,
             &state.config.security.jwt_encoding_key,
         )?;
 
+        // Seal before rotating, so a sealing failure leaves the old token usable
+        let response = token_response(
+            &state,
+            wants_sealed_tokens(&msg.body),
+            &session_check.did,
+            &msg.id,
+            &session_check.session_id,
+            now,
+            AuthRefreshResponse {
+                access_token,
+                access_expires_at,
+                refresh_token: new_refresh_token,
+                refresh_expires_at: new_refresh_expires_at,
+            },
+        )
+        .await?;
+
         // Store the new refresh token hash
         state
             .database
@@ -376,12 +404,7 @@ pub async fn authentication_refresh(
                 error_code: 0,
                 error_code_str: "NA".to_string(),
                 message: "Success".to_string(),
-                data: Some(AuthRefreshResponse {
-                    access_token,
-                    access_expires_at,
-                    refresh_token: new_refresh_token,
-                    refresh_expires_at: new_refresh_expires_at,
-                }),
+                data: Some(response),
             }),
         ))
     }
@@ -1,6 +1,9 @@
 use super::super::message_inbound::InboundMessage;
-use super::AuthenticationChallenge;
-use super::helpers::{_create_access_token, _create_refresh_token, create_random_string};
+use super::helpers::{
+    _create_access_token, _create_refresh_token, create_random_string, presented_delegations,
+    token_response, wants_sealed_tokens,
+};
+use super::{AuthenticationChallenge, TokenResponse};
 use crate::didcomm_compat::MetaEnvelope;
 use crate::{
     SharedData,
@@ -15,6 +18,7 @@ use affinidi_messaging_sdk::messages::{
 };
 use axum::{Json, extract::State};
 use http::StatusCode;
+use serde::Deserialize;
 use sha256::digest;
 use tracing::{Instrument, Level, debug, info, span};
 
@@ -26,7 +30,13 @@ use tracing::{Instrument, Level, debug, info, span};
 pub async fn authentication_response(
     State(state): State<SharedData>,
     Json(body): Json<InboundMessage>,
-) -> Result<(StatusCode, Json<SuccessResponse<AuthorizationResponse>>), AppError> {
+) -> Result<
+    (
+        StatusCode,
+        Json<SuccessResponse<TokenResponse<AuthorizationResponse>>>,
+    ),
+    AppError,
+> {
     let _span = span!(Level::DEBUG, "authentication_response",);
 
     async move {
@@ -248,7 +258,8 @@ pub async fn authentication_response(
         }
 
         // Turn message body into Challenge response
-        let challenge: AuthenticationChallenge = serde_json::from_value(msg.body).map_err(|e| {
+        let sealed = wants_sealed_tokens(&msg.body);
+        let challenge = AuthenticationChallenge::deserialize(&msg.body).map_err(|e| {
             MediatorError::problem_with_log(
                 28,
                 "",
@@ -327,6 +338,10 @@ pub async fn authentication_response(
             )
             .into());
         }
+
+        // Delegations the DID holds from others travel in its session tokens
+        let delegations = presented_delegations(&state, &session.did, &msg.body, now).await?;
+
         let old_sid = session.session_id;
         session.session_id = create_random_string(12);
 
@@ -334,6 +349,7 @@ pub async fn authentication_response(
         let (access_token, access_expires_at) = _create_access_token(
             &session.did,
             &session.session_id,
+            &delegations,
             state.config.security.jwt_access_expiry,
             now,
             &state.config.security.jwt_encoding_key,
@@ -344,6 +360,7 @@ pub async fn authentication_response(
         let (refresh_token, refresh_expires_at, refresh_token_hash) = _create_refresh_token(
             &session.did,
             &session.session_id,
+            &delegations,
             refresh_expiry,
             now,
             &state.config.security.jwt_encoding_key,
@@ -351,12 +368,21 @@ pub async fn authentication_response(
 
         session.expires_at = access_expires_at;
 
-        let response = AuthorizationResponse {
-            access_token,
-            access_expires_at,
-            refresh_token,
-            refresh_expires_at,
-        };
+        let response = token_response(
+            &state,
+            sealed,
+            &session.did,
+            &msg.id,
+            &session.session_id,
+            now,
+            AuthorizationResponse {
+                access_token,
+                access_expires_at,
+                refresh_token,
+                refresh_expires_at,
+            },
+        )
+        .await?;
 
         // Set the session state to Authorized and store refresh token hash.
         // Pass the raw DID — the trait impl computes its hash internally.
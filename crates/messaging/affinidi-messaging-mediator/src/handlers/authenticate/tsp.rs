@@ -175,6 +175,7 @@ pub async fn tsp_authentication_response(
         let (access_token, access_expires_at) = _create_access_token(
             &session.did,
             &session.session_id,
+            &[],
             state.config.security.jwt_access_expiry,
             now,
             &state.config.security.jwt_encoding_key,
@@ -184,6 +185,7 @@ pub async fn tsp_authentication_response(
         let (refresh_token, refresh_expires_at, refresh_token_hash) = _create_refresh_token(
             &session.did,
             &session.session_id,
+            &[],
             refresh_expiry,
             now,
             &state.config.security.jwt_encoding_key,
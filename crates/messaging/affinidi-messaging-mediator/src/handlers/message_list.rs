@@ -1,5 +1,6 @@
 use crate::{SharedData, common::session::Session};
 use affinidi_messaging_mediator_common::errors::{AppError, MediatorError, SuccessResponse};
+use affinidi_messaging_mediator_common::types::messages::{MessageSearchQuery, stream_id_after};
 use affinidi_messaging_sdk::messages::compat::UnpackMetadata;
 use affinidi_messaging_sdk::messages::{
     Folder, GenericDataStruct, MessageList,
@@ -7,7 +8,7 @@ use affinidi_messaging_sdk::messages::{
 };
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
@@ -41,47 +42,7 @@ pub async fn message_list_handler(
         folder = folder.to_string()
     );
     async move {
-        // ACL Check
-        if !session.acls.get_local() {
-            return Err(MediatorError::problem(
-                40,
-                session.session_id,
-                None,
-                ProblemReportSorter::Error,
-                ProblemReportScope::Protocol,
-                "authorization.local",
-                "DID isn't local to the mediator",
-                vec![],
-                StatusCode::FORBIDDEN,
-            )
-            .into());
-        }
-
-        // Sessions are scoped to a single authenticated DID — listing
-        // is allowed only for that DID. Multi-DID listing (one session
-        // owner aggregating across several DIDs) would need a session-
-        // owner concept above the DID and an owner→DIDs index in the
-        // store; tracked in PR #286's follow-up section.
-        if session
-            .did_hash
-            .as_bytes()
-            .ct_eq(did_hash.as_bytes())
-            .unwrap_u8()
-            == 0
-        {
-            return Err(MediatorError::problem(
-                45,
-                session.session_id,
-                None,
-                ProblemReportSorter::Error,
-                ProblemReportScope::Protocol,
-                "authorization.permission",
-                "DID hash does not match authenticated session",
-                vec![],
-                StatusCode::FORBIDDEN,
-            )
-            .into());
-        }
+        check_list_access(&session, &did_hash)?;
 
         let mut messages = state
             .database
@@ -114,3 +75,153 @@ pub async fn message_list_handler(
     .instrument(_span)
     .await
 }
+
+/// Searches the messages of a folder by sender and time, without their bodies
+/// ACL_MODE: Requires LOCAL access
+/// # Parameters
+/// - `session`: Session information
+/// - `folder`: Folder to search
+/// - `did_hash`: sha256 hash of the DID we are checking
+/// - `query`: Filters, see [`MessageSearchQuery`]
+///
+/// Stored messages are encrypted for their recipient, so `thid` and `type`
+/// filters are rejected rather than silently ignored. Outbox messages are
+/// reported as sent by the folder's owner.
+pub async fn message_search_handler(
+    session: Session,
+    Path((did_hash, folder)): Path<(String, Folder)>,
+    Query(query): Query<MessageSearchQuery>,
+    State(state): State<SharedData>,
+) -> Result<(StatusCode, Json<SuccessResponse<MessageList>>), AppError> {
+    let _span = span!(
+        Level::DEBUG,
+        "message_search_handler",
+        session = session.session_id,
+        session_did = session.did,
+        did_hash = did_hash,
+        folder = folder.to_string()
+    );
+    async move {
+        check_list_access(&session, &did_hash)?;
+
+        if query.thid.is_some() || query.msg_type.is_some() {
+            return Err(MediatorError::problem(
+                95,
+                session.session_id,
+                None,
+                ProblemReportSorter::Error,
+                ProblemReportScope::Protocol,
+                "api.message_search.unsupported_filter",
+                "Messages can't be searched by thread id or type: stored messages are encrypted for their recipient",
+                vec![],
+                StatusCode::BAD_REQUEST,
+            )
+            .into());
+        }
+
+        // The time range is pushed down to the store as a stream id range.
+        // The sender filter isn't, so page through the range until enough
+        // messages match or it runs out.
+        let mut start = query
+            .since
+            .map_or_else(|| "-".to_string(), |since| format!("{since}-0"));
+        let end = query
+            .until
+            .map_or_else(|| "+".to_string(), |until| format!("{until}-{}", u64::MAX));
+
+        let limit = state.config.limits.listed_messages as u32;
+        let wanted = query.limit.map_or(limit, |l| l.min(limit)) as usize;
+        let mut messages = MessageList::new();
+        loop {
+            let page = state
+                .database
+                .list_messages(
+                    &did_hash,
+                    folder.clone(),
+                    Some((start.as_str(), end.as_str())),
+                    limit,
+                )
+                .await?;
+            let next = page
+                .last()
+                .filter(|_| page.len() == limit as usize)
+                .and_then(|m| m.receive_id.as_deref().or(m.send_id.as_deref()))
+                .and_then(stream_id_after);
+
+            for mut message in page {
+                // Everything in the outbox was sent by its owner
+                if folder == Folder::Outbox {
+                    message.from_address = Some(did_hash.clone());
+                }
+                if query.matches(&message) {
+                    messages.push(message);
+                }
+            }
+            match next {
+                Some(next) if messages.len() < wanted => start = next,
+                _ => break,
+            }
+        }
+        messages.truncate(wanted);
+
+        debug!("Search matched ({}) messages", messages.len());
+        Ok((
+            StatusCode::OK,
+            Json(SuccessResponse {
+                session_id: session.session_id,
+                http_code: StatusCode::OK.as_u16(),
+                error_code: 0,
+                error_code_str: "NA".to_string(),
+                message: "Success".to_string(),
+                data: Some(messages),
+            }),
+        ))
+    }
+    .instrument(_span)
+    .await
+}
+
+/// Listing and searching are allowed only for a local DID, and only for the
+/// DID the session authenticated.
+fn check_list_access(session: &Session, did_hash: &str) -> Result<(), MediatorError> {
+    // ACL Check
+    if !session.acls.get_local() {
+        return Err(MediatorError::problem(
+            40,
+            session.session_id.clone(),
+            None,
+            ProblemReportSorter::Error,
+            ProblemReportScope::Protocol,
+            "authorization.local",
+            "DID isn't local to the mediator",
+            vec![],
+            StatusCode::FORBIDDEN,
+        ));
+    }
+
+    // Sessions are scoped to a single authenticated DID — listing
+    // is allowed only for that DID. Multi-DID listing (one session
+    // owner aggregating across several DIDs) would need a session-
+    // owner concept above the DID and an owner→DIDs index in the
+    // store; tracked in PR #286's follow-up section.
+    if session
+        .did_hash
+        .as_bytes()
+        .ct_eq(did_hash.as_bytes())
+        .unwrap_u8()
+        == 0
+    {
+        return Err(MediatorError::problem(
+            45,
+            session.session_id.clone(),
+            None,
+            ProblemReportSorter::Error,
+            ProblemReportScope::Protocol,
+            "authorization.permission",
+            "DID hash does not match authenticated session",
+            vec![],
+            StatusCode::FORBIDDEN,
+        ));
+    }
+    Ok(())
+}
@@ -41,6 +41,11 @@ pub fn application_routes(api_prefix: &str, shared_data: &SharedData) -> Router
             "/list/{did_hash}/{folder}",
             get(message_list::message_list_handler),
         )
+        // Searching a DID's messages by sender and time
+        .route(
+            "/search/{did_hash}/{folder}",
+            get(message_list::message_search_handler),
+        )
         // Delete/remove messages stored in ATM
         .route("/delete", delete(message_delete::message_delete_handler))
         // Websocket endpoint for ATM clients
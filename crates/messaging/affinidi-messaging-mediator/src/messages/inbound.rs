@@ -633,10 +633,19 @@ async fn handle_inbound_didcomm(
                     // authcrypt encryption (encrypted_from_kid).
                     // Skip for unauthenticated sessions (e.g. inter-mediator relay):
                     // there is no session DID to match against.
+                    // A session holding a `messaging/send` delegation from the
+                    // sender may send as it.
                     if state.config.security.force_session_did_match && session.authenticated {
                         let sender_kid =
                             metadata.sign_from.as_ref().or(metadata.encrypted_from_kid.as_ref());
-                        check_session_sender_match(session, &msg.id, &sender_kid)?;
+                        if let Err(e) = check_session_sender_match(session, &msg.id, &sender_kid) {
+                            let sender_did =
+                                sender_kid.and_then(|kid| kid.split_once('#')).map(|(did, _)| did);
+                            if !delegated_sender(state, session, sender_did).await {
+                                return Err(e);
+                            }
+                            debug!(sender_did, "Sending on behalf of a delegator");
+                        }
                     }
 
                     // Process the message
@@ -677,9 +686,11 @@ async fn handle_inbound_didcomm(
                     // The mediator cannot decrypt a direct-delivery envelope, so the
                     // claimed sender (JWE `skid` header) is unverified. When
                     // session/sender matching is enforced, bind it to the
-                    // authenticated session DID before it is trusted for ACL checks.
+                    // authenticated session DID, or to a DID that delegated
+                    // sending to it, before it is trusted for ACL checks.
                     if state.config.security.force_session_did_match
                         && envelope.from_did.as_deref() != Some(session.did.as_str())
+                        && !delegated_sender(state, session, envelope.from_did.as_deref()).await
                     {
                         let claimed = envelope.from_did.as_deref().unwrap_or("anonymous");
                         return Err(MediatorError::problem_with_log(
@@ -874,6 +885,24 @@ async fn peel_relay_rewrap_layers(
     }
 }
 
+/// Whether `sender_did` delegated sending to the session DID, see
+/// [`authz::sends_on_behalf_of`].
+#[cfg(feature = "didcomm")]
+async fn delegated_sender(state: &SharedData, session: &Session, sender_did: Option<&str>) -> bool {
+    match sender_did {
+        Some(sender_did) => {
+            authz::sends_on_behalf_of(
+                &state.did_resolver,
+                session,
+                sender_did,
+                state.clock.unix_secs(),
+            )
+            .await
+        }
+        None => false,
+    }
+}
+
 /// Ensure the Session DID and the message sender DID match.
 /// The sender can be identified by either a JWS signature (`sign_from`)
 /// or authcrypt encryption (`encrypted_from_kid`). Both are key IDs
@@ -9,7 +9,6 @@ use crate::messages::protocols::discover_features;
 #[cfg(feature = "didcomm")]
 use crate::{SharedData, common::session::Session};
 #[cfg(feature = "didcomm")]
-use affinidi_did_common::service::Endpoint;
 #[cfg(feature = "didcomm")]
 use affinidi_did_resolver_cache_sdk::DIDCacheClient;
 #[cfg(feature = "didcomm")]
@@ -35,8 +34,6 @@ use protocols::{
     message_pickup, routing,
 };
 #[cfg(feature = "didcomm")]
-use serde_json::Value;
-
 #[cfg(feature = "didcomm")]
 pub mod error_response;
 pub mod inbound;
@@ -313,35 +310,12 @@ impl MessageHandler for Message {
             )
         })?;
 
-        fn check_loopback(endpoint: &Value, forward_locals: &HashSet<String>) -> bool {
-            let uri = if let Some(uri) = endpoint.get("uri") {
-                if let Some(uri) = uri.as_str() {
-                    uri
-                } else {
-                    return false;
-                }
-            } else {
-                return false;
-            };
-            forward_locals.contains(uri)
-        }
-
         let _forward_loopback = to_doc.doc.service.iter().any(|service| {
-            if let Endpoint::Map(endpoints) = &service.service_endpoint {
-                if endpoints.is_array() {
-                    endpoints
-                        .as_array()
-                        .map(|arr| {
-                            arr.iter()
-                                .any(|endpoint| check_loopback(endpoint, forward_locals))
-                        })
-                        .unwrap_or(false)
-                } else {
-                    check_loopback(endpoints, forward_locals)
-                }
-            } else {
-                false
-            }
+            service
+                .service_endpoint
+                .endpoint_uris()
+                .iter()
+                .any(|endpoint| forward_locals.contains(&endpoint.uri))
         });
 
         if metadata.encrypted {
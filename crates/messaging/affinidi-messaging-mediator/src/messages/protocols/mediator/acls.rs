@@ -11,7 +11,9 @@ use affinidi_messaging_sdk::messages::compat::UnpackMetadata;
 use affinidi_messaging_sdk::{
     messages::problem_report::{ProblemReportScope, ProblemReportSorter},
     protocols::mediator::{
-        accounts::AccountType, acls::MediatorACLSet, acls_handler::MediatorACLRequest,
+        accounts::AccountType,
+        acls::MediatorACLSet,
+        acls_handler::{MediatorACLModeResponse, MediatorACLRequest},
     },
 };
 use http::StatusCode;
@@ -608,6 +610,50 @@ pub(crate) async fn process(
                     }
                 }
             }
+            MediatorACLRequest::GetACLMode {} => {
+                // Only ever reports on the caller's own DID, so no admin check
+                let acls = match state
+                    .database
+                    .get_did_acls(
+                        slice::from_ref(&session.did_hash),
+                        state.config.security.mediator_acl_mode.clone(),
+                    )
+                    .await
+                {
+                    Ok(response) => response
+                        .acl_response
+                        .into_iter()
+                        .find(|entry| entry.did_hash == session.did_hash)
+                        .map(|entry| entry.acls)
+                        .unwrap_or_else(|| session.acls.clone()),
+                    Err(e) => {
+                        warn!("Error getting ACLs. Reason: {}", e);
+                        return Err(MediatorError::problem_with_log(
+                            14,
+                            &session.session_id,
+                            Some(msg.id.to_string()),
+                            ProblemReportSorter::Error,
+                            ProblemReportScope::Protocol,
+                            "me.res.storage.error",
+                            "Database transaction error: {1}",
+                            vec![e.to_string()],
+                            StatusCode::SERVICE_UNAVAILABLE,
+                            format!("Database transaction error: {e}"),
+                        ));
+                    }
+                };
+
+                _generate_response_message(
+                    &msg.id,
+                    &session.did,
+                    &state.config.mediator_did,
+                    &json!(MediatorACLModeResponse {
+                        mediator_acl_mode: state.config.security.mediator_acl_mode.clone(),
+                        did_hash: session.did_hash.clone(),
+                        acls,
+                    }),
+                )
+            }
         }
     }
     .instrument(_span)
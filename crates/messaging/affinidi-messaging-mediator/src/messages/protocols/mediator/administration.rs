@@ -2,8 +2,13 @@
 //! Must be a administrator to use this protocol
 use crate::common::authz;
 use crate::common::time::unix_timestamp_secs;
+use crate::didcomm_compat;
 use affinidi_messaging_didcomm::message::Message;
 use affinidi_messaging_mediator_common::errors::MediatorError;
+use affinidi_messaging_mediator_common::types::administration::{
+    ANNOUNCEMENT_MESSAGE_TYPE, MediatorBroadcast, MediatorBroadcastAccepted,
+    MediatorBroadcastResult,
+};
 use affinidi_messaging_mediator_common::types::audit::AuditAction;
 use affinidi_messaging_sdk::messages::compat::UnpackMetadata;
 use affinidi_messaging_sdk::{
@@ -13,6 +18,7 @@ use affinidi_messaging_sdk::{
 use http::StatusCode;
 use serde_json::{Value, json};
 use sha256::digest;
+use std::collections::HashSet;
 use subtle::ConstantTimeEq;
 use tracing::{Instrument, span, warn};
 use uuid::Uuid;
@@ -287,6 +293,16 @@ pub(crate) async fn process(
                     }
                 }
             }
+            MediatorAdminRequest::Broadcast(broadcast) => {
+                let broadcast_id = Uuid::new_v4().to_string();
+                _spawn_broadcast(state, session, &broadcast_id, broadcast);
+                _generate_response_message(
+                    &msg.id,
+                    &session.did,
+                    &state.config.mediator_did,
+                    &json!(MediatorBroadcastAccepted { broadcast_id }),
+                )
+            }
             MediatorAdminRequest::Configuration(_) => {
                 // Return the current configuration
                 let config = json!({"version": env!("CARGO_PKG_VERSION"), "config": state.config});
@@ -303,6 +319,139 @@ pub(crate) async fn process(
     .await
 }
 
+/// Runs a broadcast as a background job, so the admin request is answered
+/// before the copies are queued. The outcome (or why the fan-out stopped) is
+/// recorded in the audit log against `broadcast_id`, with the requesting admin
+/// as the actor. The job stops at mediator shutdown.
+fn _spawn_broadcast(
+    state: &SharedData,
+    session: &Session,
+    broadcast_id: &str,
+    broadcast: MediatorBroadcast,
+) {
+    let state = state.clone();
+    let actor_did_hash = session.did_hash.clone();
+    let session_id = session.session_id.clone();
+    let broadcast_id = broadcast_id.to_string();
+    let shutdown = state.shutdown_token.clone();
+
+    tokio::spawn(async move {
+        let outcome = tokio::select! {
+            _ = shutdown.cancelled() => Err("stopped by mediator shutdown".to_string()),
+            result = _broadcast(&state, &session_id, &broadcast) => result,
+        };
+        let summary = format!(
+            "{:?} announcement \"{}\" ({broadcast_id})",
+            broadcast.announcement.kind, broadcast.announcement.title
+        );
+        let detail = match outcome {
+            Ok(result) => format!(
+                "{summary}: {} delivered, {} failed",
+                result.delivered, result.failed
+            ),
+            Err(e) => {
+                warn!("Broadcast ({broadcast_id}) didn't complete. Reason: {e}");
+                format!("{summary}: not completed: {e}")
+            }
+        };
+        super::record_audit_by(
+            &state,
+            &actor_did_hash,
+            &state.config.mediator_did_hash,
+            AuditAction::Broadcast,
+            detail,
+        )
+        .await;
+    });
+}
+
+/// Fans an announcement out to every local account matching the filter.
+///
+/// The store only knows accounts by DID hash, so each copy is signed by the
+/// mediator rather than encrypted, and queued straight into the account's
+/// inbox. Copies get their own message ID (the store deduplicates identical
+/// bodies). The mediator's own account is skipped. Clients pick announcements
+/// up on their next fetch or pickup.
+async fn _broadcast(
+    state: &SharedData,
+    session_id: &str,
+    broadcast: &MediatorBroadcast,
+) -> Result<MediatorBroadcastResult, String> {
+    let now = state.clock.unix_secs();
+    let max_expiry = now + state.config.limits.message_expiry_seconds;
+    let expires_at = broadcast
+        .expires_at
+        .map_or(max_expiry, |expires_at| expires_at.min(max_expiry));
+
+    let mut result = MediatorBroadcastResult::default();
+    // The account scan may return an account more than once.
+    let mut seen = HashSet::new();
+    let mut cursor = 0;
+    loop {
+        let page = state
+            .database
+            .account_list(cursor, 100)
+            .await
+            .map_err(|e| format!("Database transaction error: {e}"))?;
+
+        for account in page.accounts.iter().filter(|account| {
+            account.did_hash != state.config.mediator_did_hash && broadcast.filter.matches(account)
+        }) {
+            if !seen.insert(account.did_hash.clone()) {
+                continue;
+            }
+
+            let announcement = Message::build(
+                Uuid::new_v4().to_string(),
+                ANNOUNCEMENT_MESSAGE_TYPE.to_owned(),
+                json!(broadcast.announcement),
+            )
+            .from(state.config.mediator_did.clone())
+            .created_time(now)
+            .expires_time(expires_at)
+            .finalize();
+
+            let packed = didcomm_compat::pack_signed(
+                &announcement,
+                &state.config.mediator_did,
+                &state.did_resolver,
+                &*state.config.security.mediator_secrets,
+            )
+            .await
+            .map_err(|e| format!("Couldn't sign announcement: {e}"))?;
+
+            match state
+                .database
+                .store_message(
+                    session_id,
+                    &packed,
+                    &account.did_hash,
+                    Some(&state.config.mediator_did_hash),
+                    expires_at,
+                    state.config.limits.queued_receive_messages_hard as usize,
+                )
+                .await
+            {
+                Ok(_) => result.delivered += 1,
+                Err(e) => {
+                    warn!(
+                        "Couldn't queue announcement for DID ({}). Reason: {}",
+                        account.did_hash, e
+                    );
+                    result.failed += 1;
+                }
+            }
+        }
+
+        if page.cursor == 0 {
+            break;
+        }
+        cursor = page.cursor;
+    }
+
+    Ok(result)
+}
+
 /// Helper method that generates a response message
 /// - `thid` - The thread ID of the message
 /// - `to` - The recipient of the message
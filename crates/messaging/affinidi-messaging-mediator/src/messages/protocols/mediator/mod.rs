@@ -19,10 +19,22 @@ pub(crate) async fn record_audit(
     target_did_hash: &str,
     action: AuditAction,
     detail: String,
+) {
+    record_audit_by(state, &session.did_hash, target_did_hash, action, detail).await
+}
+
+/// [`record_audit`] for work that outlives the caller's session, such as a
+/// broadcast fanned out in the background.
+pub(crate) async fn record_audit_by(
+    state: &SharedData,
+    actor_did_hash: &str,
+    target_did_hash: &str,
+    action: AuditAction,
+    detail: String,
 ) {
     let entry = AuditLogEntry {
         timestamp: state.clock.unix_secs(),
-        actor_did_hash: session.did_hash.clone(),
+        actor_did_hash: actor_did_hash.to_string(),
         target_did_hash: target_did_hash.to_string(),
         action,
         detail,
@@ -1026,13 +1026,9 @@ fn service_endpoint_for_remote(state: &SharedData, next_doc: &Document) -> Optio
             continue;
         }
 
-        let uris = service.service_endpoint.get_uris();
-        for uri in &uris {
-            // Strip surrounding quotes if present (from JSON serialization)
-            let uri_clean = uri.trim_matches('"');
-
+        for uri in service.service_endpoint.get_uris() {
             // If the service endpoint points to this mediator's DID, it's local
-            if uri_clean == state.config.mediator_did {
+            if uri == state.config.mediator_did {
                 return None;
             }
 
@@ -1043,19 +1039,19 @@ fn service_endpoint_for_remote(state: &SharedData, next_doc: &Document) -> Optio
             // public alias declared via `local_endpoints`) are
             // collapsed to local delivery instead of being relayed
             // through FORWARD_Q to themselves.
-            if uri_clean.starts_with("http://")
-                || uri_clean.starts_with("https://")
-                || uri_clean.starts_with("ws://")
-                || uri_clean.starts_with("wss://")
+            if uri.starts_with("http://")
+                || uri.starts_with("https://")
+                || uri.starts_with("ws://")
+                || uri.starts_with("wss://")
             {
-                if uri_points_at_self(uri_clean, &state.self_authorities) {
+                if uri_points_at_self(&uri, &state.self_authorities) {
                     debug!(
                         "Service endpoint {} resolves to a self-authority — treating as local",
-                        uri_clean
+                        uri
                     );
                     return None;
                 }
-                return Some(uri_clean.to_string());
+                return Some(uri);
             }
         }
     }
@@ -1510,7 +1510,7 @@ async fn consume_admin_audit_log(
     let limit: u32 = typed.payload.limit.map(|n| n.get() as u32).unwrap_or(100);
 
     let page = state.database.audit_log_list(cursor, limit).await?;
-    let entries = page.entries.iter().map(map_audit_entry).collect();
+    let entries = page.entries.iter().filter_map(map_audit_entry).collect();
     let next_cursor = (page.cursor != 0)
         .then(|| ResponseNextCursor::from_str(&page.cursor.to_string()))
         .transpose()
@@ -1565,20 +1565,24 @@ fn map_admin_account(a: &MediatorAdminAccount) -> admin::list::v0_1::AdminAccoun
     }
 }
 
-fn map_audit_entry(e: &AuditLogEntry) -> admin::audit_log::v0_1::AuditEntry {
+/// `None` for entries whose action has no wire form (see [`map_audit_action`]).
+fn map_audit_entry(e: &AuditLogEntry) -> Option<admin::audit_log::v0_1::AuditEntry> {
     use admin::audit_log::v0_1::{AuditEntry as Wire, Vid};
-    Wire {
-        action: map_audit_action(e.action),
+    Some(Wire {
+        action: map_audit_action(e.action)?,
         actor: Vid::from_str(&e.actor_did_hash).expect("an account hash is a valid Vid"),
         detail: Some(e.detail.clone()),
         target: Vid::from_str(&e.target_did_hash).expect("an account hash is a valid Vid"),
         timestamp: e.timestamp,
-    }
+    })
 }
 
-fn map_audit_action(a: AuditAction) -> admin::audit_log::v0_1::AuditAction {
+/// The `audit-log/0.1` action vocabulary predates broadcasts and has no
+/// `broadcast` action, so broadcast entries are only listed through the
+/// DIDComm admin-management protocol.
+fn map_audit_action(a: AuditAction) -> Option<admin::audit_log::v0_1::AuditAction> {
     use admin::audit_log::v0_1::AuditAction as W;
-    match a {
+    Some(match a {
         AuditAction::SetAcl => W::SetAcl,
         AuditAction::AccessListAdd => W::AccessListAdd,
         AuditAction::AccessListRemove => W::AccessListRemove,
@@ -1589,7 +1593,8 @@ fn map_audit_action(a: AuditAction) -> admin::audit_log::v0_1::AuditAction {
         AuditAction::AccountChangeQueueLimits => W::AccountChangeQueueLimits,
         AuditAction::AdminAdd => W::AdminAdd,
         AuditAction::AdminStrip => W::AdminStrip,
-    }
+        AuditAction::Broadcast => return None,
+    })
 }
 
 /// Map the mediator's internal [`Account`] to the wire `account/get` shape.
@@ -14,7 +14,7 @@
 use affinidi_did_common::document::DocumentExt;
 use affinidi_did_resolver_cache_sdk::DIDCacheClient;
 use affinidi_messaging_mediator_common::errors::MediatorError;
-use affinidi_secrets_resolver::{SecretsResolver, secrets::KeyType};
+use affinidi_secrets_resolver::{SecretsResolver, secrets::KeyType, usage::KeyPurpose};
 
 /// The mediator's TSP keys, derived from its DID document + operating secrets.
 pub struct MediatorTspIdentity {
@@ -48,28 +48,36 @@ impl MediatorTspIdentity {
             })?
             .doc;
 
-        let signing_key =
-            first_private_key(doc.find_authentication(None), secrets, KeyType::Ed25519)
-                .await
-                .ok_or_else(|| {
-                    MediatorError::ConfigError(
-                        12,
-                        "NA".into(),
-                        format!(
-                            "mediator DID {did} has no Ed25519 authentication key for TSP relay"
-                        ),
-                    )
-                })?;
-        let decryption_key =
-            first_private_key(doc.find_key_agreement(None), secrets, KeyType::X25519)
-                .await
-                .ok_or_else(|| {
-                    MediatorError::ConfigError(
-                        12,
-                        "NA".into(),
-                        format!("mediator DID {did} has no X25519 keyAgreement key for TSP relay"),
-                    )
-                })?;
+        let signing_key = first_private_key(
+            doc.find_authentication(None),
+            secrets,
+            KeyType::Ed25519,
+            KeyPurpose::Signing,
+        )
+        .await
+        .map_err(|refused| {
+            MediatorError::ConfigError(
+                12,
+                "NA".into(),
+                format!(
+                    "mediator DID {did} has no Ed25519 authentication key for TSP relay{refused}"
+                ),
+            )
+        })?;
+        let decryption_key = first_private_key(
+            doc.find_key_agreement(None),
+            secrets,
+            KeyType::X25519,
+            KeyPurpose::KeyAgreement,
+        )
+        .await
+        .map_err(|refused| {
+            MediatorError::ConfigError(
+                12,
+                "NA".into(),
+                format!("mediator DID {did} has no X25519 keyAgreement key for TSP relay{refused}"),
+            )
+        })?;
 
         Ok(Self {
             vid: did.to_string(),
@@ -79,29 +87,48 @@ impl MediatorTspIdentity {
     }
 }
 
-/// First verification-method `kid` whose secret is of `want` type, as a raw
-/// 32-byte private key.
+/// First verification-method `kid` whose secret is of `want` type and may be
+/// used for `purpose`, as a raw 32-byte private key.
+///
+/// When there is none, the error lists the keys the secrets resolver's key
+/// usage policy refused (empty if it refused none), for the caller to append
+/// to its message.
 async fn first_private_key(
     kids: Vec<&str>,
     secrets: &impl SecretsResolver,
     want: KeyType,
-) -> Option<[u8; 32]> {
+    purpose: KeyPurpose,
+) -> Result<[u8; 32], String> {
+    let mut refused = Vec::new();
     for kid in kids {
-        if let Some(secret) = secrets.get_secret(kid).await
+        let secret = match secrets.get_secret_for(kid, purpose).await {
+            Ok(secret) => secret,
+            Err(e) => {
+                refused.push(format!("{kid} ({e})"));
+                continue;
+            }
+        };
+        if let Some(secret) = secret
             && secret.get_key_type() == want
             && let Ok(bytes) = <[u8; 32]>::try_from(secret.get_private_bytes())
         {
-            return Some(bytes);
+            return Ok(bytes);
         }
     }
-    None
+    if refused.is_empty() {
+        Err(String::new())
+    } else {
+        Err(format!("; refused: [{}]", refused.join("; ")))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use affinidi_did_resolver_cache_sdk::config::DIDCacheConfigBuilder;
-    use affinidi_secrets_resolver::{ThreadedSecretsResolver, secrets::Secret};
+    use affinidi_secrets_resolver::{
+        ThreadedSecretsResolver, secrets::Secret, usage::KeyUsagePolicy,
+    };
 
     /// `derive` extracts the Ed25519 key from `authentication` and the X25519 key
     /// from `keyAgreement`, keyed by the document's published verification-method
@@ -166,4 +193,33 @@ mod tests {
             "no operating secrets → derivation fails"
         );
     }
+
+    /// Keys the secrets resolver's usage policy refuses are named in the error
+    /// rather than skipped as if they weren't held.
+    #[tokio::test]
+    async fn derive_reports_refused_keys() {
+        const DID_KEY: &str = "did:key:z6MkiToqovww7vYtxm1xNM15u9JzqzUFZ1k7s7MazYJUyAxv";
+        let resolver = DIDCacheClient::new(DIDCacheConfigBuilder::default().build())
+            .await
+            .unwrap();
+        let doc = resolver.resolve(DID_KEY).await.unwrap().doc;
+        let auth_kid = doc.find_authentication(None)[0].to_string();
+
+        let (secrets, _h) = ThreadedSecretsResolver::new(None).await;
+        let secrets = secrets.with_key_usage_policy(KeyUsagePolicy::Strict);
+        secrets
+            .insert(
+                Secret::generate_ed25519(Some(&auth_kid), Some(&[3u8; 32]))
+                    .with_purposes(&[KeyPurpose::KeyAgreement]),
+            )
+            .await;
+
+        let Err(err) = MediatorTspIdentity::derive(DID_KEY, &resolver, &secrets).await else {
+            panic!("a refused signing key must not be used");
+        };
+        assert!(
+            err.to_string().contains(&format!("refused: [{auth_kid}")),
+            "{err}"
+        );
+    }
 }
@@ -26,7 +26,7 @@
 //!
 //! The file is owner-only on Unix (0o600) via [`secure_fs::write_sensitive`].
 
-use affinidi_secrets_resolver::secrets::Secret;
+use affinidi_secrets_resolver::secrets::{Secret, SerializeWithPrivate};
 use serde::Serialize;
 use std::path::{Path, PathBuf};
 
@@ -44,7 +44,7 @@ struct WireProfile<'a> {
     /// The mediator's DID — the JWT audience that
     /// `mediator-monitor` uses when minting access tokens.
     mediator: &'a str,
-    secrets: Vec<&'a Secret>,
+    secrets: Vec<SerializeWithPrivate<'a, Secret>>,
 }
 
 /// Filename written next to `mediator.toml`. Stable so tools and docs
@@ -70,7 +70,7 @@ pub fn write(
         alias: "admin",
         did: admin_did,
         mediator: mediator_did,
-        secrets: vec![admin_secret],
+        secrets: vec![admin_secret.expose_secret()],
     };
     let json = serde_json::to_vec_pretty(&wire)
         .map_err(|e| anyhow::anyhow!("could not serialise admin monitor profile: {e}"))?;
@@ -1494,7 +1494,9 @@ async fn provision_secret_backend(
             .store_entry(
                 affinidi_messaging_mediator_common::OPERATING_SECRETS,
                 "operating-secrets",
-                &artefacts.mediator_secrets,
+                &affinidi_secrets_resolver::secrets::SerializeWithPrivate::new(
+                    &artefacts.mediator_secrets,
+                ),
             )
             .await
             .map_err(|e| anyhow::anyhow!("Failed to store operating secrets: {e}"))?;
@@ -14,7 +14,7 @@
 use crate::{
     ATM, SharedState, errors::ATMError, messages::DeleteMessageRequest, profiles::ATMProfile,
 };
-use affinidi_task_utils::{CancellationToken, TaskSupervisor};
+use affinidi_task_utils::CancellationToken;
 use std::sync::Arc;
 use tokio::{
     select,
@@ -32,7 +32,8 @@ pub enum DeletionHandlerCommands {
 }
 
 impl ATM {
-    /// Starts the Deletion Handler under the shared [`TaskSupervisor`].
+    /// Starts the Deletion Handler under the TDK's shared
+    /// [`TaskSupervisor`](affinidi_task_utils::TaskSupervisor).
     ///
     /// A panic or error in the handler is detected and the task is restarted
     /// with capped backoff (it is non-load-bearing — a wedged deletion loop
@@ -53,7 +54,13 @@ impl ATM {
         // and re-lock it on each (re)start.
         let from_sdk = Arc::new(Mutex::new(from_sdk));
 
-        TaskSupervisor::new(shutdown.clone()).spawn("deletion_handler", false, move || {
+        // Share the TDK's supervisor registry + status events, but keep the
+        // handler's own shutdown token so the SDK can stop it independently.
+        let supervisor = shared_state
+            .tdk_common
+            .task_supervisor()
+            .with_shutdown(shutdown.clone());
+        supervisor.spawn("deletion_handler", false, move || {
             let shared_state = shared_state.clone();
             let from_sdk = from_sdk.clone();
             let to_sdk = to_sdk.clone();
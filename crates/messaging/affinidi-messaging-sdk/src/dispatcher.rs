@@ -0,0 +1,348 @@
+//! Routing of inbound messages to the code that handles them.
+//!
+//! With an inbound message channel enabled
+//! ([`ATMConfigBuilder::with_inbound_message_channel`](crate::config::ATMConfigBuilder::with_inbound_message_channel)),
+//! every message received on a websocket is broadcast to every subscriber, and
+//! each subscriber has to filter out what isn't meant for it. A
+//! [`MessageDispatcher`] does the filtering once: each handler registers a
+//! [`Route`] and receives only the messages that match it.
+//!
+//! ```ignore
+//! let dispatcher = Arc::new(MessageDispatcher::new(32));
+//! let mut pings = dispatcher.route(Route::protocol("https://didcomm.org/trust-ping/2.0"));
+//! let mut issuance = dispatcher.route(Route::goal_code("aries.vc.issue.*").priority(10));
+//! let mut everything_else = dispatcher.unrouted();
+//!
+//! atm.dispatch_inbound(dispatcher.clone());
+//!
+//! while let Some(inbound) = pings.recv().await {
+//!     // inbound.message, inbound.metadata
+//! }
+//! ```
+//!
+//! Each message goes to exactly one route: the matching route with the highest
+//! priority, and among equal priorities the one registered first. Messages no
+//! route matches go to [`MessageDispatcher::unrouted`], if anyone asked for
+//! them, and are otherwise dropped. Dropping a route's receiver unregisters it.
+//!
+//! Patterns may contain `*`, which matches any run of characters, so
+//! `https://didcomm.org/*/2.0/*` matches every message of every 2.0 protocol.
+
+use std::sync::{Arc, Mutex};
+
+use affinidi_messaging_didcomm::message::Message;
+use tokio::{
+    sync::{broadcast, mpsc},
+    task::JoinHandle,
+};
+use tracing::{debug, warn};
+
+use crate::{ATM, messages::compat::UnpackMetadata, transports::websockets::WebSocketResponses};
+
+/// A message delivered to a route.
+#[derive(Clone, Debug)]
+pub struct InboundMessage {
+    pub message: Message,
+    pub metadata: UnpackMetadata,
+}
+
+/// What a [`Route`] matches on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Matcher {
+    /// The message type URI.
+    MessageType(String),
+    /// The goal code in the message body (or headers).
+    GoalCode(String),
+}
+
+/// Which messages a handler receives, and how it ranks against other
+/// handlers that match the same message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    matcher: Matcher,
+    priority: i32,
+}
+
+impl Route {
+    /// Messages whose type URI matches `pattern`, e.g.
+    /// `https://didcomm.org/trust-ping/2.0/ping` or
+    /// `https://didcomm.org/trust-ping/2.0/*`.
+    pub fn message_type(pattern: &str) -> Self {
+        Route {
+            matcher: Matcher::MessageType(pattern.to_string()),
+            priority: 0,
+        }
+    }
+
+    /// Every message of the protocol `uri`, e.g.
+    /// `https://didcomm.org/trust-ping/2.0`. Wildcards are allowed.
+    pub fn protocol(uri: &str) -> Self {
+        Self::message_type(&format!("{}/*", uri.trim_end_matches('/')))
+    }
+
+    /// Messages whose `goal_code` matches `pattern`, e.g.
+    /// `aries.vc.issue` or `aries.vc.*`.
+    ///
+    /// The goal code is read from the message body, as out-of-band
+    /// invitations and most protocols carry it, or else from a `goal_code`
+    /// header.
+    pub fn goal_code(pattern: &str) -> Self {
+        Route {
+            matcher: Matcher::GoalCode(pattern.to_string()),
+            priority: 0,
+        }
+    }
+
+    /// Rank of this route when several match a message; higher wins.
+    /// Default: 0.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Whether `message` matches this route.
+    pub fn matches(&self, message: &Message) -> bool {
+        match &self.matcher {
+            Matcher::MessageType(pattern) => wildcard_match(pattern, &message.typ),
+            Matcher::GoalCode(pattern) => {
+                goal_code(message).is_some_and(|code| wildcard_match(pattern, code))
+            }
+        }
+    }
+}
+
+/// The goal code of `message`, from its body or a `goal_code` header.
+fn goal_code(message: &Message) -> Option<&str> {
+    message
+        .body
+        .get("goal_code")
+        .or_else(|| message.extra.get("goal_code"))
+        .and_then(|code| code.as_str())
+}
+
+/// Match `value` against `pattern`, where `*` matches any run of characters.
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // `split` always yields at least one part
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No wildcard at all
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+struct Registered {
+    route: Route,
+    sender: mpsc::Sender<InboundMessage>,
+}
+
+/// Routes inbound messages to handlers by message type and goal code.
+///
+/// Cheap to share behind an [`Arc`]; routes can be added while it runs.
+pub struct MessageDispatcher {
+    capacity: usize,
+    /// Sorted by descending priority, then registration order.
+    routes: Mutex<Vec<Registered>>,
+    unrouted: Mutex<Option<mpsc::Sender<InboundMessage>>>,
+}
+
+impl MessageDispatcher {
+    /// Create a dispatcher whose route channels each buffer `capacity`
+    /// messages.
+    ///
+    /// A route whose buffer is full holds up dispatching until its handler
+    /// catches up, so handlers should hand slow work off rather than do it
+    /// between `recv()` calls.
+    pub fn new(capacity: usize) -> Self {
+        MessageDispatcher {
+            capacity: capacity.max(1),
+            routes: Mutex::new(Vec::new()),
+            unrouted: Mutex::new(None),
+        }
+    }
+
+    /// Register `route` and receive the messages dispatched to it.
+    pub fn route(&self, route: Route) -> mpsc::Receiver<InboundMessage> {
+        let (sender, receiver) = mpsc::channel(self.capacity);
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        // After every route of the same priority, so earlier ones win ties
+        let position = routes
+            .iter()
+            .position(|r| r.route.priority < route.priority)
+            .unwrap_or(routes.len());
+        routes.insert(position, Registered { route, sender });
+        receiver
+    }
+
+    /// Receive the messages that match no route. Replaces any previous
+    /// unrouted receiver.
+    pub fn unrouted(&self) -> mpsc::Receiver<InboundMessage> {
+        let (sender, receiver) = mpsc::channel(self.capacity);
+        *self.unrouted.lock().unwrap_or_else(|e| e.into_inner()) = Some(sender);
+        receiver
+    }
+
+    /// Number of registered routes whose receivers are still alive.
+    pub fn route_count(&self) -> usize {
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        routes.retain(|r| !r.sender.is_closed());
+        routes.len()
+    }
+
+    /// Deliver one message to the route it matches.
+    ///
+    /// Returns `true` if a route or the unrouted receiver took it, and
+    /// `false` if it was dropped.
+    pub async fn dispatch(&self, message: Message, metadata: UnpackMetadata) -> bool {
+        let mut inbound = InboundMessage { message, metadata };
+        loop {
+            // Not held across the send, so routes can be added meanwhile
+            let sender = {
+                let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+                routes.retain(|r| !r.sender.is_closed());
+                routes
+                    .iter()
+                    .find(|r| r.route.matches(&inbound.message))
+                    .map(|r| r.sender.clone())
+            };
+            let Some(sender) = sender else { break };
+            match sender.send(inbound).await {
+                Ok(()) => return true,
+                // The receiver went away after the match; try the next route
+                Err(mpsc::error::SendError(returned)) => inbound = returned,
+            }
+        }
+
+        let unrouted = self
+            .unrouted
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(unrouted) = unrouted
+            && unrouted.send(inbound).await.is_ok()
+        {
+            return true;
+        }
+        debug!("No route for inbound message, dropping it");
+        false
+    }
+
+    /// Dispatch every message from an inbound message channel until it
+    /// closes.
+    pub async fn run(&self, mut inbound: broadcast::Receiver<WebSocketResponses>) {
+        loop {
+            match inbound.recv().await {
+                Ok(WebSocketResponses::MessageReceived(message, metadata)) => {
+                    self.dispatch(*message, *metadata).await;
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("Message dispatcher fell behind, {missed} inbound messages were missed");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+impl ATM {
+    /// Spawn a task that feeds the inbound message channel to `dispatcher`.
+    ///
+    /// Returns `None` if the inbound message channel isn't enabled.
+    pub fn dispatch_inbound(&self, dispatcher: Arc<MessageDispatcher>) -> Option<JoinHandle<()>> {
+        let inbound = self.get_inbound_channel()?;
+        Some(tokio::spawn(async move { dispatcher.run(inbound).await }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(typ: &str, body: serde_json::Value) -> Message {
+        Message::build("id".to_string(), typ.to_string(), body).finalize()
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match("a.b", "a.b"));
+        assert!(!wildcard_match("a.b", "a.bc"));
+        assert!(wildcard_match("aries.vc.*", "aries.vc.issue"));
+        assert!(!wildcard_match("aries.vc.*", "aries.vp"));
+        assert!(wildcard_match(
+            "https://didcomm.org/*/2.0/*",
+            "https://didcomm.org/trust-ping/2.0/ping"
+        ));
+        assert!(!wildcard_match(
+            "https://didcomm.org/*/2.0/*",
+            "https://didcomm.org/trust-ping/1.0/ping"
+        ));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("a*a", "a"));
+    }
+
+    #[test]
+    fn routes_match_types_and_goal_codes() {
+        let ping = message("https://didcomm.org/trust-ping/2.0/ping", json!({}));
+        assert!(Route::protocol("https://didcomm.org/trust-ping/2.0").matches(&ping));
+        assert!(Route::message_type("https://didcomm.org/trust-ping/2.0/ping").matches(&ping));
+        assert!(!Route::goal_code("*").matches(&ping));
+
+        let invitation = message(
+            "https://didcomm.org/out-of-band/2.0/invitation",
+            json!({ "goal_code": "aries.vc.issue" }),
+        );
+        assert!(Route::goal_code("aries.vc.*").matches(&invitation));
+        assert!(!Route::goal_code("aries.vc.verify").matches(&invitation));
+    }
+
+    #[tokio::test]
+    async fn highest_priority_route_wins() {
+        let dispatcher = MessageDispatcher::new(4);
+        let mut protocol = dispatcher.route(Route::protocol("https://didcomm.org/trust-ping/2.0"));
+        let mut first = dispatcher.route(Route::message_type("*").priority(5));
+        let mut second = dispatcher.route(Route::message_type("*").priority(5));
+        let mut unrouted = dispatcher.unrouted();
+
+        let ping = message("https://didcomm.org/trust-ping/2.0/ping", json!({}));
+        assert!(dispatcher.dispatch(ping, UnpackMetadata::default()).await);
+        assert!(first.try_recv().is_ok());
+        assert!(second.try_recv().is_err());
+        assert!(protocol.try_recv().is_err());
+
+        // Without the catch-all routes, the protocol route gets it
+        drop(first);
+        drop(second);
+        let ping = message("https://didcomm.org/trust-ping/2.0/ping", json!({}));
+        assert!(dispatcher.dispatch(ping, UnpackMetadata::default()).await);
+        assert!(protocol.try_recv().is_ok());
+        assert_eq!(dispatcher.route_count(), 1);
+
+        let other = message("https://didcomm.org/basicmessage/2.0/message", json!({}));
+        assert!(dispatcher.dispatch(other, UnpackMetadata::default()).await);
+        assert_eq!(
+            unrouted.try_recv().unwrap().message.typ,
+            "https://didcomm.org/basicmessage/2.0/message"
+        );
+    }
+
+    #[tokio::test]
+    async fn unmatched_messages_are_dropped_without_an_unrouted_receiver() {
+        let dispatcher = MessageDispatcher::new(1);
+        let _pings = dispatcher.route(Route::protocol("https://didcomm.org/trust-ping/2.0"));
+        let other = message("https://didcomm.org/basicmessage/2.0/message", json!({}));
+        assert!(!dispatcher.dispatch(other, UnpackMetadata::default()).await);
+    }
+}
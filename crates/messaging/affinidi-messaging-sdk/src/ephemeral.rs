@@ -0,0 +1,372 @@
+/*!
+ * Ephemeral (one-time) response DIDs.
+ *
+ * Replying from a long-term DID lets every counterparty correlate the
+ * conversations it has with the same wallet. An [`EphemeralIdentities`]
+ * registry instead mints a fresh did:peer for each outbound interaction,
+ * routed through the long-term profile's mediator, and sends from that:
+ *
+ * 1. [`EphemeralIdentities::create`] generates the did:peer, registers its
+ *    secrets with the TDK secrets resolver and adds it to the ATM as a
+ *    profile, so replies addressed to it can be fetched and unpacked.
+ * 2. [`EphemeralIdentities::reply_from`] sets the DID as a message's `from`,
+ *    making it the identity the other party answers to.
+ * 3. [`EphemeralIdentities::owner_of`] maps an ephemeral DID back to the
+ *    long-term profile it stands in for, for routing inbound replies.
+ * 4. [`EphemeralIdentities::retire`] (once the interaction is finished) or
+ *    [`EphemeralIdentities::gc`] (once its time-to-live passes) shreds the
+ *    secrets and removes the profile.
+ *
+ * The mediator has to accept the new DID: on mediators that don't create
+ * accounts on first authentication, register it through the mediator
+ * administration protocol before sending.
+ */
+
+use crate::{ATM, errors::ATMError, profiles::ATMProfile};
+use affinidi_did_common::{
+    DID, PeerCreateKey, PeerKeyPurpose, PeerService, PeerServiceEndpoint, PeerServiceEndpointLong,
+    one_or_many::OneOrMany,
+};
+use affinidi_messaging_didcomm::message::Message;
+use affinidi_secrets_resolver::{SecretsResolver, secrets::Secret, usage::KeyPurpose};
+use ahash::AHashMap as HashMap;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// How long an ephemeral identity lives before [`EphemeralIdentities::gc`]
+/// removes it, unless changed with [`EphemeralIdentities::with_ttl`].
+pub const DEFAULT_EPHEMERAL_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A one-time DID standing in for a long-term profile.
+#[derive(Clone, Debug)]
+pub struct EphemeralIdentity {
+    /// The ephemeral profile registered with the ATM
+    pub profile: Arc<ATMProfile>,
+    /// Alias of the long-term profile this identity stands in for
+    pub owner: String,
+    /// Unix timestamp (seconds) the identity was created at
+    pub created_at: u64,
+    /// Unix timestamp (seconds) after which [`EphemeralIdentities::gc`]
+    /// removes the identity
+    pub expires_at: u64,
+    /// IDs of the secrets registered for the DID
+    pub(crate) secret_ids: Vec<String>,
+}
+
+impl EphemeralIdentity {
+    /// The ephemeral DID
+    pub fn did(&self) -> &str {
+        &self.profile.inner.did
+    }
+
+    /// True once `now` (unix seconds) has reached the identity's expiry
+    pub fn is_expired(&self, now: u64) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Registry of the ephemeral identities minted for an ATM instance.
+///
+/// Example:
+/// ```ignore
+/// use affinidi_messaging_sdk::ephemeral::EphemeralIdentities;
+///
+/// let ephemeral = EphemeralIdentities::new(atm.clone());
+/// let (message, identity) = ephemeral.reply_from(&alice, message).await?;
+/// // pack and send `message` from identity.profile as usual
+///
+/// // later, once a sweep is due
+/// ephemeral.gc().await;
+/// ```
+pub struct EphemeralIdentities {
+    atm: ATM,
+    ttl: Duration,
+    live_stream: bool,
+    identities: RwLock<HashMap<String, EphemeralIdentity>>,
+}
+
+impl EphemeralIdentities {
+    /// Creates an empty registry using [`DEFAULT_EPHEMERAL_TTL`]
+    pub fn new(atm: ATM) -> Self {
+        EphemeralIdentities {
+            atm,
+            ttl: DEFAULT_EPHEMERAL_TTL,
+            live_stream: false,
+            identities: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// How long identities created from now on live before [`Self::gc`]
+    /// removes them
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// If true, each ephemeral profile opens a websocket and live streams its
+    /// inbound messages. Defaults to false: replies are fetched on demand.
+    pub fn with_live_stream(mut self, live_stream: bool) -> Self {
+        self.live_stream = live_stream;
+        self
+    }
+
+    /// Mints a new ephemeral identity for `owner`.
+    ///
+    /// The did:peer is routed through the owner's mediator. Its secrets are
+    /// registered with the secrets resolver and its profile is added to the
+    /// ATM before it is returned.
+    pub async fn create(&self, owner: &ATMProfile) -> Result<EphemeralIdentity, ATMError> {
+        let (_, mediator_did) = owner.dids()?;
+        let (did, secrets) = generate_did_peer(mediator_did)?;
+
+        let secret_ids: Vec<String> = secrets.iter().map(|s| s.id.clone()).collect();
+        self.atm
+            .get_tdk()
+            .secrets_resolver()
+            .insert_vec(&secrets)
+            .await;
+
+        let profile =
+            match ATMProfile::new(&self.atm, None, did.clone(), Some(mediator_did.to_string()))
+                .await
+            {
+                Ok(profile) => profile,
+                Err(err) => {
+                    self.shred(&secret_ids).await;
+                    return Err(err);
+                }
+            };
+        let profile = match self.atm.profile_add(&profile, self.live_stream).await {
+            Ok(profile) => profile,
+            Err(err) => {
+                self.shred(&secret_ids).await;
+                return Err(err);
+            }
+        };
+
+        let created_at = now();
+        let identity = EphemeralIdentity {
+            profile,
+            owner: owner.inner.alias.clone(),
+            created_at,
+            expires_at: created_at.saturating_add(self.ttl.as_secs()),
+            secret_ids,
+        };
+        debug!(
+            "Ephemeral identity ({did}) created for profile ({})",
+            identity.owner
+        );
+
+        self.identities.write().await.insert(did, identity.clone());
+        Ok(identity)
+    }
+
+    /// Mints a new ephemeral identity for `owner` and makes it the sender of
+    /// `message`, so the recipient replies to the ephemeral DID.
+    ///
+    /// Pack and send the returned message from the returned identity's
+    /// profile.
+    pub async fn reply_from(
+        &self,
+        owner: &ATMProfile,
+        mut message: Message,
+    ) -> Result<(Message, EphemeralIdentity), ATMError> {
+        let identity = self.create(owner).await?;
+        message.from = Some(identity.did().to_string());
+        Ok((message, identity))
+    }
+
+    /// The ephemeral identity for `did`, if it is still registered
+    pub async fn get(&self, did: &str) -> Option<EphemeralIdentity> {
+        self.identities.read().await.get(did).cloned()
+    }
+
+    /// Alias of the long-term profile `did` stands in for, if `did` is a
+    /// registered ephemeral DID
+    pub async fn owner_of(&self, did: &str) -> Option<String> {
+        self.identities
+            .read()
+            .await
+            .get(did)
+            .map(|identity| identity.owner.clone())
+    }
+
+    /// The ephemeral identities currently registered for `owner`
+    pub async fn identities_of(&self, owner: &str) -> Vec<EphemeralIdentity> {
+        self.identities
+            .read()
+            .await
+            .values()
+            .filter(|identity| identity.owner == owner)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of registered ephemeral identities
+    pub async fn len(&self) -> usize {
+        self.identities.read().await.len()
+    }
+
+    /// True if no ephemeral identities are registered
+    pub async fn is_empty(&self) -> bool {
+        self.identities.read().await.is_empty()
+    }
+
+    /// Removes the ephemeral identity for `did` now: its profile is removed
+    /// from the ATM and its secrets are shredded.
+    ///
+    /// Returns true if `did` was registered.
+    pub async fn retire(&self, did: &str) -> bool {
+        let Some(identity) = self.identities.write().await.remove(did) else {
+            return false;
+        };
+        self.remove(&identity).await;
+        true
+    }
+
+    /// Removes every identity whose time-to-live has passed.
+    ///
+    /// Returns the number of identities removed.
+    pub async fn gc(&self) -> usize {
+        let now = now();
+        let expired: Vec<EphemeralIdentity> = {
+            let mut identities = self.identities.write().await;
+            let dids: Vec<String> = identities
+                .iter()
+                .filter(|(_, identity)| identity.is_expired(now))
+                .map(|(did, _)| did.clone())
+                .collect();
+            dids.iter()
+                .filter_map(|did| identities.remove(did))
+                .collect()
+        };
+
+        for identity in &expired {
+            self.remove(identity).await;
+        }
+        expired.len()
+    }
+
+    /// Removes every registered identity, expired or not.
+    ///
+    /// Returns the number of identities removed.
+    pub async fn clear(&self) -> usize {
+        let identities: Vec<EphemeralIdentity> = self
+            .identities
+            .write()
+            .await
+            .drain()
+            .map(|(_, identity)| identity)
+            .collect();
+
+        for identity in &identities {
+            self.remove(identity).await;
+        }
+        identities.len()
+    }
+
+    async fn remove(&self, identity: &EphemeralIdentity) {
+        if let Err(err) = self.atm.profile_remove(&identity.profile.inner.alias).await {
+            warn!(
+                "Ephemeral identity ({}): couldn't remove profile: {err}",
+                identity.did()
+            );
+        }
+        self.shred(&identity.secret_ids).await;
+        debug!(
+            "Ephemeral identity ({}) removed for profile ({})",
+            identity.did(),
+            identity.owner
+        );
+    }
+
+    /// Shred through the TDK, so packing contexts holding the keys drop them
+    async fn shred(&self, secret_ids: &[String]) {
+        for id in secret_ids {
+            if let Err(err) = self.atm.get_tdk().shred_secret(id, None).await {
+                warn!("Ephemeral identity key ({id}): couldn't shred: {err}");
+            }
+        }
+    }
+}
+
+/// Generates a did:peer with an Ed25519 signing key, an X25519 key agreement
+/// key and a DIDComm service routed through `mediator_did`.
+/// Returns the DID and its secrets.
+pub(crate) fn generate_did_peer(mediator_did: &str) -> Result<(String, Vec<Secret>), ATMError> {
+    let signing = Secret::generate_ed25519(None, None);
+    let key_agreement = Secret::generate_x25519(None, None)
+        .map_err(|e| ATMError::SecretsError(format!("Couldn't generate X25519 key: {e}")))?;
+
+    let mut keys = Vec::new();
+    for (purpose, secret) in [
+        (PeerKeyPurpose::Verification, &signing),
+        (PeerKeyPurpose::Encryption, &key_agreement),
+    ] {
+        keys.push(PeerCreateKey::from_multibase(
+            purpose,
+            secret
+                .get_public_keymultibase()
+                .map_err(|e| ATMError::SecretsError(format!("Couldn't encode public key: {e}")))?,
+        ));
+    }
+
+    let services = [PeerService {
+        type_: "dm".into(),
+        endpoint: PeerServiceEndpoint::Long(OneOrMany::One(PeerServiceEndpointLong {
+            uri: mediator_did.to_string(),
+            accept: vec!["didcomm/v2".into()],
+            routing_keys: vec![],
+        })),
+        id: None,
+    }];
+
+    let (did, _) = DID::generate_peer(&keys, Some(&services))
+        .map_err(|e| ATMError::DIDError(format!("Couldn't create did:peer: {e}")))?;
+    let did = did.to_string();
+
+    let mut signing = signing.with_purposes(&[KeyPurpose::Signing]);
+    signing.id = [&did, "#key-1"].concat();
+    let mut key_agreement = key_agreement.with_purposes(&[KeyPurpose::KeyAgreement]);
+    key_agreement.id = [&did, "#key-2"].concat();
+
+    Ok((did, vec![signing, key_agreement]))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEDIATOR: &str = "did:web:mediator.example.com";
+
+    #[test]
+    fn generated_dids_are_fresh() {
+        let (first, _) = generate_did_peer(MEDIATOR).unwrap();
+        let (second, _) = generate_did_peer(MEDIATOR).unwrap();
+
+        assert!(first.starts_with("did:peer:2"));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn generated_secrets_match_the_did() {
+        let (did, secrets) = generate_did_peer(MEDIATOR).unwrap();
+
+        assert_eq!(secrets.len(), 2);
+        assert_eq!(secrets[0].id, format!("{did}#key-1"));
+        assert_eq!(secrets[0].purposes(), vec![KeyPurpose::Signing]);
+        assert_eq!(secrets[1].id, format!("{did}#key-2"));
+        assert_eq!(secrets[1].purposes(), vec![KeyPurpose::KeyAgreement]);
+    }
+}
@@ -1,9 +1,12 @@
 use affinidi_did_authentication::errors::DIDAuthError;
+use affinidi_error_codes::error_codes;
 use affinidi_messaging_didcomm::message::Message;
 use affinidi_messaging_mediator_common::types::acls::ACLError;
 use affinidi_tdk_common::errors::TDKError;
 use thiserror::Error;
 
+pub use affinidi_error_codes::{ErrorCode, ErrorCodes};
+
 use crate::messages::{known::MessageType, problem_report::ProblemReport};
 
 /// ATMError
@@ -44,8 +47,37 @@ pub enum ATMError {
     MediatorError(String, String),
     #[error("ATM DID Profile error: {0}")]
     ProfileError(String),
+    #[error("Key ceremony error: {0}")]
+    KeyCeremonyError(String),
+    #[error("Profile sync error: {0}")]
+    ProfileSyncError(String),
+    #[error("Interrupted: {0}")]
+    Interrupted(#[from] affinidi_task_utils::Interrupted),
 }
 
+error_codes!(ATMError, "ATM", {
+    1 => DIDError(..): "A DID could not be parsed or resolved.",
+    2 => SecretsError(..): "A secret is missing or unusable.",
+    3 => SSLError(..): "TLS could not be set up.",
+    4 => TransportError(..): "The HTTP(S) request to the mediator failed.",
+    5 => MsgSendError(..): "A message could not be sent.",
+    6 => MsgReceiveError(..): "A message could not be received.",
+    7 => Disconnected(..): "The WebSocket connection closed.",
+    8 => ConfigError(..): "The configuration is invalid.",
+    9 => AuthenticationError(..): "Authentication with the mediator failed.",
+    10 => ACLDenied(..): "The mediator's access control denied the request.",
+    11 => ACLConfigError(..): "An access control setting is invalid.",
+    12 => DidcommError(..): "A DIDComm message could not be built, packed or unpacked.",
+    13 => SDKError(..): "The SDK hit an internal error.",
+    14 => TDKError(..): "The TDK reported an error.",
+    15 => ProblemReport(..): "The other party answered with a DIDComm problem report.",
+    16 => MediatorError(..): "The mediator returned an error.",
+    17 => ProfileError(..): "A DID profile is missing or invalid.",
+    18 => KeyCeremonyError(..): "A key ceremony failed.",
+    19 => Interrupted(..): "The caller cancelled the call or its deadline passed.",
+    20 => ProfileSyncError(..): "Profile state could not be synced with another device.",
+});
+
 impl ATMError {
     /// Creates an ATM Error from a DIDComm Problem Report Error Message
     pub fn from_problem_report(message: &Message) -> Self {
@@ -112,6 +144,13 @@ impl From<ACLError> for ATMError {
 mod tests {
     use super::*;
 
+    #[test]
+    fn error_codes_are_stable() {
+        let e = ATMError::Disconnected("closed".into());
+        assert_eq!(e.error_code().to_string(), "ATM-0007");
+        let _ = affinidi_error_codes::Registry::new().with::<ATMError>();
+    }
+
     #[test]
     fn test_from_problem_report_works() {
         let message = Message::build(
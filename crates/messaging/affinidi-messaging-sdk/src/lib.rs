@@ -179,6 +179,10 @@
 //! export RUST_LOG=none,affinidi_messaging_sdk=debug
 //! ```
 
+#[cfg(feature = "key-ceremony")]
+use crate::protocols::key_ceremony::KeyCeremonyOps;
+#[cfg(feature = "profile-sync")]
+use crate::protocols::profile_sync::ProfileSyncOps;
 #[cfg(feature = "tsp")]
 use crate::protocols::tsp::TspOps;
 /// Re-exports of the TSP relationship-store API so consumers can implement a
@@ -197,7 +201,7 @@ use crate::protocols::{
     message_pickup::MessagePickupOps, oob_discovery::OOBDiscoveryOps, routing::RoutingOps,
     trust_ping::TrustPingOps, trust_tasks::TrustTasksOps,
 };
-use affinidi_task_utils::CancellationToken;
+pub use affinidi_task_utils::{CallLimits, CancellationToken, Interrupted};
 use affinidi_tdk_common::TDKSharedState;
 use config::ATMConfig;
 use delete_handler::DeletionHandlerCommands;
@@ -219,6 +223,8 @@ const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
 //pub mod authentication;
 pub mod config;
 pub mod delete_handler;
+pub mod dispatcher;
+pub mod ephemeral;
 pub mod errors;
 pub mod messages;
 pub mod profiles;
@@ -268,13 +274,15 @@ impl ATM {
         // Create a new channel with a capacity of at most 32. This communicates from deletion handler to the SDK
         let (deletion_sdk_tx, sdk_deletion_rx) = mpsc::channel::<DeletionHandlerCommands>(32);
 
+        // A child of the TDK's token, so a TDK shutdown stops the handler too
+        let deletion_shutdown = tdk_common.task_supervisor().shutdown_token().child_token();
         let shared_state = SharedState {
             config: config.clone(),
             tdk_common,
             profiles: Arc::new(RwLock::new(Profiles::default())),
             deletion_handler_send_stream: sdk_deletion_tx,
             deletion_handler_recv_stream: Mutex::new(sdk_deletion_rx),
-            deletion_shutdown: CancellationToken::new(),
+            deletion_shutdown,
         };
 
         let atm = ATM {
@@ -391,4 +399,16 @@ impl ATM {
     pub fn tsp(&self) -> TspOps<'_> {
         TspOps { atm: self }
     }
+
+    /// Access key ceremony helpers (threshold did:webvh update keys).
+    #[cfg(feature = "key-ceremony")]
+    pub fn key_ceremony(&self) -> KeyCeremonyOps<'_> {
+        KeyCeremonyOps { atm: self }
+    }
+
+    /// Access profile sync helpers (multi-device profile state).
+    #[cfg(feature = "profile-sync")]
+    pub fn profile_sync(&self) -> ProfileSyncOps<'_> {
+        ProfileSyncOps { atm: self }
+    }
 }
@@ -0,0 +1,374 @@
+//! Sending many DIDComm messages in one call.
+//!
+//! [`ATM::send_batch`] packs and sends a list of [`OutboundMessage`]s through a
+//! profile's mediator and reports the outcome of every message individually,
+//! so one bad recipient never hides the state of the rest:
+//!
+//! - every distinct recipient DID is resolved once, up front, and a
+//!   resolution failure is reported against each of that recipient's messages
+//!   without attempting to pack them;
+//! - packing and sending run concurrently (bounded, see
+//!   [`ATM::send_batch_with_concurrency`]) over the profile's existing
+//!   WebSocket or HTTP connection;
+//! - a mediator that accepts a message but fails to store it for a recipient
+//!   is reported as a [`BatchStage::Delivery`] failure.
+//!
+//! Results come back in input order.
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use affinidi_messaging_didcomm::message::Message;
+use futures_util::{StreamExt, stream};
+use tracing::{Instrument, Level, debug, span};
+
+use crate::{
+    ATM,
+    errors::ATMError,
+    messages::{SuccessResponse, sending::InboundMessageResponse},
+    profiles::ATMProfile,
+    transports::SendMessageResponse,
+};
+
+/// Number of messages packed and sent at the same time by [`ATM::send_batch`].
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 16;
+
+/// A plaintext message and the DID it is for.
+#[derive(Clone, Debug)]
+pub struct OutboundMessage {
+    pub message: Message,
+    pub to: String,
+    /// Anoncrypt the message instead of authcrypting it from the profile's DID.
+    pub anonymous: bool,
+    /// Time (unix seconds) after which the mediator may drop the message if it
+    /// has not been delivered.
+    pub expires_time: Option<u64>,
+}
+
+impl OutboundMessage {
+    /// Authcrypt `message` from the sending profile to `to`.
+    pub fn new(message: Message, to: impl Into<String>) -> Self {
+        OutboundMessage {
+            message,
+            to: to.into(),
+            anonymous: false,
+            expires_time: None,
+        }
+    }
+
+    /// Send anonymously (anoncrypt, unsigned forward).
+    pub fn anonymous(mut self) -> Self {
+        self.anonymous = true;
+        self
+    }
+
+    /// Let the mediator drop the message if it is undelivered at `expires_time`.
+    pub fn expires_time(mut self, expires_time: u64) -> Self {
+        self.expires_time = Some(expires_time);
+        self
+    }
+}
+
+/// The step at which a batched message failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchStage {
+    /// The recipient DID could not be resolved.
+    Resolve,
+    /// The message could not be packed for the recipient.
+    Pack,
+    /// The packed message could not be handed to the mediator.
+    Send,
+    /// The mediator accepted the message but could not store it for delivery.
+    Delivery,
+}
+
+impl fmt::Display for BatchStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchStage::Resolve => write!(f, "resolve"),
+            BatchStage::Pack => write!(f, "pack"),
+            BatchStage::Send => write!(f, "send"),
+            BatchStage::Delivery => write!(f, "delivery"),
+        }
+    }
+}
+
+/// Outcome of one batched message.
+#[derive(Debug)]
+pub enum DeliveryStatus {
+    /// Handed to the mediator; carries the mediator's response.
+    Sent(SendMessageResponse),
+    Failed {
+        stage: BatchStage,
+        error: ATMError,
+    },
+}
+
+/// Outcome of one [`OutboundMessage`], identified by its message ID.
+#[derive(Debug)]
+pub struct BatchItemResult {
+    pub message_id: String,
+    pub to: String,
+    pub status: DeliveryStatus,
+}
+
+impl BatchItemResult {
+    pub fn is_sent(&self) -> bool {
+        matches!(self.status, DeliveryStatus::Sent(_))
+    }
+}
+
+/// Per-message results of [`ATM::send_batch`], in input order.
+#[derive(Debug, Default)]
+pub struct BatchSendReport {
+    pub results: Vec<BatchItemResult>,
+}
+
+impl BatchSendReport {
+    /// True when every message was sent.
+    pub fn all_sent(&self) -> bool {
+        self.results.iter().all(BatchItemResult::is_sent)
+    }
+
+    /// Messages that were sent.
+    pub fn sent(&self) -> impl Iterator<Item = &BatchItemResult> {
+        self.results.iter().filter(|r| r.is_sent())
+    }
+
+    /// Messages that failed, with the stage and reason.
+    pub fn failed(&self) -> impl Iterator<Item = &BatchItemResult> {
+        self.results.iter().filter(|r| !r.is_sent())
+    }
+}
+
+impl ATM {
+    /// Pack and send many messages through the profile's mediator, reporting
+    /// the outcome of each. Uses [`DEFAULT_BATCH_CONCURRENCY`].
+    ///
+    /// Returns an error only when the batch cannot start at all (e.g. the
+    /// profile has no mediator); per-message failures are in the report.
+    pub async fn send_batch(
+        &self,
+        profile: &Arc<ATMProfile>,
+        messages: Vec<OutboundMessage>,
+    ) -> Result<BatchSendReport, ATMError> {
+        self.send_batch_with_concurrency(profile, messages, DEFAULT_BATCH_CONCURRENCY)
+            .await
+    }
+
+    /// [`ATM::send_batch`] with at most `concurrency` messages in flight.
+    pub async fn send_batch_with_concurrency(
+        &self,
+        profile: &Arc<ATMProfile>,
+        messages: Vec<OutboundMessage>,
+        concurrency: usize,
+    ) -> Result<BatchSendReport, ATMError> {
+        let _span = span!(Level::DEBUG, "send_batch", count = messages.len());
+
+        async move {
+            let (profile_did, mediator_did) = profile.dids()?;
+            let concurrency = concurrency.max(1);
+
+            // Resolve each recipient once; the resolver cache then serves
+            // every pack for that recipient.
+            let mut recipients: Vec<&str> = messages.iter().map(|m| m.to.as_str()).collect();
+            recipients.sort_unstable();
+            recipients.dedup();
+            let unresolved: HashMap<String, String> = stream::iter(recipients)
+                .map(|did| async move {
+                    self.inner
+                        .tdk_common
+                        .did_resolver()
+                        .resolve(did)
+                        .await
+                        .err()
+                        .map(|e| (did.to_string(), e.to_string()))
+                })
+                .buffer_unordered(concurrency)
+                .filter_map(|failed| async move { failed })
+                .collect()
+                .await;
+
+            let results = stream::iter(messages)
+                .map(|outbound| {
+                    let resolve_error = unresolved.get(&outbound.to).cloned();
+                    async move {
+                        let status = match resolve_error {
+                            Some(error) => DeliveryStatus::Failed {
+                                stage: BatchStage::Resolve,
+                                error: ATMError::DIDError(format!(
+                                    "Couldn't resolve {}: {error}",
+                                    outbound.to
+                                )),
+                            },
+                            None => {
+                                self.send_batch_item(profile, profile_did, mediator_did, &outbound)
+                                    .await
+                            }
+                        };
+                        BatchItemResult {
+                            message_id: outbound.message.id,
+                            to: outbound.to,
+                            status,
+                        }
+                    }
+                })
+                .buffered(concurrency)
+                .collect::<Vec<_>>()
+                .await;
+
+            let report = BatchSendReport { results };
+            debug!(
+                "batch sent: {} ok, {} failed",
+                report.sent().count(),
+                report.failed().count()
+            );
+            Ok(report)
+        }
+        .instrument(_span)
+        .await
+    }
+
+    /// Pack one message for its recipient, wrap it in a forward to the
+    /// profile's mediator (unless the mediator is the recipient) and send it.
+    async fn send_batch_item(
+        &self,
+        profile: &Arc<ATMProfile>,
+        profile_did: &str,
+        mediator_did: &str,
+        outbound: &OutboundMessage,
+    ) -> DeliveryStatus {
+        let failed = |stage, error| DeliveryStatus::Failed { stage, error };
+
+        let from = (!outbound.anonymous).then_some(profile_did);
+        let packed = match self
+            .inner
+            .pack_encrypted(&outbound.message, &outbound.to, from)
+            .await
+        {
+            Ok((packed, _)) => packed,
+            Err(e) => return failed(BatchStage::Pack, e),
+        };
+
+        let (send_id, envelope) = if outbound.to == mediator_did {
+            (outbound.message.id.clone(), packed)
+        } else {
+            match self
+                .routing()
+                .forward_message(
+                    profile,
+                    outbound.anonymous,
+                    &packed,
+                    mediator_did,
+                    &outbound.to,
+                    outbound.expires_time,
+                    None,
+                )
+                .await
+            {
+                Ok(forward) => forward,
+                Err(e) => return failed(BatchStage::Pack, e),
+            }
+        };
+
+        match self
+            .send_message(profile, &envelope, &send_id, false, false)
+            .await
+        {
+            Ok(response) => match storage_errors(&response) {
+                Some(errors) => failed(
+                    BatchStage::Delivery,
+                    ATMError::MsgSendError(format!("Mediator couldn't store message: {errors}")),
+                ),
+                None => DeliveryStatus::Sent(response),
+            },
+            Err(e) => failed(BatchStage::Send, e),
+        }
+    }
+}
+
+/// Storage errors reported in a mediator's REST response, if any.
+fn storage_errors(response: &SendMessageResponse) -> Option<String> {
+    let SendMessageResponse::RestAPI(value) = response else {
+        return None;
+    };
+    let response: SuccessResponse<InboundMessageResponse> =
+        serde_json::from_value(value.clone()).ok()?;
+    match response.data? {
+        InboundMessageResponse::Stored(list) if !list.errors.is_empty() => Some(
+            list.errors
+                .iter()
+                .map(|(recipient, error)| format!("{recipient}: {error}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(id: &str, status: DeliveryStatus) -> BatchItemResult {
+        BatchItemResult {
+            message_id: id.into(),
+            to: "did:example:bob".into(),
+            status,
+        }
+    }
+
+    #[test]
+    fn report_partitions_results() {
+        let report = BatchSendReport {
+            results: vec![
+                item(
+                    "1",
+                    DeliveryStatus::Sent(SendMessageResponse::EmptyResponse),
+                ),
+                item(
+                    "2",
+                    DeliveryStatus::Failed {
+                        stage: BatchStage::Pack,
+                        error: ATMError::DIDError("no key agreement".into()),
+                    },
+                ),
+            ],
+        };
+        assert!(!report.all_sent());
+        assert_eq!(
+            report
+                .sent()
+                .map(|r| r.message_id.as_str())
+                .collect::<Vec<_>>(),
+            ["1"]
+        );
+        assert_eq!(
+            report
+                .failed()
+                .map(|r| r.message_id.as_str())
+                .collect::<Vec<_>>(),
+            ["2"]
+        );
+    }
+
+    #[test]
+    fn storage_errors_are_surfaced() {
+        let response = SendMessageResponse::RestAPI(json!({
+            "sessionId": "s",
+            "httpCode": 200,
+            "errorCode": 0,
+            "errorCodeStr": "Ok",
+            "message": "Success",
+            "data": { "Stored": {
+                "messages": [],
+                "errors": [["did:example:bob", "queue full"]]
+            }}
+        }));
+        assert_eq!(
+            storage_errors(&response).as_deref(),
+            Some("did:example:bob: queue full")
+        );
+        assert!(storage_errors(&SendMessageResponse::EmptyResponse).is_none());
+    }
+}
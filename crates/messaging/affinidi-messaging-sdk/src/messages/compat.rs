@@ -3,6 +3,11 @@
 //! These types replicate the legacy API surface so that callers of the SDK
 //! (e.g. WebSocket cache, protocol handlers) continue to work without changes.
 
+use affinidi_messaging_didcomm::Message;
+pub use affinidi_messaging_didcomm::metadata::{
+    AuthenticationLevel, ForwardHop, UnpackAlgorithms, UnpackTiming,
+};
+
 /// Compatibility type matching the legacy `UnpackMetadata`.
 ///
 /// The new `affinidi_messaging_didcomm` crate returns structured `UnpackResult` variants
@@ -20,6 +25,58 @@ pub struct UnpackMetadata {
     pub sign_from: Option<String>,
     /// SHA-256 hash of the packed message (computed by the SDK before unpacking)
     pub sha256_hash: String,
+    #[serde(default)]
+    pub algorithms: UnpackAlgorithms,
+    /// Forward envelopes unwrapped to reach the message, outermost first.
+    /// Only populated when the SDK unwraps forwards (`unpack_forwards`).
+    #[serde(default)]
+    pub forward_hops: Vec<ForwardHop>,
+    #[serde(default)]
+    pub timing: Option<UnpackTiming>,
+}
+
+impl UnpackMetadata {
+    /// How the message was protected, derived from the flags above.
+    pub fn authentication_level(&self) -> AuthenticationLevel {
+        AuthenticationLevel::from_flags(self.encrypted, self.authenticated, self.non_repudiation)
+    }
+
+    /// The sender DID the envelope proves, or `None` if it proves none.
+    ///
+    /// A message's `from` is just a header the sender wrote. It is only
+    /// trustworthy when the message was authcrypted and the DID of the key
+    /// that encrypted it (`encrypted_from_kid`) is that same `from`.
+    pub fn authenticated_sender<'a>(&self, from: Option<&'a str>) -> Option<&'a str> {
+        let from = from?;
+        let kid = self.encrypted_from_kid.as_deref()?;
+        let kid_did = kid.split_once('#').map_or(kid, |(did, _)| did);
+        (self.encrypted && self.authenticated && kid_did == from).then_some(from)
+    }
+
+    /// The `from` of `message` if the envelope proves it sent the message
+    /// (see [`Self::authenticated_sender`]), otherwise the error `err` makes.
+    pub fn require_authenticated_sender<'a, E>(
+        &self,
+        message: &'a Message,
+        err: impl FnOnce() -> E,
+    ) -> Result<&'a str, E> {
+        self.authenticated_sender(message.from.as_deref())
+            .ok_or_else(err)
+    }
+}
+
+#[cfg(all(test, any(feature = "key-ceremony", feature = "profile-sync")))]
+impl UnpackMetadata {
+    /// Metadata of `message` unpacked from an authcrypt envelope sent by its
+    /// `from`.
+    pub(crate) fn authcrypted(message: &Message) -> Self {
+        Self {
+            encrypted: true,
+            authenticated: true,
+            encrypted_from_kid: message.from.as_ref().map(|from| format!("{from}#key-2")),
+            ..Default::default()
+        }
+    }
 }
 
 /// Compatibility type for the legacy `PackEncryptedMetadata`.
@@ -114,6 +114,7 @@ impl ATM {
             } else {
                 return Err(ATMError::TransportError("No messages found".to_string()));
             };
+            self.record_messages_received(profile, &list);
 
             if let FetchDeletePolicy::OnReceive = options.delete_policy {
                 match self
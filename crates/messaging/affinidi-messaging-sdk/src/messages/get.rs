@@ -102,9 +102,23 @@ impl ATM {
                 }
             }
 
+            self.record_messages_received(profile, &list);
+
             Ok(list)
         }
         .instrument(_span)
         .await
     }
+
+    /// Count the messages of `list` in the profile's usage.
+    pub(crate) fn record_messages_received(
+        &self,
+        profile: &ATMProfile,
+        list: &GetMessagesResponse,
+    ) {
+        let usage = self.get_tdk().usage_tracker();
+        for msg in list.success.iter().filter_map(|m| m.msg.as_ref()) {
+            usage.record_message_received(&profile.inner.did, msg.len());
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+pub mod batch;
 pub mod compat;
 pub mod delete;
 pub mod fetch;
@@ -7,7 +8,10 @@ pub mod get;
 pub mod known;
 pub mod list;
 pub mod pack;
+pub mod packing_context;
 pub mod problem_report;
+pub mod search;
+pub mod self_encrypted;
 pub mod sending;
 pub mod unpack;
 
@@ -20,7 +24,7 @@ pub mod unpack;
 // paths working unchanged.
 pub use affinidi_messaging_mediator_common::types::messages::{
     FetchDeletePolicy, Folder, GenericDataStruct, GetMessagesResponse, MessageList,
-    MessageListElement, MessageProtocol,
+    MessageListElement, MessageProtocol, MessageSearchQuery,
 };
 
 pub trait MessageDelete<T> {
@@ -1,10 +1,11 @@
-use affinidi_crypto::jose::key_agreement::{Curve, PrivateKeyAgreement};
+use affinidi_crypto::jose::key_agreement::{Curve, PrivateKeyAgreement, PublicKeyAgreement};
 use affinidi_did_common::{
     document::DocumentExt,
     key_negotiation::{DEFAULT_CURVE_PREFERENCE, negotiate_authcrypt, select_anoncrypt_key},
 };
 use affinidi_messaging_didcomm::message::{Message, pack};
-use affinidi_secrets_resolver::SecretsResolver;
+use affinidi_secrets_resolver::{SecretsResolver, usage::KeyPurpose};
+use affinidi_tdk_common::usage::UsageMetric;
 use tracing::{Instrument, Level, debug, span};
 
 use crate::{ATM, SharedState, errors::ATMError};
@@ -28,6 +29,62 @@ impl ATM {
     }
 }
 
+/// Keys negotiated for one sender/recipient pair, ready to pack with.
+///
+/// Negotiation resolves both DID documents and loads the sender's secrets;
+/// packing with the result does neither, so it can be reused for as long as
+/// both documents and the sender's secrets stay the same (see
+/// [`super::packing_context`]).
+#[derive(Clone, Debug)]
+pub(crate) struct PackKeys {
+    /// Sender key id and private key. `None` for anoncrypt.
+    sender: Option<(String, PrivateKeyAgreement)>,
+    recipient_kid: String,
+    recipient_pub: PublicKeyAgreement,
+}
+
+impl PackKeys {
+    pub(crate) fn pack(
+        &self,
+        message: &Message,
+    ) -> Result<(String, PackEncryptedMetadata), ATMError> {
+        let recipients = [(self.recipient_kid.as_str(), &self.recipient_pub)];
+        if let Some((sender_kid, sender_private)) = &self.sender {
+            let packed =
+                pack::pack_encrypted_authcrypt(message, sender_kid, sender_private, &recipients)
+                    .map_err(|e| {
+                        ATMError::DidcommError(
+                            "SDK".to_string(),
+                            format!("pack_encrypted() authcrypt failed. Reason: {e}"),
+                        )
+                    })?;
+
+            let metadata = PackEncryptedMetadata {
+                from_kid: Some(sender_kid.clone()),
+                sign_by_kid: None,
+                to_kids: vec![self.recipient_kid.clone()],
+            };
+
+            Ok((packed, metadata))
+        } else {
+            let packed = pack::pack_encrypted_anoncrypt(message, &recipients).map_err(|e| {
+                ATMError::DidcommError(
+                    "SDK".to_string(),
+                    format!("pack_encrypted() anoncrypt failed. Reason: {e}"),
+                )
+            })?;
+
+            let metadata = PackEncryptedMetadata {
+                from_kid: None,
+                sign_by_kid: None,
+                to_kids: vec![self.recipient_kid.clone()],
+            };
+
+            Ok((packed, metadata))
+        }
+    }
+}
+
 impl SharedState {
     /// Pack a message for sending to a recipient
     /// from: if None, then will use anonymous encryption
@@ -39,136 +96,132 @@ impl SharedState {
     ) -> Result<(String, PackEncryptedMetadata), ATMError> {
         let _span = span!(Level::DEBUG, "pack_encrypted",);
 
-        async move {
-            // Resolve recipient DID document (needed for both anoncrypt and authcrypt)
-            let recipient_doc = self
+        async move { self.negotiate_pack_keys(to, from).await?.pack(message) }
+            .instrument(_span)
+            .await
+    }
+
+    /// Resolve both parties and pick the keys to pack with.
+    /// from: if None, then negotiates for anonymous encryption
+    pub(crate) async fn negotiate_pack_keys(
+        &self,
+        to: &str,
+        from: Option<&str>,
+    ) -> Result<PackKeys, ATMError> {
+        // Resolve recipient DID document (needed for both anoncrypt and authcrypt)
+        let recipient_doc = self
+            .tdk_common
+            .did_resolver()
+            .resolve(to)
+            .await
+            .map_err(|e| {
+                ATMError::DidcommError(
+                    "pack_encrypted".into(),
+                    format!("Failed to resolve recipient DID: {e}"),
+                )
+            })?;
+        let recipient_ka_kids = recipient_doc.doc.find_key_agreement(None);
+
+        // Curve-preference policy: a runtime override from config, else
+        // the negotiator's documented default order. Shared by both the
+        // authcrypt and anoncrypt paths so they never disagree.
+        let preference = self
+            .config
+            .get_curve_preference()
+            .unwrap_or(&DEFAULT_CURVE_PREFERENCE);
+
+        if let Some(sender_did) = from {
+            // Authcrypt: enumerate the sender's *usable* key-agreement
+            // keys (a secret we hold, on a supported curve), then
+            // negotiate the best shared curve with the recipient.
+            // Selection follows the documented curve preference, so the
+            // sender's list order is irrelevant — a later sender curve can
+            // still match when the first does not.
+            let sender_doc = self
                 .tdk_common
                 .did_resolver()
-                .resolve(to)
+                .resolve(sender_did)
                 .await
                 .map_err(|e| {
                     ATMError::DidcommError(
                         "pack_encrypted".into(),
-                        format!("Failed to resolve recipient DID: {e}"),
+                        format!("Failed to resolve sender DID: {e}"),
                     )
                 })?;
-            let recipient_ka_kids = recipient_doc.doc.find_key_agreement(None);
-
-            // Curve-preference policy: a runtime override from config, else
-            // the negotiator's documented default order. Shared by both the
-            // authcrypt and anoncrypt paths so they never disagree.
-            let preference = self
-                .config
-                .get_curve_preference()
-                .unwrap_or(&DEFAULT_CURVE_PREFERENCE);
-
-            if let Some(sender_did) = from {
-                // Authcrypt: enumerate the sender's *usable* key-agreement
-                // keys (a secret we hold, on a supported curve), then
-                // negotiate the best shared curve with the recipient.
-                // Selection follows the documented curve preference, so the
-                // sender's list order is irrelevant — a later sender curve can
-                // still match when the first does not.
-                let sender_doc = self
+            let sender_ka_kids = sender_doc.doc.find_key_agreement(None);
+            // Both resolutions were made on the sender's behalf; only those
+            // that missed the cache count
+            let fresh = [&recipient_doc, &sender_doc]
+                .into_iter()
+                .filter(|resolved| !resolved.cache_hit)
+                .count() as u64;
+            if fresh > 0 {
+                self.tdk_common
+                    .usage_tracker()
+                    .record(sender_did, UsageMetric::Resolutions, fresh);
+            }
+
+            let mut sender_keys: Vec<(&str, PrivateKeyAgreement, Curve)> = Vec::new();
+            for &kid in &sender_ka_kids {
+                let Some(secret) = self
                     .tdk_common
-                    .did_resolver()
-                    .resolve(sender_did)
+                    .secrets_resolver()
+                    .get_secret_for(kid, KeyPurpose::KeyAgreement)
                     .await
-                    .map_err(|e| {
-                        ATMError::DidcommError(
-                            "pack_encrypted".into(),
-                            format!("Failed to resolve sender DID: {e}"),
-                        )
-                    })?;
-                let sender_ka_kids = sender_doc.doc.find_key_agreement(None);
-
-                let mut sender_keys: Vec<(&str, PrivateKeyAgreement, Curve)> = Vec::new();
-                for &kid in &sender_ka_kids {
-                    let Some(secret) = self.tdk_common.secrets_resolver().get_secret(kid).await
-                    else {
-                        continue;
-                    };
-                    let Some(curve) = secret.get_key_type().key_agreement_curve() else {
-                        continue;
-                    };
-                    match PrivateKeyAgreement::from_raw_bytes(curve, secret.get_private_bytes()) {
-                        Ok(private) => sender_keys.push((kid, private, curve)),
-                        Err(e) => debug!("skipping unusable sender key {kid}: {e}"),
-                    }
-                }
-                if sender_keys.is_empty() {
-                    return Err(ATMError::DidcommError(
-                        "pack_encrypted".into(),
-                        "sender has no usable key agreement key".into(),
-                    ));
-                }
-                let sender_curves: Vec<Curve> = sender_keys.iter().map(|(_, _, c)| *c).collect();
-
-                let pairing = negotiate_authcrypt(
-                    &sender_curves,
-                    &recipient_doc.doc,
-                    &recipient_ka_kids,
-                    preference,
-                )
-                .map_err(|e| ATMError::DidcommError("pack_encrypted".into(), e.to_string()))?;
-
-                // The negotiated curve was drawn from `sender_curves`, so a
-                // matching sender key is guaranteed present.
-                let (sender_kid, sender_private, _) = sender_keys
-                    .iter()
-                    .find(|(_, _, c)| *c == pairing.curve)
-                    .expect("negotiated curve came from sender_curves");
-
-                let packed = pack::pack_encrypted_authcrypt(
-                    message,
-                    sender_kid,
-                    sender_private,
-                    &[(pairing.recipient_kid, &pairing.recipient_pub)],
-                )
-                .map_err(|e| {
-                    ATMError::DidcommError(
-                        "SDK".to_string(),
-                        format!("pack_encrypted() authcrypt failed. Reason: {e}"),
-                    )
-                })?;
-
-                let metadata = PackEncryptedMetadata {
-                    from_kid: Some(sender_kid.to_string()),
-                    sign_by_kid: None,
-                    to_kids: vec![pairing.recipient_kid.to_string()],
+                    .map_err(|e| ATMError::SecretsError(e.to_string()))?
+                else {
+                    continue;
                 };
-
-                Ok((packed, metadata))
-            } else {
-                // Anoncrypt: pick the first advertised key-agreement key that
-                // resolves to a supported curve (skipping undecodable codecs),
-                // rather than blindly taking `first()`.
-                let (recipient_kid, recipient_pub) =
-                    select_anoncrypt_key(&recipient_doc.doc, &recipient_ka_kids, preference)
-                        .map_err(|e| {
-                            ATMError::DidcommError("pack_encrypted".into(), e.to_string())
-                        })?;
-
-                let packed =
-                    pack::pack_encrypted_anoncrypt(message, &[(recipient_kid, &recipient_pub)])
-                        .map_err(|e| {
-                            ATMError::DidcommError(
-                                "SDK".to_string(),
-                                format!("pack_encrypted() anoncrypt failed. Reason: {e}"),
-                            )
-                        })?;
-
-                let metadata = PackEncryptedMetadata {
-                    from_kid: None,
-                    sign_by_kid: None,
-                    to_kids: vec![recipient_kid.to_string()],
+                let Some(curve) = secret.get_key_type().key_agreement_curve() else {
+                    continue;
                 };
-
-                Ok((packed, metadata))
+                match PrivateKeyAgreement::from_raw_bytes(curve, secret.get_private_bytes()) {
+                    Ok(private) => sender_keys.push((kid, private, curve)),
+                    Err(e) => debug!("skipping unusable sender key {kid}: {e}"),
+                }
+            }
+            if sender_keys.is_empty() {
+                return Err(ATMError::DidcommError(
+                    "pack_encrypted".into(),
+                    "sender has no usable key agreement key".into(),
+                ));
             }
+            let sender_curves: Vec<Curve> = sender_keys.iter().map(|(_, _, c)| *c).collect();
+
+            let pairing = negotiate_authcrypt(
+                &sender_curves,
+                &recipient_doc.doc,
+                &recipient_ka_kids,
+                preference,
+            )
+            .map_err(|e| ATMError::DidcommError("pack_encrypted".into(), e.to_string()))?;
+
+            // The negotiated curve was drawn from `sender_curves`, so a
+            // matching sender key is guaranteed present.
+            let (sender_kid, sender_private, _) = sender_keys
+                .into_iter()
+                .find(|(_, _, c)| *c == pairing.curve)
+                .expect("negotiated curve came from sender_curves");
+
+            Ok(PackKeys {
+                sender: Some((sender_kid.to_string(), sender_private)),
+                recipient_kid: pairing.recipient_kid.to_string(),
+                recipient_pub: pairing.recipient_pub,
+            })
+        } else {
+            // Anoncrypt: pick the first advertised key-agreement key that
+            // resolves to a supported curve (skipping undecodable codecs),
+            // rather than blindly taking `first()`.
+            let (recipient_kid, recipient_pub) =
+                select_anoncrypt_key(&recipient_doc.doc, &recipient_ka_kids, preference)
+                    .map_err(|e| ATMError::DidcommError("pack_encrypted".into(), e.to_string()))?;
+
+            Ok(PackKeys {
+                sender: None,
+                recipient_kid: recipient_kid.to_string(),
+                recipient_pub,
+            })
         }
-        .instrument(_span)
-        .await
     }
 
     /// creates a plaintext (unencrypted and unsigned) message
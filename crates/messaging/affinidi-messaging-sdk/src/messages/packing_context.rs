@@ -0,0 +1,409 @@
+//! Per-conversation packing.
+//!
+//! [`ATM::pack_encrypted`] resolves both DID documents, loads the sender's
+//! secrets and negotiates a curve on every call. In an active chat that work
+//! is identical from one message to the next. A [`PackingContext`] does it
+//! once per sender/recipient pair and packs every later message with the
+//! same keys:
+//!
+//! ```ignore
+//! let chat = atm.packing_context(&bob_did, Some(&alice_did));
+//! for message in messages {
+//!     let (packed, _) = chat.pack(&message).await?;
+//!     // send `packed`
+//! }
+//! ```
+//!
+//! The context also caches the recipient's routing chain
+//! ([`PackingContext::routing_chain`]), the mediators its `DIDCommMessaging`
+//! service says to forward through.
+//!
+//! Cached results stay correct when a document changes: the context listens
+//! to the DID resolver's change events
+//! (`DIDCacheClient::subscribe_changes`) and renegotiates the first time it
+//! is used after the sender's, the recipient's or a mediator's document
+//! leaves the resolver cache. It also holds a copy of the sender's private
+//! key, so it listens to the TDK's secret changes
+//! (`TDKSharedState::subscribe_secret_changes`) as well, and renegotiates
+//! once a secret of the sender is added, replaced, removed or shredded. After
+//! editing the secrets resolver directly, announce the key with
+//! `TDKSharedState::notify_secret_changed`, or call
+//! [`PackingContext::invalidate`] on the contexts you hold.
+
+use std::sync::{Arc, Mutex};
+
+use affinidi_did_common::{
+    Document, peer_dids_equivalent,
+    service::{EndpointUri, Service},
+};
+use affinidi_messaging_didcomm::message::Message;
+use affinidi_tdk_common::usage::UsageMetric;
+use tokio::sync::broadcast::{Receiver, error::TryRecvError};
+use tracing::{Instrument, Level, debug, span};
+
+use crate::{ATM, errors::ATMError};
+
+use super::{compat::PackEncryptedMetadata, pack::PackKeys};
+
+/// Deepest chain of mediators [`PackingContext::routing_chain`] follows.
+pub const MAX_ROUTING_DEPTH: usize = 4;
+
+impl ATM {
+    /// Create a [`PackingContext`] for messages from `from` to `to`.
+    /// from: if None, then packs with anonymous encryption
+    ///
+    /// Nothing is resolved until the first message is packed.
+    pub fn packing_context(&self, to: &str, from: Option<&str>) -> PackingContext {
+        PackingContext {
+            atm: self.clone(),
+            to: to.to_string(),
+            from: from.map(str::to_string),
+            state: Arc::new(Mutex::new(ContextState {
+                changes: self.inner.tdk_common.did_resolver().subscribe_changes(),
+                secret_changes: self.inner.tdk_common.subscribe_secret_changes(),
+                keys: None,
+                routing: None,
+                generation: 0,
+            })),
+        }
+    }
+}
+
+/// Packs messages between one sender and one recipient, reusing the keys
+/// negotiated for the first message.
+///
+/// Cheap to clone; clones share the cached keys.
+#[derive(Clone)]
+pub struct PackingContext {
+    atm: ATM,
+    to: String,
+    from: Option<String>,
+    state: Arc<Mutex<ContextState>>,
+}
+
+struct ContextState {
+    changes: Receiver<String>,
+    /// Key ids whose secret was added, replaced, removed or shredded
+    secret_changes: Receiver<String>,
+    keys: Option<Arc<PackKeys>>,
+    routing: Option<Arc<[String]>>,
+    /// Bumped whenever something is invalidated, so a negotiation that
+    /// raced a document change doesn't cache its stale result.
+    generation: u64,
+}
+
+impl ContextState {
+    /// Drop whatever a document or secret change has made stale.
+    fn apply_changes(&mut self, to: &str, from: Option<&str>) {
+        loop {
+            match self.secret_changes.try_recv() {
+                Ok(key_id) => {
+                    let did = key_id.split('#').next().unwrap_or(&key_id);
+                    if from.is_some_and(|from| peer_dids_equivalent(did, from)) {
+                        self.keys = None;
+                        self.generation += 1;
+                    }
+                }
+                Err(TryRecvError::Lagged(_)) => {
+                    self.keys = None;
+                    self.generation += 1;
+                }
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            }
+        }
+
+        loop {
+            match self.changes.try_recv() {
+                Ok(did) => {
+                    // The cache reports the DID its entry was resolved for,
+                    // which may be another spelling of a did:peer
+                    let is_to = peer_dids_equivalent(&did, to);
+                    if is_to || from.is_some_and(|from| peer_dids_equivalent(&did, from)) {
+                        self.keys = None;
+                        self.generation += 1;
+                    }
+                    if is_to
+                        || self
+                            .routing
+                            .as_ref()
+                            .is_some_and(|chain| chain.contains(&did))
+                    {
+                        self.routing = None;
+                        self.generation += 1;
+                    }
+                }
+                // Missed some changes, so any of them may have been ours
+                Err(TryRecvError::Lagged(_)) => self.clear(),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.keys = None;
+        self.routing = None;
+        self.generation += 1;
+    }
+}
+
+impl std::fmt::Debug for PackingContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackingContext")
+            .field("to", &self.to)
+            .field("from", &self.from)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PackingContext {
+    /// Recipient DID.
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    /// Sender DID, or `None` for anonymous encryption.
+    pub fn from(&self) -> Option<&str> {
+        self.from.as_deref()
+    }
+
+    /// Pack `message` for the recipient, negotiating keys only when none are
+    /// cached. The output is the same as [`ATM::pack_encrypted`]'s.
+    pub async fn pack(
+        &self,
+        message: &Message,
+    ) -> Result<(String, PackEncryptedMetadata), ATMError> {
+        let _span = span!(Level::DEBUG, "packing_context_pack", to = %self.to);
+
+        async move { self.keys().await?.pack(message) }
+            .instrument(_span)
+            .await
+    }
+
+    /// DIDs of the mediators a message to the recipient is forwarded
+    /// through, nearest to the recipient first. Empty when the recipient
+    /// advertises no mediator.
+    ///
+    /// Read from the `routingKeys` and DID `uri`s of each `DIDCommMessaging`
+    /// service, following mediators of mediators up to
+    /// [`MAX_ROUTING_DEPTH`] hops.
+    pub async fn routing_chain(&self) -> Result<Arc<[String]>, ATMError> {
+        let generation = {
+            let state = self.lock();
+            if let Some(routing) = &state.routing {
+                return Ok(routing.clone());
+            }
+            state.generation
+        };
+
+        let routing: Arc<[String]> = self.resolve_routing_chain().await?.into();
+        let mut state = self.lock();
+        if state.generation == generation {
+            state.routing = Some(routing.clone());
+        }
+        Ok(routing)
+    }
+
+    /// Forget the cached keys and routing chain. The next call renegotiates.
+    pub fn invalidate(&self) {
+        self.lock().clear();
+    }
+
+    async fn keys(&self) -> Result<Arc<PackKeys>, ATMError> {
+        let generation = {
+            let state = self.lock();
+            if let Some(keys) = &state.keys {
+                return Ok(keys.clone());
+            }
+            state.generation
+        };
+
+        debug!("negotiating packing keys");
+        // Not held across the await: concurrent first calls may both
+        // negotiate, and agree.
+        let keys = Arc::new(
+            self.atm
+                .inner
+                .negotiate_pack_keys(&self.to, self.from.as_deref())
+                .await?,
+        );
+        let mut state = self.lock();
+        if state.generation == generation {
+            state.keys = Some(keys.clone());
+        }
+        Ok(keys)
+    }
+
+    /// Lock the state, first discarding anything made stale by a document
+    /// change.
+    fn lock(&self) -> std::sync::MutexGuard<'_, ContextState> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.apply_changes(&self.to, self.from.as_deref());
+        state
+    }
+
+    async fn resolve_routing_chain(&self) -> Result<Vec<String>, ATMError> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut visited = vec![self.to.clone()];
+        while visited.len() <= MAX_ROUTING_DEPTH {
+            let did = visited.last().expect("starts with the recipient");
+            let doc = self
+                .atm
+                .inner
+                .tdk_common
+                .did_resolver()
+                .resolve(did)
+                .await
+                .map_err(|e| {
+                    ATMError::DidcommError(
+                        "routing_chain".into(),
+                        format!("Failed to resolve {did}: {e}"),
+                    )
+                })?;
+            if !doc.cache_hit
+                && let Some(from) = &self.from
+            {
+                self.atm
+                    .inner
+                    .tdk_common
+                    .usage_tracker()
+                    .record(from, UsageMetric::Resolutions, 1);
+            }
+
+            let (hops, mediator) = next_hops(&doc.doc);
+            for hop in hops {
+                if hop != self.to && !chain.contains(&hop) {
+                    chain.push(hop);
+                }
+            }
+            // A mediator that routes back to a DID already visited ends the
+            // chain
+            match mediator {
+                Some(mediator) if !visited.contains(&mediator) => visited.push(mediator),
+                _ => break,
+            }
+        }
+        Ok(chain)
+    }
+}
+
+/// Mediator DIDs named by `doc`'s first `DIDCommMessaging` endpoint, in
+/// forwarding order, and the DID among them that is itself an endpoint (and
+/// so may route further).
+fn next_hops(doc: &Document) -> (Vec<String>, Option<String>) {
+    let Some(endpoint) = doc.service.iter().find_map(didcomm_endpoint) else {
+        return (Vec::new(), None);
+    };
+
+    let mut hops: Vec<String> = endpoint
+        .routing_keys
+        .iter()
+        .map(|key| key.split('#').next().unwrap_or(key).to_string())
+        .collect();
+    // routingKeys run outermost first; the chain runs nearest first
+    hops.reverse();
+
+    let mediator = Some(endpoint.uri).filter(|uri| uri.starts_with("did:"));
+    if let Some(mediator) = &mediator
+        && !hops.contains(mediator)
+    {
+        hops.insert(0, mediator.clone());
+    }
+    (hops, mediator)
+}
+
+/// The first DIDComm v2 endpoint of a `DIDCommMessaging` service.
+fn didcomm_endpoint(service: &Service) -> Option<EndpointUri> {
+    if !service.type_.iter().any(|t| t == "DIDCommMessaging") {
+        return None;
+    }
+    service
+        .service_endpoint
+        .endpoint_uris()
+        .into_iter()
+        .find(|endpoint| {
+            endpoint.accept.is_empty() || endpoint.accept.iter().any(|a| a == "didcomm/v2")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn doc(services: Value) -> Document {
+        serde_json::from_value(json!({
+            "id": "did:web:bob.example",
+            "service": services
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn direct_endpoints_have_no_hops() {
+        let doc = doc(json!([{
+            "id": "did:web:bob.example#didcomm",
+            "type": "DIDCommMessaging",
+            "serviceEndpoint": { "uri": "https://bob.example/didcomm", "accept": ["didcomm/v2"] }
+        }]));
+        assert_eq!(next_hops(&doc), (vec![], None));
+    }
+
+    #[test]
+    fn mediator_uri_and_routing_keys_are_hops() {
+        let doc = doc(json!([{
+            "id": "did:web:bob.example#didcomm",
+            "type": "DIDCommMessaging",
+            "serviceEndpoint": [
+                { "uri": "did:web:old.example", "accept": ["didcomm/aip2"] },
+                {
+                    "uri": "did:web:mediator.example",
+                    "accept": ["didcomm/v2"],
+                    "routingKeys": ["did:web:outer.example#key-1", "did:web:mediator.example#key-1"]
+                }
+            ]
+        }]));
+        let (hops, mediator) = next_hops(&doc);
+        assert_eq!(hops, ["did:web:mediator.example", "did:web:outer.example"]);
+        assert_eq!(mediator.as_deref(), Some("did:web:mediator.example"));
+    }
+
+    #[test]
+    fn secret_changes_of_the_sender_invalidate_the_keys() {
+        let (_doc_tx, changes) = tokio::sync::broadcast::channel(4);
+        let (secret_tx, secret_changes) = tokio::sync::broadcast::channel(4);
+        let mut state = ContextState {
+            changes,
+            secret_changes,
+            keys: None,
+            routing: None,
+            generation: 0,
+        };
+        let (to, from) = ("did:web:bob.example", Some("did:web:alice.example"));
+
+        secret_tx.send("did:web:bob.example#key-1".into()).unwrap();
+        state.apply_changes(to, from);
+        assert_eq!(state.generation, 0, "the recipient's secrets aren't ours");
+
+        secret_tx
+            .send("did:web:alice.example#key-1".into())
+            .unwrap();
+        state.apply_changes(to, from);
+        assert_eq!(state.generation, 1);
+
+        secret_tx
+            .send("did:web:alice.example#key-2".into())
+            .unwrap();
+        state.apply_changes(to, None);
+        assert_eq!(state.generation, 1, "anoncrypt holds no sender key");
+    }
+
+    #[test]
+    fn other_services_are_ignored() {
+        let doc = doc(json!([{
+            "id": "did:web:bob.example#linked",
+            "type": "LinkedDomains",
+            "serviceEndpoint": { "uri": "did:web:mediator.example" }
+        }]));
+        assert_eq!(next_hops(&doc), (vec![], None));
+    }
+}
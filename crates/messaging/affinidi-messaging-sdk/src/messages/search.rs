@@ -0,0 +1,265 @@
+//! Searching the messages queued on the mediator by their metadata.
+//!
+//! [`ATM::search_messages`] asks the mediator for the messages of a folder
+//! that match a [`MessageSearch`] and returns a [`MessageSummary`] for each,
+//! without downloading any message body:
+//!
+//! ```ignore
+//! let recent = atm
+//!     .search_messages(
+//!         &profile,
+//!         Folder::Inbox,
+//!         &MessageSearch::new().from_did(&sender_did).since(one_hour_ago_ms),
+//!     )
+//!     .await?;
+//! for summary in recent {
+//!     println!("{} {} bytes at {}", summary.msg_id, summary.size, summary.timestamp);
+//! }
+//! ```
+//!
+//! The mediator stores messages encrypted for their recipient, so it can
+//! only search what it records when queuing them: the sender and the time.
+//! Everything in the outbox is reported as sent by the profile itself.
+//! Thread id and message type filters are passed on for mediators that index
+//! them; other mediators reject the search with a problem report.
+
+use affinidi_messaging_mediator_common::types::messages::{
+    MessageListElement, MessageProtocol, MessageSearchQuery,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha256::digest;
+use std::sync::Arc;
+use tracing::{Instrument, Level, debug, span};
+
+use super::{Folder, MessageList};
+use crate::{ATM, errors::ATMError, messages::SuccessResponse, profiles::ATMProfile};
+
+/// Filters for [`ATM::search_messages`]. They combine with AND; an empty
+/// search matches every message, up to the mediator's list limit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageSearch {
+    from: Option<String>,
+    thid: Option<String>,
+    message_type: Option<String>,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<u32>,
+}
+
+impl MessageSearch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only messages sent by `did`.
+    pub fn from_did(mut self, did: &str) -> Self {
+        self.from = Some(did.to_string());
+        self
+    }
+
+    /// Only messages of the DIDComm thread `thid`. Needs a mediator that
+    /// indexes thread ids.
+    pub fn thid(mut self, thid: &str) -> Self {
+        self.thid = Some(thid.to_string());
+        self
+    }
+
+    /// Only messages of the DIDComm type `message_type`. Needs a mediator
+    /// that indexes message types.
+    pub fn message_type(mut self, message_type: &str) -> Self {
+        self.message_type = Some(message_type.to_string());
+        self
+    }
+
+    /// Only messages stored at or after `since` (milliseconds since epoch).
+    pub fn since(mut self, since: u64) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only messages stored at or before `until` (milliseconds since epoch).
+    pub fn until(mut self, until: u64) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// At most `limit` results. The mediator caps this at its list limit.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The query parameters sent to the mediator.
+    fn to_query(&self) -> MessageSearchQuery {
+        MessageSearchQuery {
+            from_hash: self.from.as_deref().map(digest),
+            thid: self.thid.clone(),
+            msg_type: self.message_type.clone(),
+            since: self.since,
+            until: self.until,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Metadata of a queued message, as returned by [`ATM::search_messages`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageSummary {
+    pub msg_id: String,
+    /// Id of the message in the searched folder's stream.
+    pub stream_id: Option<String>,
+    /// sha256 hash of the sender DID, if the mediator knows the sender.
+    pub from_hash: Option<String>,
+    /// sha256 hash of the recipient DID.
+    pub to_hash: Option<String>,
+    /// Size of the stored message in bytes.
+    pub size: u64,
+    /// When the mediator stored the message, in milliseconds since epoch.
+    pub timestamp: u64,
+    pub protocol: Option<MessageProtocol>,
+}
+
+impl From<MessageListElement> for MessageSummary {
+    fn from(element: MessageListElement) -> Self {
+        MessageSummary {
+            msg_id: element.msg_id,
+            stream_id: element.receive_id.or(element.send_id),
+            from_hash: element.from_address,
+            to_hash: element.to_address,
+            size: element.size,
+            timestamp: element.timestamp,
+            protocol: element.protocol,
+        }
+    }
+}
+
+impl ATM {
+    /// Searches the messages of a folder on the mediator by their metadata
+    /// # Parameters
+    /// - `profile`: The profile whose messages are searched
+    /// - `folder`: The folder to search
+    /// - `search`: The filters to apply
+    ///
+    /// Returns summaries only; use [`ATM::get_messages`] to download the
+    /// messages themselves. Mediators without message search return
+    /// `ATMError::MediatorError`.
+    pub async fn search_messages(
+        &self,
+        profile: &Arc<ATMProfile>,
+        folder: Folder,
+        search: &MessageSearch,
+    ) -> Result<Vec<MessageSummary>, ATMError> {
+        let _span = span!(Level::DEBUG, "search_messages", folder = folder.to_string());
+        async move {
+            let (profile_did, mediator_did) = profile.dids()?;
+            debug!("searching folder({}) for DID({})", folder, profile_did);
+
+            // Check if authenticated
+            let tokens = self
+                .get_tdk()
+                .authentication()
+                .authenticate(profile_did.to_string(), mediator_did.to_string(), 3, None)
+                .await?;
+
+            let Some(mediator_url) = profile.get_mediator_rest_endpoint() else {
+                return Err(ATMError::TransportError(
+                    "No mediator URL found".to_string(),
+                ));
+            };
+
+            let res = self
+                .inner
+                .tdk_common
+                .client()
+                .get(format!(
+                    "{}/search/{}/{}",
+                    mediator_url,
+                    digest(profile_did),
+                    folder,
+                ))
+                .query(&search.to_query())
+                .header("Authorization", format!("Bearer {}", tokens.access_token))
+                .timeout(self.inner.config.request_timeout)
+                .send()
+                .await
+                .map_err(|e| {
+                    ATMError::TransportError(format!(
+                        "Could not send search_messages request: {e:?}"
+                    ))
+                })?;
+
+            let status = res.status();
+            debug!("API response: status({})", status);
+
+            let body = res
+                .text()
+                .await
+                .map_err(|e| ATMError::TransportError(format!("Couldn't get body: {e:?}")))?;
+
+            if status == StatusCode::NOT_FOUND {
+                return Err(ATMError::MediatorError(
+                    "search_messages".to_string(),
+                    format!("Mediator ({mediator_did}) doesn't support message search"),
+                ));
+            } else if !status.is_success() {
+                return Err(ATMError::TransportError(format!(
+                    "Status not successful. status({status}), response({body})"
+                )));
+            }
+
+            let body =
+                serde_json::from_str::<SuccessResponse<MessageList>>(&body).map_err(|e| {
+                    ATMError::TransportError(format!(
+                        "Could not parse search_messages response: {e:?}"
+                    ))
+                })?;
+
+            let summaries: Vec<MessageSummary> = body
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .map(MessageSummary::from)
+                .collect();
+
+            debug!("Search matched ({}) messages", summaries.len());
+
+            Ok(summaries)
+        }
+        .instrument(_span)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_hashes_the_sender() {
+        let query = MessageSearch::new()
+            .from_did("did:example:alice")
+            .since(10)
+            .limit(5)
+            .to_query();
+        assert_eq!(query.from_hash, Some(digest("did:example:alice")));
+        assert_eq!(query.since, Some(10));
+        assert_eq!(query.until, None);
+        assert_eq!(query.limit, Some(5));
+    }
+
+    #[test]
+    fn summaries_take_the_folder_stream_id() {
+        let summary = MessageSummary::from(MessageListElement {
+            msg_id: "m1".into(),
+            receive_id: Some("1700000000000-0".into()),
+            from_address: Some("abc".into()),
+            size: 42,
+            timestamp: 1_700_000_000_000,
+            ..Default::default()
+        });
+        assert_eq!(summary.stream_id.as_deref(), Some("1700000000000-0"));
+        assert_eq!(summary.from_hash.as_deref(), Some("abc"));
+        assert_eq!(summary.size, 42);
+    }
+}
@@ -0,0 +1,183 @@
+//! Messages encrypted by a DID to itself, for local storage or cloud sync of
+//! drafts and notes.
+//!
+//! [`ATM::pack_for_self`] authcrypts a message from a DID to the same DID,
+//! so only a holder of that DID's key agreement secret can read it and the
+//! reader can tell it wasn't written by anyone else. [`ATM::unpack_from_self`]
+//! reverses it locally; nothing goes through a mediator.
+//!
+//! ```ignore
+//! let draft = Message::build(id, "https://example.com/notes/1.0/draft".into(), body)
+//!     .from(my_did.clone())
+//!     .finalize();
+//! let sealed = atm.pack_for_self(&my_did, &draft).await?;
+//! storage.put(&draft.id, &sealed).await?;
+//!
+//! // Later, possibly on another device holding the same secrets:
+//! let (draft, _) = atm.unpack_from_self(&my_did, &storage.get(&id).await?).await?;
+//! ```
+
+use affinidi_did_common::peer_dids_equivalent;
+use affinidi_messaging_didcomm::message::Message;
+use tracing::{Instrument, Level, span};
+
+use crate::{ATM, errors::ATMError};
+
+use super::compat::UnpackMetadata;
+
+impl ATM {
+    /// Authcrypt `message` from `did` to `did`.
+    ///
+    /// Needs a key agreement secret for `did`, as any authcrypt sender does.
+    /// The result is a JWE like any other and can be stored or synced as is.
+    pub async fn pack_for_self(&self, did: &str, message: &Message) -> Result<String, ATMError> {
+        let _span = span!(Level::DEBUG, "pack_for_self", did = did);
+
+        async move {
+            let (packed, _) = self.inner.pack_encrypted(message, did, Some(did)).await?;
+            Ok(packed)
+        }
+        .instrument(_span)
+        .await
+    }
+
+    /// Unpack a message packed by [`Self::pack_for_self`] for `did`.
+    ///
+    /// Fails unless the message was authcrypted by one of `did`'s keys to
+    /// `did` alone, so a message someone else encrypted to `did` can't pass
+    /// as one of its own notes.
+    pub async fn unpack_from_self(
+        &self,
+        did: &str,
+        packed: &str,
+    ) -> Result<(Message, UnpackMetadata), ATMError> {
+        let _span = span!(Level::DEBUG, "unpack_from_self", did = did);
+
+        async move {
+            let (message, metadata) = self.inner.unpack(packed).await?;
+            check_self_encrypted(did, &metadata)?;
+            Ok((message, metadata))
+        }
+        .instrument(_span)
+        .await
+    }
+}
+
+/// Whether `metadata` describes a message authcrypted by `did` to itself.
+fn check_self_encrypted(did: &str, metadata: &UnpackMetadata) -> Result<(), ATMError> {
+    let owned_by_did = |kid: &str| peer_dids_equivalent(kid.split('#').next().unwrap_or(kid), did);
+
+    let reason = if !metadata.encrypted || !metadata.authenticated {
+        "it is not authcrypted"
+    } else if metadata.re_wrapped_in_forward {
+        "it was wrapped in a forward"
+    } else if !metadata
+        .encrypted_from_kid
+        .as_deref()
+        .is_some_and(owned_by_did)
+    {
+        "it was encrypted by another DID"
+    } else if metadata.encrypted_to_kids.is_empty()
+        || !metadata
+            .encrypted_to_kids
+            .iter()
+            .all(|kid| owned_by_did(kid))
+    {
+        "it was encrypted to another DID"
+    } else {
+        return Ok(());
+    };
+
+    Err(ATMError::DidcommError(
+        "unpack_from_self".into(),
+        format!("Message is not a self-encrypted message of {did}: {reason}"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ATMConfig;
+    use affinidi_did_common::{DID, PeerCreateKey, PeerKeyPurpose, PeerKeyType};
+    use affinidi_secrets_resolver::{SecretsResolver, secrets::Secret};
+    use affinidi_tdk_common::{TDKSharedState, config::TDKConfig};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// A did:peer:2 with an Ed25519 (V) and an X25519 (E) key, and the
+    /// X25519 secret.
+    fn peer_did() -> (String, Secret) {
+        let mut secret = Secret::generate_x25519(Some("temp"), None).unwrap();
+        let keys = vec![
+            PeerCreateKey::new(PeerKeyPurpose::Verification, PeerKeyType::Ed25519),
+            PeerCreateKey::from_multibase(
+                PeerKeyPurpose::Encryption,
+                secret.get_public_keymultibase().unwrap(),
+            ),
+        ];
+        let (did, _) = DID::generate_peer(&keys, None).unwrap();
+        secret.id = format!("{did}#key-2");
+        (did.to_string(), secret)
+    }
+
+    async fn atm_with(secrets: &[Secret]) -> ATM {
+        let tdk = Arc::new(
+            TDKSharedState::new(TDKConfig::headless().unwrap())
+                .await
+                .unwrap(),
+        );
+        for secret in secrets {
+            tdk.secrets_resolver().insert(secret.clone()).await;
+        }
+        ATM::new(ATMConfig::builder().build().unwrap(), tdk)
+            .await
+            .unwrap()
+    }
+
+    fn note(from: &str) -> Message {
+        Message::build(
+            "note-1".to_string(),
+            "https://example.com/notes/1.0/draft".to_string(),
+            json!({ "text": "buy milk" }),
+        )
+        .from(from.to_string())
+        .finalize()
+    }
+
+    #[tokio::test]
+    async fn self_encrypted_round_trip() {
+        let (did, secret) = peer_did();
+        let atm = atm_with(&[secret]).await;
+
+        let sealed = atm.pack_for_self(&did, &note(&did)).await.unwrap();
+        let (message, metadata) = atm.unpack_from_self(&did, &sealed).await.unwrap();
+
+        assert_eq!(message.body, json!({ "text": "buy milk" }));
+        assert!(metadata.authenticated);
+        assert_eq!(metadata.encrypted_to_kids, [format!("{did}#key-2")]);
+    }
+
+    #[tokio::test]
+    async fn messages_from_others_are_rejected() {
+        let (did, secret) = peer_did();
+        let (other, other_secret) = peer_did();
+        let atm = atm_with(&[secret, other_secret]).await;
+
+        let (from_other, _) = atm
+            .pack_encrypted(&note(&other), &did, Some(&other), None)
+            .await
+            .unwrap();
+        assert!(atm.unpack_from_self(&did, &from_other).await.is_err());
+
+        let (anonymous, _) = atm
+            .pack_encrypted(&note(&did), &did, None, None)
+            .await
+            .unwrap();
+        assert!(atm.unpack_from_self(&did, &anonymous).await.is_err());
+
+        // Readable, but as a note of `other`, not of `did`
+        let sealed = atm.pack_for_self(&other, &note(&other)).await.unwrap();
+        assert!(atm.unpack_from_self(&did, &sealed).await.is_err());
+        assert!(atm.unpack_from_self(&other, &sealed).await.is_ok());
+    }
+}
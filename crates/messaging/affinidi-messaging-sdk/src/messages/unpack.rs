@@ -1,6 +1,11 @@
-use crate::{ATM, SharedState, errors::ATMError, messages::compat::UnpackMetadata};
+use crate::{
+    ATM, SharedState,
+    errors::ATMError,
+    messages::compat::{ForwardHop, UnpackAlgorithms, UnpackMetadata, UnpackTiming},
+};
 use affinidi_messaging_didcomm::message::Message;
-use affinidi_secrets_resolver::SecretsResolver;
+use affinidi_secrets_resolver::{SecretsResolver, usage::KeyPurpose};
+use affinidi_tdk_common::audit::AuditEvent;
 use base64::{Engine, prelude::BASE64_URL_SAFE};
 use tracing::{Instrument, Level, debug, span, warn};
 
@@ -35,8 +40,9 @@ impl SharedState {
         let _span = span!(Level::DEBUG, "unpack",);
 
         async move {
+            let started = std::time::Instant::now();
             let mut msg_string = message.to_string();
-            let mut forward_depth: usize = 0;
+            let mut forward_hops: Vec<ForwardHop> = Vec::new();
 
             loop {
                 // Compute SHA-256 hash of the packed message
@@ -81,8 +87,7 @@ impl SharedState {
                 if self.config.unpack_forwards
                     && msg.typ == "https://didcomm.org/routing/2.0/forward"
                 {
-                    forward_depth += 1;
-                    if forward_depth > MAX_FORWARD_DEPTH {
+                    if forward_hops.len() >= MAX_FORWARD_DEPTH {
                         return Err(ATMError::MsgReceiveError(format!(
                             "Forward message nesting depth exceeded maximum of {MAX_FORWARD_DEPTH}"
                         )));
@@ -90,7 +95,31 @@ impl SharedState {
                     // Extract the inner message and loop to unpack it
                     msg_string =
                         Self::extract_forward_payload(&msg, self.config.clock().unix_secs())?;
+                    forward_hops.push(ForwardHop {
+                        next: msg.body["next"].as_str().unwrap_or_default().to_string(),
+                        forward_id: msg.id,
+                        from_kid: metadata.encrypted_from_kid,
+                    });
                 } else {
+                    let mut metadata = metadata;
+                    metadata.re_wrapped_in_forward = !forward_hops.is_empty();
+                    metadata.forward_hops = forward_hops;
+                    metadata.timing = Some(UnpackTiming {
+                        unpacked_at: self.config.clock().unix_secs(),
+                        duration_micros: started
+                            .elapsed()
+                            .as_micros()
+                            .try_into()
+                            .unwrap_or(u64::MAX),
+                    });
+                    self.tdk_common
+                        .record_audit_event(AuditEvent::MessageReceived {
+                            message_id: msg.id.clone(),
+                            message_type: msg.typ.clone(),
+                            from: msg.from.clone(),
+                            to: msg.to.clone().unwrap_or_default(),
+                        })
+                        .await;
                     return Ok((msg, metadata));
                 }
             }
@@ -120,7 +149,12 @@ impl SharedState {
 
         for recipient in recipients {
             if let Some(kid) = recipient["header"]["kid"].as_str()
-                && let Some(secret) = self.tdk_common.secrets_resolver().get_secret(kid).await
+                && let Some(secret) = self
+                    .tdk_common
+                    .secrets_resolver()
+                    .get_secret_for(kid, KeyPurpose::KeyAgreement)
+                    .await
+                    .map_err(|e| ATMError::SecretsError(e.to_string()))?
             {
                 let Some(curve) = secret.get_key_type().key_agreement_curve() else {
                     continue;
@@ -170,7 +204,7 @@ impl SharedState {
         {
             let inner_str = std::str::from_utf8(&decrypted.plaintext)
                 .map_err(|e| ATMError::DidcommError("Invalid inner JWS".into(), e.to_string()))?;
-            let (msg, sign_from) = self.verify_inner_jws(inner_str, &inner).await?;
+            let (msg, sign_from, signature_alg) = self.verify_inner_jws(inner_str, &inner).await?;
             let metadata = UnpackMetadata {
                 encrypted: true,
                 authenticated: decrypted.authenticated,
@@ -180,6 +214,11 @@ impl SharedState {
                 non_repudiation: true,
                 sign_from: Some(sign_from),
                 sha256_hash: sha256_hash.to_string(),
+                algorithms: UnpackAlgorithms {
+                    key_wrap: Some(decrypted.header.alg),
+                    content_encryption: Some(decrypted.header.enc),
+                    signature: Some(signature_alg),
+                },
                 ..Default::default()
             };
             return Ok((msg, metadata));
@@ -196,6 +235,11 @@ impl SharedState {
             encrypted_from_kid: decrypted.sender_kid,
             encrypted_to_kids: vec![decrypted.recipient_kid],
             sha256_hash: sha256_hash.to_string(),
+            algorithms: UnpackAlgorithms {
+                key_wrap: Some(decrypted.header.alg),
+                content_encryption: Some(decrypted.header.enc),
+                signature: None,
+            },
             ..Default::default()
         };
 
@@ -294,12 +338,16 @@ impl SharedState {
         msg_string: &str,
         sha256_hash: &str,
     ) -> Result<(Message, UnpackMetadata), ATMError> {
-        let (msg, sign_from) = self.verify_inner_jws(msg_string, value).await?;
+        let (msg, sign_from, signature_alg) = self.verify_inner_jws(msg_string, value).await?;
 
         let metadata = UnpackMetadata {
             non_repudiation: true,
             sign_from: Some(sign_from),
             sha256_hash: sha256_hash.to_string(),
+            algorithms: UnpackAlgorithms {
+                signature: Some(signature_alg),
+                ..Default::default()
+            },
             ..Default::default()
         };
 
@@ -307,16 +355,16 @@ impl SharedState {
     }
 
     /// Verify a JWS — resolving the signer's Ed25519 key from its `kid`
-    /// — and return the decoded [`Message`] plus the signer kid. Shared
-    /// by top-level signed messages and the inner JWS of a
-    /// sign-then-encrypt envelope. Errors if the signer key can't be
-    /// resolved or the signature is invalid; we never surface an
+    /// — and return the decoded [`Message`], the signer kid and the
+    /// signature algorithm. Shared by top-level signed messages and the
+    /// inner JWS of a sign-then-encrypt envelope. Errors if the signer key
+    /// can't be resolved or the signature is invalid; we never surface an
     /// unverified message.
     async fn verify_inner_jws(
         &self,
         jws_str: &str,
         jws_value: &serde_json::Value,
-    ) -> Result<(Message, String), ATMError> {
+    ) -> Result<(Message, String, String), ATMError> {
         use affinidi_messaging_didcomm::jws::verify::verify_ed25519;
 
         let signer_kid = Self::jws_signer_kid(jws_value).ok_or_else(|| {
@@ -339,7 +387,7 @@ impl SharedState {
         })?;
         // Prefer the kid the verifier extracted (protected, then
         // unprotected header); fall back to the one we resolved against.
-        Ok((msg, verified.signer_kid.unwrap_or(signer_kid)))
+        Ok((msg, verified.signer_kid.unwrap_or(signer_kid), verified.alg))
     }
 
     /// Extract the signer `kid` from a JWS's first signature, preferring
@@ -417,7 +465,20 @@ impl SharedState {
         async move {
             debug!("Attempting to unpack a forwarded message");
             let inner = Self::extract_forward_payload(message, self.config.clock().unix_secs())?;
-            self.unpack(&inner).await
+            let (msg, mut metadata) = self.unpack(&inner).await?;
+            metadata.forward_hops.insert(
+                0,
+                ForwardHop {
+                    forward_id: message.id.clone(),
+                    next: message.body["next"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    from_kid: None,
+                },
+            );
+            metadata.re_wrapped_in_forward = true;
+            Ok((msg, metadata))
         }
         .instrument(_span)
         .await
@@ -492,6 +553,7 @@ impl SharedState {
 mod tests {
     use super::*;
     use crate::config::ATMConfig;
+    use crate::messages::compat::AuthenticationLevel;
     use affinidi_messaging_didcomm::message::Attachment;
     use affinidi_tdk_common::TDKSharedState;
     use serde_json::json;
@@ -589,6 +651,20 @@ mod tests {
             unpack_meta.authenticated,
             "authcrypt should be authenticated"
         );
+        assert_eq!(
+            unpack_meta.authentication_level(),
+            AuthenticationLevel::Authcrypt
+        );
+        assert_eq!(
+            unpack_meta.algorithms.key_wrap.as_deref(),
+            Some("ECDH-1PU+A256KW")
+        );
+        assert_eq!(
+            unpack_meta.algorithms.content_encryption.as_deref(),
+            Some("A256CBC-HS512")
+        );
+        assert!(unpack_meta.forward_hops.is_empty());
+        assert!(unpack_meta.timing.is_some());
     }
 
     /// Test: verifies the skid bug — when the sender has multiple keys (V + E),
@@ -1302,11 +1378,15 @@ mod tests {
         let forward2 = wrap_in_forward_base64(&forward1_json, None);
         let forward2_json = make_plaintext_json(&forward2);
 
-        let (unpacked, _metadata) = atm.unpack(&forward2_json).await.unwrap();
+        let (unpacked, metadata) = atm.unpack(&forward2_json).await.unwrap();
 
         assert_eq!(unpacked.id, "test-msg-1");
         assert_eq!(unpacked.typ, "example/v1");
         assert_eq!(unpacked.body, json!({"hello": "world"}));
+        assert!(metadata.re_wrapped_in_forward);
+        assert_eq!(metadata.forward_hops.len(), 2);
+        assert_eq!(metadata.forward_hops[0].next, "did:example:recipient");
+        assert_eq!(metadata.forward_hops[0].forward_id, "fwd-msg-1");
     }
 
     #[tokio::test]
@@ -1316,10 +1396,12 @@ mod tests {
         let inner_json = make_plaintext_json(&inner);
         let forward = wrap_in_forward_json(&inner_json, None);
 
-        let (unpacked, _metadata) = atm.unpack_forward(&forward).await.unwrap();
+        let (unpacked, metadata) = atm.unpack_forward(&forward).await.unwrap();
 
         assert_eq!(unpacked.id, "test-msg-1");
         assert_eq!(unpacked.typ, "example/v1");
+        assert_eq!(metadata.forward_hops.len(), 1);
+        assert_eq!(metadata.forward_hops[0].next, "did:example:recipient");
     }
 
     #[tokio::test]
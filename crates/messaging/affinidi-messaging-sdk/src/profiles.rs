@@ -14,14 +14,10 @@ use crate::{
         websocket::{WebSocketCommands, WebSocketTransport},
     },
 };
-use affinidi_did_common::{
-    Document,
-    service::{Endpoint, Service},
-};
+use affinidi_did_common::{Document, service::Service};
 use affinidi_messaging_core::ConnState;
 use affinidi_tdk_common::profiles::TDKProfile;
 use ahash::AHashMap as HashMap;
-use serde_json::Value;
 use std::{
     sync::{
         Arc,
@@ -272,51 +268,19 @@ impl Mediator {
     /// Helper function to find the endpoint for the Mediator
     /// protocol allows you to specify the URI scheme (http, ws, etc)
     fn _find_endpoint(service: &Service, protocol: &str) -> Option<String> {
-        fn check(value: &Value, protocol: &str) -> Option<String> {
-            if let Some(accept) = value.get("accept") {
-                let accept: Vec<String> = match serde_json::from_value(accept.to_owned()) {
-                    Ok(accept) => accept,
-                    Err(_) => return None,
-                };
-
-                if accept.contains(&"didcomm/v2".to_string())
-                    && let Some(uri) = value.get("uri")
-                    && let Some(uri) = uri.as_str()
-                    && uri.starts_with(protocol)
-                {
-                    Some(uri.to_string())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
+        if !service.type_.iter().any(|t| t == "DIDCommMessaging") {
+            return None;
         }
 
-        if service.type_.contains(&"DIDCommMessaging".to_string()) {
-            match &service.service_endpoint {
-                Endpoint::Map(map) => {
-                    if map.is_array() {
-                        map.as_array().and_then(|arr| {
-                            for item in arr {
-                                if let Some(uri) = check(item, protocol) {
-                                    return Some(uri);
-                                }
-                            }
-                            None
-                        })
-                    } else {
-                        check(map, protocol)
-                    }
-                }
-                _ => {
-                    // Ignore URI
-                    None
-                }
-            }
-        } else {
-            None
-        }
+        service
+            .service_endpoint
+            .endpoint_uris()
+            .into_iter()
+            .find(|endpoint| {
+                endpoint.accept.iter().any(|accept| accept == "didcomm/v2")
+                    && endpoint.uri.starts_with(protocol)
+            })
+            .map(|endpoint| endpoint.uri)
     }
 
     /// Finds the REST endpoint for the Mediator if it exists
@@ -717,6 +681,46 @@ mod tests {
         atm.graceful_shutdown().await;
     }
 
+    /// The transport runs under the TDK's supervisor: it reports into the
+    /// TDK task health, and a TDK shutdown stops it and waits for it to exit.
+    #[tokio::test]
+    async fn websocket_transport_is_supervised_by_the_tdk() {
+        use affinidi_tdk_common::task_utils::ComponentState;
+
+        let tdk_cfg = TDKConfig::headless().expect("headless tdk config");
+        let tdk = Arc::new(
+            TDKSharedState::new(tdk_cfg)
+                .await
+                .expect("tdk shared state"),
+        );
+        let atm_cfg = ATMConfig::builder().build().expect("atm config");
+        let atm = ATM::new(atm_cfg, tdk.clone()).await.expect("atm");
+
+        let profile = fake_profile();
+        let (handle, _ws_channel, _conn_state_rx) =
+            crate::transports::websockets::websocket::WebSocketTransport::start(
+                profile.clone(),
+                atm.inner.clone(),
+                None,
+            )
+            .await;
+        let task = "websocket:test-orphan";
+        assert_eq!(
+            tdk.task_health().get(task).expect("task registered").state,
+            ComponentState::Running
+        );
+
+        // The command sender is still held, so only the shutdown stops it.
+        timeout(Duration::from_secs(15), tdk.shutdown())
+            .await
+            .expect("TDK shutdown waits for the transport, within 15s");
+        assert!(handle.is_finished(), "transport exited by shutdown");
+        assert_eq!(
+            tdk.task_health().get(task).expect("task registered").state,
+            ComponentState::Stopped
+        );
+    }
+
     /// The connection-state signal is `None` until a transport runs, then is
     /// exposed via `ATMProfile::connection_state()` starting at `Connecting`.
     #[tokio::test]
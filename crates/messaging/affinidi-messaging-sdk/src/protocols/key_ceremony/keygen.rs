@@ -0,0 +1,380 @@
+//! Key generation: the coordinator's invitation and completion check, and
+//! each participant's run through the three FROST DKG parts.
+
+use std::collections::BTreeMap;
+
+use affinidi_crypto::frost::{Identifier, KeyPackage, PublicKeyPackage, dkg};
+use affinidi_messaging_didcomm::message::Message;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::debug;
+use uuid::Uuid;
+
+use super::{
+    build, check_thread, expect_type, identifier_of, message_types, parse_body, peers,
+    unauthenticated,
+};
+use crate::{errors::ATMError, messages::compat::UnpackMetadata};
+
+/// Ceremony parameters: participants in identifier order (the first DID is
+/// identifier 1) and the signing threshold.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CeremonyInvite {
+    pub ceremony_id: String,
+    pub coordinator: String,
+    pub participants: Vec<String>,
+    pub threshold: u16,
+}
+
+impl CeremonyInvite {
+    fn identifier_of(&self, did: &str) -> Result<Identifier, ATMError> {
+        identifier_of(&self.participants, did)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Round1Body {
+    ceremony_id: String,
+    package: dkg::Round1Package,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Round2Body {
+    ceremony_id: String,
+    package: dkg::Round2Package,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompleteBody {
+    ceremony_id: String,
+    update_key: String,
+    public_key_package: PublicKeyPackage,
+}
+
+/// Result of a successful key ceremony.
+#[derive(Clone, Debug)]
+pub struct CeremonyOutcome {
+    /// The group key as an Ed25519 Multikey (`z6Mk…`), for
+    /// `Parameters.update_keys`.
+    pub update_key: String,
+    /// The group's public key material, needed to run signing sessions.
+    pub public_key_package: PublicKeyPackage,
+}
+
+/// Coordinator side of key generation: invites the participants and checks
+/// that they all derived the same group key.
+pub struct KeyCeremonyCoordinator {
+    invite: CeremonyInvite,
+    completed: BTreeMap<Identifier, PublicKeyPackage>,
+}
+
+impl KeyCeremonyCoordinator {
+    /// Start a `threshold`-of-`participants.len()` ceremony coordinated by
+    /// `coordinator` (which may itself be one of the participants).
+    pub fn new(
+        coordinator: &str,
+        participants: Vec<String>,
+        threshold: u16,
+    ) -> Result<Self, ATMError> {
+        let count = u16::try_from(participants.len())
+            .map_err(|_| ATMError::KeyCeremonyError("too many participants".into()))?;
+        if threshold < 2 || threshold > count {
+            return Err(ATMError::KeyCeremonyError(format!(
+                "threshold must be between 2 and {count}, got {threshold}"
+            )));
+        }
+        let mut unique = participants.clone();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != participants.len() {
+            return Err(ATMError::KeyCeremonyError(
+                "participant DIDs must be unique".into(),
+            ));
+        }
+
+        Ok(KeyCeremonyCoordinator {
+            invite: CeremonyInvite {
+                ceremony_id: Uuid::new_v4().to_string(),
+                coordinator: coordinator.to_string(),
+                participants,
+                threshold,
+            },
+            completed: BTreeMap::new(),
+        })
+    }
+
+    /// The ceremony parameters.
+    pub fn invite(&self) -> &CeremonyInvite {
+        &self.invite
+    }
+
+    /// One `invite` message per participant.
+    pub fn invitations(&self) -> Vec<Message> {
+        self.invite
+            .participants
+            .iter()
+            .map(|to| {
+                build(
+                    message_types::INVITE,
+                    &self.invite.coordinator,
+                    to,
+                    &self.invite.ceremony_id,
+                    json!(self.invite),
+                )
+            })
+            .collect()
+    }
+
+    /// Record a participant's `complete` report. Returns the outcome once every
+    /// participant has reported, and fails if any two disagree on the group key.
+    pub fn handle(
+        &mut self,
+        message: &Message,
+        meta: &UnpackMetadata,
+    ) -> Result<Option<CeremonyOutcome>, ATMError> {
+        expect_type(message, message_types::COMPLETE)?;
+        let id = self
+            .invite
+            .identifier_of(meta.require_authenticated_sender(message, unauthenticated)?)?;
+        let body: CompleteBody = parse_body(message)?;
+        check_thread(&body.ceremony_id, &self.invite.ceremony_id)?;
+
+        if body.public_key_package.verifying_key_multibase() != body.update_key {
+            return Err(ATMError::KeyCeremonyError(format!(
+                "participant {id} reported an update key that does not match its key package"
+            )));
+        }
+        if let Some((other, _)) = self
+            .completed
+            .iter()
+            .find(|(_, package)| **package != body.public_key_package)
+        {
+            return Err(ATMError::KeyCeremonyError(format!(
+                "participants {other} and {id} derived different group keys"
+            )));
+        }
+        self.completed.insert(id, body.public_key_package);
+
+        if self.completed.len() < self.invite.participants.len() {
+            return Ok(None);
+        }
+        let public_key_package = self
+            .completed
+            .values()
+            .next()
+            .cloned()
+            .ok_or_else(|| ATMError::KeyCeremonyError("ceremony has no participants".into()))?;
+        Ok(Some(CeremonyOutcome {
+            update_key: public_key_package.verifying_key_multibase(),
+            public_key_package,
+        }))
+    }
+}
+
+enum DkgState {
+    Round1(dkg::Round1SecretPackage),
+    Round2(dkg::Round2SecretPackage),
+    Done(Box<(KeyPackage, PublicKeyPackage)>),
+    /// Transient placeholder while moving between states.
+    Poisoned,
+}
+
+/// Participant side of key generation.
+pub struct KeyCeremonyParticipant {
+    did: String,
+    identifier: Identifier,
+    invite: CeremonyInvite,
+    state: DkgState,
+    round1: BTreeMap<Identifier, dkg::Round1Package>,
+    round2: BTreeMap<Identifier, dkg::Round2Package>,
+}
+
+impl KeyCeremonyParticipant {
+    /// Accept an `invite` as participant `did`. Returns the participant and its
+    /// `dkg-round1` messages, one per peer.
+    pub fn accept(
+        did: &str,
+        invite: &Message,
+        meta: &UnpackMetadata,
+    ) -> Result<(Self, Vec<Message>), ATMError> {
+        expect_type(invite, message_types::INVITE)?;
+        let body: CeremonyInvite = parse_body(invite)?;
+        if meta.require_authenticated_sender(invite, unauthenticated)? != body.coordinator {
+            return Err(ATMError::KeyCeremonyError(
+                "invite was not sent by the ceremony coordinator".into(),
+            ));
+        }
+        let identifier = body.identifier_of(did)?;
+        let max_signers = u16::try_from(body.participants.len())
+            .map_err(|_| ATMError::KeyCeremonyError("too many participants".into()))?;
+
+        let (secret, package) = dkg::part1(
+            identifier,
+            max_signers,
+            body.threshold,
+            &mut rand_core::OsRng,
+        )
+        .map_err(|e| ATMError::KeyCeremonyError(e.to_string()))?;
+
+        let outgoing = peers(&body.participants, did)
+            .map(|peer| {
+                build(
+                    message_types::DKG_ROUND1,
+                    did,
+                    peer,
+                    &body.ceremony_id,
+                    json!(Round1Body {
+                        ceremony_id: body.ceremony_id.clone(),
+                        package: package.clone(),
+                    }),
+                )
+            })
+            .collect();
+
+        Ok((
+            KeyCeremonyParticipant {
+                did: did.to_string(),
+                identifier,
+                invite: body,
+                state: DkgState::Round1(secret),
+                round1: BTreeMap::new(),
+                round2: BTreeMap::new(),
+            },
+            outgoing,
+        ))
+    }
+
+    /// The ceremony this participant is in.
+    pub fn ceremony_id(&self) -> &str {
+        &self.invite.ceremony_id
+    }
+
+    /// This participant's FROST identifier.
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+
+    /// Process a `dkg-round1` or `dkg-round2` message from a peer and return
+    /// the messages to send in response (round-2 shares once every peer's
+    /// round 1 has arrived; the `complete` report once key generation finishes).
+    pub fn handle(
+        &mut self,
+        message: &Message,
+        meta: &UnpackMetadata,
+    ) -> Result<Vec<Message>, ATMError> {
+        let from = self
+            .invite
+            .identifier_of(meta.require_authenticated_sender(message, unauthenticated)?)?;
+        if from == self.identifier {
+            return Err(ATMError::KeyCeremonyError(
+                "ceremony message from ourselves".into(),
+            ));
+        }
+
+        match message.typ.as_str() {
+            message_types::DKG_ROUND1 => {
+                let body: Round1Body = parse_body(message)?;
+                check_thread(&body.ceremony_id, &self.invite.ceremony_id)?;
+                if self.round1.insert(from, body.package).is_some() {
+                    return Err(ATMError::KeyCeremonyError(format!(
+                        "duplicate round 1 package from participant {from}"
+                    )));
+                }
+            }
+            message_types::DKG_ROUND2 => {
+                let body: Round2Body = parse_body(message)?;
+                check_thread(&body.ceremony_id, &self.invite.ceremony_id)?;
+                if self.round2.insert(from, body.package).is_some() {
+                    return Err(ATMError::KeyCeremonyError(format!(
+                        "duplicate round 2 package from participant {from}"
+                    )));
+                }
+            }
+            other => {
+                return Err(ATMError::KeyCeremonyError(format!(
+                    "unexpected key ceremony message type {other}"
+                )));
+            }
+        }
+
+        self.advance()
+    }
+
+    /// Move through as many DKG parts as the packages received so far allow.
+    fn advance(&mut self) -> Result<Vec<Message>, ATMError> {
+        let peer_count = self.invite.participants.len() - 1;
+        let mut outgoing = Vec::new();
+
+        if matches!(self.state, DkgState::Round1(_)) && self.round1.len() == peer_count {
+            let DkgState::Round1(secret) = std::mem::replace(&mut self.state, DkgState::Poisoned)
+            else {
+                unreachable!("state checked above");
+            };
+            let (secret, shares) = dkg::part2(secret, &self.round1)
+                .map_err(|e| ATMError::KeyCeremonyError(e.to_string()))?;
+            self.state = DkgState::Round2(secret);
+
+            for (peer, package) in shares {
+                let to = &self.invite.participants[usize::from(peer.value()) - 1];
+                outgoing.push(build(
+                    message_types::DKG_ROUND2,
+                    &self.did,
+                    to,
+                    &self.invite.ceremony_id,
+                    json!(Round2Body {
+                        ceremony_id: self.invite.ceremony_id.clone(),
+                        package,
+                    }),
+                ));
+            }
+        }
+
+        if let DkgState::Round2(secret) = &self.state
+            && self.round2.len() == peer_count
+        {
+            let (key_package, public_key_package) = dkg::part3(secret, &self.round1, &self.round2)
+                .map_err(|e| ATMError::KeyCeremonyError(e.to_string()))?;
+            self.round2.clear();
+            debug!(
+                "key ceremony {}: participant {} holds a verified share",
+                self.invite.ceremony_id, self.identifier
+            );
+
+            outgoing.push(build(
+                message_types::COMPLETE,
+                &self.did,
+                &self.invite.coordinator,
+                &self.invite.ceremony_id,
+                json!(CompleteBody {
+                    ceremony_id: self.invite.ceremony_id.clone(),
+                    update_key: public_key_package.verifying_key_multibase(),
+                    public_key_package: public_key_package.clone(),
+                }),
+            ));
+            self.state = DkgState::Done(Box::new((key_package, public_key_package)));
+        }
+
+        Ok(outgoing)
+    }
+
+    /// Whether key generation has finished for this participant.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, DkgState::Done(_))
+    }
+
+    /// This participant's secret key share, once key generation has finished.
+    pub fn key_package(&self) -> Option<&KeyPackage> {
+        match &self.state {
+            DkgState::Done(done) => Some(&done.0),
+            _ => None,
+        }
+    }
+
+    /// The group's public key material, once key generation has finished.
+    pub fn public_key_package(&self) -> Option<&PublicKeyPackage> {
+        match &self.state {
+            DkgState::Done(done) => Some(&done.1),
+            _ => None,
+        }
+    }
+}
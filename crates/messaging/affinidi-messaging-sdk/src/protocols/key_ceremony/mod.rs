@@ -0,0 +1,428 @@
+//! Key ceremony: multi-party generation of a threshold `did:webvh` update key.
+//!
+//! For high-assurance DIDs no single party should be able to update the DID
+//! log. This protocol runs a FROST (RFC 9591) distributed key generation
+//! between `n` participants over DIDComm, producing a `t`-of-`n` Ed25519 group
+//! key: its Multikey goes into the webvh `Parameters.update_keys`, and any `t`
+//! participants can later co-sign a log entry. The combined signature is an
+//! ordinary Ed25519 signature, so resolvers verify the entry unchanged.
+//!
+//! ## Key generation
+//!
+//! 1. The coordinator creates a [`KeyCeremonyCoordinator`] and sends each
+//!    participant its `invite` ([`KeyCeremonyCoordinator::invitations`]).
+//! 2. Each participant accepts with [`KeyCeremonyParticipant::accept`], which
+//!    returns its `dkg-round1` broadcast to every peer, then feeds every
+//!    inbound ceremony message to [`KeyCeremonyParticipant::handle`] and sends
+//!    whatever it returns. Round-2 messages carry secret shares: they must be
+//!    sent authcrypted to their single recipient, which
+//!    [`KeyCeremonyOps::send`] does.
+//! 3. Once a participant holds a verified share it reports `complete` to the
+//!    coordinator. When every participant has reported the *same* group key,
+//!    [`KeyCeremonyCoordinator::handle`] returns the [`CeremonyOutcome`].
+//!
+//! Each participant persists its
+//! [`KeyPackage`](affinidi_crypto::frost::KeyPackage) (secret) and the group's
+//! [`PublicKeyPackage`](affinidi_crypto::frost::PublicKeyPackage).
+//!
+//! ## Threshold signing of log entries
+//!
+//! The coordinator opens a [`ThresholdSigningSession`] over the bytes to sign
+//! — for a webvh log entry, the Data Integrity sign input
+//! (`affinidi_data_integrity::prepare_sign_input` for `eddsa-jcs-2022`). Each
+//! chosen signer runs a [`ThresholdSigner`]: it answers `sign-request` with
+//! fresh nonce commitments and `sign-package` with its signature share, after
+//! the caller-supplied approval check has seen the exact bytes. The session
+//! verifies every share, and [`proof_value`] encodes the resulting signature
+//! as a Data Integrity `proofValue`.
+//!
+//! Every handler takes the [`UnpackMetadata`] [`ATM::unpack`] returned with
+//! the message, rejects messages that weren't authcrypted by the key of their
+//! `from` DID, and checks that the sender is the participant it claims to be.
+
+use std::{sync::Arc, time::SystemTime};
+
+use affinidi_crypto::frost::Identifier;
+use affinidi_messaging_didcomm::message::Message;
+use serde::de::DeserializeOwned;
+use uuid::Uuid;
+
+use crate::{ATM, errors::ATMError, profiles::ATMProfile, transports::SendMessageResponse};
+
+mod keygen;
+mod signing;
+
+pub use keygen::{CeremonyInvite, CeremonyOutcome, KeyCeremonyCoordinator, KeyCeremonyParticipant};
+pub use signing::{SigningProgress, ThresholdSigner, ThresholdSigningSession, proof_value};
+
+/// Protocol identifier (PIURI) prefix for key ceremony messages.
+pub const KEY_CEREMONY_PROTOCOL: &str = "https://affinidi.com/didcomm/protocols/key-ceremony/1.0";
+
+/// Message types of the key ceremony protocol.
+pub mod message_types {
+    pub const INVITE: &str = "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/invite";
+    pub const DKG_ROUND1: &str =
+        "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/dkg-round1";
+    pub const DKG_ROUND2: &str =
+        "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/dkg-round2";
+    pub const COMPLETE: &str = "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/complete";
+    pub const SIGN_REQUEST: &str =
+        "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/sign-request";
+    pub const SIGN_COMMITMENT: &str =
+        "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/sign-commitment";
+    pub const SIGN_PACKAGE: &str =
+        "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/sign-package";
+    pub const SIGN_SHARE: &str =
+        "https://affinidi.com/didcomm/protocols/key-ceremony/1.0/sign-share";
+}
+
+/// Access key ceremony helpers through [`ATM::key_ceremony`].
+pub struct KeyCeremonyOps<'a> {
+    pub(crate) atm: &'a ATM,
+}
+
+impl KeyCeremonyOps<'_> {
+    /// Authcrypt `message` from the profile's DID to its single recipient and
+    /// send it. Ceremony messages must always travel this way: round-2
+    /// packages carry secret key shares, and every handler authenticates the
+    /// sender from the envelope.
+    pub async fn send(
+        &self,
+        profile: &Arc<ATMProfile>,
+        message: &Message,
+    ) -> Result<SendMessageResponse, ATMError> {
+        let (profile_did, _) = profile.dids()?;
+        if message.from.as_deref() != Some(profile_did) {
+            return Err(ATMError::KeyCeremonyError(
+                "message is not from this profile's DID".into(),
+            ));
+        }
+        let to = match message.to.as_deref() {
+            Some([to]) => to,
+            _ => {
+                return Err(ATMError::KeyCeremonyError(
+                    "key ceremony messages have exactly one recipient".into(),
+                ));
+            }
+        };
+
+        let (packed, _) = self
+            .atm
+            .inner
+            .pack_encrypted(message, to, Some(profile_did))
+            .await
+            .map_err(|e| ATMError::MsgSendError(format!("Error packing message: {e}")))?;
+
+        self.atm
+            .send_message(profile, &packed, &message.id, false, true)
+            .await
+    }
+}
+
+fn build(typ: &str, from: &str, to: &str, thid: &str, body: serde_json::Value) -> Message {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    Message::build(Uuid::new_v4().to_string(), typ.to_owned(), body)
+        .from(from.to_owned())
+        .to(to.to_owned())
+        .thid(thid.to_owned())
+        .created_time(now)
+        .finalize()
+}
+
+fn identifier_of(participants: &[String], did: &str) -> Result<Identifier, ATMError> {
+    let index = participants
+        .iter()
+        .position(|p| p == did)
+        .ok_or_else(|| ATMError::KeyCeremonyError(format!("{did} is not a participant")))?;
+    u16::try_from(index + 1)
+        .ok()
+        .and_then(|v| Identifier::new(v).ok())
+        .ok_or_else(|| ATMError::KeyCeremonyError("too many participants".into()))
+}
+
+fn peers<'a>(participants: &'a [String], me: &'a str) -> impl Iterator<Item = &'a String> {
+    participants.iter().filter(move |p| *p != me)
+}
+
+/// The error for a message whose envelope doesn't prove its `from`.
+fn unauthenticated() -> ATMError {
+    ATMError::KeyCeremonyError("message is not authcrypted by its sender".into())
+}
+
+fn expect_type(message: &Message, typ: &str) -> Result<(), ATMError> {
+    if message.typ == typ {
+        Ok(())
+    } else {
+        Err(ATMError::KeyCeremonyError(format!(
+            "expected {typ}, got {}",
+            message.typ
+        )))
+    }
+}
+
+fn check_thread(got: &str, expected: &str) -> Result<(), ATMError> {
+    if got == expected {
+        Ok(())
+    } else {
+        Err(ATMError::KeyCeremonyError(format!(
+            "message belongs to {got}, not {expected}"
+        )))
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(message: &Message) -> Result<T, ATMError> {
+    serde_json::from_value(message.body.clone())
+        .map_err(|e| ATMError::KeyCeremonyError(format!("invalid {} body: {e}", message.typ)))
+}
+
+#[cfg(test)]
+mod tests {
+    use affinidi_crypto::frost;
+
+    use super::*;
+    use crate::messages::compat::UnpackMetadata;
+
+    fn dids(n: usize) -> Vec<String> {
+        (1..=n).map(|i| format!("did:example:p{i}")).collect()
+    }
+
+    /// Deliver every message to its recipient until nothing is left in flight.
+    fn run_dkg(
+        participants: &[String],
+        threshold: u16,
+    ) -> (CeremonyOutcome, Vec<KeyCeremonyParticipant>) {
+        let coordinator_did = "did:example:coordinator";
+        let mut coordinator =
+            KeyCeremonyCoordinator::new(coordinator_did, participants.to_vec(), threshold).unwrap();
+
+        let mut parties = Vec::new();
+        let mut in_flight = Vec::new();
+        for invite in coordinator.invitations() {
+            let to = invite.to.as_ref().unwrap()[0].clone();
+            let (party, out) =
+                KeyCeremonyParticipant::accept(&to, &invite, &UnpackMetadata::authcrypted(&invite))
+                    .unwrap();
+            parties.push(party);
+            in_flight.extend(out);
+        }
+
+        let mut outcome = None;
+        while let Some(msg) = in_flight.pop() {
+            let to = &msg.to.as_ref().unwrap()[0];
+            if to == coordinator_did {
+                if let Some(done) = coordinator
+                    .handle(&msg, &UnpackMetadata::authcrypted(&msg))
+                    .unwrap()
+                {
+                    outcome = Some(done);
+                }
+            } else {
+                let index = participants.iter().position(|p| p == to).unwrap();
+                in_flight.extend(
+                    parties[index]
+                        .handle(&msg, &UnpackMetadata::authcrypted(&msg))
+                        .unwrap(),
+                );
+            }
+        }
+        (outcome.expect("ceremony completed"), parties)
+    }
+
+    #[test]
+    fn dkg_then_threshold_signature() {
+        let participants = dids(3);
+        let (outcome, parties) = run_dkg(&participants, 2);
+        assert!(outcome.update_key.starts_with("z6Mk"));
+        assert!(parties.iter().all(|p| p.is_complete()));
+
+        let coordinator = "did:example:coordinator";
+        let signers = vec![participants[0].clone(), participants[2].clone()];
+        let mut session = ThresholdSigningSession::new(
+            coordinator,
+            participants.clone(),
+            signers.clone(),
+            outcome.public_key_package.clone(),
+            b"log entry sign input".to_vec(),
+        )
+        .unwrap();
+        let mut signer_states: Vec<_> = [0, 2]
+            .iter()
+            .map(|&i| {
+                ThresholdSigner::new(
+                    &participants[i],
+                    coordinator,
+                    participants.clone(),
+                    parties[i].key_package().unwrap().clone(),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let mut packages = Vec::new();
+        for (request, signer) in session.requests().iter().zip(&mut signer_states) {
+            let reply = signer
+                .handle(request, &UnpackMetadata::authcrypted(request), |_| true)
+                .unwrap();
+            if let SigningProgress::Send(out) = session
+                .handle(&reply, &UnpackMetadata::authcrypted(&reply))
+                .unwrap()
+            {
+                packages = out;
+            }
+        }
+        assert_eq!(packages.len(), 2);
+
+        let mut signature = None;
+        for (package, signer) in packages.iter().zip(&mut signer_states) {
+            let share = signer
+                .handle(package, &UnpackMetadata::authcrypted(package), |bytes| {
+                    bytes == b"log entry sign input"
+                })
+                .unwrap();
+            if let SigningProgress::Signed(sig) = session
+                .handle(&share, &UnpackMetadata::authcrypted(&share))
+                .unwrap()
+            {
+                signature = Some(sig);
+            }
+        }
+        let signature = signature.expect("signature aggregated");
+        frost::verify(
+            &outcome.public_key_package.verifying_key_bytes(),
+            b"log entry sign input",
+            &signature,
+        )
+        .unwrap();
+        assert!(proof_value(&signature).starts_with('z'));
+    }
+
+    #[test]
+    fn signer_can_decline() {
+        let participants = dids(2);
+        let (outcome, parties) = run_dkg(&participants, 2);
+        let coordinator = "did:example:coordinator";
+        let mut session = ThresholdSigningSession::new(
+            coordinator,
+            participants.clone(),
+            participants.clone(),
+            outcome.public_key_package,
+            b"unexpected".to_vec(),
+        )
+        .unwrap();
+        let mut signers: Vec<_> = (0..2)
+            .map(|i| {
+                ThresholdSigner::new(
+                    &participants[i],
+                    coordinator,
+                    participants.clone(),
+                    parties[i].key_package().unwrap().clone(),
+                )
+                .unwrap()
+            })
+            .collect();
+
+        let mut packages = Vec::new();
+        for (request, signer) in session.requests().iter().zip(&mut signers) {
+            let reply = signer
+                .handle(request, &UnpackMetadata::authcrypted(request), |_| true)
+                .unwrap();
+            if let SigningProgress::Send(out) = session
+                .handle(&reply, &UnpackMetadata::authcrypted(&reply))
+                .unwrap()
+            {
+                packages = out;
+            }
+        }
+        assert!(
+            signers[0]
+                .handle(
+                    &packages[0],
+                    &UnpackMetadata::authcrypted(&packages[0]),
+                    |_| false
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn messages_from_outsiders_are_rejected() {
+        let participants = dids(3);
+        let mut coordinator =
+            KeyCeremonyCoordinator::new("did:example:coordinator", participants.clone(), 2)
+                .unwrap();
+        let invite = coordinator.invitations().remove(0);
+        let (mut party, round1) = KeyCeremonyParticipant::accept(
+            &participants[0],
+            &invite,
+            &UnpackMetadata::authcrypted(&invite),
+        )
+        .unwrap();
+
+        let mut forged = round1[0].clone();
+        forged.from = Some("did:example:mallory".into());
+        assert!(
+            party
+                .handle(&forged, &UnpackMetadata::authcrypted(&forged))
+                .is_err()
+        );
+        assert!(
+            coordinator
+                .handle(&forged, &UnpackMetadata::authcrypted(&forged))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn messages_that_do_not_prove_their_sender_are_rejected() {
+        let participants = dids(2);
+        let coordinator =
+            KeyCeremonyCoordinator::new("did:example:coordinator", participants.clone(), 2)
+                .unwrap();
+        let invites = coordinator.invitations();
+        let (mut party, _) = KeyCeremonyParticipant::accept(
+            &participants[0],
+            &invites[0],
+            &UnpackMetadata::authcrypted(&invites[0]),
+        )
+        .unwrap();
+        let (_, round1) = KeyCeremonyParticipant::accept(
+            &participants[1],
+            &invites[1],
+            &UnpackMetadata::authcrypted(&invites[1]),
+        )
+        .unwrap();
+
+        let plaintext = UnpackMetadata::default();
+        let anoncrypt = UnpackMetadata {
+            encrypted: true,
+            anonymous_sender: true,
+            ..Default::default()
+        };
+        let other_key = UnpackMetadata {
+            encrypted_from_kid: Some("did:example:mallory#key-2".into()),
+            ..UnpackMetadata::authcrypted(&round1[0])
+        };
+        for meta in [&plaintext, &anoncrypt, &other_key] {
+            assert!(party.handle(&round1[0], meta).is_err());
+            assert!(KeyCeremonyParticipant::accept(&participants[0], &invites[0], meta).is_err());
+        }
+        assert!(
+            party
+                .handle(&round1[0], &UnpackMetadata::authcrypted(&round1[0]))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert!(KeyCeremonyCoordinator::new("did:example:c", dids(3), 1).is_err());
+        assert!(KeyCeremonyCoordinator::new("did:example:c", dids(3), 4).is_err());
+        let mut duplicated = dids(2);
+        duplicated.push(duplicated[0].clone());
+        assert!(KeyCeremonyCoordinator::new("did:example:c", duplicated, 2).is_err());
+    }
+}
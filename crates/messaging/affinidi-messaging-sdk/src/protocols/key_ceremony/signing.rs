@@ -0,0 +1,323 @@
+//! Threshold signing: the coordinator's session collecting commitments and
+//! shares, and each signer's answers to it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use affinidi_crypto::frost::{
+    self, Identifier, KeyPackage, PublicKeyPackage, SignatureShare, SigningCommitments,
+    SigningNonces, SigningPackage,
+};
+use affinidi_messaging_didcomm::message::Message;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{build, check_thread, identifier_of, message_types, parse_body, unauthenticated};
+use crate::{errors::ATMError, messages::compat::UnpackMetadata};
+
+#[derive(Serialize, Deserialize)]
+struct SignRequestBody {
+    session_id: String,
+    signers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignCommitmentBody {
+    session_id: String,
+    commitments: SigningCommitments,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignPackageBody {
+    session_id: String,
+    package: SigningPackage,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignShareBody {
+    session_id: String,
+    share: SignatureShare,
+}
+
+/// Coordinator side of one threshold signature.
+pub struct ThresholdSigningSession {
+    session_id: String,
+    coordinator: String,
+    participants: Vec<String>,
+    signers: Vec<String>,
+    public_key_package: PublicKeyPackage,
+    message: Vec<u8>,
+    commitments: BTreeMap<Identifier, SigningCommitments>,
+    package: Option<SigningPackage>,
+    shares: BTreeMap<Identifier, SignatureShare>,
+}
+
+impl ThresholdSigningSession {
+    /// Open a session in which `signers` (a subset of the ceremony's
+    /// `participants`, at least the threshold) sign `message`.
+    pub fn new(
+        coordinator: &str,
+        participants: Vec<String>,
+        signers: Vec<String>,
+        public_key_package: PublicKeyPackage,
+        message: Vec<u8>,
+    ) -> Result<Self, ATMError> {
+        if signers.len() < usize::from(public_key_package.min_signers()) {
+            return Err(ATMError::KeyCeremonyError(format!(
+                "{} signers chosen, {} required",
+                signers.len(),
+                public_key_package.min_signers()
+            )));
+        }
+        for signer in &signers {
+            identifier_of(&participants, signer)?;
+        }
+
+        Ok(ThresholdSigningSession {
+            session_id: Uuid::new_v4().to_string(),
+            coordinator: coordinator.to_string(),
+            participants,
+            signers,
+            public_key_package,
+            message,
+            commitments: BTreeMap::new(),
+            package: None,
+            shares: BTreeMap::new(),
+        })
+    }
+
+    /// The session's identifier (the DIDComm thread).
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// One `sign-request` per signer.
+    pub fn requests(&self) -> Vec<Message> {
+        self.signers
+            .iter()
+            .map(|to| {
+                build(
+                    message_types::SIGN_REQUEST,
+                    &self.coordinator,
+                    to,
+                    &self.session_id,
+                    json!(SignRequestBody {
+                        session_id: self.session_id.clone(),
+                        signers: self.signers.clone(),
+                    }),
+                )
+            })
+            .collect()
+    }
+
+    /// Process a `sign-commitment` or `sign-share` from a signer.
+    ///
+    /// Returns [`SigningProgress::Send`] with the `sign-package` messages once
+    /// every commitment is in, and [`SigningProgress::Signed`] with the
+    /// verified Ed25519 signature once every share is in.
+    pub fn handle(
+        &mut self,
+        message: &Message,
+        meta: &UnpackMetadata,
+    ) -> Result<SigningProgress, ATMError> {
+        let from = meta.require_authenticated_sender(message, unauthenticated)?;
+        if !self.signers.iter().any(|s| s == from) {
+            return Err(ATMError::KeyCeremonyError(format!(
+                "{from} is not a signer in this session"
+            )));
+        }
+        let id = identifier_of(&self.participants, from)?;
+
+        match message.typ.as_str() {
+            message_types::SIGN_COMMITMENT => {
+                let body: SignCommitmentBody = parse_body(message)?;
+                check_thread(&body.session_id, &self.session_id)?;
+                if self.package.is_some() || self.commitments.insert(id, body.commitments).is_some()
+                {
+                    return Err(ATMError::KeyCeremonyError(format!(
+                        "unexpected commitment from participant {id}"
+                    )));
+                }
+                if self.commitments.len() < self.signers.len() {
+                    return Ok(SigningProgress::Pending);
+                }
+
+                let package = SigningPackage::new(self.commitments.clone(), self.message.clone());
+                let outgoing = self
+                    .signers
+                    .iter()
+                    .map(|to| {
+                        build(
+                            message_types::SIGN_PACKAGE,
+                            &self.coordinator,
+                            to,
+                            &self.session_id,
+                            json!(SignPackageBody {
+                                session_id: self.session_id.clone(),
+                                package: package.clone(),
+                            }),
+                        )
+                    })
+                    .collect();
+                self.package = Some(package);
+                Ok(SigningProgress::Send(outgoing))
+            }
+            message_types::SIGN_SHARE => {
+                let body: SignShareBody = parse_body(message)?;
+                check_thread(&body.session_id, &self.session_id)?;
+                let Some(package) = &self.package else {
+                    return Err(ATMError::KeyCeremonyError(format!(
+                        "signature share from participant {id} before the signing package"
+                    )));
+                };
+                if self.shares.insert(id, body.share).is_some() {
+                    return Err(ATMError::KeyCeremonyError(format!(
+                        "duplicate signature share from participant {id}"
+                    )));
+                }
+                if self.shares.len() < self.signers.len() {
+                    return Ok(SigningProgress::Pending);
+                }
+
+                let signature = frost::aggregate(package, &self.shares, &self.public_key_package)
+                    .map_err(|e| ATMError::KeyCeremonyError(e.to_string()))?;
+                Ok(SigningProgress::Signed(signature))
+            }
+            other => Err(ATMError::KeyCeremonyError(format!(
+                "unexpected key ceremony message type {other}"
+            ))),
+        }
+    }
+}
+
+/// What a [`ThresholdSigningSession`] needs next.
+#[derive(Debug)]
+pub enum SigningProgress {
+    /// Waiting for more signers.
+    Pending,
+    /// Send these messages to the signers.
+    Send(Vec<Message>),
+    /// The group signature, verified under the group key.
+    Signed([u8; 64]),
+}
+
+/// Signer side of threshold signing: answers the coordinator's requests with
+/// this participant's key share.
+pub struct ThresholdSigner {
+    did: String,
+    coordinator: String,
+    participants: Vec<String>,
+    key_package: KeyPackage,
+    nonces: HashMap<String, SigningNonces>,
+}
+
+impl ThresholdSigner {
+    /// Sign as `did` (identifier taken from its position in `participants`)
+    /// for sessions run by `coordinator`.
+    pub fn new(
+        did: &str,
+        coordinator: &str,
+        participants: Vec<String>,
+        key_package: KeyPackage,
+    ) -> Result<Self, ATMError> {
+        if identifier_of(&participants, did)? != key_package.identifier() {
+            return Err(ATMError::KeyCeremonyError(format!(
+                "{did} is not participant {} of this group",
+                key_package.identifier()
+            )));
+        }
+        Ok(ThresholdSigner {
+            did: did.to_string(),
+            coordinator: coordinator.to_string(),
+            participants,
+            key_package,
+            nonces: HashMap::new(),
+        })
+    }
+
+    /// Answer a `sign-request` with fresh commitments, or a `sign-package`
+    /// with a signature share.
+    ///
+    /// `approve` sees the exact bytes to be signed before any share is
+    /// released; return `false` to refuse (e.g. the log entry is not one this
+    /// participant agreed to). Nonces are used once and then discarded.
+    pub fn handle(
+        &mut self,
+        message: &Message,
+        meta: &UnpackMetadata,
+        approve: impl FnOnce(&[u8]) -> bool,
+    ) -> Result<Message, ATMError> {
+        if meta.require_authenticated_sender(message, unauthenticated)? != self.coordinator {
+            return Err(ATMError::KeyCeremonyError(
+                "signing request was not sent by the coordinator".into(),
+            ));
+        }
+
+        match message.typ.as_str() {
+            message_types::SIGN_REQUEST => {
+                let body: SignRequestBody = parse_body(message)?;
+                if !body.signers.iter().any(|s| s == &self.did) {
+                    return Err(ATMError::KeyCeremonyError(
+                        "not a signer in this session".into(),
+                    ));
+                }
+                let (nonces, commitments) = frost::commit(&self.key_package, &mut rand_core::OsRng);
+                self.nonces.insert(body.session_id.clone(), nonces);
+                Ok(build(
+                    message_types::SIGN_COMMITMENT,
+                    &self.did,
+                    &self.coordinator,
+                    &body.session_id,
+                    json!(SignCommitmentBody {
+                        session_id: body.session_id.clone(),
+                        commitments,
+                    }),
+                ))
+            }
+            message_types::SIGN_PACKAGE => {
+                let body: SignPackageBody = parse_body(message)?;
+                let nonces = self.nonces.remove(&body.session_id).ok_or_else(|| {
+                    ATMError::KeyCeremonyError(format!(
+                        "no commitments outstanding for session {}",
+                        body.session_id
+                    ))
+                })?;
+                if body
+                    .package
+                    .signers()
+                    .any(|id| usize::from(id.value()) > self.participants.len())
+                {
+                    return Err(ATMError::KeyCeremonyError(
+                        "signing package names an unknown participant".into(),
+                    ));
+                }
+                if !approve(body.package.message()) {
+                    return Err(ATMError::KeyCeremonyError(
+                        "signing request declined".into(),
+                    ));
+                }
+                let share = frost::sign(&body.package, nonces, &self.key_package)
+                    .map_err(|e| ATMError::KeyCeremonyError(e.to_string()))?;
+                Ok(build(
+                    message_types::SIGN_SHARE,
+                    &self.did,
+                    &self.coordinator,
+                    &body.session_id,
+                    json!(SignShareBody {
+                        session_id: body.session_id.clone(),
+                        share,
+                    }),
+                ))
+            }
+            other => Err(ATMError::KeyCeremonyError(format!(
+                "unexpected key ceremony message type {other}"
+            ))),
+        }
+    }
+}
+
+/// Encode an Ed25519 signature as a Data Integrity `proofValue` (multibase
+/// base58btc), for the proof on a threshold-signed webvh log entry.
+pub fn proof_value(signature: &[u8; 64]) -> String {
+    affinidi_encoding::encode_base58btc(signature)
+}
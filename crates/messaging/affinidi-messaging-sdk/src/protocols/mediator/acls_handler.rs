@@ -20,8 +20,9 @@ use uuid::Uuid;
 // keeping the request/response types resolvable at their original
 // `affinidi_messaging_sdk::*` paths via these re-exports.
 pub use affinidi_messaging_mediator_common::types::acls_handler::{
-    MediatorACLExpanded, MediatorACLGetResponse, MediatorACLRequest, MediatorACLSetResponse,
-    MediatorAccessListAddResponse, MediatorAccessListGetResponse, MediatorAccessListListResponse,
+    MediatorACLExpanded, MediatorACLGetResponse, MediatorACLModeResponse, MediatorACLRequest,
+    MediatorACLSetResponse, MediatorAccessListAddResponse, MediatorAccessListGetResponse,
+    MediatorAccessListListResponse,
 };
 
 impl Mediator {
@@ -153,6 +154,73 @@ impl Mediator {
         })
     }
 
+    /// ACL Mode: Discovers how the mediator applies ACLs, and the caller's own ACL status
+    /// `atm`: ATM instance
+    /// `profile`: Profile instance
+    ///
+    /// Use this to explain an `ACLDenied` error: whether the mediator runs as an
+    /// allowlist (`ExplicitAllow`) or denylist (`ExplicitDeny`), and whether this
+    /// profile's DID is blocked or lacks send/receive permissions.
+    pub async fn acl_mode(
+        &self,
+        atm: &ATM,
+        profile: &Arc<ATMProfile>,
+    ) -> Result<MediatorACLModeResponse, ATMError> {
+        let _span = span!(Level::DEBUG, "acl_mode");
+
+        async move {
+            debug!("Start");
+
+            let (profile_did, mediator_did) = profile.dids()?;
+
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+
+            let msg = Message::build(
+                Uuid::new_v4().to_string(),
+                "https://didcomm.org/mediator/1.0/acl-management".to_owned(),
+                json!(MediatorACLRequest::GetACLMode {}),
+            )
+            .to(mediator_did.into())
+            .from(profile_did.into())
+            .created_time(now)
+            .expires_time(now + 10)
+            .finalize();
+
+            let msg_id = msg.id.clone();
+
+            // Pack the message
+            let (msg, _) = atm
+                .inner
+                .pack_encrypted(&msg, mediator_did, Some(profile_did))
+                .await
+                .map_err(|e| ATMError::MsgSendError(format!("Error packing message: {e}")))?;
+
+            match atm.send_message(profile, &msg, &msg_id, true, true).await? {
+                SendMessageResponse::Message(message) => self._parse_acl_mode_response(&message),
+                _ => Err(ATMError::MsgReceiveError(
+                    "No response from mediator".to_owned(),
+                )),
+            }
+        }
+        .instrument(_span)
+        .await
+    }
+
+    // Parses the response from the mediator for ACL Mode
+    fn _parse_acl_mode_response(
+        &self,
+        message: &Message,
+    ) -> Result<MediatorACLModeResponse, ATMError> {
+        serde_json::from_value(message.body.clone()).map_err(|err| {
+            ATMError::MsgReceiveError(format!(
+                "Mediator ACL mode response could not be parsed. Reason: {err}"
+            ))
+        })
+    }
+
     /// Access List List: Lists hash of DID's in the Access Control List for a given DID
     /// `atm`: ATM instance
     /// `profile`: Profile instance
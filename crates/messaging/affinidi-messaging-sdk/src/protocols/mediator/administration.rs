@@ -2,7 +2,10 @@
 //! Admin account management
 //! Global ACL management
 
-use crate::{ATM, errors::ATMError, profiles::ATMProfile, transports::SendMessageResponse};
+use crate::{
+    ATM, errors::ATMError, messages::compat::UnpackMetadata, profiles::ATMProfile,
+    transports::SendMessageResponse,
+};
 use affinidi_messaging_didcomm::message::Message;
 use regex::Regex;
 use serde_json::{Value, json};
@@ -16,7 +19,9 @@ use uuid::Uuid;
 // `Mediator` / `MediatorOps` client-side handler types remain in this
 // SDK module.
 pub use affinidi_messaging_mediator_common::types::administration::{
-    AdminAccount, MediatorAdminList, MediatorAdminRequest,
+    ANNOUNCEMENT_MESSAGE_TYPE, AdminAccount, AnnouncementKind, BroadcastFilter, MediatorAdminList,
+    MediatorAdminRequest, MediatorAnnouncement, MediatorBroadcast, MediatorBroadcastAccepted,
+    MediatorBroadcastResult,
 };
 pub use affinidi_messaging_mediator_common::types::audit::{
     AuditAction, AuditLogEntry, MediatorAuditLogList,
@@ -397,6 +402,118 @@ impl Mediator {
         .instrument(_span)
         .await
     }
+
+    /// Parses the response from the mediator for a broadcast
+    fn _parse_broadcast_response(
+        &self,
+        message: &Message,
+    ) -> Result<MediatorBroadcastAccepted, ATMError> {
+        serde_json::from_value(message.body.clone()).map_err(|err| {
+            ATMError::MsgReceiveError(format!(
+                "Mediator Broadcast response could not be parsed. Reason: {err}"
+            ))
+        })
+    }
+
+    /// Sends an announcement to every account on the mediator that matches
+    /// `broadcast.filter` (all accounts when the filter is empty). Admin-only.
+    ///
+    /// The mediator acknowledges the request, then signs each copy and queues
+    /// it in the account's inbox in the background; recipients recognise it
+    /// with [`Mediator::parse_announcement`].
+    /// - `atm` - The ATM client to use
+    /// - `broadcast` - The announcement, filter and optional expiry
+    /// # Returns
+    /// The broadcast's ID. How many accounts it was queued for, and how many
+    /// failed, is recorded against it in the audit log
+    /// ([`Mediator::list_audit_log`]).
+    pub async fn broadcast(
+        &self,
+        atm: &ATM,
+        profile: &Arc<ATMProfile>,
+        broadcast: &MediatorBroadcast,
+    ) -> Result<MediatorBroadcastAccepted, ATMError> {
+        let _span = span!(Level::DEBUG, "broadcast");
+
+        async move {
+            debug!(
+                "Broadcasting {:?} announcement: {}",
+                broadcast.announcement.kind, broadcast.announcement.title
+            );
+
+            let (profile_did, mediator_did) = profile.dids()?;
+
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+
+            let msg = Message::build(
+                Uuid::new_v4().to_string(),
+                "https://didcomm.org/mediator/1.0/admin-management".to_owned(),
+                json!(MediatorAdminRequest::Broadcast(broadcast.clone())),
+            )
+            .to(mediator_did.into())
+            .from(profile_did.into())
+            .created_time(now)
+            .expires_time(now + 10)
+            .finalize();
+
+            let msg_id = msg.id.clone();
+
+            // Pack the message
+            let (msg, _) = atm
+                .inner
+                .pack_encrypted(&msg, mediator_did, Some(profile_did))
+                .await
+                .map_err(|e| ATMError::MsgSendError(format!("Error packing message: {e}")))?;
+
+            match atm.send_message(profile, &msg, &msg_id, true, true).await? {
+                SendMessageResponse::Message(message) => self._parse_broadcast_response(&message),
+                _ => Err(ATMError::MsgReceiveError(
+                    "No response from mediator".to_owned(),
+                )),
+            }
+        }
+        .instrument(_span)
+        .await
+    }
+
+    /// Recognises an announcement broadcast by `mediator_did`.
+    /// - `message` / `metadata` - An unpacked inbound message
+    /// - `mediator_did` - The mediator the announcement should come from
+    /// # Returns
+    /// `Ok(None)` when the message isn't an announcement.
+    /// An error when it claims to be one but wasn't signed by the mediator,
+    /// or its body doesn't parse.
+    pub fn parse_announcement(
+        message: &Message,
+        metadata: &UnpackMetadata,
+        mediator_did: &str,
+    ) -> Result<Option<MediatorAnnouncement>, ATMError> {
+        if message.typ != ANNOUNCEMENT_MESSAGE_TYPE {
+            return Ok(None);
+        }
+
+        let signer_did = metadata
+            .sign_from
+            .as_deref()
+            .and_then(|kid| kid.split('#').next());
+        if signer_did != Some(mediator_did) || message.from.as_deref() != Some(mediator_did) {
+            return Err(ATMError::MsgReceiveError(format!(
+                "Announcement ({}) wasn't signed by the mediator ({mediator_did})",
+                message.id
+            )));
+        }
+
+        serde_json::from_value(message.body.clone())
+            .map(Some)
+            .map_err(|err| {
+                ATMError::MsgReceiveError(format!(
+                    "Mediator Announcement could not be parsed. Reason: {err}"
+                ))
+            })
+    }
 }
 
 /// Wrapper struct that holds a reference to ATM, enabling the `atm.mediator().method()` pattern
@@ -480,4 +597,16 @@ impl<'a> MediatorOps<'a> {
             .list_audit_log(self.atm, profile, cursor, limit)
             .await
     }
+
+    /// Sends an announcement to accounts on the mediator
+    /// See [`Mediator::broadcast`] for full documentation
+    pub async fn broadcast(
+        &self,
+        profile: &Arc<ATMProfile>,
+        broadcast: &MediatorBroadcast,
+    ) -> Result<MediatorBroadcastAccepted, ATMError> {
+        Mediator::default()
+            .broadcast(self.atm, profile, broadcast)
+            .await
+    }
 }
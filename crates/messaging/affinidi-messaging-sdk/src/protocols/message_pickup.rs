@@ -15,7 +15,7 @@ use std::{
     sync::Arc,
     time::{Duration, SystemTime},
 };
-use tokio::select;
+use tokio::{select, sync::mpsc};
 use tracing::{Instrument, Level, debug, span, warn};
 use uuid::Uuid;
 
@@ -92,6 +92,31 @@ pub struct MessagePickupMessagesReceived {
     pub message_id_list: Vec<String>,
 }
 
+/// A `GetMessage` request registered with the WebSocket task. Dropped unsettled
+/// — the caller gave up on [`MessagePickup::live_stream_get`] — it withdraws
+/// the request, so a reply arriving later stays queued for the next reader.
+struct WantedMessage {
+    ws_channel: mpsc::Sender<WebSocketCommands>,
+    msg_id: Option<String>,
+}
+
+impl WantedMessage {
+    /// The request was answered, or its withdrawal is handled by the caller.
+    fn settle(mut self) {
+        self.msg_id = None;
+    }
+}
+
+impl Drop for WantedMessage {
+    fn drop(&mut self) {
+        if let Some(msg_id) = self.msg_id.take() {
+            let _ = self
+                .ws_channel
+                .try_send(WebSocketCommands::CancelGetMessage(msg_id));
+        }
+    }
+}
+
 impl MessagePickup {
     /// Sends a Message Pickup 3.0 `Status Request` message
     /// recipient_did : Optional, allows you to ask for status for a specific DID. If none, will ask for default DID in ATM
@@ -491,6 +516,10 @@ impl MessagePickup {
                 ))
             })?;
             debug!("sent get request to ws_handler");
+            let wanted = WantedMessage {
+                ws_channel: ws_channel.clone(),
+                msg_id: Some(msg_id.to_string()),
+            };
 
             // Setup the timer for the wait, doesn't do anything till `await` is called in the select! macro
             let sleep = tokio::time::sleep(wait);
@@ -499,6 +528,7 @@ impl MessagePickup {
             select! {
                 _ = &mut sleep, if wait.as_millis() > 0 => {
                     debug!("Timeout reached, no message received");
+                    wanted.settle();
                     ws_channel.send(WebSocketCommands::CancelGetMessage(msg_id.to_string())).await.map_err(|err| {
                         ATMError::TransportError(format!(
                             "Could not send CancelGetMessage command to websocket: {err:?}"
@@ -507,6 +537,7 @@ impl MessagePickup {
                     Ok(None)
                 }
                 value = rx => {
+                    wanted.settle();
                     match value {
                         Ok(WebSocketResponses::MessageReceived(msg, meta)) => {
                             // If auto_delete is true, delete the message
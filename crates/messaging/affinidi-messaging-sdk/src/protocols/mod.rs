@@ -26,9 +26,13 @@ pub struct Protocols {
 }
 
 pub mod discover_features;
+#[cfg(feature = "key-ceremony")]
+pub mod key_ceremony;
 pub mod mediator;
 pub mod message_pickup;
 pub mod oob_discovery;
+#[cfg(feature = "profile-sync")]
+pub mod profile_sync;
 pub mod routing;
 pub mod trust_ping;
 pub mod trust_tasks;
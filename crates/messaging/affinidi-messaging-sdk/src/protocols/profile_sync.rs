@@ -0,0 +1,773 @@
+//! Profile sync: keep profile state consistent across devices over DIDComm.
+//!
+//! A wallet running on several devices keeps its contacts, credential
+//! metadata and read-state in a [`ProfileSync`] store on each device. Devices
+//! exchange the entries the other hasn't seen directly with each other,
+//! authcrypted end to end, so no server ever holds the state.
+//!
+//! ## Data model
+//!
+//! The store holds JSON values under `(collection, key)`; [`collections`]
+//! names the usual collections, but any name works. Every device has a
+//! [`VectorClock`] counting the writes it has seen from each device, and every
+//! entry carries the clock of the writes that produced it. Removing a key
+//! leaves a tombstone so the removal syncs like any other write.
+//!
+//! When two entries for a key arrive whose clocks are concurrent (neither
+//! device saw the other's write), a [`ConflictResolver`] picks the value; the
+//! default, [`LastWriterWins`], keeps the later write. The resolved entry
+//! carries both clocks joined, so both devices settle on the same entry. The
+//! conflict is reported in [`SyncOutcome::conflicts`] so the application can
+//! surface it.
+//!
+//! ## Exchange
+//!
+//! 1. Device A sends [`ProfileSync::request`] to device B: A's clock.
+//! 2. B's [`ProfileSync::handle`] replies with an `update`: the entries A's
+//!    clock doesn't cover, and B's own clock.
+//! 3. A merges them and replies with an `update` of the entries B's clock
+//!    doesn't cover. B merges those; the exchange ends.
+//!
+//! Devices only accept messages from the peers they were paired with
+//! ([`ProfileSync::with_peer`]). [`ProfileSync::handle`] takes the
+//! [`UnpackMetadata`] of each message and rejects any that wasn't authcrypted
+//! by the key of its `from` DID, so a plaintext or anoncrypt message naming a
+//! paired device is refused. Send with [`ProfileSyncOps::send`], which
+//! authcrypts.
+//!
+//! [`SyncState`] serialises, so the store can be persisted between runs and
+//! restored with [`ProfileSync::from_state`].
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use affinidi_messaging_didcomm::message::Message;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::{
+    ATM, errors::ATMError, messages::compat::UnpackMetadata, profiles::ATMProfile,
+    transports::SendMessageResponse,
+};
+
+/// Protocol identifier (PIURI) prefix for profile sync messages.
+pub const PROFILE_SYNC_PROTOCOL: &str = "https://affinidi.com/didcomm/protocols/profile-sync/1.0";
+
+/// Message types of the profile sync protocol.
+pub mod message_types {
+    pub const REQUEST: &str = "https://affinidi.com/didcomm/protocols/profile-sync/1.0/request";
+    pub const UPDATE: &str = "https://affinidi.com/didcomm/protocols/profile-sync/1.0/update";
+}
+
+/// Names of the collections wallets commonly sync.
+pub mod collections {
+    /// Known contacts, keyed by DID
+    pub const CONTACTS: &str = "contacts";
+    /// Credential metadata (never the credentials' secrets), keyed by credential ID
+    pub const CREDENTIALS: &str = "credentials";
+    /// Read markers, keyed by conversation or message ID
+    pub const READ_STATE: &str = "read-state";
+}
+
+/// Count of writes seen from each device.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct VectorClock(BTreeMap<String, u64>);
+
+impl VectorClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes seen from `device`.
+    pub fn get(&self, device: &str) -> u64 {
+        self.0.get(device).copied().unwrap_or(0)
+    }
+
+    /// Count one more write from `device`, returning the new count.
+    pub fn increment(&mut self, device: &str) -> u64 {
+        let counter = self.0.entry(device.to_string()).or_default();
+        *counter += 1;
+        *counter
+    }
+
+    /// Raise every count to at least `other`'s.
+    pub fn merge(&mut self, other: &VectorClock) {
+        for (device, &count) in &other.0 {
+            let counter = self.0.entry(device.clone()).or_default();
+            *counter = (*counter).max(count);
+        }
+    }
+
+    /// Whether `other` has seen every write this clock has.
+    pub fn dominated_by(&self, other: &VectorClock) -> bool {
+        self.0
+            .iter()
+            .all(|(device, &count)| count <= other.get(device))
+    }
+
+    /// How this clock orders against `other`; `None` when they are
+    /// concurrent.
+    pub fn compare(&self, other: &VectorClock) -> Option<Ordering> {
+        match (self.dominated_by(other), other.dominated_by(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+/// One value in the store, with the history that produced it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncEntry {
+    /// `None` when the key was removed
+    pub value: Option<Value>,
+    /// Writes this entry reflects
+    pub clock: VectorClock,
+    /// Device that made the last write
+    pub device: String,
+    /// Milliseconds since the UNIX epoch at the last write
+    pub updated_at: u64,
+}
+
+impl SyncEntry {
+    /// Orders writes by time, then device; used to break ties
+    /// deterministically.
+    fn recency(&self) -> (u64, &str) {
+        (self.updated_at, &self.device)
+    }
+}
+
+/// Picks the value for a key written concurrently on two devices.
+///
+/// Must give the same answer whichever entry is `a`, and must depend only
+/// on the entries, so that every device resolves the conflict the same way.
+pub trait ConflictResolver: Send + Sync {
+    fn resolve(&self, collection: &str, key: &str, a: &SyncEntry, b: &SyncEntry) -> Option<Value>;
+}
+
+/// Keeps the value of the later write; ties go to the greater device DID.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LastWriterWins;
+
+impl ConflictResolver for LastWriterWins {
+    fn resolve(
+        &self,
+        _collection: &str,
+        _key: &str,
+        a: &SyncEntry,
+        b: &SyncEntry,
+    ) -> Option<Value> {
+        if a.recency() >= b.recency() {
+            a.value.clone()
+        } else {
+            b.value.clone()
+        }
+    }
+}
+
+/// Persistable contents of a [`ProfileSync`] store.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncState {
+    /// Writes this device has seen
+    pub clock: VectorClock,
+    /// Entries by collection, then key
+    pub collections: BTreeMap<String, BTreeMap<String, SyncEntry>>,
+}
+
+/// A key two devices wrote concurrently.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncConflict {
+    pub collection: String,
+    pub key: String,
+    /// Value on this device before the merge
+    pub local: Option<Value>,
+    /// Value the peer sent
+    pub remote: Option<Value>,
+    /// Value the resolver kept
+    pub resolved: Option<Value>,
+}
+
+/// Result of handling one profile sync message.
+#[derive(Debug, Default)]
+pub struct SyncOutcome {
+    /// Message to send back to the peer, if any
+    pub reply: Option<Message>,
+    /// Entries added or replaced
+    pub applied: usize,
+    /// Concurrent writes that had to be resolved
+    pub conflicts: Vec<SyncConflict>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RequestBody {
+    clock: VectorClock,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UpdateBody {
+    clock: VectorClock,
+    entries: Vec<WireEntry>,
+    /// Whether the receiver should answer with the entries the sender lacks
+    reply: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireEntry {
+    collection: String,
+    key: String,
+    entry: SyncEntry,
+}
+
+/// One device's profile state, and its side of the sync protocol.
+pub struct ProfileSync {
+    device: String,
+    peers: BTreeSet<String>,
+    state: SyncState,
+    resolver: Arc<dyn ConflictResolver>,
+}
+
+impl fmt::Debug for ProfileSync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfileSync")
+            .field("device", &self.device)
+            .field("peers", &self.peers)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProfileSync {
+    /// An empty store for the device with DID `device`.
+    pub fn new(device: &str) -> Self {
+        Self::from_state(device, SyncState::default())
+    }
+
+    /// A store restored from persisted state.
+    pub fn from_state(device: &str, state: SyncState) -> Self {
+        ProfileSync {
+            device: device.to_string(),
+            peers: BTreeSet::new(),
+            state,
+            resolver: Arc::new(LastWriterWins),
+        }
+    }
+
+    /// Accept sync messages from the device with DID `peer`.
+    pub fn with_peer(mut self, peer: &str) -> Self {
+        self.peers.insert(peer.to_string());
+        self
+    }
+
+    /// Resolve concurrent writes with `resolver` instead of
+    /// [`LastWriterWins`]. Every device must use the same resolver.
+    pub fn with_resolver(mut self, resolver: impl ConflictResolver + 'static) -> Self {
+        self.resolver = Arc::new(resolver);
+        self
+    }
+
+    /// This device's DID.
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+
+    /// State to persist.
+    pub fn state(&self) -> &SyncState {
+        &self.state
+    }
+
+    /// Writes this device has seen.
+    pub fn clock(&self) -> &VectorClock {
+        &self.state.clock
+    }
+
+    pub fn get(&self, collection: &str, key: &str) -> Option<&Value> {
+        self.state
+            .collections
+            .get(collection)?
+            .get(key)?
+            .value
+            .as_ref()
+    }
+
+    /// Live (not removed) entries of `collection`, by key.
+    pub fn entries<'a>(&'a self, collection: &str) -> impl Iterator<Item = (&'a str, &'a Value)> {
+        self.state
+            .collections
+            .get(collection)
+            .into_iter()
+            .flatten()
+            .filter_map(|(key, entry)| Some((key.as_str(), entry.value.as_ref()?)))
+    }
+
+    /// Set `key` in `collection` to `value`.
+    pub fn put(&mut self, collection: &str, key: &str, value: Value) {
+        self.write(collection, key, Some(value));
+    }
+
+    /// Remove `key` from `collection`. Returns whether it was present.
+    pub fn remove(&mut self, collection: &str, key: &str) -> bool {
+        if self.get(collection, key).is_none() {
+            return false;
+        }
+        self.write(collection, key, None);
+        true
+    }
+
+    fn write(&mut self, collection: &str, key: &str, value: Option<Value>) {
+        let counter = self.state.clock.increment(&self.device);
+        let entries = self
+            .state
+            .collections
+            .entry(collection.to_string())
+            .or_default();
+        let mut clock = entries
+            .get(key)
+            .map(|entry| entry.clock.clone())
+            .unwrap_or_default();
+        clock.0.insert(self.device.clone(), counter);
+        entries.insert(
+            key.to_string(),
+            SyncEntry {
+                value,
+                clock,
+                device: self.device.clone(),
+                updated_at: now_millis(),
+            },
+        );
+    }
+
+    /// A `request` asking `peer` for the entries this device hasn't seen.
+    pub fn request(&self, peer: &str) -> Result<Message, ATMError> {
+        self.check_peer(peer)?;
+        let id = Uuid::new_v4().to_string();
+        Ok(build(
+            message_types::REQUEST,
+            &self.device,
+            peer,
+            &id,
+            json!(RequestBody {
+                clock: self.state.clock.clone(),
+            }),
+        ))
+    }
+
+    /// Handle a `request` or `update` from a paired device. `meta` is the
+    /// metadata [`ATM::unpack`] returned with `message`; the message must be
+    /// authcrypted by its sender.
+    pub fn handle(
+        &mut self,
+        message: &Message,
+        meta: &UnpackMetadata,
+    ) -> Result<SyncOutcome, ATMError> {
+        let peer = meta
+            .require_authenticated_sender(message, unauthenticated)?
+            .to_string();
+        self.check_peer(&peer)?;
+        let thid = message.thid.clone().unwrap_or_else(|| message.id.clone());
+
+        match message.typ.as_str() {
+            message_types::REQUEST => {
+                let body: RequestBody = parse_body(message)?;
+                Ok(SyncOutcome {
+                    reply: Some(self.update(&peer, &thid, &body.clock, true)),
+                    ..Default::default()
+                })
+            }
+            message_types::UPDATE => {
+                let body: UpdateBody = parse_body(message)?;
+                let mut outcome = SyncOutcome::default();
+                for wire in body.entries {
+                    self.merge(wire, &mut outcome);
+                }
+                // The peer sent everything our clock didn't cover, so we have
+                // now seen everything it has.
+                self.state.clock.merge(&body.clock);
+                if body.reply {
+                    outcome.reply = Some(self.update(&peer, &thid, &body.clock, false));
+                }
+                Ok(outcome)
+            }
+            other => Err(ATMError::ProfileSyncError(format!(
+                "unexpected message type ({other})"
+            ))),
+        }
+    }
+
+    /// An `update` with the entries `peer_clock` doesn't cover.
+    fn update(&self, peer: &str, thid: &str, peer_clock: &VectorClock, reply: bool) -> Message {
+        let entries = self
+            .state
+            .collections
+            .iter()
+            .flat_map(|(collection, entries)| {
+                entries
+                    .iter()
+                    .filter(|(_, entry)| !entry.clock.dominated_by(peer_clock))
+                    .map(|(key, entry)| WireEntry {
+                        collection: collection.clone(),
+                        key: key.clone(),
+                        entry: entry.clone(),
+                    })
+            })
+            .collect();
+
+        build(
+            message_types::UPDATE,
+            &self.device,
+            peer,
+            thid,
+            json!(UpdateBody {
+                clock: self.state.clock.clone(),
+                entries,
+                reply,
+            }),
+        )
+    }
+
+    fn merge(&mut self, wire: WireEntry, outcome: &mut SyncOutcome) {
+        let WireEntry {
+            collection,
+            key,
+            entry: remote,
+        } = wire;
+        let entries = self
+            .state
+            .collections
+            .entry(collection.clone())
+            .or_default();
+        let Some(local) = entries.get(&key) else {
+            entries.insert(key, remote);
+            outcome.applied += 1;
+            return;
+        };
+
+        match remote.clock.compare(&local.clock) {
+            Some(Ordering::Less | Ordering::Equal) => {}
+            Some(Ordering::Greater) => {
+                entries.insert(key, remote);
+                outcome.applied += 1;
+            }
+            None => {
+                let resolved = self.resolver.resolve(&collection, &key, local, &remote);
+                let mut clock = local.clock.clone();
+                clock.merge(&remote.clock);
+                let latest = if local.recency() >= remote.recency() {
+                    local
+                } else {
+                    &remote
+                };
+                let merged = SyncEntry {
+                    value: resolved.clone(),
+                    clock,
+                    device: latest.device.clone(),
+                    updated_at: latest.updated_at,
+                };
+                outcome.conflicts.push(SyncConflict {
+                    collection,
+                    key: key.clone(),
+                    local: local.value.clone(),
+                    remote: remote.value.clone(),
+                    resolved,
+                });
+                entries.insert(key, merged);
+                outcome.applied += 1;
+            }
+        }
+    }
+
+    fn check_peer(&self, did: &str) -> Result<(), ATMError> {
+        if self.peers.contains(did) {
+            Ok(())
+        } else {
+            Err(ATMError::ProfileSyncError(format!(
+                "{did} is not a paired device"
+            )))
+        }
+    }
+}
+
+/// Access profile sync helpers through [`ATM::profile_sync`].
+pub struct ProfileSyncOps<'a> {
+    pub(crate) atm: &'a ATM,
+}
+
+impl ProfileSyncOps<'_> {
+    /// Authcrypt `message` from the profile's DID to its single recipient and
+    /// send it. Sync messages carry the profile's private state, and
+    /// [`ProfileSync::handle`] authenticates the sender from the envelope.
+    pub async fn send(
+        &self,
+        profile: &Arc<ATMProfile>,
+        message: &Message,
+    ) -> Result<SendMessageResponse, ATMError> {
+        let (profile_did, _) = profile.dids()?;
+        if message.from.as_deref() != Some(profile_did) {
+            return Err(ATMError::ProfileSyncError(
+                "message is not from this profile's DID".into(),
+            ));
+        }
+        let to = match message.to.as_deref() {
+            Some([to]) => to,
+            _ => {
+                return Err(ATMError::ProfileSyncError(
+                    "profile sync messages have exactly one recipient".into(),
+                ));
+            }
+        };
+
+        let (packed, _) = self
+            .atm
+            .inner
+            .pack_encrypted(message, to, Some(profile_did))
+            .await
+            .map_err(|e| ATMError::MsgSendError(format!("Error packing message: {e}")))?;
+
+        self.atm
+            .send_message(profile, &packed, &message.id, false, true)
+            .await
+    }
+}
+
+fn build(typ: &str, from: &str, to: &str, thid: &str, body: Value) -> Message {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    Message::build(Uuid::new_v4().to_string(), typ.to_owned(), body)
+        .from(from.to_owned())
+        .to(to.to_owned())
+        .thid(thid.to_owned())
+        .created_time(now)
+        .finalize()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// The error for a message whose envelope doesn't prove its `from`.
+fn unauthenticated() -> ATMError {
+    ATMError::ProfileSyncError("message is not authcrypted by its sender".into())
+}
+
+fn parse_body<T: DeserializeOwned>(message: &Message) -> Result<T, ATMError> {
+    serde_json::from_value(message.body.clone())
+        .map_err(|e| ATMError::ProfileSyncError(format!("invalid {} body: {e}", message.typ)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHONE: &str = "did:example:phone";
+    const LAPTOP: &str = "did:example:laptop";
+
+    fn pair() -> (ProfileSync, ProfileSync) {
+        (
+            ProfileSync::new(PHONE).with_peer(LAPTOP),
+            ProfileSync::new(LAPTOP).with_peer(PHONE),
+        )
+    }
+
+    fn deliver(to: &mut ProfileSync, message: &Message) -> SyncOutcome {
+        to.handle(message, &UnpackMetadata::authcrypted(message))
+            .unwrap()
+    }
+
+    /// Run a full exchange started by `a`, returning the outcomes on each side.
+    fn sync(a: &mut ProfileSync, b: &mut ProfileSync) -> (SyncOutcome, SyncOutcome) {
+        let request = a.request(b.device()).unwrap();
+        let update = deliver(b, &request).reply.unwrap();
+        let at_a = deliver(a, &update);
+        let at_b = deliver(b, at_a.reply.as_ref().unwrap());
+        assert!(at_b.reply.is_none());
+        (at_a, at_b)
+    }
+
+    #[test]
+    fn clocks_order_and_merge() {
+        let mut a = VectorClock::new();
+        let mut b = VectorClock::new();
+        assert_eq!(a.compare(&b), Some(Ordering::Equal));
+
+        a.increment(PHONE);
+        assert_eq!(a.compare(&b), Some(Ordering::Greater));
+        assert_eq!(b.compare(&a), Some(Ordering::Less));
+
+        b.increment(LAPTOP);
+        assert_eq!(a.compare(&b), None);
+
+        a.merge(&b);
+        assert_eq!((a.get(PHONE), a.get(LAPTOP)), (1, 1));
+        assert!(b.dominated_by(&a));
+    }
+
+    #[test]
+    fn exchange_brings_both_devices_up_to_date() {
+        let (mut phone, mut laptop) = pair();
+        phone.put(
+            collections::CONTACTS,
+            "did:example:alice",
+            json!({"name": "Alice"}),
+        );
+        laptop.put(
+            collections::READ_STATE,
+            "chat-1",
+            json!({"last_read": "m-9"}),
+        );
+        laptop.put(
+            collections::CONTACTS,
+            "did:example:bob",
+            json!({"name": "Bob"}),
+        );
+
+        let (at_phone, at_laptop) = sync(&mut phone, &mut laptop);
+        assert_eq!((at_phone.applied, at_laptop.applied), (2, 1));
+        assert!(at_phone.conflicts.is_empty() && at_laptop.conflicts.is_empty());
+        assert_eq!(phone.state(), laptop.state());
+        assert_eq!(
+            phone.get(collections::READ_STATE, "chat-1"),
+            Some(&json!({"last_read": "m-9"}))
+        );
+
+        // Nothing new: the next exchange carries no entries
+        let (at_phone, at_laptop) = sync(&mut laptop, &mut phone);
+        assert_eq!((at_phone.applied, at_laptop.applied), (0, 0));
+    }
+
+    #[test]
+    fn later_writes_and_removals_replace_earlier_ones() {
+        let (mut phone, mut laptop) = pair();
+        phone.put(
+            collections::CONTACTS,
+            "did:example:alice",
+            json!({"name": "Alice"}),
+        );
+        sync(&mut phone, &mut laptop);
+
+        laptop.put(
+            collections::CONTACTS,
+            "did:example:alice",
+            json!({"name": "Alice B"}),
+        );
+        sync(&mut phone, &mut laptop);
+        assert_eq!(
+            phone.get(collections::CONTACTS, "did:example:alice"),
+            Some(&json!({"name": "Alice B"}))
+        );
+
+        assert!(phone.remove(collections::CONTACTS, "did:example:alice"));
+        assert!(!phone.remove(collections::CONTACTS, "did:example:alice"));
+        let (_, at_laptop) = sync(&mut laptop, &mut phone);
+        assert!(at_laptop.conflicts.is_empty());
+        assert_eq!(laptop.entries(collections::CONTACTS).count(), 0);
+    }
+
+    #[test]
+    fn concurrent_writes_resolve_the_same_way_on_both_devices() {
+        let (mut phone, mut laptop) = pair();
+        phone.put(
+            collections::CONTACTS,
+            "did:example:alice",
+            json!({"name": "Al"}),
+        );
+        laptop.put(
+            collections::CONTACTS,
+            "did:example:alice",
+            json!({"name": "Alice"}),
+        );
+
+        let (at_phone, at_laptop) = sync(&mut phone, &mut laptop);
+        assert_eq!(at_phone.conflicts.len(), 1);
+        assert!(at_laptop.conflicts.is_empty());
+        assert_eq!(phone.state(), laptop.state());
+
+        // A third device that saw neither write settles on the same entry
+        let tablet = "did:example:tablet";
+        let mut tablet_store = ProfileSync::new(tablet).with_peer(PHONE);
+        phone = ProfileSync::from_state(PHONE, phone.state().clone()).with_peer(tablet);
+        sync(&mut tablet_store, &mut phone);
+        assert_eq!(tablet_store.state().collections, laptop.state().collections);
+    }
+
+    #[test]
+    fn custom_resolver_decides_conflicts() {
+        struct KeepBoth;
+        impl ConflictResolver for KeepBoth {
+            fn resolve(&self, _: &str, _: &str, a: &SyncEntry, b: &SyncEntry) -> Option<Value> {
+                let mut names = [a.value.clone(), b.value.clone()].map(|v| v.unwrap_or_default());
+                names.sort_by_key(|v| v.to_string());
+                Some(json!(names))
+            }
+        }
+
+        let mut phone = ProfileSync::new(PHONE)
+            .with_peer(LAPTOP)
+            .with_resolver(KeepBoth);
+        let mut laptop = ProfileSync::new(LAPTOP)
+            .with_peer(PHONE)
+            .with_resolver(KeepBoth);
+        phone.put(collections::CREDENTIALS, "vc-1", json!("a"));
+        laptop.put(collections::CREDENTIALS, "vc-1", json!("b"));
+
+        sync(&mut laptop, &mut phone);
+        assert_eq!(
+            phone.get(collections::CREDENTIALS, "vc-1"),
+            Some(&json!(["a", "b"]))
+        );
+        assert_eq!(phone.state(), laptop.state());
+    }
+
+    #[test]
+    fn messages_from_unpaired_devices_are_rejected() {
+        let (mut phone, _) = pair();
+        let stranger = ProfileSync::new("did:example:stranger").with_peer(PHONE);
+        let request = stranger.request(PHONE).unwrap();
+        assert!(matches!(
+            phone.handle(&request, &UnpackMetadata::authcrypted(&request)),
+            Err(ATMError::ProfileSyncError(_))
+        ));
+        assert!(phone.request("did:example:stranger").is_err());
+    }
+
+    #[test]
+    fn messages_that_do_not_prove_their_sender_are_rejected() {
+        let (mut phone, mut laptop) = pair();
+        laptop.put(
+            collections::CONTACTS,
+            "did:example:mallory",
+            json!({"name": "Trust me"}),
+        );
+        let update = laptop.update(PHONE, "thread-1", &VectorClock::new(), false);
+
+        let plaintext = UnpackMetadata::default();
+        let anoncrypt = UnpackMetadata {
+            encrypted: true,
+            anonymous_sender: true,
+            ..Default::default()
+        };
+        let other_key = UnpackMetadata {
+            encrypted_from_kid: Some("did:example:mallory#key-2".into()),
+            ..UnpackMetadata::authcrypted(&update)
+        };
+        for meta in [plaintext, anoncrypt, other_key] {
+            assert!(matches!(
+                phone.handle(&update, &meta),
+                Err(ATMError::ProfileSyncError(_))
+            ));
+        }
+        assert_eq!(phone.entries(collections::CONTACTS).count(), 0);
+    }
+}
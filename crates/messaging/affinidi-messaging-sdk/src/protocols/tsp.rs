@@ -26,6 +26,7 @@ use std::sync::Arc;
 use affinidi_did_common::DocumentExt;
 use affinidi_secrets_resolver::SecretsResolver;
 use affinidi_secrets_resolver::secrets::KeyType;
+use affinidi_secrets_resolver::usage::KeyPurpose;
 use affinidi_tsp::message::control::{ControlMessage, ControlType};
 use affinidi_tsp::message::direct;
 use affinidi_tsp::relationship::{InvalidTransition, RelationshipEvent, RelationshipState};
@@ -1493,13 +1494,19 @@ impl TspOps<'_> {
     /// First verification-method `kid` whose secret is of `want` type, as a raw
     /// 32-byte private key.
     async fn first_private_key(&self, kids: Vec<&str>, want: KeyType) -> Option<[u8; 32]> {
+        let purpose = if want == KeyType::X25519 {
+            KeyPurpose::KeyAgreement
+        } else {
+            KeyPurpose::Signing
+        };
         for kid in kids {
-            if let Some(secret) = self
+            // Keys a strict key usage policy refuses are skipped
+            if let Ok(Some(secret)) = self
                 .atm
                 .inner
                 .tdk_common
                 .secrets_resolver()
-                .get_secret(kid)
+                .get_secret_for(kid, purpose)
                 .await
                 && secret.get_key_type() == want
                 && let Ok(bytes) = <[u8; 32]>::try_from(secret.get_private_bytes())
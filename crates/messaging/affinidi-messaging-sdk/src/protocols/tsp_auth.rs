@@ -36,7 +36,8 @@
 //! use affinidi_messaging_sdk::TspAuthHandler;
 //!
 //! // Build the custom-handler bundle the TDK takes at construction.
-//! // `secrets` is the resolver the TDK uses (`ThreadedSecretsResolver`).
+//! // `secrets` is the resolver the TDK uses (`ThreadedSecretsResolver`, or the
+//! // `TDKSecretsResolver` from `TDKSharedState::secrets_resolver`).
 //! let handlers = CustomAuthHandlers::default()
 //!     .with_auth_handler(Arc::new(TspAuthHandler::new(secrets)));
 //!
@@ -73,13 +74,15 @@ use serde_json::json;
 /// future) requires it. There is no stable way to box a generic
 /// `async-fn-in-trait` future as `Send` (that needs return-type-notation), so we
 /// implement this `#[async_trait]` (Send-boxing) adapter for the **concrete**
-/// [`ThreadedSecretsResolver`] — the resolver the TDK uses. Its `get_secret`
-/// future is `Send`, so the boxed body type-checks. (`SimpleSecretsResolver` is
+/// resolvers the TDK uses: [`ThreadedSecretsResolver`] and
+/// [`TDKSecretsResolver`], which may also read from the OS keychain. Their
+/// `get_secret` futures are `Send`, so the boxed body type-checks. (`SimpleSecretsResolver` is
 /// `RefCell`-backed and not `Sync`, so it cannot satisfy the `Send`-future
 /// contract and is intentionally excluded.) The trait is sealed; extend the
 /// concrete impls below if another `Send`-future resolver is added.
 ///
 /// [`ThreadedSecretsResolver`]: affinidi_secrets_resolver::ThreadedSecretsResolver
+/// [`TDKSecretsResolver`]: affinidi_tdk_common::secrets::TDKSecretsResolver
 #[async_trait]
 pub trait SendSecrets: sealed::Sealed + Send + Sync {
     /// Look up a secret by `kid`, returning a `Send` future.
@@ -90,6 +93,7 @@ mod sealed {
     /// Seals [`super::SendSecrets`]: only this crate may add implementors.
     pub trait Sealed {}
     impl Sealed for affinidi_secrets_resolver::ThreadedSecretsResolver {}
+    impl Sealed for affinidi_tdk_common::secrets::TDKSecretsResolver {}
 }
 
 #[async_trait]
@@ -99,6 +103,13 @@ impl SendSecrets for affinidi_secrets_resolver::ThreadedSecretsResolver {
     }
 }
 
+#[async_trait]
+impl SendSecrets for affinidi_tdk_common::secrets::TDKSecretsResolver {
+    async fn get(&self, kid: &str) -> Option<Secret> {
+        self.get_secret(kid).await
+    }
+}
+
 /// A pure-TSP [`CustomAuthHandler`]: authenticates a TSP-only client by signing
 /// the mediator's challenge with its VID's Ed25519 key.
 ///
@@ -115,10 +126,10 @@ pub struct TspAuthHandler {
 impl TspAuthHandler {
     /// Build a handler that signs challenges with keys from `secrets`.
     ///
-    /// `secrets` is the [`ThreadedSecretsResolver`] the TDK uses; it produces the
-    /// `Send` futures the [`CustomAuthHandler`] contract requires. See
-    /// [`SendSecrets`] for why the bound is concrete rather than a blanket
-    /// `S: SecretsResolver`.
+    /// `secrets` is the resolver the TDK uses (a [`ThreadedSecretsResolver`] or
+    /// the state's `TDKSecretsResolver`); it produces the `Send` futures the
+    /// [`CustomAuthHandler`] contract requires. See [`SendSecrets`] for why the
+    /// bound is concrete rather than a blanket `S: SecretsResolver`.
     ///
     /// [`ThreadedSecretsResolver`]: affinidi_secrets_resolver::ThreadedSecretsResolver
     pub fn new<S: SendSecrets + 'static>(secrets: S) -> Self {
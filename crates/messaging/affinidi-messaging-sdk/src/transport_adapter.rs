@@ -297,16 +297,8 @@ fn to_inbound(message: Message, meta: &UnpackMetadata) -> Option<Inbound> {
 /// header names. Requiring `from == DID(encrypted_from_kid)` rejects a message
 /// authcrypted by one key but claiming another party's `from`.
 fn authenticated_sender(message: &Message, meta: &UnpackMetadata) -> Option<String> {
-    if !meta.authenticated || meta.anonymous_sender {
-        return None;
-    }
-    let kid = meta.encrypted_from_kid.as_deref()?;
-    // The DID that owns the authcrypt key (strip the `#key` fragment).
-    let key_did = kid.split_once('#').map(|(did, _)| did).unwrap_or(kid);
-    match message.from.as_deref() {
-        Some(from) if from == key_did => Some(from.to_string()),
-        _ => None,
-    }
+    meta.authenticated_sender(message.from.as_deref())
+        .map(str::to_string)
 }
 
 #[cfg(test)]
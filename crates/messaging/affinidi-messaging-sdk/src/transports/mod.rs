@@ -5,6 +5,8 @@ use crate::{
     profiles::ATMProfile,
 };
 use affinidi_messaging_didcomm::message::Message;
+use affinidi_task_utils::CallLimits;
+use affinidi_tdk_common::{audit::AuditEvent, capabilities::Capability};
 use serde_json::Value;
 use sha256::digest;
 use std::{sync::Arc, time::Duration};
@@ -114,6 +116,11 @@ impl ATM {
                 "Profile ({}): WebSocket message written to socket",
                 profile.inner.alias
             );
+            self.get_tdk()
+                .usage_tracker()
+                .record_message_sent(&profile.inner.did, message.len());
+            self.audit_message_sent(profile, &mediator.did, message, msg_id)
+                .await;
 
             if wait_for_response {
                 let response = self
@@ -138,6 +145,8 @@ impl ATM {
             debug!("Profile ({}): Sending message to API", profile.inner.alias);
             // Send HTTP message
             let a = self.send_didcomm_message(profile, message, true).await?;
+            self.audit_message_sent(profile, &mediator.did, message, msg_id)
+                .await;
 
             debug!("Response: {:#?}", a);
 
@@ -173,6 +182,28 @@ impl ATM {
         }
     }
 
+    /// [`send_message`](Self::send_message) under caller-controlled
+    /// [`CallLimits`].
+    ///
+    /// When the timeout elapses or the token fires first, the send is dropped
+    /// and [`ATMError::Interrupted`] returned: an HTTP request in flight is
+    /// aborted, and a pending wait for the mediator's response is withdrawn
+    /// from the WebSocket task. A frame the WebSocket task has already taken
+    /// is still written — there is no recalling it once queued.
+    pub async fn send_message_with_limits(
+        &self,
+        profile: &Arc<ATMProfile>,
+        message: &str,
+        msg_id: &str,
+        wait_for_response: bool,
+        auto_delete: bool,
+        limits: &CallLimits,
+    ) -> Result<SendMessageResponse, ATMError> {
+        limits
+            .run(self.send_message(profile, message, msg_id, wait_for_response, auto_delete))
+            .await?
+    }
+
     /// Takes a packed message, wraps it in a forward envelope and sends it to the target DID
     /// - profile: The profile to send the message from
     /// - anonymous: Whether to send the message anonymously
@@ -227,6 +258,58 @@ impl ATM {
         .await
     }
 
+    /// [`forward_and_send_message`](Self::forward_and_send_message) under
+    /// caller-controlled [`CallLimits`]; see
+    /// [`send_message_with_limits`](Self::send_message_with_limits).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn forward_and_send_message_with_limits(
+        &self,
+        profile: &Arc<ATMProfile>,
+        anonymous: bool,
+        message: &str,
+        msg_id: Option<&str>,
+        target_did: &str,
+        next_did: &str,
+        expires_time: Option<u64>,
+        delay_milli: Option<i64>,
+        wait_for_response: bool,
+        limits: &CallLimits,
+    ) -> Result<SendMessageResponse, ATMError> {
+        limits
+            .run(self.forward_and_send_message(
+                profile,
+                anonymous,
+                message,
+                msg_id,
+                target_did,
+                next_did,
+                expires_time,
+                delay_milli,
+                wait_for_response,
+            ))
+            .await?
+    }
+
+    /// Record a message handed to the mediator in the TDK audit log, if one is
+    /// configured. The DIDComm type is sealed inside the envelope, so the
+    /// envelope's media type is recorded instead.
+    async fn audit_message_sent(
+        &self,
+        profile: &ATMProfile,
+        mediator_did: &str,
+        message: &str,
+        msg_id: &str,
+    ) {
+        self.get_tdk()
+            .record_audit_event(AuditEvent::MessageSent {
+                message_id: msg_id.to_string(),
+                message_type: envelope_media_type(message).to_string(),
+                from: Some(profile.inner.did.clone()),
+                to: vec![mediator_did.to_string()],
+            })
+            .await;
+    }
+
     /// send_didcomm_message
     /// - msg: Packed DIDComm message that we want to send
     /// - return_response: Whether to return the response from the API
@@ -254,6 +337,12 @@ impl ATM {
             .authentication()
             .authenticate(profile_did.to_string(), mediator_did.to_string(), 3, None)
             .await?;
+        self.get_tdk().capabilities().record(
+            profile_did,
+            Capability::MediatorAccount {
+                mediator: mediator_did.to_string(),
+            },
+        );
 
         let msg = message.to_owned();
 
@@ -282,6 +371,9 @@ impl ATM {
                 "API returned an error: status({status}), body({body})"
             )));
         }
+        self.get_tdk()
+            .usage_tracker()
+            .record_message_sent(&profile.inner.did, message.len());
         debug!("body =\n{}", body);
         let http_response: Value = if return_response {
             serde_json::from_str(&body)
@@ -293,3 +385,16 @@ impl ATM {
         Ok(SendMessageResponse::RestAPI(http_response))
     }
 }
+
+/// DIDComm media type of a packed message, told apart the same way
+/// [`ATM::unpack`] does.
+fn envelope_media_type(message: &str) -> &'static str {
+    let value: Value = serde_json::from_str(message).unwrap_or_default();
+    if value.get("ciphertext").is_some() {
+        "application/didcomm-encrypted+json"
+    } else if value.get("signatures").is_some() {
+        "application/didcomm-signed+json"
+    } else {
+        "application/didcomm-plain+json"
+    }
+}
@@ -5,15 +5,16 @@
 use super::{WebSocketResponses, ws_cache::MessageCache};
 use crate::{ATM, SharedState, errors::ATMError, profiles::ATMProfile};
 use affinidi_messaging_core::ConnState;
+use affinidi_tdk_common::capabilities::Capability;
 use ahash::{HashMap, HashMapExt};
 use futures_util::{SinkExt, StreamExt};
 use rand::RngExt;
-use std::{collections::VecDeque, sync::Arc, time::Duration};
+use std::{collections::VecDeque, convert::Infallible, sync::Arc, time::Duration};
 use tokio::{
     net::TcpStream,
     select,
     sync::{
-        broadcast,
+        Mutex, broadcast,
         mpsc::{self, Receiver, Sender},
         oneshot, watch,
     },
@@ -152,7 +153,7 @@ pub(crate) enum WebSocketCommands {
     /// Get a specific message from the cache - will only respond if the message is found
     GetMessage(String, oneshot::Sender<WebSocketResponses>),
 
-    /// If SDK timesout, then cancel the GetMessage request
+    /// If the SDK times out or its caller gives up, cancel the GetMessage request
     CancelGetMessage(String),
 }
 
@@ -168,38 +169,18 @@ impl WebSocketTransport {
         Sender<WebSocketCommands>,
         watch::Receiver<ConnState>,
     ) {
-        let (task_tx, mut task_rx) = mpsc::channel::<WebSocketCommands>(32);
-        let (conn_state_tx, conn_state_rx) = watch::channel(ConnState::Connecting);
-        let handle = tokio::spawn(async move {
-            let mut websocket = WebSocketTransport {
-                profile: profile.clone(),
-                shared: shared.clone(),
-                web_socket: None,
-                connect_delay_timer: None,
-                connect_delay: 0,
-                awaiting_pong: false,
-                connected_at: None,
-                access_expires_at: None,
-                inbound_cache: MessageCache {
-                    fetch_cache_limit_count: shared.config.fetch_cache_limit_count,
-                    fetch_cache_limit_bytes: shared.config.fetch_cache_limit_bytes,
-                    ..Default::default()
-                },
-                direct_channel,
-                next_requests: HashMap::new(),
-                next_requests_list: VecDeque::new(),
-                packed_cache: VecDeque::new(),
-                packed_cache_bytes: 0,
-                packed_cache_full: false,
-                skip_toggle_live_delivery: false,
-                skip_unpack_messages: false,
-                conn_state_tx,
-            };
-            websocket.run(&mut task_rx).await;
-        });
-        (handle, task_tx, conn_state_rx)
+        Self::start_with_options(profile, shared, direct_channel, false, false).await
     }
 
+    /// As [`start`](Self::start), optionally skipping the live-delivery toggle
+    /// and message unpacking.
+    ///
+    /// The transport runs under the TDK's
+    /// [`TaskSupervisor`](affinidi_tdk_common::task_utils::TaskSupervisor): a
+    /// panic restarts it with backoff, reusing the command channel so callers
+    /// are unaffected. It stops for good on [`WebSocketCommands::Stop`], once
+    /// every command sender is dropped, or when the TDK shuts down; the
+    /// returned handle completes then.
     pub(crate) async fn start_with_options(
         profile: Arc<ATMProfile>,
         shared: Arc<SharedState>,
@@ -211,35 +192,54 @@ impl WebSocketTransport {
         Sender<WebSocketCommands>,
         watch::Receiver<ConnState>,
     ) {
-        let (task_tx, mut task_rx) = mpsc::channel::<WebSocketCommands>(32);
+        let (task_tx, task_rx) = mpsc::channel::<WebSocketCommands>(32);
         let (conn_state_tx, conn_state_rx) = watch::channel(ConnState::Connecting);
-        let handle = tokio::spawn(async move {
-            let mut websocket = WebSocketTransport {
-                profile: profile.clone(),
-                shared: shared.clone(),
-                web_socket: None,
-                connect_delay_timer: None,
-                connect_delay: 0,
-                awaiting_pong: false,
-                connected_at: None,
-                access_expires_at: None,
-                inbound_cache: MessageCache {
-                    fetch_cache_limit_count: shared.config.fetch_cache_limit_count,
-                    fetch_cache_limit_bytes: shared.config.fetch_cache_limit_bytes,
-                    ..Default::default()
-                },
-                direct_channel,
-                next_requests: HashMap::new(),
-                next_requests_list: VecDeque::new(),
-                packed_cache: VecDeque::new(),
-                packed_cache_bytes: 0,
-                packed_cache_full: false,
-                skip_toggle_live_delivery,
-                skip_unpack_messages,
-                conn_state_tx,
-            };
-            websocket.run(&mut task_rx).await;
-        });
+        let task_rx = Arc::new(Mutex::new(task_rx));
+
+        let supervisor = shared.tdk_common.task_supervisor();
+        let shutdown = supervisor.shutdown_token().child_token();
+        let name = format!("websocket:{}", profile.inner.alias);
+        let handle = supervisor
+            .with_shutdown(shutdown.clone())
+            .spawn(name, false, move || {
+                let profile = profile.clone();
+                let shared = shared.clone();
+                let direct_channel = direct_channel.clone();
+                let conn_state_tx = conn_state_tx.clone();
+                let task_rx = task_rx.clone();
+                let shutdown = shutdown.clone();
+                async move {
+                    let mut websocket = WebSocketTransport {
+                        profile,
+                        shared: shared.clone(),
+                        web_socket: None,
+                        connect_delay_timer: None,
+                        connect_delay: 0,
+                        awaiting_pong: false,
+                        connected_at: None,
+                        access_expires_at: None,
+                        inbound_cache: MessageCache {
+                            fetch_cache_limit_count: shared.config.fetch_cache_limit_count,
+                            fetch_cache_limit_bytes: shared.config.fetch_cache_limit_bytes,
+                            ..Default::default()
+                        },
+                        direct_channel,
+                        next_requests: HashMap::new(),
+                        next_requests_list: VecDeque::new(),
+                        packed_cache: VecDeque::new(),
+                        packed_cache_bytes: 0,
+                        packed_cache_full: false,
+                        skip_toggle_live_delivery,
+                        skip_unpack_messages,
+                        conn_state_tx,
+                    };
+                    websocket.run(&mut *task_rx.lock().await).await;
+                    // `run` only returns once the transport is done with: stop
+                    // rather than let the supervisor restart it.
+                    shutdown.cancel();
+                    Ok::<(), Infallible>(())
+                }
+            });
         (handle, task_tx, conn_state_rx)
     }
 
@@ -421,8 +421,10 @@ impl WebSocketTransport {
                                     debug!("Message ({}) not found in cache, added to wanted list", id);
                                 }
                             }
-                            Some(WebSocketCommands::CancelGetMessage(_id)) => {
-                                debug!("Get message cancelled");
+                            Some(WebSocketCommands::CancelGetMessage(id)) => {
+                                if self.inbound_cache.cancel_wanted(&id) {
+                                    debug!("Get message ({}) cancelled", id);
+                                }
                             }
                             None => break,
                         }
@@ -591,6 +593,9 @@ impl WebSocketTransport {
 
     async fn process_inbound_didcomm_message(&mut self, atm: &ATM, message: String) {
         debug!("Received text message ({})", message);
+        atm.get_tdk()
+            .usage_tracker()
+            .record_message_received(&self.profile.inner.did, message.len());
 
         // A TSP message can't be DIDComm-unpacked. The live-stream frame is
         // self-describing (CESR qb64), so sniff it and deliver TSP frames packed
@@ -864,6 +869,12 @@ impl WebSocketTransport {
             .authentication()
             .authenticate(profile_did.to_string(), mediator_did.to_string(), 3, None)
             .await?;
+        self.shared.tdk_common.capabilities().record(
+            profile_did,
+            Capability::MediatorAccount {
+                mediator: mediator_did.to_string(),
+            },
+        );
         // Remember when this token expires so we can refresh+reconnect before
         // the mediator force-closes the socket at expiry.
         self.access_expires_at = Some(tokens.access_expires_at);
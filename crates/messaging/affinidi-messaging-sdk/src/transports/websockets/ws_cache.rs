@@ -181,6 +181,13 @@ impl MessageCache {
         self.cache_full
     }
 
+    /// Withdraw a `GetMessage` waiter whose caller gave up, so a matching
+    /// message that arrives later stays in the cache for the next reader
+    /// instead of being handed to a closed channel.
+    pub(crate) fn cancel_wanted(&mut self, msg_id: &str) -> bool {
+        self.wanted_list.remove(msg_id).is_some()
+    }
+
     /// Remove and return every pending "wanted" sender.
     ///
     /// Used when the websocket connection drops: each in-flight `GetMessage`
@@ -220,6 +227,19 @@ mod tests {
         assert!(matches!(rx_b.await, Ok(WebSocketResponses::Disconnected)));
     }
 
+    #[test]
+    fn cancel_wanted_withdraws_only_that_waiter() {
+        let mut cache = MessageCache::default();
+        let (tx_a, _rx_a) = oneshot::channel();
+        let (tx_b, _rx_b) = oneshot::channel();
+        cache.wanted_list.insert("msg-a".to_string(), tx_a);
+        cache.wanted_list.insert("msg-b".to_string(), tx_b);
+
+        assert!(cache.cancel_wanted("msg-a"));
+        assert!(!cache.cancel_wanted("msg-a"));
+        assert!(cache.wanted_list.contains_key("msg-b"));
+    }
+
     #[test]
     fn drain_wanted_on_empty_cache_is_noop() {
         let mut cache = MessageCache::default();
@@ -2,7 +2,7 @@
  * State for managing the list of chats
  */
 
-use crate::state_store::chat_message::ChatMessage;
+use crate::state_store::{at_rest::SealedChatMessage, chat_message::ChatMessage};
 use affinidi_messaging_sdk::profiles::ATMProfile;
 use affinidi_tdk::common::profiles::TDKProfile;
 use ahash::AHashMap as HashMap;
@@ -78,12 +78,28 @@ impl fmt::Display for ChatStatus {
 fn _true() -> bool {
     true
 }
+
+fn _messages() -> CircularQueue<ChatMessage> {
+    CircularQueue::with_capacity(50)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chat {
     pub name: String,
     pub status: ChatStatus,
     pub description: String,
+    /// Recent chat history. Only saved in the clear if the chat history key
+    /// is missing; otherwise each message is added to `sealed_messages`
+    #[serde(default = "_messages", skip_serializing_if = "CircularQueue::is_empty")]
     pub messages: CircularQueue<ChatMessage>,
+    /// The chat's history, encrypted, oldest first. Each message keeps the key
+    /// epoch it was first sealed under
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sealed_messages: Vec<SealedChatMessage>,
+    /// Epoch of the key the history is sealed under. Bumped when the chat's
+    /// membership changes
+    #[serde(default)]
+    pub key_epoch: u32,
     pub our_profile: TDKProfile,
     pub remote_did: Option<String>,
     pub has_unread: bool,
@@ -94,6 +110,17 @@ pub struct Chat {
     pub hidden: Option<String>, // This is used to store anything that we don't need to show. used in OOB Acceptance flow
 }
 
+impl Chat {
+    /// Sets the remote party of the chat. A different remote DID is a
+    /// membership change, so the chat moves to a new key epoch.
+    pub fn set_remote_did(&mut self, remote_did: String) {
+        if self.remote_did.as_ref() != Some(&remote_did) {
+            self.remote_did = Some(remote_did);
+            self.key_epoch += 1;
+        }
+    }
+}
+
 impl PartialEq for Chat {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
@@ -114,7 +141,9 @@ impl Default for Chat {
             name: String::new(),
             status: ChatStatus::AwaitingInvitationAcceptance,
             description: String::new(),
-            messages: CircularQueue::with_capacity(50),
+            messages: _messages(),
+            sealed_messages: Vec::new(),
+            key_epoch: 0,
             our_profile: TDKProfile::default(),
             remote_did: None,
             has_unread: false,
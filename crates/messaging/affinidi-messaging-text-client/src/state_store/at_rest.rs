@@ -0,0 +1,377 @@
+/*!
+ * Encryption of chat history at rest.
+ *
+ * Each chat's messages are saved to the state file sealed with AES-256-GCM
+ * under a key derived (HKDF-SHA256) from the [`HistoryKey`], the chat's
+ * conversation id (our profile DID, which is unique per chat) and the chat's
+ * key epoch. The history key is random and lives in the OS keyring, never in
+ * the state file, so reading the file (which holds the profiles' private
+ * keys) is not enough to read the history.
+ *
+ * The epoch is bumped when the chat's membership changes. A message is sealed
+ * once, under the epoch current when it is first saved, and keeps that seal:
+ * messages from before a membership change stay under the old epoch's key.
+ *
+ * The first line of a message is its subject line. Its words are stored as a
+ * blind index (truncated HMAC-SHA256 under a second derived key), so a search
+ * can find matching messages without decrypting the rest.
+ */
+
+use super::chat_message::{ChatMessage, ChatMessageType};
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, Payload, rand_core::RngCore},
+};
+use affinidi_tdk::common::secrets::init_keyring;
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use chrono::{DateTime, Local, Utc};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use keyring_core::{Entry, error::Error as KeyringError};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{fmt, io};
+use zeroize::Zeroizing;
+
+/// OS keyring entry (service, account) holding the [`HistoryKey`].
+const KEYRING_SERVICE: &str = "affinidi-text-client";
+const KEYRING_ACCOUNT: &str = "chat-history";
+
+const MESSAGE_KEY_INFO: &str = "affinidi-text-client/chat-messages/v1";
+const INDEX_KEY_INFO: &str = "affinidi-text-client/chat-subjects/v1";
+
+/// Bytes of HMAC output kept per indexed word: enough to make false matches
+/// negligible while keeping the index small.
+const BLIND_INDEX_BYTES: usize = 8;
+
+/// A [`ChatMessage`] as saved in the state file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SealedChatMessage {
+    pub _type: ChatMessageType,
+    pub timestamp: DateTime<Local>,
+    /// Key epoch the message was sealed under
+    pub epoch: u32,
+    pub nonce: String,
+    pub ciphertext: String,
+    /// Blind index of the words in the subject line
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subject_index: Vec<String>,
+}
+
+/// The fields of a message bound to its ciphertext as associated data, so a
+/// sealed body can't be moved to another chat or epoch, or given another type
+/// or timestamp. The timestamp is bound in UTC: the local offset it is read
+/// back with depends on the host's timezone.
+#[derive(Serialize)]
+struct AssociatedData<'a> {
+    conversation: &'a str,
+    epoch: u32,
+    _type: &'a ChatMessageType,
+    timestamp: DateTime<Utc>,
+}
+
+/// The root key every chat's keys are derived from.
+pub struct HistoryKey(Zeroizing<[u8; 32]>);
+
+impl HistoryKey {
+    /// Reads the history key from the OS keyring, creating it on first use.
+    pub fn load_or_create() -> io::Result<Self> {
+        init_keyring().map_err(io::Error::other)?;
+        let entry = Entry::new(KEYRING_SERVICE, KEYRING_ACCOUNT)
+            .map_err(|e| io::Error::other(format!("Couldn't open the keyring: {e}")))?;
+
+        match entry.get_secret() {
+            Ok(bytes) => {
+                let bytes = Zeroizing::new(bytes);
+                let key: [u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Chat history key in the keyring must be 32 bytes",
+                    )
+                })?;
+                Ok(Self(Zeroizing::new(key)))
+            }
+            Err(KeyringError::NoEntry) => {
+                let mut key = Zeroizing::new([0u8; 32]);
+                OsRng.fill_bytes(key.as_mut_slice());
+                entry.set_secret(key.as_slice()).map_err(|e| {
+                    io::Error::other(format!("Couldn't store the chat history key: {e}"))
+                })?;
+                Ok(Self(key))
+            }
+            Err(e) => Err(io::Error::other(format!(
+                "Couldn't read the chat history key: {e}"
+            ))),
+        }
+    }
+}
+
+impl fmt::Debug for HistoryKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HistoryKey(..)")
+    }
+}
+
+/// Keys for one chat at one key epoch.
+pub struct ConversationKeys {
+    conversation: String,
+    epoch: u32,
+    message: Zeroizing<[u8; 32]>,
+    index: Zeroizing<[u8; 32]>,
+}
+
+impl ConversationKeys {
+    /// Derives the keys of `conversation` at `epoch` from the history key.
+    pub fn derive(history: &HistoryKey, conversation: &str, epoch: u32) -> io::Result<Self> {
+        let hkdf = Hkdf::<Sha256>::new(Some(conversation.as_bytes()), history.0.as_slice());
+        let mut message = Zeroizing::new([0u8; 32]);
+        let mut index = Zeroizing::new([0u8; 32]);
+        hkdf.expand(
+            format!("{MESSAGE_KEY_INFO}/{epoch}").as_bytes(),
+            message.as_mut_slice(),
+        )
+        .and_then(|_| {
+            hkdf.expand(
+                format!("{INDEX_KEY_INFO}/{epoch}").as_bytes(),
+                index.as_mut_slice(),
+            )
+        })
+        .map_err(|e| io::Error::other(format!("Couldn't derive chat keys: {e}")))?;
+
+        Ok(Self {
+            conversation: conversation.to_string(),
+            epoch,
+            message,
+            index,
+        })
+    }
+
+    /// Encrypts `message` and indexes its subject line.
+    pub fn seal(&self, message: &ChatMessage) -> io::Result<SealedChatMessage> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: message.message.as_bytes(),
+                    aad: &self.aad(&message._type, &message.timestamp)?,
+                },
+            )
+            .map_err(|e| io::Error::other(format!("Couldn't encrypt chat message: {e}")))?;
+
+        Ok(SealedChatMessage {
+            _type: message._type.clone(),
+            timestamp: message.timestamp,
+            epoch: self.epoch,
+            nonce: BASE64_URL_SAFE_NO_PAD.encode(nonce),
+            ciphertext: BASE64_URL_SAFE_NO_PAD.encode(ciphertext),
+            subject_index: subject_words(&message.message)
+                .iter()
+                .map(|word| self.blind(word))
+                .collect(),
+        })
+    }
+
+    /// Decrypts a message sealed under these keys.
+    pub fn open(&self, sealed: &SealedChatMessage) -> io::Result<ChatMessage> {
+        if sealed.epoch != self.epoch {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Chat message was sealed under key epoch {} (keys are for epoch {})",
+                    sealed.epoch, self.epoch
+                ),
+            ));
+        }
+
+        let nonce: [u8; 12] = decode(&sealed.nonce)?.try_into().map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Chat message nonce must be 12 bytes",
+            )
+        })?;
+        let plaintext = self
+            .cipher()
+            .decrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: &decode(&sealed.ciphertext)?,
+                    aad: &self.aad(&sealed._type, &sealed.timestamp)?,
+                },
+            )
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Couldn't decrypt chat message: wrong key, or the state file was modified",
+                )
+            })?;
+
+        Ok(ChatMessage {
+            _type: sealed._type.clone(),
+            message: String::from_utf8(plaintext)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            timestamp: sealed.timestamp,
+            archived: true,
+        })
+    }
+
+    /// The sealed messages of this epoch whose subject line contains every
+    /// word of `query`, found from the blind index alone.
+    pub fn search<'a>(
+        &self,
+        sealed: &'a [SealedChatMessage],
+        query: &str,
+    ) -> Vec<&'a SealedChatMessage> {
+        let wanted: Vec<String> = subject_words(query)
+            .iter()
+            .map(|word| self.blind(word))
+            .collect();
+        if wanted.is_empty() {
+            return Vec::new();
+        }
+
+        sealed
+            .iter()
+            .filter(|message| message.epoch == self.epoch)
+            .filter(|message| {
+                wanted
+                    .iter()
+                    .all(|word| message.subject_index.contains(word))
+            })
+            .collect()
+    }
+
+    fn blind(&self, word: &str) -> String {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(self.index.as_slice())
+            .expect("HMAC accepts keys of any length");
+        mac.update(word.as_bytes());
+        BASE64_URL_SAFE_NO_PAD.encode(&mac.finalize().into_bytes()[..BLIND_INDEX_BYTES])
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&(*self.message).into())
+    }
+
+    fn aad(&self, _type: &ChatMessageType, timestamp: &DateTime<Local>) -> io::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(&AssociatedData {
+            conversation: &self.conversation,
+            epoch: self.epoch,
+            _type,
+            timestamp: timestamp.to_utc(),
+        })?)
+    }
+}
+
+/// The distinct lowercase words of the first line of `text`.
+fn subject_words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = text
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    words.sort();
+    words.dedup();
+    words
+}
+
+fn decode(value: &str) -> io::Result<Vec<u8>> {
+    BASE64_URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAT: &str = "did:peer:2.chat";
+
+    fn history(byte: u8) -> HistoryKey {
+        HistoryKey(Zeroizing::new([byte; 32]))
+    }
+
+    fn keys(history_byte: u8, epoch: u32) -> ConversationKeys {
+        ConversationKeys::derive(&history(history_byte), CHAT, epoch).unwrap()
+    }
+
+    fn message(text: &str) -> ChatMessage {
+        ChatMessage::new(ChatMessageType::Inbound, text.to_string())
+    }
+
+    #[test]
+    fn sealed_messages_open_with_the_same_keys() {
+        let keys = keys(1, 0);
+        let original = message("Lunch on Friday?\nSomewhere near the office");
+        let sealed = keys.seal(&original).unwrap();
+
+        assert!(!sealed.ciphertext.contains("Lunch"));
+        let opened = keys.open(&sealed).unwrap();
+        assert_eq!(opened.message, original.message);
+        assert_eq!(opened.timestamp, original.timestamp);
+        assert!(opened.archived);
+    }
+
+    #[test]
+    fn other_keys_cannot_open_a_message() {
+        let sealed = keys(1, 0).seal(&message("secret plans")).unwrap();
+
+        assert!(keys(2, 0).open(&sealed).is_err(), "other history key");
+        let other_chat = ConversationKeys::derive(&history(1), "did:peer:2.other", 0).unwrap();
+        assert!(other_chat.open(&sealed).is_err(), "other conversation");
+        assert!(keys(1, 1).open(&sealed).is_err(), "other epoch");
+    }
+
+    #[test]
+    fn tampered_messages_do_not_open() {
+        let keys = keys(1, 0);
+        let sealed = keys.seal(&message("pay 10 coins")).unwrap();
+
+        let mut ciphertext = sealed.clone();
+        let mut bytes = decode(&ciphertext.ciphertext).unwrap();
+        bytes[0] ^= 1;
+        ciphertext.ciphertext = BASE64_URL_SAFE_NO_PAD.encode(bytes);
+        assert!(keys.open(&ciphertext).is_err(), "ciphertext");
+
+        let mut retyped = sealed.clone();
+        retyped._type = ChatMessageType::Outbound;
+        assert!(keys.open(&retyped).is_err(), "type");
+
+        let mut redated = sealed;
+        redated.timestamp += chrono::Duration::seconds(1);
+        assert!(keys.open(&redated).is_err(), "timestamp");
+    }
+
+    #[test]
+    fn search_matches_every_word_of_the_subject_line() {
+        let epoch_0 = keys(1, 0);
+        let sealed: Vec<SealedChatMessage> = [
+            "Lunch on Friday?\nor Saturday",
+            "friday standup notes",
+            "Nothing here\nlunch friday",
+        ]
+        .iter()
+        .map(|text| epoch_0.seal(&message(text)).unwrap())
+        .collect();
+
+        let found = epoch_0.search(&sealed, "FRIDAY lunch");
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            epoch_0.open(found[0]).unwrap().message,
+            "Lunch on Friday?\nor Saturday"
+        );
+        assert_eq!(epoch_0.search(&sealed, "friday").len(), 2);
+        assert!(
+            epoch_0.search(&sealed, "saturday").is_empty(),
+            "body is not indexed"
+        );
+        assert!(epoch_0.search(&sealed, "").is_empty());
+        assert!(
+            keys(1, 1).search(&sealed, "friday").is_empty(),
+            "each epoch has its own index key"
+        );
+    }
+}
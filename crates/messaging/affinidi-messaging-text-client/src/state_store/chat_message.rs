@@ -29,6 +29,9 @@ pub struct ChatMessage {
     pub _type: ChatMessageType,
     pub message: String,
     pub timestamp: chrono::DateTime<Local>,
+    /// Whether the message is already in its chat's sealed history
+    #[serde(skip)]
+    pub archived: bool,
 }
 
 impl ChatMessage {
@@ -37,6 +40,7 @@ impl ChatMessage {
             _type,
             message,
             timestamp: Local::now(),
+            archived: false,
         }
     }
     // (2025-02-01 20:17:27: << )
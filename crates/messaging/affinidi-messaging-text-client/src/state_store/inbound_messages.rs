@@ -734,7 +734,7 @@ pub async fn handle_message(
                     break 'label_break;
                 };
 
-            new_secure_chat.remote_did = Some(remote_secure_did.clone());
+            new_secure_chat.set_remote_did(remote_secure_did.clone());
             new_secure_chat.status = ChatStatus::EstablishedChannel;
             let old_chat_name = new_secure_chat.name.clone();
             new_secure_chat.name = new_chat_name.clone();
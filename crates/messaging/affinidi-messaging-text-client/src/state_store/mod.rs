@@ -2,6 +2,7 @@ pub use self::state::*;
 pub use self::state_store::StateStore;
 
 pub mod actions;
+mod at_rest;
 mod chat_message;
 mod inbound_messages;
 mod outbound_messages;
@@ -1,13 +1,18 @@
-use super::actions::{
-    chat_list::{Chat, ChatList},
-    invitation::InvitePopupState,
+use super::{
+    actions::{
+        chat_list::{Chat, ChatList},
+        invitation::InvitePopupState,
+    },
+    at_rest::{ConversationKeys, HistoryKey},
+    chat_message::{ChatEffect, ChatMessage, ChatMessageType},
 };
 use affinidi_did_resolver_cache_sdk::DIDCacheClient;
 use affinidi_messaging_sdk::{ATM, protocols::oob_discovery::OOBDiscovery};
-use affinidi_tdk::secrets_resolver::secrets::Secret;
+use affinidi_tdk::secrets_resolver::secrets::{Secret, serialize_with_private};
 use ratatui::text::Line;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info};
+use std::{collections::BTreeSet, io, sync::Arc};
+use tracing::{error, info, warn};
 
 /*
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -142,9 +147,16 @@ pub struct State {
     pub manual_connect_popup: ManualConnectPopupState,
     #[serde(skip)]
     pub initialization: bool,
+    #[serde(serialize_with = "serialize_with_private")]
     pub(crate) secrets: Vec<Secret>,
+    /// Key sealing chat history, read from the OS keyring when first needed
+    #[serde(skip)]
+    history_key: Option<Arc<HistoryKey>>,
 }
 
+/// Most sealed messages kept per chat; the oldest are dropped first.
+const MAX_ARCHIVED_MESSAGES: usize = 10_000;
+
 impl State {
     pub fn add_secret(&mut self, secret: Secret) {
         self.secrets.push(secret);
@@ -154,7 +166,27 @@ impl State {
         self.secrets.append(secrets);
     }
 
-    pub fn save_to_file(&self, file_path: &str) -> Result<(), std::io::Error> {
+    /// Saves the state. Messages not yet in their chat's sealed history are
+    /// sealed under the chat's current key epoch and added to it (see
+    /// [`super::at_rest`]); the history already sealed is kept as it is.
+    /// Messages are never saved in the clear: if the history key can't be
+    /// read from the OS keyring, the rest of the state is saved and an error
+    /// reports the messages that weren't.
+    pub fn save_to_file(&mut self, file_path: &str) -> Result<(), std::io::Error> {
+        let history = self.history_key();
+        let mut unsaved = 0;
+        for chat in self.chat_list.chats.values_mut() {
+            match &history {
+                Some(history) => archive_new_messages(chat, history)?,
+                None => unsaved += chat.messages.asc_iter().filter(|m| !m.archived).count(),
+            }
+        }
+
+        let mut saved = self.clone();
+        for chat in saved.chat_list.chats.values_mut() {
+            chat.messages.clear();
+        }
+
         // State serializes `secrets` (private keys); restrict to owner-only on
         // Unix so other local users can't read them. `File::create` would use
         // the process umask (typically 0644).
@@ -171,17 +203,140 @@ impl State {
         #[cfg(not(unix))]
         let file = std::fs::File::create(file_path)?;
 
-        serde_json::to_writer_pretty(file, self)?;
+        serde_json::to_writer_pretty(file, &saved)?;
+        if unsaved > 0 {
+            return Err(io::Error::other(format!(
+                "no chat history key; {unsaved} new chat messages weren't saved"
+            )));
+        }
         Ok(())
     }
 
+    /// Reads the state, opening the most recent messages of each chat's
+    /// sealed history. Messages saved in the clear by older versions follow
+    /// them. Without a history key, each chat says that its new messages
+    /// won't be saved.
     pub fn read_from_file(file_path: &str) -> Result<Self, std::io::Error> {
         let file = std::fs::File::open(file_path)?;
-        let state: Self = serde_json::from_reader(file)?;
+        let mut state: Self = serde_json::from_reader(file)?;
+        let history = state.history_key();
+
+        for chat in state.chat_list.chats.values_mut() {
+            let unsealed: Vec<ChatMessage> = chat.messages.asc_iter().cloned().collect();
+            chat.messages.clear();
+
+            let recent = chat
+                .sealed_messages
+                .len()
+                .saturating_sub(chat.messages.capacity());
+            for sealed in &chat.sealed_messages[recent..] {
+                let opened = history
+                    .as_deref()
+                    .ok_or_else(|| io::Error::other("no chat history key"))
+                    .and_then(|history| {
+                        ConversationKeys::derive(history, &chat.our_profile.did, sealed.epoch)
+                    })
+                    .and_then(|keys| keys.open(sealed));
+                match opened {
+                    Ok(message) => {
+                        chat.messages.push(message);
+                    }
+                    Err(e) => {
+                        warn!("Couldn't open a message in chat ({}): {}", chat.name, e);
+                        chat.messages.push(ChatMessage {
+                            _type: ChatMessageType::Error,
+                            message: format!("Couldn't decrypt this message: {e}"),
+                            timestamp: sealed.timestamp,
+                            archived: true,
+                        });
+                    }
+                }
+            }
+            for message in unsealed {
+                chat.messages.push(message);
+            }
+            if history.is_none() {
+                // Marked archived so it is never sealed into the history
+                chat.messages.push(ChatMessage {
+                    archived: true,
+                    ..ChatMessage::new(
+                        ChatMessageType::Error,
+                        "Chat history key unavailable: new messages in this chat won't be saved"
+                            .into(),
+                    )
+                });
+            }
+        }
 
         Ok(state)
     }
 
+    /// Searches the subject lines (first lines) of the active chat's sealed
+    /// history and shows the matches in the chat. New messages are sealed
+    /// first; matching uses the blind index of each key epoch, and only
+    /// matches are decrypted.
+    pub(crate) fn search_active_chat(&mut self, query: &str) {
+        let Some(name) = self.chat_list.active_chat.clone() else {
+            warn!("No active chat to search");
+            return;
+        };
+        let Some(history) = self.history_key() else {
+            warn!("No chat history key; can't search chat ({})", name);
+            return;
+        };
+        let Some(chat) = self.chat_list.chats.get_mut(&name) else {
+            warn!("Active chat ({}) not found in chat list", name);
+            return;
+        };
+        if let Err(e) = archive_new_messages(chat, &history) {
+            warn!("Couldn't seal chat ({}) for search: {}", name, e);
+            return;
+        }
+
+        let epochs: BTreeSet<u32> = chat.sealed_messages.iter().map(|m| m.epoch).collect();
+        let mut found: Vec<ChatMessage> = Vec::new();
+        for epoch in epochs {
+            match ConversationKeys::derive(&history, &chat.our_profile.did, epoch) {
+                Ok(keys) => found.extend(
+                    keys.search(&chat.sealed_messages, query)
+                        .into_iter()
+                        .filter_map(|message| keys.open(message).ok()),
+                ),
+                Err(e) => warn!("Couldn't derive keys for chat ({}): {}", name, e),
+            }
+        }
+        found.sort_by_key(|message| message.timestamp);
+
+        let system = ChatMessageType::Effect {
+            effect: ChatEffect::System,
+        };
+        chat.messages.push(ChatMessage::new(
+            system.clone(),
+            format!("Search ({query}): {} matching messages", found.len()),
+        ));
+        for message in found {
+            chat.messages.push(ChatMessage::new(
+                system.clone(),
+                format!(
+                    "{}: {}",
+                    message.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    message.message.lines().next().unwrap_or_default()
+                ),
+            ));
+        }
+    }
+
+    /// The chat history key, read from the OS keyring on first use.
+    fn history_key(&mut self) -> Option<Arc<HistoryKey>> {
+        if self.history_key.is_none() {
+            match HistoryKey::load_or_create() {
+                Ok(key) => self.history_key = Some(Arc::new(key)),
+                Err(e) => warn!("Couldn't load the chat history key: {}", e),
+            }
+        }
+        self.history_key.clone()
+    }
+
     /// Shutdowns and removes a chat.
     pub async fn remove_chat(&mut self, chat: &Chat, atm: &ATM) {
         // Find our current ATM Profile
@@ -229,3 +384,22 @@ impl State {
         info!("Chat removed: {}", chat.name);
     }
 }
+
+/// Seals the messages of `chat` that aren't in its sealed history yet under
+/// the chat's current key epoch and appends them to it.
+fn archive_new_messages(chat: &mut Chat, history: &HistoryKey) -> io::Result<()> {
+    let keys = ConversationKeys::derive(history, &chat.our_profile.did, chat.key_epoch)?;
+    for message in chat.messages.asc_iter_mut() {
+        if !message.archived {
+            chat.sealed_messages.push(keys.seal(message)?);
+            message.archived = true;
+        }
+    }
+
+    let excess = chat
+        .sealed_messages
+        .len()
+        .saturating_sub(MAX_ARCHIVED_MESSAGES);
+    chat.sealed_messages.drain(..excess);
+    Ok(())
+}
@@ -19,7 +19,7 @@ use tokio::sync::{
     broadcast,
     mpsc::{self, UnboundedReceiver, UnboundedSender},
 };
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info, warn};
 
 pub struct StateStore {
     state_tx: UnboundedSender<State>,
@@ -129,7 +129,11 @@ impl StateStore {
                 },
                 Some(action) = action_rx.recv() => match action {
                     Action::SendMessage { chat_msg } => {
-                       send_message(&mut state, &atm, &chat_msg).await;
+                        if let Some(query) = chat_msg.strip_prefix("/search ") {
+                            state.search_active_chat(query);
+                        } else {
+                            send_message(&mut state, &atm, &chat_msg).await;
+                        }
                     },
                     Action::DeleteChat { chat } => {
                         match state.chat_list.chats.remove(&chat) {
@@ -237,7 +241,9 @@ impl StateStore {
             self.state_tx.send(state.clone())?;
         };
 
-        let _ = state.save_to_file("config.json");
+        if let Err(e) = state.save_to_file("config.json") {
+            error!("Couldn't save the chat state: {}", e);
+        }
 
         Ok(result)
     }
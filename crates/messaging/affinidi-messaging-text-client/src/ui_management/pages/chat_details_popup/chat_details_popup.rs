@@ -43,6 +43,8 @@ impl From<&State> for Props {
                         hidden: None,
                         initialization: false,
                         messages: CircularQueue::with_capacity(1),
+                        sealed_messages: Vec::new(),
+                        key_epoch: 0,
                     }
                 }
             }),
@@ -40,6 +40,8 @@ impl From<&State> for Props {
             .map(|(name, chat_data)| Chat {
                 name: name.clone(),
                 messages: chat_data.messages.clone(),
+                sealed_messages: Vec::new(),
+                key_epoch: chat_data.key_epoch,
                 description: chat_data.description.clone(),
                 our_profile: chat_data.our_profile.clone(),
                 remote_did: chat_data.remote_did.clone(),
@@ -150,8 +150,7 @@ fn tsp_endpoints(doc: &Document) -> Vec<Url> {
         .iter()
         .filter(|service| service.type_.iter().any(|t| t == TSP_SERVICE_TYPE))
         .flat_map(|service| service.service_endpoint.get_uris())
-        // `Endpoint::Map` yields JSON-serialized strings (quoted); strip quotes.
-        .filter_map(|uri| Url::parse(uri.trim_matches('"')).ok())
+        .filter_map(|uri| Url::parse(&uri).ok())
         .collect()
 }
 
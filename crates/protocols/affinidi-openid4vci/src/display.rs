@@ -0,0 +1,434 @@
+/*!
+ * Credential display metadata and rendering.
+ *
+ * Issuers describe how a credential should look in their metadata: a name,
+ * colours, logo and background image for the credential, and a localized
+ * label for each claim (OpenID4VCI §12.2.4). A wallet captures that
+ * description once, when the credential is issued, with
+ * [`CredentialDisplayMetadata::from_issuer_metadata`] and stores it next to
+ * the credential: the issuer may change or drop its metadata later, but the
+ * stored credential should keep looking the way it did.
+ *
+ * [`CredentialDisplayMetadata::render_model`] merges the stored description
+ * with the credential's claims into a [`RenderModel`] a UI can draw directly:
+ *
+ * ```
+ * # use affinidi_openid4vci::display::CredentialDisplayMetadata;
+ * # use serde_json::json;
+ * let display: CredentialDisplayMetadata = serde_json::from_value(json!({
+ *     "display": [{ "name": "Identity Card", "locale": "en" }],
+ *     "claims": [{ "path": ["given_name"], "display": [{ "name": "Given name", "locale": "en" }] }]
+ * })).unwrap();
+ *
+ * let model = display.render_model(&json!({ "given_name": "Alice" }), Some("en-GB"));
+ * assert_eq!(model.name.as_deref(), Some("Identity Card"));
+ * assert_eq!(model.claims[0].label, "Given name");
+ * assert_eq!(model.claims[0].value, json!("Alice"));
+ * ```
+ *
+ * Both claim description layouts are accepted: the OpenID4VCI 1.0 array of
+ * claims with a `path`, read from `credential_metadata` when present, and the
+ * nested object of earlier drafts.
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{Oid4vciError, Result};
+use crate::types::{CredentialIssuerMetadata, DisplayProperties, LogoProperties};
+
+/// Claims that describe the credential itself rather than its subject, left
+/// out of [`RenderModel::claims`] unless the issuer labels them.
+pub const TECHNICAL_CLAIMS: &[&str] = &[
+    "iss", "sub", "iat", "nbf", "exp", "jti", "cnf", "vct", "status", "_sd", "_sd_alg",
+];
+
+/// The display description of one credential configuration, as stored by a
+/// wallet alongside the credential.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialDisplayMetadata {
+    /// Display of the credential, one entry per locale.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display: Vec<DisplayProperties>,
+
+    /// Display of the issuer, one entry per locale.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issuer_display: Vec<DisplayProperties>,
+
+    /// Claim descriptions, in the order the issuer wants them shown.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub claims: Vec<ClaimMetadata>,
+}
+
+/// How one claim is labelled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimMetadata {
+    /// Path to the claim: object keys as strings, array indices as numbers,
+    /// and `null` for every element of an array.
+    pub path: Vec<Value>,
+
+    /// Whether the issuer always includes the claim.
+    #[serde(default)]
+    pub mandatory: bool,
+
+    /// Labels, one entry per locale.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub display: Vec<DisplayProperties>,
+}
+
+/// A credential ready to be drawn: display properties for the chosen locale
+/// and a labelled list of claims.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RenderModel {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub locale: Option<String>,
+    pub issuer_name: Option<String>,
+    pub logo: Option<LogoProperties>,
+    pub background_image: Option<LogoProperties>,
+    pub background_color: Option<String>,
+    pub text_color: Option<String>,
+    pub claims: Vec<RenderedClaim>,
+}
+
+/// A claim with its label.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderedClaim {
+    /// Path of the claim in the credential, with `null`s resolved to indices.
+    pub path: Vec<Value>,
+    /// The issuer's label, or the claim name when the issuer gave none.
+    pub label: String,
+    pub value: Value,
+    pub mandatory: bool,
+}
+
+impl CredentialDisplayMetadata {
+    /// Capture the display description of `configuration_id` from the
+    /// issuer's metadata.
+    pub fn from_issuer_metadata(
+        metadata: &CredentialIssuerMetadata,
+        configuration_id: &str,
+    ) -> Result<Self> {
+        let configuration = metadata
+            .credential_configurations_supported
+            .get(configuration_id)
+            .ok_or_else(|| {
+                Oid4vciError::InvalidMetadata(format!(
+                    "unknown credential configuration: {configuration_id}"
+                ))
+            })?;
+
+        // OpenID4VCI 1.0 groups display and claims under `credential_metadata`
+        let credential_metadata = configuration.additional.get("credential_metadata");
+        let display = match credential_metadata.and_then(|m| m.get("display")) {
+            Some(display) => serde_json::from_value(display.clone())
+                .map_err(|e| Oid4vciError::InvalidMetadata(format!("credential display: {e}")))?,
+            None => configuration.display.clone().unwrap_or_default(),
+        };
+        let claims = match credential_metadata
+            .and_then(|m| m.get("claims"))
+            .or(configuration.claims.as_ref())
+        {
+            Some(claims) => parse_claims(claims)?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            display,
+            issuer_display: metadata.display.clone().unwrap_or_default(),
+            claims,
+        })
+    }
+
+    /// Merge `claims`, the credential's claims as a JSON object, with the
+    /// display description, choosing entries for `locale`.
+    ///
+    /// Labelled claims come first, in the issuer's order; an array path
+    /// element of `null` yields one claim per array element. Any other
+    /// top-level claim follows, labelled with its name, unless it is one of
+    /// the [`TECHNICAL_CLAIMS`]. Claims the credential doesn't contain are
+    /// left out.
+    pub fn render_model(&self, claims: &Value, locale: Option<&str>) -> RenderModel {
+        let display = select_locale(&self.display, locale);
+        let mut model = RenderModel {
+            name: display.and_then(|d| d.name.clone()),
+            description: display.and_then(|d| d.description.clone()),
+            locale: display.and_then(|d| d.locale.clone()),
+            issuer_name: select_locale(&self.issuer_display, locale).and_then(|d| d.name.clone()),
+            logo: display.and_then(|d| d.logo.clone()),
+            background_image: display.and_then(|d| d.background_image.clone()),
+            background_color: display.and_then(|d| d.background_color.clone()),
+            text_color: display.and_then(|d| d.text_color.clone()),
+            claims: Vec::new(),
+        };
+
+        for claim in &self.claims {
+            let label = select_locale(&claim.display, locale)
+                .and_then(|d| d.name.clone())
+                .unwrap_or_else(|| default_label(&claim.path));
+            for (path, value) in select_path(claims, &claim.path) {
+                model.claims.push(RenderedClaim {
+                    path,
+                    label: label.clone(),
+                    value: value.clone(),
+                    mandatory: claim.mandatory,
+                });
+            }
+        }
+
+        if let Some(object) = claims.as_object() {
+            for (name, value) in object {
+                let labelled = self
+                    .claims
+                    .iter()
+                    .any(|c| c.path.first().and_then(Value::as_str) == Some(name));
+                if !labelled && !TECHNICAL_CLAIMS.contains(&name.as_str()) {
+                    model.claims.push(RenderedClaim {
+                        path: vec![Value::String(name.clone())],
+                        label: name.clone(),
+                        value: value.clone(),
+                        mandatory: false,
+                    });
+                }
+            }
+        }
+
+        model
+    }
+}
+
+/// Parse claim descriptions in either the 1.0 array layout or the nested
+/// object layout of earlier drafts.
+fn parse_claims(claims: &Value) -> Result<Vec<ClaimMetadata>> {
+    match claims {
+        Value::Array(_) => serde_json::from_value(claims.clone())
+            .map_err(|e| Oid4vciError::InvalidMetadata(format!("claims: {e}"))),
+        Value::Object(object) => {
+            let mut out = Vec::new();
+            flatten_claims(object, &mut Vec::new(), &mut out)?;
+            Ok(out)
+        }
+        _ => Err(Oid4vciError::InvalidMetadata(
+            "claims must be an array or an object".into(),
+        )),
+    }
+}
+
+/// Walk a draft-style claims object. A node with `display` or `mandatory`
+/// describes a claim; any other object member is a nested claim.
+fn flatten_claims(
+    object: &Map<String, Value>,
+    path: &mut Vec<Value>,
+    out: &mut Vec<ClaimMetadata>,
+) -> Result<()> {
+    for (name, node) in object {
+        let Some(node) = node.as_object() else {
+            continue;
+        };
+        path.push(Value::String(name.clone()));
+        if node.contains_key("display") || node.contains_key("mandatory") {
+            out.push(ClaimMetadata {
+                path: path.clone(),
+                mandatory: node
+                    .get("mandatory")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                display: match node.get("display") {
+                    Some(display) => serde_json::from_value(display.clone()).map_err(|e| {
+                        Oid4vciError::InvalidMetadata(format!("display of claim {name}: {e}"))
+                    })?,
+                    None => Vec::new(),
+                },
+            });
+        }
+        let nested: Map<String, Value> = node
+            .iter()
+            .filter(|(key, value)| {
+                !matches!(key.as_str(), "display" | "mandatory" | "value_type") && value.is_object()
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        flatten_claims(&nested, path, out)?;
+        path.pop();
+    }
+    Ok(())
+}
+
+/// The entry for `locale`: an exact match, then one of the same language,
+/// then one without a locale, then the first.
+fn select_locale<'a>(
+    entries: &'a [DisplayProperties],
+    locale: Option<&str>,
+) -> Option<&'a DisplayProperties> {
+    let language = |tag: &str| tag.split(['-', '_']).next().unwrap_or(tag).to_lowercase();
+    locale
+        .and_then(|locale| {
+            entries
+                .iter()
+                .find(|e| {
+                    e.locale
+                        .as_deref()
+                        .is_some_and(|l| l.eq_ignore_ascii_case(locale))
+                })
+                .or_else(|| {
+                    entries.iter().find(|e| {
+                        e.locale
+                            .as_deref()
+                            .is_some_and(|l| language(l) == language(locale))
+                    })
+                })
+        })
+        .or_else(|| entries.iter().find(|e| e.locale.is_none()))
+        .or_else(|| entries.first())
+}
+
+/// The last named segment of a path, used when the issuer gives no label.
+fn default_label(path: &[Value]) -> String {
+    path.iter()
+        .rev()
+        .find_map(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The values at `path` in `claims`, with their concrete paths.
+fn select_path<'a>(claims: &'a Value, path: &[Value]) -> Vec<(Vec<Value>, &'a Value)> {
+    let mut selected = vec![(Vec::new(), claims)];
+    for element in path {
+        selected = selected
+            .into_iter()
+            .flat_map(|(prefix, value)| {
+                let children: Vec<(Value, &Value)> = match (element, value) {
+                    (Value::String(key), Value::Object(object)) => object
+                        .get(key)
+                        .map(|child| (element.clone(), child))
+                        .into_iter()
+                        .collect(),
+                    (Value::Number(index), Value::Array(array)) => index
+                        .as_u64()
+                        .and_then(|i| array.get(i as usize).map(|child| (element.clone(), child)))
+                        .into_iter()
+                        .collect(),
+                    (Value::Null, Value::Array(array)) => array
+                        .iter()
+                        .enumerate()
+                        .map(|(i, child)| (Value::from(i), child))
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                children.into_iter().map(move |(segment, child)| {
+                    let mut path = prefix.clone();
+                    path.push(segment);
+                    (path, child)
+                })
+            })
+            .collect();
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn issuer_metadata(configuration: Value) -> CredentialIssuerMetadata {
+        serde_json::from_value(json!({
+            "credential_issuer": "https://issuer.example.com",
+            "credential_endpoint": "https://issuer.example.com/credential",
+            "credential_configurations_supported": { "PID": configuration },
+            "display": [
+                { "name": "Example Issuer", "locale": "en" },
+                { "name": "Exemple Émetteur", "locale": "fr" }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reads_v1_credential_metadata() {
+        let metadata = issuer_metadata(json!({
+            "format": "dc+sd-jwt",
+            "credential_metadata": {
+                "display": [{
+                    "name": "Identity",
+                    "locale": "en-US",
+                    "background_color": "#12107c",
+                    "background_image": { "uri": "https://issuer.example.com/bg.png" }
+                }],
+                "claims": [
+                    { "path": ["given_name"], "mandatory": true,
+                      "display": [{ "name": "Given name", "locale": "en-US" },
+                                  { "name": "Prénom", "locale": "fr" }] },
+                    { "path": ["nationalities", null], "display": [{ "name": "Nationality" }] }
+                ]
+            }
+        }));
+        let display = CredentialDisplayMetadata::from_issuer_metadata(&metadata, "PID").unwrap();
+
+        let claims = json!({
+            "iss": "https://issuer.example.com",
+            "given_name": "Alice",
+            "nationalities": ["NZ", "FR"],
+            "birthdate": "1990-01-01"
+        });
+        let model = display.render_model(&claims, Some("en"));
+        assert_eq!(model.name.as_deref(), Some("Identity"));
+        assert_eq!(model.issuer_name.as_deref(), Some("Example Issuer"));
+        assert_eq!(model.background_color.as_deref(), Some("#12107c"));
+        assert_eq!(
+            model
+                .claims
+                .iter()
+                .map(|c| c.label.as_str())
+                .collect::<Vec<_>>(),
+            ["Given name", "Nationality", "Nationality", "birthdate"]
+        );
+        assert!(model.claims[0].mandatory);
+        assert_eq!(model.claims[2].path, [json!("nationalities"), json!(1)]);
+        assert_eq!(model.claims[2].value, json!("FR"));
+
+        let french = display.render_model(&claims, Some("fr-CA"));
+        assert_eq!(french.claims[0].label, "Prénom");
+        assert_eq!(french.issuer_name.as_deref(), Some("Exemple Émetteur"));
+    }
+
+    #[test]
+    fn reads_draft_nested_claims() {
+        let metadata = issuer_metadata(json!({
+            "format": "vc+sd-jwt",
+            "display": [{ "name": "Identity" }],
+            "claims": {
+                "given_name": { "display": [{ "name": "Given name" }] },
+                "address": {
+                    "locality": { "display": [{ "name": "City" }] }
+                }
+            }
+        }));
+        let display = CredentialDisplayMetadata::from_issuer_metadata(&metadata, "PID").unwrap();
+        assert_eq!(display.claims.len(), 2);
+
+        let model = display.render_model(
+            &json!({ "address": { "locality": "Auckland" }, "given_name": "Alice" }),
+            None,
+        );
+        let city = model.claims.iter().find(|c| c.label == "City").unwrap();
+        assert_eq!(city.path, [json!("address"), json!("locality")]);
+        assert_eq!(city.value, json!("Auckland"));
+    }
+
+    #[test]
+    fn stored_metadata_round_trips() {
+        let metadata = issuer_metadata(json!({
+            "format": "vc+sd-jwt",
+            "claims": [{ "path": ["given_name"] }]
+        }));
+        let display = CredentialDisplayMetadata::from_issuer_metadata(&metadata, "PID").unwrap();
+        let stored: CredentialDisplayMetadata =
+            serde_json::from_str(&serde_json::to_string(&display).unwrap()).unwrap();
+
+        let model = stored.render_model(&json!({ "given_name": "Alice" }), None);
+        assert_eq!(model.claims[0].label, "given_name");
+        assert!(CredentialDisplayMetadata::from_issuer_metadata(&metadata, "mDL").is_err());
+    }
+}
@@ -29,8 +29,14 @@
  * - `mso_mdoc` — ISO mdoc (mandatory for eIDAS)
  * - `jwt_vc_json` — JWT-secured VC
  * - `ldp_vc` — Linked Data Proof VC
+ *
+ * # Credential Display
+ *
+ * [`display`] captures the issuer's rendering hints for a credential so a
+ * wallet can store them with it and render labelled claims.
  */
 
+pub mod display;
 pub mod error;
 pub mod issuer;
 pub mod proof;
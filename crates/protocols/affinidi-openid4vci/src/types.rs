@@ -103,6 +103,14 @@ pub struct DisplayProperties {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logo: Option<LogoProperties>,
 
+    /// Description of the credential (credential display only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Background image of the credential (credential display only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_image: Option<LogoProperties>,
+
     /// Background color (CSS color string).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub background_color: Option<String>,
@@ -112,8 +120,8 @@ pub struct DisplayProperties {
     pub text_color: Option<String>,
 }
 
-/// Logo properties for display.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Logo or image properties for display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LogoProperties {
     /// URI of the logo image.
     pub uri: String,
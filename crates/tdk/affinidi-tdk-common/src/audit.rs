@@ -0,0 +1,727 @@
+/*!
+ * Append-only, tamper-evident audit log for TDK security events.
+ *
+ * Every [`AuditEntry`] carries the SHA-256 hash of the previous entry, so
+ * editing, reordering or deleting a line breaks the chain. When a device key
+ * is configured, the log periodically appends an [`AuditCheckpoint`]: a W3C
+ * Data Integrity proof over the current head hash. A checkpoint pins every
+ * entry before it, so editing, dropping or rewriting a signed entry is
+ * detectable by anyone holding the device's public key.
+ *
+ * Truncation is not detectable from the file alone: entries after the last
+ * checkpoint are only hash-chained, and a log cut back to an earlier
+ * checkpoint still verifies. To catch it, compare against a head kept
+ * elsewhere — the [`AuditReport::entries`] and [`AuditReport::head_hash`] of
+ * an earlier verification, or a copy of the checkpoints shipped off the
+ * device — and call [`AuditLog::checkpoint`] before shutting down so the tail
+ * is signed.
+ *
+ * The on-disk format is JSON Lines: one [`AuditRecord`] per line.
+ *
+ * ```no_run
+ * use affinidi_tdk_common::audit::{AuditEvent, AuditLog, SignatureStatus};
+ * use affinidi_secrets_resolver::secrets::Secret;
+ * use std::sync::Arc;
+ *
+ * # async fn demo() -> Result<(), affinidi_tdk_common::errors::TDKError> {
+ * let device_key = Secret::generate_ed25519(Some("did:key:z6Mk...#z6Mk..."), None);
+ * let public_key = device_key.get_public_bytes().to_vec();
+ *
+ * let log = AuditLog::builder("audit.jsonl")
+ *     .with_signer(Arc::new(device_key))
+ *     .with_checkpoint_interval(100)
+ *     .open()
+ *     .await?;
+ * log.record(AuditEvent::KeyCreated {
+ *     key_id: "did:example:alice#key-1".to_string(),
+ * })
+ * .await?;
+ * log.checkpoint().await?;
+ *
+ * let report = AuditLog::verify_file("audit.jsonl", Some(&public_key)).await?;
+ * assert_eq!(report.signatures, SignatureStatus::Verified);
+ * # Ok(()) }
+ * ```
+ */
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use affinidi_data_integrity::{DataIntegrityProof, SignOptions, VerifyOptions, signer::Signer};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::errors::TDKError;
+
+/// `prevHash` of the first entry in a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Default number of entries between signed checkpoints
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 100;
+
+/// Proof purpose used for checkpoint signatures
+const CHECKPOINT_PROOF_PURPOSE: &str = "assertionMethod";
+
+/// A security-relevant event recorded in the audit log
+///
+/// Only metadata is recorded — never message bodies or key material.
+///
+/// [`TDKSharedState`](crate::TDKSharedState) records authentication outcomes
+/// and destroyed keys, the messaging SDK records messages it sends and
+/// unpacks, and the DIDComm service's sender filter records the messages it
+/// drops. Keys are created and rotated by the application, which records
+/// those events itself.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+#[non_exhaustive]
+pub enum AuditEvent {
+    /// A profile authenticated successfully against a service
+    AuthenticationSuccess {
+        profile_did: String,
+        target_did: String,
+    },
+
+    /// A profile failed to authenticate against a service
+    AuthenticationFailure {
+        profile_did: String,
+        target_did: String,
+        reason: String,
+    },
+
+    /// A new key was created
+    KeyCreated { key_id: String },
+
+    /// A key was destroyed and its secret material erased
+    KeyDestroyed { key_id: String },
+
+    /// A key was rotated and replaced by a new one
+    KeyRotated {
+        previous_key_id: String,
+        key_id: String,
+    },
+
+    /// A message was sent
+    MessageSent {
+        message_id: String,
+        message_type: String,
+        from: Option<String>,
+        to: Vec<String>,
+    },
+
+    /// A message was received
+    MessageReceived {
+        message_id: String,
+        message_type: String,
+        from: Option<String>,
+        to: Vec<String>,
+    },
+
+    /// An action was denied by policy (ACL, trust list, etc.)
+    PolicyDenied {
+        subject: String,
+        action: String,
+        reason: String,
+    },
+}
+
+/// A single hash-chained entry
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuditEntry {
+    /// Position in the log, starting at 0
+    pub sequence: u64,
+
+    /// Seconds since the UNIX epoch
+    pub timestamp: u64,
+
+    pub event: AuditEvent,
+
+    /// Hash of the previous entry ([`GENESIS_HASH`] for the first entry)
+    pub prev_hash: String,
+
+    /// Hex SHA-256 over the JCS form of every other field of this entry
+    pub hash: String,
+}
+
+/// Fields of an [`AuditEntry`] covered by its hash
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EntryHashInput<'a> {
+    sequence: u64,
+    timestamp: u64,
+    event: &'a AuditEvent,
+    prev_hash: &'a str,
+}
+
+impl AuditEntry {
+    /// Recomputes the hash of this entry from its contents
+    pub fn compute_hash(&self) -> Result<String, TDKError> {
+        hash_entry(&EntryHashInput {
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+            event: &self.event,
+            prev_hash: &self.prev_hash,
+        })
+    }
+}
+
+/// A device-key signature over the chain head at `sequence`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AuditCheckpoint {
+    /// Sequence of the last entry covered by this checkpoint
+    pub sequence: u64,
+
+    /// Hash of the entry at `sequence`
+    pub head_hash: String,
+
+    /// Data Integrity proof over `{ sequence, headHash }`
+    pub proof: DataIntegrityProof,
+}
+
+/// Document signed by an [`AuditCheckpoint`]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckpointPayload<'a> {
+    sequence: u64,
+    head_hash: &'a str,
+}
+
+/// One line of the audit log
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum AuditRecord {
+    Entry(AuditEntry),
+    Checkpoint(AuditCheckpoint),
+}
+
+/// Whether the checkpoint signatures of a log were verified
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureStatus {
+    /// No public key was supplied, so no proof was checked
+    #[default]
+    NotChecked,
+
+    /// A public key was supplied, but the log holds no checkpoint: none of
+    /// it is signed
+    Unsigned,
+
+    /// Every checkpoint proof verified against the supplied public key
+    Verified,
+}
+
+/// Result of verifying an audit log
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AuditReport {
+    /// Number of entries in the log
+    pub entries: u64,
+
+    /// Number of checkpoints in the log
+    pub checkpoints: u64,
+
+    /// Hash of the last entry ([`GENESIS_HASH`] if the log is empty)
+    pub head_hash: String,
+
+    /// Entries appended after the last checkpoint. These are hash-chained but
+    /// not yet covered by a signature, so truncation of them is undetectable.
+    pub unsigned_entries: u64,
+
+    /// Outcome of checking the checkpoint proofs
+    pub signatures: SignatureStatus,
+}
+
+/// Builder for [`AuditLog`]. Construct via [`AuditLog::builder`].
+pub struct AuditLogBuilder {
+    path: PathBuf,
+    signer: Option<Arc<dyn Signer>>,
+    checkpoint_interval: u64,
+}
+
+impl AuditLogBuilder {
+    /// Device key used to sign checkpoints. Without a signer the log is still
+    /// hash-chained, but no checkpoints are written.
+    pub fn with_signer(mut self, signer: Arc<dyn Signer>) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Number of entries between automatic checkpoints. Defaults to
+    /// [`DEFAULT_CHECKPOINT_INTERVAL`]; `0` disables automatic checkpoints
+    /// (call [`AuditLog::checkpoint`] yourself).
+    pub fn with_checkpoint_interval(mut self, checkpoint_interval: u64) -> Self {
+        self.checkpoint_interval = checkpoint_interval;
+        self
+    }
+
+    /// Opens (or creates) the log file.
+    ///
+    /// An existing log has its hash chain verified before new entries are
+    /// appended to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TDKError::Audit`] if the existing log is corrupt or has been
+    /// tampered with, and [`TDKError::Io`] on file errors.
+    pub async fn open(self) -> Result<AuditLog, TDKError> {
+        let records = read_records(&self.path).await?;
+        let state = verify_chain(&records)?;
+
+        let path = self.path.clone();
+        let file =
+            blocking(move || Ok(OpenOptions::new().create(true).append(true).open(path)?)).await?;
+
+        Ok(AuditLog {
+            path: self.path,
+            signer: self.signer,
+            checkpoint_interval: self.checkpoint_interval,
+            inner: Arc::new(Mutex::new(AuditLogState {
+                file: Arc::new(file),
+                next_sequence: state.entries,
+                head_hash: state.head_hash,
+                unsigned_entries: state.unsigned_entries,
+            })),
+        })
+    }
+}
+
+struct AuditLogState {
+    file: Arc<File>,
+    next_sequence: u64,
+    head_hash: String,
+    unsigned_entries: u64,
+}
+
+/// Append-only audit log. Cloning is cheap; clones share the same file handle.
+#[derive(Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    signer: Option<Arc<dyn Signer>>,
+    checkpoint_interval: u64,
+    inner: Arc<Mutex<AuditLogState>>,
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("path", &self.path)
+            .field(
+                "signer",
+                &self.signer.as_ref().map(|s| s.verification_method()),
+            )
+            .field("checkpoint_interval", &self.checkpoint_interval)
+            .finish()
+    }
+}
+
+impl AuditLog {
+    /// Returns a fresh [`AuditLogBuilder`] for the log at `path`.
+    pub fn builder(path: impl Into<PathBuf>) -> AuditLogBuilder {
+        AuditLogBuilder {
+            path: path.into(),
+            signer: None,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+        }
+    }
+
+    /// Path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `event` to the log and returns the written entry.
+    ///
+    /// The line is flushed and synced to disk before returning. A signed
+    /// checkpoint is appended afterwards whenever the checkpoint interval is
+    /// reached.
+    pub async fn record(&self, event: AuditEvent) -> Result<AuditEntry, TDKError> {
+        let mut state = self.inner.lock().await;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let mut entry = AuditEntry {
+            sequence: state.next_sequence,
+            timestamp,
+            event,
+            prev_hash: state.head_hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash()?;
+
+        append_record(&state.file, &AuditRecord::Entry(entry.clone())).await?;
+        state.next_sequence += 1;
+        state.head_hash = entry.hash.clone();
+        state.unsigned_entries += 1;
+
+        if self.checkpoint_interval > 0 && state.unsigned_entries >= self.checkpoint_interval {
+            self.write_checkpoint(&mut state).await?;
+        }
+
+        Ok(entry)
+    }
+
+    /// Signs the current chain head immediately.
+    ///
+    /// Returns `Ok(None)` if no signer is configured or nothing has been
+    /// appended since the last checkpoint.
+    pub async fn checkpoint(&self) -> Result<Option<AuditCheckpoint>, TDKError> {
+        let mut state = self.inner.lock().await;
+        self.write_checkpoint(&mut state).await
+    }
+
+    async fn write_checkpoint(
+        &self,
+        state: &mut AuditLogState,
+    ) -> Result<Option<AuditCheckpoint>, TDKError> {
+        let Some(signer) = &self.signer else {
+            return Ok(None);
+        };
+        if state.unsigned_entries == 0 {
+            return Ok(None);
+        }
+
+        let sequence = state.next_sequence - 1;
+        let proof = DataIntegrityProof::sign(
+            &CheckpointPayload {
+                sequence,
+                head_hash: &state.head_hash,
+            },
+            signer.as_ref(),
+            SignOptions::new().with_proof_purpose(CHECKPOINT_PROOF_PURPOSE),
+        )
+        .await?;
+
+        let checkpoint = AuditCheckpoint {
+            sequence,
+            head_hash: state.head_hash.clone(),
+            proof,
+        };
+        append_record(&state.file, &AuditRecord::Checkpoint(checkpoint.clone())).await?;
+        state.unsigned_entries = 0;
+
+        Ok(Some(checkpoint))
+    }
+
+    /// Returns every record at or after `from_sequence`, for compliance export.
+    ///
+    /// Checkpoints are included when they cover an exported entry.
+    pub async fn export(&self, from_sequence: u64) -> Result<Vec<AuditRecord>, TDKError> {
+        // Hold the lock so a concurrent append can't leave a partial line
+        let _state = self.inner.lock().await;
+        Ok(read_records(&self.path)
+            .await?
+            .into_iter()
+            .filter(|record| match record {
+                AuditRecord::Entry(entry) => entry.sequence >= from_sequence,
+                AuditRecord::Checkpoint(checkpoint) => checkpoint.sequence >= from_sequence,
+            })
+            .collect())
+    }
+
+    /// Verifies this log. See [`AuditLog::verify_records`].
+    pub async fn verify(&self, public_key: Option<&[u8]>) -> Result<AuditReport, TDKError> {
+        let _state = self.inner.lock().await;
+        Self::verify_records(&read_records(&self.path).await?, public_key)
+    }
+
+    /// Verifies the log file at `path`. See [`AuditLog::verify_records`].
+    pub async fn verify_file(
+        path: impl AsRef<Path>,
+        public_key: Option<&[u8]>,
+    ) -> Result<AuditReport, TDKError> {
+        Self::verify_records(&read_records(path.as_ref()).await?, public_key)
+    }
+
+    /// Verifies a complete set of records, starting from the genesis entry.
+    ///
+    /// Checks that every entry hash is correct and chains to its predecessor,
+    /// and that every checkpoint pins the entry it follows. When `public_key`
+    /// is supplied, each checkpoint proof is also verified against it; a log
+    /// without checkpoints then reports [`SignatureStatus::Unsigned`], since
+    /// there is no signature to verify.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TDKError::Audit`] describing the first inconsistency found.
+    pub fn verify_records(
+        records: &[AuditRecord],
+        public_key: Option<&[u8]>,
+    ) -> Result<AuditReport, TDKError> {
+        let mut report = verify_chain(records)?;
+
+        if let Some(public_key) = public_key {
+            for record in records {
+                if let AuditRecord::Checkpoint(checkpoint) = record {
+                    checkpoint
+                        .proof
+                        .verify_with_public_key(
+                            &CheckpointPayload {
+                                sequence: checkpoint.sequence,
+                                head_hash: &checkpoint.head_hash,
+                            },
+                            public_key,
+                            VerifyOptions::new(),
+                        )
+                        .map_err(|e| {
+                            TDKError::Audit(format!(
+                                "checkpoint at sequence {} has an invalid signature: {e}",
+                                checkpoint.sequence
+                            ))
+                        })?;
+                }
+            }
+            report.signatures = if report.checkpoints > 0 {
+                SignatureStatus::Verified
+            } else {
+                SignatureStatus::Unsigned
+            };
+        }
+
+        Ok(report)
+    }
+}
+
+/// Walks the hash chain, returning the report without signature checks
+fn verify_chain(records: &[AuditRecord]) -> Result<AuditReport, TDKError> {
+    let mut report = AuditReport {
+        head_hash: GENESIS_HASH.to_string(),
+        ..Default::default()
+    };
+
+    for record in records {
+        match record {
+            AuditRecord::Entry(entry) => {
+                if entry.sequence != report.entries {
+                    return Err(TDKError::Audit(format!(
+                        "expected entry {} but found entry {}",
+                        report.entries, entry.sequence
+                    )));
+                }
+                if entry.prev_hash != report.head_hash {
+                    return Err(TDKError::Audit(format!(
+                        "entry {} does not chain to the previous entry",
+                        entry.sequence
+                    )));
+                }
+                if entry.compute_hash()? != entry.hash {
+                    return Err(TDKError::Audit(format!(
+                        "entry {} has been modified (hash mismatch)",
+                        entry.sequence
+                    )));
+                }
+                report.entries += 1;
+                report.unsigned_entries += 1;
+                report.head_hash = entry.hash.clone();
+            }
+            AuditRecord::Checkpoint(checkpoint) => {
+                if report.entries == 0
+                    || checkpoint.sequence != report.entries - 1
+                    || checkpoint.head_hash != report.head_hash
+                {
+                    return Err(TDKError::Audit(format!(
+                        "checkpoint at sequence {} does not match the chain head",
+                        checkpoint.sequence
+                    )));
+                }
+                report.checkpoints += 1;
+                report.unsigned_entries = 0;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+fn hash_entry(input: &EntryHashInput<'_>) -> Result<String, TDKError> {
+    let canonical = serde_json_canonicalizer::to_vec(input)?;
+    Ok(Sha256::digest(canonical)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// Run a file call off the async runtime.
+async fn blocking<T: Send + 'static>(
+    call: impl FnOnce() -> Result<T, TDKError> + Send + 'static,
+) -> Result<T, TDKError> {
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| TDKError::Audit(format!("audit log file call failed: {e}")))?
+}
+
+async fn append_record(file: &Arc<File>, record: &AuditRecord) -> Result<(), TDKError> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    let file = file.clone();
+    blocking(move || {
+        (&*file).write_all(&line)?;
+        file.sync_data()?;
+        Ok(())
+    })
+    .await
+}
+
+async fn read_records(path: &Path) -> Result<Vec<AuditRecord>, TDKError> {
+    let path = path.to_path_buf();
+    let contents = match blocking(move || Ok(std::fs::read_to_string(path))).await? {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut records = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(line).map_err(|e| {
+            TDKError::Audit(format!(
+                "line {} is not a valid audit record: {e}",
+                number + 1
+            ))
+        })?);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use affinidi_secrets_resolver::secrets::Secret;
+
+    fn device_key() -> Secret {
+        Secret::generate_ed25519(Some("did:example:device#key-0"), Some(&[7; 32]))
+    }
+
+    fn key_created(n: u32) -> AuditEvent {
+        AuditEvent::KeyCreated {
+            key_id: format!("did:example:alice#key-{n}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn entries_chain_and_checkpoints_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let key = device_key();
+        let public_key = key.get_public_bytes().to_vec();
+
+        let log = AuditLog::builder(&path)
+            .with_signer(Arc::new(key))
+            .with_checkpoint_interval(2)
+            .open()
+            .await
+            .unwrap();
+        for n in 0..5 {
+            log.record(key_created(n)).await.unwrap();
+        }
+
+        let report = log.verify(Some(&public_key)).await.unwrap();
+        assert_eq!(report.entries, 5);
+        assert_eq!(report.checkpoints, 2);
+        assert_eq!(report.unsigned_entries, 1);
+        assert_eq!(report.signatures, SignatureStatus::Verified);
+
+        assert!(log.checkpoint().await.unwrap().is_some());
+        assert!(log.checkpoint().await.unwrap().is_none());
+        let report = AuditLog::verify_file(&path, Some(&public_key))
+            .await
+            .unwrap();
+        assert_eq!(report.unsigned_entries, 0);
+    }
+
+    #[tokio::test]
+    async fn a_log_without_checkpoints_is_not_reported_as_signed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let public_key = device_key().get_public_bytes().to_vec();
+
+        let log = AuditLog::builder(&path)
+            .with_signer(Arc::new(device_key()))
+            .open()
+            .await
+            .unwrap();
+        log.record(key_created(0)).await.unwrap();
+
+        let report = log.verify(Some(&public_key)).await.unwrap();
+        assert_eq!(report.checkpoints, 0);
+        assert_eq!(report.signatures, SignatureStatus::Unsigned);
+        let report = log.verify(None).await.unwrap();
+        assert_eq!(report.signatures, SignatureStatus::NotChecked);
+    }
+
+    #[tokio::test]
+    async fn reopen_continues_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let log = AuditLog::builder(&path).open().await.unwrap();
+        let first = log.record(key_created(0)).await.unwrap();
+        drop(log);
+
+        let log = AuditLog::builder(&path).open().await.unwrap();
+        let second = log.record(key_created(1)).await.unwrap();
+        assert_eq!(second.sequence, 1);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(log.export(1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let log = AuditLog::builder(&path).open().await.unwrap();
+        for n in 0..3 {
+            log.record(key_created(n)).await.unwrap();
+        }
+
+        let mut records = log.export(0).await.unwrap();
+        if let AuditRecord::Entry(entry) = &mut records[1] {
+            entry.event = key_created(99);
+        }
+        assert!(matches!(
+            AuditLog::verify_records(&records, None),
+            Err(TDKError::Audit(_))
+        ));
+
+        let mut records = log.export(0).await.unwrap();
+        records.remove(1);
+        assert!(AuditLog::verify_records(&records, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn forged_checkpoint_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let forger = Secret::generate_ed25519(Some("did:example:device#key-0"), Some(&[9; 32]));
+
+        let log = AuditLog::builder(&path)
+            .with_signer(Arc::new(forger))
+            .with_checkpoint_interval(1)
+            .open()
+            .await
+            .unwrap();
+        log.record(key_created(0)).await.unwrap();
+
+        let public_key = device_key().get_public_bytes().to_vec();
+        assert!(log.verify(None).await.is_ok());
+        assert!(log.verify(Some(&public_key)).await.is_err());
+    }
+}
@@ -0,0 +1,261 @@
+/*!
+ * Profile capabilities: which services a profile has been onboarded to.
+ *
+ * Apps otherwise keep asking "is this DID registered with the mediator yet?"
+ * before every flow. A [`Capability`] names one onboarding — an account on a
+ * mediator, registration with a Meeting Place service, or a DID
+ * authentication that succeeded against a service — and the
+ * [`CapabilityRegistry`] records, per profile DID, when each was last seen to
+ * work.
+ *
+ * The TDK maintains the registry as it works: every successful DID
+ * authentication records [`Capability::Authentication`], the messaging SDK
+ * records [`Capability::MediatorAccount`] once the mediator accepts the
+ * profile, and the Meeting Place client records [`Capability::MeetingPlace`].
+ * Read it with [`crate::TDKSharedState::capabilities`], and onboard whatever
+ * is missing with [`crate::TDKSharedState::ensure_capability`].
+ *
+ * ```
+ * use affinidi_tdk_common::capabilities::{Capability, CapabilityRegistry};
+ *
+ * let registry = CapabilityRegistry::default();
+ * let mediator = Capability::MediatorAccount {
+ *     mediator: "did:web:mediator.example.com".to_string(),
+ * };
+ * assert!(!registry.has("did:example:alice", &mediator));
+ *
+ * registry.record("did:example:alice", mediator.clone());
+ * assert!(registry.has("did:example:alice", &mediator));
+ * ```
+ *
+ * The registry lives in memory. Capabilities persist with the profile: copy
+ * them onto a [`TDKProfile`](crate::profiles::TDKProfile) with
+ * [`crate::TDKSharedState::sync_capabilities`] before saving the environment,
+ * and they are loaded back when the profile is added to the TDK.
+ */
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A service a profile can be onboarded to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum Capability {
+    /// The profile has an account on this mediator
+    MediatorAccount {
+        /// DID of the mediator
+        mediator: String,
+    },
+    /// The profile is registered with this Meeting Place service
+    MeetingPlace {
+        /// DID of the Meeting Place service
+        service: String,
+    },
+    /// DID authentication against this service has succeeded
+    Authentication {
+        /// DID of the service
+        service: String,
+    },
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::MediatorAccount { mediator } => write!(f, "mediator account ({mediator})"),
+            Capability::MeetingPlace { service } => write!(f, "meeting place ({service})"),
+            Capability::Authentication { service } => write!(f, "authentication ({service})"),
+        }
+    }
+}
+
+/// A capability and when it was last seen to work.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityRecord {
+    #[serde(flatten)]
+    pub capability: Capability,
+    /// Unix timestamp (seconds) the capability was last confirmed
+    pub verified_at: u64,
+}
+
+/// Capabilities of every profile, keyed by profile DID.
+///
+/// Cloning is cheap; clones share the same records.
+#[derive(Clone, Default)]
+pub struct CapabilityRegistry {
+    profiles: Arc<RwLock<HashMap<String, BTreeMap<Capability, u64>>>>,
+}
+
+impl CapabilityRegistry {
+    /// Record that `capability` works for `profile_did` as of now.
+    pub fn record(&self, profile_did: &str, capability: Capability) {
+        self.write()
+            .entry(profile_did.to_string())
+            .or_default()
+            .insert(capability, now());
+    }
+
+    /// Load capabilities persisted with a profile. A record already held is
+    /// kept if it is more recent.
+    pub fn load(&self, profile_did: &str, records: &[CapabilityRecord]) {
+        let mut profiles = self.write();
+        let capabilities = profiles.entry(profile_did.to_string()).or_default();
+        for record in records {
+            let verified_at = capabilities
+                .entry(record.capability.clone())
+                .or_insert(record.verified_at);
+            *verified_at = (*verified_at).max(record.verified_at);
+        }
+    }
+
+    /// Forget `capability` for `profile_did`, e.g. after its mediator account
+    /// was removed. Returns `true` if it was recorded.
+    pub fn revoke(&self, profile_did: &str, capability: &Capability) -> bool {
+        self.write()
+            .get_mut(profile_did)
+            .is_some_and(|capabilities| capabilities.remove(capability).is_some())
+    }
+
+    /// Whether `capability` is recorded for `profile_did`.
+    pub fn has(&self, profile_did: &str, capability: &Capability) -> bool {
+        self.verified_at(profile_did, capability).is_some()
+    }
+
+    /// When `capability` was last confirmed for `profile_did`.
+    pub fn verified_at(&self, profile_did: &str, capability: &Capability) -> Option<u64> {
+        self.read()
+            .get(profile_did)
+            .and_then(|capabilities| capabilities.get(capability).copied())
+    }
+
+    /// Every capability recorded for `profile_did`, in a stable order.
+    pub fn capabilities(&self, profile_did: &str) -> Vec<CapabilityRecord> {
+        self.read()
+            .get(profile_did)
+            .map(|capabilities| {
+                capabilities
+                    .iter()
+                    .map(|(capability, verified_at)| CapabilityRecord {
+                        capability: capability.clone(),
+                        verified_at: *verified_at,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Forget every capability of `profile_did`.
+    pub fn clear(&self, profile_did: &str) {
+        self.write().remove(profile_did);
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, BTreeMap<Capability, u64>>> {
+        self.profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<String, BTreeMap<Capability, u64>>> {
+        self.profiles
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mediator() -> Capability {
+        Capability::MediatorAccount {
+            mediator: "did:web:mediator.example.com".to_string(),
+        }
+    }
+
+    #[test]
+    fn capabilities_are_per_profile() {
+        let registry = CapabilityRegistry::default();
+        registry.record("did:example:a", mediator());
+
+        assert!(registry.has("did:example:a", &mediator()));
+        assert!(!registry.has("did:example:b", &mediator()));
+        assert!(registry.capabilities("did:example:b").is_empty());
+    }
+
+    #[test]
+    fn revoke_forgets_one_capability() {
+        let registry = CapabilityRegistry::default();
+        let auth = Capability::Authentication {
+            service: "did:web:service.example.com".to_string(),
+        };
+        registry.record("did:example:a", mediator());
+        registry.record("did:example:a", auth.clone());
+
+        assert!(registry.revoke("did:example:a", &mediator()));
+        assert!(!registry.revoke("did:example:a", &mediator()));
+        assert!(registry.has("did:example:a", &auth));
+    }
+
+    #[test]
+    fn load_keeps_the_most_recent_record() {
+        let registry = CapabilityRegistry::default();
+        registry.record("did:example:a", mediator());
+        let recorded = registry.verified_at("did:example:a", &mediator()).unwrap();
+
+        registry.load(
+            "did:example:a",
+            &[CapabilityRecord {
+                capability: mediator(),
+                verified_at: 1,
+            }],
+        );
+        assert_eq!(
+            registry.verified_at("did:example:a", &mediator()),
+            Some(recorded)
+        );
+
+        registry.load(
+            "did:example:b",
+            &[CapabilityRecord {
+                capability: mediator(),
+                verified_at: 1,
+            }],
+        );
+        assert_eq!(registry.verified_at("did:example:b", &mediator()), Some(1));
+    }
+
+    #[test]
+    fn records_serialise_flat() {
+        let record = CapabilityRecord {
+            capability: Capability::MeetingPlace {
+                service: "did:web:mp.example.com".to_string(),
+            },
+            verified_at: 42,
+        };
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "meetingPlace",
+                "service": "did:web:mp.example.com",
+                "verifiedAt": 42,
+            })
+        );
+        let back: CapabilityRecord = serde_json::from_value(json).unwrap();
+        assert_eq!(back, record);
+    }
+}
@@ -6,11 +6,14 @@
  * [`TDKConfig`] (e.g. [`TDKConfig::environment_path`]).
  */
 
-use affinidi_did_authentication::CustomAuthHandlers;
+use affinidi_did_authentication::{CustomAuthHandlers, ServiceVerification};
 use affinidi_did_resolver_cache_sdk::{DIDCacheClient, config::DIDCacheConfig};
-use affinidi_secrets_resolver::ThreadedSecretsResolver;
+use affinidi_secrets_resolver::usage::KeyUsagePolicy;
 
-use crate::{environments::TDKEnvironment, errors::TDKError};
+use crate::{
+    audit::AuditLog, environments::TDKEnvironment, errors::TDKError, secrets::TDKSecretsResolver,
+    unlock::UnlockFactors,
+};
 
 const DEFAULT_ENVIRONMENT_PATH: &str = "environments.json";
 
@@ -22,18 +25,23 @@ const DEFAULT_ENVIRONMENT_PATH: &str = "environments.json";
 pub struct TDKConfig {
     pub(crate) did_resolver: Option<DIDCacheClient>,
     pub(crate) did_resolver_config: Option<DIDCacheConfig>,
-    pub(crate) secrets_resolver: Option<ThreadedSecretsResolver>,
+    pub(crate) secrets_resolver: Option<TDKSecretsResolver>,
+    pub(crate) key_usage_policy: Option<KeyUsagePolicy>,
     pub(crate) environment_path: String,
     pub(crate) load_environment: bool,
+    pub(crate) strict_environment: bool,
+    pub(crate) environment_unlock: Option<UnlockFactors>,
     pub(crate) environment_name: String,
     pub(crate) authentication_cache_limit: usize,
     pub(crate) use_atm: bool,
     pub(crate) custom_auth_handlers: Option<CustomAuthHandlers>,
+    pub(crate) service_verification: ServiceVerification,
     /// Pre-built environment supplied via
     /// [`TDKConfigBuilder::with_environment`]. When `Some`, takes priority
     /// over the file-load path at [`crate::TDKSharedState::new`] time and
     /// `load_environment` is ignored.
     pub(crate) prebuilt_environment: Option<TDKEnvironment>,
+    pub(crate) audit_log: Option<AuditLog>,
 }
 
 impl TDKConfig {
@@ -96,10 +104,15 @@ impl TDKConfig {
     }
 
     /// Pre-built `SecretsResolver`, if one was supplied to the builder.
-    pub fn secrets_resolver(&self) -> Option<&ThreadedSecretsResolver> {
+    pub fn secrets_resolver(&self) -> Option<&TDKSecretsResolver> {
         self.secrets_resolver.as_ref()
     }
 
+    /// Key usage policy applied to the secrets resolver, if set.
+    pub fn key_usage_policy(&self) -> Option<KeyUsagePolicy> {
+        self.key_usage_policy
+    }
+
     /// Path to the on-disk environment file.
     pub fn environment_path(&self) -> &str {
         &self.environment_path
@@ -111,6 +124,16 @@ impl TDKConfig {
         self.load_environment
     }
 
+    /// Whether loading the environment file rejects unknown fields.
+    pub fn strict_environment(&self) -> bool {
+        self.strict_environment
+    }
+
+    /// Factors that unlock an encrypted environment file, if set.
+    pub fn environment_unlock(&self) -> Option<&UnlockFactors> {
+        self.environment_unlock.as_ref()
+    }
+
     /// Name of the environment to load on startup.
     pub fn environment_name(&self) -> &str {
         &self.environment_name
@@ -134,12 +157,23 @@ impl TDKConfig {
         self.custom_auth_handlers.as_ref()
     }
 
+    /// How strictly authentication verifies that tokens came from the
+    /// service DID.
+    pub fn service_verification(&self) -> ServiceVerification {
+        self.service_verification
+    }
+
     /// Pre-built environment supplied via
     /// [`TDKConfigBuilder::with_environment`], if any. Wins over the file
     /// loader at [`crate::TDKSharedState::new`] time when present.
     pub fn prebuilt_environment(&self) -> Option<&TDKEnvironment> {
         self.prebuilt_environment.as_ref()
     }
+
+    /// Security-event audit log, if one was supplied to the builder.
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.audit_log.as_ref()
+    }
 }
 
 /// Manual `Debug` impl. The upstream `DIDCacheClient`,
@@ -158,8 +192,11 @@ impl std::fmt::Debug for TDKConfig {
                 "secrets_resolver",
                 &self.secrets_resolver.as_ref().map(|_| "<SecretsResolver>"),
             )
+            .field("key_usage_policy", &self.key_usage_policy)
             .field("environment_path", &self.environment_path)
             .field("load_environment", &self.load_environment)
+            .field("strict_environment", &self.strict_environment)
+            .field("environment_unlock", &self.environment_unlock)
             .field("environment_name", &self.environment_name)
             .field(
                 "authentication_cache_limit",
@@ -173,7 +210,9 @@ impl std::fmt::Debug for TDKConfig {
                     .as_ref()
                     .map(|_| "<CustomAuthHandlers>"),
             )
+            .field("service_verification", &self.service_verification)
             .field("prebuilt_environment", &self.prebuilt_environment)
+            .field("audit_log", &self.audit_log)
             .finish()
     }
 }
@@ -182,14 +221,19 @@ impl std::fmt::Debug for TDKConfig {
 pub struct TDKConfigBuilder {
     did_resolver: Option<DIDCacheClient>,
     did_resolver_config: Option<DIDCacheConfig>,
-    secrets_resolver: Option<ThreadedSecretsResolver>,
+    secrets_resolver: Option<TDKSecretsResolver>,
+    key_usage_policy: Option<KeyUsagePolicy>,
     environment_path: Option<String>,
     load_environment: bool,
+    strict_environment: bool,
+    environment_unlock: Option<UnlockFactors>,
     environment_name: Option<String>,
     authentication_cache_limit: usize,
     use_atm: bool,
     custom_auth_handlers: Option<CustomAuthHandlers>,
+    service_verification: ServiceVerification,
     prebuilt_environment: Option<TDKEnvironment>,
+    audit_log: Option<AuditLog>,
 }
 
 impl Default for TDKConfigBuilder {
@@ -198,13 +242,18 @@ impl Default for TDKConfigBuilder {
             did_resolver: None,
             did_resolver_config: None,
             secrets_resolver: None,
+            key_usage_policy: None,
             environment_path: None,
             load_environment: true,
+            strict_environment: false,
+            environment_unlock: None,
             environment_name: None,
             authentication_cache_limit: 1_000,
             use_atm: true,
             custom_auth_handlers: None,
+            service_verification: ServiceVerification::default(),
             prebuilt_environment: None,
+            audit_log: None,
         }
     }
 }
@@ -216,17 +265,22 @@ impl TDKConfigBuilder {
             did_resolver: self.did_resolver,
             did_resolver_config: self.did_resolver_config,
             secrets_resolver: self.secrets_resolver,
+            key_usage_policy: self.key_usage_policy,
             environment_path: self
                 .environment_path
                 .unwrap_or_else(|| DEFAULT_ENVIRONMENT_PATH.to_string()),
             load_environment: self.load_environment,
+            strict_environment: self.strict_environment,
+            environment_unlock: self.environment_unlock,
             environment_name: self
                 .environment_name
                 .unwrap_or_else(|| "default".to_string()),
             authentication_cache_limit: self.authentication_cache_limit,
             use_atm: self.use_atm,
             custom_auth_handlers: self.custom_auth_handlers,
+            service_verification: self.service_verification,
             prebuilt_environment: self.prebuilt_environment,
+            audit_log: self.audit_log,
         })
     }
 
@@ -248,10 +302,24 @@ impl TDKConfigBuilder {
         self
     }
 
-    /// Supply a pre-built `SecretsResolver`. If absent, a fresh empty
-    /// in-memory resolver is created at `TDKSharedState::new`.
-    pub fn with_secrets_resolver(mut self, secrets_resolver: ThreadedSecretsResolver) -> Self {
-        self.secrets_resolver = Some(secrets_resolver);
+    /// Supply a pre-built `SecretsResolver`: a `ThreadedSecretsResolver`, or a
+    /// [`KeychainSecretsResolver`](crate::secrets::KeychainSecretsResolver) to
+    /// keep keys in the OS keychain. If absent, a fresh empty in-memory
+    /// resolver is created at `TDKSharedState::new`.
+    pub fn with_secrets_resolver(
+        mut self,
+        secrets_resolver: impl Into<TDKSecretsResolver>,
+    ) -> Self {
+        self.secrets_resolver = Some(secrets_resolver.into());
+        self
+    }
+
+    /// Whether a key used for the wrong purpose (signing with a key-agreement
+    /// key, or the reverse) is refused or only logged. Applies to the
+    /// secrets resolver the TDK creates or is given; when unset, a supplied
+    /// resolver keeps its own policy and a created one is permissive.
+    pub fn with_key_usage_policy(mut self, policy: KeyUsagePolicy) -> Self {
+        self.key_usage_policy = Some(policy);
         self
     }
 
@@ -268,6 +336,23 @@ impl TDKConfigBuilder {
         self
     }
 
+    /// Fail to load an environment file that has fields this version of the
+    /// TDK doesn't know, instead of logging and ignoring them. Defaults to
+    /// `false`. See
+    /// [`TDKEnvironments::load_file_strict`](crate::environments::TDKEnvironments::load_file_strict).
+    pub fn with_strict_environment(mut self, strict_environment: bool) -> Self {
+        self.strict_environment = strict_environment;
+        self
+    }
+
+    /// Open the environment file as an encrypted file, unlocked with
+    /// `factors`. See
+    /// [`TDKEnvironments::load_encrypted_file`](crate::environments::TDKEnvironments::load_encrypted_file).
+    pub fn with_environment_unlock(mut self, factors: UnlockFactors) -> Self {
+        self.environment_unlock = Some(factors);
+        self
+    }
+
     /// Name of the environment to load on startup. Defaults to `"default"`.
     pub fn with_environment_name(mut self, environment_name: String) -> Self {
         self.environment_name = Some(environment_name);
@@ -295,6 +380,15 @@ impl TDKConfigBuilder {
         self
     }
 
+    /// How strictly to verify that authentication tokens came from the
+    /// service DID. Defaults to [`ServiceVerification::Preferred`]; use
+    /// [`ServiceVerification::Required`] to refuse services that can't seal
+    /// their tokens.
+    pub fn with_service_verification(mut self, verification: ServiceVerification) -> Self {
+        self.service_verification = verification;
+        self
+    }
+
     /// Supply a pre-built [`TDKEnvironment`] instead of loading one from disk.
     ///
     /// Takes priority over [`with_environment_path`](Self::with_environment_path)
@@ -306,6 +400,13 @@ impl TDKConfigBuilder {
         self.prebuilt_environment = Some(environment);
         self
     }
+
+    /// Record security events (authentication outcomes, etc.) to an
+    /// append-only [`AuditLog`]. Disabled by default.
+    pub fn with_audit_log(mut self, audit_log: AuditLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -320,6 +421,8 @@ mod tests {
         assert_eq!(cfg.authentication_cache_limit(), 1_000);
         assert!(cfg.use_atm());
         assert!(cfg.load_environment());
+        assert_eq!(cfg.key_usage_policy(), None);
+        assert_eq!(cfg.service_verification(), ServiceVerification::Preferred);
     }
 
     #[test]
@@ -329,11 +432,15 @@ mod tests {
             .with_environment_name("prod".to_string())
             .with_authentication_cache_limit(50)
             .with_load_environment(false)
+            .with_key_usage_policy(KeyUsagePolicy::Strict)
+            .with_service_verification(ServiceVerification::Required)
             .build()
             .unwrap();
         assert_eq!(cfg.environment_path(), "custom.json");
+        assert_eq!(cfg.key_usage_policy(), Some(KeyUsagePolicy::Strict));
         assert_eq!(cfg.environment_name(), "prod");
         assert_eq!(cfg.authentication_cache_limit(), 50);
         assert!(!cfg.load_environment());
+        assert_eq!(cfg.service_verification(), ServiceVerification::Required);
     }
 }
@@ -0,0 +1,567 @@
+/*!
+ * Signed consent receipts for credential presentations.
+ *
+ * When a wallet presents credentials, it records what it shared, with whom,
+ * when and for what purpose as a [`ConsentReceipt`]: a document signed with
+ * the holder's key using a W3C Data Integrity proof. The receipt id is the
+ * SHA-256 of the receipt's contents, so any edit is detectable even without
+ * the key.
+ *
+ * Receipts are kept in a [`ConsentStore`], a JSON Lines file with one receipt
+ * per line, and exported from there for the user or an auditor. File access
+ * runs on tokio's blocking pool.
+ *
+ * With the `vc` or `openid4vp` feature, `affinidi_tdk::consent` builds the
+ * receipt from the presentation that was sent, and `TDK::record_consent`
+ * signs it with a profile's key and records it.
+ *
+ * ```no_run
+ * use affinidi_tdk_common::consent::{ConsentReceipt, ConsentStore, SharedCredential};
+ * use affinidi_secrets_resolver::secrets::Secret;
+ *
+ * # async fn demo() -> Result<(), affinidi_tdk_common::errors::TDKError> {
+ * let holder_key = Secret::generate_ed25519(Some("did:key:z6Mk...#z6Mk..."), None);
+ * let store = ConsentStore::open("consent.jsonl").await?;
+ *
+ * // After sending the presentation
+ * let receipt = ConsentReceipt::builder(
+ *     "did:key:z6Mk...",
+ *     "https://verifier.example.com",
+ *     "Age verification for account opening",
+ * )
+ * .share(
+ *     SharedCredential::new("vc+sd-jwt")
+ *         .with_types(["IdentityCredential"])
+ *         .with_disclosed(["birthdate"]),
+ * )
+ * .with_reference("oid4vp-state-123")
+ * .sign(&holder_key)
+ * .await?;
+ * store.record(&receipt).await?;
+ *
+ * let json = store.export_json(Some("https://verifier.example.com")).await?;
+ * # Ok(()) }
+ * ```
+ */
+
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use affinidi_data_integrity::{DataIntegrityProof, SignOptions, VerifyOptions, signer::Signer};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::errors::TDKError;
+
+/// Proof purpose used for receipt signatures
+const RECEIPT_PROOF_PURPOSE: &str = "assertionMethod";
+
+/// Prefix of a receipt id; the rest is the hex SHA-256 of its contents
+const RECEIPT_ID_PREFIX: &str = "urn:sha256:";
+
+/// One credential included in a presentation
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct SharedCredential {
+    /// Credential id, if it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Credential format, e.g. `vc+sd-jwt`, `ldp_vc` or `mso_mdoc`
+    pub format: String,
+
+    /// Credential types, e.g. `IdentityCredential`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+
+    /// Claims revealed to the recipient. Empty when the whole credential was
+    /// shared.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disclosed: Vec<String>,
+}
+
+impl SharedCredential {
+    pub fn new(format: impl Into<String>) -> Self {
+        Self {
+            format: format.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    pub fn with_types<I, S>(mut self, types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.types = types.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_disclosed<I, S>(mut self, claims: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.disclosed = claims.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// A signed record of the holder consenting to a presentation
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct ConsentReceipt {
+    /// `urn:sha256:` followed by the hex SHA-256 over the JCS form of the
+    /// receipt's contents (every field except `id` and `proof`)
+    pub id: String,
+
+    /// Seconds since the UNIX epoch
+    pub timestamp: u64,
+
+    /// DID of the holder who consented
+    pub holder: String,
+
+    /// Who the credentials were shared with: a verifier DID, or the client id
+    /// of an OpenID4VP verifier
+    pub recipient: String,
+
+    /// Purpose of the presentation, as shown to the holder
+    pub purpose: String,
+
+    pub shared: Vec<SharedCredential>,
+
+    /// Identifier of the exchange, e.g. an OpenID4VP `state` or a DIDComm
+    /// thread id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+
+    /// Data Integrity proof over every other field, by the holder's key
+    pub proof: DataIntegrityProof,
+}
+
+/// Fields of a [`ConsentReceipt`] covered by its id
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptContents<'a> {
+    timestamp: u64,
+    holder: &'a str,
+    recipient: &'a str,
+    purpose: &'a str,
+    shared: &'a [SharedCredential],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference: Option<&'a str>,
+}
+
+/// Document signed by a [`ConsentReceipt`]: its contents plus its id
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedReceipt<'a> {
+    id: &'a str,
+    #[serde(flatten)]
+    contents: ReceiptContents<'a>,
+}
+
+impl ConsentReceipt {
+    /// Returns a [`ConsentReceiptBuilder`] for a presentation by `holder` to
+    /// `recipient`.
+    pub fn builder(
+        holder: impl Into<String>,
+        recipient: impl Into<String>,
+        purpose: impl Into<String>,
+    ) -> ConsentReceiptBuilder {
+        ConsentReceiptBuilder {
+            holder: holder.into(),
+            recipient: recipient.into(),
+            purpose: purpose.into(),
+            shared: Vec::new(),
+            reference: None,
+            timestamp: None,
+        }
+    }
+
+    fn contents(&self) -> ReceiptContents<'_> {
+        ReceiptContents {
+            timestamp: self.timestamp,
+            holder: &self.holder,
+            recipient: &self.recipient,
+            purpose: &self.purpose,
+            shared: &self.shared,
+            reference: self.reference.as_deref(),
+        }
+    }
+
+    /// Checks that the id matches the contents, that the proof was made by a
+    /// key of the holder and that it verifies against `public_key`.
+    ///
+    /// `public_key` must be the key `proof.verificationMethod` names; resolve
+    /// it from the holder's DID document.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TDKError::Consent`] if the receipt has been modified, was
+    /// signed by someone other than the holder, or the signature is invalid.
+    pub fn verify_with_public_key(&self, public_key: &[u8]) -> Result<(), TDKError> {
+        if receipt_id(&self.contents())? != self.id {
+            return Err(TDKError::Consent(format!(
+                "receipt ({}) has been modified (id mismatch)",
+                self.id
+            )));
+        }
+
+        let signer = self
+            .proof
+            .verification_method
+            .split_once('#')
+            .map_or(self.proof.verification_method.as_str(), |(did, _)| did);
+        if signer != self.holder {
+            return Err(TDKError::Consent(format!(
+                "receipt ({}) was signed by {} rather than its holder ({})",
+                self.id, self.proof.verification_method, self.holder
+            )));
+        }
+
+        self.proof
+            .verify_with_public_key(
+                &SignedReceipt {
+                    id: &self.id,
+                    contents: self.contents(),
+                },
+                public_key,
+                VerifyOptions::new(),
+            )
+            .map_err(|e| {
+                TDKError::Consent(format!(
+                    "receipt ({}) has an invalid signature: {e}",
+                    self.id
+                ))
+            })
+    }
+}
+
+/// Builder for [`ConsentReceipt`]. Construct via [`ConsentReceipt::builder`].
+#[derive(Clone, Debug)]
+pub struct ConsentReceiptBuilder {
+    holder: String,
+    recipient: String,
+    purpose: String,
+    shared: Vec<SharedCredential>,
+    reference: Option<String>,
+    timestamp: Option<u64>,
+}
+
+impl ConsentReceiptBuilder {
+    /// Adds a credential included in the presentation.
+    pub fn share(mut self, credential: SharedCredential) -> Self {
+        self.shared.push(credential);
+        self
+    }
+
+    /// Identifier of the exchange, e.g. an OpenID4VP `state` or a DIDComm
+    /// thread id.
+    pub fn with_reference(mut self, reference: impl Into<String>) -> Self {
+        self.reference = Some(reference.into());
+        self
+    }
+
+    /// When the presentation was sent, in seconds since the UNIX epoch.
+    /// Defaults to the time of signing.
+    pub fn with_timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Signs the receipt with the holder's key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TDKError::Consent`] if nothing was shared, and
+    /// [`TDKError::DataIntegrity`] if signing fails.
+    pub async fn sign(self, signer: &dyn Signer) -> Result<ConsentReceipt, TDKError> {
+        if self.shared.is_empty() {
+            return Err(TDKError::Consent(
+                "a consent receipt must list at least one shared credential".to_string(),
+            ));
+        }
+
+        let timestamp = self.timestamp.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        });
+        let contents = ReceiptContents {
+            timestamp,
+            holder: &self.holder,
+            recipient: &self.recipient,
+            purpose: &self.purpose,
+            shared: &self.shared,
+            reference: self.reference.as_deref(),
+        };
+        let id = receipt_id(&contents)?;
+        let proof = DataIntegrityProof::sign(
+            &SignedReceipt { id: &id, contents },
+            signer,
+            SignOptions::new().with_proof_purpose(RECEIPT_PROOF_PURPOSE),
+        )
+        .await?;
+
+        Ok(ConsentReceipt {
+            id,
+            timestamp,
+            holder: self.holder,
+            recipient: self.recipient,
+            purpose: self.purpose,
+            shared: self.shared,
+            reference: self.reference,
+            proof,
+        })
+    }
+}
+
+/// Append-only store of consent receipts. Cloning is cheap; clones share the
+/// same file handle.
+#[derive(Clone)]
+pub struct ConsentStore {
+    path: PathBuf,
+    file: Arc<Mutex<Arc<File>>>,
+}
+
+impl std::fmt::Debug for ConsentStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsentStore")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl ConsentStore {
+    /// Opens (or creates) the store at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TDKError::Consent`] if an existing file holds a line that
+    /// isn't a receipt, and [`TDKError::Io`] on file errors.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self, TDKError> {
+        let path = path.into();
+        read_receipts(&path).await?;
+
+        let file = {
+            let path = path.clone();
+            blocking(move || Ok(OpenOptions::new().create(true).append(true).open(path)?)).await?
+        };
+
+        Ok(Self {
+            path,
+            file: Arc::new(Mutex::new(Arc::new(file))),
+        })
+    }
+
+    /// Path of the store file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `receipt` to the store. The line is flushed and synced to disk
+    /// before returning.
+    pub async fn record(&self, receipt: &ConsentReceipt) -> Result<(), TDKError> {
+        let file = self.file.lock().await;
+        let mut line = serde_json::to_vec(receipt)?;
+        line.push(b'\n');
+        let handle = file.clone();
+        blocking(move || {
+            (&*handle).write_all(&line)?;
+            handle.sync_data()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Every receipt, oldest first.
+    pub async fn receipts(&self) -> Result<Vec<ConsentReceipt>, TDKError> {
+        // Hold the lock so a concurrent append can't leave a partial line
+        let _file = self.file.lock().await;
+        read_receipts(&self.path).await
+    }
+
+    /// Looks up a receipt by its id.
+    pub async fn get(&self, id: &str) -> Result<Option<ConsentReceipt>, TDKError> {
+        Ok(self
+            .receipts()
+            .await?
+            .into_iter()
+            .find(|receipt| receipt.id == id))
+    }
+
+    /// The receipts as a pretty-printed JSON array, for handing to the user
+    /// or an auditor. With a `recipient`, only receipts for presentations to
+    /// that recipient are included.
+    pub async fn export_json(&self, recipient: Option<&str>) -> Result<String, TDKError> {
+        let receipts: Vec<ConsentReceipt> = self
+            .receipts()
+            .await?
+            .into_iter()
+            .filter(|receipt| recipient.is_none_or(|recipient| receipt.recipient == recipient))
+            .collect();
+        Ok(serde_json::to_string_pretty(&receipts)?)
+    }
+}
+
+fn receipt_id(contents: &ReceiptContents<'_>) -> Result<String, TDKError> {
+    let canonical = serde_json_canonicalizer::to_vec(contents)?;
+    let hash: String = Sha256::digest(canonical)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    Ok(format!("{RECEIPT_ID_PREFIX}{hash}"))
+}
+
+/// Runs blocking file IO off the async runtime
+async fn blocking<T: Send + 'static>(
+    call: impl FnOnce() -> Result<T, TDKError> + Send + 'static,
+) -> Result<T, TDKError> {
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| TDKError::Consent(format!("consent store file call failed: {e}")))?
+}
+
+async fn read_receipts(path: &Path) -> Result<Vec<ConsentReceipt>, TDKError> {
+    let path = path.to_path_buf();
+    let contents = match blocking(move || Ok(std::fs::read_to_string(path))).await? {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut receipts = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        receipts.push(serde_json::from_str(line).map_err(|e| {
+            TDKError::Consent(format!(
+                "line {} is not a valid consent receipt: {e}",
+                number + 1
+            ))
+        })?);
+    }
+    Ok(receipts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use affinidi_secrets_resolver::secrets::Secret;
+
+    const VERIFIER: &str = "https://verifier.example.com";
+
+    fn holder_key() -> Secret {
+        Secret::generate_ed25519(Some("did:example:alice#key-0"), Some(&[7; 32]))
+    }
+
+    async fn receipt(recipient: &str) -> ConsentReceipt {
+        ConsentReceipt::builder("did:example:alice", recipient, "Age verification")
+            .share(
+                SharedCredential::new("vc+sd-jwt")
+                    .with_types(["IdentityCredential"])
+                    .with_disclosed(["birthdate"]),
+            )
+            .with_reference("state-123")
+            .sign(&holder_key())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn receipt_verifies() {
+        let key = holder_key();
+        let receipt = receipt(VERIFIER).await;
+
+        assert!(receipt.id.starts_with(RECEIPT_ID_PREFIX));
+        receipt
+            .verify_with_public_key(key.get_public_bytes())
+            .unwrap();
+
+        let other = Secret::generate_ed25519(Some("did:example:bob#key-0"), Some(&[8; 32]));
+        assert!(matches!(
+            receipt.verify_with_public_key(other.get_public_bytes()),
+            Err(TDKError::Consent(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn tampering_is_detected() {
+        let key = holder_key();
+        let mut receipt = receipt(VERIFIER).await;
+        receipt.shared[0].disclosed.push("address".to_string());
+
+        let err = receipt
+            .verify_with_public_key(key.get_public_bytes())
+            .unwrap_err();
+        assert!(err.to_string().contains("id mismatch"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn receipt_signed_by_someone_else_is_rejected() {
+        let mallory = Secret::generate_ed25519(Some("did:example:mallory#key-0"), Some(&[9; 32]));
+        let receipt = ConsentReceipt::builder("did:example:alice", VERIFIER, "Age verification")
+            .share(SharedCredential::new("vc+sd-jwt"))
+            .sign(&mallory)
+            .await
+            .unwrap();
+
+        let err = receipt
+            .verify_with_public_key(mallory.get_public_bytes())
+            .unwrap_err();
+        assert!(err.to_string().contains("rather than its holder"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn empty_receipt_is_rejected() {
+        let result = ConsentReceipt::builder("did:example:alice", VERIFIER, "Nothing")
+            .sign(&holder_key())
+            .await;
+        assert!(matches!(result, Err(TDKError::Consent(_))));
+    }
+
+    #[tokio::test]
+    async fn store_records_and_exports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("consent.jsonl");
+        let key = holder_key();
+
+        let store = ConsentStore::open(&path).await.unwrap();
+        let first = receipt(VERIFIER).await;
+        store.record(&first).await.unwrap();
+        store
+            .record(&receipt("did:example:other").await)
+            .await
+            .unwrap();
+        drop(store);
+
+        let store = ConsentStore::open(&path).await.unwrap();
+        assert_eq!(store.receipts().await.unwrap().len(), 2);
+
+        let stored = store.get(&first.id).await.unwrap().unwrap();
+        stored
+            .verify_with_public_key(key.get_public_bytes())
+            .unwrap();
+
+        let exported: Vec<ConsentReceipt> =
+            serde_json::from_str(&store.export_json(Some(VERIFIER)).await.unwrap()).unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].id, first.id);
+    }
+}
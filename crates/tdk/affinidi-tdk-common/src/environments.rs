@@ -14,20 +14,64 @@
  *
  * Environments are grouped on disk via [`TDKEnvironments`], a JSON
  * top-level keyed by environment name (e.g. `"local"`, `"dev"`, `"prod"`).
+ *
+ * # Schema versions
+ *
+ * The file carries a `version` field ([`ENVIRONMENTS_SCHEMA_VERSION`]; files
+ * written before it existed are version 0). [`TDKEnvironments::load_file`]
+ * upgrades older files through [`MIGRATIONS`], keeping a copy of the original
+ * next to it as `<file>.v<N>.bak`, and refuses files from a newer version
+ * rather than dropping the fields it doesn't know. Unknown fields are logged;
+ * [`TDKEnvironments::load_file_strict`] rejects them instead.
+ *
+ * # Encryption
+ *
+ * The file holds profile secrets. [`TDKEnvironments::encrypt_with`] seals it
+ * on every later save under a key derived from a passphrase and, optionally,
+ * a FIDO2 security key (see [`crate::unlock`]); open it again with
+ * [`TDKEnvironments::load_encrypted_file`], or configure
+ * [`TDKConfigBuilder::with_environment_unlock`](crate::config::TDKConfigBuilder::with_environment_unlock)
+ * for [`crate::TDKSharedState`] to do so.
 */
 
 use crate::{
     errors::{Result, TDKError},
     profiles::TDKProfile,
+    unlock::{Envelope, EnvironmentKey, UnlockFactors},
 };
 use rustls::pki_types::CertificateDer;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{
     collections::HashMap,
     fs::File,
     io::{BufReader, Write},
     path::Path,
+    sync::Arc,
 };
+use tracing::{info, warn};
+
+/// Schema version of the environments file this build reads and writes.
+pub const ENVIRONMENTS_SCHEMA_VERSION: u32 = 1;
+
+/// One step of the environments-file upgrade path.
+pub struct Migration {
+    /// Version this step upgrades from, to `from + 1`
+    pub from: u32,
+    /// What the step changes, for logs
+    pub description: &'static str,
+    /// Rewrites the file's JSON in place
+    pub migrate: fn(&mut Value) -> Result<()>,
+}
+
+/// Upgrade steps, one per version, in order. When the file format changes,
+/// bump [`ENVIRONMENTS_SCHEMA_VERSION`] and append the step that rewrites the
+/// previous version's JSON.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add the schema version field",
+    migrate: |_| Ok(()),
+}];
 
 /// A named environment: a bag of profiles plus environment-level defaults
 /// (mediator, admin identity, custom TLS roots).
@@ -79,6 +123,19 @@ impl TDKEnvironment {
         self.profiles.remove(alias)
     }
 
+    /// Drop the secret `key_id` from every profile of this environment,
+    /// including the admin profile. Save the environments file afterwards
+    /// to remove it from disk.
+    ///
+    /// Returns `true` if any profile held the secret.
+    pub fn shred_secret(&mut self, key_id: &str) -> bool {
+        let mut found = false;
+        for profile in self.profiles.values_mut().chain(self.admin_did.as_mut()) {
+            found |= profile.shred_secret(key_id);
+        }
+        found
+    }
+
     /// All profiles in this environment, keyed by alias.
     pub fn profiles(&self) -> &HashMap<String, TDKProfile> {
         &self.profiles
@@ -121,8 +178,9 @@ impl TDKEnvironment {
     /// **Disk-persistence warning**: the admin profile is part of the
     /// serialised [`TDKEnvironment`]. If the parent [`TDKEnvironments`] is
     /// later persisted via [`TDKEnvironments::save`], the admin's
-    /// `secrets` `Vec` is written to disk in plaintext JSON. Avoid setting
-    /// an admin profile with active secrets on an environment that will be
+    /// `secrets` `Vec` is written to disk in plaintext JSON unless the file
+    /// is encrypted ([`TDKEnvironments::encrypt_with`]). Avoid setting an
+    /// admin profile with active secrets on an environment that will be
     /// saved unless the destination file is itself protected (filesystem
     /// permissions, encrypted volume, etc).
     pub fn set_admin_did(&mut self, admin_did: Option<TDKProfile>) {
@@ -171,14 +229,32 @@ impl TDKEnvironment {
 
 /// TDK Environments, where each environment is a collection of TDK Profiles.
 /// This can be used to manage for example a local and a remote environment and switch easily between them
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TDKEnvironments {
+    /// Schema version of the file, see [`ENVIRONMENTS_SCHEMA_VERSION`]
+    version: u32,
+
     /// HashMap of profile name to TDKProfile
     environments: HashMap<String, TDKEnvironment>,
 
     /// Used to know location of file if saving changes back to disk
     #[serde(skip)]
     file_name: Option<String>,
+
+    /// Key the file is encrypted with on save, if any
+    #[serde(skip)]
+    key: Option<Arc<EnvironmentKey>>,
+}
+
+impl Default for TDKEnvironments {
+    fn default() -> Self {
+        TDKEnvironments {
+            version: ENVIRONMENTS_SCHEMA_VERSION,
+            environments: HashMap::new(),
+            file_name: None,
+            key: None,
+        }
+    }
 }
 
 impl TDKEnvironments {
@@ -193,10 +269,22 @@ impl TDKEnvironments {
     pub fn fetch_from_file(
         file_path: Option<&str>,
         environment_name: &str,
+    ) -> Result<TDKEnvironment> {
+        Self::fetch(file_path, environment_name, false, None)
+    }
+
+    /// [`fetch_from_file`](Self::fetch_from_file), rejecting unknown fields
+    /// when `strict` is set (see [`load_file_strict`](Self::load_file_strict))
+    /// and decrypting the file with `unlock` when given.
+    pub(crate) fn fetch(
+        file_path: Option<&str>,
+        environment_name: &str,
+        strict: bool,
+        unlock: Option<&UnlockFactors>,
     ) -> Result<TDKEnvironment> {
         let file_path = file_path.unwrap_or("environments.json");
 
-        let environments = TDKEnvironments::load_file(file_path)?;
+        let environments = TDKEnvironments::load(file_path, strict, unlock)?;
 
         if let Some(environment) = environments.environments.get(environment_name) {
             Ok(environment.clone())
@@ -211,26 +299,117 @@ impl TDKEnvironments {
     ///
     /// If the file does not exist, returns an empty [`TDKEnvironments`] with
     /// `file_name` set so a subsequent [`save`](Self::save) creates it.
-    /// Permissions / IO errors and JSON-parse errors propagate as
+    /// Files of an older schema version are migrated and saved back, after
+    /// copying the original to `<path>.v<N>.bak`; files of a newer version
+    /// are rejected. Fields this version doesn't know are logged and
+    /// ignored. Permissions / IO errors and JSON-parse errors propagate as
     /// [`TDKError::Profile`].
     pub fn load_file(path: &str) -> Result<Self> {
+        Self::load(path, false, None)
+    }
+
+    /// [`load_file`](Self::load_file), but fail if the file contains fields
+    /// this version doesn't know instead of ignoring them — they would be
+    /// lost on the next [`save`](Self::save).
+    pub fn load_file_strict(path: &str) -> Result<Self> {
+        Self::load(path, true, None)
+    }
+
+    /// [`load_file`](Self::load_file) for a file encrypted with
+    /// [`encrypt_with`](Self::encrypt_with), unlocked with `factors`. When
+    /// the file doesn't exist yet, the first [`save`](Self::save) creates it
+    /// encrypted under a new key from `factors`.
+    ///
+    /// Fails if the file isn't encrypted, or if the passphrase or security
+    /// key don't match.
+    pub fn load_encrypted_file(path: &str, factors: &UnlockFactors) -> Result<Self> {
+        Self::load(path, false, Some(factors))
+    }
+
+    fn load(path: &str, strict: bool, unlock: Option<&UnlockFactors>) -> Result<Self> {
         match Path::new(path).try_exists() {
             Ok(true) => {
                 let file = File::open(path).map_err(|err| {
                     TDKError::Profile(format!("Failed to open environments file ({path}): {err}"))
                 })?;
-                let reader = BufReader::new(file);
-                let mut profiles: TDKEnvironments =
-                    serde_json::from_reader(reader).map_err(|err| {
+                let mut json: Value =
+                    serde_json::from_reader(BufReader::new(file)).map_err(|err| {
                         TDKError::Profile(format!(
                             "Failed to deserialise environments file ({path}): {err}"
                         ))
                     })?;
+                let key = match (json.get("encrypted").is_some(), unlock) {
+                    (false, None) => None,
+                    (true, Some(factors)) => {
+                        let envelope: Envelope = serde_json::from_value(json).map_err(|err| {
+                            TDKError::Profile(format!(
+                                "Failed to deserialise encrypted environments file ({path}): {err}"
+                            ))
+                        })?;
+                        let (key, plaintext) = EnvironmentKey::open(envelope, factors)?;
+                        json = serde_json::from_slice(&plaintext).map_err(|err| {
+                            TDKError::Profile(format!(
+                                "Failed to deserialise environments file ({path}): {err}"
+                            ))
+                        })?;
+                        Some(Arc::new(key))
+                    }
+                    (true, None) => {
+                        return Err(TDKError::Profile(format!(
+                            "Environments file ({path}) is encrypted; open it with TDKEnvironments::load_encrypted_file"
+                        )));
+                    }
+                    (false, Some(_)) => {
+                        return Err(TDKError::Profile(format!(
+                            "Environments file ({path}) is not encrypted; load it with TDKEnvironments::load_file and encrypt it with encrypt_with"
+                        )));
+                    }
+                };
+                let from_version = migrate(&mut json, path)?;
+
+                let mut unknown = Vec::new();
+                let mut profiles: TDKEnvironments =
+                    serde_ignored::deserialize(json, |field| unknown.push(field.to_string()))
+                        .map_err(|err| {
+                            TDKError::Profile(format!(
+                                "Failed to deserialise environments file ({path}): {err}"
+                            ))
+                        })?;
+                if !unknown.is_empty() {
+                    if strict {
+                        return Err(TDKError::Profile(format!(
+                            "Environments file ({path}) has unknown fields: {}",
+                            unknown.join(", ")
+                        )));
+                    }
+                    warn!(path, fields = %unknown.join(", "), "ignoring unknown fields in environments file");
+                }
                 profiles.file_name = Some(path.to_string());
+                profiles.key = key;
+
+                if from_version < ENVIRONMENTS_SCHEMA_VERSION {
+                    let backup = format!("{path}.v{from_version}.bak");
+                    std::fs::copy(path, &backup).map_err(|err| {
+                        TDKError::Profile(format!(
+                            "Failed to back up environments file ({path}) to ({backup}): {err}"
+                        ))
+                    })?;
+                    profiles.save()?;
+                    info!(
+                        path,
+                        backup,
+                        from_version,
+                        to_version = ENVIRONMENTS_SCHEMA_VERSION,
+                        "migrated environments file"
+                    );
+                }
                 Ok(profiles)
             }
             Ok(false) => Ok(TDKEnvironments {
                 file_name: Some(path.to_string()),
+                key: unlock
+                    .map(|factors| EnvironmentKey::create(factors).map(Arc::new))
+                    .transpose()?,
                 ..Default::default()
             }),
             Err(err) => Err(TDKError::Profile(format!(
@@ -240,8 +419,9 @@ impl TDKEnvironments {
     }
 
     /// Persist environments to the file the [`TDKEnvironments`] was loaded
-    /// from (or whose name was supplied at construction). Errors if no
-    /// file name has been recorded.
+    /// from (or whose name was supplied at construction), encrypted if
+    /// [`is_encrypted`](Self::is_encrypted). Errors if no file name has been
+    /// recorded.
     pub fn save(&self) -> Result<()> {
         let Some(file_name) = &self.file_name else {
             return Err(TDKError::Profile(
@@ -253,6 +433,15 @@ impl TDKEnvironments {
         let contents = serde_json::to_string_pretty(self).map_err(|err| {
             TDKError::Profile(format!("Failed to serialise TDKEnvironments: {err}"))
         })?;
+        let contents = match &self.key {
+            Some(key) => {
+                let envelope = key.seal(zeroize::Zeroizing::new(contents).as_bytes())?;
+                serde_json::to_string_pretty(&envelope).map_err(|err| {
+                    TDKError::Profile(format!("Failed to serialise TDKEnvironments: {err}"))
+                })?
+            }
+            None => contents,
+        };
 
         // TDKEnvironments serialises `TDKProfile.secrets` (DID private keys);
         // restrict to owner-only on Unix so other local users can't read them.
@@ -285,6 +474,38 @@ impl TDKEnvironments {
         Ok(())
     }
 
+    /// Encrypt the file on every later [`save`](Self::save), under a new key
+    /// derived from `factors`. Also changes the passphrase or security key
+    /// of a file that is already encrypted.
+    pub fn encrypt_with(&mut self, factors: &UnlockFactors) -> Result<()> {
+        self.key = Some(Arc::new(EnvironmentKey::create(factors)?));
+        Ok(())
+    }
+
+    /// Save the file in plaintext from now on.
+    pub fn remove_encryption(&mut self) {
+        self.key = None;
+    }
+
+    /// True when [`save`](Self::save) encrypts the file.
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// True when opening the file needs a security key as well as the
+    /// passphrase.
+    pub fn requires_security_key(&self) -> bool {
+        self.key
+            .as_ref()
+            .is_some_and(|key| key.requires_security_key())
+    }
+
+    /// Schema version of the file, always [`ENVIRONMENTS_SCHEMA_VERSION`]
+    /// once loaded.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
     /// Get an environment by name
     pub fn get(&self, environment_name: &str) -> Option<&TDKEnvironment> {
         self.environments.get(environment_name)
@@ -319,6 +540,39 @@ impl TDKEnvironments {
     }
 }
 
+/// Upgrade the environments file JSON to [`ENVIRONMENTS_SCHEMA_VERSION`],
+/// returning the version it was written with.
+fn migrate(json: &mut Value, path: &str) -> Result<u32> {
+    let Some(object) = json.as_object() else {
+        return Err(TDKError::Profile(format!(
+            "Environments file ({path}) is not a JSON object"
+        )));
+    };
+    let from_version = match object.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                TDKError::Profile(format!(
+                    "Environments file ({path}) has an invalid version ({version})"
+                ))
+            })?,
+    };
+    if from_version > ENVIRONMENTS_SCHEMA_VERSION {
+        return Err(TDKError::Profile(format!(
+            "Environments file ({path}) has schema version {from_version}, but this version of the TDK reads up to {ENVIRONMENTS_SCHEMA_VERSION}; upgrade the TDK"
+        )));
+    }
+
+    for step in MIGRATIONS.iter().filter(|step| step.from >= from_version) {
+        (step.migrate)(json)?;
+        json["version"] = Value::from(step.from + 1);
+        info!(path, from = step.from, "{}", step.description);
+    }
+    Ok(from_version)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -376,6 +630,74 @@ mod tests {
         assert!(env.default_mediator().is_none());
     }
 
+    #[test]
+    fn shred_secret_covers_admin_profile() {
+        use affinidi_secrets_resolver::secrets::Secret;
+
+        let key_id = "did:example:admin#key-1";
+        let mut env = TDKEnvironment::default();
+        env.add_profile(TDKProfile::new("alice", "did:example:alice", None, vec![]));
+        env.set_admin_did(Some(TDKProfile::new(
+            "admin",
+            "did:example:admin",
+            None,
+            vec![Secret::generate_ed25519(Some(key_id), None)],
+        )));
+
+        assert!(env.shred_secret(key_id));
+        assert!(env.admin_did().unwrap().secrets().is_empty());
+        assert!(!env.shred_secret(key_id));
+    }
+
+    #[test]
+    fn encrypted_save_then_load_roundtrip() {
+        use crate::unlock::{
+            SecurityKey,
+            tests::{FakeDevice, factors},
+        };
+        use std::sync::Arc;
+
+        let dir = TempDir::new().unwrap();
+        let path = tmp_path(&dir, "envs.json");
+        let with_key = factors("pass").with_security_key(SecurityKey::new(
+            Arc::new(FakeDevice([1; 32])),
+            b"cred".to_vec(),
+        ));
+
+        let mut envs = TDKEnvironments::load_encrypted_file(&path, &with_key).unwrap();
+        assert!(envs.requires_security_key());
+        let mut env = TDKEnvironment::default();
+        env.add_profile(TDKProfile::new("alice", "did:example:alice", None, vec![]));
+        envs.add("local", env);
+        envs.save().unwrap();
+
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains("did:example:alice"));
+        let err = TDKEnvironments::load_file(&path).unwrap_err();
+        assert!(err.to_string().contains("is encrypted"), "{err}");
+        assert!(TDKEnvironments::load_encrypted_file(&path, &factors("pass")).is_err());
+
+        let mut reloaded = TDKEnvironments::load_encrypted_file(&path, &with_key).unwrap();
+        assert!(reloaded.get("local").unwrap().profile("alice").is_some());
+
+        // Change to passphrase-only, then back to plaintext
+        reloaded.encrypt_with(&factors("new pass")).unwrap();
+        reloaded.save().unwrap();
+        let mut reloaded =
+            TDKEnvironments::load_encrypted_file(&path, &factors("new pass")).unwrap();
+        assert!(!reloaded.requires_security_key());
+        reloaded.remove_encryption();
+        reloaded.save().unwrap();
+        assert!(
+            TDKEnvironments::load_file(&path)
+                .unwrap()
+                .get("local")
+                .is_some()
+        );
+        let err = TDKEnvironments::load_encrypted_file(&path, &factors("new pass")).unwrap_err();
+        assert!(err.to_string().contains("not encrypted"), "{err}");
+    }
+
     #[test]
     fn load_ssl_certificates_empty_when_unset() {
         let env = TDKEnvironment::default();
@@ -443,6 +765,83 @@ mod tests {
         assert!(matches!(err, TDKError::Profile(_)));
     }
 
+    #[test]
+    fn legacy_file_is_migrated_with_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = tmp_path(&dir, "envs.json");
+        let secret = affinidi_secrets_resolver::secrets::Secret::generate_ed25519(
+            Some("did:example:alice#key-1"),
+            None,
+        );
+        let legacy = serde_json::json!({
+            "environments": {
+                "local": {
+                    "profiles": {
+                        "alice": {
+                            "alias": "alice",
+                            "did": "did:example:alice",
+                            "mediator": null,
+                            "secrets": [secret.expose_secret()],
+                        }
+                    },
+                    "default_mediator": "did:web:mediator.example.com"
+                }
+            }
+        });
+        std::fs::write(&path, legacy.to_string()).unwrap();
+
+        let envs = TDKEnvironments::load_file_strict(&path).unwrap();
+        assert_eq!(envs.version(), ENVIRONMENTS_SCHEMA_VERSION);
+        let alice = envs.get("local").unwrap().profile("alice").unwrap();
+        assert_eq!(alice.secrets().len(), 1);
+
+        let backup: Value =
+            serde_json::from_str(&std::fs::read_to_string(format!("{path}.v0.bak")).unwrap())
+                .unwrap();
+        assert_eq!(backup, legacy);
+        let migrated: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(migrated["version"], ENVIRONMENTS_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn newer_schema_version_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = tmp_path(&dir, "envs.json");
+        let newer = ENVIRONMENTS_SCHEMA_VERSION + 1;
+        std::fs::write(
+            &path,
+            format!(r#"{{"version": {newer}, "environments": {{}}}}"#),
+        )
+        .unwrap();
+
+        let err = TDKEnvironments::load_file(&path).unwrap_err();
+        assert!(err.to_string().contains("upgrade the TDK"), "{err}");
+    }
+
+    #[test]
+    fn unknown_fields_fail_only_in_strict_mode() {
+        let dir = TempDir::new().unwrap();
+        let path = tmp_path(&dir, "envs.json");
+        let json = serde_json::json!({
+            "version": ENVIRONMENTS_SCHEMA_VERSION,
+            "environments": { "local": { "profiles": {}, "relay": "did:web:relay" } }
+        });
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        assert!(
+            TDKEnvironments::load_file(&path)
+                .unwrap()
+                .get("local")
+                .is_some()
+        );
+        let err = TDKEnvironments::load_file_strict(&path).unwrap_err();
+        assert!(
+            err.to_string().contains("environments.local.relay"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn add_profile_returns_false_on_replace() {
         let mut env = TDKEnvironment::default();
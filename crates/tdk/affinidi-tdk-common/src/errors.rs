@@ -5,9 +5,13 @@
 use affinidi_data_integrity::DataIntegrityError;
 use affinidi_did_common::PeerError;
 use affinidi_did_resolver_cache_sdk::errors::DIDCacheError;
+use affinidi_error_codes::error_codes;
 use affinidi_secrets_resolver::errors::SecretsResolverError;
+use affinidi_task_utils::TaskStartError;
 use thiserror::Error;
 
+pub use affinidi_error_codes::{ErrorCode, ErrorCodes};
+
 /// Affinidi Trust Development Kit Errors
 ///
 /// Marked `#[non_exhaustive]` — consumers must include a wildcard arm when
@@ -51,6 +55,22 @@ pub enum TDKError {
     #[error("Config Error: {0}")]
     Config(String),
 
+    /// A critical background task failed to start
+    #[error("Background task error: {0}")]
+    Task(String),
+
+    /// Audit log is corrupt, has been tampered with, or failed verification
+    #[error("Audit log error: {0}")]
+    Audit(String),
+
+    /// Consent receipt is invalid, has been modified, or its store is corrupt
+    #[error("Consent receipt error: {0}")]
+    Consent(String),
+
+    /// Wallet backup is malformed or uses an unsupported encryption
+    #[error("Wallet backup error: {0}")]
+    Wallet(String),
+
     #[error("Data Integrity Error")]
     DataIntegrity(#[from] DataIntegrityError),
 
@@ -67,12 +87,39 @@ pub enum TDKError {
 
 pub type Result<T> = std::result::Result<T, TDKError>;
 
+error_codes!(TDKError, "TDK", {
+    1 => Authentication(..): "Authentication failed; it can be retried.",
+    2 => AuthenticationAbort(..): "Authentication failed and cannot be retried.",
+    3 => ACLDenied(..): "Access control denied the request.",
+    4 => Profile(..): "A profile is missing or invalid.",
+    5 => DIDResolver(..): "A DID could not be resolved.",
+    6 => PermissionDenied(..): "The operation is not permitted.",
+    7 => DIDComm(..): "A DIDComm message could not be built, packed or unpacked.",
+    8 => ATM(..): "The messaging SDK reported an error.",
+    9 => Secrets(..): "A secret is missing or unusable.",
+    10 => DIDMethod(..): "A DID method operation failed.",
+    11 => Config(..): "The configuration is invalid.",
+    12 => Task(..): "A critical background task failed to start.",
+    13 => Audit(..): "The audit log is corrupt or failed verification.",
+    14 => DataIntegrity(..): "A Data Integrity proof could not be created or verified.",
+    15 => Io(..): "An I/O operation failed.",
+    16 => Json(..): "JSON could not be serialised or deserialised.",
+    17 => Consent(..): "A consent receipt is invalid or failed verification.",
+    18 => Wallet(..): "A wallet backup is malformed or can't be opened.",
+});
+
 impl From<DIDCacheError> for TDKError {
     fn from(error: DIDCacheError) -> Self {
         TDKError::DIDResolver(error.to_string())
     }
 }
 
+impl From<TaskStartError> for TDKError {
+    fn from(error: TaskStartError) -> Self {
+        TDKError::Task(error.to_string())
+    }
+}
+
 impl From<SecretsResolverError> for TDKError {
     fn from(error: SecretsResolverError) -> Self {
         TDKError::Secrets(error.to_string())
@@ -104,6 +151,13 @@ mod tests {
         assert!(matches!(tdk, TDKError::DataIntegrity(_)));
     }
 
+    #[test]
+    fn error_codes_are_stable() {
+        let e = TDKError::AuthenticationAbort("revoked".into());
+        assert_eq!(e.error_code().to_string(), "TDK-0002");
+        let _ = affinidi_error_codes::Registry::new().with::<TDKError>();
+    }
+
     #[test]
     fn display_format_preserves_payload() {
         let e = TDKError::Authentication("token expired".into());
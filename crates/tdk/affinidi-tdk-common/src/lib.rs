@@ -11,7 +11,8 @@ The crate is organised around four core concepts:
   authentication handlers used by the rest of the stack.
 - **[`TDKSharedState`]** — the runtime container that other Affinidi crates take
   by reference. It bundles the DID resolver, secrets resolver, HTTPS client, and
-  the [`AuthenticationCache`].
+  the [`AuthenticationCache`], and supervises the background tasks behind them
+  (see [`TDKSharedState::task_health`] / [`TDKSharedState::task_events`]).
   Subsystems are exposed via accessors (e.g. [`TDKSharedState::client`]) rather
   than public fields, so the internal layout can evolve without breaking
   consumers.
@@ -21,6 +22,16 @@ The crate is organised around four core concepts:
 - **[`KeyringStore`](secrets::KeyringStore)** ([`secrets`]) — handle into the
   OS native credential store (macOS Keychain, Windows Credential Manager,
   freedesktop Secret Service) for persisting profile secrets.
+- **[`AuditLog`](audit::AuditLog)** ([`audit`]) — optional append-only,
+  hash-chained log of security events with signed checkpoints.
+- **[`ConsentReceipt`](consent::ConsentReceipt)** ([`consent`]) — signed
+  receipts of what a holder shared, with whom and why, kept in a
+  [`ConsentStore`](consent::ConsentStore).
+- **[`UsageTracker`](usage::UsageTracker)** ([`usage`]) — per-profile
+  counters of messages, bytes, authentications and resolutions, with quotas.
+- **[`WalletBackup`](wallet::WalletBackup)** ([`wallet`]) — export and
+  import of a whole identity as a Universal Wallet 2020 backup, optionally
+  encrypted.
 
 Errors are funneled through [`TDKError`]; consumers convert it to their own
 error types via `From<TDKError>` impls.
@@ -52,22 +63,27 @@ source, a mock authenticator for tests): invert *that one* subsystem at the
 boundary — the secrets resolver and DID resolver are already reached through
 traits ([`affinidi_secrets_resolver::SecretsResolver`] and the
 `affinidi_did_resolver_cache_sdk` resolver traits); introduce a trait for the
-keyring/auth piece only when a real second implementation appears. A full
+keyring/auth piece only when a real second implementation appears. The state
+itself holds a [`TDKSecretsResolver`](secrets::TDKSecretsResolver): the
+in-memory resolver, or one over the OS keychain. A full
 trait-inversion of [`TDKSharedState`] over all its subsystems was considered and
 deliberately deferred: the abstractions mostly already exist and the concrete
 churn it would isolate is rare, so it isn't worth pre-abstracting.
 */
 
-use std::sync::OnceLock;
-
-// `Arc` is only used by the extra-roots TLS path, which is compiled out on
-// Android (see `create_http_client`).
-#[cfg(not(target_os = "android"))]
-use std::sync::Arc;
+use std::{
+    convert::Infallible,
+    sync::{Arc, OnceLock},
+};
 
 use affinidi_did_authentication::{AuthorizationTokens, errors::DIDAuthError};
 use affinidi_did_resolver_cache_sdk::{DIDCacheClient, config::DIDCacheConfigBuilder};
-use affinidi_secrets_resolver::{SecretsResolver, ThreadedSecretsResolver};
+use affinidi_secrets_resolver::{
+    SecretsResolver, ThreadedSecretsResolver, secrets::Secret, task::SecretsTask,
+};
+use affinidi_task_utils::{CancellationToken, ComponentHealth, HealthRegistry, TaskSupervisor};
+use audit::{AuditEvent, AuditLog};
+use capabilities::{Capability, CapabilityRegistry};
 use config::TDKConfig;
 use environments::{TDKEnvironment, TDKEnvironments};
 use errors::TDKError;
@@ -75,22 +91,36 @@ use profiles::TDKProfile;
 use reqwest::Client;
 use rustls::{ClientConfig, pki_types::CertificateDer};
 use rustls_platform_verifier::ConfigVerifierExt;
+use secrets::KeyringStore;
+use secrets::TDKSecretsResolver;
 // `Verifier::new_with_extra_roots` is not available on the Android backend of
 // `rustls-platform-verifier` (see `create_http_client`).
 #[cfg(not(target_os = "android"))]
 use rustls_platform_verifier::Verifier;
+use tokio::sync::broadcast;
 use tracing::warn;
+use usage::{UsageStats, UsageTracker};
 
+pub mod audit;
+pub mod capabilities;
 pub mod config;
+pub mod consent;
 pub mod environments;
 pub mod errors;
 pub mod profiles;
 pub mod secrets;
 pub mod tasks;
+pub mod unlock;
+pub mod usage;
+pub mod wallet;
 
 pub use affinidi_secrets_resolver as secrets_resolver;
+pub use affinidi_task_utils as task_utils;
 use tasks::authentication::AuthenticationCache;
 
+/// Events buffered per [`TDKSharedState::subscribe_secret_changes`] receiver.
+pub const SECRET_CHANGE_CHANNEL_CAPACITY: usize = 256;
+
 /// Runtime state shared across Affinidi TDK crates.
 ///
 /// Construct with [`TDKSharedState::new`]. Subsystems are exposed via accessor
@@ -101,10 +131,28 @@ use tasks::authentication::AuthenticationCache;
 pub struct TDKSharedState {
     pub(crate) config: TDKConfig,
     pub(crate) did_resolver: DIDCacheClient,
-    pub(crate) secrets_resolver: ThreadedSecretsResolver,
+    pub(crate) secrets_resolver: TDKSecretsResolver,
     pub(crate) client: Client,
     pub(crate) environment: TDKEnvironment,
     pub(crate) authentication: AuthenticationCache,
+    pub(crate) supervisor: TaskSupervisor,
+    pub(crate) usage: UsageTracker,
+    pub(crate) capabilities: CapabilityRegistry,
+    /// Key ids whose secret was added, replaced, removed or shredded, for
+    /// [`subscribe_secret_changes`](TDKSharedState::subscribe_secret_changes).
+    pub(crate) secret_changes: broadcast::Sender<String>,
+    /// Cancels the supervisor when the last clone is dropped, so supervised
+    /// tasks never outlive the state that owns them.
+    pub(crate) _task_guard: Arc<TaskShutdownGuard>,
+}
+
+/// Cancels its token on drop.
+pub(crate) struct TaskShutdownGuard(CancellationToken);
+
+impl Drop for TaskShutdownGuard {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
 }
 
 /// How long an idle pooled connection may be reused before it is discarded.
@@ -229,14 +277,23 @@ impl TDKSharedState {
     /// The secrets resolver is similarly taken from config if present, else a
     /// fresh empty in-memory resolver is created.
     ///
-    /// The [`AuthenticationCache`] task is spawned and runs until
-    /// [`TDKSharedState::shutdown`] is called.
+    /// The secrets task (when the resolver is created here) and the
+    /// [`AuthenticationCache`] task run under a [`TaskSupervisor`] until
+    /// [`TDKSharedState::shutdown`] is called or the last clone is dropped. A
+    /// task that panics is restarted with backoff; watch
+    /// [`task_events`](Self::task_events) or [`task_health`](Self::task_health).
     ///
     /// # Errors
     ///
     /// Returns [`TDKError::Config`] if the DID resolver or HTTP client fails
-    /// to initialise.
+    /// to initialise, and [`TDKError::Task`] if a critical background task
+    /// fails to start.
     pub async fn new(config: TDKConfig) -> Result<Self, TDKError> {
+        let shutdown = CancellationToken::new();
+        let supervisor = TaskSupervisor::new(shutdown.clone());
+        // Dropped on any early return below, stopping whatever was started.
+        let task_guard = Arc::new(TaskShutdownGuard(shutdown));
+
         let did_resolver = if let Some(resolver) = config.did_resolver.clone() {
             resolver
         } else {
@@ -252,8 +309,22 @@ impl TDKSharedState {
         let secrets_resolver = if let Some(sr) = config.secrets_resolver.clone() {
             sr
         } else {
-            let (sr, _) = ThreadedSecretsResolver::new(None).await;
-            sr
+            let (task, tx) = SecretsTask::new();
+            let sr = ThreadedSecretsResolver::from_task(&task, tx);
+            supervisor
+                .spawn_critical(tasks::SECRETS_TASK_NAME, move || {
+                    let task = task.clone();
+                    async move {
+                        task.serve().await;
+                        Ok::<(), Infallible>(())
+                    }
+                })
+                .await?;
+            TDKSecretsResolver::Threaded(sr)
+        };
+        let secrets_resolver = match config.key_usage_policy {
+            Some(policy) => secrets_resolver.with_key_usage_policy(policy),
+            None => secrets_resolver,
         };
 
         // Resolve the environment in priority order:
@@ -265,10 +336,22 @@ impl TDKSharedState {
         let environment = if let Some(env) = config.prebuilt_environment.clone() {
             env
         } else if config.load_environment {
-            match TDKEnvironments::fetch_from_file(
-                Some(&config.environment_path),
-                &config.environment_name,
-            ) {
+            // Unlocking runs Argon2 and may wait on a security-key touch:
+            // keep both off the async runtime
+            let path = config.environment_path.clone();
+            let name = config.environment_name.clone();
+            let strict = config.strict_environment;
+            let unlock = config.environment_unlock.clone();
+            let loaded = tokio::task::spawn_blocking(move || {
+                TDKEnvironments::fetch(Some(&path), &name, strict, unlock.as_ref())
+            })
+            .await
+            .unwrap_or_else(|e| {
+                Err(TDKError::Profile(format!(
+                    "environment-file load task failed: {e}"
+                )))
+            });
+            match loaded {
                 Ok(env) => env,
                 Err(e) => {
                     warn!(
@@ -295,8 +378,11 @@ impl TDKSharedState {
             secrets_resolver.clone(),
             &client,
             config.custom_auth_handlers.clone(),
+            config.service_verification,
         );
-        authentication.start();
+        authentication.start_supervised(&supervisor).await?;
+        let usage = authentication.usage().clone();
+        let capabilities = authentication.capabilities().clone();
 
         Ok(TDKSharedState {
             config,
@@ -305,10 +391,16 @@ impl TDKSharedState {
             client,
             environment,
             authentication,
+            supervisor,
+            usage,
+            capabilities,
+            secret_changes: broadcast::channel(SECRET_CHANGE_CHANNEL_CAPACITY).0,
+            _task_guard: task_guard,
         })
     }
 
-    /// Add a [`TDKProfile`]'s secrets to the shared `SecretsResolver`.
+    /// Add a [`TDKProfile`]'s secrets to the shared `SecretsResolver`, and
+    /// its persisted capabilities to the [`CapabilityRegistry`].
     ///
     /// Note: this borrows the profile's secrets — the original `Vec` lives
     /// on with the profile until it is dropped or [`TDKProfile::take_secrets`]
@@ -316,7 +408,8 @@ impl TDKSharedState {
     /// [`add_profile_drained`](Self::add_profile_drained), which moves the
     /// secrets into the resolver in one call.
     pub async fn add_profile(&self, profile: &TDKProfile) {
-        self.secrets_resolver.insert_vec(profile.secrets()).await;
+        self.insert_secrets(profile.secrets()).await;
+        self.capabilities.load(&profile.did, &profile.capabilities);
     }
 
     /// Drain a profile's secrets into the shared `SecretsResolver`.
@@ -332,7 +425,8 @@ impl TDKSharedState {
     /// later re-registration into a different resolver).
     pub async fn add_profile_drained(&self, profile: &mut TDKProfile) {
         let secrets = profile.take_secrets();
-        self.secrets_resolver.insert_vec(&secrets).await;
+        self.insert_secrets(&secrets).await;
+        self.capabilities.load(&profile.did, &profile.capabilities);
     }
 
     /// Resolve the effective mediator DID for a profile, using the active
@@ -363,7 +457,7 @@ impl TDKSharedState {
         };
         let mut admin = admin.clone();
         let secrets = admin.take_secrets();
-        self.secrets_resolver.insert_vec(&secrets).await;
+        self.insert_secrets(&secrets).await;
         Ok(Some(admin))
     }
 
@@ -375,14 +469,161 @@ impl TDKSharedState {
     ///
     /// Returns cached tokens if a valid record exists; otherwise runs a
     /// fresh DID Auth handshake (or refresh, depending on token state).
+    ///
+    /// The outcome is recorded in the configured [`AuditLog`](audit::AuditLog),
+    /// if any.
     pub async fn authenticate_profile(
         &self,
         profile: &TDKProfile,
         target_did: &str,
     ) -> Result<AuthorizationTokens, DIDAuthError> {
-        self.authentication
+        let result = self
+            .authentication
             .authenticate_default(profile.did.clone(), target_did.to_string())
+            .await;
+
+        self.record_audit_event(match &result {
+            Ok(_) => AuditEvent::AuthenticationSuccess {
+                profile_did: profile.did.clone(),
+                target_did: target_did.to_string(),
+            },
+            Err(e) => AuditEvent::AuthenticationFailure {
+                profile_did: profile.did.clone(),
+                target_did: target_did.to_string(),
+                reason: e.to_string(),
+            },
+        })
+        .await;
+
+        result
+    }
+
+    /// Make sure `profile` has `capability`, onboarding it if it doesn't.
+    ///
+    /// Returns at once if the capability is already recorded. Otherwise the
+    /// profile authenticates against the capability's service: mediators and
+    /// Meeting Place create the account on a profile's first DID
+    /// authentication, so a successful handshake is the onboarding. The
+    /// capability is then recorded.
+    ///
+    /// A mediator that only admits pre-registered DIDs refuses the handshake;
+    /// that surfaces as a [`TDKError::Authentication`] or
+    /// [`TDKError::AuthenticationAbort`] error.
+    pub async fn ensure_capability(
+        &self,
+        profile: &TDKProfile,
+        capability: Capability,
+    ) -> Result<(), TDKError> {
+        if self.capabilities.has(&profile.did, &capability) {
+            return Ok(());
+        }
+
+        let service = match &capability {
+            Capability::MediatorAccount { mediator } => mediator,
+            Capability::MeetingPlace { service } | Capability::Authentication { service } => {
+                service
+            }
+        };
+        self.authenticate_profile(profile, service)
             .await
+            .map_err(|e| match e {
+                DIDAuthError::AuthenticationAbort(reason) => TDKError::AuthenticationAbort(
+                    format!("Couldn't onboard {capability}: {reason}"),
+                ),
+                e => TDKError::Authentication(format!("Couldn't onboard {capability}: {e}")),
+            })?;
+
+        self.capabilities.record(&profile.did, capability);
+        Ok(())
+    }
+
+    /// Copy the capabilities recorded for `profile` onto it, so they persist
+    /// when the environment holding the profile is saved.
+    pub fn sync_capabilities(&self, profile: &mut TDKProfile) {
+        profile.capabilities = self.capabilities.capabilities(&profile.did);
+    }
+
+    /// Destroy the secret `key_id`: remove it from the secrets resolver and
+    /// zeroize it, and, if `keyring` is given, shred it from the keyring
+    /// entry of the key's DID (the part of `key_id` before `#`).
+    ///
+    /// The destruction is recorded in the configured
+    /// [`AuditLog`](audit::AuditLog), if any, and announced to
+    /// [`subscribe_secret_changes`](Self::subscribe_secret_changes), whenever
+    /// the key was found. Profiles keep their own copies of their secrets: drop the key from
+    /// any [`TDKEnvironment`] you persist with
+    /// [`TDKEnvironment::shred_secret`] and save it.
+    ///
+    /// Returns `true` if the key was found in the resolver or the keyring.
+    pub async fn shred_secret(
+        &self,
+        key_id: &str,
+        keyring: Option<KeyringStore<'_>>,
+    ) -> Result<bool, TDKError> {
+        let mut found = self.secrets_resolver.shred(key_id).await;
+
+        if let Some(keyring) = keyring {
+            let did = key_id.split('#').next().unwrap_or(key_id);
+            found |= keyring.shred_key(did, key_id)?;
+        }
+
+        if found {
+            self.notify_secret_changed(key_id);
+            self.record_audit_event(AuditEvent::KeyDestroyed {
+                key_id: key_id.to_string(),
+            })
+            .await;
+        }
+
+        Ok(found)
+    }
+
+    /// Remove the secret `key_id` from the secrets resolver, returning it.
+    ///
+    /// Unlike calling [`SecretsResolver::remove_secret`] on
+    /// [`secrets_resolver`](Self::secrets_resolver) directly, the removal is
+    /// announced to [`subscribe_secret_changes`](Self::subscribe_secret_changes),
+    /// so nothing keeps using a copy of the key.
+    pub async fn remove_secret(&self, key_id: &str) -> Option<Secret> {
+        let removed = self.secrets_resolver.remove_secret(key_id).await;
+        if removed.is_some() {
+            self.notify_secret_changed(key_id);
+        }
+        removed
+    }
+
+    /// Subscribe to key ids whose secret was added, replaced, removed or
+    /// shredded through this state, or announced with
+    /// [`notify_secret_changed`](Self::notify_secret_changed).
+    ///
+    /// Anything holding key material derived from a secret (such as the keys
+    /// a messaging SDK `PackingContext` negotiated) should drop it after its
+    /// key id appears here. A receiver that falls more than
+    /// [`SECRET_CHANGE_CHANNEL_CAPACITY`] events behind gets
+    /// [`RecvError::Lagged`](broadcast::error::RecvError::Lagged) and should
+    /// treat every key as changed.
+    pub fn subscribe_secret_changes(&self) -> broadcast::Receiver<String> {
+        self.secret_changes.subscribe()
+    }
+
+    /// Announce that the secret `key_id` changed behind this state's back,
+    /// e.g. it was inserted into or removed from
+    /// [`secrets_resolver`](Self::secrets_resolver) directly, so subscribers
+    /// of [`subscribe_secret_changes`](Self::subscribe_secret_changes) drop
+    /// what they derived from it.
+    pub fn notify_secret_changed(&self, key_id: &str) {
+        // No receivers is fine: nothing holds a copy
+        let _ = self.secret_changes.send(key_id.to_string());
+    }
+
+    /// Insert `secrets` into the resolver, announcing each key: an inserted
+    /// key may replace one of the same id, or be preferred over the keys a
+    /// subscriber negotiated with.
+    async fn insert_secrets(&self, secrets: &[Secret]) {
+        self.secrets_resolver.insert_vec(secrets).await;
+        for secret in secrets {
+            self.notify_secret_changed(&secret.id);
+        }
     }
 
     /// Configuration this state was built from.
@@ -395,8 +636,9 @@ impl TDKSharedState {
         &self.did_resolver
     }
 
-    /// Threaded `SecretsResolver` (clone-cheap, internally `Arc`).
-    pub fn secrets_resolver(&self) -> &ThreadedSecretsResolver {
+    /// `SecretsResolver` (clone-cheap, internally `Arc`): in memory unless
+    /// the config supplied a keychain resolver.
+    pub fn secrets_resolver(&self) -> &TDKSecretsResolver {
         &self.secrets_resolver
     }
 
@@ -410,14 +652,72 @@ impl TDKSharedState {
         &self.environment
     }
 
+    /// Security-event audit log, if one was configured.
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.config.audit_log()
+    }
+
+    /// Record `event` in the configured [`AuditLog`], if any.
+    ///
+    /// Crates built on this state report their security events (messages
+    /// sent and received, policy denials) through here. A failed write is
+    /// logged rather than returned: auditing must not fail the operation
+    /// being audited.
+    pub async fn record_audit_event(&self, event: AuditEvent) {
+        if let Some(audit_log) = self.config.audit_log()
+            && let Err(e) = audit_log.record(event).await
+        {
+            warn!(error = %e, "failed to record event in audit log");
+        }
+    }
+
+    /// Which services each profile has been onboarded to. See
+    /// [`capabilities`] for what records them.
+    pub fn capabilities(&self) -> &CapabilityRegistry {
+        &self.capabilities
+    }
+
+    /// Per-profile usage counters (messages, bytes, authentications,
+    /// resolutions), shared by every crate built on this state.
+    pub fn usage_tracker(&self) -> &UsageTracker {
+        &self.usage
+    }
+
+    /// Usage counters of the profile `profile_did`.
+    pub fn usage(&self, profile_did: &str) -> UsageStats {
+        self.usage.usage(profile_did)
+    }
+
     /// In-process authentication cache + worker handle.
     pub fn authentication(&self) -> &AuthenticationCache {
         &self.authentication
     }
 
-    /// Stop the background [`AuthenticationCache`] task and wait for it to
-    /// exit. Call before process shutdown for graceful drain.
+    /// Supervisor running the TDK background tasks. Other crates built on this
+    /// state (e.g. the messaging SDK) spawn their tasks through it — or through
+    /// [`TaskSupervisor::with_shutdown`] — so all task health lands in one place.
+    pub fn task_supervisor(&self) -> &TaskSupervisor {
+        &self.supervisor
+    }
+
+    /// Current health of every supervised background task, keyed by task name.
+    pub fn task_health(&self) -> HealthRegistry {
+        self.supervisor.registry()
+    }
+
+    /// Subscribe to background-task status events: a [`ComponentHealth`]
+    /// snapshot on every start, crash/restart and stop.
+    pub fn task_events(&self) -> broadcast::Receiver<ComponentHealth> {
+        self.supervisor.subscribe()
+    }
+
+    /// Stop the supervised background tasks (the [`AuthenticationCache`],
+    /// when created here the secrets task, and those other crates spawned
+    /// through [`task_supervisor`](Self::task_supervisor)) and wait for them
+    /// to exit. Call before process shutdown for graceful drain; dropping the
+    /// last clone stops them too, without waiting.
     pub async fn shutdown(&self) {
-        self.authentication.terminate().await;
+        self.supervisor.shutdown_token().cancel();
+        self.supervisor.stopped().await;
     }
 }
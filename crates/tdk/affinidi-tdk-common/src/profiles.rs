@@ -11,9 +11,17 @@
  * `TDKProfile.secrets` is `pub(crate)` to discourage long-lived plaintext
  * exposure. Read via [`TDKProfile::secrets`] (borrow) or drain via
  * [`TDKProfile::take_secrets`] when handing them to a `SecretsResolver`.
+ *
+ * # Capabilities
+ *
+ * `TDKProfile.capabilities` persists which services the profile has been
+ * onboarded to (see [`crate::capabilities`]). The TDK keeps the live record;
+ * [`crate::TDKSharedState::sync_capabilities`] copies it back onto the
+ * profile before the environment is saved.
  */
 
-use affinidi_secrets_resolver::secrets::Secret;
+use crate::capabilities::{Capability, CapabilityRecord};
+use affinidi_secrets_resolver::secrets::{Secret, serialize_with_private};
 use serde::{Deserialize, Serialize};
 
 /// Serialisable identity profile.
@@ -37,9 +45,17 @@ pub struct TDKProfile {
     /// [`affinidi_secrets_resolver::ThreadedSecretsResolver`] via
     /// [`TDKProfile::take_secrets`] or [`crate::TDKSharedState::add_profile`].
     /// Field is `pub(crate)` to discourage incidental retention; use the
-    /// accessor methods.
-    #[serde(default)]
+    /// accessor methods. Unlike a bare [`Secret`], it serializes with the
+    /// private keys: the environment file is where they're kept.
+    #[serde(default, serialize_with = "serialize_with_private")]
     pub(crate) secrets: Vec<Secret>,
+
+    /// Services this profile has been onboarded to, and when each was last
+    /// confirmed. Loaded into the TDK's
+    /// [`CapabilityRegistry`](crate::capabilities::CapabilityRegistry) when
+    /// the profile is added.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub capabilities: Vec<CapabilityRecord>,
 }
 
 impl TDKProfile {
@@ -50,9 +66,17 @@ impl TDKProfile {
             did: did.to_string(),
             mediator: mediator.map(|s| s.to_string()),
             secrets,
+            capabilities: Vec::new(),
         }
     }
 
+    /// Whether `capability` is recorded on the profile.
+    pub fn has_capability(&self, capability: &Capability) -> bool {
+        self.capabilities
+            .iter()
+            .any(|record| &record.capability == capability)
+    }
+
     /// Borrow the profile's secrets without taking ownership.
     ///
     /// The borrow keeps the underlying `Vec<Secret>` alive on the profile —
@@ -74,6 +98,16 @@ impl TDKProfile {
     pub fn take_secrets(&mut self) -> Vec<Secret> {
         std::mem::take(&mut self.secrets)
     }
+
+    /// Drop the secret `key_id` from the profile. The dropped `Secret` is
+    /// zeroized; save the environment afterwards to remove it from disk.
+    ///
+    /// Returns `true` if the profile held the secret.
+    pub fn shred_secret(&mut self, key_id: &str) -> bool {
+        let before = self.secrets.len();
+        self.secrets.retain(|secret| secret.id != key_id);
+        self.secrets.len() != before
+    }
 }
 
 #[cfg(test)]
@@ -89,6 +123,19 @@ mod tests {
         assert!(p.secrets().is_empty());
     }
 
+    #[test]
+    fn shred_secret_drops_only_that_key() {
+        let secrets = vec![
+            Secret::generate_ed25519(Some("did:example:1#key-1"), None),
+            Secret::generate_ed25519(Some("did:example:1#key-2"), None),
+        ];
+        let mut p = TDKProfile::new("alice", "did:example:1", None, secrets);
+        assert!(p.shred_secret("did:example:1#key-1"));
+        assert!(!p.shred_secret("did:example:1#key-1"));
+        assert_eq!(p.secrets().len(), 1);
+        assert_eq!(p.secrets()[0].id, "did:example:1#key-2");
+    }
+
     #[test]
     fn take_secrets_drains() {
         let s = Secret::generate_ed25519(Some("kid"), Some(&[1u8; 32]));
@@ -100,6 +147,24 @@ mod tests {
         assert!(p.secrets().is_empty());
     }
 
+    #[test]
+    fn capabilities_roundtrip_and_are_omitted_when_empty() {
+        let mut p = TDKProfile::new("alice", "did:example:1", None, vec![]);
+        let json = serde_json::to_value(&p).unwrap();
+        assert!(json.get("capabilities").is_none());
+
+        let mediator = Capability::MediatorAccount {
+            mediator: "did:web:m".to_string(),
+        };
+        p.capabilities.push(CapabilityRecord {
+            capability: mediator.clone(),
+            verified_at: 7,
+        });
+        let back: TDKProfile = serde_json::from_str(&serde_json::to_string(&p).unwrap()).unwrap();
+        assert!(back.has_capability(&mediator));
+        assert_eq!(back.capabilities, p.capabilities);
+    }
+
     #[test]
     fn serde_roundtrips_with_no_secrets() {
         let p = TDKProfile::new("alice", "did:example:1", Some("did:web:m"), vec![]);
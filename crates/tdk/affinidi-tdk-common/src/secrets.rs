@@ -12,13 +12,30 @@
  *   unlocked). On macOS / Windows this is the user's logged-in session; on Linux
  *   it is the user's Secret Service-managed login keyring.
  * - **In memory**: decoded `Vec<Secret>` is held in process memory until it is
- *   inserted into the [`affinidi_secrets_resolver`] and then dropped. We do not
- *   currently zeroize the JSON intermediate. Mitigation: keep load windows short
- *   and prefer `load_into` which hands secrets to the resolver immediately.
+ *   inserted into the [`affinidi_secrets_resolver`] and then dropped. The JSON
+ *   read from or written to the keychain is zeroized once it has been decoded
+ *   or stored. Keep load windows short and prefer `load_into`, which hands
+ *   secrets to the resolver immediately.
  * - **Storage format**: raw UTF-8 JSON bytes of `Vec<Secret>`. Earlier versions
  *   (`affinidi-tdk-common <= 0.5.x`) wrapped the JSON in `BASE64_STANDARD_NO_PAD`;
  *   [`KeyringStore::read`] auto-detects and silently migrates legacy entries on
  *   read. The legacy reader will be removed in 0.8.
+ * - **Destruction**: [`KeyringStore::shred`] overwrites an entry with zeros
+ *   before deleting it, and [`KeyringStore::shred_key`] removes a single key.
+ *   [`crate::TDKSharedState::shred_secret`] also drops the key from the
+ *   resolver and records it in the audit log.
+ *
+ * # Read-through resolver
+ *
+ * [`KeychainSecretsResolver`] is a [`SecretsResolver`] over the same entries:
+ * keys stay in the keychain and are read into memory, one DID at a time, when
+ * first needed, so a desktop application never has to hold them in a JSON
+ * file. Hand it to [`TDKConfigBuilder::with_secrets_resolver`] and the TDK,
+ * and ATM on top of it, use it in place of the in-memory
+ * [`ThreadedSecretsResolver`]; [`TDKSecretsResolver`] is the type that holds
+ * either.
+ *
+ * [`TDKConfigBuilder::with_secrets_resolver`]: crate::config::TDKConfigBuilder::with_secrets_resolver
  *
  * # Default-store registration
  *
@@ -30,11 +47,22 @@
  */
 
 use crate::errors::TDKError;
-use affinidi_secrets_resolver::{SecretsResolver, secrets::Secret};
+/// [`TDKSecretsResolver`]'s methods (`insert`, `insert_vec`, `get_secret`,
+/// ...) come from this trait; import it alongside the resolver.
+pub use affinidi_secrets_resolver::SecretsResolver;
+use affinidi_secrets_resolver::{
+    ThreadedSecretsResolver,
+    secrets::{Secret, SerializeWithPrivate},
+    usage::KeyUsagePolicy,
+};
 use base64::{Engine, prelude::BASE64_STANDARD_NO_PAD};
 use keyring_core::{Entry, error::Error as KeyringError};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, OnceLock, RwLock},
+};
 use tracing::{debug, warn};
+use zeroize::Zeroizing;
 
 /// A handle to the platform-native credential store, scoped to a single
 /// `service_id` namespace.
@@ -90,12 +118,14 @@ impl<'a> KeyringStore<'a> {
     /// Any existing entry for the same `(service_id, did)` is overwritten.
     pub fn save(&self, did: &str, secrets: &[Secret]) -> Result<(), TDKError> {
         let entry = self.entry(did)?;
-        let bytes = serde_json::to_vec(secrets).map_err(|e| {
-            TDKError::Secrets(format!(
-                "Failed to serialise secrets (service_id={}, did={did}): {e}",
-                self.service_id
-            ))
-        })?;
+        let bytes = serde_json::to_vec(&SerializeWithPrivate::new(secrets))
+            .map(Zeroizing::new)
+            .map_err(|e| {
+                TDKError::Secrets(format!(
+                    "Failed to serialise secrets (service_id={}, did={did}): {e}",
+                    self.service_id
+                ))
+            })?;
         entry.set_secret(&bytes).map_err(|e| {
             TDKError::Secrets(format!(
                 "Failed to write keyring entry (service_id={}, did={did}): {e}",
@@ -119,12 +149,29 @@ impl<'a> KeyringStore<'a> {
                 self.service_id
             ))
         })?;
+        self.decode(did, Zeroizing::new(bytes))
+    }
+
+    /// Like [`read`](Self::read), but `Ok(None)` if no entry exists for
+    /// `did`.
+    pub fn find(&self, did: &str) -> Result<Option<Vec<Secret>>, TDKError> {
+        match self.entry(did)?.get_secret() {
+            Ok(bytes) => self.decode(did, Zeroizing::new(bytes)).map(Some),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(TDKError::Secrets(format!(
+                "Failed to read keyring entry (service_id={}, did={did}): {e}",
+                self.service_id
+            ))),
+        }
+    }
 
+    /// Deserialise an entry read for `did`, migrating a legacy entry.
+    fn decode(&self, did: &str, bytes: Zeroizing<Vec<u8>>) -> Result<Vec<Secret>, TDKError> {
         if let Ok(secrets) = serde_json::from_slice::<Vec<Secret>>(&bytes) {
             return Ok(secrets);
         }
 
-        let decoded = BASE64_STANDARD_NO_PAD.decode(&bytes).map_err(|e| {
+        let decoded = BASE64_STANDARD_NO_PAD.decode(&bytes).map(Zeroizing::new).map_err(|e| {
             TDKError::Secrets(format!(
                 "Keyring entry (service_id={}, did={did}) is neither valid JSON nor legacy base64: {e}",
                 self.service_id
@@ -170,6 +217,68 @@ impl<'a> KeyringStore<'a> {
         }
     }
 
+    /// Destroy the keyring entry for `did`: overwrite the stored record with
+    /// zeros, then delete it.
+    ///
+    /// Use this rather than [`delete`](Self::delete) when keys must be
+    /// destroyed rather than just forgotten. Overwriting first means a store
+    /// that keeps deleted records around (undo history, sync tombstones)
+    /// keeps the zeros, not the key material. Storage the OS manages below
+    /// the credential store (disk blocks, backups) is out of reach of this
+    /// call. Returns `Ok(())` if no entry existed.
+    pub fn shred(&self, did: &str) -> Result<(), TDKError> {
+        let entry = self.entry(did)?;
+        let mut bytes = match entry.get_secret() {
+            Ok(bytes) => bytes,
+            Err(KeyringError::NoEntry) => return Ok(()),
+            Err(e) => {
+                return Err(TDKError::Secrets(format!(
+                    "Failed to read keyring entry (service_id={}, did={did}): {e}",
+                    self.service_id
+                )));
+            }
+        };
+        bytes.fill(0);
+        entry.set_secret(&bytes).map_err(|e| {
+            TDKError::Secrets(format!(
+                "Failed to overwrite keyring entry (service_id={}, did={did}): {e}",
+                self.service_id
+            ))
+        })?;
+        self.delete(did)
+    }
+
+    /// Destroy the key `key_id` in the keyring entry for `did`, keeping the
+    /// entry's other secrets.
+    ///
+    /// The entry is rewritten without the key, which replaces the stored
+    /// record, or [shredded](Self::shred) if `key_id` was its last secret.
+    /// Returns `Ok(false)` if the entry or the key doesn't exist.
+    pub fn shred_key(&self, did: &str, key_id: &str) -> Result<bool, TDKError> {
+        let mut secrets = match self.entry(did)?.get_secret() {
+            Ok(bytes) => self.decode(did, Zeroizing::new(bytes))?,
+            Err(KeyringError::NoEntry) => return Ok(false),
+            Err(e) => {
+                return Err(TDKError::Secrets(format!(
+                    "Failed to read keyring entry (service_id={}, did={did}): {e}",
+                    self.service_id
+                )));
+            }
+        };
+        let before = secrets.len();
+        secrets.retain(|secret| secret.id != key_id);
+        if secrets.len() == before {
+            return Ok(false);
+        }
+
+        if secrets.is_empty() {
+            self.shred(did)?;
+        } else {
+            self.save(did, &secrets)?;
+        }
+        Ok(true)
+    }
+
     /// Read secrets for `did` and insert them into the supplied resolver.
     ///
     /// Convenience over [`read`](Self::read) +
@@ -186,6 +295,374 @@ impl<'a> KeyringStore<'a> {
     }
 }
 
+/// A [`SecretsResolver`] backed by the OS keychain, loading keys on demand.
+///
+/// Private keys live in the platform credential store (macOS Keychain,
+/// Windows Credential Manager, which encrypts them with DPAPI, or Secret
+/// Service), one entry per DID in the [`KeyringStore`] format, rather than in
+/// a JSON file. Nothing is read up front: the first lookup of a key reads its
+/// DID's entry into memory, and later lookups of that DID's keys are served
+/// from memory. Where the platform guards items with an access prompt (a
+/// macOS Keychain item read by an application not on its access list, a
+/// locked Secret Service collection), the user is asked when a DID's keys are
+/// first needed, once per entry; call [`load`](Self::load) to take the prompt
+/// at a moment of the application's choosing.
+///
+/// Inserting a secret writes it to the keychain as well as to memory;
+/// removing one [shreds](KeyringStore::shred_key) it from the keychain.
+/// [`forget`](Self::forget) drops a DID's keys from memory only.
+///
+/// Clones share the keys held in memory.
+///
+/// Keychain calls can block on a prompt, so they run on tokio's blocking
+/// thread pool. Failures are logged; the [`SecretsResolver`] methods treat
+/// them as a missing key. Use [`load`](Self::load) and [`store`](Self::store)
+/// to see the error.
+///
+/// ```no_run
+/// # use affinidi_secrets_resolver::{SecretsResolver, secrets::Secret};
+/// # use affinidi_tdk_common::{secrets::KeychainSecretsResolver, errors::TDKError};
+/// # async fn run(secret: Secret) -> Result<(), TDKError> {
+/// let resolver = KeychainSecretsResolver::new("my-app");
+/// resolver.store(&[secret]).await?;
+///
+/// // Read from the keychain on first use
+/// let _key = resolver.get_secret("did:example:123#key-1").await;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct KeychainSecretsResolver {
+    service_id: String,
+    cache: Arc<RwLock<KeychainCache>>,
+    /// Serialises keychain access: concurrent lookups of one DID prompt once,
+    /// and writes don't lose each other's read-modify-write of an entry
+    load_lock: Arc<tokio::sync::Mutex<()>>,
+    key_usage_policy: KeyUsagePolicy,
+}
+
+#[derive(Default)]
+struct KeychainCache {
+    secrets: HashMap<String, Secret>,
+    /// DIDs whose keychain entry has been read
+    loaded: HashSet<String>,
+}
+
+impl KeychainSecretsResolver {
+    /// Resolve secrets from the keychain entries under `service_id`.
+    pub fn new(service_id: impl Into<String>) -> Self {
+        Self {
+            service_id: service_id.into(),
+            cache: Arc::new(RwLock::new(KeychainCache::default())),
+            load_lock: Arc::new(tokio::sync::Mutex::new(())),
+            key_usage_policy: KeyUsagePolicy::default(),
+        }
+    }
+
+    /// Set how keys used for the wrong purpose are handled. Clones made
+    /// afterwards share the policy.
+    pub fn with_key_usage_policy(mut self, policy: KeyUsagePolicy) -> Self {
+        self.key_usage_policy = policy;
+        self
+    }
+
+    /// The keychain service namespace secrets are stored under.
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    /// Read `did`'s keychain entry into memory now, replacing any of its keys
+    /// already held. Returns the number of keys loaded, `0` if the keychain
+    /// has no entry for `did`.
+    pub async fn load(&self, did: &str) -> Result<usize, TDKError> {
+        let _guard = self.load_lock.lock().await;
+        self.read_entry(did).await
+    }
+
+    /// Write `secrets` to the keychain, merged into their DIDs' entries, and
+    /// keep them in memory. A secret replaces a stored one with the same id.
+    pub async fn store(&self, secrets: &[Secret]) -> Result<(), TDKError> {
+        let mut by_did: HashMap<String, Vec<Secret>> = HashMap::new();
+        for secret in secrets {
+            by_did
+                .entry(key_did(&secret.id).to_string())
+                .or_default()
+                .push(secret.clone());
+        }
+
+        // Held across each entry's read-modify-write, so concurrent stores
+        // (and removals) of keys of one DID don't overwrite each other.
+        let _guard = self.load_lock.lock().await;
+        for (did, secrets) in by_did {
+            let service_id = self.service_id.clone();
+            let stored = secrets.clone();
+            let entry_did = did.clone();
+            blocking(move || {
+                let store = KeyringStore::new(&service_id);
+                let mut entry = store.find(&entry_did)?.unwrap_or_default();
+                entry.retain(|existing| !stored.iter().any(|secret| secret.id == existing.id));
+                entry.extend(stored);
+                store.save(&entry_did, &entry)
+            })
+            .await?;
+
+            let mut cache = self.write();
+            for secret in secrets {
+                cache.secrets.insert(secret.id.clone(), secret);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop `did`'s keys from memory. They stay in the keychain, and are read
+    /// again when next needed.
+    pub fn forget(&self, did: &str) {
+        let mut cache = self.write();
+        cache.secrets.retain(|id, _| key_did(id) != did);
+        cache.loaded.remove(did);
+    }
+
+    /// Read `did`'s entry unless it has been read already.
+    async fn ensure_loaded(&self, did: &str) {
+        if self.read().loaded.contains(did) {
+            return;
+        }
+        let _guard = self.load_lock.lock().await;
+        if self.read().loaded.contains(did) {
+            return;
+        }
+        if let Err(e) = self.read_entry(did).await {
+            warn!(
+                service_id = self.service_id,
+                did,
+                error = %e,
+                "couldn't read secrets from the keychain"
+            );
+        }
+    }
+
+    async fn read_entry(&self, did: &str) -> Result<usize, TDKError> {
+        let service_id = self.service_id.clone();
+        let entry_did = did.to_string();
+        let secrets = blocking(move || KeyringStore::new(&service_id).find(&entry_did))
+            .await?
+            .unwrap_or_default();
+
+        let mut cache = self.write();
+        cache.secrets.retain(|id, _| key_did(id) != did);
+        let count = secrets.len();
+        for secret in secrets {
+            cache.secrets.insert(secret.id.clone(), secret);
+        }
+        cache.loaded.insert(did.to_string());
+        Ok(count)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, KeychainCache> {
+        self.cache
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, KeychainCache> {
+        self.cache
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl SecretsResolver for KeychainSecretsResolver {
+    async fn insert(&self, secret: Secret) {
+        self.insert_vec(&[secret]).await;
+    }
+
+    async fn insert_vec(&self, secrets: &[Secret]) {
+        if let Err(e) = self.store(secrets).await {
+            warn!(
+                service_id = self.service_id,
+                error = %e,
+                "couldn't write secrets to the keychain"
+            );
+        }
+    }
+
+    async fn get_secret(&self, secret_id: &str) -> Option<Secret> {
+        if let Some(secret) = self.read().secrets.get(secret_id) {
+            return Some(secret.clone());
+        }
+        self.ensure_loaded(key_did(secret_id)).await;
+        self.read().secrets.get(secret_id).cloned()
+    }
+
+    fn key_usage_policy(&self) -> KeyUsagePolicy {
+        self.key_usage_policy
+    }
+
+    async fn find_secrets(&self, secret_ids: &[String]) -> Vec<String> {
+        let mut found = Vec::new();
+        for secret_id in secret_ids {
+            if self.get_secret(secret_id).await.is_some() {
+                found.push(secret_id.clone());
+            }
+        }
+        found
+    }
+
+    async fn remove_secret(&self, secret_id: &str) -> Option<Secret> {
+        let did = key_did(secret_id);
+        self.ensure_loaded(did).await;
+        let _guard = self.load_lock.lock().await;
+        let removed = self.write().secrets.remove(secret_id);
+
+        let service_id = self.service_id.clone();
+        let (entry_did, key_id) = (did.to_string(), secret_id.to_string());
+        if let Err(e) =
+            blocking(move || KeyringStore::new(&service_id).shred_key(&entry_did, &key_id)).await
+        {
+            warn!(
+                service_id = self.service_id,
+                key_id = secret_id,
+                error = %e,
+                "couldn't remove secret from the keychain"
+            );
+        }
+        removed
+    }
+
+    /// Keys held in memory: those of the DIDs loaded so far.
+    async fn len(&self) -> usize {
+        self.read().secrets.len()
+    }
+
+    async fn is_empty(&self) -> bool {
+        self.read().secrets.is_empty()
+    }
+}
+
+/// The secrets resolver behind a [`crate::TDKSharedState`], and so behind ATM:
+/// secrets held in memory, or read through from the OS keychain.
+///
+/// Build one with `From` from either resolver, or let the TDK create an
+/// in-memory one. Clones share the same secrets.
+///
+/// ```no_run
+/// # use affinidi_tdk_common::{config::TDKConfig, secrets::KeychainSecretsResolver};
+/// let config = TDKConfig::builder()
+///     .with_secrets_resolver(KeychainSecretsResolver::new("my-app"))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum TDKSecretsResolver {
+    /// Secrets held in memory by a Secrets Task
+    Threaded(ThreadedSecretsResolver),
+    /// Secrets read from the OS keychain when first needed
+    Keychain(KeychainSecretsResolver),
+}
+
+impl TDKSecretsResolver {
+    /// Set how keys used for the wrong purpose are handled
+    pub fn with_key_usage_policy(self, policy: KeyUsagePolicy) -> Self {
+        match self {
+            Self::Threaded(resolver) => Self::Threaded(resolver.with_key_usage_policy(policy)),
+            Self::Keychain(resolver) => Self::Keychain(resolver.with_key_usage_policy(policy)),
+        }
+    }
+}
+
+impl From<ThreadedSecretsResolver> for TDKSecretsResolver {
+    fn from(resolver: ThreadedSecretsResolver) -> Self {
+        Self::Threaded(resolver)
+    }
+}
+
+impl From<KeychainSecretsResolver> for TDKSecretsResolver {
+    fn from(resolver: KeychainSecretsResolver) -> Self {
+        Self::Keychain(resolver)
+    }
+}
+
+impl SecretsResolver for TDKSecretsResolver {
+    async fn insert(&self, secret: Secret) {
+        match self {
+            Self::Threaded(resolver) => resolver.insert(secret).await,
+            Self::Keychain(resolver) => resolver.insert(secret).await,
+        }
+    }
+
+    async fn insert_vec(&self, secrets: &[Secret]) {
+        match self {
+            Self::Threaded(resolver) => resolver.insert_vec(secrets).await,
+            Self::Keychain(resolver) => resolver.insert_vec(secrets).await,
+        }
+    }
+
+    async fn get_secret(&self, secret_id: &str) -> Option<Secret> {
+        match self {
+            Self::Threaded(resolver) => resolver.get_secret(secret_id).await,
+            Self::Keychain(resolver) => resolver.get_secret(secret_id).await,
+        }
+    }
+
+    fn key_usage_policy(&self) -> KeyUsagePolicy {
+        match self {
+            Self::Threaded(resolver) => resolver.key_usage_policy(),
+            Self::Keychain(resolver) => resolver.key_usage_policy(),
+        }
+    }
+
+    async fn find_secrets(&self, secret_ids: &[String]) -> Vec<String> {
+        match self {
+            Self::Threaded(resolver) => resolver.find_secrets(secret_ids).await,
+            Self::Keychain(resolver) => resolver.find_secrets(secret_ids).await,
+        }
+    }
+
+    async fn remove_secret(&self, secret_id: &str) -> Option<Secret> {
+        match self {
+            Self::Threaded(resolver) => resolver.remove_secret(secret_id).await,
+            Self::Keychain(resolver) => resolver.remove_secret(secret_id).await,
+        }
+    }
+
+    async fn shred(&self, secret_id: &str) -> bool {
+        match self {
+            Self::Threaded(resolver) => resolver.shred(secret_id).await,
+            Self::Keychain(resolver) => resolver.shred(secret_id).await,
+        }
+    }
+
+    async fn len(&self) -> usize {
+        match self {
+            Self::Threaded(resolver) => resolver.len().await,
+            Self::Keychain(resolver) => resolver.len().await,
+        }
+    }
+
+    async fn is_empty(&self) -> bool {
+        match self {
+            Self::Threaded(resolver) => resolver.is_empty().await,
+            Self::Keychain(resolver) => resolver.is_empty().await,
+        }
+    }
+}
+
+/// The DID a key id belongs to: the part before `#`.
+fn key_did(key_id: &str) -> &str {
+    key_id.split('#').next().unwrap_or(key_id)
+}
+
+/// Run a keychain call, which may block on an access prompt, off the async
+/// runtime.
+async fn blocking<T: Send + 'static>(
+    call: impl FnOnce() -> Result<T, TDKError> + Send + 'static,
+) -> Result<T, TDKError> {
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|e| TDKError::Secrets(format!("Keychain call failed: {e}")))?
+}
+
 /// Eagerly register the platform-native credential store with `keyring-core`.
 ///
 /// Apps that want to surface platform-store initialisation failures (e.g.
@@ -4,7 +4,10 @@
  * Enables a TDK Profile to authenticate using its DID and secrets to other
  * services that accept DID Auth. The cache is shared across all profiles in
  * the host process and runs as a single background task driven by an MPSC
- * command channel.
+ * command channel. [`AuthenticationCache::start_supervised`] runs that task
+ * under a [`TaskSupervisor`], which restarts it if it panics; the channel and
+ * the cached tokens survive the restart. [`AuthenticationCache::terminate`]
+ * stops it for good.
  *
  * Cached entries expire when their refresh token expires; expired-token
  * authentication kicks off a fresh DID Auth handshake.
@@ -12,10 +15,10 @@
 
 use affinidi_did_authentication::{
     AuthenticationType, AuthorizationTokens, CustomAuthHandlers, DIDAuthentication, RefreshCheck,
-    errors::DIDAuthError, refresh_check,
+    ServiceVerification, errors::DIDAuthError, refresh_check,
 };
 use affinidi_did_resolver_cache_sdk::DIDCacheClient;
-use affinidi_secrets_resolver::ThreadedSecretsResolver;
+use affinidi_task_utils::{CallLimits, CancellationToken, TaskStartError, TaskSupervisor};
 use ahash::{AHasher, RandomState};
 use moka::{
     Expiry,
@@ -23,12 +26,14 @@ use moka::{
 };
 use reqwest::Client;
 use std::{
+    convert::Infallible,
     hash::Hasher,
     sync::{Arc, Mutex},
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::{
     sync::{
+        self,
         mpsc::{self, error::TrySendError},
         oneshot,
     },
@@ -36,6 +41,12 @@ use tokio::{
 };
 use tracing::{debug, warn};
 
+use crate::{
+    capabilities::{Capability, CapabilityRegistry},
+    secrets::TDKSecretsResolver,
+    usage::{UsageMetric, UsageTracker},
+};
+
 /// MPSC channel buffer size for [`AuthenticationCommand`]. Sized for short
 /// burst tolerance — sustained backpressure shows up as `TrySendError::Full`
 /// warnings and should be addressed at the call site.
@@ -51,6 +62,9 @@ pub const DEFAULT_AUTH_TIMEOUT: Duration = Duration::from_secs(10);
 /// Timeout for the lightweight [`AuthenticationCache::authenticated`] check.
 pub const AUTHENTICATED_QUERY_TIMEOUT: Duration = Duration::from_secs(2);
 
+/// Name the background task is registered under in a [`TaskSupervisor`].
+pub const AUTHENTICATION_TASK_NAME: &str = "authentication_cache";
+
 /// Top-level Authentication Cache handle.
 ///
 /// Cheap to clone — internally an [`Arc<mpsc::Sender>`] plus a slot for the
@@ -60,21 +74,29 @@ pub struct AuthenticationCache {
     tx: mpsc::Sender<AuthenticationCommand>,
     /// Holds the spawned task's `JoinHandle` until [`terminate`](Self::terminate)
     /// awaits it. `Mutex<Option<...>>` rather than `RwLock` because contention
-    /// is essentially zero (start once, terminate once).
+    /// is essentially zero (start once, terminate once). Stays `None` when the
+    /// task runs under a supervisor.
     handle: Arc<Mutex<Option<JoinHandle<()>>>>,
-    /// Inner state — moved into the spawned task at [`start`](Self::start)
-    /// time. After `start`, this field is `None`.
-    state: Arc<Mutex<Option<AuthenticationCacheInner>>>,
+    /// Shutdown token of the supervised task, cancelled once its loop has
+    /// exited so the supervisor stops rather than restarts it.
+    supervised: Arc<Mutex<Option<CancellationToken>>>,
+    /// State served by the background task. Shared rather than moved into the
+    /// task so a supervisor can restart the loop over the same channel + cache.
+    inner: Arc<AuthenticationCacheInner>,
 }
 
-/// Inner state owned by the background task.
+/// Inner state served by the background task.
 struct AuthenticationCacheInner {
     cache: Cache<u64, AuthenticationRecord, RandomState>,
-    channel_rx: mpsc::Receiver<AuthenticationCommand>,
+    /// Locked by the running loop for its lifetime; a restarted loop re-locks it.
+    channel_rx: sync::Mutex<mpsc::Receiver<AuthenticationCommand>>,
     did_resolver: DIDCacheClient,
-    secrets_resolver: ThreadedSecretsResolver,
+    secrets_resolver: TDKSecretsResolver,
     client: Client,
     custom_handlers: Option<CustomAuthHandlers>,
+    service_verification: ServiceVerification,
+    usage: UsageTracker,
+    capabilities: CapabilityRegistry,
 }
 
 /// MPSC commands consumed by the background authentication task.
@@ -120,6 +142,8 @@ pub(crate) enum AuthenticationCommand {
 struct AuthenticationRecord {
     tokens: AuthorizationTokens,
     type_: AuthenticationType,
+    /// The service proved it issued the tokens; refreshes must keep doing so
+    service_verified: bool,
 }
 
 /// Sets up expiry for the AuthenticationRecord to expire when the refresh
@@ -149,18 +173,22 @@ impl AuthenticationCache {
     /// * `secrets_resolver` — `SecretsResolver`.
     /// * `client` — `reqwest::Client`.
     /// * `custom_handlers` — optional custom authentication handlers.
+    /// * `service_verification` — how strictly to verify that tokens came
+    ///   from the service DID.
     pub fn new(
         max_capacity: u64,
         did_resolver: &DIDCacheClient,
-        secrets_resolver: ThreadedSecretsResolver,
+        secrets_resolver: TDKSecretsResolver,
         client: &Client,
         custom_handlers: Option<CustomAuthHandlers>,
+        service_verification: ServiceVerification,
     ) -> Self {
         let (tx, rx) = mpsc::channel(COMMAND_CHANNEL_CAPACITY);
 
         let expiry_template = AuthenticationRecord {
             tokens: AuthorizationTokens::default(),
             type_: AuthenticationType::Unknown,
+            service_verified: false,
         };
         let cache = CacheBuilder::new(max_capacity)
             .expire_after(expiry_template)
@@ -168,33 +196,78 @@ impl AuthenticationCache {
 
         let inner = AuthenticationCacheInner {
             cache,
-            channel_rx: rx,
+            channel_rx: sync::Mutex::new(rx),
             did_resolver: did_resolver.clone(),
             secrets_resolver,
             client: client.clone(),
             custom_handlers,
+            service_verification,
+            usage: UsageTracker::default(),
+            capabilities: CapabilityRegistry::default(),
         };
 
         AuthenticationCache {
             tx,
             handle: Arc::new(Mutex::new(None)),
-            state: Arc::new(Mutex::new(Some(inner))),
+            supervised: Arc::new(Mutex::new(None)),
+            inner: Arc::new(inner),
         }
     }
 
     /// Spawn the background task. Idempotent — if the task is already running
     /// the call is a no-op. Synchronous because no async work happens here;
     /// the spawned task runs concurrently.
+    ///
+    /// A task started this way is not restarted if it panics; prefer
+    /// [`start_supervised`](Self::start_supervised).
     pub fn start(&self) {
-        let inner = match self.state.lock() {
-            Ok(mut guard) => guard.take(),
-            Err(_) => return,
+        let Ok(mut slot) = self.handle.lock() else {
+            return;
         };
-        let Some(inner) = inner else { return };
-        let handle = tokio::spawn(run(inner));
-        if let Ok(mut slot) = self.handle.lock() {
-            *slot = Some(handle);
+        if slot.is_some() {
+            return;
         }
+        let inner = self.inner.clone();
+        *slot = Some(tokio::spawn(async move { run(&inner).await }));
+    }
+
+    /// Spawn the background task under `supervisor` as a critical task
+    /// ([`AUTHENTICATION_TASK_NAME`]): it is restarted with backoff if it
+    /// panics, and its state is published to the supervisor's health registry
+    /// and status events.
+    ///
+    /// Stop it with [`terminate`](Self::terminate), which lets the loop
+    /// finish its current command, or by cancelling the supervisor's shutdown
+    /// token.
+    ///
+    /// # Errors
+    ///
+    /// [`TaskStartError`] if the first run fails before it starts serving.
+    pub async fn start_supervised(
+        &self,
+        supervisor: &TaskSupervisor,
+    ) -> Result<(), TaskStartError> {
+        let stop = supervisor.shutdown_token().child_token();
+        if let Ok(mut slot) = self.supervised.lock() {
+            *slot = Some(stop.clone());
+        }
+
+        let inner = self.inner.clone();
+        let exited = stop.clone();
+        supervisor
+            .with_shutdown(stop)
+            .spawn_critical(AUTHENTICATION_TASK_NAME, move || {
+                let inner = inner.clone();
+                let exited = exited.clone();
+                async move {
+                    run(&inner).await;
+                    // The loop only returns once terminated or when every
+                    // handle is gone: a stop, not a fault to restart from
+                    exited.cancel();
+                    Ok::<(), Infallible>(())
+                }
+            })
+            .await
     }
 
     /// Send a Terminate command and wait for the background task to exit.
@@ -204,6 +277,10 @@ impl AuthenticationCache {
         if let Some(h) = handle {
             let _ = h.await;
         }
+        let supervised = self.supervised.lock().ok().and_then(|slot| slot.clone());
+        if let Some(stop) = supervised {
+            stop.cancelled().await;
+        }
     }
 
     /// Check whether `(profile_did, service_endpoint_did)` is currently
@@ -313,6 +390,32 @@ impl AuthenticationCache {
         }
     }
 
+    /// [`authenticate`](Self::authenticate) under caller-controlled
+    /// [`CallLimits`]. The limits' timeout, if set, replaces
+    /// [`DEFAULT_AUTH_TIMEOUT`].
+    ///
+    /// If the token fires first, the call returns
+    /// [`DIDAuthError::AuthenticationAbort`] and the background task aborts the
+    /// handshake it was running for this call, or skips it if it had not yet
+    /// started.
+    pub async fn authenticate_with_limits(
+        &self,
+        profile_did: String,
+        service_endpoint_did: String,
+        retry_limit: u8,
+        limits: &CallLimits,
+    ) -> Result<AuthorizationTokens, DIDAuthError> {
+        limits
+            .run(self.authenticate(
+                profile_did,
+                service_endpoint_did,
+                retry_limit,
+                limits.timeout(),
+            ))
+            .await
+            .unwrap_or_else(|e| Err(DIDAuthError::AuthenticationAbort(e.to_string())))
+    }
+
     /// Convenience helper using [`DEFAULT_AUTH_RETRIES`] and the default
     /// timeout. Equivalent to `authenticate(p, s, DEFAULT_AUTH_RETRIES, None)`.
     pub async fn authenticate_default(
@@ -405,24 +508,42 @@ impl AuthenticationCache {
             warn!(error = %e, "Failed to send Invalidate command");
         }
     }
+
+    /// Per-profile usage counters. Each handshake or token refresh the cache
+    /// runs counts as one [`UsageMetric::Authentications`]; answers served
+    /// from cached tokens are not counted.
+    pub fn usage(&self) -> &UsageTracker {
+        &self.inner.usage
+    }
+
+    /// Per-profile capabilities. Each handshake or token refresh that
+    /// succeeds records [`Capability::Authentication`] for its service.
+    pub fn capabilities(&self) -> &CapabilityRegistry {
+        &self.inner.capabilities
+    }
 }
 
-/// Background task entry point. Owns `inner` for its lifetime — when this
-/// future completes, the task exits.
-async fn run(mut inner: AuthenticationCacheInner) {
+/// Background task entry point. Holds the command channel for its lifetime —
+/// when this future completes (`Terminate` or channel closed), the task exits.
+async fn run(inner: &AuthenticationCacheInner) {
+    let mut channel_rx = inner.channel_rx.lock().await;
     loop {
-        tokio::select! {
-            msg = inner.channel_rx.recv() => {
-                if inner.handle_channel(msg).await {
-                    break;
-                }
-            }
+        let msg = channel_rx.recv().await;
+        if inner.handle_channel(msg).await {
+            break;
         }
     }
     debug!("Exiting Authentication Task");
 }
 
 impl AuthenticationCacheInner {
+    /// A fresh handshake configured like this cache
+    fn new_authentication(&self) -> DIDAuthentication {
+        DIDAuthentication::new()
+            .with_custom_handlers(self.custom_handlers.clone())
+            .with_service_verification(self.service_verification)
+    }
+
     /// Returns `true` when the task should exit.
     async fn handle_channel(&self, cmd: Option<AuthenticationCommand>) -> bool {
         match cmd {
@@ -493,8 +614,12 @@ impl AuthenticationCacheInner {
         retry_limit: u8,
         timeout: Duration,
         force_refresh: bool,
-        tx: oneshot::Sender<Result<AuthorizationTokens, DIDAuthError>>,
+        mut tx: oneshot::Sender<Result<AuthorizationTokens, DIDAuthError>>,
     ) {
+        if tx.is_closed() {
+            debug!(profile = %profile_did, "caller gave up before authentication started");
+            return;
+        }
         let key = hash(&profile_did, &service_endpoint_did);
         debug!(
             profile = %profile_did,
@@ -523,20 +648,20 @@ impl AuthenticationCacheInner {
                 }
                 RefreshCheck::Refresh => {
                     debug!("Refresh needed");
-                    DIDAuthentication {
-                        type_: record.type_,
-                        tokens: Some(record.tokens.clone()),
-                        authenticated: true,
-                        custom_handlers: self.custom_handlers.clone(),
-                    }
+                    let mut auth = self.new_authentication();
+                    auth.type_ = record.type_;
+                    auth.tokens = Some(record.tokens.clone());
+                    auth.authenticated = true;
+                    auth.service_verified = record.service_verified;
+                    auth
                 }
                 RefreshCheck::Expired => {
                     debug!("Tokens expired; running fresh authentication");
-                    DIDAuthentication::new().with_custom_handlers(self.custom_handlers.clone())
+                    self.new_authentication()
                 }
             }
         } else {
-            DIDAuthentication::new().with_custom_handlers(self.custom_handlers.clone())
+            self.new_authentication()
         };
 
         let did_resolver = self.did_resolver.clone();
@@ -562,42 +687,73 @@ impl AuthenticationCacheInner {
             }
         });
 
+        // Whichever of the handshake, the timeout and the caller's departure
+        // comes first. Decided before replying, as `tx.closed()` borrows `tx`.
+        enum Waited<T> {
+            Finished(T),
+            TimedOut,
+            Abandoned,
+        }
+        let abort = handle.abort_handle();
         let sleep = tokio::time::sleep(timeout);
         tokio::pin!(sleep);
 
-        tokio::select! {
-            value = handle => {
-                match value {
-                    Ok(Ok(auth)) => {
-                        if let Some(tokens) = &auth.tokens {
-                            self.cache
-                                .insert(
-                                    key,
-                                    AuthenticationRecord { tokens: tokens.clone(), type_: auth.type_ },
-                                )
-                                .await;
-                            let _ = tx.send(Ok(tokens.clone()));
-                        } else {
-                            let _ = tx.send(Err(DIDAuthError::AuthenticationAbort(
-                                "Internal Error: Authenticated ok, but no tokens".to_string(),
-                            )));
-                        }
-                    }
-                    Ok(Err(e)) => {
-                        warn!(profile = %profile_did, service = %service_endpoint_did, error = %e, "authentication failed");
-                        let _ = tx.send(Err(e));
-                    }
-                    Err(e) => {
-                        warn!(profile = %profile_did, service = %service_endpoint_did, error = %e, "join error on authentication task");
-                        let _ = tx.send(Err(DIDAuthError::AuthenticationAbort(format!(
-                            "JoinHandle error on spawned authentication task: {e}"
-                        ))));
+        let waited = tokio::select! {
+            value = handle => Waited::Finished(value),
+            _ = &mut sleep => Waited::TimedOut,
+            _ = tx.closed() => Waited::Abandoned,
+        };
+
+        match waited {
+            Waited::Finished(value) => match value {
+                Ok(Ok(auth)) => {
+                    if let Some(tokens) = &auth.tokens {
+                        self.usage
+                            .record(&profile_did, UsageMetric::Authentications, 1);
+                        self.capabilities.record(
+                            &profile_did,
+                            Capability::Authentication {
+                                service: service_endpoint_did.to_string(),
+                            },
+                        );
+                        self.cache
+                            .insert(
+                                key,
+                                AuthenticationRecord {
+                                    tokens: tokens.clone(),
+                                    type_: auth.type_,
+                                    service_verified: auth.service_verified,
+                                },
+                            )
+                            .await;
+                        let _ = tx.send(Ok(tokens.clone()));
+                    } else {
+                        let _ = tx.send(Err(DIDAuthError::AuthenticationAbort(
+                            "Internal Error: Authenticated ok, but no tokens".to_string(),
+                        )));
                     }
                 }
-            }
-            _ = &mut sleep => {
+                Ok(Err(e)) => {
+                    warn!(profile = %profile_did, service = %service_endpoint_did, error = %e, "authentication failed");
+                    let _ = tx.send(Err(e));
+                }
+                Err(e) => {
+                    warn!(profile = %profile_did, service = %service_endpoint_did, error = %e, "join error on authentication task");
+                    let _ = tx.send(Err(DIDAuthError::AuthenticationAbort(format!(
+                        "JoinHandle error on spawned authentication task: {e}"
+                    ))));
+                }
+            },
+            Waited::TimedOut => {
                 warn!("Timeout reached during authentication");
-                let _ = tx.send(Err(DIDAuthError::AuthenticationAbort("Timeout reached".to_string())));
+                abort.abort();
+                let _ = tx.send(Err(DIDAuthError::AuthenticationAbort(
+                    "Timeout reached".to_string(),
+                )));
+            }
+            Waited::Abandoned => {
+                debug!(profile = %profile_did, "caller gave up; aborting authentication");
+                abort.abort();
             }
         }
     }
@@ -629,6 +785,7 @@ mod tests {
         let template = AuthenticationRecord {
             tokens: AuthorizationTokens::default(),
             type_: AuthenticationType::Unknown,
+            service_verified: false,
         };
         let cache = CacheBuilder::new(1)
             .expire_after(template)
@@ -646,6 +803,7 @@ mod tests {
                         refresh_expires_at: now.as_secs() + 1,
                     },
                     type_: AuthenticationType::Unknown,
+                    service_verified: false,
                 },
             )
             .await;
@@ -665,6 +823,7 @@ mod tests {
         let template = AuthenticationRecord {
             tokens: AuthorizationTokens::default(),
             type_: AuthenticationType::Unknown,
+            service_verified: false,
         };
         let already_expired = AuthenticationRecord {
             tokens: AuthorizationTokens {
@@ -674,6 +833,7 @@ mod tests {
                 refresh_expires_at: 1,
             },
             type_: AuthenticationType::Unknown,
+            service_verified: false,
         };
         let ttl = template.expire_after_create(&0u64, &already_expired, Instant::now());
         assert_eq!(ttl, Some(Duration::ZERO));
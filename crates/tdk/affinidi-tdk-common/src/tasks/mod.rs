@@ -2,5 +2,13 @@
  * # Tasks represent state machines that can be executed by the TDK
  *
  * You interact with these tasks via Channels and Commands
+ *
+ * [`TDKSharedState`](crate::TDKSharedState) runs its background tasks under a
+ * single [`TaskSupervisor`](affinidi_task_utils::TaskSupervisor): a task that
+ * panics is restarted with backoff, every state change is published as a
+ * status event, and construction fails if a critical task cannot start.
  */
 pub mod authentication;
+
+/// Name the TDK-owned secrets task is registered under in the task supervisor.
+pub const SECRETS_TASK_NAME: &str = "secrets_task";
@@ -0,0 +1,499 @@
+/*!
+ * Keys for encrypted environments files.
+ *
+ * An encrypted environments file is sealed with AES-256-GCM under an
+ * [`EnvironmentKey`]. The key is derived from the factors in
+ * [`UnlockFactors`]:
+ *
+ * - a passphrase, stretched with Argon2id, and optionally
+ * - a FIDO2 security key (a YubiKey, for example), through the CTAP2
+ *   `hmac-secret` extension. The authenticator computes an HMAC over a salt
+ *   stored in the file, using a secret that never leaves the device, so the
+ *   file can't be opened without the physical token — and a touch on it —
+ *   even by someone who knows the passphrase.
+ *
+ * Both outputs go through HKDF-SHA256 to form the key. The salts, Argon2
+ * cost and credential ID are stored in the file in the clear; they are
+ * bound to the ciphertext as associated data.
+ *
+ * The TDK doesn't talk to authenticators itself. Implement
+ * [`HmacSecretDevice`] over the CTAP2 library of your platform (for example
+ * `ctap-hid-fido2` on desktop, or the platform FIDO API on mobile), using a
+ * credential created with the `hmac-secret` extension enabled:
+ *
+ * ```ignore
+ * use affinidi_tdk_common::{environments::TDKEnvironments, unlock::{SecurityKey, UnlockFactors}};
+ *
+ * let factors = UnlockFactors::passphrase(passphrase)
+ *     .with_security_key(SecurityKey::new(Arc::new(MyYubiKey::open()?), credential_id));
+ * let mut environments = TDKEnvironments::load_encrypted_file("environments.json", &factors)?;
+ * ```
+ *
+ * Deriving a key is slow by design and may wait for a touch on the security
+ * key, so the environments calls that do it are blocking.
+ * [`TDKSharedState::new`](crate::TDKSharedState::new) runs them on a
+ * blocking thread; do the same (`tokio::task::spawn_blocking`) when calling
+ * them from async code.
+ */
+
+use crate::errors::{Result, TDKError};
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, Payload, rand_core::RngCore},
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{fmt, sync::Arc};
+use zeroize::Zeroizing;
+
+/// Version of the encrypted file envelope this build reads and writes.
+pub const ENCRYPTION_VERSION: u32 = 1;
+
+/// Default Argon2id memory cost, in KiB.
+pub const DEFAULT_KDF_MEMORY_KIB: u32 = 64 * 1024;
+
+/// Default Argon2id iterations.
+pub const DEFAULT_KDF_ITERATIONS: u32 = 3;
+
+/// Largest Argon2id memory cost a key is derived with, in KiB. Costs come
+/// from the file or backup being opened, so they are bounded before running.
+pub const MAX_KDF_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// Largest Argon2id iteration count a key is derived with.
+pub const MAX_KDF_ITERATIONS: u32 = 64;
+
+const MAX_KDF_PARALLELISM: u32 = 16;
+const KDF_PARALLELISM: u32 = 4;
+const HKDF_INFO: &[u8] = b"affinidi-tdk/environments/v1";
+
+/// A FIDO2 authenticator that can evaluate the CTAP2 `hmac-secret`
+/// extension.
+pub trait HmacSecretDevice: Send + Sync {
+    /// Run a `getAssertion` for `credential_id` with the `hmac-secret`
+    /// extension over `salt`, and return the 32-byte output. Expect this to
+    /// wait for the user to touch the key.
+    fn hmac_secret(&self, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32]>;
+}
+
+/// A security key and the `hmac-secret` credential on it to unlock with.
+#[derive(Clone)]
+pub struct SecurityKey {
+    device: Arc<dyn HmacSecretDevice>,
+    credential_id: Vec<u8>,
+}
+
+impl SecurityKey {
+    pub fn new(device: Arc<dyn HmacSecretDevice>, credential_id: impl Into<Vec<u8>>) -> Self {
+        SecurityKey {
+            device,
+            credential_id: credential_id.into(),
+        }
+    }
+
+    pub fn credential_id(&self) -> &[u8] {
+        &self.credential_id
+    }
+}
+
+impl fmt::Debug for SecurityKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecurityKey")
+            .field(
+                "credential_id",
+                &BASE64_URL_SAFE_NO_PAD.encode(&self.credential_id),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+/// What it takes to unlock an encrypted environments file.
+#[derive(Clone)]
+pub struct UnlockFactors {
+    passphrase: Zeroizing<String>,
+    security_key: Option<SecurityKey>,
+    kdf_memory_kib: u32,
+    kdf_iterations: u32,
+}
+
+impl UnlockFactors {
+    /// Unlock with `passphrase` alone.
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        UnlockFactors {
+            passphrase: Zeroizing::new(passphrase.into()),
+            security_key: None,
+            kdf_memory_kib: DEFAULT_KDF_MEMORY_KIB,
+            kdf_iterations: DEFAULT_KDF_ITERATIONS,
+        }
+    }
+
+    /// Also require `security_key`. Files encrypted with a security key
+    /// can't be opened without it.
+    pub fn with_security_key(mut self, security_key: SecurityKey) -> Self {
+        self.security_key = Some(security_key);
+        self
+    }
+
+    /// Argon2id cost of new keys (defaults: [`DEFAULT_KDF_MEMORY_KIB`],
+    /// [`DEFAULT_KDF_ITERATIONS`]; at most [`MAX_KDF_MEMORY_KIB`],
+    /// [`MAX_KDF_ITERATIONS`]). Existing files keep the cost stored in them.
+    pub fn with_kdf_cost(mut self, memory_kib: u32, iterations: u32) -> Self {
+        self.kdf_memory_kib = memory_kib;
+        self.kdf_iterations = iterations;
+        self
+    }
+}
+
+impl fmt::Debug for UnlockFactors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnlockFactors")
+            .field("passphrase", &"<redacted>")
+            .field("security_key", &self.security_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parameters of an [`EnvironmentKey`], stored in the file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KeyParams {
+    kdf: KdfParams,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    security_key: Option<SecurityKeyParams>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KdfParams {
+    algorithm: String,
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SecurityKeyParams {
+    credential_id: String,
+    salt: String,
+}
+
+/// An encrypted environments file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Envelope {
+    /// Envelope version; its presence marks the file as encrypted
+    pub(crate) encrypted: u32,
+    key: KeyParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The key an encrypted environments file is sealed with.
+pub struct EnvironmentKey {
+    key: Zeroizing<[u8; 32]>,
+    params: KeyParams,
+}
+
+impl fmt::Debug for EnvironmentKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnvironmentKey")
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EnvironmentKey {
+    /// A new key with fresh salts, for encrypting a file.
+    pub fn create(factors: &UnlockFactors) -> Result<Self> {
+        let security_key = factors.security_key.as_ref().map(|key| SecurityKeyParams {
+            credential_id: BASE64_URL_SAFE_NO_PAD.encode(&key.credential_id),
+            salt: BASE64_URL_SAFE_NO_PAD.encode(random::<32>()),
+        });
+        let params = KeyParams {
+            kdf: KdfParams {
+                algorithm: "argon2id".into(),
+                salt: BASE64_URL_SAFE_NO_PAD.encode(random::<16>()),
+                memory_kib: factors.kdf_memory_kib,
+                iterations: factors.kdf_iterations,
+                parallelism: KDF_PARALLELISM,
+            },
+            security_key,
+        };
+        Self::derive(params, factors)
+    }
+
+    /// True when the key needs a security key as well as the passphrase.
+    pub fn requires_security_key(&self) -> bool {
+        self.params.security_key.is_some()
+    }
+
+    /// The salts and costs the key was derived with.
+    pub(crate) fn params(&self) -> &KeyParams {
+        &self.params
+    }
+
+    /// Derive the key of an existing file from `factors`.
+    pub(crate) fn derive(params: KeyParams, factors: &UnlockFactors) -> Result<Self> {
+        if params.kdf.algorithm != "argon2id" {
+            return Err(TDKError::Profile(format!(
+                "Unsupported environments key derivation ({})",
+                params.kdf.algorithm
+            )));
+        }
+        // Bounded before use: an oversized cost in a file or backup would
+        // otherwise tie up the host's memory and CPU before the key is known
+        // to be wrong.
+        if params.kdf.memory_kib > MAX_KDF_MEMORY_KIB
+            || params.kdf.iterations > MAX_KDF_ITERATIONS
+            || params.kdf.parallelism > MAX_KDF_PARALLELISM
+        {
+            return Err(TDKError::Profile(format!(
+                "Argon2 cost out of range (memory {} KiB, {} iterations, parallelism {}); \
+                 the limits are {MAX_KDF_MEMORY_KIB} KiB, {MAX_KDF_ITERATIONS} iterations \
+                 and parallelism {MAX_KDF_PARALLELISM}",
+                params.kdf.memory_kib, params.kdf.iterations, params.kdf.parallelism
+            )));
+        }
+        let argon2 = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(
+                params.kdf.memory_kib,
+                params.kdf.iterations,
+                params.kdf.parallelism,
+                Some(32),
+            )
+            .map_err(|e| TDKError::Profile(format!("Invalid Argon2 parameters: {e}")))?,
+        );
+        let mut ikm = Zeroizing::new([0u8; 64]);
+        argon2
+            .hash_password_into(
+                factors.passphrase.as_bytes(),
+                &decode(&params.kdf.salt)?,
+                &mut ikm[..32],
+            )
+            .map_err(|e| TDKError::Profile(format!("Couldn't derive environments key: {e}")))?;
+
+        let ikm_len = match (&params.security_key, &factors.security_key) {
+            (None, _) => 32,
+            (Some(_), None) => {
+                return Err(TDKError::Profile(
+                    "Environments file is locked to a security key; none was provided".into(),
+                ));
+            }
+            (Some(stored), Some(security_key)) => {
+                if decode(&stored.credential_id)? != security_key.credential_id {
+                    return Err(TDKError::Profile(
+                        "Environments file is locked to a different security key credential".into(),
+                    ));
+                }
+                let salt: [u8; 32] = decode(&stored.salt)?
+                    .try_into()
+                    .map_err(|_| TDKError::Profile("Security key salt must be 32 bytes".into()))?;
+                let output = Zeroizing::new(
+                    security_key
+                        .device
+                        .hmac_secret(&security_key.credential_id, &salt)?,
+                );
+                ikm[32..].copy_from_slice(output.as_slice());
+                64
+            }
+        };
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        Hkdf::<Sha256>::new(None, &ikm[..ikm_len])
+            .expand(HKDF_INFO, key.as_mut_slice())
+            .map_err(|e| TDKError::Profile(format!("Couldn't derive environments key: {e}")))?;
+        Ok(EnvironmentKey { key, params })
+    }
+
+    /// Encrypt `plaintext` with `aad` bound to it. Returns the nonce and the
+    /// ciphertext, tag appended.
+    pub(crate) fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<([u8; 12], Vec<u8>)> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|e| TDKError::Profile(format!("Couldn't encrypt: {e}")))?;
+        Ok((nonce.into(), ciphertext))
+    }
+
+    /// Reverse of [`encrypt`](Self::encrypt); `None` when the key, the
+    /// ciphertext or `aad` is wrong.
+    pub(crate) fn decrypt(
+        &self,
+        nonce: &[u8; 12],
+        ciphertext: &[u8],
+        aad: &[u8],
+    ) -> Option<Zeroizing<Vec<u8>>> {
+        self.cipher()
+            .decrypt(
+                &Nonce::from(*nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .ok()
+            .map(Zeroizing::new)
+    }
+
+    /// Encrypt `plaintext` into a file envelope.
+    pub(crate) fn seal(&self, plaintext: &[u8]) -> Result<Envelope> {
+        let (nonce, ciphertext) = self.encrypt(plaintext, &self.aad()?)?;
+        Ok(Envelope {
+            encrypted: ENCRYPTION_VERSION,
+            key: self.params.clone(),
+            nonce: BASE64_URL_SAFE_NO_PAD.encode(nonce),
+            ciphertext: BASE64_URL_SAFE_NO_PAD.encode(ciphertext),
+        })
+    }
+
+    /// Derive the key of `envelope` from `factors` and decrypt it.
+    pub(crate) fn open(
+        envelope: Envelope,
+        factors: &UnlockFactors,
+    ) -> Result<(Self, Zeroizing<Vec<u8>>)> {
+        if envelope.encrypted != ENCRYPTION_VERSION {
+            return Err(TDKError::Profile(format!(
+                "Environments file is encrypted with envelope version {}, but this version of the TDK reads {ENCRYPTION_VERSION}; upgrade the TDK",
+                envelope.encrypted
+            )));
+        }
+        let key = Self::derive(envelope.key, factors)?;
+        let nonce: [u8; 12] = decode(&envelope.nonce)?
+            .try_into()
+            .map_err(|_| TDKError::Profile("Environments nonce must be 12 bytes".into()))?;
+        let plaintext = key
+            .decrypt(&nonce, &decode(&envelope.ciphertext)?, &key.aad()?)
+            .ok_or_else(|| {
+                TDKError::Profile(
+                    "Couldn't decrypt environments: wrong passphrase or security key, or the file was modified"
+                        .into(),
+                )
+            })?;
+        Ok((key, plaintext))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&(*self.key).into())
+    }
+
+    /// The key parameters, bound to the ciphertext so they can't be swapped.
+    fn aad(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(&self.params)
+            .map_err(|e| TDKError::Profile(format!("Couldn't serialise key parameters: {e}")))
+    }
+}
+
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    BASE64_URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|e| TDKError::Profile(format!("Invalid base64 in environments file: {e}")))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use sha2::Digest;
+
+    /// Stands in for an authenticator: HMAC-like output from a device secret.
+    pub(crate) struct FakeDevice(pub(crate) [u8; 32]);
+
+    impl HmacSecretDevice for FakeDevice {
+        fn hmac_secret(&self, credential_id: &[u8], salt: &[u8; 32]) -> Result<[u8; 32]> {
+            Ok(Sha256::new()
+                .chain_update(self.0)
+                .chain_update(credential_id)
+                .chain_update(salt)
+                .finalize()
+                .into())
+        }
+    }
+
+    /// Cheap Argon2 so tests stay fast.
+    pub(crate) fn factors(passphrase: &str) -> UnlockFactors {
+        UnlockFactors::passphrase(passphrase).with_kdf_cost(64, 1)
+    }
+
+    fn reopen(envelope: Envelope, factors: &UnlockFactors) -> Result<Vec<u8>> {
+        EnvironmentKey::open(envelope, factors).map(|(_, plaintext)| plaintext.to_vec())
+    }
+
+    #[test]
+    fn passphrase_round_trip() {
+        let key = EnvironmentKey::create(&factors("correct horse")).unwrap();
+        assert!(!key.requires_security_key());
+        let envelope = key.seal(b"secret profiles").unwrap();
+
+        assert_eq!(
+            reopen(envelope, &factors("correct horse")).unwrap(),
+            b"secret profiles"
+        );
+        let envelope = key.seal(b"secret profiles").unwrap();
+        assert!(reopen(envelope, &factors("wrong horse")).is_err());
+    }
+
+    #[test]
+    fn security_key_is_required_once_enrolled() {
+        let device = Arc::new(FakeDevice([7; 32]));
+        let with_key =
+            factors("pass").with_security_key(SecurityKey::new(device, b"cred".to_vec()));
+        let key = EnvironmentKey::create(&with_key).unwrap();
+        assert!(key.requires_security_key());
+
+        let seal = || key.seal(b"profiles").unwrap();
+        assert_eq!(reopen(seal(), &with_key).unwrap(), b"profiles");
+
+        // Passphrase alone
+        let err = reopen(seal(), &factors("pass")).unwrap_err();
+        assert!(
+            err.to_string().contains("locked to a security key"),
+            "{err}"
+        );
+
+        // Another authenticator holding a credential with the same ID
+        let other = factors("pass").with_security_key(SecurityKey::new(
+            Arc::new(FakeDevice([8; 32])),
+            b"cred".to_vec(),
+        ));
+        assert!(reopen(seal(), &other).is_err());
+
+        // Another credential
+        let other = factors("pass").with_security_key(SecurityKey::new(
+            Arc::new(FakeDevice([7; 32])),
+            b"other".to_vec(),
+        ));
+        let err = reopen(seal(), &other).unwrap_err();
+        assert!(err.to_string().contains("different security key"), "{err}");
+    }
+
+    #[test]
+    fn stored_parameters_are_authenticated() {
+        let device = Arc::new(FakeDevice([7; 32]));
+        let with_key =
+            factors("pass").with_security_key(SecurityKey::new(device, b"cred".to_vec()));
+        let key = EnvironmentKey::create(&with_key).unwrap();
+
+        // Dropping the security key requirement doesn't open the file
+        let mut envelope = key.seal(b"profiles").unwrap();
+        envelope.key.security_key = None;
+        assert!(reopen(envelope, &with_key).is_err());
+    }
+}
@@ -0,0 +1,339 @@
+/*!
+ * Per-profile usage counters, for hosts that meter or bill the agents they embed.
+ *
+ * A [`UsageTracker`] counts, for each profile DID, the messages it sent and
+ * received, the bytes those messages carried, the authentication handshakes
+ * it ran and the DID resolutions it triggered. The TDK records these as it
+ * works; read them with [`crate::TDKSharedState::usage`].
+ *
+ * ```
+ * use affinidi_tdk_common::usage::{UsageMetric, UsageQuota, UsageTracker};
+ *
+ * let tracker = UsageTracker::default();
+ * tracker.record_message_sent("did:example:alice", 1_200);
+ * tracker.set_quota(
+ *     "did:example:alice",
+ *     UsageQuota::new().with_limit(UsageMetric::BytesSent, 1_000),
+ * );
+ * assert_eq!(tracker.exceeded("did:example:alice"), vec![UsageMetric::BytesSent]);
+ *
+ * // Bill the period, then start the next one from zero
+ * let period = tracker.reset("did:example:alice");
+ * assert_eq!(period.messages_sent, 1);
+ * assert_eq!(tracker.usage("did:example:alice").messages_sent, 0);
+ * ```
+ *
+ * Counters live in memory and start from zero with each process. Quotas are
+ * tracked, not enforced: [`UsageTracker::exceeded`] tells the host which
+ * limits a profile is over, and the host decides what to do about it.
+ */
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A counted kind of usage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum UsageMetric {
+    /// Messages sent to a mediator
+    MessagesSent,
+    /// Messages received from a mediator
+    MessagesReceived,
+    /// Authentication handshakes and token refreshes run against a service
+    Authentications,
+    /// DID resolutions made on the profile's behalf that missed the
+    /// resolver cache
+    Resolutions,
+    /// Bytes of the messages sent
+    BytesSent,
+    /// Bytes of the messages received
+    BytesReceived,
+}
+
+impl UsageMetric {
+    /// Every metric, in counter order.
+    pub const ALL: [UsageMetric; 6] = [
+        UsageMetric::MessagesSent,
+        UsageMetric::MessagesReceived,
+        UsageMetric::Authentications,
+        UsageMetric::Resolutions,
+        UsageMetric::BytesSent,
+        UsageMetric::BytesReceived,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A snapshot of a profile's counters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageStats {
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub authentications: u64,
+    pub resolutions: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl UsageStats {
+    /// The value of one metric.
+    pub fn get(&self, metric: UsageMetric) -> u64 {
+        match metric {
+            UsageMetric::MessagesSent => self.messages_sent,
+            UsageMetric::MessagesReceived => self.messages_received,
+            UsageMetric::Authentications => self.authentications,
+            UsageMetric::Resolutions => self.resolutions,
+            UsageMetric::BytesSent => self.bytes_sent,
+            UsageMetric::BytesReceived => self.bytes_received,
+        }
+    }
+
+    /// The metrics of `quota` these stats are over.
+    pub fn exceeded(&self, quota: &UsageQuota) -> Vec<UsageMetric> {
+        UsageMetric::ALL
+            .into_iter()
+            .filter(|metric| {
+                quota
+                    .limit(*metric)
+                    .is_some_and(|max| self.get(*metric) > max)
+            })
+            .collect()
+    }
+
+    fn from_counters(counters: &[u64; 6]) -> Self {
+        UsageStats {
+            messages_sent: counters[UsageMetric::MessagesSent.index()],
+            messages_received: counters[UsageMetric::MessagesReceived.index()],
+            authentications: counters[UsageMetric::Authentications.index()],
+            resolutions: counters[UsageMetric::Resolutions.index()],
+            bytes_sent: counters[UsageMetric::BytesSent.index()],
+            bytes_received: counters[UsageMetric::BytesReceived.index()],
+        }
+    }
+}
+
+/// Upper limits on some metrics. Metrics without a limit are unbounded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageQuota {
+    limits: HashMap<UsageMetric, u64>,
+}
+
+impl UsageQuota {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow at most `max` of `metric`.
+    pub fn with_limit(mut self, metric: UsageMetric, max: u64) -> Self {
+        self.limits.insert(metric, max);
+        self
+    }
+
+    /// The limit on `metric`, if any.
+    pub fn limit(&self, metric: UsageMetric) -> Option<u64> {
+        self.limits.get(&metric).copied()
+    }
+}
+
+#[derive(Default)]
+struct ProfileUsage {
+    counters: [AtomicU64; 6],
+    quota: RwLock<Option<UsageQuota>>,
+}
+
+impl ProfileUsage {
+    fn snapshot(&self) -> UsageStats {
+        UsageStats::from_counters(&self.counters.each_ref().map(|c| c.load(Ordering::Relaxed)))
+    }
+
+    fn take(&self) -> UsageStats {
+        UsageStats::from_counters(
+            &self
+                .counters
+                .each_ref()
+                .map(|c| c.swap(0, Ordering::Relaxed)),
+        )
+    }
+}
+
+/// Usage counters for every profile, keyed by profile DID.
+///
+/// Cloning is cheap; clones share the same counters.
+#[derive(Clone, Default)]
+pub struct UsageTracker {
+    profiles: Arc<RwLock<HashMap<String, Arc<ProfileUsage>>>>,
+}
+
+impl UsageTracker {
+    /// Add `amount` to `metric` for `profile_did`.
+    pub fn record(&self, profile_did: &str, metric: UsageMetric, amount: u64) {
+        self.profile(profile_did).counters[metric.index()].fetch_add(amount, Ordering::Relaxed);
+    }
+
+    /// Count a message of `bytes` sent by `profile_did`.
+    pub fn record_message_sent(&self, profile_did: &str, bytes: usize) {
+        let usage = self.profile(profile_did);
+        usage.counters[UsageMetric::MessagesSent.index()].fetch_add(1, Ordering::Relaxed);
+        usage.counters[UsageMetric::BytesSent.index()].fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Count a message of `bytes` received by `profile_did`.
+    pub fn record_message_received(&self, profile_did: &str, bytes: usize) {
+        let usage = self.profile(profile_did);
+        usage.counters[UsageMetric::MessagesReceived.index()].fetch_add(1, Ordering::Relaxed);
+        usage.counters[UsageMetric::BytesReceived.index()]
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// The counters of `profile_did`; all zero for a profile never seen.
+    pub fn usage(&self, profile_did: &str) -> UsageStats {
+        self.read()
+            .get(profile_did)
+            .map(|usage| usage.snapshot())
+            .unwrap_or_default()
+    }
+
+    /// The counters of every profile seen.
+    pub fn all(&self) -> HashMap<String, UsageStats> {
+        self.read()
+            .iter()
+            .map(|(did, usage)| (did.clone(), usage.snapshot()))
+            .collect()
+    }
+
+    /// Zero the counters of `profile_did`, returning their values before the
+    /// reset. Usage recorded concurrently lands in exactly one of the two.
+    pub fn reset(&self, profile_did: &str) -> UsageStats {
+        self.read()
+            .get(profile_did)
+            .map(|usage| usage.take())
+            .unwrap_or_default()
+    }
+
+    /// Zero the counters of every profile, returning their values before the
+    /// reset.
+    pub fn reset_all(&self) -> HashMap<String, UsageStats> {
+        self.read()
+            .iter()
+            .map(|(did, usage)| (did.clone(), usage.take()))
+            .collect()
+    }
+
+    /// Set or clear the quota of `profile_did`. Quotas survive resets.
+    pub fn set_quota(&self, profile_did: &str, quota: impl Into<Option<UsageQuota>>) {
+        *self
+            .profile(profile_did)
+            .quota
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = quota.into();
+    }
+
+    /// The quota of `profile_did`, if one is set.
+    pub fn quota(&self, profile_did: &str) -> Option<UsageQuota> {
+        self.read().get(profile_did).and_then(|usage| {
+            usage
+                .quota
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone()
+        })
+    }
+
+    /// The metrics `profile_did` has used more of than its quota allows.
+    /// Empty when no quota is set.
+    pub fn exceeded(&self, profile_did: &str) -> Vec<UsageMetric> {
+        match self.quota(profile_did) {
+            Some(quota) => self.usage(profile_did).exceeded(&quota),
+            None => Vec::new(),
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<String, Arc<ProfileUsage>>> {
+        self.profiles
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The counters of `profile_did`, created on first use.
+    fn profile(&self, profile_did: &str) -> Arc<ProfileUsage> {
+        if let Some(usage) = self.read().get(profile_did) {
+            return usage.clone();
+        }
+        self.profiles
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(profile_did.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_are_per_profile() {
+        let tracker = UsageTracker::default();
+        tracker.record_message_sent("did:example:a", 10);
+        tracker.record_message_sent("did:example:a", 5);
+        tracker.record_message_received("did:example:b", 7);
+        tracker.record("did:example:b", UsageMetric::Resolutions, 2);
+
+        let a = tracker.usage("did:example:a");
+        assert_eq!((a.messages_sent, a.bytes_sent), (2, 15));
+        assert_eq!(a.messages_received, 0);
+
+        let b = tracker.usage("did:example:b");
+        assert_eq!((b.messages_received, b.bytes_received), (1, 7));
+        assert_eq!(b.resolutions, 2);
+
+        assert_eq!(tracker.usage("did:example:c"), UsageStats::default());
+        assert_eq!(tracker.all().len(), 2);
+    }
+
+    #[test]
+    fn reset_returns_the_period_and_keeps_quotas() {
+        let tracker = UsageTracker::default();
+        tracker.record("did:example:a", UsageMetric::Authentications, 3);
+        tracker.set_quota(
+            "did:example:a",
+            UsageQuota::new().with_limit(UsageMetric::Authentications, 2),
+        );
+        assert_eq!(
+            tracker.exceeded("did:example:a"),
+            vec![UsageMetric::Authentications]
+        );
+
+        assert_eq!(tracker.reset("did:example:a").authentications, 3);
+        assert_eq!(tracker.usage("did:example:a").authentications, 0);
+        assert!(tracker.exceeded("did:example:a").is_empty());
+        assert!(tracker.quota("did:example:a").is_some());
+
+        tracker.set_quota("did:example:a", None);
+        assert!(tracker.quota("did:example:a").is_none());
+    }
+
+    #[test]
+    fn stats_serialise_in_camel_case() {
+        let stats = UsageStats {
+            messages_sent: 1,
+            bytes_sent: 100,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(stats).unwrap();
+        assert_eq!(json["messagesSent"], 1);
+        assert_eq!(json["bytesSent"], 100);
+    }
+}
@@ -0,0 +1,800 @@
+/*!
+ * Wallet backups in the [Universal Wallet 2020] interop format.
+ *
+ * A [`WalletBackup`] moves a whole identity between wallets: the profiles of
+ * a [`TDKEnvironment`] with their secrets, plus the credentials and contacts
+ * the application keeps. The TDK doesn't store credentials or contacts
+ * itself, so they are passed in on export and handed back on import.
+ *
+ * The unlocked form is a `UniversalWallet2020` document. Its `contents` hold
+ * one item per profile, key, credential and contact:
+ *
+ * | Item | `type` | Fields |
+ * |---|---|---|
+ * | Profile | `Profile` | `id` (the DID), `name` (the alias), `mediator`, `capabilities`, `tags: ["admin"]` on the admin profile |
+ * | Key | the secret's type (`JsonWebKey2020`, `Multikey`, ...) | `id`, `controller` (the profile's DID), `privateKeyJwk` or `privateKeyMultibase`; on import also `privateKeyBase58` (Ed25519 and X25519) |
+ * | Credential | includes `VerifiableCredential` | the credential as issued |
+ * | Contact | `Person` | `id` (the contact's DID), `name` |
+ *
+ * Each profile is exported with the mediator it uses, including one it takes
+ * from the environment's default. On import, a key whose controller has no
+ * `Profile` item gets a profile of its own, named after the DID. Items of
+ * any other type, written by other wallets, are kept and written back on the
+ * next export.
+ *
+ * [`WalletBackup::lock`] encrypts the wallet into an `EncryptedWallet`
+ * credential. Its `encryptedWalletContents` is a JWE (`dir`, `A256GCM`)
+ * under a key derived from [`UnlockFactors`], as for an encrypted
+ * environments file (see [`crate::unlock`]). The derivation parameters are
+ * carried in the JWE protected header, so a wallet implementing the same
+ * derivation can open it. The locked form is not interoperable otherwise:
+ * to move to another vendor's wallet, move the unlocked form over a channel
+ * both sides already trust. Costs in the header above the TDK's limits are
+ * refused rather than run.
+ *
+ * ```no_run
+ * use affinidi_tdk_common::{
+ *     environments::{TDKEnvironment, TDKEnvironments},
+ *     unlock::UnlockFactors,
+ *     wallet::{WalletBackup, WalletContact},
+ * };
+ * # fn demo(environment: &TDKEnvironment, credentials: Vec<serde_json::Value>)
+ * #     -> Result<(), affinidi_tdk_common::errors::TDKError> {
+ * let factors = UnlockFactors::passphrase("correct horse battery staple");
+ *
+ * // Export
+ * let locked = WalletBackup::from_environment(environment)
+ *     .with_credentials(credentials)
+ *     .with_contacts(vec![WalletContact::new("did:web:bob.example.com").with_name("Bob")])
+ *     .lock(&factors)?;
+ *
+ * // Import, in this or another app
+ * let mut backup = WalletBackup::unlock(&locked, &factors)?;
+ * let mut environments = TDKEnvironments::load_file("environments.json")?;
+ * let mut restored = TDKEnvironment::default();
+ * backup.restore_into(&mut restored);
+ * environments.add("restored", restored);
+ * environments.save()?;
+ * # Ok(()) }
+ * ```
+ *
+ * [Universal Wallet 2020]: https://w3c-ccg.github.io/universal-wallet-interop-spec/
+ */
+
+use crate::{
+    capabilities::CapabilityRecord,
+    environments::TDKEnvironment,
+    errors::{Result, TDKError},
+    profiles::TDKProfile,
+    unlock::{EnvironmentKey, KeyParams, UnlockFactors},
+};
+use affinidi_encoding::decode_base58btc;
+use affinidi_secrets_resolver::secrets::Secret;
+use base64::{Engine, prelude::BASE64_URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use zeroize::Zeroizing;
+
+/// JSON-LD context of Universal Wallet documents
+pub const WALLET_CONTEXT: &str = "https://w3id.org/wallet/v1";
+
+/// JSON-LD context of the `EncryptedWallet` credential
+const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+
+const WALLET_TYPE: &str = "UniversalWallet2020";
+const ENCRYPTED_WALLET_TYPE: &str = "EncryptedWallet";
+const PROFILE_TYPE: &str = "Profile";
+const CONTACT_TYPE: &str = "Person";
+const CREDENTIAL_TYPE: &str = "VerifiableCredential";
+
+/// Tag on the `Profile` item of the environment's admin profile
+const ADMIN_TAG: &str = "admin";
+
+/// Someone the holder is in contact with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct WalletContact {
+    /// The contact's DID
+    #[serde(rename = "id")]
+    pub did: String,
+
+    /// Display name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl WalletContact {
+    pub fn new(did: impl Into<String>) -> Self {
+        WalletContact {
+            did: did.into(),
+            name: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
+
+/// A whole identity, on its way out of one wallet or into another.
+#[derive(Clone, Debug)]
+pub struct WalletBackup {
+    id: String,
+    profiles: Vec<TDKProfile>,
+    admin_profile: Option<TDKProfile>,
+    credentials: Vec<Value>,
+    contacts: Vec<WalletContact>,
+    /// Items this module doesn't read, kept for the next export
+    other: Vec<Value>,
+}
+
+/// Protected header of the `encryptedWalletContents` JWE
+#[derive(Serialize, Deserialize)]
+struct JweHeader {
+    alg: String,
+    enc: String,
+    #[serde(flatten)]
+    key: KeyParams,
+}
+
+/// The JWE (flattened JSON serialization) holding an encrypted wallet
+#[derive(Serialize, Deserialize)]
+struct Jwe {
+    protected: String,
+    iv: String,
+    ciphertext: String,
+    tag: String,
+}
+
+impl WalletBackup {
+    /// A backup of every profile in `environment`, including the admin
+    /// profile, with their secrets. Each profile carries the mediator it
+    /// resolves to.
+    pub fn from_environment(environment: &TDKEnvironment) -> Self {
+        let with_mediator = |profile: &TDKProfile| {
+            let mut profile = profile.clone();
+            profile.mediator = environment.resolve_mediator(&profile).map(str::to_owned);
+            profile
+        };
+        let mut profiles: Vec<TDKProfile> =
+            environment.profiles().values().map(with_mediator).collect();
+        profiles.sort_by(|a, b| a.alias.cmp(&b.alias));
+
+        WalletBackup {
+            id: format!("urn:uuid:{}", uuid::Uuid::new_v4()),
+            profiles,
+            admin_profile: environment.admin_did().map(with_mediator),
+            credentials: Vec::new(),
+            contacts: Vec::new(),
+            other: Vec::new(),
+        }
+    }
+
+    /// Add credentials, each as the JSON it was issued as.
+    pub fn with_credentials(mut self, credentials: impl IntoIterator<Item = Value>) -> Self {
+        self.credentials.extend(credentials);
+        self
+    }
+
+    pub fn with_contacts(mut self, contacts: impl IntoIterator<Item = WalletContact>) -> Self {
+        self.contacts.extend(contacts);
+        self
+    }
+
+    /// The wallet's id, kept across export and import
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Profiles, with their secrets. Empty after
+    /// [`restore_into`](Self::restore_into).
+    pub fn profiles(&self) -> &[TDKProfile] {
+        &self.profiles
+    }
+
+    pub fn admin_profile(&self) -> Option<&TDKProfile> {
+        self.admin_profile.as_ref()
+    }
+
+    pub fn credentials(&self) -> &[Value] {
+        &self.credentials
+    }
+
+    pub fn contacts(&self) -> &[WalletContact] {
+        &self.contacts
+    }
+
+    /// Move the profiles into `environment`, replacing any with the same
+    /// alias, and the admin profile if the backup has one. Credentials and
+    /// contacts stay on the backup for the application to store. Save the
+    /// environments file afterwards to keep the profiles.
+    pub fn restore_into(&mut self, environment: &mut TDKEnvironment) {
+        for profile in self.profiles.drain(..) {
+            environment.add_profile(profile);
+        }
+        if let Some(admin) = self.admin_profile.take() {
+            environment.set_admin_did(Some(admin));
+        }
+    }
+
+    /// The unlocked `UniversalWallet2020` document, private keys included.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.to_value()?)?)
+    }
+
+    /// Read an unlocked `UniversalWallet2020` document.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_value(serde_json::from_str(json)?)
+    }
+
+    /// The wallet encrypted into an `EncryptedWallet` credential.
+    pub fn lock(&self, factors: &UnlockFactors) -> Result<String> {
+        let key = EnvironmentKey::create(factors)?;
+        let header = JweHeader {
+            alg: "dir".into(),
+            enc: "A256GCM".into(),
+            key: key.params().clone(),
+        };
+        let protected = BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?);
+        let plaintext = Zeroizing::new(serde_json::to_vec(&self.to_value()?)?);
+        let (iv, mut ciphertext) = key.encrypt(&plaintext, protected.as_bytes())?;
+        let tag = ciphertext.split_off(ciphertext.len() - 16);
+        let jwe = Jwe {
+            protected,
+            iv: BASE64_URL_SAFE_NO_PAD.encode(iv),
+            ciphertext: BASE64_URL_SAFE_NO_PAD.encode(ciphertext),
+            tag: BASE64_URL_SAFE_NO_PAD.encode(tag),
+        };
+
+        Ok(serde_json::to_string_pretty(&json!({
+            "@context": [CREDENTIALS_CONTEXT, WALLET_CONTEXT],
+            "id": self.id,
+            "type": [CREDENTIAL_TYPE, ENCRYPTED_WALLET_TYPE],
+            "issuer": self.id,
+            "credentialSubject": {
+                "id": self.id,
+                "encryptedWalletContents": jwe,
+            },
+        }))?)
+    }
+
+    /// Decrypt an `EncryptedWallet` credential written by
+    /// [`lock`](Self::lock).
+    pub fn unlock(locked: &str, factors: &UnlockFactors) -> Result<Self> {
+        let credential: Value = serde_json::from_str(locked)?;
+        if !has_type(&credential, ENCRYPTED_WALLET_TYPE) {
+            return Err(TDKError::Wallet(
+                "Not an EncryptedWallet; read an unlocked wallet with WalletBackup::from_json"
+                    .into(),
+            ));
+        }
+        let jwe: Jwe = serde_json::from_value(
+            credential
+                .pointer("/credentialSubject/encryptedWalletContents")
+                .cloned()
+                .ok_or_else(|| {
+                    TDKError::Wallet("EncryptedWallet has no encryptedWalletContents".into())
+                })?,
+        )
+        .map_err(|e| TDKError::Wallet(format!("Invalid encryptedWalletContents: {e}")))?;
+
+        let header: JweHeader = serde_json::from_slice(&decode(&jwe.protected)?)
+            .map_err(|e| TDKError::Wallet(format!("Invalid JWE protected header: {e}")))?;
+        if header.alg != "dir" || header.enc != "A256GCM" {
+            return Err(TDKError::Wallet(format!(
+                "Unsupported wallet encryption (alg {}, enc {})",
+                header.alg, header.enc
+            )));
+        }
+        let iv: [u8; 12] = decode(&jwe.iv)?
+            .try_into()
+            .map_err(|_| TDKError::Wallet("JWE iv must be 12 bytes".into()))?;
+        let mut ciphertext = decode(&jwe.ciphertext)?;
+        ciphertext.extend(decode(&jwe.tag)?);
+
+        let key = EnvironmentKey::derive(header.key, factors)?;
+        let plaintext = key
+            .decrypt(&iv, &ciphertext, jwe.protected.as_bytes())
+            .ok_or_else(|| {
+                TDKError::Wallet(
+                    "Couldn't decrypt wallet: wrong passphrase or security key, or the backup was modified"
+                        .into(),
+                )
+            })?;
+        Self::from_value(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn to_value(&self) -> Result<Value> {
+        let mut contents = Vec::new();
+        let mut written_keys: Vec<&str> = Vec::new();
+        let profiles = self.profiles.iter().map(|profile| (profile, false));
+        for (profile, admin) in profiles.chain(self.admin_profile.iter().map(|p| (p, true))) {
+            let mut item = json!({
+                "id": profile.did,
+                "type": PROFILE_TYPE,
+                "name": profile.alias,
+            });
+            if let Some(mediator) = &profile.mediator {
+                item["mediator"] = json!(mediator);
+            }
+            if !profile.capabilities.is_empty() {
+                item["capabilities"] = serde_json::to_value(&profile.capabilities)?;
+            }
+            if admin {
+                item["tags"] = json!([ADMIN_TAG]);
+            }
+            contents.push(item);
+
+            // A DID shared by two profiles has its keys written once.
+            for secret in profile.secrets() {
+                if written_keys.contains(&secret.id.as_str()) {
+                    continue;
+                }
+                written_keys.push(&secret.id);
+                let mut item = serde_json::to_value(secret.expose_secret())?;
+                item["controller"] = json!(profile.did);
+                contents.push(item);
+            }
+        }
+        contents.extend(self.credentials.iter().cloned());
+        for contact in &self.contacts {
+            let mut item = serde_json::to_value(contact)?;
+            item["type"] = json!(CONTACT_TYPE);
+            contents.push(item);
+        }
+        contents.extend(self.other.iter().cloned());
+
+        Ok(json!({
+            "@context": [WALLET_CONTEXT],
+            "id": self.id,
+            "type": WALLET_TYPE,
+            "status": "UNLOCKED",
+            "contents": contents,
+        }))
+    }
+
+    fn from_value(wallet: Value) -> Result<Self> {
+        if !has_type(&wallet, WALLET_TYPE) {
+            return Err(TDKError::Wallet(format!("Not a {WALLET_TYPE} document")));
+        }
+        if wallet.get("status").and_then(Value::as_str) == Some("LOCKED") {
+            return Err(TDKError::Wallet(
+                "Wallet is locked; open it with WalletBackup::unlock".into(),
+            ));
+        }
+        let id = wallet.get("id").and_then(Value::as_str).map_or_else(
+            || format!("urn:uuid:{}", uuid::Uuid::new_v4()),
+            str::to_owned,
+        );
+
+        let mut backup = WalletBackup {
+            id,
+            profiles: Vec::new(),
+            admin_profile: None,
+            credentials: Vec::new(),
+            contacts: Vec::new(),
+            other: Vec::new(),
+        };
+        // (controller DID, key)
+        let mut keys: Vec<(String, Secret)> = Vec::new();
+
+        let contents = match wallet.get("contents") {
+            Some(Value::Array(contents)) => contents.clone(),
+            None => Vec::new(),
+            Some(_) => return Err(TDKError::Wallet("Wallet contents must be an array".into())),
+        };
+        for mut item in contents {
+            if has_type(&item, PROFILE_TYPE) {
+                let (profile, admin) = read_profile(&item)?;
+                if admin {
+                    backup.admin_profile = Some(profile);
+                } else {
+                    backup.profiles.push(profile);
+                }
+            } else if item.get("privateKeyJwk").is_some()
+                || item.get("privateKeyMultibase").is_some()
+                || item.get("privateKeyBase58").is_some()
+            {
+                let controller = item
+                    .as_object_mut()
+                    .and_then(|item| item.remove("controller"));
+                let secret = read_key(item)?;
+                let controller = controller
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .unwrap_or_else(|| secret.id.split('#').next().unwrap_or_default())
+                    .to_string();
+                keys.push((controller, secret));
+            } else if has_type(&item, CREDENTIAL_TYPE) {
+                backup.credentials.push(item);
+            } else if has_type(&item, CONTACT_TYPE) && item.get("id").is_some() {
+                backup.contacts.push(
+                    serde_json::from_value(item)
+                        .map_err(|e| TDKError::Wallet(format!("Invalid contact: {e}")))?,
+                );
+            } else {
+                backup.other.push(item);
+            }
+        }
+
+        for (controller, _) in &keys {
+            let known = backup
+                .profiles
+                .iter()
+                .chain(&backup.admin_profile)
+                .any(|profile| &profile.did == controller);
+            if !known {
+                backup
+                    .profiles
+                    .push(TDKProfile::new(controller, controller, None, Vec::new()));
+            }
+        }
+        for profile in backup
+            .profiles
+            .iter_mut()
+            .chain(backup.admin_profile.as_mut())
+        {
+            profile.secrets = keys
+                .iter()
+                .filter(|(controller, _)| controller == &profile.did)
+                .map(|(_, secret)| secret.clone())
+                .collect();
+        }
+        Ok(backup)
+    }
+}
+
+/// Reads a key item. `Secret` reads JWK and multibase keys; the older
+/// `privateKeyBase58` form of other wallets is an Ed25519 or X25519 key
+/// whose curve comes from the `type`.
+fn read_key(item: Value) -> Result<Secret> {
+    let Some(base58) = item.get("privateKeyBase58") else {
+        return serde_json::from_value(item)
+            .map_err(|e| TDKError::Wallet(format!("Invalid key: {e}")));
+    };
+    let id = item
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| TDKError::Wallet("Key has no id".into()))?;
+    let type_ = item.get("type").and_then(Value::as_str).unwrap_or_default();
+    let bytes = Zeroizing::new(
+        base58
+            .as_str()
+            .and_then(|base58| decode_base58btc(&format!("z{base58}")).ok())
+            .ok_or_else(|| TDKError::Wallet(format!("Invalid privateKeyBase58 of {id}")))?,
+    );
+    // An Ed25519 private key is the 32-byte seed, often followed by the
+    // public key.
+    let seed: &[u8; 32] = bytes
+        .first_chunk()
+        .filter(|_| bytes.len() == 32 || bytes.len() == 64)
+        .ok_or_else(|| {
+            TDKError::Wallet(format!("privateKeyBase58 of {id} must be 32 or 64 bytes"))
+        })?;
+    match type_ {
+        "Ed25519VerificationKey2018" | "Ed25519VerificationKey2020" => {
+            Ok(Secret::generate_ed25519(Some(id), Some(seed)))
+        }
+        "X25519KeyAgreementKey2019" | "X25519KeyAgreementKey2020" if bytes.len() == 32 => {
+            Secret::generate_x25519(Some(id), Some(seed))
+                .map_err(|e| TDKError::Wallet(format!("Invalid key {id}: {e}")))
+        }
+        _ => Err(TDKError::Wallet(format!(
+            "Unsupported privateKeyBase58 key type ({type_}) of {id}"
+        ))),
+    }
+}
+
+/// Reads a `Profile` item; the flag is set for the admin profile.
+fn read_profile(item: &Value) -> Result<(TDKProfile, bool)> {
+    let did = item
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| TDKError::Wallet("Profile has no id".into()))?;
+    let alias = item.get("name").and_then(Value::as_str).unwrap_or(did);
+    let mediator = item.get("mediator").and_then(Value::as_str);
+    let mut profile = TDKProfile::new(alias, did, mediator, Vec::new());
+    if let Some(capabilities) = item.get("capabilities") {
+        profile.capabilities =
+            serde_json::from_value::<Vec<CapabilityRecord>>(capabilities.clone())
+                .map_err(|e| TDKError::Wallet(format!("Invalid capabilities of {did}: {e}")))?;
+    }
+    let admin = item
+        .get("tags")
+        .and_then(Value::as_array)
+        .is_some_and(|tags| tags.iter().any(|tag| tag == ADMIN_TAG));
+    Ok((profile, admin))
+}
+
+/// Whether `type` is `type_`, or an array holding it.
+fn has_type(value: &Value, type_: &str) -> bool {
+    match value.get("type") {
+        Some(Value::String(t)) => t == type_,
+        Some(Value::Array(types)) => types.iter().any(|t| t == type_),
+        _ => false,
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>> {
+    BASE64_URL_SAFE_NO_PAD
+        .decode(value)
+        .map_err(|e| TDKError::Wallet(format!("Invalid base64 in wallet backup: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        capabilities::Capability,
+        unlock::SecurityKey,
+        unlock::tests::{FakeDevice, factors},
+    };
+    use std::sync::Arc;
+
+    fn environment() -> TDKEnvironment {
+        let mut environment = TDKEnvironment::default();
+        environment.set_default_mediator(Some("did:web:mediator.example.com".into()));
+
+        let mut alice = TDKProfile::new(
+            "Alice",
+            "did:example:alice",
+            None,
+            vec![
+                Secret::generate_ed25519(Some("did:example:alice#key-1"), Some(&[1; 32])),
+                Secret::generate_x25519(Some("did:example:alice#key-2"), Some(&[2; 32])).unwrap(),
+            ],
+        );
+        alice.capabilities.push(CapabilityRecord {
+            capability: Capability::MediatorAccount {
+                mediator: "did:web:mediator.example.com".into(),
+            },
+            verified_at: 7,
+        });
+        environment.add_profile(alice);
+        environment.add_profile(TDKProfile::new(
+            "Bob",
+            "did:example:bob",
+            Some("did:web:other-mediator.example.com"),
+            vec![Secret::generate_ed25519(
+                Some("did:example:bob#key-1"),
+                Some(&[3; 32]),
+            )],
+        ));
+        environment.set_admin_did(Some(TDKProfile::new(
+            "Admin",
+            "did:example:admin",
+            None,
+            vec![Secret::generate_ed25519(
+                Some("did:example:admin#key-1"),
+                Some(&[4; 32]),
+            )],
+        )));
+        environment
+    }
+
+    fn credential() -> Value {
+        json!({
+            "@context": ["https://www.w3.org/ns/credentials/v2"],
+            "id": "urn:uuid:2b0a7a4e-8a4f-4d55-9c3d-1c2f2e6f0d1a",
+            "type": ["VerifiableCredential", "MembershipCredential"],
+            "issuer": "did:example:issuer",
+            "credentialSubject": {"id": "did:example:alice"},
+        })
+    }
+
+    fn backup() -> WalletBackup {
+        WalletBackup::from_environment(&environment())
+            .with_credentials([credential()])
+            .with_contacts([WalletContact::new("did:example:carol").with_name("Carol")])
+    }
+
+    fn private_bytes(profile: &TDKProfile) -> Vec<(String, Vec<u8>)> {
+        let mut keys: Vec<_> = profile
+            .secrets()
+            .iter()
+            .map(|s| (s.id.clone(), s.get_private_bytes().to_vec()))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    fn assert_restores(mut restored: WalletBackup) {
+        let original = environment();
+        assert_eq!(restored.credentials(), [credential()]);
+        assert_eq!(restored.contacts()[0].name.as_deref(), Some("Carol"));
+
+        let mut environment = TDKEnvironment::default();
+        restored.restore_into(&mut environment);
+        assert!(restored.profiles().is_empty());
+
+        let alice = environment.profile("Alice").unwrap();
+        assert_eq!(
+            private_bytes(alice),
+            private_bytes(original.profile("Alice").unwrap())
+        );
+        assert_eq!(
+            alice.capabilities,
+            original.profile("Alice").unwrap().capabilities
+        );
+        // The environment's default mediator travels with the profile.
+        assert_eq!(
+            alice.mediator.as_deref(),
+            Some("did:web:mediator.example.com")
+        );
+        assert_eq!(
+            environment.profile("Bob").unwrap().mediator.as_deref(),
+            Some("did:web:other-mediator.example.com")
+        );
+
+        let admin = environment.admin_did().unwrap();
+        assert_eq!(admin.alias, "Admin");
+        assert_eq!(
+            private_bytes(admin),
+            private_bytes(original.admin_did().unwrap())
+        );
+    }
+
+    #[test]
+    fn unlocked_round_trip() {
+        let json = backup().to_json().unwrap();
+        let wallet: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(wallet["type"], WALLET_TYPE);
+        assert_eq!(wallet["@context"][0], WALLET_CONTEXT);
+
+        let restored = WalletBackup::from_json(&json).unwrap();
+        assert_eq!(restored.id(), wallet["id"]);
+        assert_restores(restored);
+    }
+
+    #[test]
+    fn locked_round_trip() {
+        let locked = backup().lock(&factors("correct horse")).unwrap();
+        let credential: Value = serde_json::from_str(&locked).unwrap();
+        assert!(has_type(&credential, ENCRYPTED_WALLET_TYPE));
+        assert!(!locked.contains("privateKeyJwk"));
+        assert!(!locked.contains("Alice"));
+
+        assert_restores(WalletBackup::unlock(&locked, &factors("correct horse")).unwrap());
+
+        let err = WalletBackup::unlock(&locked, &factors("wrong horse")).unwrap_err();
+        assert!(matches!(err, TDKError::Wallet(_)), "{err}");
+    }
+
+    #[test]
+    fn locked_with_security_key() {
+        let with_key = factors("pass").with_security_key(SecurityKey::new(
+            Arc::new(FakeDevice([7; 32])),
+            b"cred".to_vec(),
+        ));
+        let locked = backup().lock(&with_key).unwrap();
+        assert_restores(WalletBackup::unlock(&locked, &with_key).unwrap());
+        assert!(WalletBackup::unlock(&locked, &factors("pass")).is_err());
+    }
+
+    #[test]
+    fn protected_header_is_authenticated() {
+        let locked = backup().lock(&factors("pass")).unwrap();
+        let mut credential: Value = serde_json::from_str(&locked).unwrap();
+        let jwe = &mut credential["credentialSubject"]["encryptedWalletContents"];
+        let mut header: Value =
+            serde_json::from_slice(&decode(jwe["protected"].as_str().unwrap()).unwrap()).unwrap();
+        header["kid"] = json!("injected");
+        jwe["protected"] = json!(BASE64_URL_SAFE_NO_PAD.encode(header.to_string()));
+
+        let err = WalletBackup::unlock(&credential.to_string(), &factors("pass")).unwrap_err();
+        assert!(err.to_string().contains("Couldn't decrypt wallet"), "{err}");
+    }
+
+    #[test]
+    fn imports_wallet_from_another_app() {
+        let key = Secret::generate_ed25519(Some("did:example:dave#key-1"), Some(&[5; 32]));
+        let mut key_item = serde_json::to_value(key.expose_secret()).unwrap();
+        key_item["controller"] = json!("did:example:dave");
+        let mnemonic = json!({
+            "id": "urn:uuid:c410e44a-9525-11ea-bb37-0242ac130002",
+            "type": "Mnemonic",
+            "value": "humble piece toy mimic miss hurdle smile awkward patch drama hurry mixture",
+        });
+        let wallet = json!({
+            "@context": [WALLET_CONTEXT],
+            "id": "did:example:wallet",
+            "type": WALLET_TYPE,
+            "status": "UNLOCKED",
+            "contents": [key_item, credential(), mnemonic],
+        });
+
+        let mut backup = WalletBackup::from_json(&wallet.to_string()).unwrap();
+        assert_eq!(backup.credentials().len(), 1);
+
+        // The unknown item survives the next export.
+        let exported: Value = serde_json::from_str(&backup.to_json().unwrap()).unwrap();
+        assert!(exported["contents"].as_array().unwrap().contains(&mnemonic));
+
+        // A key with no profile gets one named after its DID.
+        let mut environment = TDKEnvironment::default();
+        backup.restore_into(&mut environment);
+        let dave = environment.profile("did:example:dave").unwrap();
+        assert_eq!(
+            dave.secrets()[0].get_private_bytes(),
+            key.get_private_bytes()
+        );
+    }
+
+    #[test]
+    fn imports_base58_keys_and_exports_them_again() {
+        let base58 = |bytes: &[u8]| affinidi_encoding::encode_base58btc(bytes)[1..].to_string();
+        let signing = Secret::generate_ed25519(Some("did:example:erin#key-1"), Some(&[6; 32]));
+        let agreement =
+            Secret::generate_x25519(Some("did:example:erin#key-2"), Some(&[8; 32])).unwrap();
+        // Ed25519 as seed and public key, the usual 2018 suite encoding
+        let seed_and_public = [signing.get_private_bytes(), signing.get_public_bytes()].concat();
+        let wallet = json!({
+            "@context": [WALLET_CONTEXT],
+            "id": "did:example:wallet",
+            "type": WALLET_TYPE,
+            "contents": [
+                {
+                    "id": "did:example:erin#key-1",
+                    "type": "Ed25519VerificationKey2018",
+                    "controller": "did:example:erin",
+                    "privateKeyBase58": base58(&seed_and_public),
+                },
+                {
+                    "id": "did:example:erin#key-2",
+                    "type": "X25519KeyAgreementKey2019",
+                    "controller": "did:example:erin",
+                    "privateKeyBase58": base58(agreement.get_private_bytes()),
+                },
+            ],
+        });
+
+        let imported = WalletBackup::from_json(&wallet.to_string()).unwrap();
+        let expected = TDKProfile::new("erin", "did:example:erin", None, vec![signing, agreement]);
+        assert_eq!(
+            private_bytes(&imported.profiles()[0]),
+            private_bytes(&expected)
+        );
+        let erin = &imported.profiles()[0];
+        assert_eq!(
+            erin.secrets()[0].get_public_bytes(),
+            expected.secrets()[0].get_public_bytes()
+        );
+
+        // Exported in this wallet's own form, and imported again
+        let reimported = WalletBackup::from_json(&imported.to_json().unwrap()).unwrap();
+        assert_eq!(
+            private_bytes(&reimported.profiles()[0]),
+            private_bytes(&expected)
+        );
+
+        let mut bad = wallet.clone();
+        bad["contents"][0]["type"] = json!("EcdsaSecp256k1VerificationKey2019");
+        let err = WalletBackup::from_json(&bad.to_string()).unwrap_err();
+        assert!(err.to_string().contains("Unsupported"), "{err}");
+    }
+
+    #[test]
+    fn unlock_refuses_oversized_kdf_cost() {
+        let locked = backup().lock(&factors("pass")).unwrap();
+        let mut credential: Value = serde_json::from_str(&locked).unwrap();
+        let jwe = &mut credential["credentialSubject"]["encryptedWalletContents"];
+        let mut header: Value =
+            serde_json::from_slice(&decode(jwe["protected"].as_str().unwrap()).unwrap()).unwrap();
+        header["kdf"]["memoryKib"] = json!(u32::MAX);
+        jwe["protected"] = json!(BASE64_URL_SAFE_NO_PAD.encode(header.to_string()));
+
+        let err = WalletBackup::unlock(&credential.to_string(), &factors("pass")).unwrap_err();
+        assert!(err.to_string().contains("out of range"), "{err}");
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        let err = WalletBackup::from_json(r#"{"type": "VerifiableCredential"}"#).unwrap_err();
+        assert!(matches!(err, TDKError::Wallet(_)));
+
+        let unlocked = backup().to_json().unwrap();
+        let err = WalletBackup::unlock(&unlocked, &factors("pass")).unwrap_err();
+        assert!(err.to_string().contains("from_json"), "{err}");
+    }
+}
@@ -76,6 +76,83 @@ async fn full_lifecycle_via_public_api() {
     state.shutdown().await;
 }
 
+/// The secrets and authentication tasks run under the state's supervisor: both
+/// report healthy after construction and stop (with an event) on shutdown.
+#[tokio::test]
+async fn background_tasks_are_supervised() {
+    use affinidi_tdk_common::{
+        task_utils::ComponentState,
+        tasks::{SECRETS_TASK_NAME, authentication::AUTHENTICATION_TASK_NAME},
+    };
+
+    let config = TDKConfig::builder()
+        .with_load_environment(false)
+        .with_environment(TDKEnvironment::default())
+        .build()
+        .expect("config builds");
+    let state = TDKSharedState::new(config).await.expect("state builds");
+
+    let health = state.task_health();
+    for name in [SECRETS_TASK_NAME, AUTHENTICATION_TASK_NAME] {
+        let task = health.get(name).expect("task registered");
+        assert_eq!(task.state, ComponentState::Running);
+        assert!(task.load_bearing);
+    }
+
+    let mut events = state.task_events();
+    state.shutdown().await;
+    let event = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv())
+        .await
+        .expect("stop event")
+        .expect("event channel open");
+    assert_eq!(event.state, ComponentState::Stopped);
+}
+
+/// Terminating the supervised authentication task stops it for good rather
+/// than having the supervisor restart it.
+#[tokio::test]
+async fn terminated_authentication_task_is_not_restarted() {
+    use affinidi_tdk_common::{
+        task_utils::ComponentState, tasks::authentication::AUTHENTICATION_TASK_NAME,
+    };
+
+    let config = TDKConfig::builder()
+        .with_load_environment(false)
+        .with_environment(TDKEnvironment::default())
+        .build()
+        .expect("config builds");
+    let state = TDKSharedState::new(config).await.expect("state builds");
+
+    tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        state.authentication().terminate(),
+    )
+    .await
+    .expect("terminate returns");
+
+    // The supervisor records the stop as the task exits
+    let health = state.task_health();
+    let stopped = || {
+        health
+            .get(AUTHENTICATION_TASK_NAME)
+            .is_some_and(|task| task.state == ComponentState::Stopped)
+    };
+    for _ in 0..100 {
+        if stopped() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let task = health
+        .get(AUTHENTICATION_TASK_NAME)
+        .expect("task registered")
+        .clone();
+    assert_eq!(task.state, ComponentState::Stopped);
+    assert_eq!(task.restarts, 0);
+
+    state.shutdown().await;
+}
+
 /// Smoke test for `affinidi_tdk_common::create_http_client(&[])` — covers
 /// the no-extra-roots path that `TDKSharedState::new` takes when no
 /// `ssl_certificates` are configured.
@@ -87,3 +164,47 @@ fn create_http_client_default_succeeds() {
     // would require sending a request, which we avoid in unit-style tests.
     drop(client);
 }
+
+/// Replacing, removing or shredding a secret through the state is announced,
+/// so holders of derived key material (packing contexts) can drop it.
+#[tokio::test]
+async fn replaced_removed_and_shredded_secrets_are_announced() {
+    let config = TDKConfig::builder()
+        .with_load_environment(false)
+        .with_environment(TDKEnvironment::default())
+        .build()
+        .expect("config builds");
+    let state = TDKSharedState::new(config).await.expect("state builds");
+
+    let removed = "did:example:changes#key-1";
+    let shredded = "did:example:changes#key-2";
+    let profile = TDKProfile::new(
+        "changes",
+        "did:example:changes",
+        None,
+        vec![
+            Secret::generate_ed25519(Some(removed), Some(&[4u8; 32])),
+            Secret::generate_ed25519(Some(shredded), Some(&[5u8; 32])),
+        ],
+    );
+    state.add_profile(&profile).await;
+
+    let mut changes = state.subscribe_secret_changes();
+    // Re-adding replaces both keys
+    state.add_profile(&profile).await;
+    assert_eq!(changes.recv().await.expect("replaced"), removed);
+    assert_eq!(changes.recv().await.expect("replaced"), shredded);
+
+    assert!(state.remove_secret(removed).await.is_some());
+    assert!(state.shred_secret(shredded, None).await.expect("shred"));
+    // Neither key is left, so nothing more is announced
+    assert!(state.remove_secret(removed).await.is_none());
+    assert!(!state.shred_secret(shredded, None).await.expect("shred"));
+
+    assert_eq!(changes.recv().await.expect("removal"), removed);
+    assert_eq!(changes.recv().await.expect("shred"), shredded);
+    assert!(changes.try_recv().is_err());
+    assert!(state.secrets_resolver().get_secret(removed).await.is_none());
+
+    state.shutdown().await;
+}
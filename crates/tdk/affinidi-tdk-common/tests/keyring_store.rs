@@ -9,8 +9,11 @@
 //! process-global. This is the only place in the workspace that touches
 //! the keyring default store; any new keyring tests should live here.
 
-use affinidi_secrets_resolver::secrets::Secret;
-use affinidi_tdk_common::secrets::{KeyringStore, init_keyring};
+use affinidi_secrets_resolver::{
+    SecretsResolver,
+    secrets::{Secret, SerializeWithPrivate},
+};
+use affinidi_tdk_common::secrets::{KeychainSecretsResolver, KeyringStore, init_keyring};
 use base64::{Engine, prelude::BASE64_STANDARD_NO_PAD};
 use keyring_core::{Entry, mock::Store as MockStore};
 use std::sync::Mutex;
@@ -57,7 +60,7 @@ fn read_legacy_base64_entry_migrates() {
     let secrets = vec![sample_secret(&format!("{did}#key-1"))];
 
     // Mimic the 0.5.x on-disk format: base64(json_bytes).
-    let json_bytes = serde_json::to_vec(&secrets).unwrap();
+    let json_bytes = serde_json::to_vec(&SerializeWithPrivate::new(&secrets)).unwrap();
     let legacy_payload = BASE64_STANDARD_NO_PAD.encode(&json_bytes);
     Entry::new(service, did)
         .unwrap()
@@ -89,3 +92,201 @@ fn init_keyring_respects_existing_default() {
     store.save("did:example:init", &secrets).unwrap();
     store.delete("did:example:init").unwrap();
 }
+
+#[test]
+fn shred_removes_the_entry() {
+    let _g = SERIALISE.lock().unwrap();
+    install_mock_store();
+    let store = KeyringStore::new("tdk-test-shred");
+    let did = "did:example:shred";
+    store
+        .save(did, &[sample_secret(&format!("{did}#key-1"))])
+        .unwrap();
+
+    store.shred(did).unwrap();
+    assert!(store.read(did).is_err());
+    // Shredding a missing entry is not an error
+    store.shred(did).unwrap();
+}
+
+#[test]
+fn shred_key_keeps_other_keys() {
+    let _g = SERIALISE.lock().unwrap();
+    install_mock_store();
+    let store = KeyringStore::new("tdk-test-shred-key");
+    let did = "did:example:shred-key";
+    let key_1 = format!("{did}#key-1");
+    let key_2 = format!("{did}#key-2");
+    store
+        .save(did, &[sample_secret(&key_1), sample_secret(&key_2)])
+        .unwrap();
+
+    assert!(store.shred_key(did, &key_1).unwrap());
+    assert!(!store.shred_key(did, &key_1).unwrap());
+    let loaded = store.read(did).unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].id, key_2);
+
+    // Shredding the last key shreds the entry
+    assert!(store.shred_key(did, &key_2).unwrap());
+    assert!(store.read(did).is_err());
+    assert!(!store.shred_key(did, &key_2).unwrap());
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn find_missing_entry_is_none() {
+    let _g = SERIALISE.lock().unwrap();
+    install_mock_store();
+    let store = KeyringStore::new("tdk-test-find");
+    assert!(store.find("did:example:never-saved").unwrap().is_none());
+}
+
+#[test]
+fn keychain_resolver_reads_through_on_first_use() {
+    let _g = SERIALISE.lock().unwrap();
+    install_mock_store();
+    let service = "tdk-test-keychain-read";
+    let did = "did:example:keychain-read";
+    let key_1 = format!("{did}#key-1");
+    let key_2 = format!("{did}#key-2");
+    KeyringStore::new(service)
+        .save(did, &[sample_secret(&key_1), sample_secret(&key_2)])
+        .unwrap();
+
+    block_on(async {
+        let resolver = KeychainSecretsResolver::new(service);
+        assert!(resolver.is_empty().await);
+
+        // One lookup loads every key of the DID
+        assert_eq!(resolver.get_secret(&key_1).await.unwrap().id, key_1);
+        assert_eq!(resolver.len().await, 2);
+        assert!(resolver.get_secret(&format!("{did}#key-3")).await.is_none());
+        assert!(
+            resolver
+                .get_secret("did:example:unknown#key-1")
+                .await
+                .is_none()
+        );
+
+        // Forgotten keys are read again when next needed
+        resolver.forget(did);
+        assert!(resolver.is_empty().await);
+        assert_eq!(
+            resolver.find_secrets(std::slice::from_ref(&key_2)).await,
+            vec![key_2.clone()]
+        );
+    });
+    KeyringStore::new(service).delete(did).unwrap();
+}
+
+#[test]
+fn keychain_resolver_persists_inserts_and_removals() {
+    let _g = SERIALISE.lock().unwrap();
+    install_mock_store();
+    let service = "tdk-test-keychain-write";
+    let did = "did:example:keychain-write";
+    let key_1 = format!("{did}#key-1");
+    let key_2 = format!("{did}#key-2");
+    let store = KeyringStore::new(service);
+    store.save(did, &[sample_secret(&key_1)]).unwrap();
+
+    block_on(async {
+        let resolver = KeychainSecretsResolver::new(service);
+        // Merged into the existing entry, not replacing it
+        resolver.insert(sample_secret(&key_2)).await;
+        let stored = store.read(did).unwrap();
+        assert_eq!(stored.len(), 2);
+
+        assert!(resolver.remove_secret(&key_1).await.is_some());
+        let stored = store.read(did).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, key_2);
+
+        // A fresh resolver sees only what the keychain holds
+        let fresh = KeychainSecretsResolver::new(service);
+        assert_eq!(fresh.load(did).await.unwrap(), 1);
+        assert!(fresh.get_secret(&key_1).await.is_none());
+        assert!(fresh.get_secret(&key_2).await.is_some());
+    });
+    store.delete(did).unwrap();
+}
+
+#[test]
+fn keychain_resolver_concurrent_inserts_for_one_did_all_persist() {
+    let _g = SERIALISE.lock().unwrap();
+    install_mock_store();
+    let service = "tdk-test-keychain-concurrent";
+    let did = "did:example:keychain-concurrent";
+    let store = KeyringStore::new(service);
+
+    block_on(async {
+        let resolver = KeychainSecretsResolver::new(service);
+        let inserts = (1..=8).map(|n| {
+            let resolver = resolver.clone();
+            tokio::spawn(async move {
+                resolver
+                    .insert(sample_secret(&format!("{did}#key-{n}")))
+                    .await
+            })
+        });
+        for insert in inserts.collect::<Vec<_>>() {
+            insert.await.unwrap();
+        }
+    });
+    assert_eq!(store.read(did).unwrap().len(), 8);
+    store.delete(did).unwrap();
+}
+
+#[test]
+fn tdk_reads_secrets_through_a_keychain_resolver() {
+    use affinidi_tdk_common::{
+        TDKSharedState, config::TDKConfig, environments::TDKEnvironment,
+        secrets::TDKSecretsResolver,
+    };
+
+    let _g = SERIALISE.lock().unwrap();
+    install_mock_store();
+    let service = "tdk-test-keychain-state";
+    let did = "did:example:keychain-state";
+    let key_1 = format!("{did}#key-1");
+    let store = KeyringStore::new(service);
+    store.save(did, &[sample_secret(&key_1)]).unwrap();
+
+    block_on(async {
+        let config = TDKConfig::builder()
+            .with_load_environment(false)
+            .with_environment(TDKEnvironment::default())
+            .with_secrets_resolver(KeychainSecretsResolver::new(service))
+            .build()
+            .unwrap();
+        let state = TDKSharedState::new(config).await.unwrap();
+        assert!(matches!(
+            state.secrets_resolver(),
+            TDKSecretsResolver::Keychain(_)
+        ));
+        assert_eq!(
+            state
+                .secrets_resolver()
+                .get_secret(&key_1)
+                .await
+                .unwrap()
+                .id,
+            key_1
+        );
+
+        // Profile secrets added through the state land in the keychain
+        let key_2 = format!("{did}#key-2");
+        state.secrets_resolver().insert(sample_secret(&key_2)).await;
+        assert_eq!(store.read(did).unwrap().len(), 2);
+
+        state.shutdown().await;
+    });
+    store.delete(did).unwrap();
+}
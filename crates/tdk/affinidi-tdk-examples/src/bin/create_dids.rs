@@ -0,0 +1,13 @@
+//! [`affinidi_tdk_examples::create_dids`], as a binary:
+//!
+//! ```bash
+//! cargo run -p affinidi-tdk-examples --bin create-dids
+//! ```
+
+use affinidi_tdk_examples::{Result, create_dids, headless_tdk};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    create_dids::run(&headless_tdk().await?).await?;
+    Ok(())
+}
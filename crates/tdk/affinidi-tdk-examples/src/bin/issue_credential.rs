@@ -0,0 +1,13 @@
+//! [`affinidi_tdk_examples::issue_credential`], as a binary:
+//!
+//! ```bash
+//! cargo run -p affinidi-tdk-examples --bin issue-credential
+//! ```
+
+use affinidi_tdk_examples::{Result, headless_tdk, issue_credential};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    issue_credential::run(&headless_tdk().await?).await?;
+    Ok(())
+}
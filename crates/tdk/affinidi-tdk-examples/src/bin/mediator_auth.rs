@@ -0,0 +1,16 @@
+//! [`affinidi_tdk_examples::mediator_auth`], as a binary:
+//!
+//! ```bash
+//! cargo run -p affinidi-tdk-examples --bin mediator-auth
+//! ```
+
+use affinidi_messaging_test_mediator::TestEnvironment;
+use affinidi_tdk_examples::{Result, mediator_auth};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let env = TestEnvironment::spawn().await?;
+    let result = mediator_auth::run(&env).await;
+    env.shutdown().await?;
+    result.map(|_| ())
+}
@@ -0,0 +1,13 @@
+//! [`affinidi_tdk_examples::rotate_keys`], as a binary:
+//!
+//! ```bash
+//! cargo run -p affinidi-tdk-examples --bin rotate-keys
+//! ```
+
+use affinidi_tdk_examples::{Result, headless_tdk, rotate_keys};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    rotate_keys::run(&headless_tdk().await?).await?;
+    Ok(())
+}
@@ -0,0 +1,16 @@
+//! [`affinidi_tdk_examples::send_message`], as a binary:
+//!
+//! ```bash
+//! cargo run -p affinidi-tdk-examples --bin send-message
+//! ```
+
+use affinidi_messaging_test_mediator::TestEnvironment;
+use affinidi_tdk_examples::{Result, send_message};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let env = TestEnvironment::spawn().await?;
+    let result = send_message::run(&env, "Hello, Bob!").await;
+    env.shutdown().await?;
+    result.map(|_| ())
+}
@@ -0,0 +1,158 @@
+//! Create a DID of each method the TDK creates, and resolve it.
+//!
+//! - `did:key`: the DID is the public key, so there is nothing to publish.
+//! - `did:peer`: a `did:peer:2` DID carries its keys (here one for signing,
+//!   one for key agreement) and, optionally, a DIDComm service endpoint.
+//! - `did:web`: the controller builds the document and hosts it at
+//!   `https://<domain>/.well-known/did.json`. Here the document goes straight
+//!   into the resolver's cache instead (see [`host_web_document`]).
+//! - `did:webvh`: `didwebvh-rs` signs the genesis entry of the DID's log with
+//!   an update key; the controller hosts the log at
+//!   `https://<domain>/.well-known/did.jsonl`. Here the log's document goes
+//!   into the resolver's cache, as for `did:web`.
+//! - `did:scid`: a `did:scid:vh:1` DID is the `did:webvh` log under a
+//!   host-independent name, so it is created alongside the `did:webvh` DID
+//!   and listed in the document's `alsoKnownAs`.
+//!
+//! `did:ebsi` DIDs are registered with the EBSI ledger's onboarding service,
+//! which has no offline equivalent, so they are only resolved.
+
+use crate::Result;
+use affinidi_tdk::{
+    TDK,
+    did_common::{Document, DocumentBuilder, VerificationMethodBuilder},
+    dids::{DID, KeyType, PeerKeyRole},
+    secrets_resolver::secrets::Secret,
+};
+use didwebvh_rs::{
+    DIDWebVHState,
+    create::{CreateDIDConfig, create_did},
+    log_entry::LogEntryMethods,
+    parameters::Parameters,
+};
+use serde_json::json;
+use std::sync::Arc;
+
+/// The `did:web` this scenario creates.
+pub const WEB_DID: &str = "did:web:issuer.example.com";
+
+/// Where the `did:webvh` log this scenario creates would be hosted.
+pub const WEBVH_ADDRESS: &str = "https://ledger.example.com";
+
+/// The DIDs created, in order: `did:key`, `did:peer`, `did:web`, `did:webvh`,
+/// `did:scid`.
+pub async fn run(tdk: &TDK) -> Result<Vec<String>> {
+    // did:key — the secret's id is already the DID's verification method.
+    let (key_did, key_secret) = DID::generate_did_key(KeyType::Ed25519)?;
+    println!("did:key  {key_did}\n         signs as {}", key_secret.id);
+
+    // did:peer — a signing key and a key agreement key, no service.
+    let (peer_did, peer_secrets) = DID::generate_did_peer(
+        vec![
+            (PeerKeyRole::Verification, KeyType::Ed25519),
+            (PeerKeyRole::Encryption, KeyType::X25519),
+        ],
+        None,
+    )?;
+    println!("did:peer {peer_did}\n         {} keys", peer_secrets.len());
+
+    // did:web — the controller picks the key ids.
+    let web_key = Secret::generate_ed25519(Some(&format!("{WEB_DID}#key-1")), None);
+    host_web_document(tdk, web_document(WEB_DID, &[&web_key])?).await;
+    println!("did:web  {WEB_DID}\n         signs as {}", web_key.id);
+
+    // did:webvh and did:scid — one log, two names.
+    let (webvh_did, scid_did) = create_webvh(tdk).await?;
+    println!("did:webvh {webvh_did}\ndid:scid {scid_did}");
+
+    let mut created = Vec::new();
+    for did in [key_did, peer_did, WEB_DID.to_string(), webvh_did, scid_did] {
+        let resolved = tdk.did_resolver().resolve(&did).await?;
+        println!(
+            "resolved {did}: {} verification method(s)",
+            resolved.doc.verification_method.len()
+        );
+        created.push(did);
+    }
+    Ok(created)
+}
+
+/// Creates a `did:webvh` DID hosted at [`WEBVH_ADDRESS`], with one signing key
+/// and a `did:scid` alias, and publishes its document under both names.
+///
+/// Returns the `did:webvh` and `did:scid` DIDs.
+pub async fn create_webvh(tdk: &TDK) -> Result<(String, String)> {
+    // The update key authorizes changes to the log; it is a did:key.
+    let (_, update_key) = DID::generate_did_key(KeyType::Ed25519)?;
+    let update_public = update_key.get_public_keymultibase()?;
+    let signing_key = Secret::generate_ed25519(None, None);
+
+    // `{DID}` is filled in once the log's SCID is known.
+    let config = CreateDIDConfig::builder()
+        .address(WEBVH_ADDRESS)
+        .authorization_key(update_key)
+        .did_document(json!({
+            "@context": [
+                "https://www.w3.org/ns/did/v1",
+                "https://w3id.org/security/multikey/v1"
+            ],
+            "id": "{DID}",
+            "verificationMethod": [{
+                "id": "{DID}#key-1",
+                "type": "Multikey",
+                "controller": "{DID}",
+                "publicKeyMultibase": signing_key.get_public_keymultibase()?,
+            }],
+            "authentication": ["{DID}#key-1"],
+            "assertionMethod": ["{DID}#key-1"],
+        }))
+        .parameters(Parameters {
+            update_keys: Some(Arc::new(vec![update_public.into()])),
+            ..Default::default()
+        })
+        .also_known_as_scid(true)
+        .build()?;
+    let created = create_did(config).await?;
+
+    let webvh_did = created.did().to_string();
+    let scid_did = DIDWebVHState::convert_webvh_id_to_scid_id(&webvh_did).to_string();
+    let document: Document = serde_json::from_value(created.log_entry().get_did_document()?)?;
+    if !document.also_known_as.contains(&scid_did) {
+        return Err(format!("{webvh_did} doesn't list {scid_did} in alsoKnownAs").into());
+    }
+
+    let mut resolver = tdk.did_resolver().clone();
+    resolver.add_did_document(&scid_did, document.clone()).await;
+    host_web_document(tdk, document).await;
+    Ok((webvh_did, scid_did))
+}
+
+/// A `did:web` document whose verification methods are `keys`, each usable
+/// for authentication and assertions. A key's id (`<did>#<fragment>`) is its
+/// verification method id.
+pub fn web_document(did: &str, keys: &[&Secret]) -> Result<Document> {
+    let mut builder = DocumentBuilder::new(did)?
+        .context_did_v1()
+        .context_multikey_v1();
+    for key in keys {
+        let method = VerificationMethodBuilder::new(&key.id, "Multikey", did)?
+            .public_key_multibase(key.get_public_keymultibase()?)
+            .build();
+        builder = builder
+            .verification_method(method)
+            .authentication_reference(&key.id)?
+            .assertion_method_reference(&key.id)?;
+    }
+    Ok(builder.build())
+}
+
+/// Publishes `document` for this process, replacing any earlier version: the
+/// resolver answers from its cache before going to the network. A real
+/// `did:web` is published by serving the document over HTTPS, and a real
+/// `did:webvh` by serving its log.
+pub async fn host_web_document(tdk: &TDK, document: Document) {
+    let did = document.id.to_string();
+    // Clones of the resolver share its cache.
+    let mut resolver = tdk.did_resolver().clone();
+    resolver.add_did_document(&did, document).await;
+}
@@ -0,0 +1,73 @@
+//! Issue a W3C Verifiable Credential (VCDM 2.0) with an embedded Data
+//! Integrity proof, and verify it.
+//!
+//! The issuer signs with `eddsa-jcs-2022` (picked for an Ed25519 key). The
+//! verifier resolves the issuer's DID to find the public key named by the
+//! proof's `verificationMethod`, so it needs nothing from the issuer but the
+//! credential.
+
+use crate::Result;
+use affinidi_tdk::{
+    TDK,
+    data_integrity::{DataIntegrityProof, SignOptions},
+    dids::{DID, KeyType},
+    secrets_resolver::secrets::Secret,
+    vc::{CredentialBuilder, VerifiableCredential},
+};
+use serde_json::json;
+
+/// Issues a credential about `subject_did`, signed with `issuer`. The issuer
+/// is the DID part of the key's id.
+pub async fn issue(issuer: &Secret, subject_did: &str) -> Result<VerifiableCredential> {
+    let issuer_did = issuer
+        .id
+        .split_once('#')
+        .map_or(&*issuer.id, |(did, _)| did);
+    let mut subject = serde_json::Map::new();
+    subject.insert("id".into(), json!(subject_did));
+    subject.insert("memberOf".into(), json!("Example Rust Guild"));
+
+    let mut credential = CredentialBuilder::v2()
+        .id(format!("urn:uuid:{}", uuid::Uuid::new_v4()))
+        .add_type("MembershipCredential")
+        .issuer_uri(issuer_did)
+        .subject(subject)
+        .build()?;
+
+    // Signed without a `proof`, which is then embedded.
+    let proof = DataIntegrityProof::sign(&credential, issuer, SignOptions::new()).await?;
+    credential.proof = Some(serde_json::to_value(proof)?);
+    Ok(credential)
+}
+
+/// Checks the credential's proof against the key its issuer's DID document
+/// holds now.
+pub async fn verify(tdk: &TDK, credential: &VerifiableCredential) -> Result<()> {
+    let mut unsigned = credential.clone();
+    let proof: DataIntegrityProof =
+        serde_json::from_value(unsigned.proof.take().ok_or("credential has no proof")?)?;
+    tdk.verify_data(&unsigned, None, &proof).await?;
+    Ok(())
+}
+
+/// Issues a credential from a `did:key` issuer and verifies it, then checks
+/// that a tampered copy is rejected. Returns the credential.
+pub async fn run(tdk: &TDK) -> Result<VerifiableCredential> {
+    let (issuer_did, issuer) = DID::generate_did_key(KeyType::Ed25519)?;
+    let (holder_did, _) = DID::generate_did_key(KeyType::Ed25519)?;
+
+    let credential = issue(&issuer, &holder_did).await?;
+    println!("{}", serde_json::to_string_pretty(&credential)?);
+
+    verify(tdk, &credential).await?;
+    println!("verified: issued by {issuer_did}");
+
+    let mut tampered = credential.clone();
+    tampered.types.push("AdministratorCredential".into());
+    if verify(tdk, &tampered).await.is_ok() {
+        return Err("a tampered credential verified".into());
+    }
+    println!("rejected: tampered copy");
+
+    Ok(credential)
+}
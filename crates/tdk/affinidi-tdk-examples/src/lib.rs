@@ -0,0 +1,43 @@
+/*!
+Runnable end-to-end scenarios for the Affinidi TDK.
+
+Each scenario is a module with a `run` function, a binary that calls it, and
+a test in `tests/scenarios.rs` that runs it, so every example here compiles
+and works against the current public API.
+
+| Scenario | Module | Binary |
+|----------|--------|--------|
+| Create a DID of each method, and resolve it | [`create_dids`] | `create-dids` |
+| Authenticate to a mediator | [`mediator_auth`] | `mediator-auth` |
+| Send a message and receive it | [`send_message`] | `send-message` |
+| Issue a credential and verify it | [`issue_credential`] | `issue-credential` |
+| Rotate a DID's signing key | [`rotate_keys`] | `rotate-keys` |
+
+```bash
+cargo run -p affinidi-tdk-examples --bin send-message
+```
+
+Everything runs in-process: the messaging scenarios spawn a
+[`TestEnvironment`](affinidi_messaging_test_mediator::TestEnvironment)
+(an embedded mediator), and `did:web` and `did:webvh` documents are served
+from the resolver's cache instead of a web server (see
+[`create_dids::host_web_document`]).
+*/
+
+use affinidi_tdk::prelude::{TDK, TDKConfig};
+
+pub mod create_dids;
+pub mod issue_credential;
+pub mod mediator_auth;
+pub mod rotate_keys;
+pub mod send_message;
+
+/// Scenarios mix errors from several crates and only report them.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A TDK that reads no environment file and starts no messaging client,
+/// enough for resolving DIDs and signing and verifying data.
+pub async fn headless_tdk() -> Result<TDK> {
+    Ok(TDK::new(TDKConfig::headless()?, None).await?)
+}
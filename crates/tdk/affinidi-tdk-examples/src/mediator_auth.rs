@@ -0,0 +1,30 @@
+//! Authenticate to a mediator.
+//!
+//! A mediator hands out tokens to whoever proves control of a DID: it sends
+//! a challenge, the client signs it with the DID's authentication key and
+//! gets back an access token and a refresh token. The TDK caches the tokens
+//! per DID and refreshes them as they expire; messaging calls authenticate
+//! on demand, so this is only needed to talk to a mediator's API directly.
+
+use crate::Result;
+use affinidi_messaging_test_mediator::TestEnvironment;
+use affinidi_tdk::prelude::TDKProfile;
+
+/// Registers Alice with the mediator and authenticates as her. Returns when
+/// her access token expires (Unix seconds).
+pub async fn run(env: &TestEnvironment) -> Result<u64> {
+    let mediator_did = env.mediator.did();
+    let alice = env.add_user("Alice").await?;
+    println!("mediator {mediator_did} at {}", env.mediator.endpoint());
+
+    // Alice's secrets are already in the shared resolver, which is where
+    // authentication looks for her keys.
+    let profile = TDKProfile::new(&alice.alias, &alice.did, Some(mediator_did), alice.secrets);
+    let tokens = env.tdk.authenticate_profile(&profile, mediator_did).await?;
+
+    println!(
+        "authenticated {}: access token expires at {}, refresh token at {}",
+        alice.did, tokens.access_expires_at, tokens.refresh_expires_at
+    );
+    Ok(tokens.access_expires_at)
+}
@@ -0,0 +1,78 @@
+//! Rotate the signing key of a `did:web`.
+//!
+//! A rotation publishes the new key alongside the old one, moves signing to
+//! the new key, then retires the old key from the DID document and destroys
+//! its secret. While both keys are published, credentials signed with either
+//! verify; once the old key is retired, only new signatures do. Credentials
+//! that must outlive a key are re-issued during the overlap.
+
+use crate::{
+    Result,
+    create_dids::{host_web_document, web_document},
+    issue_credential::{issue, verify},
+};
+use affinidi_tdk::{
+    TDK, prelude::SecretsResolver, secrets_resolver::secrets::Secret, vc::VerifiableCredential,
+};
+
+/// The `did:web` whose key this scenario rotates.
+pub const WEB_DID: &str = "did:web:rotating.example.com";
+
+const HOLDER_DID: &str = "did:example:holder";
+
+pub async fn run(tdk: &TDK) -> Result<()> {
+    let secrets = tdk.shared().secrets_resolver();
+    let old_key = Secret::generate_ed25519(Some(&format!("{WEB_DID}#key-1")), None);
+    let new_key = Secret::generate_ed25519(Some(&format!("{WEB_DID}#key-2")), None);
+
+    // 1. Published with key-1, which signs.
+    host_web_document(tdk, web_document(WEB_DID, &[&old_key])?).await;
+    secrets.insert(old_key.clone()).await;
+    let before = issue(&signing_key(tdk, &old_key.id).await?, HOLDER_DID).await?;
+    verify(tdk, &before).await?;
+    println!("1. {WEB_DID} signs with {}", old_key.id);
+
+    // 2. Overlap: both keys published, key-2 signs from now on.
+    host_web_document(tdk, web_document(WEB_DID, &[&old_key, &new_key])?).await;
+    secrets.insert(new_key.clone()).await;
+    let after = issue(&signing_key(tdk, &new_key.id).await?, HOLDER_DID).await?;
+    expect(tdk, &before, true).await?;
+    expect(tdk, &after, true).await?;
+    println!("2. both keys published; signing with {}", new_key.id);
+
+    // 3. key-1 retired and its secret destroyed.
+    host_web_document(tdk, web_document(WEB_DID, &[&new_key])?).await;
+    tdk.shared().shred_secret(&old_key.id, None).await?;
+    if secrets.get_secret(&old_key.id).await.is_some() {
+        return Err(format!("{} is still held after shredding", old_key.id).into());
+    }
+    expect(tdk, &before, false).await?;
+    expect(tdk, &after, true).await?;
+    println!(
+        "3. {} retired: its credentials no longer verify",
+        old_key.id
+    );
+
+    Ok(())
+}
+
+/// The agent signs with whichever key its secrets resolver holds.
+async fn signing_key(tdk: &TDK, key_id: &str) -> Result<Secret> {
+    tdk.shared()
+        .secrets_resolver()
+        .get_secret(key_id)
+        .await
+        .ok_or_else(|| format!("no secret for {key_id}").into())
+}
+
+async fn expect(tdk: &TDK, credential: &VerifiableCredential, verifies: bool) -> Result<()> {
+    match (verify(tdk, credential).await, verifies) {
+        (Ok(()), true) | (Err(_), false) => Ok(()),
+        (Ok(()), false) => Err(format!(
+            "{} verified with a retired key",
+            credential.id.as_deref().unwrap_or("credential")
+        )
+        .into()),
+        (Err(e), true) => Err(e),
+    }
+}
@@ -0,0 +1,68 @@
+//! Send a DIDComm message through a mediator and receive it.
+//!
+//! Alice encrypts a basic message for Bob (authcrypt: Bob can tell it is from
+//! her), wraps it in a forward for Bob's mediator and sends it there. Bob
+//! keeps a live WebSocket stream open to the mediator, which delivers the
+//! message as soon as it arrives.
+
+use crate::Result;
+use affinidi_messaging_test_mediator::TestEnvironment;
+use affinidi_tdk::prelude::Message;
+use serde_json::{Value, json};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const BASIC_MESSAGE_TYPE: &str = "https://didcomm.org/basicmessage/2.0/message";
+
+/// Sends `text` from Alice to Bob. Returns the text Bob received.
+pub async fn run(env: &TestEnvironment, text: &str) -> Result<String> {
+    let mediator_did = env.mediator.did();
+    let alice = env.add_user("Alice").await?;
+    let bob = env.add_user("Bob").await?;
+
+    // Bob listens first: delivery is live, not polled.
+    env.atm.profile_enable_websocket(&bob.profile).await?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let message = Message::new(BASIC_MESSAGE_TYPE, json!({ "content": text }))
+        .from(alice.did.clone())
+        .to(vec![bob.did.clone()])
+        .created_time(now);
+    let message_id = message.id.clone();
+
+    let (packed, _) = env
+        .atm
+        .pack_encrypted(&message, &bob.did, Some(&alice.did), Some(&alice.did))
+        .await?;
+    let (_, forward) = env
+        .atm
+        .routing()
+        .forward_message(
+            &alice.profile,
+            false,
+            &packed,
+            mediator_did,
+            &bob.did,
+            None,
+            None,
+        )
+        .await?;
+    env.atm
+        .send_message(&alice.profile, &forward, &message_id, false, false)
+        .await?;
+    println!("{} -> {}: {text}", alice.alias, bob.alias);
+
+    let (received, _) = env
+        .atm
+        .message_pickup()
+        .live_stream_get(&bob.profile, &message_id, Duration::from_secs(5), true)
+        .await?
+        .ok_or("message not delivered")?;
+    let content = received
+        .body
+        .get("content")
+        .and_then(Value::as_str)
+        .ok_or("message has no content")?
+        .to_string();
+    println!("{} received: {content}", bob.alias);
+    Ok(content)
+}
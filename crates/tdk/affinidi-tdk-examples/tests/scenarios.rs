@@ -0,0 +1,54 @@
+//! Runs every scenario, so the examples keep compiling and working as the
+//! API changes.
+
+use affinidi_messaging_test_mediator::TestEnvironment;
+use affinidi_tdk_examples::{
+    create_dids, headless_tdk, issue_credential, mediator_auth, rotate_keys, send_message,
+};
+
+#[tokio::test]
+async fn create_dids() {
+    let tdk = headless_tdk().await.unwrap();
+    let dids = create_dids::run(&tdk).await.unwrap();
+
+    let methods: Vec<_> = dids.iter().map(|did| did.split(':').nth(1)).collect();
+    assert_eq!(
+        methods,
+        [
+            Some("key"),
+            Some("peer"),
+            Some("web"),
+            Some("webvh"),
+            Some("scid")
+        ]
+    );
+}
+
+#[tokio::test]
+async fn issue_credential() {
+    let tdk = headless_tdk().await.unwrap();
+    let credential = issue_credential::run(&tdk).await.unwrap();
+    assert!(credential.proof.is_some());
+}
+
+#[tokio::test]
+async fn rotate_keys() {
+    let tdk = headless_tdk().await.unwrap();
+    rotate_keys::run(&tdk).await.unwrap();
+}
+
+#[tokio::test]
+async fn mediator_auth() {
+    let env = TestEnvironment::spawn().await.unwrap();
+    let expires_at = mediator_auth::run(&env).await.unwrap();
+    env.shutdown().await.unwrap();
+    assert!(expires_at > 0);
+}
+
+#[tokio::test]
+async fn send_message() {
+    let env = TestEnvironment::spawn().await.unwrap();
+    let received = send_message::run(&env, "Hello, Bob!").await.unwrap();
+    env.shutdown().await.unwrap();
+    assert_eq!(received, "Hello, Bob!");
+}
@@ -46,8 +46,9 @@
 use affinidi_crypto::CryptoError;
 use affinidi_messaging_didcomm::Message;
 use affinidi_messaging_didcomm::message::pack::{
-    pack_encrypted_anoncrypt, pack_encrypted_authcrypt, pack_signed,
+    pack_encrypted_anoncrypt_with_rng, pack_encrypted_authcrypt_with_rng, pack_signed,
 };
+use rand_chacha::{ChaCha20Rng, rand_core::SeedableRng};
 
 // Re-exported so callers can name the curve / key types without depending on
 // affinidi-crypto directly.
@@ -133,16 +134,18 @@ pub fn signing_keypair_from_seed(seed: u64) -> ([u8; 32], [u8; 32]) {
 }
 
 /// Pack `msg` as an authcrypt (ECDH-1PU, sender-authenticated) JWE addressed to
-/// a deterministic recipient/sender derived from `seed`.
+/// a deterministic recipient/sender derived from `seed`. The ephemeral key, CEK
+/// and IV are seeded too, so the same `(seed, msg)` packs to the same bytes.
 pub fn authcrypt_envelope(seed: u64, msg: &Message) -> Result<PackedEnvelope, DidcommFuzzError> {
     let sender = key_agreement_from_seed(Curve::X25519, seed ^ SENDER_SALT, sender_kid(seed))?;
     let recipient = key_agreement_from_seed(Curve::X25519, seed, recipient_kid(seed))?;
 
-    let envelope = pack_encrypted_authcrypt(
+    let envelope = pack_encrypted_authcrypt_with_rng(
         msg,
         &sender.kid,
         &sender.private,
         &[(recipient.kid.as_str(), &recipient.public())],
+        &mut encryption_rng(seed),
     )
     .map_err(|e| DidcommFuzzError::Pack(e.to_string()))?;
 
@@ -156,12 +159,17 @@ pub fn authcrypt_envelope(seed: u64, msg: &Message) -> Result<PackedEnvelope, Di
 }
 
 /// Pack `msg` as an anoncrypt (ECDH-ES, anonymous) JWE addressed to a
-/// deterministic recipient derived from `seed`.
+/// deterministic recipient derived from `seed`, with seeded encryption
+/// randomness as in [`authcrypt_envelope`].
 pub fn anoncrypt_envelope(seed: u64, msg: &Message) -> Result<PackedEnvelope, DidcommFuzzError> {
     let recipient = key_agreement_from_seed(Curve::X25519, seed, recipient_kid(seed))?;
 
-    let envelope = pack_encrypted_anoncrypt(msg, &[(recipient.kid.as_str(), &recipient.public())])
-        .map_err(|e| DidcommFuzzError::Pack(e.to_string()))?;
+    let envelope = pack_encrypted_anoncrypt_with_rng(
+        msg,
+        &[(recipient.kid.as_str(), &recipient.public())],
+        &mut encryption_rng(seed),
+    )
+    .map_err(|e| DidcommFuzzError::Pack(e.to_string()))?;
 
     Ok(PackedEnvelope {
         envelope,
@@ -193,9 +201,9 @@ pub fn signed_envelope(seed: u64, msg: &Message) -> Result<PackedEnvelope, Didco
 /// each protected shape (authcrypt, anoncrypt, signed, plaintext) over a couple
 /// of message bodies. Every entry opens cleanly under the keys it ships with.
 ///
-/// The *keys* are deterministic (seeded), but the envelope bytes are not stable
-/// across runs: encryption draws a fresh ephemeral key + IV and `Message::new`
-/// mints a random UUID — exactly as production does. That is fine for seeding;
+/// The keys and encryption randomness are seeded, but the envelope bytes are
+/// not stable across runs: `Message::new` mints a random UUID and stamps the
+/// current time, exactly as production does. That is fine for seeding;
 /// the harness wants valid-and-openable inputs, not byte-identical ones. Dump
 /// `.envelope` to files if a committed on-disk corpus is wanted.
 pub fn seed_corpus() -> Vec<PackedEnvelope> {
@@ -252,6 +260,15 @@ const KEY_DOMAIN_SIGN: u8 = 0x02;
 /// recipient never collide.
 const SENDER_SALT: u64 = 0xA5A5_A5A5_A5A5_A5A5;
 
+/// Salt mixed into a seed for the encryption RNG, so it never replays the
+/// bytes a key was derived from.
+const ENCRYPTION_SALT: u64 = 0x5A5A_5A5A_5A5A_5A5A;
+
+/// The RNG a seeded JWE draws its ephemeral key, CEK and IV from.
+fn encryption_rng(seed: u64) -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(seed ^ ENCRYPTION_SALT)
+}
+
 /// Fill 32 deterministic bytes from `(seed, domain)` via SplitMix64. Not
 /// cryptographic — these are throwaway test keys — but stable across runs and
 /// platforms, which is all the corpus needs.
@@ -348,6 +365,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn encrypted_envelopes_are_reproducible() {
+        let msg = Message::new("t", serde_json::json!({ "x": 4 }));
+        for pack in [authcrypt_envelope, anoncrypt_envelope] {
+            let a = pack(5, &msg).unwrap().envelope;
+            assert_eq!(a, pack(5, &msg).unwrap().envelope);
+            assert_ne!(a, pack(6, &msg).unwrap().envelope);
+        }
+    }
+
     #[test]
     fn signed_round_trips() {
         let msg = Message::new("t", serde_json::json!({ "x": 3 }));
@@ -0,0 +1,414 @@
+/*!
+ * Golden-output snapshot fixtures: resolver and pack outputs as `insta` snapshots.
+ *
+ * Refactors such as the `Document` model migration need to prove that what a
+ * resolver emits — and what the DIDComm pack path puts on the wire — has not
+ * drifted. This module turns those outputs into stable, reviewable text so a
+ * test can pin them with [`insta`] (re-exported here, so consumers need no
+ * extra dev-dependency):
+ *
+ * - **Resolver outputs** — [`document_snapshot`] renders a `Document` in its
+ *   JCS (RFC 8785) canonical key order, and [`canonical_document`] returns the
+ *   exact canonical bytes for assertions that must be byte-level.
+ *   [`golden_dids`] is a per-method set of inputs (`did:key` per key type, seeded
+ *   `did:peer`) and [`resolve_snapshot`] drives any [`AsyncResolver`] over them.
+ * - **Hosted documents** — [`golden_hosted_dids`] adds the methods whose
+ *   documents are built by their controller: a hand-written `did:web`
+ *   `did.json`, and a `did:webvh` log (with a `did:scid` alias) signed under a
+ *   seeded update key at a fixed time. [`hosted_snapshot`] reads them the way
+ *   the resolvers do, without fetching anything.
+ * - **Pack outputs** — [`golden_message`] pins the two fields DIDComm fills from
+ *   the RNG and the clock (`id`, `created_time`); keys and the encryption
+ *   randomness (ephemeral key, CEK, IV) come from the seeded
+ *   [`crate::didcomm_fuzz`] helpers. Every envelope shape is then
+ *   byte-for-byte reproducible, and [`envelope_snapshot`] pins it in full.
+ *
+ * Snapshot files live next to the *calling* test (`tests/snapshots/`), as with
+ * any `insta` assertion.
+ *
+ * **TEST-ONLY.** The seeded keys and encryption randomness behind the pack
+ * fixtures are predictable by construction.
+ *
+ * ```
+ * use affinidi_tdk_test_support::didcomm_fuzz::signed_envelope;
+ * use affinidi_tdk_test_support::golden::{envelope_snapshot, golden_message};
+ *
+ * let msg = golden_message("https://didcomm.org/basicmessage/2.0/message",
+ *     serde_json::json!({ "content": "hello" }));
+ *
+ * // Same seed + pinned id/time → identical signed envelope, every run.
+ * let a = envelope_snapshot(&signed_envelope(1, &msg).unwrap()).unwrap();
+ * let b = envelope_snapshot(&signed_envelope(1, &msg).unwrap()).unwrap();
+ * assert_eq!(a, b);
+ * ```
+ */
+
+use std::sync::Arc;
+
+use affinidi_did_common::{DID, Document};
+use affinidi_did_resolver_traits::AsyncResolver;
+use affinidi_messaging_didcomm::message::unpack::unpack;
+use affinidi_messaging_didcomm::{Message, UnpackResult};
+use affinidi_secrets_resolver::secrets::Secret;
+use didwebvh_rs::{
+    DIDWebVHState,
+    create::{CreateDIDConfig, create_did},
+    log_entry::LogEntryMethods,
+    parameters::Parameters,
+};
+use serde_json::{Value, json};
+
+use crate::determinism::didcomm_identity_from_seed;
+use crate::didcomm_fuzz::PackedEnvelope;
+
+// Re-exported so a consuming test can `use affinidi_tdk_test_support::golden::insta`
+// without its own dev-dependency.
+pub use insta;
+
+/// The `id` every [`golden_message`] carries (normally a random UUID).
+pub const GOLDEN_MESSAGE_ID: &str = "00000000-0000-4000-8000-000000000001";
+
+/// The `created_time` every [`golden_message`] carries (normally "now").
+pub const GOLDEN_CREATED_TIME: u64 = 1_700_000_000;
+
+/// The `versionTime` of the golden `did:webvh` log's genesis entry (normally
+/// "now"); the log's SCID is derived from it.
+pub const GOLDEN_WEBVH_VERSION_TIME: &str = "2023-11-14T22:13:20Z";
+
+/// Errors from building a golden snapshot.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum GoldenError {
+    /// The document could not be canonicalised.
+    #[error("document: {0}")]
+    Document(String),
+
+    /// The resolver failed, or did not handle the DID.
+    #[error("resolution of {did}: {reason}")]
+    Resolution {
+        /// The DID being resolved.
+        did: String,
+        /// Why resolution produced no document.
+        reason: String,
+    },
+
+    /// The envelope is not JSON, or an encrypted envelope did not open under the
+    /// keys it shipped with.
+    #[error("envelope: {0}")]
+    Envelope(String),
+
+    /// A hosted golden DID could not be created.
+    #[error("fixture: {0}")]
+    Fixture(String),
+}
+
+/// One per-method resolver input from [`golden_dids`].
+#[derive(Clone, Debug)]
+pub struct GoldenDid {
+    /// Stable snapshot name, e.g. `key_ed25519` or `peer2_didcomm`.
+    pub name: &'static str,
+    /// The DID to resolve.
+    pub did: String,
+}
+
+/// The per-method golden inputs: `did:key` for every locally-resolvable key
+/// type, plus a `did:peer:2` DIDComm identity derived from a fixed seed (so the
+/// DID itself is stable across runs).
+pub fn golden_dids() -> Vec<GoldenDid> {
+    let (peer, _secrets) =
+        didcomm_identity_from_seed(0, Some("https://mediator.example.com".into()))
+            .expect("seeded did:peer is infallible for Ed25519 + X25519");
+
+    vec![
+        GoldenDid {
+            name: "key_ed25519",
+            did: "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK".into(),
+        },
+        GoldenDid {
+            name: "key_p256",
+            did: "did:key:zDnaerDaTF5BXEavCrfRZEk316dpbLsfPDZ3WJ5hRTPFU2169".into(),
+        },
+        GoldenDid {
+            name: "key_secp256k1",
+            did: "did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme".into(),
+        },
+        GoldenDid {
+            name: "peer2_didcomm",
+            did: peer,
+        },
+    ]
+}
+
+/// One hosted golden DID from [`golden_hosted_dids`].
+#[derive(Clone, Debug)]
+pub struct GoldenHostedDid {
+    /// Stable snapshot name, e.g. `web` or `webvh`.
+    pub name: &'static str,
+    /// The DID to resolve.
+    pub did: String,
+    /// What the DID's host serves: the `did.json` document for `did:web`, the
+    /// `did.jsonl` log for `did:webvh`.
+    pub served: String,
+}
+
+/// The hosted golden inputs: a `did:web` document written by hand, with a
+/// signing key, a key agreement key and a DIDComm service, and a `did:webvh`
+/// log whose document lists its `did:scid` alias. Keys come from fixed seeds
+/// and the log from a fixed [`GOLDEN_WEBVH_VERSION_TIME`], so both DIDs and
+/// documents are stable across runs.
+pub async fn golden_hosted_dids() -> Result<Vec<GoldenHostedDid>, GoldenError> {
+    let fixture = |e: &dyn std::fmt::Display| GoldenError::Fixture(e.to_string());
+
+    let signing = Secret::generate_ed25519(None, Some(&[1; 32]));
+    let agreement = signing.to_x25519().map_err(|e| fixture(&e))?;
+    let signing_key = signing.get_public_keymultibase().map_err(|e| fixture(&e))?;
+    let agreement_key = agreement
+        .get_public_keymultibase()
+        .map_err(|e| fixture(&e))?;
+
+    let web_did = "did:web:example.com";
+    let (web_key_1, web_key_2) = (format!("{web_did}#key-1"), format!("{web_did}#key-2"));
+    let web = json!({
+        "@context": [
+            "https://www.w3.org/ns/did/v1",
+            "https://w3id.org/security/multikey/v1"
+        ],
+        "id": web_did,
+        "verificationMethod": [
+            {
+                "id": web_key_1,
+                "type": "Multikey",
+                "controller": web_did,
+                "publicKeyMultibase": signing_key
+            },
+            {
+                "id": web_key_2,
+                "type": "Multikey",
+                "controller": web_did,
+                "publicKeyMultibase": agreement_key
+            }
+        ],
+        "authentication": [web_key_1],
+        "assertionMethod": [web_key_1],
+        "keyAgreement": [web_key_2],
+        "service": [{
+            "id": format!("{web_did}#didcomm"),
+            "type": "DIDCommMessaging",
+            "serviceEndpoint": {
+                "uri": "https://mediator.example.com",
+                "accept": ["didcomm/v2"]
+            }
+        }]
+    });
+
+    let mut update_key = Secret::generate_ed25519(None, Some(&[2; 32]));
+    let update_public = update_key
+        .get_public_keymultibase()
+        .map_err(|e| fixture(&e))?;
+    update_key.id = format!("did:key:{update_public}#{update_public}");
+    let version_time =
+        chrono::DateTime::parse_from_rfc3339(GOLDEN_WEBVH_VERSION_TIME).map_err(|e| fixture(&e))?;
+    let config = CreateDIDConfig::builder()
+        .address("https://example.com")
+        .authorization_key(update_key)
+        .did_document(json!({
+            "@context": [
+                "https://www.w3.org/ns/did/v1",
+                "https://w3id.org/security/multikey/v1"
+            ],
+            "id": "{DID}",
+            "verificationMethod": [
+                {
+                    "id": "{DID}#key-1",
+                    "type": "Multikey",
+                    "controller": "{DID}",
+                    "publicKeyMultibase": signing_key
+                },
+                {
+                    "id": "{DID}#key-2",
+                    "type": "Multikey",
+                    "controller": "{DID}",
+                    "publicKeyMultibase": agreement_key
+                }
+            ],
+            "authentication": ["{DID}#key-1"],
+            "assertionMethod": ["{DID}#key-1"],
+            "keyAgreement": ["{DID}#key-2"]
+        }))
+        .parameters(Parameters {
+            update_keys: Some(Arc::new(vec![update_public.into()])),
+            ..Default::default()
+        })
+        .also_known_as_scid(true)
+        .version_time(version_time)
+        .build()
+        .map_err(|e| fixture(&e))?;
+    let created = create_did(config).await.map_err(|e| fixture(&e))?;
+
+    Ok(vec![
+        GoldenHostedDid {
+            name: "web",
+            did: web_did.to_string(),
+            served: web.to_string(),
+        },
+        GoldenHostedDid {
+            name: "webvh",
+            did: created.did().to_string(),
+            served: serde_json::to_string(created.log_entry()).map_err(|e| fixture(&e))?,
+        },
+    ])
+}
+
+/// Resolve a [`GoldenHostedDid`] from what its host serves, as the network
+/// resolvers do once the fetch is done, and render the result with
+/// [`document_snapshot`]: `did:web` documents are parsed as served, `did:webvh`
+/// logs are verified and replayed by `didwebvh-rs`.
+pub async fn hosted_snapshot(golden: &GoldenHostedDid) -> Result<String, GoldenError> {
+    let resolution_error = |reason: String| GoldenError::Resolution {
+        did: golden.did.clone(),
+        reason,
+    };
+
+    let document: Document = if golden.did.starts_with("did:webvh:") {
+        let mut state = DIDWebVHState::default();
+        let (log_entry, _) = state
+            .resolve_log(&golden.did, &golden.served, None)
+            .await
+            .map_err(|e| resolution_error(e.to_string()))?;
+        let value = log_entry
+            .get_did_document()
+            .map_err(|e| resolution_error(e.to_string()))?;
+        serde_json::from_value(value).map_err(|e| resolution_error(e.to_string()))?
+    } else if golden.did.starts_with("did:web:") {
+        serde_json::from_str(&golden.served).map_err(|e| resolution_error(e.to_string()))?
+    } else {
+        return Err(resolution_error("not a hosted DID method".into()));
+    };
+
+    document_snapshot(&document)
+}
+
+/// The exact JCS canonical serialisation of `document` — assert on this where a
+/// refactor must be byte-for-byte compatible.
+pub fn canonical_document(document: &Document) -> Result<String, GoldenError> {
+    document
+        .canonical_json()
+        .map_err(|e| GoldenError::Document(e.to_string()))
+}
+
+/// Render `document` for a snapshot: canonical key order (so field reordering in
+/// the model is not a diff), pretty-printed (so a real change is reviewable).
+pub fn document_snapshot(document: &Document) -> Result<String, GoldenError> {
+    let canonical: Value = serde_json::from_str(&canonical_document(document)?)
+        .map_err(|e| GoldenError::Document(e.to_string()))?;
+    serde_json::to_string_pretty(&canonical).map_err(|e| GoldenError::Document(e.to_string()))
+}
+
+/// Resolve `did` through `resolver` and render the result with
+/// [`document_snapshot`].
+pub async fn resolve_snapshot(
+    resolver: &dyn AsyncResolver,
+    did: &str,
+) -> Result<String, GoldenError> {
+    let resolution_error = |reason: String| GoldenError::Resolution {
+        did: did.to_string(),
+        reason,
+    };
+
+    let parsed: DID = did.parse().map_err(|e| resolution_error(format!("{e}")))?;
+    match resolver.resolve(&parsed).await {
+        Some(Ok(document)) => document_snapshot(&document),
+        Some(Err(e)) => Err(resolution_error(e.to_string())),
+        None => Err(resolution_error(format!(
+            "not handled by {}",
+            resolver.name()
+        ))),
+    }
+}
+
+/// A DIDComm message with its RNG- and clock-derived fields pinned to
+/// [`GOLDEN_MESSAGE_ID`] / [`GOLDEN_CREATED_TIME`], so packing it under seeded
+/// keys is reproducible.
+pub fn golden_message(typ: impl Into<String>, body: Value) -> Message {
+    Message::build(GOLDEN_MESSAGE_ID, typ, body)
+        .created_time(GOLDEN_CREATED_TIME)
+        .finalize()
+}
+
+/// Render a packed envelope for a snapshot.
+///
+/// The envelope is returned in full. With a [`golden_message`] and the seeded
+/// [`crate::didcomm_fuzz`] packers every shape is reproducible: EdDSA is
+/// deterministic, and JWEs draw their ephemeral key, CEK and IV from a seeded
+/// RNG. Encrypted envelopes are unpacked first, so a snapshot can only pin a
+/// JWE that still opens under the keys it shipped with.
+pub fn envelope_snapshot(envelope: &PackedEnvelope) -> Result<Value, GoldenError> {
+    let value: Value = serde_json::from_str(&envelope.envelope)
+        .map_err(|e| GoldenError::Envelope(e.to_string()))?;
+
+    if value.get("ciphertext").is_some() {
+        let opened = unpack(
+            &envelope.envelope,
+            envelope.recipient_kid.as_deref(),
+            envelope.recipient_private.as_ref(),
+            envelope.sender_public.as_ref(),
+            envelope.signer_public.as_ref(),
+        )
+        .map_err(|e| GoldenError::Envelope(format!("unpack: {e}")))?;
+        if !matches!(opened, UnpackResult::Encrypted { .. }) {
+            return Err(GoldenError::Envelope(
+                "JWE did not unpack as encrypted".into(),
+            ));
+        }
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::didcomm_fuzz::{anoncrypt_envelope, authcrypt_envelope};
+
+    #[test]
+    fn golden_dids_are_stable() {
+        let a: Vec<String> = golden_dids().into_iter().map(|g| g.did).collect();
+        let b: Vec<String> = golden_dids().into_iter().map(|g| g.did).collect();
+        assert_eq!(a, b);
+    }
+
+    #[tokio::test]
+    async fn golden_hosted_dids_are_stable() {
+        let a = golden_hosted_dids().await.unwrap();
+        let b = golden_hosted_dids().await.unwrap();
+        for (a, b) in a.iter().zip(&b) {
+            assert_eq!(a.did, b.did);
+            assert_eq!(
+                hosted_snapshot(a).await.unwrap(),
+                hosted_snapshot(b).await.unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn encrypted_snapshots_are_reproducible() {
+        let msg = golden_message("t", serde_json::json!({ "x": 1 }));
+        for pack in [authcrypt_envelope, anoncrypt_envelope] {
+            let a = envelope_snapshot(&pack(9, &msg).unwrap()).unwrap();
+            let b = envelope_snapshot(&pack(9, &msg).unwrap()).unwrap();
+            assert_eq!(a, b);
+            assert!(a["ciphertext"].is_string());
+        }
+    }
+
+    #[test]
+    fn envelopes_that_do_not_open_are_rejected() {
+        let msg = golden_message("t", serde_json::json!({ "x": 1 }));
+        let mut envelope = anoncrypt_envelope(9, &msg).unwrap();
+        envelope.recipient_private = anoncrypt_envelope(10, &msg).unwrap().recipient_private;
+        assert!(matches!(
+            envelope_snapshot(&envelope),
+            Err(GoldenError::Envelope(_))
+        ));
+    }
+}
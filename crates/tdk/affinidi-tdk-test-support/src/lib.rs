@@ -20,6 +20,8 @@
  *   coverage-guided fuzzing of the `unpack`/`decrypt` entry points.
  * - [`credential_scenario`] — issuer/holder/verifier `CredentialScenario` for
  *   SD-JWT VC, with the [`mdoc_scenario`] and [`oid4vp`] flows layered on top.
+ * - [`golden`] — `insta` snapshot helpers for per-method resolver outputs and
+ *   pack outputs (pinned message id/time, seeded keys, redacted randomness).
  * - [`vectors`] — shared `tests/vectors/` layout and loader.
  *
  * The embedded-mediator fixtures (`TestMediator` / `TestEnvironment` /
@@ -40,6 +42,7 @@ pub mod credential_scenario;
 pub mod determinism;
 pub mod did_web;
 pub mod didcomm_fuzz;
+pub mod golden;
 pub mod mdoc_scenario;
 pub mod oid4vp;
 pub mod resolver;
@@ -0,0 +1,69 @@
+//! Golden outputs — per-method resolver documents and DIDComm pack outputs are
+//! pinned as `insta` snapshots (`tests/snapshots/`), so a model or pack-path
+//! refactor that changes what goes on the wire shows up as a reviewable diff.
+//!
+//! Run `cargo insta review` (or `INSTA_UPDATE=always cargo test`) after an
+//! intentional change to accept the new output.
+
+use affinidi_did_resolver_traits::{KeyResolver, PeerResolver};
+use affinidi_tdk_test_support::didcomm_fuzz::{
+    anoncrypt_envelope, authcrypt_envelope, signed_envelope,
+};
+use affinidi_tdk_test_support::golden::{
+    envelope_snapshot, golden_dids, golden_hosted_dids, golden_message, hosted_snapshot, insta,
+    resolve_snapshot,
+};
+
+fn sample_message() -> affinidi_messaging_didcomm::Message {
+    golden_message(
+        "https://didcomm.org/basicmessage/2.0/message",
+        serde_json::json!({ "content": "hello" }),
+    )
+}
+
+/// Every per-method golden DID resolves to the pinned document.
+#[tokio::test]
+async fn resolver_outputs_match_snapshots() {
+    for golden in golden_dids() {
+        let snapshot = if golden.did.starts_with("did:key:") {
+            resolve_snapshot(&KeyResolver, &golden.did).await
+        } else {
+            resolve_snapshot(&PeerResolver, &golden.did).await
+        }
+        .expect("golden DID resolves");
+
+        insta::assert_snapshot!(format!("resolve_{}", golden.name), snapshot);
+    }
+}
+
+/// The hosted methods (`did:web`, `did:webvh` with its `did:scid` alias)
+/// resolve to the pinned documents, DIDs included.
+#[tokio::test]
+async fn hosted_outputs_match_snapshots() {
+    for golden in golden_hosted_dids().await.expect("hosted golden DIDs") {
+        let snapshot = hosted_snapshot(&golden).await.expect("golden DID resolves");
+        insta::assert_snapshot!(format!("resolve_{}", golden.name), snapshot);
+    }
+}
+
+/// JWS is deterministic under a seeded key + pinned id/time: byte-level snapshot.
+#[test]
+fn signed_envelope_matches_snapshot() {
+    let envelope = signed_envelope(1, &sample_message()).expect("sign");
+    insta::assert_json_snapshot!(envelope_snapshot(&envelope).expect("snapshot"));
+}
+
+/// JWE is reproducible too once its ephemeral key, CEK and IV come from a seeded
+/// RNG: byte-level snapshot.
+#[test]
+fn encrypted_envelopes_match_snapshots() {
+    let msg = sample_message();
+    insta::assert_json_snapshot!(
+        "authcrypt_envelope",
+        envelope_snapshot(&authcrypt_envelope(1, &msg).expect("authcrypt")).expect("snapshot")
+    );
+    insta::assert_json_snapshot!(
+        "anoncrypt_envelope",
+        envelope_snapshot(&anoncrypt_envelope(1, &msg).expect("anoncrypt")).expect("snapshot")
+    );
+}
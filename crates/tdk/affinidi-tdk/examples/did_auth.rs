@@ -8,15 +8,12 @@
 
 // Facade-first: every Affinidi type comes through `affinidi_tdk::*` re-exports,
 // so this example never depends on a sub-crate directly.
-use affinidi_tdk::common::{
-    create_http_client,
-    environments::TDKEnvironments,
-    errors::{Result, TDKError},
-    profiles::TDKProfile,
-};
+use affinidi_tdk::common::{create_http_client, environments::TDKEnvironments, errors::Result};
 use affinidi_tdk::did_authentication::DIDAuthentication;
-use affinidi_tdk::did_resolver::{DIDCacheClient, config::DIDCacheConfigBuilder};
-use affinidi_tdk::secrets_resolver::{SecretsResolver, ThreadedSecretsResolver, secrets::Secret};
+use affinidi_tdk::prelude::{
+    DIDCacheClient, DIDCacheConfigBuilder, Secret, SecretsResolver, TDKError, TDKProfile,
+    ThreadedSecretsResolver,
+};
 use clap::{Parser, Subcommand};
 use std::{
     env,
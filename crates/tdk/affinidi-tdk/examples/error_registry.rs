@@ -0,0 +1,15 @@
+/*!
+ * Print the error code registry of the TDK as JSON.
+ *
+ * ```sh
+ * cargo run -p affinidi-tdk --example error_registry > error-codes.json
+ * ```
+ *
+ * Each entry gives a stable `PREFIX-NNNN` code, the error type and variant it
+ * belongs to, and a description. Products embedding the TDK can key their
+ * own handling and translated messages by `code`.
+ */
+
+fn main() {
+    println!("{}", affinidi_tdk::errors::registry().to_json());
+}
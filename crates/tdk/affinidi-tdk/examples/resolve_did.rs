@@ -11,7 +11,7 @@
  */
 
 use affinidi_tdk::common::errors::Result;
-use affinidi_tdk::did_resolver::{DIDCacheClient, config::DIDCacheConfigBuilder};
+use affinidi_tdk::prelude::{DIDCacheClient, DIDCacheConfigBuilder};
 use clap::Parser;
 
 #[derive(Parser)]
@@ -0,0 +1,210 @@
+/*!
+ * Consent receipts for the presentations a wallet sends.
+ *
+ * The builders here fill a [`ConsentReceiptBuilder`] from the presentation
+ * itself, so the receipt lists exactly what was shared:
+ *
+ * - with `openid4vp`: [`openid4vp_receipt`], from the authorization request
+ *   and the response sent to the verifier
+ * - with `vc`: [`presentation_receipt`], from a W3C Verifiable Presentation
+ *
+ * [`TDK::record_consent`] then signs the receipt with one of the holder's
+ * keys and appends it to a [`ConsentStore`]:
+ *
+ * ```ignore
+ * let response = build_authorization_response(vp_token, submission, request.state.clone());
+ * // ... send `response` to the verifier ...
+ * let receipt = tdk
+ *     .record_consent(
+ *         openid4vp_receipt(&profile.did, &request, &response),
+ *         &format!("{}#key-1", profile.did),
+ *         &store,
+ *     )
+ *     .await?;
+ * ```
+ */
+
+use affinidi_secrets_resolver::SecretsResolver;
+use affinidi_tdk_common::{
+    consent::{ConsentReceipt, ConsentReceiptBuilder, ConsentStore},
+    errors::{Result, TDKError},
+};
+
+#[cfg(any(feature = "vc", feature = "openid4vp"))]
+use affinidi_tdk_common::consent::SharedCredential;
+
+use crate::TDK;
+
+/// Purpose recorded when the verifier didn't state one
+#[cfg(feature = "openid4vp")]
+const UNSTATED_PURPOSE: &str = "Not stated by the verifier";
+
+/// Starts a receipt for an OpenID4VP presentation by `holder`.
+///
+/// The recipient is the verifier's `client_id` and the purpose the one its
+/// presentation definition gives. Each descriptor of the submission becomes a
+/// shared credential, with the claims its input descriptor asked for as the
+/// disclosed claims. The `state` of the exchange is kept as the reference.
+#[cfg(feature = "openid4vp")]
+pub fn openid4vp_receipt(
+    holder: &str,
+    request: &affinidi_openid4vp::AuthorizationRequest,
+    response: &affinidi_openid4vp::AuthorizationResponse,
+) -> ConsentReceiptBuilder {
+    let definition = request.presentation_definition.as_ref();
+    let purpose = definition
+        .and_then(|definition| definition.purpose.as_ref().or(definition.name.as_ref()))
+        .map_or(UNSTATED_PURPOSE, String::as_str);
+
+    let mut builder = ConsentReceipt::builder(holder, &request.client_id, purpose);
+    for entry in &response.presentation_submission.descriptor_map {
+        let claims = definition
+            .and_then(|definition| {
+                definition
+                    .input_descriptors
+                    .iter()
+                    .find(|descriptor| descriptor.id == entry.id)
+            })
+            .and_then(|descriptor| descriptor.constraints.fields.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|field| field.path.first().cloned());
+        builder = builder.share(SharedCredential::new(&entry.format).with_disclosed(claims));
+    }
+    if let Some(state) = response.state.as_ref().or(request.state.as_ref()) {
+        builder = builder.with_reference(state);
+    }
+    builder
+}
+
+/// Starts a receipt for a W3C Verifiable Presentation sent to `recipient`.
+///
+/// Embedded credentials are listed with their id and types (`ldp_vc`);
+/// enveloped ones by their format alone (`vc+sd-jwt` or `jwt_vc`).
+///
+/// # Errors
+///
+/// Returns [`TDKError::Consent`] if the presentation names no holder.
+#[cfg(feature = "vc")]
+pub fn presentation_receipt(
+    presentation: &affinidi_vc::VerifiablePresentation,
+    recipient: &str,
+    purpose: &str,
+) -> Result<ConsentReceiptBuilder> {
+    use serde_json::Value;
+
+    let holder = presentation
+        .holder
+        .as_deref()
+        .ok_or_else(|| TDKError::Consent("the presentation doesn't name its holder".to_string()))?;
+
+    let mut builder = ConsentReceipt::builder(holder, recipient, purpose);
+    if let Some(id) = &presentation.id {
+        builder = builder.with_reference(id);
+    }
+    for credential in presentation.verifiable_credential.iter().flatten() {
+        let shared = match credential {
+            Value::String(token) if token.contains('~') => SharedCredential::new("vc+sd-jwt"),
+            Value::String(_) => SharedCredential::new("jwt_vc"),
+            credential => {
+                let mut shared = SharedCredential::new("ldp_vc").with_types(
+                    credential["type"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .filter(|t| *t != "VerifiableCredential"),
+                );
+                if let Some(id) = credential["id"].as_str() {
+                    shared = shared.with_id(id);
+                }
+                shared
+            }
+        };
+        builder = builder.share(shared);
+    }
+    Ok(builder)
+}
+
+impl TDK {
+    /// Signs `receipt` with the secret `key_id` from the shared secrets
+    /// resolver and appends it to `store`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TDKError::Consent`] if `key_id` isn't one of the holder's
+    /// keys or isn't loaded, and any error from signing or writing the store.
+    pub async fn record_consent(
+        &self,
+        receipt: ConsentReceiptBuilder,
+        key_id: &str,
+        store: &ConsentStore,
+    ) -> Result<ConsentReceipt> {
+        let Some(key) = self.inner.secrets_resolver().get_secret(key_id).await else {
+            return Err(TDKError::Consent(format!(
+                "signing key ({key_id}) isn't loaded"
+            )));
+        };
+
+        let receipt = receipt.sign(&key).await?;
+        if key_id.split_once('#').map_or(key_id, |(did, _)| did) != receipt.holder {
+            return Err(TDKError::Consent(format!(
+                "signing key ({key_id}) doesn't belong to the holder ({})",
+                receipt.holder
+            )));
+        }
+        store.record(&receipt).await?;
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "openid4vp")]
+    #[tokio::test]
+    async fn openid4vp_receipt_lists_the_requested_claims() {
+        use affinidi_openid4vp::{
+            AuthorizationRequest,
+            wallet::{build_authorization_response, build_presentation_submission},
+        };
+        use affinidi_secrets_resolver::secrets::Secret;
+        use serde_json::json;
+
+        let request: AuthorizationRequest = serde_json::from_value(json!({
+            "response_type": "vp_token",
+            "client_id": "https://verifier.example.com",
+            "nonce": "n-0S6_WzA2Mj",
+            "state": "state-123",
+            "presentation_definition": {
+                "id": "pd-1",
+                "purpose": "Age verification",
+                "input_descriptors": [{
+                    "id": "age",
+                    "constraints": { "fields": [{ "path": ["$.birthdate"] }] }
+                }]
+            }
+        }))
+        .unwrap();
+        let response = build_authorization_response(
+            json!("eyJ...~"),
+            build_presentation_submission("pd-1", vec![("age", "vc+sd-jwt", "$")]),
+            request.state.clone(),
+        );
+
+        let key = Secret::generate_ed25519(Some("did:example:alice#key-0"), Some(&[7; 32]));
+        let receipt = super::openid4vp_receipt("did:example:alice", &request, &response)
+            .sign(&key)
+            .await
+            .unwrap();
+
+        assert_eq!(receipt.recipient, "https://verifier.example.com");
+        assert_eq!(receipt.purpose, "Age verification");
+        assert_eq!(receipt.reference.as_deref(), Some("state-123"));
+        assert_eq!(receipt.shared.len(), 1);
+        assert_eq!(receipt.shared[0].format, "vc+sd-jwt");
+        assert_eq!(receipt.shared[0].disclosed, ["$.birthdate"]);
+        receipt
+            .verify_with_public_key(key.get_public_bytes())
+            .unwrap();
+    }
+}
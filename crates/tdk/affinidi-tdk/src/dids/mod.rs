@@ -18,6 +18,8 @@ pub use affinidi_did_common::one_or_many::OneOrMany;
 #[cfg(feature = "did-peer")]
 pub use affinidi_did_common::{PeerService, PeerServiceEndpoint, PeerServiceEndpointLong};
 use affinidi_secrets_resolver::secrets::{KeyType as CryptoKeyType, Secret};
+#[cfg(feature = "did-peer")]
+use affinidi_secrets_resolver::usage::KeyPurpose;
 use affinidi_tdk_common::errors::{Result, TDKError};
 use std::fmt::Display;
 
@@ -109,6 +111,13 @@ impl PeerKeyRole {
             PeerKeyRole::Encryption => PeerKeyPurpose::Encryption,
         }
     }
+
+    fn to_key_purpose(self) -> KeyPurpose {
+        match self {
+            PeerKeyRole::Verification => KeyPurpose::Signing,
+            PeerKeyRole::Encryption => KeyPurpose::KeyAgreement,
+        }
+    }
 }
 
 pub struct DID;
@@ -155,6 +164,7 @@ impl DID {
                 role.to_peer_key_purpose(),
                 secret.get_public_keymultibase()?,
             ));
+            secret.set_purposes(&[role.to_key_purpose()]);
             secrets.push(secret);
         }
 
@@ -200,7 +210,7 @@ impl DID {
                 key.0.to_peer_key_purpose(),
                 did[8..].to_string(),
             ));
-            secrets.push(secret);
+            secrets.push(secret.with_purposes(&[key.0.to_key_purpose()]));
         }
 
         let mut secrets_mut: Vec<&mut Secret> = Vec::new();
@@ -0,0 +1,59 @@
+/*!
+ * Error codes of the TDK error types.
+ *
+ * Every TDK error enum maps its variants to a stable `PREFIX-NNNN` code (see
+ * [`affinidi_error_codes`]). [`registry`] lists the codes of every error type
+ * this build of the TDK can return, for client-side handling and
+ * localisation:
+ *
+ * ```ignore
+ * use affinidi_tdk::errors::{ErrorCodes, registry};
+ *
+ * match tdk.did_resolver().resolve(did).await {
+ *     Err(e) => show(translations.get(&e.error_code().to_string())),
+ *     Ok(resolved) => { /* … */ }
+ * }
+ *
+ * std::fs::write("error-codes.json", registry().to_json())?;
+ * ```
+ *
+ * The `error_registry` example prints the registry as JSON.
+ *
+ * [`catalog`] turns codes into user-facing messages. It ships English
+ * templates; add a locale from a translation file keyed by code:
+ *
+ * ```ignore
+ * let catalog = affinidi_tdk::errors::catalog()
+ *     .with_json("de", &std::fs::read_to_string("errors.de.json")?)?;
+ *
+ * if let Err(e) = tdk.did_resolver().resolve(did).await {
+ *     show(catalog.message(&user_locale, &e));
+ * }
+ * ```
+ */
+
+pub use affinidi_error_codes::{
+    ErrorCode, ErrorCodeEntry, ErrorCodes, MessageCatalog, MessageParams, Registry,
+};
+
+/// The codes of every error type re-exported by this build of the TDK.
+///
+/// `did:webvh` log errors come from the external `didwebvh-rs` crate and
+/// reach TDK callers wrapped in these types, so they have no prefix of their
+/// own here.
+pub fn registry() -> Registry {
+    let registry = Registry::new()
+        .with::<affinidi_did_resolver_cache_sdk::errors::DIDCacheError>()
+        .with::<affinidi_tdk_common::errors::TDKError>();
+    #[cfg(feature = "messaging")]
+    let registry = registry.with::<affinidi_messaging_sdk::errors::ATMError>();
+    #[cfg(feature = "meeting-place")]
+    let registry = registry.with::<affinidi_meeting_place::errors::MeetingPlaceError>();
+    registry
+}
+
+/// The English messages of every code in [`registry`], to extend with the
+/// locales a product supports.
+pub fn catalog() -> MessageCatalog {
+    MessageCatalog::from_registry(&registry())
+}
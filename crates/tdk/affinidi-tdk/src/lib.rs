@@ -8,6 +8,10 @@
  *
  * Construct with [`TDK::new`]; the heavy lifting is delegated to
  * [`TDKSharedState::new`].
+ *
+ * Start with `use affinidi_tdk::prelude::*;` — [`prelude`] names the current
+ * type for each concept, where the re-exported crates below may offer more
+ * than one path.
  */
 
 #![forbid(unsafe_code)]
@@ -20,13 +24,17 @@ use affinidi_did_resolver_cache_sdk::DIDCacheClient;
 #[cfg(feature = "messaging")]
 use affinidi_messaging_sdk::{ATM, config::ATMConfigBuilder};
 use affinidi_tdk_common::{
-    TDKSharedState, config::TDKConfig, errors::Result, profiles::TDKProfile,
+    TDKSharedState, capabilities::Capability, config::TDKConfig, errors::Result,
+    profiles::TDKProfile, usage::UsageStats,
 };
 #[cfg(feature = "data-integrity")]
 use serde::Serialize;
 use std::sync::Arc;
 
+pub mod consent;
 pub mod dids;
+pub mod errors;
+pub mod prelude;
 pub mod secrets;
 
 // Re-exports for application convenience.
@@ -45,14 +53,17 @@ pub use affinidi_data_integrity as data_integrity;
 pub use affinidi_mdoc as mdoc;
 #[cfg(feature = "sd-jwt")]
 pub use affinidi_sd_jwt as sd_jwt;
-// SD-JWT VC merged into affinidi-vc; re-export the module from its new home so
-// the `affinidi_tdk::sd_jwt_vc` path is unchanged for facade users.
 #[cfg(feature = "status-list")]
 pub use affinidi_status_list as status_list;
 #[cfg(feature = "vc")]
 pub use affinidi_vc as vc;
+/// SD-JWT VC merged into `affinidi-vc` and now lives at [`vc::sd_jwt_vc`].
+/// This path remains so existing imports keep compiling.
 #[cfg(feature = "sd-jwt-vc")]
-pub use affinidi_vc::sd_jwt_vc;
+#[deprecated(since = "0.8.5", note = "use affinidi_tdk::vc::sd_jwt_vc instead")]
+pub mod sd_jwt_vc {
+    pub use affinidi_vc::sd_jwt_vc::*;
+}
 
 // ── Protocols ────────────────────────────────────────────────────────────
 #[cfg(feature = "oid4vc-core")]
@@ -157,6 +168,38 @@ impl TDK {
         self.inner.add_profile(profile).await;
     }
 
+    /// Usage counters of `profile`: messages and bytes sent and received,
+    /// authentications and DID resolutions. See
+    /// [`common::usage::UsageTracker`] for quotas and resetting every profile.
+    pub fn usage(&self, profile: &TDKProfile) -> UsageStats {
+        self.inner.usage(&profile.did)
+    }
+
+    /// Zero the usage counters of `profile`, returning their values before
+    /// the reset (e.g. at the end of a billing period).
+    pub fn reset_usage(&self, profile: &TDKProfile) -> UsageStats {
+        self.inner.usage_tracker().reset(&profile.did)
+    }
+
+    /// Make sure `profile` is onboarded to `capability` (a mediator account,
+    /// Meeting Place, or a service it authenticates to), running the
+    /// onboarding if it isn't. Passthrough to
+    /// [`TDKSharedState::ensure_capability`].
+    ///
+    /// ```ignore
+    /// tdk.ensure_capability(&profile, Capability::MediatorAccount {
+    ///     mediator: mediator_did.to_string(),
+    /// })
+    /// .await?;
+    /// ```
+    pub async fn ensure_capability(
+        &self,
+        profile: &TDKProfile,
+        capability: Capability,
+    ) -> Result<()> {
+        self.inner.ensure_capability(profile, capability).await
+    }
+
     /// Borrow the shared DID resolver.
     pub fn did_resolver(&self) -> &DIDCacheClient {
         self.inner.did_resolver()
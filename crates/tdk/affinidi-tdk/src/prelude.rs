@@ -0,0 +1,39 @@
+/*!
+ * The types most applications need, from their current home.
+ *
+ * The TDK spans many crates, and some types are reachable by several paths.
+ * `use affinidi_tdk::prelude::*;` brings in one blessed path for each:
+ *
+ * - [`TDK`], its [`TDKConfig`], [`TDKSharedState`], [`TDKProfile`] (and its
+ *   [`Capability`]s) and [`TDKError`]
+ * - DIDs: [`DID`], [`Document`] (from `affinidi-did-common`) with
+ *   [`DocumentExt`], and the [`DIDCacheClient`] resolver with its
+ *   [`DIDCacheConfigBuilder`]
+ * - secrets: the [`SecretsResolver`] trait, [`ThreadedSecretsResolver`],
+ *   [`KeychainSecretsResolver`], the [`TDKSecretsResolver`] holding either, and
+ *   [`Secret`]
+ * - [`AuthenticationCache`] for mediator and service tokens
+ * - the DIDComm [`Message`]
+ * - with `messaging`: [`ATM`], [`ATMConfigBuilder`] and [`ATMProfile`]
+ *
+ * Only types that are current live here; anything superseded is left out
+ * so it can't be picked up by a glob import.
+ */
+
+pub use crate::TDK;
+pub use affinidi_did_common::{DID, Document, document::DocumentExt};
+pub use affinidi_did_resolver_cache_sdk::{DIDCacheClient, config::DIDCacheConfigBuilder};
+pub use affinidi_messaging_didcomm::Message;
+pub use affinidi_secrets_resolver::{SecretsResolver, ThreadedSecretsResolver, secrets::Secret};
+pub use affinidi_tdk_common::{
+    TDKSharedState,
+    capabilities::Capability,
+    config::TDKConfig,
+    errors::TDKError,
+    profiles::TDKProfile,
+    secrets::{KeychainSecretsResolver, TDKSecretsResolver},
+    tasks::authentication::AuthenticationCache,
+};
+
+#[cfg(feature = "messaging")]
+pub use affinidi_messaging_sdk::{ATM, config::ATMConfigBuilder, profiles::ATMProfile};
@@ -0,0 +1,261 @@
+//! Workspace build tooling, run as `cargo xtask <command>`.
+//!
+//! Feature-gated code (`cfg(feature = "network")`, wasm-only dependencies, an
+//! optional DID method) only compiles in the builds that enable it, so a
+//! change that breaks it goes unnoticed until someone builds that combination,
+//! often at release. `features` builds and tests every combination listed in
+//! [`matrix::COMBINATIONS`]; `vectors` runs the interop vector suite.
+
+use std::{
+    env,
+    path::PathBuf,
+    process::{Command, ExitCode},
+    time::{Duration, Instant},
+};
+
+mod matrix;
+
+use matrix::{COMBINATIONS, Combination, VECTOR_SUITES};
+
+const USAGE: &str = "\
+Usage: cargo xtask <command> [options]
+
+Commands:
+  features [--check] [NAME...]  Build and test each feature combination, or
+                                only the named ones. --check runs `cargo check`
+                                instead, which is faster but skips the tests.
+  features --list               List the feature combinations
+  vectors                       Run the interop vector suite
+  ci                            `features` followed by `vectors`
+";
+
+/// Outcome of one cargo invocation, for the summary.
+struct Outcome {
+    name: String,
+    passed: bool,
+    elapsed: Duration,
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some((command, options)) = args.split_first() else {
+        eprint!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let outcomes = match command.as_str() {
+        "features" => {
+            if options.iter().any(|option| option == "--list") {
+                list();
+                return ExitCode::SUCCESS;
+            }
+            match select(options) {
+                Ok(combinations) => {
+                    let check = options.iter().any(|option| option == "--check");
+                    features(&combinations, check)
+                }
+                Err(e) => {
+                    eprintln!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        "vectors" => vectors(),
+        "ci" => {
+            let mut outcomes = features(&COMBINATIONS.iter().collect::<Vec<_>>(), false);
+            outcomes.extend(vectors());
+            outcomes
+        }
+        "help" | "--help" | "-h" => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        other => {
+            eprintln!("Unknown command ({other})\n");
+            eprint!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    summarise(&outcomes)
+}
+
+fn list() {
+    for combination in COMBINATIONS {
+        let mut args = vec!["-p", combination.package];
+        args.extend(combination.flags);
+        if let Some(target) = combination.target {
+            args.extend(["--target", target]);
+        }
+        println!("{:<24} {}", combination.name, args.join(" "));
+    }
+}
+
+/// The combinations named in `options`, or all of them if none are.
+fn select(options: &[String]) -> Result<Vec<&'static Combination>, String> {
+    let names: Vec<&String> = options
+        .iter()
+        .filter(|option| !option.starts_with("--"))
+        .collect();
+    if names.is_empty() {
+        return Ok(COMBINATIONS.iter().collect());
+    }
+
+    names
+        .into_iter()
+        .map(|name| {
+            COMBINATIONS
+                .iter()
+                .find(|combination| combination.name == name)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown feature combination ({name}); see `cargo xtask features --list`"
+                    )
+                })
+        })
+        .collect()
+}
+
+fn features(combinations: &[&Combination], check: bool) -> Vec<Outcome> {
+    let installed = installed_targets();
+    let mut outcomes = Vec::new();
+
+    for combination in combinations {
+        let mut args = vec!["-p", combination.package];
+        args.extend(combination.flags);
+
+        let Some(target) = combination.target else {
+            if check {
+                outcomes.push(cargo(
+                    combination.name,
+                    "check",
+                    &[&args[..], &["--all-targets"]].concat(),
+                    &[],
+                ));
+                continue;
+            }
+            let build = cargo(
+                combination.name,
+                "build",
+                &[&args[..], &["--all-targets"]].concat(),
+                &[],
+            );
+            let built = build.passed;
+            outcomes.push(build);
+            if built {
+                outcomes.push(cargo(combination.name, "test", &args, &[]));
+            }
+            continue;
+        };
+
+        if let Some(installed) = &installed
+            && !installed.iter().any(|installed| installed == target)
+        {
+            eprintln!(
+                "==> {}: target {target} is not installed (rustup target add {target})",
+                combination.name
+            );
+            outcomes.push(Outcome {
+                name: format!("{} (check)", combination.name),
+                passed: false,
+                elapsed: Duration::ZERO,
+            });
+            continue;
+        }
+
+        // Only the library: dev-dependencies such as a multi-threaded tokio
+        // don't build for wasm. getrandom needs its wasm backend selected.
+        let rustflags = format!(
+            "{} --cfg getrandom_backend=\"wasm_js\"",
+            env::var("RUSTFLAGS").unwrap_or_default()
+        );
+        outcomes.push(cargo(
+            combination.name,
+            "check",
+            &[&args[..], &["--lib", "--target", target]].concat(),
+            &[("RUSTFLAGS", rustflags.trim_start())],
+        ));
+    }
+
+    outcomes
+}
+
+fn vectors() -> Vec<Outcome> {
+    VECTOR_SUITES
+        .iter()
+        .map(|(package, tests)| {
+            let mut args = vec!["-p", package];
+            for test in *tests {
+                args.extend(["--test", test]);
+            }
+            cargo(&format!("vectors {package}"), "test", &args, &[])
+        })
+        .collect()
+}
+
+/// Run `cargo <subcommand> <args>` from the workspace root.
+fn cargo(name: &str, subcommand: &str, args: &[&str], envs: &[(&str, &str)]) -> Outcome {
+    println!("==> {name}: cargo {subcommand} {}", args.join(" "));
+
+    let started = Instant::now();
+    let passed = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+        .current_dir(workspace_root())
+        .arg(subcommand)
+        .args(args)
+        .envs(envs.iter().copied())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or_else(|e| {
+            eprintln!("==> {name}: couldn't run cargo: {e}");
+            false
+        });
+
+    Outcome {
+        name: format!("{name} ({subcommand})"),
+        passed,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// Targets installed with rustup, or `None` if rustup isn't available, in
+/// which case cargo reports a missing target itself.
+fn installed_targets() -> Option<Vec<String>> {
+    let output = Command::new("rustup")
+        .args(["target", "list", "--installed"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn workspace_root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace root")
+        .to_path_buf()
+}
+
+fn summarise(outcomes: &[Outcome]) -> ExitCode {
+    println!("\nSummary:");
+    for outcome in outcomes {
+        println!(
+            "  {:<6} {:<48} {:>6.1}s",
+            if outcome.passed { "ok" } else { "FAILED" },
+            outcome.name,
+            outcome.elapsed.as_secs_f64()
+        );
+    }
+
+    let failed = outcomes.iter().filter(|outcome| !outcome.passed).count();
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        println!("\n{failed} of {} steps failed", outcomes.len());
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,146 @@
+//! The feature combinations worth building, and the interop vector suite.
+//!
+//! Add a combination when a feature gates code that no other entry compiles:
+//! a `cfg(feature = ...)` path nobody builds is a path that breaks unnoticed.
+
+/// WebAssembly target the wasm combinations are checked against.
+pub const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+/// One crate built with one set of features.
+pub struct Combination {
+    /// Unique name, used to select the combination on the command line
+    pub name: &'static str,
+    pub package: &'static str,
+    /// Feature flags passed to cargo, e.g. `--no-default-features`
+    pub flags: &'static [&'static str],
+    /// Cross-compilation target. Such combinations are only checked: their
+    /// tests can't run on the host.
+    pub target: Option<&'static str>,
+}
+
+impl Combination {
+    const fn native(
+        name: &'static str,
+        package: &'static str,
+        flags: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            package,
+            flags,
+            target: None,
+        }
+    }
+
+    const fn wasm(
+        name: &'static str,
+        package: &'static str,
+        flags: &'static [&'static str],
+    ) -> Self {
+        Self {
+            name,
+            package,
+            flags,
+            target: Some(WASM_TARGET),
+        }
+    }
+}
+
+/// Every combination `cargo xtask features` builds, in order.
+pub const COMBINATIONS: &[Combination] = &[
+    // DID resolver SDK: local vs network resolution, optional methods
+    Combination::native(
+        "resolver-local",
+        "affinidi-did-resolver-cache-sdk",
+        &["--no-default-features", "--features", "local"],
+    ),
+    Combination::native("resolver-default", "affinidi-did-resolver-cache-sdk", &[]),
+    Combination::native(
+        "resolver-network",
+        "affinidi-did-resolver-cache-sdk",
+        &["--features", "network"],
+    ),
+    Combination::native(
+        "resolver-did-example",
+        "affinidi-did-resolver-cache-sdk",
+        &["--features", "did_example"],
+    ),
+    Combination::native(
+        "resolver-cheqd",
+        "affinidi-did-resolver-cache-sdk",
+        &["--features", "did-cheqd"],
+    ),
+    // DID cache server: with and without the WebSocket endpoint
+    Combination::native(
+        "cache-server-network",
+        "affinidi-did-resolver-cache-server",
+        &[],
+    ),
+    Combination::native(
+        "cache-server-http-only",
+        "affinidi-did-resolver-cache-server",
+        &["--no-default-features"],
+    ),
+    // Messaging SDK: DIDComm only vs the optional protocols
+    Combination::native("messaging-sdk", "affinidi-messaging-sdk", &[]),
+    Combination::native(
+        "messaging-sdk-all",
+        "affinidi-messaging-sdk",
+        &["--all-features"],
+    ),
+    // TDK facade: messaging on and off
+    Combination::native("tdk-default", "affinidi-tdk", &[]),
+    Combination::native(
+        "tdk-no-messaging",
+        "affinidi-tdk",
+        &["--no-default-features"],
+    ),
+    Combination::native(
+        "tdk-messaging-only",
+        "affinidi-tdk",
+        &["--no-default-features", "--features", "messaging"],
+    ),
+    // WASM: the crates that support a browser build
+    Combination::wasm("wasm-crypto", "affinidi-crypto", &[]),
+    Combination::wasm("wasm-secrets-resolver", "affinidi-secrets-resolver", &[]),
+    Combination::wasm(
+        "wasm-resolver-local",
+        "affinidi-did-resolver-cache-sdk",
+        &["--no-default-features", "--features", "local"],
+    ),
+];
+
+/// Test targets checking the implementation against published vectors:
+/// specification examples and known-answer tests from other implementations.
+pub const VECTOR_SUITES: &[(&str, &[&str])] = &[
+    ("affinidi-bbs", &["interop_kat"]),
+    (
+        "affinidi-rdf-encoding",
+        &[
+            "rdfc10_w3c_suite",
+            "rdfc1_test_vectors",
+            "eddsa_rdfc_2022_b1",
+            "vc_di_bbs_canon",
+        ],
+    ),
+    (
+        "affinidi-data-integrity",
+        &["jcs_spec_test", "rdfc_spec_test"],
+    ),
+    ("affinidi-sd-jwt", &["rfc9901_vectors", "spec_vectors"]),
+    ("affinidi-tdk-test-support", &["golden"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn combination_names_are_unique() {
+        let mut names = HashSet::new();
+        for combination in COMBINATIONS {
+            assert!(names.insert(combination.name), "{}", combination.name);
+        }
+    }
+}